@@ -0,0 +1,29 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// Measures scanning throughput with and without view tags on 1 to 4 threads
+// Run with: cargo run --release --features bench --example scan_bench [blocks]
+
+use libmonero::keys::{run_scan_benchmark, BenchConfig};
+
+fn main() {
+    let blocks = std::env::args().nth(1).and_then(|blocks| blocks.parse().ok()).unwrap_or(200);
+    for view_tags in [true, false] {
+        for threads in [1, 2, 4] {
+            let config = BenchConfig {
+                blocks,
+                view_tags,
+                threads,
+                ..BenchConfig::default()
+            };
+            println!("{}", run_scan_benchmark(&config));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::nodes::RpcLogin;
+use md5::{Digest, Md5};
+use rand::RngCore;
+
+/// Digest challenge sent by a daemon started with `--rpc-login` in its `WWW-Authenticate` header
+pub(crate) struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    qop_auth: bool,
+    session: bool,
+}
+
+impl DigestChallenge {
+    /// Picks the challenge to answer among the `WWW-Authenticate` headers of a response, monerod sends one per
+    /// supported algorithm and plain MD5 is preferred
+    pub(crate) fn from_headers(headers: &[&str]) -> Result<DigestChallenge, String> {
+        let challenges: Vec<DigestChallenge> = headers.iter().filter_map(|header| DigestChallenge::parse(header)).collect();
+        let index = challenges.iter().position(|challenge| !challenge.session).unwrap_or(0);
+        challenges.into_iter().nth(index).ok_or("Daemon requires an unsupported authentication scheme".to_string())
+    }
+
+    // Parses a single `Digest ...` header, returns None for other schemes or unsupported algorithms
+    fn parse(header: &str) -> Option<DigestChallenge> {
+        let (scheme, params) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("Digest") {
+            return None;
+        }
+        let params = split_params(params);
+        let param = |name: &str| params.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.clone());
+        let session = match param("algorithm").as_deref() {
+            None => false,
+            Some(algorithm) if algorithm.eq_ignore_ascii_case("MD5") => false,
+            Some(algorithm) if algorithm.eq_ignore_ascii_case("MD5-sess") => true,
+            Some(_) => return None,
+        };
+        Some(DigestChallenge {
+            realm: param("realm").unwrap_or_default(),
+            nonce: param("nonce")?,
+            opaque: param("opaque"),
+            qop_auth: param("qop").is_some_and(|qop| qop.split(',').any(|value| value.trim() == "auth")),
+            session,
+        })
+    }
+
+    /// Returns the `Authorization` header value answering the challenge for a request with given method and URI
+    pub(crate) fn authorization(&self, login: &RpcLogin, method: &str, uri: &str) -> String {
+        let mut cnonce = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut cnonce);
+        self.authorization_with_cnonce(login, method, uri, &hex::encode(cnonce))
+    }
+
+    // RFC 2617 response computation with a fixed client nonce, sent once so the nonce count is always 1
+    fn authorization_with_cnonce(&self, login: &RpcLogin, method: &str, uri: &str, cnonce: &str) -> String {
+        let nc = "00000001";
+        let mut ha1 = md5_hex(&format!("{}:{}:{}", login.username, self.realm, login.password.as_str()));
+        if self.session {
+            ha1 = md5_hex(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}",
+            login.username,
+            self.realm,
+            self.nonce,
+            uri,
+            if self.session { "MD5-sess" } else { "MD5" }
+        );
+        let response = match self.qop_auth {
+            true => {
+                header.push_str(&format!(", qop=auth, nc={}, cnonce=\"{}\"", nc, cnonce));
+                md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, self.nonce, nc, cnonce, ha2))
+            }
+            false => md5_hex(&format!("{}:{}:{}", ha1, self.nonce, ha2)),
+        };
+        header.push_str(&format!(", response=\"{}\"", response));
+        if let Some(opaque) = &self.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+        header
+    }
+}
+
+fn md5_hex(data: &str) -> String {
+    hex::encode(Md5::digest(data.as_bytes()))
+}
+
+// Splits `key=value, key="quoted, value"` pairs, commas inside quotes are kept
+fn split_params(params: &str) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+    let mut rest = params.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let after = after.trim_start();
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (quoted[..end].to_string(), &quoted[end + 1..]),
+                None => (quoted.to_string(), ""),
+            },
+            None => match after.find(',') {
+                Some(end) => (after[..end].trim().to_string(), &after[end..]),
+                None => (after.trim().to_string(), ""),
+            },
+        };
+        result.push((key, value));
+        rest = remaining.trim_start().trim_start_matches(',');
+    }
+    result
+}
@@ -0,0 +1,207 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::{Block, BlockHash, TxHash};
+use super::transactions::{read_miner_transaction, Transaction};
+use crate::utils::varint::{read_varint, write_varint};
+use sha3::{Digest, Keccak256};
+
+// Mainnet block 202612 was hashed differently by the daemon because of a Merkle root bug, consensus keeps the old hash
+const BLOCK_202612_COMPUTED_HASH: &str = "3a8a2b3a29b50fc86ff73dd087ea43c6f0d6b8f936c849194d5c84c737903966";
+const BLOCK_202612_HASH: &str = "bbd604d2ba11ba27935e006ed39c9bfdd99b76bf4a50654bc1e1e61217962698";
+
+/// ParsedBlock is a block parsed from its consensus binary format with [`from_blob`](#method.from_blob)
+///
+/// Only the hashes of the non-miner transactions are part of a block, the transactions themselves are sent apart.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::ParsedBlock;
+///
+/// // Mainnet genesis block
+/// let blob = hex::decode(format!(
+///     "010000{}10270000013c01ff0001ffffffffffff03029b2e4c0281c0b02e7c53291a94d1d0cbff8883f8024f5142ee494ffbbd08807121017767aafcde9be00dcfd098715ebcf7f410daebc582fda69d24a28e9d0bc890d100",
+///     "00".repeat(32)
+/// )).unwrap();
+/// let block = ParsedBlock::from_blob(&blob).unwrap();
+/// assert_eq!(block.nonce, 10000);
+/// assert!(block.miner_tx.is_coinbase());
+/// assert!(block.tx_hashes.is_empty());
+/// assert_eq!(block.hash().to_string(), "418015bb9ae982a1975da7d79277c2705727a56894ba0fb246adaabb1f4632e3");
+/// assert_eq!(block.to_blob(), blob);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedBlock {
+    pub major_version: u64,
+    pub minor_version: u64,
+    pub timestamp: u64,
+    pub prev_id: BlockHash,
+    pub nonce: u32,
+    pub miner_tx: Transaction,
+    pub tx_hashes: Vec<TxHash>,
+}
+
+impl ParsedBlock {
+    /// Parses a block from its consensus binary format, as found in the `blob` field of
+    /// [`get_block_from_height`](fn.get_block_from_height.html) answers or in [`get_blocks_bin`](fn.get_blocks_bin.html) entries
+    ///
+    /// Only canonical encodings are accepted, as for [`Transaction::from_blob`](struct.Transaction.html#method.from_blob)
+    ///
+    /// Returns the block as a ParsedBlock struct if succesfull
+    /// Returns an error message if the blob is invalid or not canonically encoded
+    pub fn from_blob(blob: &[u8]) -> Result<ParsedBlock, String> {
+        let mut pos = 0;
+        let mut read = || -> Result<ParsedBlock, String> {
+            let major_version = read_varint(blob, &mut pos)?;
+            let minor_version = read_varint(blob, &mut pos)?;
+            let timestamp = read_varint(blob, &mut pos)?;
+            let prev_id = BlockHash::from_bytes(read_array(blob, &mut pos)?);
+            let nonce = u32::from_le_bytes(read_array(blob, &mut pos)?);
+            let miner_tx = read_miner_transaction(blob, &mut pos).map_err(|e| format!("invalid miner transaction: {}", e))?;
+            let tx_count = read_varint(blob, &mut pos)?;
+            if tx_count > ((blob.len() - pos) / 32) as u64 {
+                return Err(format!("transaction count {} exceeds the remaining data", tx_count));
+            }
+            let tx_hashes = (0..tx_count)
+                .map(|_| Ok(TxHash::from_bytes(read_array(blob, &mut pos)?)))
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(ParsedBlock {
+                major_version,
+                minor_version,
+                timestamp,
+                prev_id,
+                nonce,
+                miner_tx,
+                tx_hashes,
+            })
+        };
+        let block = read().map_err(|e| format!("Error while parsing the block: {}", e))?;
+        if pos != blob.len() {
+            return Err("Error while parsing the block: trailing data after the transaction hashes".to_string());
+        }
+        if block.to_blob() != blob {
+            return Err("Error while parsing the block: blob is not canonically encoded".to_string());
+        }
+        Ok(block)
+    }
+
+    /// Serializes the block to its consensus binary format, the reverse of [`from_blob`](#method.from_blob)
+    ///
+    /// Blocks parsed from a blob serialize back to the exact same bytes, so the hash of a parsed block is the hash of
+    /// its blob
+    pub fn to_blob(&self) -> Vec<u8> {
+        let mut blob = self.header_blob();
+        blob.extend_from_slice(&self.miner_tx.to_blob());
+        write_varint(self.tx_hashes.len() as u64, &mut blob);
+        for hash in self.tx_hashes.iter() {
+            blob.extend_from_slice(hash.as_bytes());
+        }
+        blob
+    }
+
+    /// Returns the hashing blob of the block: its header, the Merkle root of its transactions and their count,
+    /// as hashed for the block ID and the proof of work
+    pub fn hashing_blob(&self) -> Vec<u8> {
+        // Miner transactions have a null RingCT type, whose prunable hash is zero
+        let mut hashes = vec![*self.miner_tx.hash_with_prunable_hash(&[0; 32]).as_bytes()];
+        hashes.extend(self.tx_hashes.iter().map(|hash| *hash.as_bytes()));
+        let mut blob = self.header_blob();
+        blob.extend_from_slice(&tree_hash(&hashes));
+        write_varint(hashes.len() as u64, &mut blob);
+        blob
+    }
+
+    /// Returns the hash identifying the block
+    pub fn hash(&self) -> BlockHash {
+        let hashing_blob = self.hashing_blob();
+        let mut data = Vec::with_capacity(hashing_blob.len() + 2);
+        write_varint(hashing_blob.len() as u64, &mut data);
+        data.extend_from_slice(&hashing_blob);
+        let hash = hex::encode(Keccak256::digest(&data));
+        match hash.as_str() {
+            BLOCK_202612_COMPUTED_HASH => BlockHash::from_hex(BLOCK_202612_HASH).unwrap(),
+            _ => BlockHash::from_hex(&hash).unwrap(),
+        }
+    }
+
+    // Major version, minor version, timestamp, previous block hash and nonce
+    fn header_blob(&self) -> Vec<u8> {
+        let mut blob = Vec::with_capacity(80);
+        write_varint(self.major_version, &mut blob);
+        write_varint(self.minor_version, &mut blob);
+        write_varint(self.timestamp, &mut blob);
+        blob.extend_from_slice(self.prev_id.as_bytes());
+        blob.extend_from_slice(&self.nonce.to_le_bytes());
+        blob
+    }
+}
+
+impl Block {
+    /// Parses the `blob` of the block returned by the daemon, so its content can be checked against the returned hash
+    /// Returns the block as a ParsedBlock struct if succesfull
+    /// Returns an error message if not succesfull
+    pub fn parse_blob(&self) -> Result<ParsedBlock, String> {
+        let blob = hex::decode(&self.blob).map_err(|e| format!("Error while decoding the block blob: {}", e))?;
+        ParsedBlock::from_blob(&blob)
+    }
+}
+
+/// Computes the hash of a block from its consensus binary format
+/// Returns the block hash if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::get_block_hash;
+///
+/// let blob = hex::decode(format!(
+///     "010000{}10270000013c01ff0001ffffffffffff03029b2e4c0281c0b02e7c53291a94d1d0cbff8883f8024f5142ee494ffbbd08807121017767aafcde9be00dcfd098715ebcf7f410daebc582fda69d24a28e9d0bc890d100",
+///     "00".repeat(32)
+/// )).unwrap();
+/// assert_eq!(get_block_hash(&blob).unwrap().to_string(), "418015bb9ae982a1975da7d79277c2705727a56894ba0fb246adaabb1f4632e3");
+/// ```
+pub fn get_block_hash(blob: &[u8]) -> Result<BlockHash, String> {
+    Ok(ParsedBlock::from_blob(blob)?.hash())
+}
+
+// Merkle root of the transaction hashes, Monero's tree hash: the leaves beyond the largest power of two below the
+// count are paired first, then the tree is a perfect binary one
+pub(crate) fn tree_hash(hashes: &[[u8; 32]]) -> [u8; 32] {
+    let hash_pair = |left: &[u8; 32], right: &[u8; 32]| -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    };
+    match hashes {
+        [] => [0; 32],
+        [hash] => *hash,
+        [left, right] => hash_pair(left, right),
+        _ => {
+            let mut count = 1;
+            while count * 2 < hashes.len() {
+                count *= 2;
+            }
+            let unpaired = 2 * count - hashes.len();
+            let mut level: Vec<[u8; 32]> = hashes[..unpaired].to_vec();
+            level.extend(hashes[unpaired..].chunks_exact(2).map(|pair| hash_pair(&pair[0], &pair[1])));
+            while level.len() > 2 {
+                level = level.chunks_exact(2).map(|pair| hash_pair(&pair[0], &pair[1])).collect();
+            }
+            hash_pair(&level[0], &level[1])
+        }
+    }
+}
+
+fn read_array<const N: usize>(blob: &[u8], pos: &mut usize) -> Result<[u8; N], String> {
+    let bytes = blob.get(*pos..*pos + N).ok_or("unexpected end of data")?;
+    *pos += N;
+    Ok(bytes.try_into().unwrap())
+}
@@ -10,6 +10,32 @@
 
 #![allow(non_snake_case)]
 
+use crate::keys::types::bytes32_newtype;
+use crate::keys::{Commitment, KeyImage, OneTimeKey};
+
+bytes32_newtype!(
+    /// TxHash is the hash identifying a transaction
+    ///
+    /// Hashes, keys, key images and commitments all have their own type, so passing one where another is expected does not compile
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::TxHash;
+    ///
+    /// let hash = TxHash::from_hex("e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11").unwrap();
+    /// assert_eq!(hash.to_string(), "e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11");
+    /// assert!(TxHash::from_hex("e451").is_err());
+    /// ```
+    TxHash,
+    "transaction hash"
+);
+
+bytes32_newtype!(
+    /// BlockHash is the hash identifying a block
+    BlockHash,
+    "block hash"
+);
+
 // Block structs
 
 pub struct BlockHeader {
@@ -20,17 +46,17 @@ pub struct BlockHeader {
     pub depth: u64,
     pub difficulty: u64,
     pub difficulty_top64: u64,
-    pub hash: String,
+    pub hash: BlockHash,
     pub height: u64,
     pub long_term_weight: u64,
     pub major_version: u64,
-    pub miner_tx_hash: String,
+    pub miner_tx_hash: TxHash,
     pub minor_version: u64,
     pub nonce: u64,
     pub num_txes: u64,
     pub orphan_status: bool,
     pub pow_hash: String,
-    pub prev_hash: String,
+    pub prev_hash: BlockHash,
     pub reward: u64,
     pub timestamp: u64,
     pub wide_cumulative_difficulty: String,
@@ -46,7 +72,7 @@ pub struct Vin {
 }
 
 pub struct TaggedKey {
-    pub key: String,
+    pub key: OneTimeKey,
     pub view_tag: String,
 }
 
@@ -59,15 +85,18 @@ pub struct Vout {
     pub target: Target, 
 }
 
+/// Encrypted amount of an output, `trunc_amount` is 8 bytes for RingCT types 4 and above and 32 bytes before,
+/// `mask` is only used by RingCT types 1 to 3
 pub struct EcdhInfo {
     pub trunc_amount: String,
+    pub mask: String,
 }
 
 pub struct RctSignatures {
     pub type_int: u64,
     pub txn_fee: u64,
     pub ecdh_info: Vec<EcdhInfo>,
-    pub out_pk: Vec<String>,
+    pub out_pk: Vec<Commitment>,
 }
 
 pub struct MinerTxInfo {
@@ -83,10 +112,10 @@ pub struct BlockDetailsJSON {
     pub major_version: u64,
     pub minor_version: u64,
     pub timestamp: u64,
-    pub prev_id: String,
+    pub prev_id: BlockHash,
     pub nonce: u64,
     pub miner_tx: MinerTxInfo,
-    pub tx_hashes: Vec<String>,
+    pub tx_hashes: Vec<TxHash>,
 }
 
 pub struct Block {
@@ -94,18 +123,301 @@ pub struct Block {
     pub block_header: BlockHeader,
     pub credits: u64,
     pub json: BlockDetailsJSON,
-    pub miner_tx_hash: String,
+    pub miner_tx_hash: TxHash,
     pub status: String,
     pub top_hash: String,
     pub untrusted: bool
 }
 
+/// Chain of blocks the daemon received that lost against the main chain, as returned by `get_alternate_chains`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AltChain {
+    /// Hash of the top block of the chain
+    pub block_hash: BlockHash,
+    /// Hashes of the blocks of the chain, from the top block down to the first block after the fork
+    pub block_hashes: Vec<BlockHash>,
+    /// Height of the top block of the chain
+    pub height: u64,
+    /// Number of blocks in the chain
+    pub length: u64,
+    /// Cumulative difficulty of the chain, low 64 bits
+    pub difficulty: u64,
+    pub wide_difficulty: String,
+    /// Last block the chain shares with the main chain
+    pub main_chain_parent_block: BlockHash,
+}
+
+impl AltChain {
+    /// Returns the height of the first block of the chain, right after its main chain parent block
+    pub fn fork_height(&self) -> u64 {
+        self.height + 1 - self.length
+    }
+}
+
+/// Both sides of a fork, as returned by [`get_fork`](fn.get_fork.html)
+pub struct Fork {
+    /// Height of the first block after the parent block
+    pub fork_height: u64,
+    /// Last block both sides share
+    pub parent: BlockHash,
+    /// Headers of the alternative chain, ordered by height from `fork_height`
+    pub alternative: Vec<BlockHeader>,
+    /// Headers of the main chain over the same heights, ordered by height from `fork_height`
+    pub main: Vec<BlockHeader>,
+}
+
+/// Current height and top block hash of a daemon, as returned by `/get_height`
+pub struct HeightInfo {
+    pub hash: BlockHash,
+    pub height: u64,
+    pub untrusted: bool,
+}
+
+/// Status of a transaction as seen by a daemon, as returned by `get_transactions`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct TxStatus {
+    /// Whether the daemon knows the transaction at all
+    pub found: bool,
+    pub in_pool: bool,
+    /// Height of the block containing the transaction, 0 while in pool
+    pub block_height: u64,
+    pub confirmations: u64,
+    /// Whether the daemon saw another transaction spending the same key images
+    pub double_spend_seen: bool,
+    pub untrusted: bool,
+}
+
+/// Spent status of a key image, as returned by `is_key_image_spent`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyImageStatus {
+    Unspent,
+    /// Spent by a transaction of the main chain
+    SpentInChain,
+    /// Spent by a transaction waiting in the pool
+    SpentInPool,
+}
+
+/// Transaction waiting in the daemon's pool, as returned by `get_txpool_backlog`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxBacklogEntry {
+    pub weight: u64,
+    pub fee: u64,
+    pub time_in_pool: u64,
+}
+
+/// General information about a daemon and the chain it follows, as returned by `get_info`
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct DaemonInfo {
+    pub adjusted_time: u64,
+    pub alt_blocks_count: u64,
+    pub block_size_limit: u64,
+    pub block_size_median: u64,
+    pub block_weight_limit: u64,
+    pub block_weight_median: u64,
+    pub busy_syncing: bool,
+    pub cumulative_difficulty: u64,
+    pub database_size: u64,
+    pub difficulty: u64,
+    pub free_space: u64,
+    pub grey_peerlist_size: u64,
+    pub height: u64,
+    pub incoming_connections_count: u64,
+    /// Network of the daemon: `mainnet`, `testnet`, `stagenet` or `fakechain`
+    pub nettype: String,
+    pub offline: bool,
+    pub outgoing_connections_count: u64,
+    pub restricted: bool,
+    pub start_time: u64,
+    pub synchronized: bool,
+    /// Height the daemon is syncing to, 0 when synchronized
+    pub target_height: u64,
+    pub top_block_hash: BlockHash,
+    pub tx_count: u64,
+    pub tx_pool_size: u64,
+    pub untrusted: bool,
+    pub update_available: bool,
+    pub version: String,
+    pub white_peerlist_size: u64,
+}
+
+/// Result of a transaction broadcast, as returned by `send_raw_transaction`
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct SendTxResult {
+    /// Whether the daemon accepted the transaction (status `OK`)
+    pub accepted: bool,
+    /// Status string of the daemon, `OK` or `Failed` and sometimes a more detailed message
+    pub status: String,
+    /// Reason given by the daemon for a rejection, may be empty
+    pub reason: String,
+    pub double_spend: bool,
+    pub fee_too_low: bool,
+    pub invalid_input: bool,
+    pub invalid_output: bool,
+    pub low_mixin: bool,
+    pub nonzero_unlock_time: bool,
+    /// Whether the transaction was accepted but not relayed to other nodes
+    pub not_relayed: bool,
+    pub overspend: bool,
+    pub sanity_check_failed: bool,
+    pub too_big: bool,
+    pub too_few_outputs: bool,
+    pub tx_extra_too_big: bool,
+    pub untrusted: bool,
+}
+
+impl SendTxResult {
+    /// Returns the names of the rejection flags set by the daemon, followed by its reason if any
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::SendTxResult;
+    ///
+    /// let result = SendTxResult { status: "Failed".to_string(), double_spend: true, fee_too_low: true, ..SendTxResult::default() };
+    /// assert_eq!(result.rejection_reasons(), vec!["double_spend", "fee_too_low"]);
+    /// ```
+    pub fn rejection_reasons(&self) -> Vec<&str> {
+        let flags = [
+            (self.double_spend, "double_spend"),
+            (self.fee_too_low, "fee_too_low"),
+            (self.invalid_input, "invalid_input"),
+            (self.invalid_output, "invalid_output"),
+            (self.low_mixin, "low_mixin"),
+            (self.nonzero_unlock_time, "nonzero_unlock_time"),
+            (self.overspend, "overspend"),
+            (self.sanity_check_failed, "sanity_check_failed"),
+            (self.too_big, "too_big"),
+            (self.too_few_outputs, "too_few_outputs"),
+            (self.tx_extra_too_big, "tx_extra_too_big"),
+        ];
+        let mut reasons: Vec<&str> = flags.iter().filter(|(set, _)| *set).map(|(_, name)| *name).collect();
+        if !self.reason.is_empty() {
+            reasons.push(&self.reason);
+        }
+        reasons
+    }
+}
+
+/// Fee estimate of a daemon, as returned by `get_fee_estimate`
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct FeeEstimate {
+    /// Base fee per byte of weight
+    pub fee: u64,
+    /// Fee per byte of weight for each priority, from low to priority, empty on old daemons
+    pub fees: Vec<u64>,
+    /// Fees should be rounded up to a multiple of this value
+    pub quantization_mask: u64,
+    pub untrusted: bool,
+}
+
+/// Transaction in the daemon's pool, as returned by `get_transaction_pool`
+pub struct PoolTx {
+    /// Hash of the transaction
+    pub id_hash: TxHash,
+    pub tx: RawTx,
+    pub blob_size: u64,
+    pub weight: u64,
+    pub fee: u64,
+    /// Time the daemon received the transaction, as a Unix timestamp
+    pub receive_time: u64,
+    pub last_relayed_time: u64,
+    pub relayed: bool,
+    pub double_spend_seen: bool,
+    /// Whether the transaction comes from a popped block
+    pub kept_by_block: bool,
+}
+
+/// Block with its transactions in binary form, as returned by `get_blocks.bin`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockCompleteEntry {
+    /// Serialized block, miner transaction included
+    pub block: Vec<u8>,
+    /// Serialized transactions of the block, miner transaction excluded, in block order
+    pub txs: Vec<Vec<u8>>,
+    /// Global output indices of the miner transaction followed by those of every transaction of `txs`
+    pub output_indices: Vec<Vec<u64>>,
+}
+
+/// Blocks returned by `get_blocks.bin`, the batch a wallet syncs at once
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlocksBinResult {
+    /// Height of the first block of the batch
+    pub start_height: u64,
+    /// Height of the daemon's chain
+    pub current_height: u64,
+    pub blocks: Vec<BlockCompleteEntry>,
+    /// Whether the answer comes from the daemon's bootstrap daemon
+    pub untrusted: bool,
+}
+
+/// Output of the chain, as returned by `get_outs`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputEntry {
+    /// Height of the block containing the output
+    pub height: u64,
+    pub key: OneTimeKey,
+    /// Amount commitment of the output, for pre-RingCT outputs the daemon returns the commitment to the clear amount
+    pub mask: Commitment,
+    pub txid: TxHash,
+    /// Whether the output is unlocked at the current height
+    pub unlocked: bool,
+}
+
+/// Distribution of outputs of an amount over the blocks, as returned by `get_output_distribution`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputDistribution {
+    /// Amount of the outputs, 0 for RingCT outputs
+    pub amount: u64,
+    /// Height of the first block in the distribution
+    pub start_height: u64,
+    /// Number of outputs created before `start_height`
+    pub base: u64,
+    /// Cumulative number of outputs at each height from `start_height`, base included
+    pub distribution: Vec<u64>,
+}
+
+/// Number of outputs of an amount, as returned by `get_output_histogram`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputHistogramEntry {
+    pub amount: u64,
+    pub total_instances: u64,
+    pub unlocked_instances: u64,
+    pub recent_instances: u64,
+}
+
+/// Block template to mine on, as returned by `get_block_template`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockTemplate {
+    /// Serialized block with a zero nonce, ready to be submitted once a valid nonce is set
+    pub blocktemplate_blob: Vec<u8>,
+    /// Serialized block header, Merkle root and transaction count, the input of the proof of work hash
+    pub blockhashing_blob: Vec<u8>,
+    /// Difficulty of the next block, 0 if it does not fit in 64 bits, see `wide_difficulty`
+    pub difficulty: u64,
+    pub difficulty_top64: u64,
+    /// Difficulty of the next block as a hex string
+    pub wide_difficulty: String,
+    /// Coinbase reward of the block, fees included
+    pub expected_reward: u64,
+    /// Height of the block being mined
+    pub height: u64,
+    pub prev_hash: BlockHash,
+    /// Offset of the reserved bytes in `blocktemplate_blob`, inside the miner transaction's extra
+    pub reserved_offset: usize,
+    /// Size of the reserved bytes, as requested
+    pub reserve_size: usize,
+    /// RandomX seed hash of the block
+    pub seed_hash: String,
+    /// RandomX seed hash of the next epoch, empty if not known yet
+    pub next_seed_hash: String,
+    pub untrusted: bool,
+}
+
 // Tx structs
 
 pub struct KeyRawTx {
     pub amount: u64,
     pub key_offsets: Vec<u64>,
-    pub k_image: String
+    pub k_image: KeyImage
 }
 
 pub struct VinRawTx {
@@ -144,4 +456,5 @@ pub struct RawTx {
     pub extra: Vec<u8>,
     pub rct_signatures: RctSignatures,
     pub rctsig_prunable: RctsigPrunable,
+    pub untrusted: bool,
 }
\ No newline at end of file
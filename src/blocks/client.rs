@@ -0,0 +1,484 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::{
+    auth::DigestChallenge,
+    block::{AltChain, Block, BlockHash, BlockHeader, BlocksBinResult, BlockTemplate, DaemonInfo, FeeEstimate, Fork, HeightInfo, KeyImageStatus, OutputDistribution, OutputEntry, OutputHistogramEntry, PoolTx, RawTx, SendTxResult, TxBacklogEntry, TxHash, TxStatus},
+    height::{HeightSearch, HeightStep},
+    nodes::{CancelToken, DaemonNode, RpcConfig},
+    proxy::ProxyConfig,
+    rpcs,
+    tip::{ChainTip, TipWatcher},
+    transactions::Transaction,
+    transport::RpcTransport,
+};
+use crate::keys::KeyImage;
+use crate::utils::Network;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Error the blocking RPC functions get for requests whose response has not been fetched yet
+const PENDING: &str = "Response not fetched yet";
+
+/// Future of a request sent with an [`AsyncRpcTransport`](trait.AsyncRpcTransport.html), resolving to the response body
+pub type RpcFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, String>> + Send + 'a>>;
+
+/// AsyncRpcTransport is the non-blocking counterpart of [`RpcTransport`](trait.RpcTransport.html), used by [`DaemonClient`](struct.DaemonClient.html)
+///
+/// Implementations must not block the thread polling the future, [`AsyncHttpTransport`](struct.AsyncHttpTransport.html)
+/// is used unless another one is set with [`DaemonClient::with_transport`](struct.DaemonClient.html#method.with_transport)
+pub trait AsyncRpcTransport: Send + Sync {
+    /// Sends `body` to the given `path` of the node (e.g. `/json_rpc`, `/get_height`) and resolves to the response body
+    fn call<'a>(&'a self, node: &'a DaemonNode, path: &'a str, body: &'a [u8]) -> RpcFuture<'a>;
+}
+
+/// AsyncHttpTransport sends requests over HTTP(S) with a non-blocking client, it is the default transport of
+/// [`DaemonClient`](struct.DaemonClient.html)
+///
+/// Timeouts, SOCKS5 proxy and RPC login of the node are used as by [`HttpTransport`](struct.HttpTransport.html). The
+/// HTTP client is built at the first request and kept, so connections and TLS sessions are reused by later requests
+/// and by the clones of the transport. It is only rebuilt when called for a node with other timeouts or another proxy.
+#[derive(Clone, Debug, Default)]
+pub struct AsyncHttpTransport {
+    client: Arc<Mutex<Option<(ClientSettings, reqwest::Client)>>>,
+}
+
+// Node settings the HTTP client is built with
+type ClientSettings = (Duration, Duration, Option<ProxyConfig>);
+
+impl AsyncHttpTransport {
+    /// Creates a transport, its HTTP client is built at the first request
+    pub fn new() -> AsyncHttpTransport {
+        AsyncHttpTransport::default()
+    }
+
+    // Returns the HTTP client for the settings of the given node, building it if they changed
+    fn client(&self, node: &DaemonNode) -> Result<reqwest::Client, String> {
+        let settings = (node.config.connect_timeout, node.config.request_timeout, node.proxy.clone());
+        let mut cached = self.client.lock().map_err(|_| "HTTP client lock is poisoned".to_string())?;
+        if let Some((cached_settings, client)) = cached.as_ref() {
+            if *cached_settings == settings {
+                return Ok(client.clone());
+            }
+        }
+        let mut client = reqwest::Client::builder()
+            .http1_title_case_headers()
+            .connect_timeout(settings.0)
+            .timeout(settings.1);
+        if let Some(proxy) = &settings.2 {
+            client = client.proxy(proxy.to_reqwest()?);
+        }
+        let client = client.build().map_err(|e| e.to_string())?;
+        *cached = Some((settings, client.clone()));
+        Ok(client)
+    }
+}
+
+impl AsyncRpcTransport for AsyncHttpTransport {
+    fn call<'a>(&'a self, node: &'a DaemonNode, path: &'a str, body: &'a [u8]) -> RpcFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}{}", node.base_url(), path);
+            let client = self.client(node)?;
+            let request = || client.post(&url).header("Content-Type", "application/json").body(body.to_vec());
+            let response = request().send().await.map_err(|e| e.to_string())?;
+            let response = match (response.status().as_u16(), &node.login) {
+                // Daemons started with `--rpc-login` answer unauthenticated requests with a digest challenge
+                (401, Some(login)) => {
+                    let headers: Vec<&str> = response.headers().get_all("WWW-Authenticate").iter().filter_map(|header| header.to_str().ok()).collect();
+                    let challenge = DigestChallenge::from_headers(&headers)?;
+                    let response = request()
+                        .header("Authorization", challenge.authorization(login, "POST", path))
+                        .send()
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    if response.status().as_u16() == 401 {
+                        return Err("Daemon rejected the RPC login".to_string());
+                    }
+                    response
+                }
+                (401, None) => return Err("Daemon requires an RPC login, see DaemonNode::with_login".to_string()),
+                _ => response,
+            };
+            let response = response.error_for_status().map_err(|e| e.to_string())?;
+            Ok(response.bytes().await.map_err(|e| e.to_string())?.to_vec())
+        })
+    }
+}
+
+// Path and body of a request with the response fetched for it
+type Exchange = (String, Vec<u8>, Result<Vec<u8>, String>);
+
+// Requests made by a blocking RPC function and the responses fetched for them so far
+#[derive(Default)]
+struct Exchanges {
+    fetched: Vec<Exchange>,
+    served: usize,
+    pending: Option<(String, Vec<u8>)>,
+}
+
+// Serves the fetched responses to a blocking RPC function in request order, the first request without a response is
+// kept as pending and answered with an error, as are all requests after it
+struct ExchangeTransport(Mutex<Exchanges>);
+
+impl RpcTransport for ExchangeTransport {
+    fn call(&self, _node: &DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let mut exchanges = self.0.lock().map_err(|_| "Exchange lock is poisoned".to_string())?;
+        if exchanges.pending.is_some() {
+            return Err(PENDING.to_string());
+        }
+        let served = exchanges.served;
+        match exchanges.fetched.get(served) {
+            Some((fetched_path, fetched_body, response)) if fetched_path == path && fetched_body == body => {
+                let response = response.clone();
+                exchanges.served += 1;
+                response
+            }
+            Some(_) => Err("RPC function made different requests for the same responses".to_string()),
+            None => {
+                exchanges.pending = Some((path.to_string(), body.to_vec()));
+                Err(PENDING.to_string())
+            }
+        }
+    }
+}
+
+/// DaemonClient exposes the daemon RPCs as `async fn`s, for use inside async services
+///
+/// Requests are sent with a non-blocking [`AsyncRpcTransport`](trait.AsyncRpcTransport.html), the responses are parsed
+/// by the same code as the blocking RPC functions, so both always return the same results. No call blocks the async
+/// executor or needs a blocking thread pool.
+///
+/// The parsing code is run once per request it needs a response for, as the responses come in, and must make the same
+/// requests when given the same responses, which all RPCs of the client do. Calls made of one or two requests cost
+/// about as much as their blocking versions, [`height_at`](#method.height_at), the only one searching through many
+/// blocks, is implemented on the async requests directly. The transport set on the node with
+/// [`DaemonNode::with_transport`](struct.DaemonNode.html#method.with_transport) is not used, set an async one with
+/// [`with_transport`](#method.with_transport). Needs the `async` feature and must be used inside a tokio runtime.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{DaemonClient, DaemonNode};
+///
+/// # async fn run() -> Result<(), String> {
+/// let client = DaemonClient::new(DaemonNode::cake_wallet_default());
+/// let height = client.get_height().await?;
+/// let block = client.get_block_from_height(height - 1).await?;
+/// println!("Top block hash: {}", block.block_header.hash);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DaemonClient {
+    node: DaemonNode,
+    transport: Arc<dyn AsyncRpcTransport>,
+}
+
+impl fmt::Debug for DaemonClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaemonClient").field("node", &self.node).finish()
+    }
+}
+
+impl DaemonClient {
+    /// Creates a client calling the given node
+    pub fn new(node: DaemonNode) -> DaemonClient {
+        DaemonClient { node, transport: Arc::new(AsyncHttpTransport::new()) }
+    }
+
+    /// Creates a client calling the given node with the given timeouts and retry policy, see [`RpcConfig`](struct.RpcConfig.html)
+    pub fn with_config(node: DaemonNode, config: RpcConfig) -> DaemonClient {
+        DaemonClient::new(node.with_config(config))
+    }
+
+    /// Sets the transport used to deliver requests to the node, e.g. a mock for tests
+    pub fn with_transport(mut self, transport: Arc<dyn AsyncRpcTransport>) -> DaemonClient {
+        self.transport = transport;
+        self
+    }
+
+    /// Returns the node this client calls
+    pub fn node(&self) -> &DaemonNode {
+        &self.node
+    }
+
+    // Sends a request through the transport, retrying as set in the RpcConfig of the node until its token is cancelled
+    async fn call(&self, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let cancelled = || self.node.cancel.as_ref().is_some_and(CancelToken::is_cancelled);
+        let mut retry = 0;
+        loop {
+            if cancelled() {
+                return Err(CancelToken::error());
+            }
+            match self.transport.call(&self.node, path, body).await {
+                Err(_) if retry < self.node.config.retries && !cancelled() => {
+                    tokio::time::sleep(self.node.config.retry_delay(retry)).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Runs a blocking RPC function against the responses fetched so far, each request it is missing a response for is
+    // sent asynchronously and the function is run again, until it needs no more responses. A function making n requests
+    // is run n + 1 times, it must make the same requests for the same responses or the call fails
+    async fn run<T, F>(&self, call: F) -> Result<T, String>
+    where
+        F: Fn(DaemonNode) -> Result<T, String>,
+    {
+        let exchanges = Arc::new(ExchangeTransport(Mutex::new(Exchanges::default())));
+        let mut node = self.node.clone().with_config(RpcConfig { retries: 0, ..self.node.config }).with_transport(exchanges.clone());
+        node.cancel = None;
+        loop {
+            let result = call(node.clone());
+            let pending = {
+                let mut state = exchanges.0.lock().map_err(|_| "Exchange lock is poisoned".to_string())?;
+                state.served = 0;
+                state.pending.take()
+            };
+            let Some((path, body)) = pending else {
+                return result;
+            };
+            let response = self.call(&path, &body).await;
+            exchanges.0.lock().map_err(|_| "Exchange lock is poisoned".to_string())?.fetched.push((path, body, response));
+        }
+    }
+
+    /// Async version of [`get_height`](fn.get_height.html)
+    pub async fn get_height(&self) -> Result<u64, String> {
+        self.run(rpcs::get_height).await
+    }
+
+    /// Async version of [`get_height_info`](fn.get_height_info.html)
+    pub async fn get_height_info(&self) -> Result<HeightInfo, String> {
+        self.run(rpcs::get_height_info).await
+    }
+
+    /// Async version of [`get_block_from_height`](fn.get_block_from_height.html)
+    pub async fn get_block_from_height(&self, block_height: u64) -> Result<Block, String> {
+        self.run(move |node| rpcs::get_block_from_height(block_height, node)).await
+    }
+
+    /// Async version of [`get_block_header_by_height`](fn.get_block_header_by_height.html)
+    pub async fn get_block_header_by_height(&self, block_height: u64) -> Result<BlockHeader, String> {
+        self.run(move |node| rpcs::get_block_header_by_height(block_height, node)).await
+    }
+
+    /// Async version of [`get_block_by_hash`](fn.get_block_by_hash.html)
+    pub async fn get_block_by_hash(&self, hash: BlockHash) -> Result<Block, String> {
+        self.run(move |node| rpcs::get_block_by_hash(hash, node)).await
+    }
+
+    /// Async version of [`get_block_header_by_hash`](fn.get_block_header_by_hash.html)
+    pub async fn get_block_header_by_hash(&self, hash: BlockHash) -> Result<BlockHeader, String> {
+        self.run(move |node| rpcs::get_block_header_by_hash(hash, node)).await
+    }
+
+    /// Async version of [`get_alternate_chains`](fn.get_alternate_chains.html)
+    pub async fn get_alternate_chains(&self) -> Result<Vec<AltChain>, String> {
+        self.run(rpcs::get_alternate_chains).await
+    }
+
+    /// Async version of [`get_fork`](fn.get_fork.html)
+    pub async fn get_fork(&self, chain: AltChain) -> Result<Fork, String> {
+        self.run(move |node| rpcs::get_fork(&chain, node)).await
+    }
+
+    /// Async version of [`get_transaction`](fn.get_transaction.html)
+    pub async fn get_transaction(&self, hash: TxHash) -> Result<Transaction, String> {
+        self.run(move |node| rpcs::get_transaction(hash, node)).await
+    }
+
+    /// Async version of [`get_transaction_from_hash`](fn.get_transaction_from_hash.html)
+    pub async fn get_transaction_from_hash(&self, hash: TxHash) -> Result<RawTx, String> {
+        self.run(move |node| rpcs::get_transaction_from_hash(hash, node)).await
+    }
+
+    /// Async version of [`get_transaction_status`](fn.get_transaction_status.html)
+    pub async fn get_transaction_status(&self, hash: TxHash) -> Result<TxStatus, String> {
+        self.run(move |node| rpcs::get_transaction_status(hash, node)).await
+    }
+
+    /// Async version of [`get_txpool_backlog`](fn.get_txpool_backlog.html)
+    pub async fn get_txpool_backlog(&self) -> Result<Vec<TxBacklogEntry>, String> {
+        self.run(rpcs::get_txpool_backlog).await
+    }
+
+    /// Async version of [`height_at_with_node`](fn.height_at_with_node.html)
+    pub async fn height_at(&self, timestamp: u64, network: Network) -> Result<u64, String> {
+        let mut search = HeightSearch::new(timestamp, network, self.get_height().await?);
+        let mut step = search.next(None);
+        loop {
+            match step {
+                HeightStep::Fetch(height) => step = search.next(Some(self.get_block_header_by_height(height).await?.timestamp)),
+                HeightStep::Done(height) => return Ok(height),
+            }
+        }
+    }
+
+    /// Async version of [`get_info`](fn.get_info.html)
+    pub async fn get_info(&self) -> Result<DaemonInfo, String> {
+        self.run(rpcs::get_info).await
+    }
+
+    /// Async version of [`get_last_block_header`](fn.get_last_block_header.html)
+    pub async fn get_last_block_header(&self) -> Result<BlockHeader, String> {
+        self.run(rpcs::get_last_block_header).await
+    }
+
+    /// Async version of [`get_block_headers_range`](fn.get_block_headers_range.html)
+    pub async fn get_block_headers_range(&self, start_height: u64, end_height: u64) -> Result<Vec<BlockHeader>, String> {
+        self.run(move |node| rpcs::get_block_headers_range(start_height, end_height, node)).await
+    }
+
+    /// Async version of [`get_block_count`](fn.get_block_count.html)
+    pub async fn get_block_count(&self) -> Result<u64, String> {
+        self.run(rpcs::get_block_count).await
+    }
+
+    /// Async version of [`get_fee_estimate`](fn.get_fee_estimate.html)
+    pub async fn get_fee_estimate(&self, grace_blocks: u64) -> Result<FeeEstimate, String> {
+        self.run(move |node| rpcs::get_fee_estimate(grace_blocks, node)).await
+    }
+
+    /// Async version of [`send_raw_transaction`](fn.send_raw_transaction.html)
+    pub async fn send_raw_transaction(&self, tx_as_hex: String, do_not_relay: bool) -> Result<SendTxResult, String> {
+        self.run(move |node| rpcs::send_raw_transaction(tx_as_hex.clone(), do_not_relay, node)).await
+    }
+
+    /// Async version of [`get_block_template`](fn.get_block_template.html)
+    pub async fn get_block_template(&self, wallet_address: String, reserve_size: u64) -> Result<BlockTemplate, String> {
+        self.run(move |node| rpcs::get_block_template(&wallet_address, reserve_size, node)).await
+    }
+
+    /// Async version of [`submit_block`](fn.submit_block.html)
+    pub async fn submit_block(&self, block_blob: Vec<u8>) -> Result<(), String> {
+        self.run(move |node| rpcs::submit_block(&block_blob, node)).await
+    }
+
+    /// Async version of [`get_transaction_pool`](fn.get_transaction_pool.html)
+    pub async fn get_transaction_pool(&self) -> Result<Vec<PoolTx>, String> {
+        self.run(rpcs::get_transaction_pool).await
+    }
+
+    /// Async version of [`get_transaction_pool_hashes`](fn.get_transaction_pool_hashes.html)
+    pub async fn get_transaction_pool_hashes(&self) -> Result<Vec<TxHash>, String> {
+        self.run(rpcs::get_transaction_pool_hashes).await
+    }
+
+    /// Async version of [`get_outs`](fn.get_outs.html)
+    pub async fn get_outs(&self, indices: Vec<u64>) -> Result<Vec<OutputEntry>, String> {
+        self.run(move |node| rpcs::get_outs(&indices, node)).await
+    }
+
+    /// Async version of [`get_outs_by_amount`](fn.get_outs_by_amount.html)
+    pub async fn get_outs_by_amount(&self, amount: u64, indices: Vec<u64>) -> Result<Vec<OutputEntry>, String> {
+        self.run(move |node| rpcs::get_outs_by_amount(amount, &indices, node)).await
+    }
+
+    /// Async version of [`get_outs_bin`](fn.get_outs_bin.html)
+    pub async fn get_outs_bin(&self, indices: Vec<u64>) -> Result<Vec<OutputEntry>, String> {
+        self.run(move |node| rpcs::get_outs_bin(&indices, node)).await
+    }
+
+    /// Async version of [`get_blocks_bin`](fn.get_blocks_bin.html)
+    pub async fn get_blocks_bin(&self, block_ids: Vec<BlockHash>, start_height: u64, prune: bool) -> Result<BlocksBinResult, String> {
+        self.run(move |node| rpcs::get_blocks_bin(&block_ids, start_height, prune, node)).await
+    }
+
+    /// Async version of [`get_output_distribution`](fn.get_output_distribution.html)
+    pub async fn get_output_distribution(&self, amounts: Vec<u64>, from_height: u64, to_height: u64) -> Result<Vec<OutputDistribution>, String> {
+        self.run(move |node| rpcs::get_output_distribution(&amounts, from_height, to_height, node)).await
+    }
+
+    /// Async version of [`get_output_histogram`](fn.get_output_histogram.html)
+    pub async fn get_output_histogram(&self, amounts: Vec<u64>, unlocked: bool) -> Result<Vec<OutputHistogramEntry>, String> {
+        self.run(move |node| rpcs::get_output_histogram(&amounts, unlocked, node)).await
+    }
+
+    /// Async version of [`get_global_output_indexes`](fn.get_global_output_indexes.html)
+    pub async fn get_global_output_indexes(&self, hash: TxHash) -> Result<Vec<u64>, String> {
+        self.run(move |node| rpcs::get_global_output_indexes(hash, node)).await
+    }
+
+    /// Async version of [`is_key_image_spent`](fn.is_key_image_spent.html)
+    pub async fn is_key_image_spent(&self, key_images: Vec<KeyImage>) -> Result<Vec<KeyImageStatus>, String> {
+        self.run(move |node| rpcs::is_key_image_spent(&key_images, node)).await
+    }
+
+    /// Async version of [`watch_height`](fn.watch_height.html), the changes of the main chain are awaited with
+    /// [`TipStream::next`](struct.TipStream.html#method.next)
+    pub fn watch_height(&self, poll_interval: Duration) -> TipStream {
+        TipStream {
+            client: self.clone(),
+            poll_interval,
+            watcher: TipWatcher::new(100),
+            pending: VecDeque::new(),
+            first: true,
+        }
+    }
+}
+
+/// TipStream follows the top of the main chain of a node from async code, see [`DaemonClient::watch_height`](struct.DaemonClient.html#method.watch_height)
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{ChainTip, DaemonClient, DaemonNode};
+/// use std::time::Duration;
+///
+/// # async fn run() {
+/// let mut tips = DaemonClient::new(DaemonNode::cake_wallet_default()).watch_height(Duration::from_secs(10));
+/// loop {
+///     if let ChainTip::Reorg { fork_height, .. } = tips.next().await {
+///         println!("Reorganization from height {}", fork_height);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TipStream {
+    client: DaemonClient,
+    poll_interval: Duration,
+    watcher: TipWatcher,
+    pending: VecDeque<ChainTip>,
+    first: bool,
+}
+
+impl TipStream {
+    /// Waits for the next change of the main chain, errors of the node are retried at the next poll
+    pub async fn next(&mut self) -> ChainTip {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return event;
+            }
+            if !self.first {
+                tokio::time::sleep(self.poll_interval).await;
+            }
+            self.first = false;
+            let watcher = &self.watcher;
+            let polled = self
+                .client
+                .run(|node| {
+                    let mut watcher = watcher.clone();
+                    let events = watcher.poll(&node)?;
+                    Ok((watcher, events))
+                })
+                .await;
+            if let Ok((watcher, events)) = polled {
+                self.watcher = watcher;
+                self.pending.extend(events);
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::{PoolTx, RawTx, TxHash};
+use super::monitor::MonitorHandle;
+use super::nodes::DaemonNode;
+use super::rpcs::get_transaction_pool;
+use crate::keys::KeyImage;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// DoubleSpendAlert describes a threat to a watched unconfirmed transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DoubleSpendAlert {
+    /// Another pool transaction spends some of the key images of the watched transaction, at most one of them can be mined
+    Conflict { watched: TxHash, conflicting: TxHash, key_images: Vec<KeyImage> },
+    /// Daemon flags the watched transaction as double spend seen, the conflicting transaction may not be in its pool
+    Flagged { watched: TxHash },
+    /// Pool could not be queried, watched transactions are not protected until it can be again
+    PoolUnreachable { error: String },
+}
+
+/// DoubleSpendWatcher watches the pool for transactions spending the same key images as the unconfirmed incoming
+/// transactions a merchant already credited
+///
+/// Each alert is raised once. Clones share the watched transactions, so transactions can be added while a clone runs
+/// in the background with [`run`](#method.run). Conflicting transactions mined without going through the watched
+/// daemon's pool are not seen, follow the watched transactions with a [`TxTracker`](struct.TxTracker.html) as well.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{DoubleSpendAlert, DoubleSpendWatcher, TxHash};
+/// use libmonero::keys::KeyImage;
+///
+/// let watcher = DoubleSpendWatcher::new();
+/// let paid = TxHash::from_bytes([0xaa; 32]);
+/// watcher.watch(paid, vec![KeyImage::from_bytes([1; 32]), KeyImage::from_bytes([2; 32])]);
+/// let attacker = TxHash::from_bytes([0xbb; 32]);
+/// let alerts = watcher.check_key_images(&[(attacker, vec![KeyImage::from_bytes([2; 32])], false)]);
+/// assert_eq!(alerts, vec![DoubleSpendAlert::Conflict { watched: paid, conflicting: attacker, key_images: vec![KeyImage::from_bytes([2; 32])] }]);
+/// assert!(watcher.check_key_images(&[(attacker, vec![KeyImage::from_bytes([2; 32])], false)]).is_empty());
+/// ```
+#[derive(Clone, Default)]
+pub struct DoubleSpendWatcher {
+    state: Arc<Mutex<WatcherState>>,
+}
+
+#[derive(Default)]
+struct WatcherState {
+    // Key images of every watched transaction, ordered so alerts come in a stable order
+    watched: BTreeMap<TxHash, Vec<KeyImage>>,
+    reported: HashSet<(TxHash, Option<TxHash>)>,
+}
+
+impl DoubleSpendWatcher {
+    /// Creates a watcher without watched transactions
+    pub fn new() -> DoubleSpendWatcher {
+        DoubleSpendWatcher::default()
+    }
+
+    /// Watches the transaction with given hash, spending the given key images
+    pub fn watch(&self, hash: TxHash, key_images: Vec<KeyImage>) {
+        self.state.lock().unwrap().watched.insert(hash, key_images);
+    }
+
+    /// Watches the given transaction, e.g. as returned by [`get_transaction_from_hash`](fn.get_transaction_from_hash.html)
+    pub fn watch_tx(&self, hash: TxHash, tx: &RawTx) {
+        let key_images = tx
+            .vin
+            .iter()
+            .map(|input| input.key.k_image)
+            .filter(|key_image| *key_image != KeyImage::default())
+            .collect();
+        self.watch(hash, key_images);
+    }
+
+    /// Stops watching the transaction with given hash, e.g. once it has enough confirmations
+    pub fn unwatch(&self, hash: &TxHash) {
+        let mut state = self.state.lock().unwrap();
+        state.watched.remove(hash);
+        state.reported.retain(|(watched, _)| watched != hash);
+    }
+
+    /// Returns the hashes of the watched transactions
+    pub fn watched(&self) -> Vec<TxHash> {
+        self.state.lock().unwrap().watched.keys().copied().collect()
+    }
+
+    /// Checks the given pool transactions, given as hash, key images and double spend seen flag, against the
+    /// watched transactions and returns the alerts not raised before
+    pub fn check_key_images(&self, pool: &[(TxHash, Vec<KeyImage>, bool)]) -> Vec<DoubleSpendAlert> {
+        let mut state = self.state.lock().unwrap();
+        let WatcherState { watched, reported } = &mut *state;
+        let mut alerts = Vec::new();
+        for (hash, watched_images) in watched.iter() {
+            for (pool_hash, pool_images, double_spend_seen) in pool {
+                if pool_hash == hash {
+                    if *double_spend_seen && reported.insert((*hash, None)) {
+                        alerts.push(DoubleSpendAlert::Flagged { watched: *hash });
+                    }
+                    continue;
+                }
+                let shared: Vec<KeyImage> = watched_images.iter().filter(|image| pool_images.contains(image)).copied().collect();
+                if !shared.is_empty() && reported.insert((*hash, Some(*pool_hash))) {
+                    alerts.push(DoubleSpendAlert::Conflict {
+                        watched: *hash,
+                        conflicting: *pool_hash,
+                        key_images: shared,
+                    });
+                }
+            }
+        }
+        alerts
+    }
+
+    /// Checks the given pool, as returned by [`get_transaction_pool`](fn.get_transaction_pool.html), against the
+    /// watched transactions and returns the alerts not raised before
+    pub fn check(&self, pool: &[PoolTx]) -> Vec<DoubleSpendAlert> {
+        let pool: Vec<(TxHash, Vec<KeyImage>, bool)> = pool
+            .iter()
+            .map(|tx| (tx.id_hash, tx.tx.vin.iter().map(|input| input.key.k_image).collect(), tx.double_spend_seen))
+            .collect();
+        self.check_key_images(&pool)
+    }
+
+    /// Gets the pool of the given node and checks it, see [`check`](#method.check)
+    pub fn poll(&self, node: &DaemonNode) -> Result<Vec<DoubleSpendAlert>, String> {
+        if self.state.lock().unwrap().watched.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self.check(&get_transaction_pool(node.clone())?))
+    }
+
+    /// Polls the pool of the given node every `interval` on a background thread and calls `on_alerts` whenever alerts are raised
+    ///
+    /// Polling goes on until [`MonitorHandle::stop`](struct.MonitorHandle.html#method.stop) is called, a failing poll
+    /// raises a `PoolUnreachable` alert
+    pub fn run<F>(&self, node: DaemonNode, interval: Duration, mut on_alerts: F) -> MonitorHandle
+    where
+        F: FnMut(&[DoubleSpendAlert]) + Send + 'static,
+    {
+        let watcher = self.clone();
+        MonitorHandle::spawn(interval, move || {
+            let alerts = match watcher.poll(&node) {
+                Ok(alerts) => alerts,
+                Err(error) => vec![DoubleSpendAlert::PoolUnreachable { error }],
+            };
+            if !alerts.is_empty() {
+                on_alerts(&alerts);
+            }
+        })
+    }
+}
@@ -0,0 +1,317 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// Epee portable storage, the binary format of the daemon's `.bin` endpoints
+
+// Storage header: two signatures and the format version
+const SIGNATURE: [u8; 9] = [0x01, 0x11, 0x01, 0x01, 0x01, 0x01, 0x02, 0x01, 0x01];
+const MAX_DEPTH: usize = 64;
+
+const TYPE_INT64: u8 = 1;
+const TYPE_INT32: u8 = 2;
+const TYPE_INT16: u8 = 3;
+const TYPE_INT8: u8 = 4;
+const TYPE_UINT64: u8 = 5;
+const TYPE_UINT32: u8 = 6;
+const TYPE_UINT16: u8 = 7;
+const TYPE_UINT8: u8 = 8;
+const TYPE_DOUBLE: u8 = 9;
+const TYPE_STRING: u8 = 10;
+const TYPE_BOOL: u8 = 11;
+const TYPE_OBJECT: u8 = 12;
+const TYPE_ARRAY: u8 = 13;
+const FLAG_ARRAY: u8 = 0x80;
+
+/// EpeeValue is a value of the epee portable storage, the binary format of the daemon's `.bin` endpoints
+///
+/// Strings are raw bytes since they usually hold binary blobs
+#[derive(Clone, Debug, PartialEq)]
+pub enum EpeeValue {
+    I64(i64),
+    I32(i32),
+    I16(i16),
+    I8(i8),
+    U64(u64),
+    U32(u32),
+    U16(u16),
+    U8(u8),
+    F64(f64),
+    Blob(Vec<u8>),
+    Bool(bool),
+    Object(EpeeObject),
+    /// Type code of the elements, see [`type_code`](#method.type_code), and the elements, which must all be of that type
+    Array(u8, Vec<EpeeValue>),
+}
+
+/// EpeeObject is a section of a portable storage, entries keep their order
+pub type EpeeObject = Vec<(String, EpeeValue)>;
+
+impl EpeeValue {
+    /// Returns the portable storage type code of the value, as used for the elements of an [`Array`](#variant.Array)
+    pub fn type_code(&self) -> u8 {
+        match self {
+            EpeeValue::I64(_) => TYPE_INT64,
+            EpeeValue::I32(_) => TYPE_INT32,
+            EpeeValue::I16(_) => TYPE_INT16,
+            EpeeValue::I8(_) => TYPE_INT8,
+            EpeeValue::U64(_) => TYPE_UINT64,
+            EpeeValue::U32(_) => TYPE_UINT32,
+            EpeeValue::U16(_) => TYPE_UINT16,
+            EpeeValue::U8(_) => TYPE_UINT8,
+            EpeeValue::F64(_) => TYPE_DOUBLE,
+            EpeeValue::Blob(_) => TYPE_STRING,
+            EpeeValue::Bool(_) => TYPE_BOOL,
+            EpeeValue::Object(_) => TYPE_OBJECT,
+            EpeeValue::Array(..) => TYPE_ARRAY,
+        }
+    }
+
+    /// Returns any unsigned or non-negative signed integer as u64
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            EpeeValue::U64(v) => Some(v),
+            EpeeValue::U32(v) => Some(v as u64),
+            EpeeValue::U16(v) => Some(v as u64),
+            EpeeValue::U8(v) => Some(v as u64),
+            EpeeValue::I64(v) => u64::try_from(v).ok(),
+            EpeeValue::I32(v) => u64::try_from(v).ok(),
+            EpeeValue::I16(v) => u64::try_from(v).ok(),
+            EpeeValue::I8(v) => u64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            EpeeValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes of a string
+    pub fn as_blob(&self) -> Option<&[u8]> {
+        match self {
+            EpeeValue::Blob(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of an array
+    pub fn as_array(&self) -> Option<&[EpeeValue]> {
+        match self {
+            EpeeValue::Array(_, v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of an object
+    pub fn as_object(&self) -> Option<&EpeeObject> {
+        match self {
+            EpeeValue::Object(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the value of the entry with given name in a section
+pub fn epee_get<'a>(object: &'a EpeeObject, name: &str) -> Option<&'a EpeeValue> {
+    object.iter().find(|(key, _)| key == name).map(|(_, value)| value)
+}
+
+/// Serializes the given root section, as sent to the `.bin` endpoints
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{epee_from_bytes, epee_get, epee_to_bytes, EpeeValue};
+///
+/// let request = vec![
+///     ("txid".to_string(), EpeeValue::Blob(vec![0xaa; 32])),
+///     ("indexes".to_string(), EpeeValue::Array(EpeeValue::U64(0).type_code(), vec![EpeeValue::U64(5), EpeeValue::U64(7)])),
+/// ];
+/// let bytes = epee_to_bytes(&request).unwrap();
+/// let parsed = epee_from_bytes(&bytes).unwrap();
+/// assert_eq!(parsed, request);
+/// assert_eq!(epee_get(&parsed, "indexes").unwrap().as_array().unwrap()[1].as_u64(), Some(7));
+/// ```
+pub fn epee_to_bytes(root: &EpeeObject) -> Result<Vec<u8>, String> {
+    let mut out = SIGNATURE.to_vec();
+    write_object(root, &mut out)?;
+    Ok(out)
+}
+
+/// Parses a serialized storage, as returned by the `.bin` endpoints, and returns its root section
+pub fn epee_from_bytes(data: &[u8]) -> Result<EpeeObject, String> {
+    if !data.starts_with(&SIGNATURE) {
+        return Err("Invalid portable storage signature".to_string());
+    }
+    let mut pos = SIGNATURE.len();
+    let root = read_object(data, &mut pos, 0)?;
+    if pos != data.len() {
+        return Err("Trailing data after portable storage".to_string());
+    }
+    Ok(root)
+}
+
+// Epee varints store their size in the 2 low bits of the first byte
+fn write_size(size: usize, out: &mut Vec<u8>) -> Result<(), String> {
+    let size = size as u64;
+    if size < 1 << 6 {
+        out.push((size << 2) as u8);
+    } else if size < 1 << 14 {
+        out.extend_from_slice(&((size << 2) as u16 | 1).to_le_bytes());
+    } else if size < 1 << 30 {
+        out.extend_from_slice(&((size << 2) as u32 | 2).to_le_bytes());
+    } else if size < 1 << 62 {
+        out.extend_from_slice(&((size << 2) | 3).to_le_bytes());
+    } else {
+        return Err("Size too big for portable storage".to_string());
+    }
+    Ok(())
+}
+
+fn read_size(data: &[u8], pos: &mut usize) -> Result<usize, String> {
+    let first = *data.get(*pos).ok_or("Unexpected end of portable storage")?;
+    let len = 1usize << (first & 3);
+    let bytes = take(data, pos, len)?;
+    let mut buf = [0u8; 8];
+    buf[..len].copy_from_slice(bytes);
+    usize::try_from(u64::from_le_bytes(buf) >> 2).map_err(|_| "Size too big for portable storage".to_string())
+}
+
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let end = pos.checked_add(len).filter(|end| *end <= data.len()).ok_or("Unexpected end of portable storage")?;
+    let bytes = &data[*pos..end];
+    *pos = end;
+    Ok(bytes)
+}
+
+fn write_object(object: &EpeeObject, out: &mut Vec<u8>) -> Result<(), String> {
+    write_size(object.len(), out)?;
+    for (name, value) in object {
+        if name.len() > 255 {
+            return Err(format!("Entry name too long: {}", name));
+        }
+        out.push(name.len() as u8);
+        out.extend_from_slice(name.as_bytes());
+        match value {
+            EpeeValue::Array(element_type, elements) => {
+                out.push(element_type | FLAG_ARRAY);
+                write_array(*element_type, elements, out)?;
+            }
+            _ => {
+                out.push(value.type_code());
+                write_value(value, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_array(element_type: u8, elements: &[EpeeValue], out: &mut Vec<u8>) -> Result<(), String> {
+    write_size(elements.len(), out)?;
+    for element in elements {
+        if element.type_code() != element_type {
+            return Err("Array elements must all have the array type".to_string());
+        }
+        match element {
+            // Nested arrays carry their own element type
+            EpeeValue::Array(nested_type, nested) => {
+                out.push(nested_type | FLAG_ARRAY);
+                write_array(*nested_type, nested, out)?;
+            }
+            _ => write_value(element, out)?,
+        }
+    }
+    Ok(())
+}
+
+fn write_value(value: &EpeeValue, out: &mut Vec<u8>) -> Result<(), String> {
+    match value {
+        EpeeValue::I64(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::I32(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::I16(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::I8(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::U64(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::U32(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::U16(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::U8(v) => out.push(*v),
+        EpeeValue::F64(v) => out.extend_from_slice(&v.to_le_bytes()),
+        EpeeValue::Blob(v) => {
+            write_size(v.len(), out)?;
+            out.extend_from_slice(v);
+        }
+        EpeeValue::Bool(v) => out.push(*v as u8),
+        EpeeValue::Object(v) => write_object(v, out)?,
+        EpeeValue::Array(..) => return Err("Arrays are written with their type".to_string()),
+    }
+    Ok(())
+}
+
+fn read_object(data: &[u8], pos: &mut usize, depth: usize) -> Result<EpeeObject, String> {
+    if depth > MAX_DEPTH {
+        return Err("Portable storage is nested too deeply".to_string());
+    }
+    let count = read_size(data, pos)?;
+    // Every entry takes at least 3 bytes, bound the allocation by the remaining data
+    let mut object = Vec::with_capacity(count.min((data.len() - *pos) / 3));
+    for _ in 0..count {
+        let name_len = take(data, pos, 1)?[0] as usize;
+        let name = String::from_utf8(take(data, pos, name_len)?.to_vec()).map_err(|_| "Invalid entry name in portable storage")?;
+        let type_code = take(data, pos, 1)?[0];
+        let value = read_typed(data, pos, type_code, depth)?;
+        object.push((name, value));
+    }
+    Ok(object)
+}
+
+fn read_typed(data: &[u8], pos: &mut usize, type_code: u8, depth: usize) -> Result<EpeeValue, String> {
+    if type_code & FLAG_ARRAY == 0 {
+        return read_value(data, pos, type_code, depth);
+    }
+    let element_type = type_code & !FLAG_ARRAY;
+    let count = read_size(data, pos)?;
+    let mut elements = Vec::with_capacity(count.min(data.len() - *pos));
+    for _ in 0..count {
+        elements.push(match element_type {
+            TYPE_ARRAY => {
+                let nested_type = take(data, pos, 1)?[0];
+                if nested_type & FLAG_ARRAY == 0 {
+                    return Err("Invalid nested array in portable storage".to_string());
+                }
+                read_typed(data, pos, nested_type, depth + 1)?
+            }
+            _ => read_value(data, pos, element_type, depth)?,
+        });
+    }
+    Ok(EpeeValue::Array(element_type, elements))
+}
+
+fn read_value(data: &[u8], pos: &mut usize, type_code: u8, depth: usize) -> Result<EpeeValue, String> {
+    let mut fixed = |len: usize| take(data, pos, len);
+    Ok(match type_code {
+        TYPE_INT64 => EpeeValue::I64(i64::from_le_bytes(fixed(8)?.try_into().unwrap())),
+        TYPE_INT32 => EpeeValue::I32(i32::from_le_bytes(fixed(4)?.try_into().unwrap())),
+        TYPE_INT16 => EpeeValue::I16(i16::from_le_bytes(fixed(2)?.try_into().unwrap())),
+        TYPE_INT8 => EpeeValue::I8(fixed(1)?[0] as i8),
+        TYPE_UINT64 => EpeeValue::U64(u64::from_le_bytes(fixed(8)?.try_into().unwrap())),
+        TYPE_UINT32 => EpeeValue::U32(u32::from_le_bytes(fixed(4)?.try_into().unwrap())),
+        TYPE_UINT16 => EpeeValue::U16(u16::from_le_bytes(fixed(2)?.try_into().unwrap())),
+        TYPE_UINT8 => EpeeValue::U8(fixed(1)?[0]),
+        TYPE_DOUBLE => EpeeValue::F64(f64::from_le_bytes(fixed(8)?.try_into().unwrap())),
+        TYPE_STRING => {
+            let len = read_size(data, pos)?;
+            EpeeValue::Blob(take(data, pos, len)?.to_vec())
+        }
+        TYPE_BOOL => EpeeValue::Bool(fixed(1)?[0] != 0),
+        TYPE_OBJECT => EpeeValue::Object(read_object(data, pos, depth + 1)?),
+        _ => return Err(format!("Unknown portable storage type {}", type_code)),
+    })
+}
@@ -0,0 +1,490 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::transactions::PaymentId;
+use crate::keys::PublicKey;
+use crate::utils::varint::{read_varint, write_varint};
+use crate::utils::Severity;
+use std::fmt;
+
+const TX_EXTRA_TAG_PADDING: u8 = 0x00;
+const TX_EXTRA_TAG_PUBKEY: u8 = 0x01;
+const TX_EXTRA_NONCE: u8 = 0x02;
+const TX_EXTRA_MERGE_MINING_TAG: u8 = 0x03;
+const TX_EXTRA_TAG_ADDITIONAL_PUBKEYS: u8 = 0x04;
+const TX_EXTRA_MYSTERIOUS_MINERGATE_TAG: u8 = 0xde;
+
+// Size limits of the reference implementation, padding counting its tag
+const TX_EXTRA_PADDING_MAX_COUNT: usize = 255;
+const TX_EXTRA_NONCE_MAX_COUNT: usize = 255;
+
+/// Largest tx_extra relayed by the reference daemon, in bytes
+pub const MAX_TX_EXTRA_SIZE: usize = 1060;
+
+// Extra nonce prefixes of payment IDs
+const NONCE_PAYMENT_ID: u8 = 0x00;
+const NONCE_ENCRYPTED_PAYMENT_ID: u8 = 0x01;
+
+/// Field of tx_extra
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExtraField {
+    /// Zero bytes ending tx_extra, the count includes the tag
+    Padding(usize),
+    /// Main transaction public key `r * G`
+    PublicKey([u8; 32]),
+    /// Arbitrary data of at most 255 bytes, usually a payment ID
+    Nonce(Vec<u8>),
+    /// Merge mining tag of blocks also mined for another chain
+    MergeMining { depth: u64, merkle_root: [u8; 32] },
+    /// Per output transaction public keys, used when paying subaddresses
+    AdditionalPublicKeys(Vec<[u8; 32]>),
+    /// Data left by the MinerGate pool in old miner transactions
+    MysteriousMinergate(Vec<u8>),
+}
+
+impl ExtraField {
+    // Position of the field in a sorted tx_extra, the order used by the reference wallet
+    fn order(&self) -> u8 {
+        match self {
+            ExtraField::PublicKey(_) => 0,
+            ExtraField::Nonce(_) => 1,
+            ExtraField::MergeMining { .. } => 2,
+            ExtraField::AdditionalPublicKeys(_) => 3,
+            ExtraField::MysteriousMinergate(_) => 4,
+            ExtraField::Padding(_) => 5,
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            ExtraField::Padding(_) => TX_EXTRA_TAG_PADDING,
+            ExtraField::PublicKey(_) => TX_EXTRA_TAG_PUBKEY,
+            ExtraField::Nonce(_) => TX_EXTRA_NONCE,
+            ExtraField::MergeMining { .. } => TX_EXTRA_MERGE_MINING_TAG,
+            ExtraField::AdditionalPublicKeys(_) => TX_EXTRA_TAG_ADDITIONAL_PUBKEYS,
+            ExtraField::MysteriousMinergate(_) => TX_EXTRA_MYSTERIOUS_MINERGATE_TAG,
+        }
+    }
+}
+
+/// ExtraIssue is a problem found in tx_extra by [`check_tx_extra`](fn.check_tx_extra.html)
+///
+/// Errors make the daemon refuse to relay the transaction, warnings are relayed but set the transaction apart from
+/// those of the reference wallet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExtraIssue {
+    /// tx_extra is longer than [`MAX_TX_EXTRA_SIZE`](constant.MAX_TX_EXTRA_SIZE.html)
+    Oversized(usize),
+    /// tx_extra cannot be parsed: unknown tag, truncated field, oversized nonce or invalid padding
+    Unparsable(String),
+    /// Additional public keys are present but there is not one per output
+    AdditionalKeyCount { keys: usize, outputs: usize },
+    /// Fields are not in the order the reference wallet sorts them in
+    Unsorted,
+    /// Field of this tag appears more than once, only the first one is read by wallets
+    Duplicate(u8),
+    /// There is no main transaction public key
+    MissingTxPublicKey,
+    /// Nonce holds an unencrypted payment ID, deprecated and no longer created by the reference wallet
+    UnencryptedPaymentId,
+    /// Field only found in old miner transactions
+    MysteriousMinergate,
+}
+
+impl ExtraIssue {
+    /// Returns the severity of the issue, errors block the relay of the transaction
+    pub fn severity(&self) -> Severity {
+        match self {
+            ExtraIssue::Oversized(_) | ExtraIssue::Unparsable(_) | ExtraIssue::AdditionalKeyCount { .. } => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for ExtraIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtraIssue::Oversized(size) => write!(f, "tx_extra is {} bytes long, at most {} are relayed", size, MAX_TX_EXTRA_SIZE),
+            ExtraIssue::Unparsable(reason) => write!(f, "{}", reason),
+            ExtraIssue::AdditionalKeyCount { keys, outputs } => write!(f, "{} additional public keys for {} outputs", keys, outputs),
+            ExtraIssue::Unsorted => write!(f, "tx_extra fields are not sorted"),
+            ExtraIssue::Duplicate(tag) => write!(f, "tx_extra has several fields of tag {:#04x}", tag),
+            ExtraIssue::MissingTxPublicKey => write!(f, "tx_extra has no transaction public key"),
+            ExtraIssue::UnencryptedPaymentId => write!(f, "tx_extra holds an unencrypted payment ID"),
+            ExtraIssue::MysteriousMinergate => write!(f, "tx_extra holds a MinerGate field"),
+        }
+    }
+}
+
+/// Checks tx_extra against the relay rules of the reference daemon and the standard layout of the reference wallet
+///
+/// `outputs` is the number of outputs of the transaction, checked against the number of additional public keys.
+/// Returns every issue found, empty for a standard tx_extra. Fields are only checked once tx_extra parses.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{check_tx_extra, ExtraIssue, PaymentId, TxExtra};
+/// use libmonero::keys::PrivateKey;
+/// use libmonero::utils::Severity;
+///
+/// let tx_public_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap().public_key();
+/// let extra = TxExtra::new().with_tx_public_key(&tx_public_key).with_payment_id(PaymentId::Encrypted([1; 8]));
+/// assert!(check_tx_extra(&extra.to_bytes(), 2).is_empty());
+///
+/// let issues = check_tx_extra(&extra.with_payment_id(PaymentId::Unencrypted([1; 32])).to_bytes(), 2);
+/// assert_eq!(issues, vec![ExtraIssue::UnencryptedPaymentId]);
+/// assert_eq!(issues[0].severity(), Severity::Warning);
+/// assert_eq!(check_tx_extra(&[0x07, 0x00], 2)[0].severity(), Severity::Error);
+/// ```
+pub fn check_tx_extra(extra: &[u8], outputs: usize) -> Vec<ExtraIssue> {
+    let mut issues = Vec::new();
+    if extra.len() > MAX_TX_EXTRA_SIZE {
+        issues.push(ExtraIssue::Oversized(extra.len()));
+    }
+    let parsed = match TxExtra::from_bytes(extra) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            issues.push(ExtraIssue::Unparsable(e));
+            return issues;
+        }
+    };
+
+    if let Some(ExtraField::AdditionalPublicKeys(keys)) = parsed.fields.iter().find(|field| matches!(field, ExtraField::AdditionalPublicKeys(_))) {
+        if keys.len() != outputs {
+            issues.push(ExtraIssue::AdditionalKeyCount { keys: keys.len(), outputs });
+        }
+    }
+    if parsed.fields.windows(2).any(|pair| pair[0].order() > pair[1].order()) {
+        issues.push(ExtraIssue::Unsorted);
+    }
+    let mut seen = [false; 6];
+    for field in parsed.fields.iter() {
+        if std::mem::replace(&mut seen[field.order() as usize], true) {
+            issues.push(ExtraIssue::Duplicate(field.tag()));
+        }
+    }
+    if !parsed.fields.iter().any(|field| matches!(field, ExtraField::PublicKey(_))) {
+        issues.push(ExtraIssue::MissingTxPublicKey);
+    }
+    if let Some(PaymentId::Unencrypted(_)) = parsed.payment_id() {
+        issues.push(ExtraIssue::UnencryptedPaymentId);
+    }
+    if parsed.fields.iter().any(|field| matches!(field, ExtraField::MysteriousMinergate(_))) {
+        issues.push(ExtraIssue::MysteriousMinergate);
+    }
+    issues
+}
+
+/// TxExtra is the `extra` field of a transaction split into its fields
+///
+/// [`from_bytes`](#method.from_bytes) is strict and fails on anything the reference implementation would not parse,
+/// while scanning reads whatever comes before a malformed field. Fields built with the `with_` methods are kept in
+/// the order the reference wallet writes them.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{ExtraField, PaymentId, TxExtra};
+/// use libmonero::keys::PrivateKey;
+///
+/// let tx_public_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap().public_key();
+/// let extra = TxExtra::new()
+///     .with_payment_id(PaymentId::Encrypted([1, 2, 3, 4, 5, 6, 7, 8]))
+///     .with_tx_public_key(&tx_public_key);
+/// let bytes = extra.to_bytes();
+/// assert_eq!(bytes[0], 0x01);
+/// assert_eq!(bytes[33..36], [0x02, 9, 0x01]);
+///
+/// let parsed = TxExtra::from_bytes(&bytes).unwrap();
+/// assert_eq!(parsed, extra);
+/// assert_eq!(parsed.tx_public_key(), Some(tx_public_key));
+/// assert_eq!(parsed.payment_id(), Some(PaymentId::Encrypted([1, 2, 3, 4, 5, 6, 7, 8])));
+/// assert!(TxExtra::from_bytes(&[0x01, 0xaa]).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxExtra {
+    pub fields: Vec<ExtraField>,
+}
+
+impl TxExtra {
+    /// Creates an empty tx_extra
+    pub fn new() -> TxExtra {
+        TxExtra::default()
+    }
+
+    /// Parses tx_extra, failing on unknown tags, truncated fields, oversized nonces and padding that is not made of
+    /// at most 255 zero bytes at the end
+    /// Returns the fields as a TxExtra struct if succesfull
+    /// Returns an error message if not succesfull
+    pub fn from_bytes(extra: &[u8]) -> Result<TxExtra, String> {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        let error = |what: &str, pos: usize| format!("Error while parsing tx_extra: {} at byte {}", what, pos);
+        while pos < extra.len() {
+            let start = pos;
+            let tag = extra[pos];
+            pos += 1;
+            let field = match tag {
+                TX_EXTRA_TAG_PADDING => {
+                    let padding = &extra[start..];
+                    if padding.len() > TX_EXTRA_PADDING_MAX_COUNT || padding.iter().any(|byte| *byte != 0) {
+                        return Err(error("invalid padding", start));
+                    }
+                    pos = extra.len();
+                    ExtraField::Padding(padding.len())
+                }
+                TX_EXTRA_TAG_PUBKEY => ExtraField::PublicKey(read_key(extra, &mut pos).ok_or(error("truncated public key", start))?),
+                TX_EXTRA_TAG_ADDITIONAL_PUBKEYS => {
+                    let count = read_varint(extra, &mut pos).map_err(|_| error("invalid key count", start))?;
+                    if count > ((extra.len() - pos) / 32) as u64 {
+                        return Err(error("truncated additional public keys", start));
+                    }
+                    ExtraField::AdditionalPublicKeys((0..count).map(|_| read_key(extra, &mut pos).unwrap()).collect())
+                }
+                TX_EXTRA_NONCE | TX_EXTRA_MERGE_MINING_TAG | TX_EXTRA_MYSTERIOUS_MINERGATE_TAG => {
+                    let len = read_varint(extra, &mut pos).map_err(|_| error("invalid field length", start))?;
+                    if len > (extra.len() - pos) as u64 {
+                        return Err(error("truncated field", start));
+                    }
+                    let data = &extra[pos..pos + len as usize];
+                    pos += data.len();
+                    match tag {
+                        TX_EXTRA_NONCE if data.len() > TX_EXTRA_NONCE_MAX_COUNT => return Err(error("oversized nonce", start)),
+                        TX_EXTRA_NONCE => ExtraField::Nonce(data.to_vec()),
+                        TX_EXTRA_MERGE_MINING_TAG => {
+                            let mut data_pos = 0;
+                            let depth = read_varint(data, &mut data_pos).map_err(|_| error("invalid merge mining depth", start))?;
+                            let merkle_root = read_key(data, &mut data_pos).filter(|_| data_pos == data.len()).ok_or(error("invalid merge mining tag", start))?;
+                            ExtraField::MergeMining { depth, merkle_root }
+                        }
+                        _ => ExtraField::MysteriousMinergate(data.to_vec()),
+                    }
+                }
+                tag => return Err(error(&format!("unknown tag {:#04x}", tag), start)),
+            };
+            fields.push(field);
+        }
+        Ok(TxExtra { fields })
+    }
+
+    /// Serializes the fields in their current order
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut extra = Vec::new();
+        for field in self.fields.iter() {
+            match field {
+                ExtraField::Padding(count) => extra.resize(extra.len() + count, 0),
+                ExtraField::PublicKey(key) => {
+                    extra.push(TX_EXTRA_TAG_PUBKEY);
+                    extra.extend_from_slice(key);
+                }
+                ExtraField::Nonce(data) => {
+                    extra.push(TX_EXTRA_NONCE);
+                    write_varint(data.len() as u64, &mut extra);
+                    extra.extend_from_slice(data);
+                }
+                ExtraField::MergeMining { depth, merkle_root } => {
+                    let mut data = Vec::with_capacity(33);
+                    write_varint(*depth, &mut data);
+                    data.extend_from_slice(merkle_root);
+                    extra.push(TX_EXTRA_MERGE_MINING_TAG);
+                    write_varint(data.len() as u64, &mut extra);
+                    extra.extend_from_slice(&data);
+                }
+                ExtraField::AdditionalPublicKeys(keys) => {
+                    extra.push(TX_EXTRA_TAG_ADDITIONAL_PUBKEYS);
+                    write_varint(keys.len() as u64, &mut extra);
+                    for key in keys.iter() {
+                        extra.extend_from_slice(key);
+                    }
+                }
+                ExtraField::MysteriousMinergate(data) => {
+                    extra.push(TX_EXTRA_MYSTERIOUS_MINERGATE_TAG);
+                    write_varint(data.len() as u64, &mut extra);
+                    extra.extend_from_slice(data);
+                }
+            }
+        }
+        extra
+    }
+
+    /// Sets the main transaction public key, replacing any previous one
+    pub fn with_tx_public_key(mut self, tx_public_key: &PublicKey) -> TxExtra {
+        self.fields.retain(|field| !matches!(field, ExtraField::PublicKey(_)));
+        self.insert(ExtraField::PublicKey(*tx_public_key.as_bytes()))
+    }
+
+    /// Sets the additional public keys, one per output, replacing any previous ones
+    pub fn with_additional_public_keys(mut self, keys: &[PublicKey]) -> TxExtra {
+        self.fields.retain(|field| !matches!(field, ExtraField::AdditionalPublicKeys(_)));
+        self.insert(ExtraField::AdditionalPublicKeys(keys.iter().map(|key| *key.as_bytes()).collect()))
+    }
+
+    /// Sets the payment ID, replacing any previous nonce
+    pub fn with_payment_id(self, payment_id: PaymentId) -> TxExtra {
+        let nonce = match payment_id {
+            PaymentId::Unencrypted(id) => [&[NONCE_PAYMENT_ID], id.as_slice()].concat(),
+            PaymentId::Encrypted(id) => [&[NONCE_ENCRYPTED_PAYMENT_ID], id.as_slice()].concat(),
+        };
+        self.with_nonce(nonce).unwrap()
+    }
+
+    /// Sets the nonce, replacing any previous one, fails if the nonce is longer than 255 bytes
+    pub fn with_nonce(mut self, nonce: Vec<u8>) -> Result<TxExtra, String> {
+        if nonce.len() > TX_EXTRA_NONCE_MAX_COUNT {
+            return Err(format!("Extra nonce is {} bytes long, at most {} are allowed", nonce.len(), TX_EXTRA_NONCE_MAX_COUNT));
+        }
+        self.fields.retain(|field| !matches!(field, ExtraField::Nonce(_)));
+        Ok(self.insert(ExtraField::Nonce(nonce)))
+    }
+
+    /// Ends tx_extra with `count` zero bytes, replacing any previous padding, fails if `count` is 0 or above 255
+    pub fn with_padding(mut self, count: usize) -> Result<TxExtra, String> {
+        if count == 0 || count > TX_EXTRA_PADDING_MAX_COUNT {
+            return Err(format!("Padding must be 1 to {} bytes long, got {}", TX_EXTRA_PADDING_MAX_COUNT, count));
+        }
+        self.fields.retain(|field| !matches!(field, ExtraField::Padding(_)));
+        Ok(self.insert(ExtraField::Padding(count)))
+    }
+
+    /// Checks the serialized fields with [`check_tx_extra`](fn.check_tx_extra.html) for a transaction of `outputs`
+    /// outputs, before it is signed
+    /// Returns the warnings if the daemon would relay the transaction
+    /// Returns an error message listing the errors if not
+    pub fn check(&self, outputs: usize) -> Result<Vec<ExtraIssue>, String> {
+        let (errors, warnings): (Vec<ExtraIssue>, Vec<ExtraIssue>) =
+            check_tx_extra(&self.to_bytes(), outputs).into_iter().partition(|issue| issue.severity() == Severity::Error);
+        if !errors.is_empty() {
+            let errors: Vec<String> = errors.iter().map(|issue| issue.to_string()).collect();
+            return Err(format!("Non-standard tx_extra: {}", errors.join(", ")));
+        }
+        Ok(warnings)
+    }
+
+    // Inserts a field after the fields that come before it in the reference wallet order
+    fn insert(mut self, field: ExtraField) -> TxExtra {
+        let index = self.fields.iter().position(|other| other.order() > field.order()).unwrap_or(self.fields.len());
+        self.fields.insert(index, field);
+        self
+    }
+
+    /// Returns the first main transaction public key, if any and valid
+    pub fn tx_public_key(&self) -> Option<PublicKey> {
+        self.fields.iter().find_map(|field| match field {
+            ExtraField::PublicKey(key) => PublicKey::from_bytes(*key).ok(),
+            _ => None,
+        })
+    }
+
+    /// Returns the additional public keys, empty if there are none or if one is not a valid point
+    pub fn additional_public_keys(&self) -> Vec<PublicKey> {
+        self.fields
+            .iter()
+            .find_map(|field| match field {
+                ExtraField::AdditionalPublicKeys(keys) => keys.iter().map(|key| PublicKey::from_bytes(*key)).collect::<Result<Vec<_>, _>>().ok(),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the first nonce, if any
+    pub fn nonce(&self) -> Option<&[u8]> {
+        self.fields.iter().find_map(|field| match field {
+            ExtraField::Nonce(nonce) => Some(nonce.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Returns the payment ID held by the first nonce, if any
+    pub fn payment_id(&self) -> Option<PaymentId> {
+        nonce_payment_id(self.nonce()?)
+    }
+}
+
+// Payment ID held by an extra nonce, if it holds one
+pub(crate) fn nonce_payment_id(nonce: &[u8]) -> Option<PaymentId> {
+    match nonce.split_first()? {
+        (&NONCE_PAYMENT_ID, id) => Some(PaymentId::Unencrypted(id.try_into().ok()?)),
+        (&NONCE_ENCRYPTED_PAYMENT_ID, id) => Some(PaymentId::Encrypted(id.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn read_key(data: &[u8], pos: &mut usize) -> Option<[u8; 32]> {
+    let key = data.get(*pos..*pos + 32)?.try_into().ok()?;
+    *pos += 32;
+    Some(key)
+}
+
+// Calls `visit` with the tag and the payload of every field of tx_extra, the payload of additional public keys
+// being the keys without their count
+// Walking stops at the first malformed or unknown field, like the reference wallet does
+fn walk_extra<'a>(extra: &'a [u8], mut visit: impl FnMut(u8, &'a [u8])) {
+    let mut pos = 0;
+    while pos < extra.len() {
+        let tag = extra[pos];
+        pos += 1;
+        match tag {
+            TX_EXTRA_TAG_PADDING => break,
+            TX_EXTRA_TAG_PUBKEY => match extra.get(pos..pos + 32) {
+                Some(key) => {
+                    visit(tag, key);
+                    pos += 32;
+                }
+                None => break,
+            },
+            TX_EXTRA_TAG_ADDITIONAL_PUBKEYS => {
+                let count = match read_varint(extra, &mut pos) {
+                    Ok(count) => count as usize,
+                    Err(_) => break,
+                };
+                let keys = match count.checked_mul(32).and_then(|len| extra.get(pos..pos + len)) {
+                    Some(keys) => keys,
+                    None => break,
+                };
+                visit(tag, keys);
+                pos += keys.len();
+            }
+            TX_EXTRA_NONCE | TX_EXTRA_MERGE_MINING_TAG | TX_EXTRA_MYSTERIOUS_MINERGATE_TAG => {
+                let len = match read_varint(extra, &mut pos) {
+                    Ok(len) => len as usize,
+                    Err(_) => break,
+                };
+                if extra.len() - pos < len {
+                    break;
+                }
+                visit(tag, &extra[pos..pos + len]);
+                pos += len;
+            }
+            _ => break,
+        }
+    }
+}
+
+// Transaction public keys found in tx_extra, main keys first and additional (per output) keys second
+pub(crate) fn extract_tx_pub_keys(extra: &[u8]) -> (Vec<[u8; 32]>, Vec<[u8; 32]>) {
+    let mut main_keys = Vec::new();
+    let mut additional_keys = Vec::new();
+    walk_extra(extra, |tag, payload| match tag {
+        TX_EXTRA_TAG_PUBKEY => main_keys.push(payload.try_into().unwrap()),
+        TX_EXTRA_TAG_ADDITIONAL_PUBKEYS => additional_keys.extend(payload.chunks(32).map(|key| <[u8; 32]>::try_from(key).unwrap())),
+        _ => {}
+    });
+    (main_keys, additional_keys)
+}
+
+// Content of the first extra nonce field of tx_extra, which holds the payment ID if any
+pub(crate) fn extract_extra_nonce(extra: &[u8]) -> Option<&[u8]> {
+    let mut nonce = None;
+    walk_extra(extra, |tag, payload| {
+        if tag == TX_EXTRA_NONCE && nonce.is_none() {
+            nonce = Some(payload);
+        }
+    });
+    nonce
+}
@@ -0,0 +1,216 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::{FeeEstimate, TxBacklogEntry};
+use super::rpcs::get_fee_estimate;
+use super::DaemonNode;
+use crate::wallet::DEFAULT_RING_SIZE;
+
+// Number of blocks the fee estimate must stay valid for, same as the reference wallet
+pub(crate) const FEE_ESTIMATE_GRACE_BLOCKS: u64 = 10;
+// Multipliers of the base fee for each priority, used by the reference wallet when the daemon gives no per priority fees
+const FEE_MULTIPLIERS: [u64; 4] = [1, 5, 25, 1000];
+
+/// FeePriority lists the fee priorities known by Monero wallets, from the cheapest to the most expensive one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeePriority {
+    /// Lowest fee, only safe when there is no backlog
+    Low = 1,
+    /// Default fee
+    Normal = 2,
+    /// Higher fee, for congested pools
+    Elevated = 3,
+    /// Highest fee
+    Priority = 4,
+}
+
+/// Estimates in how many blocks the pool transactions paying at least `fee_per_byte` would be mined
+///
+/// Uses the same estimation as the reference wallet: the weight of all pool transactions paying at least the given
+/// fee per byte is divided by the full reward zone, which is half of the block weight limit
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{estimate_backlog_blocks, TxBacklogEntry};
+///
+/// let backlog = vec![TxBacklogEntry { weight: 300000, fee: 6000000000, time_in_pool: 10 }; 3];
+/// assert_eq!(estimate_backlog_blocks(&backlog, 20000, 600000), 3);
+/// assert_eq!(estimate_backlog_blocks(&backlog, 30000, 600000), 0);
+/// ```
+pub fn estimate_backlog_blocks(backlog: &[TxBacklogEntry], fee_per_byte: u64, block_weight_limit: u64) -> u64 {
+    let full_reward_zone = block_weight_limit / 2;
+    if full_reward_zone == 0 {
+        return 0;
+    }
+    let weight: u64 = backlog
+        .iter()
+        .filter(|entry| entry.weight > 0 && entry.fee as f64 / entry.weight as f64 >= fee_per_byte as f64)
+        .map(|entry| entry.weight)
+        .sum();
+    weight / full_reward_zone
+}
+
+/// Suggests a fee priority from the state of the transaction pool and of the last blocks
+///
+/// Same logic as the reference wallet's automatic priority: `Low` is suggested only when no pool transaction pays the base
+/// fee or more (so nothing would be mined before a low priority transaction) and recent blocks fill at most 80% of the
+/// full reward zone, `Normal` is suggested otherwise
+///
+/// Arguments:
+/// - `backlog`: the pool backlog, see [`get_txpool_backlog`](fn.get_txpool_backlog.html)
+/// - `base_fee_per_byte`: the base (lowest priority) fee per byte estimated by the daemon
+/// - `block_weight_limit`: current block weight limit
+/// - `recent_block_weights`: weights of the last blocks, the reference wallet uses the last 10
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{suggest_priority, FeePriority, TxBacklogEntry};
+///
+/// let recent = vec![100000; 10];
+/// assert_eq!(suggest_priority(&[], 20000, 600000, &recent), FeePriority::Low);
+/// let backlog = vec![TxBacklogEntry { weight: 2000, fee: 60000000, time_in_pool: 10 }];
+/// assert_eq!(suggest_priority(&backlog, 20000, 600000, &recent), FeePriority::Normal);
+/// assert_eq!(suggest_priority(&[], 20000, 600000, &vec![290000; 10]), FeePriority::Normal);
+/// ```
+pub fn suggest_priority(backlog: &[TxBacklogEntry], base_fee_per_byte: u64, block_weight_limit: u64, recent_block_weights: &[u64]) -> FeePriority {
+    // Any transaction paying at least the base fee would be mined before ours
+    let competing = backlog
+        .iter()
+        .any(|entry| entry.weight > 0 && entry.fee as f64 / entry.weight as f64 >= base_fee_per_byte as f64);
+    if competing {
+        return FeePriority::Normal;
+    }
+    let full_reward_zone = block_weight_limit / 2;
+    if full_reward_zone == 0 || recent_block_weights.is_empty() {
+        return FeePriority::Normal;
+    }
+    let weight_sum: u128 = recent_block_weights.iter().map(|weight| *weight as u128).sum();
+    let fill_percent = 100 * weight_sum / (recent_block_weights.len() as u128 * full_reward_zone as u128);
+    if fill_percent > 80 {
+        FeePriority::Normal
+    } else {
+        FeePriority::Low
+    }
+}
+
+impl FeeEstimate {
+    /// Returns the fee per byte of weight for given priority
+    ///
+    /// When the daemon gave no per priority fees, the base fee is multiplied by 1, 5, 25 or 1000 like the reference
+    /// wallet does
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::{FeeEstimate, FeePriority};
+    ///
+    /// let estimate = FeeEstimate { fee: 20000, fees: vec![20000, 80000, 320000, 4000000], quantization_mask: 10000, untrusted: false };
+    /// assert_eq!(estimate.fee_per_byte(FeePriority::Elevated), 320000);
+    /// let base_only = FeeEstimate { fees: Vec::new(), ..estimate };
+    /// assert_eq!(base_only.fee_per_byte(FeePriority::Elevated), 500000);
+    /// ```
+    pub fn fee_per_byte(&self, priority: FeePriority) -> u64 {
+        let index = priority as usize - 1;
+        match self.fees.get(index) {
+            Some(fee) => *fee,
+            None => self.fee.saturating_mul(FEE_MULTIPLIERS[index]),
+        }
+    }
+
+    /// Returns the fee for a transaction of given weight at given priority, rounded up to the quantization mask
+    pub fn fee_for_weight(&self, weight: u64, priority: FeePriority) -> u64 {
+        let fee = self.fee_per_byte(priority).saturating_mul(weight);
+        if self.quantization_mask <= 1 {
+            return fee;
+        }
+        fee.div_ceil(self.quantization_mask).saturating_mul(self.quantization_mask)
+    }
+}
+
+/// Estimates the weight of a CLSAG transaction with Bulletproofs+ and view tags, the weight fees are paid on
+///
+/// Same estimation as the reference wallet: the size of every field is counted with varints at their usual width, then
+/// the Bulletproofs+ clawback of transactions with more than 2 outputs is added
+///
+/// Arguments:
+/// - `inputs`: number of inputs
+/// - `outputs`: number of outputs, change included
+/// - `ring_size`: number of members of each ring, 16 since the 15th hard fork
+/// - `extra_size`: size of the `tx_extra` field in bytes
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::estimate_tx_weight;
+///
+/// assert_eq!(estimate_tx_weight(1, 2, 16, 44), 1536);
+/// assert_eq!(estimate_tx_weight(2, 3, 16, 44), 2818);
+/// ```
+pub fn estimate_tx_weight(inputs: usize, outputs: usize, ring_size: usize, extra_size: usize) -> u64 {
+    let (inputs, outputs, ring_size) = (inputs as u64, outputs as u64, ring_size as u64);
+    let mut log_padded_outputs = 0;
+    while (1 << log_padded_outputs) < outputs {
+        log_padded_outputs += 1;
+    }
+    // Version and unlock time
+    let mut size = 1 + 6;
+    // Inputs: tag, amount, key offsets and key image
+    size += inputs * (1 + 6 + ring_size * 2 + 32);
+    // Outputs: amount, key
+    size += outputs * (6 + 32);
+    size += extra_size as u64;
+    // RingCT type
+    size += 1;
+    // Bulletproof+ proving all outputs
+    size += (2 * (6 + log_padded_outputs) + 6) * 32 + 3;
+    // CLSAG: s of each member, c1 and D
+    size += inputs * (32 * ring_size + 64);
+    // Pseudo outputs
+    size += 32 * inputs;
+    // Encrypted amounts, commitments and view tags
+    size += outputs * (8 + 32 + 1);
+    // Fee
+    size += 4;
+
+    // Proofs of more than 2 outputs are smaller than as many 2 output proofs, the weight takes back 80% of the difference
+    if outputs > 2 {
+        let bp_base = (32 * (6 + 7 * 2)) / 2;
+        let log_padded_outputs = log_padded_outputs.max(2);
+        let bp_size = 32 * (6 + 2 * (6 + log_padded_outputs));
+        size += (bp_base * (1 << log_padded_outputs) - bp_size) * 4 / 5;
+    }
+    size
+}
+
+/// Estimates the fee of a transaction with the current fee estimate of the given daemon
+///
+/// The weight is estimated by [`estimate_tx_weight`](fn.estimate_tx_weight.html) with rings of 16 members, then
+/// priced like the reference wallet does: the fee per byte of the priority times the weight, rounded up to the
+/// quantization mask of the daemon. The estimate is asked to stay valid for 10 blocks.
+///
+/// Arguments:
+/// - `priority`: fee priority of the transaction
+/// - `inputs`: number of inputs
+/// - `outputs`: number of outputs, change included
+/// - `extra_size`: size of the `tx_extra` field in bytes
+/// - `node`: daemon giving the fee estimate
+///
+/// Returns the fee in piconeros if succesfull
+/// Returns an error message if the fee estimate could not be fetched
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{estimate_fee, DaemonNode, FeePriority};
+///
+/// let fee = estimate_fee(FeePriority::Normal, 2, 2, 44, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Fee of a 2 input 2 output transaction: {}", fee);
+/// ```
+pub fn estimate_fee(priority: FeePriority, inputs: usize, outputs: usize, extra_size: usize, node: DaemonNode) -> Result<u64, String> {
+    let estimate = get_fee_estimate(FEE_ESTIMATE_GRACE_BLOCKS, node)?;
+    Ok(estimate.fee_for_weight(estimate_tx_weight(inputs, outputs, DEFAULT_RING_SIZE, extra_size), priority))
+}
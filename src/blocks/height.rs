@@ -0,0 +1,197 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::nodes::DaemonNode;
+use super::rpcs::{get_block_header_by_height, get_height};
+use crate::utils::Network;
+
+/// Target time between two blocks since the second hard fork, in seconds
+pub const BLOCK_TARGET_SECONDS: u64 = 120;
+
+// Known (height, timestamp) of a block after which blocks target 2 minutes, and the target before it
+fn anchor(network: Network) -> (u64, u64, u64) {
+    match network {
+        Network::Mainnet => (1009827, 1458748658, 60),
+        Network::Testnet => (624634, 1448285909, 60),
+        Network::Stagenet => (32000, 1520937818, 120),
+    }
+}
+
+/// Estimates the height of the blockchain at given UNIX timestamp, using the block target time
+///
+/// Actual heights differ by a few blocks per day of distance to the anchor block, use
+/// [`height_at_with_node`](fn.height_at_with_node.html) for an exact answer
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{height_at, timestamp_at};
+/// use libmonero::utils::Network;
+///
+/// // 17 October 2023, block 3000000 was mined that day
+/// let height = height_at(1697569418, Network::Mainnet);
+/// assert_eq!(height, 3000000);
+/// assert_eq!(timestamp_at(height, Network::Mainnet), 1697569418);
+/// ```
+pub fn height_at(timestamp: u64, network: Network) -> u64 {
+    let (anchor_height, anchor_timestamp, early_target) = anchor(network);
+    if timestamp >= anchor_timestamp {
+        anchor_height + (timestamp - anchor_timestamp) / BLOCK_TARGET_SECONDS
+    } else {
+        anchor_height.saturating_sub((anchor_timestamp - timestamp).div_ceil(early_target))
+    }
+}
+
+/// Estimates the UNIX timestamp of the block at given height, using the block target time
+pub fn timestamp_at(height: u64, network: Network) -> u64 {
+    let (anchor_height, anchor_timestamp, early_target) = anchor(network);
+    if height >= anchor_height {
+        anchor_timestamp + (height - anchor_height) * BLOCK_TARGET_SECONDS
+    } else {
+        anchor_timestamp.saturating_sub((anchor_height - height) * early_target)
+    }
+}
+
+/// Finds the highest block whose timestamp is not after the given UNIX timestamp, asking the given node
+///
+/// Starts from the estimation of [`height_at`](fn.height_at.html) and refines it with a binary search over block headers,
+/// which makes it suitable for restore heights. Returns an error message if the node could not be queried.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{height_at_with_node, DaemonNode};
+/// use libmonero::utils::Network;
+///
+/// let height = height_at_with_node(1697569418, Network::Mainnet, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Restore height: {}", height);
+/// ```
+pub fn height_at_with_node(timestamp: u64, network: Network, node: DaemonNode) -> Result<u64, String> {
+    let mut search = HeightSearch::new(timestamp, network, get_height(node.clone())?);
+    let mut step = search.next(None);
+    loop {
+        match step {
+            HeightStep::Fetch(height) => step = search.next(Some(get_block_header_by_height(height, node.clone())?.timestamp)),
+            HeightStep::Done(height) => return Ok(height),
+        }
+    }
+}
+
+/// Next step of a [`HeightSearch`], fetching the timestamp of the block at a height or the height found
+pub(crate) enum HeightStep {
+    Fetch(u64),
+    Done(u64),
+}
+
+// Block whose timestamp the search waits for
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Probe {
+    Low,
+    High,
+    Final,
+    Middle,
+}
+
+/// Search of [`height_at_with_node`](fn.height_at_with_node.html) without I/O, the caller fetches the block timestamps
+/// it asks for so blocking and async clients share it
+pub(crate) struct HeightSearch {
+    timestamp: u64,
+    top: u64,
+    estimate: u64,
+    window: u64,
+    low: u64,
+    high: u64,
+    low_ok: bool,
+    probe: Probe,
+}
+
+impl HeightSearch {
+    /// Starts a search in a chain of the given height
+    pub(crate) fn new(timestamp: u64, network: Network, chain_height: u64) -> HeightSearch {
+        let top = chain_height.saturating_sub(1);
+        let mut search = HeightSearch {
+            timestamp,
+            top,
+            estimate: height_at(timestamp, network).min(top),
+            window: 720,
+            low: 0,
+            high: 0,
+            low_ok: false,
+            probe: Probe::Low,
+        };
+        search.bracket();
+        search
+    }
+
+    // Brackets the answer around the estimation with the current window
+    fn bracket(&mut self) {
+        self.low = self.estimate.saturating_sub(self.window);
+        self.high = (self.estimate + self.window).min(self.top);
+        self.probe = Probe::Low;
+    }
+
+    /// Gives the timestamp of the block asked for by the last step, None for the first step, and returns the next step
+    pub(crate) fn next(&mut self, block_timestamp: Option<u64>) -> HeightStep {
+        // Chains of at most one block end at the genesis block
+        if self.top == 0 {
+            return HeightStep::Done(0);
+        }
+        let mut fetched = block_timestamp;
+        loop {
+            match self.probe {
+                // The answer is bracketed once the block at `low` is not after the timestamp and the one at `high` is,
+                // otherwise the window is widened
+                Probe::Low => match (self.low == 0, fetched.take()) {
+                    (true, _) => {
+                        self.low_ok = true;
+                        self.probe = Probe::High;
+                    }
+                    (false, Some(block_timestamp)) => {
+                        self.low_ok = block_timestamp <= self.timestamp;
+                        self.probe = Probe::High;
+                    }
+                    (false, None) => return HeightStep::Fetch(self.low),
+                },
+                Probe::High => {
+                    let high_ok = match (self.high == self.top, fetched.take()) {
+                        (true, _) => true,
+                        (false, Some(block_timestamp)) => block_timestamp > self.timestamp,
+                        (false, None) => return HeightStep::Fetch(self.high),
+                    };
+                    match self.low_ok && high_ok {
+                        true => self.probe = Probe::Final,
+                        false => {
+                            self.window *= 4;
+                            self.bracket();
+                        }
+                    }
+                }
+                Probe::Final => match fetched.take() {
+                    Some(block_timestamp) if block_timestamp <= self.timestamp => return HeightStep::Done(self.high),
+                    Some(_) => self.probe = Probe::Middle,
+                    None => return HeightStep::Fetch(self.high),
+                },
+                // Invariant: block at `high` is after the timestamp, block at `low` is not (or `low` is the genesis block)
+                Probe::Middle => {
+                    let middle = self.low + (self.high - self.low) / 2;
+                    if let Some(block_timestamp) = fetched.take() {
+                        match block_timestamp <= self.timestamp {
+                            true => self.low = middle,
+                            false => self.high = middle,
+                        }
+                        continue;
+                    }
+                    if self.high - self.low <= 1 {
+                        return HeightStep::Done(self.low);
+                    }
+                    return HeightStep::Fetch(middle);
+                }
+            }
+        }
+    }
+}
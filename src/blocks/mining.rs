@@ -0,0 +1,212 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::blob::ParsedBlock;
+use super::block::BlockTemplate;
+use crate::utils::varint::read_varint;
+use std::ops::RangeInclusive;
+
+// Returns the offset of the 4-byte nonce in a serialized block header, which starts both blobs of a template:
+// major version, minor version and timestamp varints, then the 32-byte previous block hash
+fn nonce_offset(blob: &[u8]) -> Result<usize, String> {
+    let mut pos = 0;
+    for _ in 0..3 {
+        read_varint(blob, &mut pos)?;
+    }
+    let offset = pos + 32;
+    if offset + 4 > blob.len() {
+        return Err("Block blob is too short to hold a nonce".to_string());
+    }
+    Ok(offset)
+}
+
+impl BlockTemplate {
+    /// Writes the given nonce in both the template blob and the hashing blob
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::{BlockHash, BlockTemplate};
+    ///
+    /// // Major version 16, minor version 16, timestamp 1, previous hash, zero nonce
+    /// let mut header = vec![16, 16, 1];
+    /// header.extend_from_slice(&[0xaa; 32]);
+    /// header.extend_from_slice(&[0; 4]);
+    /// let mut template = BlockTemplate {
+    ///     blocktemplate_blob: header.clone(),
+    ///     blockhashing_blob: header,
+    ///     difficulty: 1,
+    ///     difficulty_top64: 0,
+    ///     wide_difficulty: "0x1".to_string(),
+    ///     expected_reward: 600000000000,
+    ///     height: 3000000,
+    ///     prev_hash: BlockHash::from_bytes([0xaa; 32]),
+    ///     reserved_offset: 0,
+    ///     reserve_size: 0,
+    ///     seed_hash: String::new(),
+    ///     next_seed_hash: String::new(),
+    ///     untrusted: false,
+    /// };
+    /// template.set_nonce(0x01020304).unwrap();
+    /// assert_eq!(template.blockhashing_blob[35..39], [4, 3, 2, 1]);
+    /// assert_eq!(template.blocktemplate_blob[35..39], [4, 3, 2, 1]);
+    /// ```
+    pub fn set_nonce(&mut self, nonce: u32) -> Result<(), String> {
+        for blob in [&mut self.blocktemplate_blob, &mut self.blockhashing_blob] {
+            let offset = nonce_offset(blob)?;
+            blob[offset..offset + 4].copy_from_slice(&nonce.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// Writes the given bytes at the start of the reserved space of the template blob
+    ///
+    /// Pools use it to give each miner a distinct extra nonce. As the reserved bytes are part of the miner transaction,
+    /// the hashing blob returned by the daemon only matches the template while they are left zeroed: after changing them,
+    /// the Merkle root of the hashing blob has to be recomputed or a new template requested,
+    /// [`set_extra_nonce`](#method.set_extra_nonce) does both steps.
+    pub fn set_reserved(&mut self, data: &[u8]) -> Result<(), String> {
+        if data.len() > self.reserve_size {
+            return Err(format!("Reserved data is {} bytes long, only {} bytes are reserved", data.len(), self.reserve_size));
+        }
+        let end = self.reserved_offset + data.len();
+        if end > self.blocktemplate_blob.len() {
+            return Err("Reserved offset is out of the template blob".to_string());
+        }
+        self.blocktemplate_blob[self.reserved_offset..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Writes the given extra nonce in the reserved space, zeroing the reserved bytes it does not fill, then recomputes
+    /// the hashing blob from the template blob
+    ///
+    /// The nonce already set in the template is kept. Extra nonces are usually handed out by an
+    /// [`ExtraNonceCounter`](struct.ExtraNonceCounter.html), each worker then searching its own
+    /// [`nonce_ranges`](fn.nonce_ranges.html) over its own template.
+    /// Returns `()` if succesfull
+    /// Returns an error message if the extra nonce does not fit in the reserved space or the template can not be parsed
+    pub fn set_extra_nonce(&mut self, extra_nonce: &[u8]) -> Result<(), String> {
+        if extra_nonce.len() > self.reserve_size {
+            return Err(format!("Extra nonce is {} bytes long, only {} bytes are reserved", extra_nonce.len(), self.reserve_size));
+        }
+        let mut reserved = vec![0u8; self.reserve_size];
+        reserved[..extra_nonce.len()].copy_from_slice(extra_nonce);
+        self.set_reserved(&reserved)?;
+        self.refresh_hashing_blob()
+    }
+
+    /// Recomputes the hashing blob from the template blob: header, Merkle root of the transactions and their count
+    ///
+    /// Needed after any change of the miner transaction, e.g. through [`set_reserved`](#method.set_reserved).
+    /// Returns `()` if succesfull
+    /// Returns an error message if the template blob can not be parsed
+    pub fn refresh_hashing_blob(&mut self) -> Result<(), String> {
+        self.blockhashing_blob = ParsedBlock::from_blob(&self.blocktemplate_blob)?.hashing_blob();
+        Ok(())
+    }
+}
+
+/// Splits the 32-bit nonce space in `workers` contiguous ranges of the same size, the first ones taking the remainder,
+/// so workers sharing a template never hash the same nonce
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::nonce_ranges;
+///
+/// let ranges = nonce_ranges(3);
+/// assert_eq!(ranges, vec![0..=0x55555555, 0x55555556..=0xaaaaaaaa, 0xaaaaaaab..=0xffffffff]);
+/// assert!(nonce_ranges(0).is_empty());
+/// ```
+pub fn nonce_ranges(workers: u32) -> Vec<RangeInclusive<u32>> {
+    if workers == 0 {
+        return Vec::new();
+    }
+    let space = u32::MAX as u64 + 1;
+    let (size, remainder) = (space / workers as u64, space % workers as u64);
+    let mut start = 0u64;
+    (0..workers as u64)
+        .map(|worker| {
+            let end = start + size + u64::from(worker < remainder) - 1;
+            let range = start as u32..=end as u32;
+            start = end + 1;
+            range
+        })
+        .collect()
+}
+
+/// ExtraNonceCounter hands out distinct extra nonces for the reserved space of a block template
+///
+/// Every extra nonce starts with a fixed prefix, e.g. the ID of the pool instance so several instances can share a
+/// wallet address, followed by a little-endian counter filling the rest of the reserved space.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::ExtraNonceCounter;
+///
+/// let mut counter = ExtraNonceCounter::new(&[0x07], 3).unwrap();
+/// assert_eq!(counter.next_extra_nonce().unwrap(), vec![7, 0, 0]);
+/// assert_eq!(counter.next_extra_nonce().unwrap(), vec![7, 1, 0]);
+/// assert!(ExtraNonceCounter::new(&[1, 2, 3], 3).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtraNonceCounter {
+    prefix: Vec<u8>,
+    counter_size: usize,
+    next: u64,
+}
+
+impl ExtraNonceCounter {
+    /// Creates a counter for a reserved space of `reserve_size` bytes, see
+    /// [`get_block_template`](fn.get_block_template.html)
+    /// Returns the counter if succesfull
+    /// Returns an error message if the prefix leaves no room for the counter in the reserved space
+    pub fn new(prefix: &[u8], reserve_size: usize) -> Result<ExtraNonceCounter, String> {
+        if prefix.len() >= reserve_size {
+            return Err(format!("Prefix of {} bytes leaves no room for a counter in {} reserved bytes", prefix.len(), reserve_size));
+        }
+        Ok(ExtraNonceCounter {
+            prefix: prefix.to_vec(),
+            counter_size: (reserve_size - prefix.len()).min(8),
+            next: 0,
+        })
+    }
+
+    /// Returns the next extra nonce
+    /// Returns an error message once every value of the counter was handed out
+    pub fn next_extra_nonce(&mut self) -> Result<Vec<u8>, String> {
+        if self.counter_size < 8 && self.next >> (8 * self.counter_size) != 0 {
+            return Err(format!("All {} extra nonces were handed out", 1u64 << (8 * self.counter_size)));
+        }
+        let mut extra_nonce = self.prefix.clone();
+        extra_nonce.extend_from_slice(&self.next.to_le_bytes()[..self.counter_size]);
+        self.next = self.next.checked_add(1).ok_or("All extra nonces were handed out")?;
+        Ok(extra_nonce)
+    }
+}
+
+/// Checks if a proof of work hash meets the given difficulty, which is the case when `hash * difficulty` (with the hash
+/// read as a little-endian 256-bit number) does not overflow 256 bits
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::meets_difficulty;
+///
+/// let mut hash = [0u8; 32];
+/// hash[31] = 0x7f;
+/// assert!(meets_difficulty(&hash, 2));
+/// assert!(!meets_difficulty(&hash, 3));
+/// ```
+pub fn meets_difficulty(pow_hash: &[u8; 32], difficulty: u64) -> bool {
+    let mut carry: u128 = 0;
+    for limb in pow_hash.chunks_exact(8) {
+        let limb = u64::from_le_bytes(limb.try_into().unwrap());
+        carry = (limb as u128 * difficulty as u128 + carry) >> 64;
+    }
+    carry == 0
+}
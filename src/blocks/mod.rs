@@ -10,9 +10,49 @@
 
 pub(crate) mod rpcs;
 pub(crate) mod nodes;
+pub(crate) mod privacy;
+pub(crate) mod proxy;
+pub(crate) mod socket;
+pub(crate) mod tip;
+pub(crate) mod tracker;
 pub(crate) mod transactions;
 pub(crate) mod block;
+pub(crate) mod auth;
+#[cfg(feature = "async")]
+pub(crate) mod client;
+pub(crate) mod blob;
+pub(crate) mod doublespend;
+pub(crate) mod epee;
+pub(crate) mod extra;
+pub(crate) mod fees;
+pub(crate) mod height;
+pub(crate) mod mining;
+pub(crate) mod monitor;
+pub(crate) mod pool;
+pub(crate) mod transport;
+#[cfg(feature = "zmq")]
+pub(crate) mod zmq;
 
 pub use rpcs::*;
 pub use nodes::*;
-pub use block::*;
\ No newline at end of file
+pub use privacy::*;
+pub use proxy::*;
+pub use socket::*;
+pub use tip::*;
+pub use block::*;
+pub use blob::*;
+#[cfg(feature = "async")]
+pub use client::*;
+pub use doublespend::*;
+pub use epee::*;
+pub use extra::*;
+pub use fees::*;
+pub use height::*;
+pub use mining::*;
+pub use monitor::*;
+pub use pool::*;
+pub use tracker::*;
+pub use transactions::*;
+pub use transport::*;
+#[cfg(feature = "zmq")]
+pub use zmq::*;
@@ -0,0 +1,186 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::BlockHash;
+use super::nodes::DaemonNode;
+use super::rpcs::get_height_info;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// ChainAlarm describes a problem detected while comparing the chains seen by several nodes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChainAlarm {
+    /// Node is behind the highest node by more than the allowed number of blocks
+    NodeLagging { node: String, height: u64, best_height: u64 },
+    /// Nodes report different top block hashes at the same height, hashes are mapped to the nodes reporting them
+    ChainSplit { height: u64, hashes: BTreeMap<BlockHash, Vec<String>> },
+    /// Node could not be queried
+    NodeUnreachable { node: String, error: String },
+}
+
+/// ChainMonitor compares the heights and top block hashes reported by several nodes and raises alarms
+/// when a node lags behind or when nodes disagree on the chain
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{BlockHash, ChainAlarm, ChainMonitor};
+///
+/// let mut monitor = ChainMonitor::new(5);
+/// monitor.observe("node-a", 3000000, &BlockHash::from_bytes([0xaa; 32]));
+/// monitor.observe("node-b", 3000000, &BlockHash::from_bytes([0xbb; 32]));
+/// monitor.observe("node-c", 2999990, &BlockHash::from_bytes([0xcc; 32]));
+/// let alarms = monitor.check();
+/// assert!(alarms.iter().any(|alarm| matches!(alarm, ChainAlarm::ChainSplit { height: 3000000, .. })));
+/// assert!(alarms.contains(&ChainAlarm::NodeLagging { node: "node-c".to_string(), height: 2999990, best_height: 3000000 }));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChainMonitor {
+    max_lag: u64,
+    tips: HashMap<String, (u64, BlockHash)>,
+    unreachable: HashMap<String, String>,
+}
+
+impl ChainMonitor {
+    /// Creates a monitor raising a lag alarm when a node is more than `max_lag` blocks behind the highest node
+    pub fn new(max_lag: u64) -> ChainMonitor {
+        ChainMonitor {
+            max_lag,
+            tips: HashMap::new(),
+            unreachable: HashMap::new(),
+        }
+    }
+
+    /// Records the height and top block hash reported by the given node
+    pub fn observe(&mut self, node: &str, height: u64, top_hash: &BlockHash) {
+        self.unreachable.remove(node);
+        self.tips.insert(node.to_string(), (height, *top_hash));
+    }
+
+    /// Records that the given node could not be queried, its last known tip is forgotten
+    pub fn observe_error(&mut self, node: &str, error: &str) {
+        self.tips.remove(node);
+        self.unreachable.insert(node.to_string(), error.to_string());
+    }
+
+    /// Returns the alarms matching the last observations
+    pub fn check(&self) -> Vec<ChainAlarm> {
+        let mut alarms = Vec::new();
+        let mut unreachable: Vec<_> = self.unreachable.iter().collect();
+        unreachable.sort();
+        for (node, error) in unreachable {
+            alarms.push(ChainAlarm::NodeUnreachable {
+                node: node.clone(),
+                error: error.clone(),
+            });
+        }
+        let best_height = match self.tips.values().map(|(height, _)| *height).max() {
+            Some(height) => height,
+            None => return alarms,
+        };
+        let mut by_height: BTreeMap<u64, BTreeMap<BlockHash, Vec<String>>> = BTreeMap::new();
+        for (node, (height, hash)) in self.tips.iter() {
+            by_height.entry(*height).or_default().entry(*hash).or_default().push(node.clone());
+        }
+        for (height, mut hashes) in by_height {
+            for nodes in hashes.values_mut() {
+                nodes.sort();
+            }
+            if hashes.len() > 1 {
+                alarms.push(ChainAlarm::ChainSplit { height, hashes: hashes.clone() });
+            }
+            if best_height - height > self.max_lag {
+                for node in hashes.into_values().flatten() {
+                    alarms.push(ChainAlarm::NodeLagging { node, height, best_height });
+                }
+            }
+        }
+        alarms
+    }
+
+    /// Queries the height and top block hash of every given node, records them and returns the resulting alarms
+    ///
+    /// Nodes are identified by their base URL
+    pub fn poll(&mut self, nodes: &[DaemonNode]) -> Vec<ChainAlarm> {
+        for node in nodes {
+            let name = node.base_url();
+            match get_height_info(node.clone()) {
+                Ok(info) => self.observe(&name, info.height, &info.hash),
+                Err(e) => self.observe_error(&name, &e),
+            }
+        }
+        self.check()
+    }
+
+    /// Polls the given nodes every `interval` on a background thread and calls `on_alarms` whenever alarms are raised
+    ///
+    /// Polling goes on until [`MonitorHandle::stop`](struct.MonitorHandle.html#method.stop) is called
+    pub fn watch<F>(mut self, nodes: Vec<DaemonNode>, interval: Duration, mut on_alarms: F) -> MonitorHandle
+    where
+        F: FnMut(&[ChainAlarm]) + Send + 'static,
+    {
+        MonitorHandle::spawn(interval, move || {
+            let alarms = self.poll(&nodes);
+            if !alarms.is_empty() {
+                on_alarms(&alarms);
+            }
+        })
+    }
+}
+
+/// MonitorHandle controls a background monitor started with [`ChainMonitor::watch`](struct.ChainMonitor.html#method.watch),
+/// [`NodePool::watch`](struct.NodePool.html#method.watch) or [`DoubleSpendWatcher::run`](struct.DoubleSpendWatcher.html#method.run),
+/// the monitor is stopped when the handle is dropped
+pub struct MonitorHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MonitorHandle {
+    /// Runs `task` every `interval` on a background thread until the returned handle is stopped or dropped
+    pub(crate) fn spawn<F>(interval: Duration, mut task: F) -> MonitorHandle
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                task();
+                std::thread::park_timeout(interval);
+            }
+        });
+        MonitorHandle {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops the monitor and waits for its thread to finish
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for MonitorHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
@@ -8,11 +8,200 @@
  *
  */
 
+use super::proxy::ProxyConfig;
+use super::socket::LocalSocketTransport;
+use super::transport::{HttpTransport, RpcTransport};
+use crate::utils::RpcError;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// TrustLevel describes how much the answers of a daemon node can be relied on
+///
+/// Levels are ordered from the most trusted to the least trusted one, so `Local < TrustedRemote < UntrustedRemote`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TrustLevel {
+    /// Node running on the same machine or in the same private network, fully controlled by the user
+    Local,
+    /// Remote node operated by the user or by someone the user trusts
+    TrustedRemote,
+    /// Remote node operated by a third party, e.g. a public node
+    UntrustedRemote,
+}
+
+/// RpcOperation lists the kinds of daemon operations whose answers may need to be cross-checked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RpcOperation {
+    /// Blockchain height and top block hash
+    Height,
+    /// Block data (headers, blobs, miner transactions)
+    BlockData,
+    /// Transaction data
+    TransactionData,
+    /// Fee estimates, a malicious node can return very high fees or fingerprinting values
+    FeeEstimate,
+    /// Output distribution, used for decoy selection
+    OutputDistribution,
+    /// Output keys and commitments, used as ring members
+    Outputs,
+    /// Relaying a transaction to the network
+    Broadcast,
+}
+
+/// RpcPriority tells a [`NodePool`](struct.NodePool.html) limiting its concurrent calls which waiting call to run first
+///
+/// Priorities are ordered from the most urgent to the least urgent one, so `Interactive < Background`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RpcPriority {
+    /// Call a user is waiting for, e.g. a balance refresh or a transaction broadcast
+    #[default]
+    Interactive,
+    /// Bulk call that can wait, e.g. block fetches of the initial wallet sync
+    Background,
+}
+
+/// CancelToken cancels the RPC calls of every node it is set on, see [`DaemonNode::with_cancel`](struct.DaemonNode.html#method.with_cancel)
+///
+/// Clones of a token share its state. Cancelling stops calls waiting for a slot of a [`NodePool`](struct.NodePool.html),
+/// retries and failover to the next node, a request already sent to a daemon still runs to completion.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Creates a token which is not cancelled
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Cancels the pending calls of the nodes holding this token and every call they make later
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks if the token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Error returned by calls stopped by the token
+    pub(crate) fn error() -> String {
+        RpcError::Cancelled.to_string()
+    }
+}
+
+/// RpcLogin holds the credentials of a daemon started with `--rpc-login username:password`
+#[derive(Clone)]
+pub struct RpcLogin {
+    pub username: String,
+    pub password: Zeroizing<String>,
+}
+
+/// RpcConfig sets the timeouts and the retry policy of the RPC calls made to a node
+///
+/// A failed call is retried up to `retries` times, waiting `backoff` before the first retry and twice as long before
+/// each following one. The default keeps the previous behavior of a single attempt.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{DaemonNode, ProxyConfig, RpcConfig};
+/// use std::time::Duration;
+///
+/// let node = DaemonNode::new("xmrnode.onion".to_string(), 18081, false)
+///     .with_proxy(ProxyConfig::tor())
+///     .with_config(RpcConfig::tor());
+/// assert_eq!(node.config.retries, 3);
+/// assert_eq!(node.config.retry_delay(2), Duration::from_secs(8));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RpcConfig {
+    /// Maximum time to establish the connection
+    pub connect_timeout: Duration,
+    /// Maximum time for the whole request, connection included
+    pub request_timeout: Duration,
+    /// Number of retries after a failed attempt
+    pub retries: u32,
+    /// Delay before the first retry, doubled before each following one
+    pub backoff: Duration,
+}
+
+impl Default for RpcConfig {
+    fn default() -> RpcConfig {
+        RpcConfig {
+            connect_timeout: Duration::from_secs(30),
+            request_timeout: Duration::from_secs(120),
+            retries: 0,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RpcConfig {
+    /// Returns a configuration suited to nodes in the local network, which fail fast and are retried quickly
+    pub fn lan() -> RpcConfig {
+        RpcConfig {
+            connect_timeout: Duration::from_secs(2),
+            request_timeout: Duration::from_secs(30),
+            retries: 2,
+            backoff: Duration::from_millis(100),
+        }
+    }
+
+    /// Returns a configuration suited to nodes reached through Tor, where circuits are slow to build and long block fetches are common
+    pub fn tor() -> RpcConfig {
+        RpcConfig {
+            connect_timeout: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(600),
+            retries: 3,
+            backoff: Duration::from_secs(2),
+        }
+    }
+
+    /// Returns the delay to wait before the given retry, starting at 0
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(retry))
+    }
+}
+
 /// DaemonNode struct contains all necessary and additional information about a daemon node
+#[derive(Clone)]
 pub struct DaemonNode {
     pub url: String,
     pub port: u16,
     pub tls: bool,
+    pub trust: TrustLevel,
+    /// Credentials for daemons requiring HTTP digest authentication
+    pub login: Option<RpcLogin>,
+    /// SOCKS5 proxy the default HTTP transport connects through
+    pub proxy: Option<ProxyConfig>,
+    /// Timeouts and retry policy of the RPC calls
+    pub config: RpcConfig,
+    /// Priority of the calls when the node is taken from a [`NodePool`](struct.NodePool.html)
+    pub priority: RpcPriority,
+    /// Token stopping the calls of the node once cancelled
+    pub cancel: Option<CancelToken>,
+    pub(crate) transport: Option<Arc<dyn RpcTransport>>,
+}
+
+impl fmt::Debug for DaemonNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaemonNode")
+            .field("url", &self.url)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("trust", &self.trust)
+            .field("username", &self.login.as_ref().map(|login| login.username.as_str()))
+            .field("proxy", &self.proxy)
+            .field("config", &self.config)
+            .field("priority", &self.priority)
+            .field("cancel", &self.cancel)
+            .field("custom_transport", &self.transport.is_some())
+            .finish()
+    }
 }
 
 /// DaemonNode functions etc.
@@ -22,16 +211,199 @@ impl DaemonNode {
         DaemonNode {
             url: "xmr-node.cakewallet.com".to_string(),
             port: 18081,
-            tls: false
+            tls: false,
+            trust: TrustLevel::UntrustedRemote,
+            login: None,
+            proxy: None,
+            config: RpcConfig::default(),
+            priority: RpcPriority::Interactive,
+            cancel: None,
+            transport: None,
         }
     }
 
     /// Creates a new DaemonNode from a given URL, port and tls flag
+    ///
+    /// Trust level is `Local` for loopback addresses and `UntrustedRemote` for everything else,
+    /// use [`with_trust`](#method.with_trust) to change it
     pub fn new(url: String, port: u16, tls: bool) -> DaemonNode {
+        let trust = match url.as_str() {
+            "localhost" | "127.0.0.1" | "::1" | "[::1]" => TrustLevel::Local,
+            _ => TrustLevel::UntrustedRemote,
+        };
         DaemonNode {
             url,
             port,
-            tls
+            tls,
+            trust,
+            login: None,
+            proxy: None,
+            config: RpcConfig::default(),
+            priority: RpcPriority::Interactive,
+            cancel: None,
+            transport: None,
         }
     }
-}
\ No newline at end of file
+
+    /// Creates a node reached through the Unix domain socket (or Windows named pipe) at the given path,
+    /// see [`LocalSocketTransport`](struct.LocalSocketTransport.html), its trust level is `Local`
+    pub fn local_socket<P: Into<PathBuf>>(path: P) -> DaemonNode {
+        DaemonNode::new("localhost".to_string(), 0, false).with_transport(Arc::new(LocalSocketTransport::new(path)))
+    }
+
+    /// Sets the trust level of the node
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::{DaemonNode, TrustLevel};
+    ///
+    /// let node = DaemonNode::new("node.example.com".to_string(), 18081, false).with_trust(TrustLevel::TrustedRemote);
+    /// assert_eq!(node.trust, TrustLevel::TrustedRemote);
+    /// ```
+    pub fn with_trust(mut self, trust: TrustLevel) -> DaemonNode {
+        self.trust = trust;
+        self
+    }
+
+    /// Sets the username and password used to authenticate to a daemon started with `--rpc-login`
+    ///
+    /// The default HTTP transport answers the daemon's digest challenge with them, the password is never sent in clear
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::DaemonNode;
+    ///
+    /// let node = DaemonNode::new("node.example.com".to_string(), 18081, true).with_login("monero", "hunter2");
+    /// assert_eq!(node.login.unwrap().username, "monero");
+    /// ```
+    pub fn with_login(mut self, username: &str, password: &str) -> DaemonNode {
+        self.login = Some(RpcLogin {
+            username: username.to_string(),
+            password: Zeroizing::new(password.to_string()),
+        });
+        self
+    }
+
+    /// Routes the RPC connections of the default HTTP transport through the given SOCKS5 proxy, see [`ProxyConfig`](struct.ProxyConfig.html)
+    ///
+    /// The trust level is left unchanged, a `.onion` node is as trustworthy as its operator
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> DaemonNode {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets the timeouts and retry policy of the RPC calls made to the node, see [`RpcConfig`](struct.RpcConfig.html)
+    pub fn with_config(mut self, config: RpcConfig) -> DaemonNode {
+        self.config = config;
+        self
+    }
+
+    /// Sets the priority of the calls made through the node, see [`RpcPriority`](enum.RpcPriority.html)
+    ///
+    /// Only nodes sending their calls through a [`NodePool`](struct.NodePool.html) limited with
+    /// [`with_max_concurrent`](struct.NodePool.html#method.with_max_concurrent) queue them, other nodes call right away
+    pub fn with_priority(mut self, priority: RpcPriority) -> DaemonNode {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the token cancelling the calls of the node, see [`CancelToken`](struct.CancelToken.html)
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::{get_height, CancelToken, DaemonNode};
+    ///
+    /// let cancel = CancelToken::new();
+    /// let node = DaemonNode::cake_wallet_default().with_cancel(cancel.clone());
+    /// cancel.cancel();
+    /// assert_eq!(get_height(node).unwrap_err(), "Error while getting the block count (height) from daemon: RPC call cancelled");
+    /// ```
+    pub fn with_cancel(mut self, cancel: CancelToken) -> DaemonNode {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Sets the transport used to deliver RPC requests to the node, e.g. a [`RecordingTransport`](struct.RecordingTransport.html)
+    /// or a [`ReplayTransport`](struct.ReplayTransport.html) for reproducible tests
+    pub fn with_transport(mut self, transport: Arc<dyn RpcTransport>) -> DaemonNode {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Returns the base URL of the node, e.g. `http://127.0.0.1:18081`
+    pub fn base_url(&self) -> String {
+        match self.tls {
+            true => format!("https://{}:{}", self.url, self.port),
+            false => format!("http://{}:{}", self.url, self.port),
+        }
+    }
+
+    /// Sends a raw request to the given path of the node through its transport, retrying as set in its [`RpcConfig`](struct.RpcConfig.html)
+    pub(crate) fn call(&self, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        self.call_with_cancel(path, body, self.cancel.as_ref())
+    }
+
+    /// Same as [`call`](#method.call), stopped by the given token instead of the token of the node
+    pub(crate) fn call_with_cancel(&self, path: &str, body: &[u8], cancel: Option<&CancelToken>) -> Result<Vec<u8>, String> {
+        let cancelled = || cancel.is_some_and(CancelToken::is_cancelled);
+        let mut retry = 0;
+        loop {
+            if cancelled() {
+                return Err(CancelToken::error());
+            }
+            let result = match &self.transport {
+                Some(transport) => transport.call(self, path, body),
+                None => HttpTransport.call(self, path, body),
+            };
+            match result {
+                Err(_) if retry < self.config.retries && !cancelled() => {
+                    std::thread::sleep(self.config.retry_delay(retry));
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a JSON request to the given path of the node and parses the JSON response
+    pub(crate) fn call_json(&self, path: &str, body: &serde_json::Value) -> Result<serde_json::Value, String> {
+        let response = self.call(path, body.to_string().as_bytes())?;
+        Ok(serde_json::from_slice(&response).unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Returns the trust level that applies to a single response, taking the `untrusted` flag of the response into account
+    ///
+    /// Daemons set `untrusted` when the answer comes from their bootstrap daemon, in that case the answer is
+    /// treated as coming from an untrusted remote node regardless of the configured trust level
+    pub fn effective_trust(&self, untrusted: bool) -> TrustLevel {
+        if untrusted {
+            TrustLevel::UntrustedRemote
+        } else {
+            self.trust
+        }
+    }
+
+    /// Checks if the answer of the given operation should be cross-checked against another node before being used
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::{DaemonNode, RpcOperation, TrustLevel};
+    ///
+    /// let local = DaemonNode::new("127.0.0.1".to_string(), 18081, false);
+    /// assert!(!local.requires_cross_check(RpcOperation::FeeEstimate, false));
+    /// assert!(local.requires_cross_check(RpcOperation::FeeEstimate, true));
+    /// let public = DaemonNode::cake_wallet_default();
+    /// assert!(public.requires_cross_check(RpcOperation::OutputDistribution, false));
+    /// assert!(!public.requires_cross_check(RpcOperation::Height, false));
+    /// ```
+    pub fn requires_cross_check(&self, operation: RpcOperation, untrusted: bool) -> bool {
+        match self.effective_trust(untrusted) {
+            TrustLevel::Local => false,
+            TrustLevel::TrustedRemote => false,
+            TrustLevel::UntrustedRemote => matches!(
+                operation,
+                RpcOperation::FeeEstimate | RpcOperation::OutputDistribution | RpcOperation::Outputs
+            ),
+        }
+    }
+}
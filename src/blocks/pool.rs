@@ -0,0 +1,292 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::monitor::{ChainAlarm, ChainMonitor, MonitorHandle};
+use super::nodes::{CancelToken, DaemonNode, RpcPriority, TrustLevel};
+use super::rpcs::get_info;
+use super::transport::RpcTransport;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// How often calls waiting for a slot check their cancel token
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// NodeHealth is the last known state of a node of a [`NodePool`](struct.NodePool.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeHealth {
+    /// Base URL of the node
+    pub url: String,
+    /// False if the last health check or the last call failed, or if the node lags too much
+    pub healthy: bool,
+    /// Time the last successful health check took
+    pub latency: Option<Duration>,
+    /// Height reported by the last successful health check
+    pub height: Option<u64>,
+    /// Number of blocks the node is behind the highest node of the pool
+    pub lag: u64,
+    pub last_error: Option<String>,
+}
+
+struct PoolState {
+    health: Vec<NodeHealth>,
+    monitor: ChainMonitor,
+    max_lag: u64,
+}
+
+// Call slots of the pool, waiting calls are served by priority then in arrival order
+struct CallQueue {
+    max_concurrent: usize,
+    active: usize,
+    waiting: BTreeSet<(RpcPriority, u64)>,
+    next_ticket: u64,
+}
+
+// Slot held by a running call, released on drop
+struct CallSlot<'a> {
+    slots: &'a (Mutex<CallQueue>, Condvar),
+}
+
+impl Drop for CallSlot<'_> {
+    fn drop(&mut self) {
+        lock_ignoring_poison(&self.slots.0).active -= 1;
+        self.slots.1.notify_all();
+    }
+}
+
+/// NodePool spreads RPC calls over several daemon nodes and fails over to the next healthy node when one is unreachable
+///
+/// [`check_health`](#method.check_health) queries `get_info` on every node, measures its latency and height lag and
+/// compares the chains the nodes are on. Calls go to the healthy node with the lowest latency first, and are retried on
+/// the following nodes when the transport fails. A pool is used like a single node through [`node`](#method.node), so
+/// every RPC function of this module works with it. Clones of a pool share its nodes and their health.
+///
+/// A pool limited with [`with_max_concurrent`](#method.with_max_concurrent) runs at most that many calls at once, the
+/// others wait for a slot and the [`RpcPriority`](enum.RpcPriority.html) of their node decides which one runs next.
+/// Calls of a wallet UI stay responsive this way while the initial sync keeps the remaining slots busy.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{get_height, DaemonNode, NodePool};
+/// use std::time::Duration;
+///
+/// let pool = NodePool::new(vec![
+///     DaemonNode::cake_wallet_default(),
+///     DaemonNode::new("node.example.com".to_string(), 18089, true),
+/// ]).unwrap();
+/// let alarms = pool.check_health();
+/// println!("Alarms: {:?}", alarms);
+/// let _handle = pool.watch(Duration::from_secs(60), |alarms| println!("Alarms: {:?}", alarms));
+/// println!("Height: {}", get_height(pool.node()).unwrap());
+/// ```
+#[derive(Clone)]
+pub struct NodePool {
+    nodes: Arc<Vec<DaemonNode>>,
+    state: Arc<Mutex<PoolState>>,
+    slots: Arc<(Mutex<CallQueue>, Condvar)>,
+}
+
+impl NodePool {
+    /// Creates a pool of the given nodes, considered healthy until checked, a node more than 2 blocks behind is unhealthy
+    pub fn new(nodes: Vec<DaemonNode>) -> Result<NodePool, String> {
+        if nodes.is_empty() {
+            return Err("Node pool needs at least one node".to_string());
+        }
+        let health = nodes
+            .iter()
+            .map(|node| NodeHealth {
+                url: node.base_url(),
+                healthy: true,
+                latency: None,
+                height: None,
+                lag: 0,
+                last_error: None,
+            })
+            .collect();
+        Ok(NodePool {
+            nodes: Arc::new(nodes),
+            state: Arc::new(Mutex::new(PoolState {
+                health,
+                monitor: ChainMonitor::new(2),
+                max_lag: 2,
+            })),
+            slots: Arc::new((
+                Mutex::new(CallQueue {
+                    max_concurrent: usize::MAX,
+                    active: 0,
+                    waiting: BTreeSet::new(),
+                    next_ticket: 0,
+                }),
+                Condvar::new(),
+            )),
+        })
+    }
+
+    /// Sets how many blocks a node may lag behind the highest node of the pool and still be used
+    pub fn with_max_lag(self, max_lag: u64) -> NodePool {
+        {
+            let mut state = self.lock_state();
+            state.monitor = ChainMonitor::new(max_lag);
+            state.max_lag = max_lag;
+        }
+        self
+    }
+
+    /// Limits the number of calls running at once through the pool, which is unlimited by default
+    ///
+    /// Example:
+    /// ```no_run
+    /// use libmonero::blocks::{get_blocks_bin, get_height, DaemonNode, NodePool, RpcPriority};
+    ///
+    /// let pool = NodePool::new(vec![DaemonNode::cake_wallet_default()]).unwrap().with_max_concurrent(4);
+    /// let sync_node = pool.node().with_priority(RpcPriority::Background);
+    /// let sync = std::thread::spawn(move || get_blocks_bin(&[], 3000000, true, sync_node));
+    /// // Runs before the background calls still waiting for a slot
+    /// println!("Height: {}", get_height(pool.node()).unwrap());
+    /// sync.join().unwrap().unwrap();
+    /// ```
+    pub fn with_max_concurrent(self, max_concurrent: usize) -> NodePool {
+        lock_ignoring_poison(&self.slots.0).max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    /// Returns a node sending its calls through the pool, to be passed to any RPC function
+    ///
+    /// Its trust level is the lowest trust level of the pool nodes, since any of them may answer
+    pub fn node(&self) -> DaemonNode {
+        let trust = self.nodes.iter().map(|node| node.trust).max().unwrap_or(TrustLevel::UntrustedRemote);
+        DaemonNode::new("node-pool".to_string(), 0, false)
+            .with_trust(trust)
+            .with_transport(Arc::new(self.clone()))
+    }
+
+    /// Returns the last known health of every node, in pool order
+    pub fn health(&self) -> Vec<NodeHealth> {
+        self.lock_state().health.clone()
+    }
+
+    /// Queries every node with `get_info`, updates their health and returns the alarms of the chains they are on
+    pub fn check_health(&self) -> Vec<ChainAlarm> {
+        let results: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let start = Instant::now();
+                get_info(node.clone()).map(|info| (info, start.elapsed()))
+            })
+            .collect();
+        let best_height = results.iter().filter_map(|result| result.as_ref().ok()).map(|(info, _)| info.height).max().unwrap_or(0);
+        let mut state = self.lock_state();
+        for (index, result) in results.into_iter().enumerate() {
+            let url = self.nodes[index].base_url();
+            match result {
+                Ok((info, latency)) => {
+                    state.monitor.observe(&url, info.height, &info.top_block_hash);
+                    let max_lag = state.max_lag;
+                    let health = &mut state.health[index];
+                    health.lag = best_height - info.height;
+                    health.healthy = health.lag <= max_lag;
+                    health.latency = Some(latency);
+                    health.height = Some(info.height);
+                    health.last_error = None;
+                }
+                Err(e) => {
+                    state.monitor.observe_error(&url, &e);
+                    let health = &mut state.health[index];
+                    health.healthy = false;
+                    health.last_error = Some(e);
+                }
+            }
+        }
+        state.monitor.check()
+    }
+
+    /// Runs [`check_health`](#method.check_health) every `interval` on a background thread and calls `on_alarms`
+    /// whenever alarms are raised, until the returned handle is stopped or dropped
+    pub fn watch<F>(&self, interval: Duration, mut on_alarms: F) -> MonitorHandle
+    where
+        F: FnMut(&[ChainAlarm]) + Send + 'static,
+    {
+        let pool = self.clone();
+        MonitorHandle::spawn(interval, move || {
+            let alarms = pool.check_health();
+            if !alarms.is_empty() {
+                on_alarms(&alarms);
+            }
+        })
+    }
+
+    // Node indexes in the order calls try them: healthy nodes by latency (unchecked ones last), then unhealthy ones
+    fn call_order(&self) -> Vec<usize> {
+        let state = self.lock_state();
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        order.sort_by_key(|index| {
+            let health = &state.health[*index];
+            (!health.healthy, health.latency.unwrap_or(Duration::MAX))
+        });
+        order
+    }
+
+    // Waits until a slot is free and no call of a higher priority or arriving earlier is waiting
+    fn acquire_slot(&self, priority: RpcPriority, cancel: Option<&CancelToken>) -> Result<CallSlot<'_>, String> {
+        let (queue, available) = &*self.slots;
+        let mut state = lock_ignoring_poison(queue);
+        let ticket = (priority, state.next_ticket);
+        state.next_ticket += 1;
+        state.waiting.insert(ticket);
+        loop {
+            if cancel.is_some_and(CancelToken::is_cancelled) {
+                state.waiting.remove(&ticket);
+                available.notify_all();
+                return Err(CancelToken::error());
+            }
+            if state.active < state.max_concurrent && state.waiting.first() == Some(&ticket) {
+                state.waiting.remove(&ticket);
+                state.active += 1;
+                return Ok(CallSlot { slots: &self.slots });
+            }
+            state = available
+                .wait_timeout(state, CANCEL_POLL_INTERVAL)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, PoolState> {
+        lock_ignoring_poison(&self.state)
+    }
+}
+
+// A panic while holding a lock of the pool cannot leave its data inconsistent, so poisoning is ignored
+fn lock_ignoring_poison<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl RpcTransport for NodePool {
+    fn call(&self, node: &DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let cancel = node.cancel.as_ref();
+        let _slot = self.acquire_slot(node.priority, cancel)?;
+        let mut errors = Vec::new();
+        for index in self.call_order() {
+            match self.nodes[index].call_with_cancel(path, body, cancel.or(self.nodes[index].cancel.as_ref())) {
+                Ok(response) => return Ok(response),
+                // A cancelled call says nothing about the health of the node
+                Err(e) if cancel.is_some_and(CancelToken::is_cancelled) => return Err(e),
+                Err(e) => {
+                    let mut state = self.lock_state();
+                    state.health[index].healthy = false;
+                    state.health[index].last_error = Some(e.clone());
+                    errors.push(format!("{}: {}", self.nodes[index].base_url(), e));
+                }
+            }
+        }
+        Err(format!("Every node of the pool failed: {}", errors.join(", ")))
+    }
+}
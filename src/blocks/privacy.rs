@@ -0,0 +1,203 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::RawTx;
+use crate::keys::KeyImage;
+use std::collections::HashSet;
+
+/// InputPrivacy describes the ring of a single input of a transaction created by the wallet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputPrivacy {
+    /// Key image of the input
+    pub key_image: KeyImage,
+    /// Global output indices of the ring members, sorted ascending
+    pub ring: Vec<u64>,
+    /// Position of the real output in the ring
+    pub real_position: usize,
+    /// Age of the real output in blocks
+    pub real_age: u64,
+    /// Ages of the decoys in blocks, in ring order
+    pub decoy_ages: Vec<u64>,
+    /// Number of ring members younger than the real output, 0 means the real output is the youngest member
+    pub younger_decoys: usize,
+    /// Highest number of members shared with another ring, either of this transaction or of a previous one
+    pub max_shared_members: usize,
+    /// Whether the exact same ring was already used by another input
+    pub reused_ring: bool,
+}
+
+/// TxPrivacyReport summarizes the privacy characteristics of a transaction created by the wallet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxPrivacyReport {
+    /// Per input details, in transaction order
+    pub inputs: Vec<InputPrivacy>,
+    /// Number of outputs of the transaction
+    pub output_count: usize,
+    /// Position of the change output, if any
+    pub change_index: Option<usize>,
+}
+
+impl TxPrivacyReport {
+    /// Returns the indices of inputs whose real output is the youngest member of the ring
+    ///
+    /// This is the most common heuristic used to guess real spends, a spend that matches it is weaker than it looks
+    pub fn youngest_real_inputs(&self) -> Vec<usize> {
+        self.inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, input)| input.younger_decoys == 0 && !input.decoy_ages.is_empty())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the indices of inputs that share all or part of their ring with another ring
+    pub fn overlapping_inputs(&self) -> Vec<usize> {
+        self.inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, input)| input.reused_ring || input.max_shared_members > 1)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Converts the relative key offsets of an input (as stored in transactions) to absolute global output indices
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::absolute_offsets;
+///
+/// assert_eq!(absolute_offsets(&[100, 5, 20]).unwrap(), vec![100, 105, 125]);
+/// ```
+///
+/// Returns the absolute indices if succesfull
+/// Returns an error message if the offsets add up beyond the largest global index
+pub fn absolute_offsets(relative: &[u64]) -> Result<Vec<u64>, String> {
+    let mut sum = 0u64;
+    relative
+        .iter()
+        .map(|offset| {
+            sum = sum.checked_add(*offset).ok_or("Key offsets overflow the global output index")?;
+            Ok(sum)
+        })
+        .collect()
+}
+
+/// Converts sorted absolute global output indices to the relative key offsets stored in transactions
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::relative_offsets;
+///
+/// assert_eq!(relative_offsets(&[100, 105, 125]).unwrap(), vec![100, 5, 20]);
+/// ```
+///
+/// Returns the relative offsets if succesfull
+/// Returns an error message if the indices are not sorted in ascending order
+pub fn relative_offsets(absolute: &[u64]) -> Result<Vec<u64>, String> {
+    let mut previous = 0u64;
+    absolute
+        .iter()
+        .map(|index| {
+            let offset = index.checked_sub(previous).ok_or(format!("Global output index {} comes after the larger index {}", index, previous))?;
+            previous = *index;
+            Ok(offset)
+        })
+        .collect()
+}
+
+/// Builds a privacy report for a transaction created by the wallet
+///
+/// Arguments:
+/// - `tx`: the transaction
+/// - `real_positions`: position of the real output in the ring of each input, as known by the wallet
+/// - `change_index`: position of the change output, if any
+/// - `previous_rings`: rings (absolute global indices) of previous transactions of the wallet, to detect reused rings
+/// - `current_height`: current height of the blockchain
+/// - `output_height`: returns the height of the block containing the output with the given global index
+///
+/// Returns an error if the data does not match the transaction, its key offsets overflow or if a height lookup fails
+pub fn build_privacy_report<F>(
+    tx: &RawTx,
+    real_positions: &[usize],
+    change_index: Option<usize>,
+    previous_rings: &[Vec<u64>],
+    current_height: u64,
+    mut output_height: F,
+) -> Result<TxPrivacyReport, String>
+where
+    F: FnMut(u64) -> Result<u64, String>,
+{
+    if real_positions.len() != tx.vin.len() {
+        return Err(format!("Expected {} real positions, got {}", tx.vin.len(), real_positions.len()));
+    }
+    if let Some(change) = change_index {
+        if change >= tx.vout.len() {
+            return Err(format!("Change index {} is out of range", change));
+        }
+    }
+    let rings: Vec<Vec<u64>> = tx.vin.iter().map(|vin| absolute_offsets(&vin.key.key_offsets)).collect::<Result<_, _>>()?;
+    let ring_sets: Vec<HashSet<u64>> = rings.iter().map(|ring| ring.iter().copied().collect()).collect();
+    let previous_sets: Vec<HashSet<u64>> = previous_rings.iter().map(|ring| ring.iter().copied().collect()).collect();
+
+    let mut inputs = Vec::with_capacity(rings.len());
+    for (i, ring) in rings.iter().enumerate() {
+        let real_position = real_positions[i];
+        if real_position >= ring.len() {
+            return Err(format!("Real position {} is out of range for input {}", real_position, i));
+        }
+        let mut ages = Vec::with_capacity(ring.len());
+        for global_index in ring {
+            let height = output_height(*global_index)?;
+            ages.push(current_height.saturating_sub(height));
+        }
+        let real_age = ages[real_position];
+        let younger_decoys = ages.iter().filter(|age| **age < real_age).count();
+        let decoy_ages = ages
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| *position != real_position)
+            .map(|(_, age)| *age)
+            .collect();
+
+        let others = ring_sets
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, set)| set)
+            .chain(previous_sets.iter());
+        let mut max_shared_members = 0;
+        let mut reused_ring = false;
+        for other in others {
+            let shared = ring_sets[i].intersection(other).count();
+            max_shared_members = max_shared_members.max(shared);
+            if *other == ring_sets[i] {
+                reused_ring = true;
+            }
+        }
+
+        inputs.push(InputPrivacy {
+            key_image: tx.vin[i].key.k_image,
+            ring: ring.clone(),
+            real_position,
+            real_age,
+            decoy_ages,
+            younger_decoys,
+            max_shared_members,
+            reused_ring,
+        });
+    }
+
+    Ok(TxPrivacyReport {
+        inputs,
+        output_count: tx.vout.len(),
+        change_index,
+    })
+}
@@ -0,0 +1,95 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// ProxyConfig is a SOCKS5 proxy that RPC connections to a daemon go through, e.g. a Tor client
+///
+/// Host names of nodes are resolved by the proxy, so `.onion` nodes can be reached and no DNS request leaks
+/// to the local resolver. Tor isolates circuits by SOCKS credentials: giving each node its own username
+/// keeps their connections on separate circuits.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{DaemonNode, ProxyConfig};
+///
+/// let node = DaemonNode::new("xmrnodexxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx.onion".to_string(), 18089, false)
+///     .with_proxy(ProxyConfig::tor().with_auth("wallet-1", "isolation"));
+/// assert_eq!(node.proxy.unwrap().port, 9050);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<Zeroizing<String>>,
+}
+
+impl fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .finish()
+    }
+}
+
+impl ProxyConfig {
+    /// Creates a SOCKS5 proxy config without authentication
+    pub fn socks5(host: &str, port: u16) -> ProxyConfig {
+        ProxyConfig {
+            host: host.to_string(),
+            port,
+            username: None,
+            password: None,
+        }
+    }
+
+    /// Returns the default SOCKS port of a local Tor client, `127.0.0.1:9050`
+    pub fn tor() -> ProxyConfig {
+        ProxyConfig::socks5("127.0.0.1", 9050)
+    }
+
+    /// Sets the username and password sent to the proxy
+    pub fn with_auth(mut self, username: &str, password: &str) -> ProxyConfig {
+        self.username = Some(username.to_string());
+        self.password = Some(Zeroizing::new(password.to_string()));
+        self
+    }
+
+    /// Returns the proxy in the form used by the HTTP client
+    pub(crate) fn to_ureq(&self) -> Result<ureq::Proxy, String> {
+        ureq::Proxy::new(self.url("socks5")?.as_str()).map_err(|e| format!("Invalid proxy configuration: {}", e))
+    }
+
+    /// Returns the proxy in the form used by the async HTTP client, host names are resolved by the proxy
+    #[cfg(feature = "async")]
+    pub(crate) fn to_reqwest(&self) -> Result<reqwest::Proxy, String> {
+        reqwest::Proxy::all(self.url("socks5h")?.as_str()).map_err(|e| format!("Invalid proxy configuration: {}", e))
+    }
+
+    // Proxy URL with the given scheme and the credentials, if any
+    fn url(&self, scheme: &str) -> Result<Zeroizing<String>, String> {
+        let credentials = match (&self.username, &self.password) {
+            (Some(username), _) if username.contains(':') || username.contains('@') => {
+                return Err("Proxy username must not contain ':' or '@'".to_string())
+            }
+            (Some(username), Some(password)) => Zeroizing::new(format!("{}:{}@", username, password.as_str())),
+            (Some(username), None) => Zeroizing::new(format!("{}:@", username)),
+            (None, _) => Zeroizing::new(String::new()),
+        };
+        if self.host.contains(':') || self.host.contains('@') {
+            return Err("Proxy host must be a host name or an IPv4 address".to_string());
+        }
+        Ok(Zeroizing::new(format!("{}://{}{}:{}", scheme, credentials.as_str(), self.host, self.port)))
+    }
+}
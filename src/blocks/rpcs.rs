@@ -8,23 +8,11 @@
  *
  */
 
-use std::io::Cursor;
-
-use super::{block::{Block, BlockDetailsJSON, BlockHeader, EcdhInfo, Gen, KeyRawTx, MinerTxInfo, RawTx, RctSignatures, RctsigPrunable, TaggedKey, Target, Vin, VinRawTx, Vout, BPP, CLSAG}, nodes::DaemonNode};
-
-fn get_json_rpc_url(node: DaemonNode) -> String {
-    match node.tls {
-        true => format!("https://{}:{}/json_rpc", node.url, node.port),
-        false => format!("http://{}:{}/json_rpc", node.url, node.port),
-    }
-}
-
-fn get_rpc_url(node: DaemonNode) -> String {
-    match node.tls {
-        true => format!("https://{}:{}", node.url, node.port),
-        false => format!("http://{}:{}", node.url, node.port),
-    }
-}
+use super::{block::{AltChain, Block, BlockCompleteEntry, BlockDetailsJSON, BlockHash, BlockHeader, BlocksBinResult, BlockTemplate, DaemonInfo, EcdhInfo, FeeEstimate, Fork, Gen, HeightInfo, KeyImageStatus, KeyRawTx, MinerTxInfo, OutputDistribution, OutputEntry, OutputHistogramEntry, PoolTx, RawTx, RctSignatures, RctsigPrunable, SendTxResult, TaggedKey, Target, TxBacklogEntry, TxHash, TxStatus, Vin, VinRawTx, Vout, BPP, CLSAG}, nodes::DaemonNode};
+use super::transactions::{parse_transaction_blob, Transaction};
+use super::epee::{epee_from_bytes, epee_get, epee_to_bytes, EpeeObject, EpeeValue};
+use crate::keys::{Commitment, KeyImage, OneTimeKey};
+use crate::utils::{Context, Error, RpcError};
 
 /// Gets the block from the given daemon by its height
 /// Returns the block as a Block struct if succesfull
@@ -39,45 +27,42 @@ fn get_rpc_url(node: DaemonNode) -> String {
 /// println!("Block hash: {}", block.block_header.hash);
 /// ```
 pub fn get_block_from_height(block_height: u64, node: DaemonNode) -> Result<Block, String> {
-    let rpc_url = get_json_rpc_url(node);
-    let response = ureq::post(&rpc_url)
-        .set("Content-Type", "application/json")
-        .send_json(ureq::json!({
-            "jsonrpc": "2.0",
-            "id": "0",
-            "method": "get_block",
-            "params": {
-                "height": block_height
-            }
-        }));
+    get_block(&node, serde_json::json!({ "height": block_height }))
+}
+
+/// Gets the block with the given hash from the given daemon, alternative blocks included
+/// Blocks outside of the main chain have `orphan_status` set in their header
+/// Returns the block as a Block struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{get_block_by_hash, BlockHash, DaemonNode};
+///
+/// let hash = BlockHash::from_hex("0cf3a8e84f3d8bbe7ba3a8c0e0f6e4a2bd25b3bdf2b7a2dce1c2c8e8d1b0c1a5").unwrap();
+/// let block = get_block_by_hash(hash, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Block {} orphaned: {}", block.block_header.height, block.block_header.orphan_status);
+/// ```
+pub fn get_block_by_hash(hash: BlockHash, node: DaemonNode) -> Result<Block, String> {
+    get_block(&node, serde_json::json!({ "hash": hash.to_hex() }))
+}
+
+// Calls `get_block` with the given height or hash parameter
+fn get_block(node: &DaemonNode, params: serde_json::Value) -> Result<Block, String> {
+    let response = node.call_json("/json_rpc", &serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": "0",
+        "method": "get_block",
+        "params": params
+    }));
     if let Err(e) = response.as_ref() {
         return Err(format!("Error while getting the block from daemon: {}", e));
     }
-    let response:  serde_json::Value = response.unwrap().into_json().unwrap();
-    let block_header = BlockHeader {
-        block_size: response["result"]["block_header"]["block_size"].as_u64().unwrap(),
-        block_weight: response["result"]["block_header"]["block_weight"].as_u64().unwrap(),
-        cumulative_difficulty: response["result"]["block_header"]["cumulative_difficulty"].as_u64().unwrap(),
-        cumulative_difficulty_top64: response["result"]["block_header"]["cumulative_difficulty_top64"].as_u64().unwrap(),
-        depth: response["result"]["block_header"]["depth"].as_u64().unwrap(),
-        difficulty: response["result"]["block_header"]["difficulty"].as_u64().unwrap(),
-        difficulty_top64: response["result"]["block_header"]["difficulty_top64"].as_u64().unwrap(),
-        hash: response["result"]["block_header"]["hash"].as_str().unwrap().to_string(),
-        height: response["result"]["block_header"]["height"].as_u64().unwrap(),
-        long_term_weight: response["result"]["block_header"]["long_term_weight"].as_u64().unwrap(),
-        major_version: response["result"]["block_header"]["major_version"].as_u64().unwrap(),
-        miner_tx_hash: response["result"]["block_header"]["miner_tx_hash"].as_str().unwrap().to_string(),
-        minor_version: response["result"]["block_header"]["minor_version"].as_u64().unwrap(),
-        nonce: response["result"]["block_header"]["nonce"].as_u64().unwrap(),
-        num_txes: response["result"]["block_header"]["num_txes"].as_u64().unwrap(),
-        orphan_status: response["result"]["block_header"]["orphan_status"].as_bool().unwrap(),
-        pow_hash: response["result"]["block_header"]["pow_hash"].as_str().unwrap().to_string(),
-        prev_hash: response["result"]["block_header"]["prev_hash"].as_str().unwrap().to_string(),
-        reward: response["result"]["block_header"]["reward"].as_u64().unwrap(),
-        timestamp: response["result"]["block_header"]["timestamp"].as_u64().unwrap(),
-        wide_cumulative_difficulty: response["result"]["block_header"]["wide_cumulative_difficulty"].as_str().unwrap().to_string(),
-        wide_difficulty: response["result"]["block_header"]["wide_difficulty"].as_str().unwrap().to_string(),
-    };
+    let response = response.unwrap();
+    if response["result"].is_null() {
+        return Err("Error while parsing the block JSON".to_string());
+    }
+    let block_header = parse_block_header(&response["result"]["block_header"])?;
     let json = response["result"]["json"].as_str().unwrap().to_string();
     let parsed_json: serde_json::Value = serde_json::from_str(&json).unwrap_or(serde_json::Value::Null);
     if parsed_json.is_null() {
@@ -92,19 +77,7 @@ pub fn get_block_from_height(block_height: u64, node: DaemonNode) -> Result<Bloc
             }
         });
     };
-    let vout_array = parsed_json["miner_tx"]["vout"].as_array().unwrap();
-    let mut vout_vec: Vec<Vout> = Vec::new();
-    for vout in vout_array {
-        vout_vec.push(Vout {
-            amount: vout["amount"].as_u64().unwrap_or(0),
-            target: Target {
-                tagged_key: TaggedKey {
-                    key: vout["target"]["key"].as_str().unwrap_or("").to_string(),
-                    view_tag: vout["target"]["view_tag"].as_str().unwrap_or("").to_string(),
-                }
-            }
-        });
-    };
+    let vout_vec = parse_vouts(&parsed_json["miner_tx"]["vout"])?;
     Ok(Block {
         blob: response["result"]["blob"].as_str().unwrap_or("").to_string(),
         block_header,
@@ -113,14 +86,14 @@ pub fn get_block_from_height(block_height: u64, node: DaemonNode) -> Result<Bloc
             major_version: parsed_json["major_version"].as_u64().unwrap_or(0),
             minor_version: parsed_json["minor_version"].as_u64().unwrap_or(0),
             timestamp: parsed_json["timestamp"].as_u64().unwrap_or(0),
-            prev_id: parsed_json["prev_id"].as_str().unwrap_or("").to_string(),
+            prev_id: parse_bytes32(&parsed_json["prev_id"], BlockHash::from_hex, "prev_id")?,
             nonce: parsed_json["nonce"].as_u64().unwrap_or(0),
             miner_tx: MinerTxInfo {
                 version: parsed_json["miner_tx"]["version"].as_u64().unwrap_or(0),
                 unlock_time: parsed_json["miner_tx"]["unlock_time"].as_u64().unwrap_or(0),
                 vin: vin_vec,
                 vout: vout_vec,
-                extra: parsed_json["miner_tx"]["extra"].as_str().unwrap_or("").as_bytes().to_vec(),
+                extra: parse_extra(&parsed_json["miner_tx"]["extra"]),
                 rct_signatures: RctSignatures {
                     type_int: parsed_json["miner_tx"]["rct_signatures"]["type"].as_u64().unwrap_or(0),
                     txn_fee: 0,
@@ -128,15 +101,67 @@ pub fn get_block_from_height(block_height: u64, node: DaemonNode) -> Result<Bloc
                     out_pk: Vec::new(),
                 }
             },
-            tx_hashes: parsed_json["tx_hashes"].as_array().unwrap().to_vec().iter().map(|x| x.as_str().unwrap().to_string()).collect(),
+            tx_hashes: parse_bytes32_array(&parsed_json["tx_hashes"], TxHash::from_hex, "tx_hashes")?,
         },
-        miner_tx_hash: response["result"]["miner_tx_hash"].as_str().unwrap_or("").to_string(),
+        miner_tx_hash: parse_bytes32(&response["result"]["miner_tx_hash"], TxHash::from_hex, "miner_tx_hash")?,
         status: response["result"]["status"].as_str().unwrap_or("ERROR").to_string(),
         top_hash: response["result"]["top_hash"].as_str().unwrap_or("").to_string(),
         untrusted: response["result"]["untrusted"].as_bool().unwrap_or(false),
     })
 }
 
+/// Gets the header of the block at given height from the given daemon
+/// Returns the header as a BlockHeader struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_block_header_by_height;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let header = get_block_header_by_height(3000000, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Block timestamp: {}", header.timestamp);
+/// ```
+pub fn get_block_header_by_height(block_height: u64, node: DaemonNode) -> Result<BlockHeader, String> {
+    let response = node.call_json("/json_rpc", &serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": "0",
+        "method": "get_block_header_by_height",
+        "params": {
+            "height": block_height
+        }
+    }));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the block header from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if let Some(message) = response["error"]["message"].as_str() {
+        return Err(format!("Error while getting the block header from daemon: {}", message));
+    }
+    parse_block_header(&response["result"]["block_header"])
+}
+
+/// Gets the header of the block with the given hash from the given daemon, alternative blocks included
+/// Returns the header as a BlockHeader struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{get_block_header_by_hash, BlockHash, DaemonNode};
+///
+/// let hash = BlockHash::from_hex("0cf3a8e84f3d8bbe7ba3a8c0e0f6e4a2bd25b3bdf2b7a2dce1c2c8e8d1b0c1a5").unwrap();
+/// let header = get_block_header_by_hash(hash, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Block height: {}", header.height);
+/// ```
+pub fn get_block_header_by_hash(hash: BlockHash, node: DaemonNode) -> Result<BlockHeader, String> {
+    let result = call_json_rpc(&node, "get_block_header_by_hash", serde_json::json!({ "hash": hash.to_hex() }), "block header")?;
+    let header = parse_block_header(&result["block_header"])?;
+    if header.hash != hash {
+        return Err(format!("Daemon returned the block header of {} instead of {}", header.hash, hash));
+    }
+    Ok(header)
+}
+
 /// Gets the current height of the blockchain from the given daemon
 /// Returns the height as a u64 if succesfull
 /// Returns an error message if not succesfull
@@ -150,20 +175,45 @@ pub fn get_block_from_height(block_height: u64, node: DaemonNode) -> Result<Bloc
 /// println!("Current height: {}", height);
 /// ```
 pub fn get_height(node: DaemonNode) -> Result<u64, String> {
-    let rpc_url = get_rpc_url(node);
-    let reader = Cursor::new(Vec::new());
-    let response = ureq::get(format!("{}/get_height", &rpc_url).as_str())
-        .set("Content-Type", "application/json").send(reader);
+    let response = node.call_json("/get_height", &serde_json::json!({}));
     if let Err(e) = response.as_ref() {
         return Err(format!("Error while getting the block count (height) from daemon: {}", e));
     }
-    let response:  serde_json::Value = response.unwrap().into_json().unwrap_or(serde_json::Value::Null);
+    let response = response.unwrap();
     if response.is_null() {
         return Err("Error while parsing the block count (height) JSON".to_string());
     }
     Ok(response["height"].as_u64().unwrap_or(0))
 }
 
+/// Gets the current height, top block hash and `untrusted` flag from the given daemon
+/// Returns a HeightInfo struct if succesfull
+/// Returns an error message if not succesfull
+/// 
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_height_info;
+/// use libmonero::blocks::DaemonNode;
+/// 
+/// let info = get_height_info(DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Current height: {}, top hash: {}, untrusted: {}", info.height, info.hash, info.untrusted);
+/// ```
+pub fn get_height_info(node: DaemonNode) -> Result<HeightInfo, String> {
+    let response = node.call_json("/get_height", &serde_json::json!({}));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the block count (height) from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if response.is_null() {
+        return Err("Error while parsing the block count (height) JSON".to_string());
+    }
+    Ok(HeightInfo {
+        hash: parse_bytes32(&response["hash"], BlockHash::from_hex, "hash")?,
+        height: response["height"].as_u64().unwrap_or(0),
+        untrusted: response["untrusted"].as_bool().unwrap_or(false),
+    })
+}
+
 /// Gets the transaction from the given daemon by its hash
 /// Returns the transaction as a RawTx struct if succesfull
 /// Returns an error message if not succesfull
@@ -171,24 +221,21 @@ pub fn get_height(node: DaemonNode) -> Result<u64, String> {
 /// Example:
 /// ```
 /// use libmonero::blocks::get_transaction_from_hash;
-/// use libmonero::blocks::DaemonNode;
+/// use libmonero::blocks::{DaemonNode, TxHash};
 /// 
-/// let tx_hash = "e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11";
-/// let tx = get_transaction_from_hash(tx_hash.to_string(), DaemonNode::cake_wallet_default()).unwrap();
+/// let tx_hash = TxHash::from_hex("e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11").unwrap();
+/// let tx = get_transaction_from_hash(tx_hash, DaemonNode::cake_wallet_default()).unwrap();
 /// println!("Unlock time: {}", tx.unlock_time);
 /// ```
-pub fn get_transaction_from_hash(hash: String, node: DaemonNode) -> Result<RawTx, String> {
-    let rpc_url = format!("{}/get_transactions", get_rpc_url(node));
-    let response = ureq::post(&rpc_url)
-        .set("Content-Type", "application/json")
-        .send_json(ureq::json!({
-            "txs_hashes": [hash],
-            "decode_as_json": true,
-        }));
+pub fn get_transaction_from_hash(hash: TxHash, node: DaemonNode) -> Result<RawTx, String> {
+    let response = node.call_json("/get_transactions", &serde_json::json!({
+        "txs_hashes": [hash.to_hex()],
+        "decode_as_json": true,
+    }));
     if let Err(e) = response.as_ref() {
         return Err(format!("Error while getting the transaction from daemon: {}", e));
     }
-    let response: serde_json::Value = response.unwrap().into_json().unwrap_or(serde_json::Value::Null);
+    let response = response.unwrap();
     if response.is_null() {
         return Err("Error while parsing the transaction JSON".to_string());
     }
@@ -196,40 +243,1051 @@ pub fn get_transaction_from_hash(hash: String, node: DaemonNode) -> Result<RawTx
     if json_part.is_empty() {
         return Err("Error while getting the as_json part".to_string());
     }
-    let json_final = serde_json::from_str(&json_part).unwrap_or(serde_json::Value::Null);
-    if json_final.is_null() {
-        return Err("Error while parsing the as_json part".to_string());
+    let mut tx = parse_transaction_json(&json_part)?;
+    tx.untrusted = response["untrusted"].as_bool().unwrap_or(false);
+    Ok(tx)
+}
+
+/// Gets the transaction from the given daemon by its hash, in binary form, and parses it with
+/// [`parse_transaction_blob`](fn.parse_transaction_blob.html), which keeps every field the JSON form loses
+///
+/// The hash of the returned transaction is checked, a daemon can't return another transaction than the requested one.
+/// Returns the transaction as a Transaction struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_transaction;
+/// use libmonero::blocks::{DaemonNode, TxHash};
+///
+/// let tx_hash = TxHash::from_hex("e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11").unwrap();
+/// let tx = get_transaction(tx_hash, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Outputs: {}, payment ID: {:?}", tx.prefix.outputs.len(), tx.payment_id());
+/// ```
+pub fn get_transaction(hash: TxHash, node: DaemonNode) -> Result<Transaction, String> {
+    let response = node.call_json("/get_transactions", &serde_json::json!({
+        "txs_hashes": [hash.to_hex()],
+        "decode_as_json": false,
+    }));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the transaction from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if response["status"].as_str() != Some("OK") {
+        return Err(format!("Error while getting the transaction from daemon: {}", response["status"].as_str().unwrap_or("invalid response")));
+    }
+    let tx = &response["txs"][0];
+    // Pruned daemons split the blob in its pruned and prunable parts
+    let blob_hex = match tx["as_hex"].as_str() {
+        Some(hex) if !hex.is_empty() => hex.to_string(),
+        _ => format!("{}{}", tx["pruned_as_hex"].as_str().unwrap_or(""), tx["prunable_as_hex"].as_str().unwrap_or("")),
+    };
+    if blob_hex.is_empty() {
+        return Err("Error while getting the transaction from daemon: transaction not found".to_string());
+    }
+    let blob = hex::decode(blob_hex).map_err(|_| "Error while parsing the transaction: invalid hex".to_string())?;
+    let parsed = parse_transaction_blob(&blob)?;
+    // Pruned transactions are checked with the prunable hash given by the daemon
+    let parsed_hash = match (parsed.hash(), tx["prunable_hash"].as_str().and_then(|hash| TxHash::from_hex(hash).ok())) {
+        (Ok(parsed_hash), _) => parsed_hash,
+        (Err(_), Some(prunable_hash)) => parsed.hash_with_prunable_hash(prunable_hash.as_bytes()),
+        (Err(e), None) => return Err(format!("Error while checking the transaction hash: {}", e)),
+    };
+    if parsed_hash != hash {
+        return Err(format!("Error while getting the transaction from daemon: daemon returned transaction {} instead of {}", parsed_hash, hash));
+    }
+    Ok(parsed)
+}
+
+/// Gets the status (pool, mined, confirmations, double spend) of a transaction from the given daemon by its hash
+/// Returns the status as a TxStatus struct if succesfull, `found` is false if the daemon does not know the transaction
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_transaction_status;
+/// use libmonero::blocks::{DaemonNode, TxHash};
+///
+/// let tx_hash = TxHash::from_hex("e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11").unwrap();
+/// let status = get_transaction_status(tx_hash, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("In pool: {}, confirmations: {}", status.in_pool, status.confirmations);
+/// ```
+pub fn get_transaction_status(hash: TxHash, node: DaemonNode) -> Result<TxStatus, String> {
+    let response = node.call_json("/get_transactions", &serde_json::json!({
+        "txs_hashes": [hash.to_hex()],
+    }));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the transaction status from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if response.is_null() {
+        return Err("Error while parsing the transaction status JSON".to_string());
+    }
+    let untrusted = response["untrusted"].as_bool().unwrap_or(false);
+    let tx = match response["txs"].as_array().and_then(|txs| txs.first()) {
+        Some(tx) => tx,
+        None => {
+            return Ok(TxStatus {
+                untrusted,
+                ..TxStatus::default()
+            })
+        }
+    };
+    let in_pool = tx["in_pool"].as_bool().unwrap_or(false);
+    Ok(TxStatus {
+        found: true,
+        in_pool,
+        block_height: if in_pool { 0 } else { tx["block_height"].as_u64().unwrap_or(0) },
+        confirmations: if in_pool { 0 } else { tx["confirmations"].as_u64().unwrap_or(0) },
+        double_spend_seen: tx["double_spend_seen"].as_bool().unwrap_or(false),
+        untrusted,
+    })
+}
+
+/// Gets the weight, fee and time in pool of every transaction in the daemon's transaction pool
+/// Returns the entries as a vector of TxBacklogEntry structs if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_txpool_backlog;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let backlog = get_txpool_backlog(DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Transactions in pool: {}", backlog.len());
+/// ```
+pub fn get_txpool_backlog(node: DaemonNode) -> Result<Vec<TxBacklogEntry>, String> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": "0",
+        "method": "get_txpool_backlog",
+    });
+    let response = node.call("/json_rpc", body.to_string().as_bytes());
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the transaction pool backlog from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    // The backlog is a binary blob inside a JSON string, which is usually not valid UTF-8, so it is extracted by hand
+    let blob = match extract_json_blob(&response, "backlog") {
+        Some(blob) => blob,
+        None => {
+            let response: serde_json::Value = serde_json::from_slice(&response).unwrap_or(serde_json::Value::Null);
+            if response["result"]["status"].as_str() == Some("OK") {
+                // Empty pools have no backlog field
+                return Ok(Vec::new());
+            }
+            return Err("Error while parsing the transaction pool backlog JSON".to_string());
+        }
+    };
+    if blob.len() % 24 != 0 {
+        return Err("Error while parsing the transaction pool backlog: invalid blob size".to_string());
+    }
+    Ok(blob
+        .chunks(24)
+        .map(|entry| TxBacklogEntry {
+            weight: u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+            fee: u64::from_le_bytes(entry[8..16].try_into().unwrap()),
+            time_in_pool: u64::from_le_bytes(entry[16..24].try_into().unwrap()),
+        })
+        .collect())
+}
+
+/// Gets general information (height, sync state, network, connections, pool size...) from the given daemon
+/// Returns the information as a DaemonInfo struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_info;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let info = get_info(DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Height: {}, network: {}, synchronized: {}", info.height, info.nettype, info.synchronized);
+/// ```
+pub fn get_info(node: DaemonNode) -> Result<DaemonInfo, String> {
+    let info = call_json_rpc(&node, "get_info", serde_json::json!({}), "daemon information")?;
+    Ok(DaemonInfo {
+        adjusted_time: info["adjusted_time"].as_u64().unwrap_or(0),
+        alt_blocks_count: info["alt_blocks_count"].as_u64().unwrap_or(0),
+        block_size_limit: info["block_size_limit"].as_u64().unwrap_or(0),
+        block_size_median: info["block_size_median"].as_u64().unwrap_or(0),
+        block_weight_limit: info["block_weight_limit"].as_u64().unwrap_or(0),
+        block_weight_median: info["block_weight_median"].as_u64().unwrap_or(0),
+        busy_syncing: info["busy_syncing"].as_bool().unwrap_or(false),
+        cumulative_difficulty: info["cumulative_difficulty"].as_u64().unwrap_or(0),
+        database_size: info["database_size"].as_u64().unwrap_or(0),
+        difficulty: info["difficulty"].as_u64().unwrap_or(0),
+        free_space: info["free_space"].as_u64().unwrap_or(0),
+        grey_peerlist_size: info["grey_peerlist_size"].as_u64().unwrap_or(0),
+        height: info["height"].as_u64().unwrap_or(0),
+        incoming_connections_count: info["incoming_connections_count"].as_u64().unwrap_or(0),
+        nettype: info["nettype"].as_str().unwrap_or("").to_string(),
+        offline: info["offline"].as_bool().unwrap_or(false),
+        outgoing_connections_count: info["outgoing_connections_count"].as_u64().unwrap_or(0),
+        restricted: info["restricted"].as_bool().unwrap_or(false),
+        start_time: info["start_time"].as_u64().unwrap_or(0),
+        synchronized: info["synchronized"].as_bool().unwrap_or(false),
+        target_height: info["target_height"].as_u64().unwrap_or(0),
+        top_block_hash: parse_bytes32(&info["top_block_hash"], BlockHash::from_hex, "top_block_hash")?,
+        tx_count: info["tx_count"].as_u64().unwrap_or(0),
+        tx_pool_size: info["tx_pool_size"].as_u64().unwrap_or(0),
+        untrusted: info["untrusted"].as_bool().unwrap_or(false),
+        update_available: info["update_available"].as_bool().unwrap_or(false),
+        version: info["version"].as_str().unwrap_or("").to_string(),
+        white_peerlist_size: info["white_peerlist_size"].as_u64().unwrap_or(0),
+    })
+}
+
+/// Gets the header of the last block from the given daemon
+/// Returns the header as a BlockHeader struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_last_block_header;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let header = get_last_block_header(DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Top block: {} at height {}", header.hash, header.height);
+/// ```
+pub fn get_last_block_header(node: DaemonNode) -> Result<BlockHeader, String> {
+    let result = call_json_rpc(&node, "get_last_block_header", serde_json::json!({}), "last block header")?;
+    parse_block_header(&result["block_header"])
+}
+
+/// Gets the headers of the blocks from `start_height` to `end_height`, both included, from the given daemon
+/// Large ranges are split into several requests as restricted daemons serve at most 1000 headers per call
+/// Returns the headers ordered by height as a Vec<BlockHeader> if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_block_headers_range;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let headers = get_block_headers_range(3000000, 3000099, DaemonNode::cake_wallet_default()).unwrap();
+/// for header in headers {
+///     println!("Block {} mined at {} with reward {}", header.height, header.timestamp, header.reward);
+/// }
+/// ```
+pub fn get_block_headers_range(start_height: u64, end_height: u64, node: DaemonNode) -> Result<Vec<BlockHeader>, String> {
+    if start_height > end_height {
+        return Err(format!("Invalid block range: start height {} is above end height {}", start_height, end_height));
+    }
+    let mut headers = Vec::new();
+    let mut chunk_start = start_height;
+    loop {
+        let chunk_end = end_height.min(chunk_start.saturating_add(MAX_HEADERS_PER_REQUEST - 1));
+        let result = call_json_rpc(&node, "get_block_headers_range", serde_json::json!({
+            "start_height": chunk_start,
+            "end_height": chunk_end
+        }), "block headers")?;
+        let chunk = match result["headers"].as_array() {
+            Some(chunk) => chunk,
+            None => return Err("Error while parsing the block headers JSON".to_string()),
+        };
+        if chunk.len() as u64 != chunk_end - chunk_start + 1 {
+            return Err(format!("Daemon returned {} block headers, expected {}", chunk.len(), chunk_end - chunk_start + 1));
+        }
+        for (height, header) in (chunk_start..=chunk_end).zip(chunk) {
+            let header = parse_block_header(header)?;
+            if header.height != height {
+                return Err(format!("Daemon returned the block header at height {} instead of {}", header.height, height));
+            }
+            headers.push(header);
+        }
+        if chunk_end == end_height {
+            return Ok(headers);
+        }
+        chunk_start = chunk_end + 1;
+    }
+}
+
+/// Gets the alternative chains the given daemon knows of, blocks it received that lost against the main chain
+/// Needs an unrestricted daemon, restricted daemons refuse the call
+/// Returns the chains as a Vec<AltChain> if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_alternate_chains;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let node = DaemonNode::new("127.0.0.1".to_string(), 18081, false);
+/// for chain in get_alternate_chains(node).unwrap() {
+///     println!("{} blocks forked at height {}", chain.length, chain.fork_height());
+/// }
+/// ```
+pub fn get_alternate_chains(node: DaemonNode) -> Result<Vec<AltChain>, String> {
+    let result = call_json_rpc(&node, "get_alternate_chains", serde_json::json!({}), "alternate chains")?;
+    if let Some(status) = result["status"].as_str().filter(|status| *status != "OK") {
+        return Err(format!("Error while getting the alternate chains from daemon: {}", status));
+    }
+    let chains = match &result["chains"] {
+        // Daemons leave the field out when there is no alternative chain
+        serde_json::Value::Null => return Ok(Vec::new()),
+        serde_json::Value::Array(chains) => chains,
+        _ => return Err("Error while parsing the alternate chains JSON".to_string()),
+    };
+    let mut parsed = Vec::with_capacity(chains.len());
+    for chain in chains {
+        let alt_chain = AltChain {
+            block_hash: parse_bytes32(&chain["block_hash"], BlockHash::from_hex, "block_hash")?,
+            block_hashes: parse_bytes32_array(&chain["block_hashes"], BlockHash::from_hex, "block_hashes")?,
+            height: chain["height"].as_u64().unwrap_or(0),
+            length: chain["length"].as_u64().unwrap_or(0),
+            difficulty: chain["difficulty"].as_u64().unwrap_or(0),
+            wide_difficulty: chain["wide_difficulty"].as_str().unwrap_or("").to_string(),
+            main_chain_parent_block: parse_bytes32(&chain["main_chain_parent_block"], BlockHash::from_hex, "main_chain_parent_block")?,
+        };
+        if alt_chain.length == 0 || alt_chain.length > alt_chain.height || alt_chain.block_hashes.len() as u64 != alt_chain.length {
+            return Err(format!("Invalid alternate chain of {} blocks ending at height {}", alt_chain.length, alt_chain.height));
+        }
+        parsed.push(alt_chain);
+    }
+    Ok(parsed)
+}
+
+/// Gets the headers of both sides of the fork of the given alternative chain from the given daemon
+/// The main side covers the heights of the alternative chain the main chain reached
+/// Returns both sides as a Fork struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{get_alternate_chains, get_fork};
+/// use libmonero::blocks::DaemonNode;
+///
+/// let node = DaemonNode::new("127.0.0.1".to_string(), 18081, false);
+/// for chain in get_alternate_chains(node.clone()).unwrap() {
+///     let fork = get_fork(&chain, node.clone()).unwrap();
+///     for (alternative, main) in fork.alternative.iter().zip(&fork.main) {
+///         println!("{}: {} lost against {}", alternative.height, alternative.hash, main.hash);
+///     }
+/// }
+/// ```
+pub fn get_fork(chain: &AltChain, node: DaemonNode) -> Result<Fork, String> {
+    let mut alternative = Vec::with_capacity(chain.block_hashes.len());
+    // The daemon lists the blocks of the chain from its top down
+    for hash in chain.block_hashes.iter().rev() {
+        alternative.push(get_block_header_by_hash(*hash, node.clone())?);
+    }
+    let fork_height = chain.fork_height();
+    for (height, header) in (fork_height..).zip(&alternative) {
+        if header.height != height {
+            return Err(format!("Alternative block {} is at height {} instead of {}", header.hash, header.height, height));
+        }
+    }
+    if alternative.first().map(|header| header.prev_hash) != Some(chain.main_chain_parent_block) {
+        return Err("Alternative chain does not start on its main chain parent block".to_string());
+    }
+    let top_height = get_block_count(node.clone())?.saturating_sub(1);
+    let main = match chain.height.min(top_height) {
+        end_height if end_height >= fork_height => get_block_headers_range(fork_height, end_height, node)?,
+        _ => Vec::new(),
+    };
+    Ok(Fork {
+        fork_height,
+        parent: chain.main_chain_parent_block,
+        alternative,
+        main,
+    })
+}
+
+/// Gets the number of blocks in the chain from the given daemon, which is the height of the next block
+/// Returns the count as a u64 if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_block_count;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let count = get_block_count(DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Block count: {}", count);
+/// ```
+pub fn get_block_count(node: DaemonNode) -> Result<u64, String> {
+    let result = call_json_rpc(&node, "get_block_count", serde_json::json!({}), "block count")?;
+    result["count"].as_u64().ok_or("Error while parsing the block count JSON".to_string())
+}
+
+/// Gets the fee estimate of the given daemon, `grace_blocks` is the number of blocks the estimate should stay valid for
+/// Returns the estimate as a FeeEstimate struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{get_fee_estimate, DaemonNode, FeePriority};
+///
+/// let estimate = get_fee_estimate(10, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Normal fee per byte: {}", estimate.fee_per_byte(FeePriority::Normal));
+/// ```
+pub fn get_fee_estimate(grace_blocks: u64, node: DaemonNode) -> Result<FeeEstimate, String> {
+    let result = call_json_rpc(&node, "get_fee_estimate", serde_json::json!({ "grace_blocks": grace_blocks }), "fee estimate")?;
+    Ok(FeeEstimate {
+        fee: result["fee"].as_u64().ok_or("Error while parsing the fee estimate JSON")?,
+        fees: result["fees"].as_array().map(|fees| fees.iter().filter_map(|fee| fee.as_u64()).collect()).unwrap_or_default(),
+        quantization_mask: result["quantization_mask"].as_u64().unwrap_or(1),
+        untrusted: result["untrusted"].as_bool().unwrap_or(false),
+    })
+}
+
+/// Broadcasts a signed transaction through the given daemon, `tx_as_hex` is the serialized transaction in hex
+/// If `do_not_relay` is true, the daemon only adds the transaction to its own pool
+/// Returns the parsed daemon answer as a SendTxResult struct, whose `accepted` field tells if the transaction was accepted
+/// Returns an error message if the daemon could not be reached or its answer could not be parsed
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::send_raw_transaction;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let result = send_raw_transaction("02000102...".to_string(), false, DaemonNode::cake_wallet_default()).unwrap();
+/// if !result.accepted {
+///     println!("Rejected: {:?}", result.rejection_reasons());
+/// }
+/// ```
+pub fn send_raw_transaction(tx_as_hex: String, do_not_relay: bool, node: DaemonNode) -> Result<SendTxResult, String> {
+    let response = node.call_json("/send_raw_transaction", &serde_json::json!({
+        "tx_as_hex": tx_as_hex,
+        "do_not_relay": do_not_relay,
+    }));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while sending the transaction to daemon: {}", e));
+    }
+    let response = response.unwrap();
+    let status = match response["status"].as_str() {
+        Some(status) => status.to_string(),
+        None => return Err("Error while parsing the send transaction JSON".to_string()),
+    };
+    let flag = |name: &str| response[name].as_bool().unwrap_or(false);
+    Ok(SendTxResult {
+        accepted: status == "OK",
+        reason: response["reason"].as_str().unwrap_or("").to_string(),
+        double_spend: flag("double_spend"),
+        fee_too_low: flag("fee_too_low"),
+        invalid_input: flag("invalid_input"),
+        invalid_output: flag("invalid_output"),
+        low_mixin: flag("low_mixin"),
+        nonzero_unlock_time: flag("nonzero_unlock_time"),
+        not_relayed: flag("not_relayed"),
+        overspend: flag("overspend"),
+        sanity_check_failed: flag("sanity_check_failed"),
+        too_big: flag("too_big"),
+        too_few_outputs: flag("too_few_outputs"),
+        tx_extra_too_big: flag("tx_extra_too_big"),
+        untrusted: flag("untrusted"),
+        status,
+    })
+}
+
+/// Gets every transaction in the daemon's pool, decoded
+/// Returns the transactions as a vector of PoolTx structs if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_transaction_pool;
+/// use libmonero::blocks::DaemonNode;
+///
+/// for pool_tx in get_transaction_pool(DaemonNode::cake_wallet_default()).unwrap() {
+///     println!("{}: {} outputs, fee {}", pool_tx.id_hash, pool_tx.tx.vout.len(), pool_tx.fee);
+/// }
+/// ```
+pub fn get_transaction_pool(node: DaemonNode) -> Result<Vec<PoolTx>, String> {
+    let response = node.call_json("/get_transaction_pool", &serde_json::json!({}));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the transaction pool from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if response["status"].as_str() != Some("OK") {
+        return Err("Error while parsing the transaction pool JSON".to_string());
+    }
+    let transactions = match response["transactions"].as_array() {
+        Some(transactions) => transactions,
+        // Empty pools have no transactions field
+        None => return Ok(Vec::new()),
+    };
+    let mut pool = Vec::with_capacity(transactions.len());
+    for transaction in transactions {
+        let tx_json = transaction["tx_json"].as_str().ok_or("Error while getting the tx_json part")?;
+        let mut tx = parse_transaction_json(tx_json)?;
+        tx.untrusted = response["untrusted"].as_bool().unwrap_or(false);
+        pool.push(PoolTx {
+            id_hash: parse_bytes32(&transaction["id_hash"], TxHash::from_hex, "id_hash")?,
+            tx,
+            blob_size: transaction["blob_size"].as_u64().unwrap_or(0),
+            weight: transaction["weight"].as_u64().unwrap_or(0),
+            fee: transaction["fee"].as_u64().unwrap_or(0),
+            receive_time: transaction["receive_time"].as_u64().unwrap_or(0),
+            last_relayed_time: transaction["last_relayed_time"].as_u64().unwrap_or(0),
+            relayed: transaction["relayed"].as_bool().unwrap_or(false),
+            double_spend_seen: transaction["double_spend_seen"].as_bool().unwrap_or(false),
+            kept_by_block: transaction["kept_by_block"].as_bool().unwrap_or(false),
+        });
+    }
+    Ok(pool)
+}
+
+/// Gets the hashes of every transaction in the daemon's pool
+/// Returns the hashes as a vector of Strings if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_transaction_pool_hashes;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let hashes = get_transaction_pool_hashes(DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Transactions in pool: {}", hashes.len());
+/// ```
+pub fn get_transaction_pool_hashes(node: DaemonNode) -> Result<Vec<TxHash>, String> {
+    let response = node.call_json("/get_transaction_pool_hashes", &serde_json::json!({}));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the transaction pool hashes from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if response["status"].as_str() != Some("OK") {
+        return Err("Error while parsing the transaction pool hashes JSON".to_string());
+    }
+    if response["tx_hashes"].is_null() {
+        // Empty pools have no tx_hashes field
+        return Ok(Vec::new());
+    }
+    parse_bytes32_array(&response["tx_hashes"], TxHash::from_hex, "tx_hashes")
+}
+
+/// Gets the RingCT outputs with given global indices from the given daemon, as needed to build rings
+/// Returns the outputs as a vector of OutputEntry structs, in the order of the indices, if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_outs;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let outs = get_outs(&[90000000, 90000001], DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Output key: {}, unlocked: {}", outs[0].key, outs[0].unlocked);
+/// ```
+pub fn get_outs(indices: &[u64], node: DaemonNode) -> Result<Vec<OutputEntry>, String> {
+    get_outs_by_amount(0, indices, node)
+}
+
+/// Gets the outputs of the given amount with given indices among the outputs of that amount from the given daemon, as
+/// needed to build the rings of pre-RingCT outputs, which can only be mixed with outputs of the same amount
+/// RingCT outputs have amount 0, see [`get_outs`](fn.get_outs.html). The mask of pre-RingCT outputs is the commitment
+/// to their clear amount, see [`zero_commit`](../keys/fn.zero_commit.html)
+/// Returns the outputs as a vector of OutputEntry structs, in the order of the indices, if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_outs_by_amount;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let outs = get_outs_by_amount(20000000000, &[0, 1], DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Output key: {}, unlocked: {}", outs[0].key, outs[0].unlocked);
+/// ```
+pub fn get_outs_by_amount(amount: u64, indices: &[u64], node: DaemonNode) -> Result<Vec<OutputEntry>, String> {
+    let outputs: Vec<serde_json::Value> = indices.iter().map(|index| serde_json::json!({ "amount": amount, "index": index })).collect();
+    let response = node.call_json("/get_outs", &serde_json::json!({
+        "outputs": outputs,
+        "get_txid": true,
+    }));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the outputs from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if response["status"].as_str() != Some("OK") {
+        return Err(format!("Error while getting the outputs from daemon: {}", response["status"].as_str().unwrap_or("invalid response")));
+    }
+    let outs = response["outs"].as_array().ok_or("Error while parsing the outputs JSON")?;
+    if outs.len() != indices.len() {
+        return Err(format!("Error while parsing the outputs JSON: asked for {} outputs, got {}", indices.len(), outs.len()));
+    }
+    outs.iter()
+        .map(|out| {
+            Ok(OutputEntry {
+                height: out["height"].as_u64().unwrap_or(0),
+                key: parse_bytes32(&out["key"], OneTimeKey::from_hex, "key")?,
+                mask: parse_bytes32(&out["mask"], Commitment::from_hex, "mask")?,
+                txid: parse_bytes32(&out["txid"], TxHash::from_hex, "txid")?,
+                unlocked: out["unlocked"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// Same as [`get_outs`](fn.get_outs.html) through the binary `get_outs.bin` endpoint, which is much faster for the
+/// large batches of ring members wallets fetch
+/// Returns the outputs as a vector of OutputEntry structs, in the order of the indices, if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_outs_bin;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let outs = get_outs_bin(&[90000000, 90000001], DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Output key: {}, unlocked: {}", outs[0].key, outs[0].unlocked);
+/// ```
+pub fn get_outs_bin(indices: &[u64], node: DaemonNode) -> Result<Vec<OutputEntry>, String> {
+    let outputs = indices
+        .iter()
+        .map(|index| EpeeValue::Object(vec![("amount".to_string(), EpeeValue::U64(0)), ("index".to_string(), EpeeValue::U64(*index))]))
+        .collect();
+    let request = epee_to_bytes(&vec![
+        ("outputs".to_string(), EpeeValue::Array(EpeeValue::Object(Vec::new()).type_code(), outputs)),
+        ("get_txid".to_string(), EpeeValue::Bool(true)),
+    ])?;
+    let response = call_epee(&node, "/get_outs.bin", &request, "outputs")?;
+    let outs = epee_get(&response, "outs").and_then(|outs| outs.as_array()).unwrap_or(&[]);
+    if outs.len() != indices.len() {
+        return Err(format!("Error while parsing the outputs: asked for {} outputs, got {}", indices.len(), outs.len()));
+    }
+    outs.iter()
+        .map(|out| {
+            let out = out.as_object().ok_or("Error while parsing the outputs: invalid output")?;
+            let field = |name: &str| match epee_get(out, name).and_then(|value| value.as_blob()).map(<[u8; 32]>::try_from) {
+                Some(Ok(bytes)) => Ok(bytes),
+                _ => Err(format!("Error while parsing the outputs: invalid {}", name)),
+            };
+            Ok(OutputEntry {
+                height: epee_get(out, "height").and_then(|height| height.as_u64()).unwrap_or(0),
+                key: OneTimeKey::from_bytes(field("key")?),
+                mask: Commitment::from_bytes(field("mask")?),
+                txid: TxHash::from_bytes(field("txid")?),
+                unlocked: epee_get(out, "unlocked").and_then(|unlocked| unlocked.as_bool()).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// Gets a batch of blocks with their transactions and output indices through the binary `get_blocks.bin` endpoint,
+/// which is what wallets use to sync
+///
+/// `block_ids` is the wallet's sparse chain history: hashes of known blocks from the most recent one, ending with the
+/// genesis block hash. The daemon starts the batch after the most recent of them still in its chain, or at
+/// `start_height` if that is higher. Pruned transactions lack their signatures, which scanning does not need
+/// Returns the blocks as a BlocksBinResult struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_blocks_bin;
+/// use libmonero::blocks::{BlockHash, DaemonNode};
+///
+/// let genesis = BlockHash::from_hex("418015bb9ae982a1975da7d79277c2705727a56894ba0fb246adaabb1f4632e3").unwrap();
+/// let batch = get_blocks_bin(&[genesis], 3000000, true, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Got {} blocks from height {}", batch.blocks.len(), batch.start_height);
+/// ```
+pub fn get_blocks_bin(block_ids: &[BlockHash], start_height: u64, prune: bool, node: DaemonNode) -> Result<BlocksBinResult, String> {
+    if block_ids.is_empty() {
+        return Err("At least the genesis block hash is needed".to_string());
+    }
+    let request = epee_to_bytes(&vec![
+        ("block_ids".to_string(), EpeeValue::Blob(block_ids.iter().flat_map(|id| *id.as_bytes()).collect())),
+        ("start_height".to_string(), EpeeValue::U64(start_height)),
+        ("prune".to_string(), EpeeValue::Bool(prune)),
+        ("no_miner_tx".to_string(), EpeeValue::Bool(false)),
+    ])?;
+    let response = call_epee(&node, "/get_blocks.bin", &request, "blocks")?;
+    let invalid = |what: &str| format!("Error while parsing the blocks: invalid {}", what);
+    let number = |name: &str| epee_get(&response, name).and_then(|value| value.as_u64()).ok_or(invalid(name));
+    let entries = epee_get(&response, "blocks").and_then(|blocks| blocks.as_array()).unwrap_or(&[]);
+    let indices = epee_get(&response, "output_indices").and_then(|indices| indices.as_array()).unwrap_or(&[]);
+    if indices.len() != entries.len() {
+        return Err(invalid("output_indices"));
+    }
+    let mut blocks = Vec::with_capacity(entries.len());
+    for (entry, block_indices) in entries.iter().zip(indices) {
+        let entry = entry.as_object().ok_or(invalid("block entry"))?;
+        let block = epee_get(entry, "block").and_then(|block| block.as_blob()).ok_or(invalid("block"))?;
+        // Pruned transactions are objects holding the blob and the hash of the pruned data
+        let txs = epee_get(entry, "txs")
+            .and_then(|txs| txs.as_array())
+            .unwrap_or(&[])
+            .iter()
+            .map(|tx| match tx {
+                EpeeValue::Object(tx) => epee_get(tx, "blob").and_then(|blob| blob.as_blob()),
+                tx => tx.as_blob(),
+            })
+            .map(|tx| tx.map(|tx| tx.to_vec()).ok_or(invalid("transaction")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let output_indices = nested_indices(block_indices).ok_or(invalid("output_indices"))?;
+        if output_indices.len() != txs.len() + 1 {
+            return Err(invalid("output_indices"));
+        }
+        blocks.push(BlockCompleteEntry {
+            block: block.to_vec(),
+            txs,
+            output_indices,
+        });
+    }
+    Ok(BlocksBinResult {
+        start_height: number("start_height")?,
+        current_height: number("current_height")?,
+        blocks,
+        untrusted: epee_get(&response, "untrusted").and_then(|untrusted| untrusted.as_bool()).unwrap_or(false),
+    })
+}
+
+// Reads the `{"indices": [{"indices": [...]}, ...]}` structure of the output indices of a block, epee omits empty arrays
+fn nested_indices(value: &EpeeValue) -> Option<Vec<Vec<u64>>> {
+    let txs = epee_get(value.as_object()?, "indices").map_or(Some(&[][..]), |txs| txs.as_array())?;
+    txs.iter()
+        .map(|tx| {
+            let indices = epee_get(tx.as_object()?, "indices").map_or(Some(&[][..]), |indices| indices.as_array())?;
+            indices.iter().map(|index| index.as_u64()).collect()
+        })
+        .collect()
+}
+
+/// Gets the cumulative distribution of the outputs of given amounts between two heights from the given daemon,
+/// `to_height` 0 meaning the top of the chain. Use amount 0 for RingCT outputs, which is what decoy selection needs
+/// Returns the distributions as a vector of OutputDistribution structs, one per amount, if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_output_distribution;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let distributions = get_output_distribution(&[0], 3000000, 0, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("RingCT outputs up to the top: {}", distributions[0].distribution.last().unwrap());
+/// ```
+pub fn get_output_distribution(amounts: &[u64], from_height: u64, to_height: u64, node: DaemonNode) -> Result<Vec<OutputDistribution>, String> {
+    let params = serde_json::json!({
+        "amounts": amounts,
+        "from_height": from_height,
+        "to_height": to_height,
+        "cumulative": true,
+        "binary": false,
+        "compress": false,
+    });
+    let result = call_json_rpc(&node, "get_output_distribution", params, "output distribution")?;
+    let distributions = result["distributions"].as_array().ok_or("Error while parsing the output distribution JSON")?;
+    distributions
+        .iter()
+        .map(|distribution| {
+            Ok(OutputDistribution {
+                amount: distribution["amount"].as_u64().unwrap_or(0),
+                start_height: distribution["start_height"].as_u64().unwrap_or(0),
+                base: distribution["base"].as_u64().unwrap_or(0),
+                distribution: distribution["distribution"]
+                    .as_array()
+                    .ok_or("Error while parsing the output distribution JSON")?
+                    .iter()
+                    .map(|count| count.as_u64().ok_or("Error while parsing the output distribution JSON".to_string()))
+                    .collect::<Result<Vec<u64>, String>>()?,
+            })
+        })
+        .collect()
+}
+
+/// Gets how many outputs of each of the given amounts exist on chain from the given daemon,
+/// only unlocked outputs are counted if `unlocked` is true
+/// Pre-RingCT outputs can only be mixed with outputs of the same amount, so this tells which of them are unmixable
+/// Returns one OutputHistogramEntry per amount that has outputs if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_output_histogram;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let histogram = get_output_histogram(&[20000000000], true, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Unlocked outputs of 0.02 XMR: {}", histogram[0].unlocked_instances);
+/// ```
+pub fn get_output_histogram(amounts: &[u64], unlocked: bool, node: DaemonNode) -> Result<Vec<OutputHistogramEntry>, String> {
+    let params = serde_json::json!({
+        "amounts": amounts,
+        "min_count": 0,
+        "max_count": 0,
+        "unlocked": unlocked,
+        "recent_cutoff": 0,
+    });
+    let result = call_json_rpc(&node, "get_output_histogram", params, "output histogram")?;
+    // Epee omits empty arrays
+    let histogram = match result["histogram"].as_array() {
+        Some(histogram) => histogram,
+        None => return Ok(Vec::new()),
+    };
+    histogram
+        .iter()
+        .map(|entry| {
+            Ok(OutputHistogramEntry {
+                amount: entry["amount"].as_u64().ok_or("Error while parsing the output histogram JSON")?,
+                total_instances: entry["total_instances"].as_u64().unwrap_or(0),
+                unlocked_instances: entry["unlocked_instances"].as_u64().unwrap_or(0),
+                recent_instances: entry["recent_instances"].as_u64().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Gets the global output indices of every output of the transaction with given hash from the given daemon,
+/// they are needed to spend the outputs since rings reference outputs by global index
+/// Returns the indices in output order if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_global_output_indexes;
+/// use libmonero::blocks::{DaemonNode, TxHash};
+///
+/// let tx_hash = TxHash::from_hex("e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11").unwrap();
+/// let indexes = get_global_output_indexes(tx_hash, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Global index of output 0: {}", indexes[0]);
+/// ```
+pub fn get_global_output_indexes(hash: TxHash, node: DaemonNode) -> Result<Vec<u64>, String> {
+    let request = epee_to_bytes(&vec![("txid".to_string(), EpeeValue::Blob(hash.as_bytes().to_vec()))])?;
+    let response = call_epee(&node, "/get_o_indexes.bin", &request, "output indexes")?;
+    match epee_get(&response, "o_indexes").and_then(|indexes| indexes.as_array()) {
+        Some(indexes) => indexes
+            .iter()
+            .map(|index| index.as_u64().ok_or("Error while parsing the output indexes: invalid index".to_string()))
+            .collect(),
+        // Epee omits empty arrays
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Gets the spent status of key images from the given daemon, as needed to check that outputs are still unspent
+/// Returns the status of every key image, in order, if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{is_key_image_spent, DaemonNode, KeyImageStatus};
+/// use libmonero::keys::KeyImage;
+///
+/// let key_image = KeyImage::from_hex("8c4ad50ab4e2ab43a4c9f4ed3d7e36e8cb3d9ad7d4ee0bfcc5e91d7e9a07fa4a").unwrap();
+/// let status = is_key_image_spent(&[key_image], DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Spent: {}", status[0] != KeyImageStatus::Unspent);
+/// ```
+pub fn is_key_image_spent(key_images: &[KeyImage], node: DaemonNode) -> Result<Vec<KeyImageStatus>, String> {
+    let hexes: Vec<String> = key_images.iter().map(|key_image| key_image.to_hex()).collect();
+    let response = node.call_json("/is_key_image_spent", &serde_json::json!({
+        "key_images": hexes,
+    }));
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the key image status from daemon: {}", e));
+    }
+    let response = response.unwrap();
+    if response["status"].as_str() != Some("OK") {
+        return Err(format!("Error while getting the key image status from daemon: {}", response["status"].as_str().unwrap_or("invalid response")));
+    }
+    let statuses = response["spent_status"].as_array().ok_or("Error while parsing the key image status JSON")?;
+    if statuses.len() != key_images.len() {
+        return Err(format!("Error while parsing the key image status JSON: asked for {} key images, got {}", key_images.len(), statuses.len()));
+    }
+    statuses
+        .iter()
+        .map(|status| match status.as_u64() {
+            Some(0) => Ok(KeyImageStatus::Unspent),
+            Some(1) => Ok(KeyImageStatus::SpentInChain),
+            Some(2) => Ok(KeyImageStatus::SpentInPool),
+            _ => Err(format!("Error while parsing the key image status JSON: invalid status {}", status)),
+        })
+        .collect()
+}
+
+/// Gets a block template paying the coinbase reward to `wallet_address` from the given daemon
+/// `reserve_size` bytes (at most 255) are reserved in the miner transaction's extra for pools and miners to fill,
+/// see [`BlockTemplate::set_reserved`](struct.BlockTemplate.html#method.set_reserved)
+/// Returns the template as a BlockTemplate struct with decoded blobs if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::get_block_template;
+/// use libmonero::blocks::DaemonNode;
+///
+/// let address = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+/// let template = get_block_template(address, 8, DaemonNode::cake_wallet_default()).unwrap();
+/// println!("Mining block {} at difficulty {}", template.height, template.difficulty);
+/// ```
+pub fn get_block_template(wallet_address: &str, reserve_size: u64, node: DaemonNode) -> Result<BlockTemplate, String> {
+    if reserve_size > 255 {
+        return Err(format!("Reserve size {} is too big, at most 255 bytes can be reserved", reserve_size));
+    }
+    let result = call_json_rpc(&node, "get_block_template", serde_json::json!({
+        "wallet_address": wallet_address,
+        "reserve_size": reserve_size
+    }), "block template")?;
+    let blob = |field: &str| match result[field].as_str().map(hex::decode) {
+        Some(Ok(blob)) => Ok(blob),
+        _ => Err(format!("Error while parsing the block template JSON: invalid {}", field)),
+    };
+    let template = BlockTemplate {
+        blocktemplate_blob: blob("blocktemplate_blob")?,
+        blockhashing_blob: blob("blockhashing_blob")?,
+        difficulty: result["difficulty"].as_u64().unwrap_or(0),
+        difficulty_top64: result["difficulty_top64"].as_u64().unwrap_or(0),
+        wide_difficulty: result["wide_difficulty"].as_str().unwrap_or("").to_string(),
+        expected_reward: result["expected_reward"].as_u64().unwrap_or(0),
+        height: result["height"].as_u64().unwrap_or(0),
+        prev_hash: parse_bytes32(&result["prev_hash"], BlockHash::from_hex, "prev_hash")?,
+        reserved_offset: result["reserved_offset"].as_u64().unwrap_or(0) as usize,
+        reserve_size: reserve_size as usize,
+        seed_hash: result["seed_hash"].as_str().unwrap_or("").to_string(),
+        next_seed_hash: result["next_seed_hash"].as_str().unwrap_or("").to_string(),
+        untrusted: result["untrusted"].as_bool().unwrap_or(false),
+    };
+    if reserve_size > 0 && (template.reserved_offset == 0 || template.reserved_offset + template.reserve_size > template.blocktemplate_blob.len()) {
+        return Err(format!("Error while parsing the block template JSON: reserved offset {} is out of the blob", template.reserved_offset));
+    }
+    Ok(template)
+}
+
+/// Submits a mined block to the given daemon, `block_blob` is the serialized block,
+/// usually [`BlockTemplate::blocktemplate_blob`](struct.BlockTemplate.html) with its nonce set
+/// Returns nothing if the block was accepted
+/// Returns an error message if the block was rejected or the daemon could not be reached
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{get_block_template, submit_block};
+/// use libmonero::blocks::DaemonNode;
+///
+/// let address = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+/// let mut template = get_block_template(address, 0, DaemonNode::cake_wallet_default()).unwrap();
+/// template.set_nonce(123456).unwrap();
+/// submit_block(&template.blocktemplate_blob, DaemonNode::cake_wallet_default()).unwrap();
+/// ```
+pub fn submit_block(block_blob: &[u8], node: DaemonNode) -> Result<(), String> {
+    let result = call_json_rpc(&node, "submit_block", serde_json::json!([hex::encode(block_blob)]), "block submission")?;
+    match result["status"].as_str() {
+        Some("OK") => Ok(()),
+        Some(status) => Err(format!("Block was not accepted: {}", status)),
+        None => Err("Error while parsing the block submission JSON".to_string()),
+    }
+}
+
+// Maximum number of block headers restricted daemons return for one `get_block_headers_range` call
+const MAX_HEADERS_PER_REQUEST: u64 = 1000;
+
+// Sends an epee request to a `.bin` endpoint and returns the response once its status is checked
+fn call_epee(node: &DaemonNode, path: &str, request: &[u8], what: &str) -> Result<EpeeObject, String> {
+    let response = node.call(path, request);
+    if let Err(e) = response.as_ref() {
+        return Err(format!("Error while getting the {} from daemon: {}", what, e));
+    }
+    let response = epee_from_bytes(&response.unwrap()).map_err(|e| format!("Error while parsing the {}: {}", what, e))?;
+    let status = epee_get(&response, "status").and_then(|status| status.as_blob()).unwrap_or(b"");
+    if status != b"OK" {
+        return Err(format!("Error while getting the {} from daemon: {}", what, String::from_utf8_lossy(status)));
+    }
+    Ok(response)
+}
+
+// Calls the given JSON-RPC method and returns its result, `what` names the requested data in error messages
+fn call_json_rpc(node: &DaemonNode, method: &str, params: serde_json::Value, what: &str) -> Result<serde_json::Value, String> {
+    let response = node.call_json("/json_rpc", &serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": "0",
+        "method": method,
+        "params": params,
+    }));
+    let context = || format!("Error while getting the {} from daemon", what);
+    let response = response.map_err(RpcError::Transport).with_context(context)?;
+    // Statuses are checked by the callers, some of them accept other statuses than OK
+    if let Some(error @ RpcError::Daemon { .. }) = RpcError::from_json_rpc(&response) {
+        return Err(Error::from(error).with_context(context()).into());
+    }
+    if !response["result"].is_object() {
+        return Err(format!("Error while parsing the {} JSON", what));
+    }
+    Ok(response["result"].clone())
+}
+
+// Finds the string field with given name in a raw JSON response and unescapes it to bytes
+// The daemon writes binary blobs as JSON strings with bytes above 0x7f left as is
+pub(crate) fn extract_json_blob(response: &[u8], field: &str) -> Option<Vec<u8>> {
+    let needle = format!("\"{}\"", field);
+    let start = response.windows(needle.len()).position(|window| window == needle.as_bytes())? + needle.len();
+    let mut pos = start;
+    while pos < response.len() && (response[pos] == b' ' || response[pos] == b':' || response[pos].is_ascii_whitespace()) {
+        pos += 1;
+    }
+    if response.get(pos) != Some(&b'"') {
+        return None;
+    }
+    pos += 1;
+    let mut blob = Vec::new();
+    while pos < response.len() {
+        match response[pos] {
+            b'"' => return Some(blob),
+            b'\\' => {
+                let escaped = *response.get(pos + 1)?;
+                pos += 2;
+                match escaped {
+                    b'b' => blob.push(0x08),
+                    b'f' => blob.push(0x0c),
+                    b'n' => blob.push(b'\n'),
+                    b'r' => blob.push(b'\r'),
+                    b't' => blob.push(b'\t'),
+                    b'u' => {
+                        let code = std::str::from_utf8(response.get(pos..pos + 4)?).ok()?;
+                        blob.push(u8::try_from(u16::from_str_radix(code, 16).ok()?).ok()?);
+                        pos += 4;
+                    }
+                    other => blob.push(other),
+                }
+            }
+            byte => {
+                blob.push(byte);
+                pos += 1;
+            }
+        }
+    }
+    None
+}
+
+/// Parses a transaction from its JSON representation, as returned by the daemon with `decode_as_json`
+/// Returns the transaction as a RawTx struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::parse_transaction_json;
+///
+/// let json = r#"{"version": 2, "unlock_time": 60, "vin": [{"gen": {"height": 3000000}}], "vout": [{"amount": 600000000000, "target": {"tagged_key": {"key": "e6854544fbb66d55fc3546f4d3e69f8234257b69fa2237712af3b058a5f01ba1", "view_tag": "4a"}}}], "extra": [1, 2, 3], "rct_signatures": {"type": 0}}"#;
+/// let tx = parse_transaction_json(json).unwrap();
+/// assert_eq!(tx.vout[0].amount, 600000000000);
+/// assert_eq!(tx.vout[0].target.tagged_key.view_tag, "4a");
+/// assert_eq!(tx.extra, vec![1, 2, 3]);
+/// ```
+pub fn parse_transaction_json(json: &str) -> Result<RawTx, String> {
+    let json_final: serde_json::Value = serde_json::from_str(json).unwrap_or(serde_json::Value::Null);
+    if !json_final.is_object() {
+        return Err("Error while parsing the transaction JSON".to_string());
     }
     let mut vin_raw_tx = Vec::new();
-    for vin in json_final["vin"].as_array().unwrap().iter() {
+    for vin in json_final["vin"].as_array().unwrap_or(&Vec::new()).iter() {
         vin_raw_tx.push(VinRawTx {
             key: KeyRawTx {
                 amount: vin["key"]["amount"].as_u64().unwrap_or(0),
                 key_offsets: vin["key"]["key_offsets"].as_array().unwrap_or(&Vec::new()).to_vec().iter().map(|x| x.as_u64().unwrap_or(0)).collect(),
-                k_image: vin["key"]["k_image"].as_str().unwrap_or("").to_string(),
-            }
-        });
-    }
-    let mut vout_raw_tx = Vec::new();
-    for vout in json_final["vout"].as_array().unwrap().iter() {
-        vout_raw_tx.push(Vout {
-            amount: vout["amount"].as_u64().unwrap_or(0),
-            target: Target {
-                tagged_key: TaggedKey {
-                    key: vout["target"]["tagged_key"]["key"].as_str().unwrap_or("").to_string(),
-                    view_tag: vout["target"]["tagged_key"]["view_tag"].as_str().unwrap_or("").to_string(),
-                }
+                // Coinbase inputs have no key image
+                k_image: match vin["key"].is_object() {
+                    true => parse_bytes32(&vin["key"]["k_image"], KeyImage::from_hex, "k_image")?,
+                    false => KeyImage::default(),
+                },
             }
         });
     }
+    let vout_raw_tx = parse_vouts(&json_final["vout"])?;
     let mut ecdh_raw_tx = Vec::new();
-    for ecdh in json_final["rct_signatures"]["ecdhInfo"].as_array().unwrap().iter() {
+    for ecdh in json_final["rct_signatures"]["ecdhInfo"].as_array().unwrap_or(&Vec::new()).iter() {
         ecdh_raw_tx.push(EcdhInfo {
-            trunc_amount: ecdh["trunc_amount"].as_str().unwrap_or("").to_string(),
+            trunc_amount: ecdh["amount"].as_str().or(ecdh["trunc_amount"].as_str()).unwrap_or("").to_string(),
+            mask: ecdh["mask"].as_str().unwrap_or("").to_string(),
         });
     }
     let mut bpp_raw_tx = Vec::new();
-    for bpp in json_final["rctsig_prunable"]["bpp"].as_array().unwrap().iter() {
+    for bpp in json_final["rctsig_prunable"]["bpp"].as_array().unwrap_or(&Vec::new()).iter() {
         bpp_raw_tx.push(BPP {
             A: bpp["A"].as_str().unwrap_or("").to_string(),
             A1: bpp["A1"].as_str().unwrap_or("").to_string(),
@@ -254,7 +1312,7 @@ pub fn get_transaction_from_hash(hash: String, node: DaemonNode) -> Result<RawTx
         });
     }
     let mut clsags_raw_tx = Vec::new();
-    for clsag in json_final["rctsig_prunable"]["CLSAGs"].as_array().unwrap().iter() {
+    for clsag in json_final["rctsig_prunable"]["CLSAGs"].as_array().unwrap_or(&Vec::new()).iter() {
         clsags_raw_tx.push(CLSAG {
             s: {
                 let mut s = Vec::new();
@@ -272,18 +1330,96 @@ pub fn get_transaction_from_hash(hash: String, node: DaemonNode) -> Result<RawTx
         unlock_time: json_final["unlock_time"].as_u64().unwrap_or(0),
         vin: vin_raw_tx,
         vout: vout_raw_tx,
-        extra: json_final["extra"].as_str().unwrap_or("").as_bytes().to_vec(),
+        extra: parse_extra(&json_final["extra"]),
         rct_signatures: RctSignatures {
             type_int: json_final["rct_signatures"]["type"].as_u64().unwrap_or(0),
             txn_fee: json_final["rct_signatures"]["txnFee"].as_u64().unwrap_or(0),
             ecdh_info: ecdh_raw_tx,
-            out_pk: json_final["rct_signatures"]["outPk"].as_array().unwrap().to_vec().iter().map(|x| x.as_str().unwrap_or("").to_string()).collect(),
+            out_pk: match json_final["rct_signatures"]["outPk"].is_null() {
+                true => Vec::new(),
+                false => parse_bytes32_array(&json_final["rct_signatures"]["outPk"], Commitment::from_hex, "outPk")?,
+            },
         },
         rctsig_prunable: RctsigPrunable {
             nbp: json_final["rctsig_prunable"]["nbp"].as_u64().unwrap_or(0),
             bpp: bpp_raw_tx,
             CLSAGs: clsags_raw_tx,
-            pseudo_outs: json_final["rctsig_prunable"]["pseudoOuts"].as_array().unwrap().to_vec().iter().map(|x| x.as_str().unwrap_or("").to_string()).collect(),
-        }
+            pseudo_outs: json_final["rctsig_prunable"]["pseudoOuts"].as_array().unwrap_or(&Vec::new()).to_vec().iter().map(|x| x.as_str().unwrap_or("").to_string()).collect(),
+        },
+        untrusted: false,
+    })
+}
+
+// Parses outputs, both `key` (before view tags) and `tagged_key` targets
+fn parse_vouts(vouts: &serde_json::Value) -> Result<Vec<Vout>, String> {
+    let mut vout_vec = Vec::new();
+    for vout in vouts.as_array().unwrap_or(&Vec::new()).iter() {
+        let target = &vout["target"];
+        let (key, view_tag) = match target["tagged_key"].is_object() {
+            true => (&target["tagged_key"]["key"], target["tagged_key"]["view_tag"].as_str()),
+            false => (&target["key"], None),
+        };
+        vout_vec.push(Vout {
+            amount: vout["amount"].as_u64().unwrap_or(0),
+            target: Target {
+                tagged_key: TaggedKey {
+                    key: parse_bytes32(key, OneTimeKey::from_hex, "output key")?,
+                    view_tag: view_tag.unwrap_or("").to_string(),
+                }
+            }
+        });
+    }
+    Ok(vout_vec)
+}
+
+// Parses tx_extra, which the daemon returns as an array of bytes
+fn parse_extra(extra: &serde_json::Value) -> Vec<u8> {
+    extra.as_array().unwrap_or(&Vec::new()).iter().map(|x| x.as_u64().unwrap_or(0) as u8).collect()
+}
+
+// Parses a block header as returned by the `get_block*` methods
+fn parse_block_header(header: &serde_json::Value) -> Result<BlockHeader, String> {
+    if !header.is_object() {
+        return Err("Error while parsing the block header JSON".to_string());
+    }
+    Ok(BlockHeader {
+        block_size: header["block_size"].as_u64().unwrap_or(0),
+        block_weight: header["block_weight"].as_u64().unwrap_or(0),
+        cumulative_difficulty: header["cumulative_difficulty"].as_u64().unwrap_or(0),
+        cumulative_difficulty_top64: header["cumulative_difficulty_top64"].as_u64().unwrap_or(0),
+        depth: header["depth"].as_u64().unwrap_or(0),
+        difficulty: header["difficulty"].as_u64().unwrap_or(0),
+        difficulty_top64: header["difficulty_top64"].as_u64().unwrap_or(0),
+        hash: parse_bytes32(&header["hash"], BlockHash::from_hex, "hash")?,
+        height: header["height"].as_u64().unwrap_or(0),
+        long_term_weight: header["long_term_weight"].as_u64().unwrap_or(0),
+        major_version: header["major_version"].as_u64().unwrap_or(0),
+        miner_tx_hash: parse_bytes32(&header["miner_tx_hash"], TxHash::from_hex, "miner_tx_hash")?,
+        minor_version: header["minor_version"].as_u64().unwrap_or(0),
+        nonce: header["nonce"].as_u64().unwrap_or(0),
+        num_txes: header["num_txes"].as_u64().unwrap_or(0),
+        orphan_status: header["orphan_status"].as_bool().unwrap_or(false),
+        pow_hash: header["pow_hash"].as_str().unwrap_or("").to_string(),
+        prev_hash: parse_bytes32(&header["prev_hash"], BlockHash::from_hex, "prev_hash")?,
+        reward: header["reward"].as_u64().unwrap_or(0),
+        timestamp: header["timestamp"].as_u64().unwrap_or(0),
+        wide_cumulative_difficulty: header["wide_cumulative_difficulty"].as_str().unwrap_or("").to_string(),
+        wide_difficulty: header["wide_difficulty"].as_str().unwrap_or("").to_string(),
     })
-}
\ No newline at end of file
+}
+
+// Parses a hex string field holding 32 bytes (hash, key, key image or commitment) with given constructor
+pub(crate) fn parse_bytes32<T>(value: &serde_json::Value, from_hex: fn(&str) -> Result<T, String>, field: &str) -> Result<T, String> {
+    match value.as_str() {
+        Some(hex_str) => from_hex(hex_str).map_err(|e| format!("Error while parsing {}: {}", field, e)),
+        None => Err(format!("Error while parsing {}: missing field", field)),
+    }
+}
+
+// Parses an array of hex strings holding 32 bytes each
+pub(crate) fn parse_bytes32_array<T>(value: &serde_json::Value, from_hex: fn(&str) -> Result<T, String>, field: &str) -> Result<Vec<T>, String> {
+    match value.as_array() {
+        Some(values) => values.iter().map(|value| parse_bytes32(value, from_hex, field)).collect(),
+        None => Err(format!("Error while parsing {}: missing field", field)),
+    }
+}
@@ -0,0 +1,159 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::auth::DigestChallenge;
+use super::nodes::DaemonNode;
+use super::transport::RpcTransport;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Status code, headers and body of an HTTP response
+type HttpResponse = (u16, Vec<(String, String)>, Vec<u8>);
+
+/// LocalSocketTransport sends HTTP requests over a Unix domain socket, or a named pipe on Windows, instead of TCP
+///
+/// monerod only listens on TCP, the socket is usually exposed by a local reverse proxy (e.g. nginx or socat)
+/// in front of it, which keeps the RPC port closed to the rest of the host and to sandboxed processes.
+/// Digest authentication is supported as with the default transport.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{get_height, DaemonNode};
+///
+/// let node = DaemonNode::local_socket("/run/monerod/rpc.sock");
+/// println!("Height: {}", get_height(node).unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct LocalSocketTransport {
+    path: PathBuf,
+}
+
+impl LocalSocketTransport {
+    /// Creates a transport connecting to the socket or named pipe at the given path
+    pub fn new<P: Into<PathBuf>>(path: P) -> LocalSocketTransport {
+        LocalSocketTransport { path: path.into() }
+    }
+
+    // Sockets are local, only the request timeout of the node's RpcConfig applies
+    #[cfg(unix)]
+    fn connect(&self, timeout: Duration) -> Result<impl Read + Write, String> {
+        let stream = std::os::unix::net::UnixStream::connect(&self.path)
+            .map_err(|e| format!("Error while connecting to {}: {}", self.path.display(), e))?;
+        stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        Ok(stream)
+    }
+
+    #[cfg(windows)]
+    fn connect(&self, _timeout: Duration) -> Result<impl Read + Write, String> {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&self.path)
+            .map_err(|e| format!("Error while connecting to {}: {}", self.path.display(), e))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn connect(&self, _timeout: Duration) -> Result<std::fs::File, String> {
+        Err("Local sockets are not supported on this platform".to_string())
+    }
+
+    // Sends one request on a new connection, returns the status code, the headers and the body of the response
+    fn exchange(&self, node: &DaemonNode, path: &str, body: &[u8], authorization: Option<&str>) -> Result<HttpResponse, String> {
+        let mut stream = self.connect(node.config.request_timeout)?;
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            path,
+            body.len()
+        );
+        if let Some(authorization) = authorization {
+            request.push_str(&format!("Authorization: {}\r\n", authorization));
+        }
+        request.push_str("\r\n");
+        let mut request = request.into_bytes();
+        request.extend_from_slice(body);
+        stream.write_all(&request).map_err(|e| format!("Error while sending the request: {}", e))?;
+        read_response(&mut BufReader::new(stream)).map_err(|e| format!("Error while reading the response: {}", e))
+    }
+}
+
+impl RpcTransport for LocalSocketTransport {
+    fn call(&self, node: &DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let (mut status, headers, mut response) = self.exchange(node, path, body, None)?;
+        if status == 401 {
+            let login = node.login.as_ref().ok_or("Daemon requires an RPC login, see DaemonNode::with_login")?;
+            let challenges: Vec<&str> = headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("WWW-Authenticate"))
+                .map(|(_, value)| value.as_str())
+                .collect();
+            let authorization = DigestChallenge::from_headers(&challenges)?.authorization(login, "POST", path);
+            (status, _, response) = self.exchange(node, path, body, Some(&authorization))?;
+            if status == 401 {
+                return Err("Daemon rejected the RPC login".to_string());
+            }
+        }
+        match status {
+            200..=299 => Ok(response),
+            _ => Err(format!("{}: status code {}", self.path.display(), status)),
+        }
+    }
+}
+
+// Reads an HTTP/1.1 response, the body is delimited by Content-Length, chunked encoding or the end of the stream
+fn read_response<R: BufRead>(reader: &mut R) -> Result<HttpResponse, String> {
+    let status_line = read_line(reader)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or(format!("Invalid status line: {}", status_line))?;
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').ok_or(format!("Invalid header: {}", line))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+    let header = |name: &str| headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str());
+    let mut body = Vec::new();
+    if header("Transfer-Encoding").is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked")) {
+        loop {
+            let size_line = read_line(reader)?;
+            let size = usize::from_str_radix(size_line.split(';').next().unwrap_or("").trim(), 16)
+                .map_err(|_| format!("Invalid chunk size: {}", size_line))?;
+            if size == 0 {
+                break;
+            }
+            let start = body.len();
+            body.resize(start + size, 0);
+            reader.read_exact(&mut body[start..]).map_err(|e| e.to_string())?;
+            read_line(reader)?;
+        }
+    } else if let Some(length) = header("Content-Length") {
+        let length = length.parse::<usize>().map_err(|_| format!("Invalid content length: {}", length))?;
+        body.resize(length, 0);
+        reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    } else {
+        reader.read_to_end(&mut body).map_err(|e| e.to_string())?;
+    }
+    Ok((status, headers, body))
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+        return Err("Connection closed".to_string());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
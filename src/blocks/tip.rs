@@ -0,0 +1,234 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::BlockHash;
+use super::nodes::DaemonNode;
+use super::rpcs::{get_block_headers_range, get_height_info};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// Restricted RPC servers return at most 1000 headers per request
+const MAX_HEADERS_PER_POLL: u64 = 1000;
+
+/// ChainTip is a change of the main chain seen by a [`TipWatcher`](struct.TipWatcher.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChainTip {
+    /// Block added on top of the main chain
+    NewBlock { height: u64, hash: BlockHash },
+    /// Blocks from `fork_height` were replaced, `orphaned` holding their hashes from `fork_height` up
+    ///
+    /// The blocks of the new chain follow as `NewBlock` events. If the reorganization is deeper than the tracked
+    /// blocks, `fork_height` is the height of the oldest tracked block and the actual fork may be lower.
+    Reorg { fork_height: u64, orphaned: Vec<BlockHash> },
+}
+
+/// TipWatcher follows the top of the main chain of a node, tracking the hashes of the last blocks so reorganizations
+/// are detected and reported along with the orphaned blocks
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{BlockHash, ChainTip, TipWatcher};
+///
+/// let mut watcher = TipWatcher::new(100);
+/// let (a, b, c) = (BlockHash::from_bytes([0xaa; 32]), BlockHash::from_bytes([0xbb; 32]), BlockHash::from_bytes([0xcc; 32]));
+/// assert_eq!(watcher.update(3000000, a, &[]).unwrap(), vec![ChainTip::NewBlock { height: 3000000, hash: a }]);
+/// // Block 3000001 is replaced by c
+/// watcher.update(3000001, b, &[(a, b)]).unwrap();
+/// let events = watcher.update(3000001, c, &[(a, c)]).unwrap();
+/// assert_eq!(events[0], ChainTip::Reorg { fork_height: 3000001, orphaned: vec![b] });
+/// assert_eq!(events[1], ChainTip::NewBlock { height: 3000001, hash: c });
+/// ```
+#[derive(Clone, Debug)]
+pub struct TipWatcher {
+    depth: usize,
+    blocks: VecDeque<(u64, BlockHash)>,
+}
+
+impl TipWatcher {
+    /// Creates a watcher tracking the last `depth` blocks, reorganizations up to that depth are reported exactly
+    pub fn new(depth: usize) -> TipWatcher {
+        TipWatcher {
+            depth: depth.max(1),
+            blocks: VecDeque::new(),
+        }
+    }
+
+    /// Returns the height and hash of the last known top block, if any
+    pub fn tip(&self) -> Option<(u64, BlockHash)> {
+        self.blocks.back().copied()
+    }
+
+    /// Returns the hash of the tracked block at given height, if any
+    pub fn hash_at(&self, height: u64) -> Option<BlockHash> {
+        let (first_height, _) = self.blocks.front()?;
+        let index = usize::try_from(height.checked_sub(*first_height)?).ok()?;
+        self.blocks.get(index).map(|(_, hash)| *hash)
+    }
+
+    /// Updates the watcher with blocks of the main chain ending with the block `top_hash` at `top_height`
+    ///
+    /// `links` are the previous block hash and the hash of consecutive blocks ending with the top block. Their first block
+    /// must be tracked or have a tracked parent, the first update only needs the top block.
+    /// Returns the changes since the last update
+    /// Returns an error message if the blocks do not link to the tracked ones
+    pub fn update(&mut self, top_height: u64, top_hash: BlockHash, links: &[(BlockHash, BlockHash)]) -> Result<Vec<ChainTip>, String> {
+        if self.tip() == Some((top_height, top_hash)) {
+            return Ok(Vec::new());
+        }
+        if links.last().is_some_and(|(_, hash)| *hash != top_hash) {
+            return Err("Last block does not match the top block hash".to_string());
+        }
+        if links.windows(2).any(|pair| pair[1].0 != pair[0].1) {
+            return Err("Blocks do not form a chain".to_string());
+        }
+        let first_height = (top_height + 1)
+            .checked_sub(links.len() as u64)
+            .ok_or("More blocks than the chain height")?;
+
+        let mut events = Vec::new();
+        if self.blocks.is_empty() {
+            self.push(top_height, top_hash, &mut events);
+            return Ok(events);
+        }
+        if links.is_empty() {
+            return Err("Blocks are needed to link the top block to the tracked ones".to_string());
+        }
+        if !self.links_to(first_height, &links[0]) {
+            return match first_height < self.blocks.front().unwrap().0 {
+                true => Err(format!("Blocks start below the {} tracked blocks", self.blocks.len())),
+                false => Err(format!("Block at height {} does not link to the tracked chain", first_height)),
+            };
+        }
+        // Blocks the caller sent again and which did not change are skipped
+        let fork_height = (first_height..=top_height)
+            .zip(links.iter())
+            .find(|(height, (_, hash))| self.hash_at(*height) != Some(*hash))
+            .map(|(height, _)| height)
+            .unwrap_or(top_height + 1);
+        self.orphan_from(fork_height, &mut events);
+        for (height, (_, hash)) in (first_height..=top_height).zip(links.iter()) {
+            if height >= fork_height {
+                self.push(height, *hash, &mut events);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Queries the top of the main chain from the given node and updates the watcher, see [`update`](#method.update)
+    ///
+    /// Headers are fetched from the tip up, or further down until they link to the tracked blocks after a
+    /// reorganization. At most 1000 new blocks are fetched per poll, the next polls fetch the rest.
+    /// Returns the changes since the last poll if succesfull
+    /// Returns an error message if not succesfull
+    pub fn poll(&mut self, node: &DaemonNode) -> Result<Vec<ChainTip>, String> {
+        let info = get_height_info(node.clone())?;
+        let top_height = info.height.checked_sub(1).ok_or("Daemon has no block")?;
+        let (tip_height, tip_hash) = match self.tip() {
+            Some(tip) => tip,
+            None => return self.update(top_height, info.hash, &[]),
+        };
+        if (tip_height, tip_hash) == (top_height, info.hash) {
+            return Ok(Vec::new());
+        }
+        let oldest_height = self.blocks.front().unwrap().0;
+        let mut start_height = (tip_height + 1).min(top_height);
+        let mut step = 1;
+        loop {
+            let end_height = top_height.min(start_height + MAX_HEADERS_PER_POLL - 1);
+            let headers = get_block_headers_range(start_height, end_height, node.clone())?;
+            let links: Vec<(BlockHash, BlockHash)> = headers.iter().map(|header| (header.prev_hash, header.hash)).collect();
+            let linked = links.first().is_some_and(|link| self.links_to(start_height, link));
+            if linked {
+                let end_hash = links.last().map(|(_, hash)| *hash).ok_or("Daemon returned no block header")?;
+                return self.update(end_height, end_hash, &links);
+            }
+            if start_height <= oldest_height {
+                // Deeper than tracked: everything tracked is orphaned and the chain restarts from the top
+                let mut events = Vec::new();
+                self.orphan_from(oldest_height, &mut events);
+                events.extend(self.update(top_height, info.hash, &[])?);
+                return Ok(events);
+            }
+            // Walk down faster the deeper the reorganization is
+            start_height = start_height.saturating_sub(step).max(oldest_height);
+            step *= 2;
+        }
+    }
+
+    // Checks if the block at given height is tracked or has a tracked parent, the genesis block always links
+    fn links_to(&self, height: u64, (prev_hash, hash): &(BlockHash, BlockHash)) -> bool {
+        match height.checked_sub(1) {
+            Some(parent_height) => self.hash_at(parent_height) == Some(*prev_hash) || self.hash_at(height) == Some(*hash),
+            None => true,
+        }
+    }
+
+    fn orphan_from(&mut self, fork_height: u64, events: &mut Vec<ChainTip>) {
+        let mut orphaned = Vec::new();
+        while let Some((height, _)) = self.blocks.back() {
+            if *height < fork_height {
+                break;
+            }
+            orphaned.push(self.blocks.pop_back().unwrap().1);
+        }
+        if !orphaned.is_empty() {
+            orphaned.reverse();
+            events.push(ChainTip::Reorg { fork_height, orphaned });
+        }
+    }
+
+    fn push(&mut self, height: u64, hash: BlockHash, events: &mut Vec<ChainTip>) {
+        if self.blocks.back().is_some_and(|(tip_height, _)| *tip_height + 1 != height) {
+            self.blocks.clear();
+        }
+        self.blocks.push_back((height, hash));
+        if self.blocks.len() > self.depth {
+            self.blocks.pop_front();
+        }
+        events.push(ChainTip::NewBlock { height, hash });
+    }
+}
+
+/// Watches the top of the main chain of the given node, polling it every `poll_interval`, and returns its changes
+/// as they come
+///
+/// The first event is the top block at the time of the first poll. The last 100 blocks are tracked for reorganizations,
+/// see [`TipWatcher`](struct.TipWatcher.html). Errors of the node are retried at the next poll, use
+/// [`TipWatcher::poll`](struct.TipWatcher.html#method.poll) to handle them. The iterator never ends.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{watch_height, ChainTip, DaemonNode};
+/// use std::time::Duration;
+///
+/// for event in watch_height(DaemonNode::cake_wallet_default(), Duration::from_secs(10)) {
+///     match event {
+///         ChainTip::NewBlock { height, hash } => println!("Block {} at height {}", hash, height),
+///         ChainTip::Reorg { fork_height, orphaned } => println!("{} block(s) orphaned from height {}", orphaned.len(), fork_height),
+///     }
+/// }
+/// ```
+pub fn watch_height(node: DaemonNode, poll_interval: Duration) -> impl Iterator<Item = ChainTip> {
+    let mut watcher = TipWatcher::new(100);
+    let mut pending: VecDeque<ChainTip> = VecDeque::new();
+    let mut first = true;
+    std::iter::from_fn(move || loop {
+        if let Some(event) = pending.pop_front() {
+            return Some(event);
+        }
+        if !first {
+            std::thread::sleep(poll_interval);
+        }
+        first = false;
+        if let Ok(events) = watcher.poll(&node) {
+            pending.extend(events);
+        }
+    })
+}
@@ -0,0 +1,168 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::{TxHash, TxStatus};
+use super::nodes::DaemonNode;
+use super::rpcs::get_transaction_status;
+use std::time::Duration;
+
+/// TxState is the lifecycle state of a tracked transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxState {
+    /// Transaction was not seen by the daemon yet
+    Pending,
+    /// Transaction is in the daemon's pool, `double_spend_seen` is set if a conflicting transaction was seen
+    InPool { double_spend_seen: bool },
+    /// Transaction is in a block but does not have enough confirmations yet
+    Mined { height: u64, confirmations: u64 },
+    /// Final: transaction reached the requested number of confirmations
+    Confirmed { height: u64, confirmations: u64 },
+    /// Final: transaction left the pool without being mined
+    Dropped,
+    /// Final: transaction left the pool without being mined after a conflicting transaction was seen
+    DoubleSpent,
+}
+
+impl TxState {
+    /// Checks if the state is final, no more state changes follow
+    pub fn is_final(&self) -> bool {
+        matches!(self, TxState::Confirmed { .. } | TxState::Dropped | TxState::DoubleSpent)
+    }
+}
+
+/// TxTracker follows a broadcast transaction through pool, mined and confirmed states, or until it is dropped
+///
+/// A transaction never seen by the daemon is reported as dropped after `max_missing_polls` consecutive polls,
+/// a transaction that was seen and disappears from the pool is reported as dropped (or double spent) right away.
+/// Mined transactions going back to the pool after a reorganization are followed as well.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{TxHash, TxState, TxStatus, TxTracker};
+///
+/// let hash = TxHash::from_hex("e4516854a5984eaf5f8750ac7af41d1e0b2c602a2297a673001e8c0af88eba11").unwrap();
+/// let mut tracker = TxTracker::new(hash, 10);
+/// let in_pool = TxStatus { found: true, in_pool: true, ..TxStatus::default() };
+/// assert_eq!(tracker.update(&in_pool), Some(TxState::InPool { double_spend_seen: false }));
+/// assert_eq!(tracker.update(&in_pool), None);
+/// let mined = TxStatus { found: true, block_height: 3000000, confirmations: 10, ..TxStatus::default() };
+/// assert_eq!(tracker.update(&mined), Some(TxState::Confirmed { height: 3000000, confirmations: 10 }));
+/// assert!(tracker.state().is_final());
+/// ```
+#[derive(Clone, Debug)]
+pub struct TxTracker {
+    hash: TxHash,
+    confirmations: u64,
+    max_missing_polls: u32,
+    missing_polls: u32,
+    seen: bool,
+    double_spend_seen: bool,
+    state: TxState,
+}
+
+impl TxTracker {
+    /// Creates a tracker for the transaction with given hash, which is confirmed after `confirmations` confirmations
+    pub fn new(hash: TxHash, confirmations: u64) -> TxTracker {
+        TxTracker {
+            hash,
+            confirmations: confirmations.max(1),
+            max_missing_polls: 30,
+            missing_polls: 0,
+            seen: false,
+            double_spend_seen: false,
+            state: TxState::Pending,
+        }
+    }
+
+    /// Sets after how many consecutive polls a transaction never seen by the daemon is considered dropped, 30 by default
+    pub fn with_max_missing_polls(mut self, max_missing_polls: u32) -> TxTracker {
+        self.max_missing_polls = max_missing_polls;
+        self
+    }
+
+    /// Returns the hash of the tracked transaction
+    pub fn hash(&self) -> TxHash {
+        self.hash
+    }
+
+    /// Returns the current state of the transaction
+    pub fn state(&self) -> TxState {
+        self.state
+    }
+
+    /// Updates the tracker with a new status of the transaction, returns the new state if it changed
+    ///
+    /// Updates after a final state are ignored
+    pub fn update(&mut self, status: &TxStatus) -> Option<TxState> {
+        if self.state.is_final() {
+            return None;
+        }
+        let new_state = if !status.found {
+            self.missing_polls += 1;
+            if self.seen || self.missing_polls >= self.max_missing_polls {
+                if self.double_spend_seen {
+                    TxState::DoubleSpent
+                } else {
+                    TxState::Dropped
+                }
+            } else {
+                self.state
+            }
+        } else {
+            self.seen = true;
+            self.missing_polls = 0;
+            self.double_spend_seen |= status.double_spend_seen;
+            if status.in_pool {
+                TxState::InPool {
+                    double_spend_seen: self.double_spend_seen,
+                }
+            } else if status.confirmations >= self.confirmations {
+                TxState::Confirmed {
+                    height: status.block_height,
+                    confirmations: status.confirmations,
+                }
+            } else {
+                TxState::Mined {
+                    height: status.block_height,
+                    confirmations: status.confirmations,
+                }
+            }
+        };
+        if new_state == self.state {
+            return None;
+        }
+        self.state = new_state;
+        Some(new_state)
+    }
+
+    /// Queries the status of the transaction from the given node and updates the tracker, returns the new state if it changed
+    pub fn poll(&mut self, node: &DaemonNode) -> Result<Option<TxState>, String> {
+        let status = get_transaction_status(self.hash, node.clone())?;
+        Ok(self.update(&status))
+    }
+
+    /// Polls the given node every `interval` until the transaction reaches a final state, which is returned
+    ///
+    /// `on_change` is called on every state change, including the final one. Errors of the node are returned right away.
+    pub fn track<F>(&mut self, node: &DaemonNode, interval: Duration, mut on_change: F) -> Result<TxState, String>
+    where
+        F: FnMut(&TxState),
+    {
+        loop {
+            if let Some(state) = self.poll(node)? {
+                on_change(&state);
+            }
+            if self.state.is_final() {
+                return Ok(self.state);
+            }
+            std::thread::sleep(interval);
+        }
+    }
+}
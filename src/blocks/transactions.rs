@@ -8,21 +8,714 @@
  *
  */
 
-use std::collections::HashMap;
+use super::block::{EcdhInfo, KeyRawTx, RawTx, TxHash, RctSignatures, RctsigPrunable, TaggedKey, Target, VinRawTx, Vout};
+use super::extra::{extract_extra_nonce, extract_tx_pub_keys, nonce_payment_id, TxExtra};
+use crate::keys::{Commitment, KeyImage, OneTimeKey, PublicKey};
+use crate::utils::varint::{read_varint, write_varint};
+use sha3::{Digest, Keccak256};
 
-/// Transactions struct contains all the information about a single transaction
-pub struct Transaction {
-    pub sender: String,
-    pub receiver: String,
+// Input and output tags of the consensus format
+const TXIN_GEN: u8 = 0xff;
+const TXIN_TO_KEY: u8 = 0x02;
+const TXOUT_TO_KEY: u8 = 0x02;
+const TXOUT_TO_TAGGED_KEY: u8 = 0x03;
+
+/// Input of a transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxInput {
+    /// Coinbase input of a miner transaction, creating the block reward
+    Gen { height: u64 },
+    /// Ring input spending one of the outputs referenced by `key_offsets`, which are relative global output indices
+    ToKey { amount: u64, key_offsets: Vec<u64>, key_image: KeyImage },
+}
+
+/// Destination of a transaction output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxOutTarget {
+    /// One-time key, used before view tags
+    ToKey { key: OneTimeKey },
+    /// One-time key with its view tag, used since hard fork 15
+    ToTaggedKey { key: OneTimeKey, view_tag: u8 },
+}
+
+/// Output of a transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxOutput {
+    /// Clear amount, 0 for RingCT outputs
     pub amount: u64,
-    pub timestamp: u64,
-    pub block_height: u64,
-    pub tx_hash: String,
-    pub tx_fee: u64,
-    pub additional_data: HashMap<String, String>
+    pub target: TxOutTarget,
+}
+
+impl TxOutput {
+    /// Returns the one-time key of the output
+    pub fn key(&self) -> OneTimeKey {
+        match self.target {
+            TxOutTarget::ToKey { key } | TxOutTarget::ToTaggedKey { key, .. } => key,
+        }
+    }
+
+    /// Returns the view tag of the output, if any
+    pub fn view_tag(&self) -> Option<u8> {
+        match self.target {
+            TxOutTarget::ToKey { .. } => None,
+            TxOutTarget::ToTaggedKey { view_tag, .. } => Some(view_tag),
+        }
+    }
+}
+
+/// Prefix of a transaction, the part signed by its inputs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionPrefix {
+    pub version: u64,
+    /// Height (below 500000000) or Unix timestamp until which the outputs are locked, 0 if unlocked
+    pub unlock_time: u64,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+    pub extra: Vec<u8>,
+}
+
+/// RingCT type of a version 2 transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RctType {
+    /// No RingCT data, used by miner transactions
+    Null = 0,
+    Full = 1,
+    Simple = 2,
+    Bulletproof = 3,
+    Bulletproof2 = 4,
+    Clsag = 5,
+    BulletproofPlus = 6,
+}
+
+impl RctType {
+    /// Returns the RingCT type with given consensus code
+    pub fn from_u8(code: u8) -> Option<RctType> {
+        Some(match code {
+            0 => RctType::Null,
+            1 => RctType::Full,
+            2 => RctType::Simple,
+            3 => RctType::Bulletproof,
+            4 => RctType::Bulletproof2,
+            5 => RctType::Clsag,
+            6 => RctType::BulletproofPlus,
+            _ => return None,
+        })
+    }
+
+    // Encrypted amounts are 8 bytes without mask since Bulletproof2
+    fn has_compact_amounts(&self) -> bool {
+        *self >= RctType::Bulletproof2
+    }
+}
+
+/// Encrypted amount of a RingCT output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncryptedAmount {
+    /// Encrypted mask and amount, as 32-byte scalars, used by RingCT types 1 to 3
+    Full { mask: [u8; 32], amount: [u8; 32] },
+    /// Amount XORed with a key stream, the mask being derived from the shared secret, used since RingCT type 4
+    Compact { amount: [u8; 8] },
+}
+
+impl EncryptedAmount {
+    /// Parses an `ecdhInfo` entry of a transaction, `compact` is true for RingCT types 4 and above
+    pub fn from_ecdh_info(ecdh: &EcdhInfo, compact: bool) -> Result<EncryptedAmount, String> {
+        let amount = hex::decode(&ecdh.trunc_amount).map_err(|_| format!("encrypted amount \"{}\" is not hex", ecdh.trunc_amount))?;
+        if compact {
+            let amount = amount.get(..8).ok_or(format!("encrypted amount is {} bytes long instead of 8", amount.len()))?;
+            return Ok(EncryptedAmount::Compact { amount: amount.try_into().unwrap() });
+        }
+        let amount = amount.as_slice().try_into().map_err(|_| format!("encrypted amount is {} bytes long instead of 32", amount.len()))?;
+        let mask = hex::decode(&ecdh.mask)
+            .ok()
+            .and_then(|mask| mask.try_into().ok())
+            .ok_or(format!("encrypted mask \"{}\" is not 32 bytes of hex", ecdh.mask))?;
+        Ok(EncryptedAmount::Full { mask, amount })
+    }
+
+    /// Returns the entry as an `ecdhInfo` entry, compact amounts have an empty mask
+    pub fn to_ecdh_info(&self) -> EcdhInfo {
+        match self {
+            EncryptedAmount::Full { mask, amount } => EcdhInfo { trunc_amount: hex::encode(amount), mask: hex::encode(mask) },
+            EncryptedAmount::Compact { amount } => EcdhInfo { trunc_amount: hex::encode(amount), mask: String::new() },
+        }
+    }
+}
+
+/// Non-prunable part of the RingCT signatures of a version 2 transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RctBase {
+    pub rct_type: RctType,
+    pub fee: u64,
+    /// Pseudo output commitments of the inputs, only stored here by `Simple` transactions, later types keep them in the prunable part
+    pub pseudo_outs: Vec<Commitment>,
+    /// Encrypted amount of every output
+    pub encrypted_amounts: Vec<EncryptedAmount>,
+    /// Amount commitment of every output
+    pub commitments: Vec<Commitment>,
+}
+
+/// Signatures of a transaction, in the format of its version
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxSignatures {
+    /// Ring signatures of a version 1 transaction, one 64-byte signature per ring member of every input,
+    /// empty for miner transactions and pruned transactions
+    V1 { ring_signatures: Vec<Vec<[u8; 64]>> },
+    /// RingCT signatures of a version 2 transaction, the prunable part (range proofs, ring signatures and
+    /// pseudo outputs) is kept serialized and is empty for pruned transactions
+    V2 { base: RctBase, prunable: Vec<u8> },
+}
+
+/// Payment ID found in the extra nonce of a transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaymentId {
+    /// Deprecated 32-byte payment ID, readable by anyone
+    Unencrypted([u8; 32]),
+    /// 8-byte payment ID of an integrated address, encrypted for the recipient
+    Encrypted([u8; 8]),
+}
+
+/// Transaction parsed from its consensus binary format with [`parse_transaction_blob`](fn.parse_transaction_blob.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub prefix: TransactionPrefix,
+    pub signatures: TxSignatures,
+}
+
+impl Transaction {
+    /// Checks if the transaction is a miner transaction
+    pub fn is_coinbase(&self) -> bool {
+        matches!(self.prefix.inputs.as_slice(), [TxInput::Gen { .. }])
+    }
+
+    /// Returns the fee of the transaction, the explicit RingCT fee for version 2 transactions and the difference
+    /// between input and output amounts for version 1 transactions
+    pub fn fee(&self) -> u64 {
+        match &self.signatures {
+            TxSignatures::V2 { base, .. } => base.fee,
+            TxSignatures::V1 { .. } if self.is_coinbase() => 0,
+            TxSignatures::V1 { .. } => {
+                let inputs: u64 = self
+                    .prefix
+                    .inputs
+                    .iter()
+                    .map(|input| match input {
+                        TxInput::ToKey { amount, .. } => *amount,
+                        TxInput::Gen { .. } => 0,
+                    })
+                    .fold(0, u64::saturating_add);
+                let outputs = self.prefix.outputs.iter().map(|output| output.amount).fold(0, u64::saturating_add);
+                inputs.saturating_sub(outputs)
+            }
+        }
+    }
+
+    /// Returns the key images of the ring inputs
+    pub fn key_images(&self) -> Vec<KeyImage> {
+        self.prefix
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                TxInput::ToKey { key_image, .. } => Some(*key_image),
+                TxInput::Gen { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Returns the main transaction public key found in tx_extra, if any
+    pub fn tx_public_key(&self) -> Option<PublicKey> {
+        self.tx_public_keys().into_iter().next()
+    }
+
+    /// Returns every main transaction public key found in tx_extra, some wallets wrongly add several of them
+    pub fn tx_public_keys(&self) -> Vec<PublicKey> {
+        let (main_keys, _) = extract_tx_pub_keys(&self.prefix.extra);
+        main_keys.into_iter().filter_map(|key| PublicKey::from_bytes(key).ok()).collect()
+    }
+
+    /// Returns the additional (per output) public keys found in tx_extra, empty if the transaction has none
+    pub fn additional_public_keys(&self) -> Vec<PublicKey> {
+        let (_, additional_keys) = extract_tx_pub_keys(&self.prefix.extra);
+        additional_keys.into_iter().filter_map(|key| PublicKey::from_bytes(key).ok()).collect()
+    }
+
+    /// Returns the payment ID found in the extra nonce of tx_extra, if any
+    pub fn payment_id(&self) -> Option<PaymentId> {
+        nonce_payment_id(extract_extra_nonce(&self.prefix.extra)?)
+    }
+
+    /// Parses the tx_extra of the transaction into its fields, see [`TxExtra::from_bytes`](struct.TxExtra.html#method.from_bytes)
+    pub fn extra(&self) -> Result<TxExtra, String> {
+        TxExtra::from_bytes(&self.prefix.extra)
+    }
+
+    /// Returns the prefix hash `Keccak256(prefix)`, the message signed by the inputs
+    pub fn prefix_hash(&self) -> [u8; 32] {
+        let mut prefix = Vec::new();
+        write_prefix(&self.prefix, &mut prefix);
+        Keccak256::digest(&prefix).into()
+    }
+
+    /// Checks if the signatures were pruned, leaving only what is needed to scan the transaction
+    pub fn is_pruned(&self) -> bool {
+        match &self.signatures {
+            TxSignatures::V1 { ring_signatures } => ring_signatures.is_empty() && !self.is_coinbase(),
+            TxSignatures::V2 { base, prunable } => prunable.is_empty() && base.rct_type != RctType::Null,
+        }
+    }
+
+    /// Returns the hash identifying the transaction
+    ///
+    /// Version 1 transactions hash their whole blob. Version 2 transactions hash the concatenation of the prefix hash,
+    /// the hash of the RingCT base and the hash of the prunable part (zero for miner transactions), so pruned
+    /// transactions can still be identified knowing the hash of their prunable part, see
+    /// [`hash_with_prunable_hash`](#method.hash_with_prunable_hash).
+    /// Returns the transaction hash if succesfull
+    /// Returns an error message if the transaction is pruned
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::parse_transaction_blob;
+    ///
+    /// let blob = hex::decode(format!("029c0801ffe8070180ade20403{}5e2101{}00", "aa".repeat(32), format!("58{}", "66".repeat(31)))).unwrap();
+    /// let tx = parse_transaction_blob(&blob).unwrap();
+    /// assert_eq!(tx.hash().unwrap(), tx.hash_with_prunable_hash(&[0; 32]));
+    /// assert_ne!(tx.hash().unwrap().as_bytes(), &tx.prefix_hash());
+    /// ```
+    pub fn hash(&self) -> Result<TxHash, String> {
+        if self.is_pruned() {
+            return Err("Transaction is pruned, its hash needs the hash of the prunable part".to_string());
+        }
+        Ok(match &self.signatures {
+            TxSignatures::V1 { .. } => TxHash::from_bytes(Keccak256::digest(self.to_blob()).into()),
+            TxSignatures::V2 { base, prunable } => match base.rct_type {
+                RctType::Null => self.hash_with_prunable_hash(&[0; 32]),
+                _ => self.hash_with_prunable_hash(&Keccak256::digest(prunable).into()),
+            },
+        })
+    }
+
+    /// Returns the hash of a version 2 transaction from the given hash of its prunable part, as given by daemons
+    /// along pruned transactions, the prunable part held by the transaction is ignored
+    ///
+    /// Version 1 transactions have no prunable hash, their hash is the hash of their blob.
+    pub fn hash_with_prunable_hash(&self, prunable_hash: &[u8; 32]) -> TxHash {
+        match &self.signatures {
+            TxSignatures::V1 { .. } => TxHash::from_bytes(Keccak256::digest(self.to_blob()).into()),
+            TxSignatures::V2 { base, .. } => {
+                let mut rct_base = Vec::new();
+                write_rct_base(base, &mut rct_base);
+                let mut hasher = Keccak256::new();
+                hasher.update(self.prefix_hash());
+                hasher.update(Keccak256::digest(&rct_base));
+                hasher.update(prunable_hash);
+                TxHash::from_bytes(hasher.finalize().into())
+            }
+        }
+    }
+
+    /// Parses a transaction from its consensus binary format, pruned transactions are accepted
+    ///
+    /// Only canonical encodings are accepted: varints must be minimal, no data may follow the transaction and
+    /// [`to_blob`](#method.to_blob) must give back the exact same bytes, so hashes and signatures computed over the
+    /// parsed transaction match those of the blob.
+    ///
+    /// Returns the transaction as a Transaction struct if succesfull
+    /// Returns an error message if the blob is invalid or not canonically encoded
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::Transaction;
+    ///
+    /// let blob = hex::decode(format!("029c0801ffe8070180ade20403{}5e2101{}00", "aa".repeat(32), "66".repeat(32))).unwrap();
+    /// let tx = Transaction::from_blob(&blob).unwrap();
+    /// assert_eq!(tx.to_blob(), blob);
+    ///
+    /// // Trailing bytes and padded varints (the unlock time 1052 as 9c 88 00) are refused
+    /// assert!(Transaction::from_blob(&[blob.as_slice(), &[0]].concat()).is_err());
+    /// let padded = [&blob[..2], &[0x88, 0x00], &blob[3..]].concat();
+    /// assert!(Transaction::from_blob(&padded).is_err());
+    /// ```
+    pub fn from_blob(blob: &[u8]) -> Result<Transaction, String> {
+        let mut pos = 0;
+        let prefix = read_prefix(blob, &mut pos).map_err(|e| format!("Error while parsing the transaction prefix: {}", e))?;
+        let signatures = match prefix.version {
+            1 => read_v1_signatures(blob, &mut pos, &prefix),
+            2 => read_rct_signatures(blob, &mut pos, &prefix),
+            version => Err(format!("unsupported version {}", version)),
+        }
+        .map_err(|e| format!("Error while parsing the transaction signatures: {}", e))?;
+        let transaction = Transaction { prefix, signatures };
+        // Every field is either checked or kept verbatim, this guards the guarantee of to_blob
+        if transaction.to_blob() != blob {
+            return Err("Error while parsing the transaction: blob is not canonically encoded".to_string());
+        }
+        Ok(transaction)
+    }
+
+    /// Serializes the transaction to its consensus binary format, the reverse of [`from_blob`](#method.from_blob)
+    ///
+    /// Transactions parsed from a blob serialize back to the exact same bytes. Pruned transactions serialize to their
+    /// pruned blob.
+    pub fn to_blob(&self) -> Vec<u8> {
+        let mut blob = Vec::new();
+        write_prefix(&self.prefix, &mut blob);
+        match &self.signatures {
+            TxSignatures::V1 { ring_signatures } => {
+                for signature in ring_signatures.iter().flatten() {
+                    blob.extend_from_slice(signature);
+                }
+            }
+            TxSignatures::V2 { base, prunable } => {
+                write_rct_base(base, &mut blob);
+                blob.extend_from_slice(prunable);
+            }
+        }
+        blob
+    }
+
+    /// Converts the transaction to a RawTx, as returned by [`get_transaction_from_hash`](fn.get_transaction_from_hash.html),
+    /// so it can be scanned with [`scan_tx_for_outputs`](../keys/fn.scan_tx_for_outputs.html)
+    ///
+    /// Only the fields scanning needs are filled, the prunable signatures are left empty
+    pub fn to_raw_tx(&self) -> RawTx {
+        let vin = self
+            .prefix
+            .inputs
+            .iter()
+            .map(|input| VinRawTx {
+                key: match input {
+                    TxInput::ToKey { amount, key_offsets, key_image } => KeyRawTx {
+                        amount: *amount,
+                        key_offsets: key_offsets.clone(),
+                        k_image: *key_image,
+                    },
+                    // Coinbase inputs have no key image
+                    TxInput::Gen { .. } => KeyRawTx {
+                        amount: 0,
+                        key_offsets: Vec::new(),
+                        k_image: KeyImage::default(),
+                    },
+                },
+            })
+            .collect();
+        let vout = self
+            .prefix
+            .outputs
+            .iter()
+            .map(|output| Vout {
+                amount: output.amount,
+                target: Target {
+                    tagged_key: TaggedKey {
+                        key: output.key(),
+                        view_tag: output.view_tag().map(|tag| hex::encode([tag])).unwrap_or_default(),
+                    },
+                },
+            })
+            .collect();
+        let rct_signatures = match &self.signatures {
+            TxSignatures::V2 { base, .. } => RctSignatures {
+                type_int: base.rct_type as u64,
+                txn_fee: base.fee,
+                ecdh_info: base.encrypted_amounts.iter().map(EncryptedAmount::to_ecdh_info).collect(),
+                out_pk: base.commitments.clone(),
+            },
+            TxSignatures::V1 { .. } => RctSignatures {
+                type_int: 0,
+                txn_fee: 0,
+                ecdh_info: Vec::new(),
+                out_pk: Vec::new(),
+            },
+        };
+        RawTx {
+            version: self.prefix.version,
+            unlock_time: self.prefix.unlock_time,
+            vin,
+            vout,
+            extra: self.prefix.extra.clone(),
+            rct_signatures,
+            rctsig_prunable: RctsigPrunable {
+                nbp: 0,
+                bpp: Vec::new(),
+                CLSAGs: Vec::new(),
+                pseudo_outs: Vec::new(),
+            },
+            untrusted: false,
+        }
+    }
+}
+
+/// Parses a transaction from its consensus binary format, as found in blocks, in `get_blocks.bin` answers or in the
+/// `as_hex` field of `get_transactions`, pruned transactions are accepted, see [`Transaction::from_blob`](struct.Transaction.html#method.from_blob)
+/// Returns the transaction as a Transaction struct if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{parse_transaction_blob, TxInput, TxSignatures};
+///
+/// // Miner transaction of version 2 at height 1000 with one tagged output and a transaction public key
+/// let output_key = "aa".repeat(32);
+/// let tx_public_key = format!("58{}", "66".repeat(31));
+/// let blob = hex::decode(format!("029c0801ffe8070180ade20403{}5e2101{}00", output_key, tx_public_key)).unwrap();
+/// let tx = parse_transaction_blob(&blob).unwrap();
+/// assert!(tx.is_coinbase());
+/// assert_eq!(tx.prefix.unlock_time, 1052);
+/// assert_eq!(tx.prefix.inputs, vec![TxInput::Gen { height: 1000 }]);
+/// assert_eq!(tx.prefix.outputs[0].amount, 10000000);
+/// assert_eq!(tx.prefix.outputs[0].view_tag(), Some(0x5e));
+/// assert!(tx.tx_public_key().is_some());
+/// assert!(matches!(tx.signatures, TxSignatures::V2 { .. }));
+/// ```
+pub fn parse_transaction_blob(blob: &[u8]) -> Result<Transaction, String> {
+    Transaction::from_blob(blob)
+}
+
+// Reads the miner transaction of a block blob, which is followed by the transaction hashes of the block: miner
+// transactions have no ring signatures and version 2 ones have a null RingCT type, so their end is known
+pub(crate) fn read_miner_transaction(blob: &[u8], pos: &mut usize) -> Result<Transaction, String> {
+    let prefix = read_prefix(blob, pos)?;
+    if !matches!(prefix.inputs.as_slice(), [TxInput::Gen { .. }]) {
+        return Err("miner transaction must have a single coinbase input".to_string());
+    }
+    let signatures = match prefix.version {
+        1 => TxSignatures::V1 { ring_signatures: Vec::new() },
+        2 => match read_bytes(blob, pos, 1)?[0] {
+            0 => TxSignatures::V2 {
+                base: RctBase {
+                    rct_type: RctType::Null,
+                    fee: 0,
+                    pseudo_outs: Vec::new(),
+                    encrypted_amounts: Vec::new(),
+                    commitments: Vec::new(),
+                },
+                prunable: Vec::new(),
+            },
+            code => return Err(format!("miner transaction has RingCT type {}", code)),
+        },
+        version => return Err(format!("unsupported version {}", version)),
+    };
+    Ok(Transaction { prefix, signatures })
+}
+
+fn read_prefix(blob: &[u8], pos: &mut usize) -> Result<TransactionPrefix, String> {
+    let version = read_varint(blob, pos)?;
+    let unlock_time = read_varint(blob, pos)?;
+    let input_count = read_count(blob, pos, 1)?;
+    let mut inputs = Vec::with_capacity(input_count);
+    for _ in 0..input_count {
+        inputs.push(match read_bytes(blob, pos, 1)?[0] {
+            TXIN_GEN => TxInput::Gen { height: read_varint(blob, pos)? },
+            TXIN_TO_KEY => {
+                let amount = read_varint(blob, pos)?;
+                let offset_count = read_count(blob, pos, 1)?;
+                let key_offsets = (0..offset_count).map(|_| read_varint(blob, pos)).collect::<Result<Vec<_>, _>>()?;
+                let key_image = KeyImage::from_bytes(read_bytes32(blob, pos)?);
+                TxInput::ToKey { amount, key_offsets, key_image }
+            }
+            tag => return Err(format!("unsupported input type {:#04x}", tag)),
+        });
+    }
+    let output_count = read_count(blob, pos, 2)?;
+    let mut outputs = Vec::with_capacity(output_count);
+    for _ in 0..output_count {
+        let amount = read_varint(blob, pos)?;
+        let target = match read_bytes(blob, pos, 1)?[0] {
+            TXOUT_TO_KEY => TxOutTarget::ToKey { key: OneTimeKey::from_bytes(read_bytes32(blob, pos)?) },
+            TXOUT_TO_TAGGED_KEY => TxOutTarget::ToTaggedKey {
+                key: OneTimeKey::from_bytes(read_bytes32(blob, pos)?),
+                view_tag: read_bytes(blob, pos, 1)?[0],
+            },
+            tag => return Err(format!("unsupported output type {:#04x}", tag)),
+        };
+        outputs.push(TxOutput { amount, target });
+    }
+    let extra_len = read_count(blob, pos, 1)?;
+    let extra = read_bytes(blob, pos, extra_len)?.to_vec();
+    Ok(TransactionPrefix {
+        version,
+        unlock_time,
+        inputs,
+        outputs,
+        extra,
+    })
+}
+
+fn read_v1_signatures(blob: &[u8], pos: &mut usize, prefix: &TransactionPrefix) -> Result<TxSignatures, String> {
+    let mut ring_signatures = Vec::new();
+    // Pruned transactions end with the prefix
+    if *pos < blob.len() {
+        for input in prefix.inputs.iter() {
+            let ring_size = match input {
+                TxInput::ToKey { key_offsets, .. } => key_offsets.len(),
+                TxInput::Gen { .. } => continue,
+            };
+            let signatures = (0..ring_size)
+                .map(|_| Ok(read_bytes(blob, pos, 64)?.try_into().unwrap()))
+                .collect::<Result<Vec<[u8; 64]>, String>>()?;
+            ring_signatures.push(signatures);
+        }
+    }
+    if *pos != blob.len() {
+        return Err("trailing data after the ring signatures".to_string());
+    }
+    Ok(TxSignatures::V1 { ring_signatures })
+}
+
+// Reads a full Bulletproofs+ transaction followed by other data, the prunable part is measured instead of taking the
+// rest of the data, as in the pending transactions of signed transaction sets
+pub(crate) fn read_transaction(blob: &[u8], pos: &mut usize) -> Result<Transaction, String> {
+    let prefix = read_prefix(blob, pos)?;
+    if prefix.version != 2 {
+        return Err(format!("unsupported version {}", prefix.version));
+    }
+    let base = read_rct_base(blob, pos, &prefix)?;
+    if base.rct_type != RctType::BulletproofPlus {
+        return Err(format!("unsupported RingCT type {:?}", base.rct_type));
+    }
+    let start = *pos;
+    // Bulletproofs+: A, A1, B, r1, s1, d1 then L and R
+    for _ in 0..read_count(blob, pos, 6 * 32)? {
+        read_bytes(blob, pos, 6 * 32)?;
+        for _ in 0..2 {
+            let count = read_count(blob, pos, 32)?;
+            read_bytes(blob, pos, count * 32)?;
+        }
+    }
+    // CLSAG of every input, s of each member, c1 and D, then the pseudo outputs
+    for input in prefix.inputs.iter() {
+        let TxInput::ToKey { key_offsets, .. } = input else {
+            return Err("RingCT transaction has a coinbase input".to_string());
+        };
+        read_bytes(blob, pos, key_offsets.len() * 32 + 64)?;
+    }
+    read_bytes(blob, pos, prefix.inputs.len() * 32)?;
+    let prunable = blob[start..*pos].to_vec();
+    Ok(Transaction { prefix, signatures: TxSignatures::V2 { base, prunable } })
+}
+
+fn read_rct_signatures(blob: &[u8], pos: &mut usize, prefix: &TransactionPrefix) -> Result<TxSignatures, String> {
+    let base = read_rct_base(blob, pos, prefix)?;
+    if base.rct_type == RctType::Null && *pos != blob.len() {
+        return Err("trailing data after the null RingCT signatures".to_string());
+    }
+    let prunable = blob[*pos..].to_vec();
+    *pos = blob.len();
+    Ok(TxSignatures::V2 { base, prunable })
+}
+
+fn read_rct_base(blob: &[u8], pos: &mut usize, prefix: &TransactionPrefix) -> Result<RctBase, String> {
+    let code = read_bytes(blob, pos, 1)?[0];
+    let rct_type = RctType::from_u8(code).ok_or(format!("unknown RingCT type {}", code))?;
+    let mut base = RctBase {
+        rct_type,
+        fee: 0,
+        pseudo_outs: Vec::new(),
+        encrypted_amounts: Vec::new(),
+        commitments: Vec::new(),
+    };
+    if rct_type != RctType::Null {
+        base.fee = read_varint(blob, pos)?;
+        if rct_type == RctType::Simple {
+            for _ in 0..prefix.inputs.len() {
+                base.pseudo_outs.push(Commitment::from_bytes(read_bytes32(blob, pos)?));
+            }
+        }
+        for _ in 0..prefix.outputs.len() {
+            base.encrypted_amounts.push(match rct_type.has_compact_amounts() {
+                true => EncryptedAmount::Compact { amount: read_bytes(blob, pos, 8)?.try_into().unwrap() },
+                false => EncryptedAmount::Full {
+                    mask: read_bytes32(blob, pos)?,
+                    amount: read_bytes32(blob, pos)?,
+                },
+            });
+        }
+        for _ in 0..prefix.outputs.len() {
+            base.commitments.push(Commitment::from_bytes(read_bytes32(blob, pos)?));
+        }
+    }
+    Ok(base)
+}
+
+// Reads an element count, rejecting counts that could not fit in the remaining data given the minimum element size
+fn read_count(blob: &[u8], pos: &mut usize, min_element_size: usize) -> Result<usize, String> {
+    let count = read_varint(blob, pos)?;
+    match usize::try_from(count) {
+        Ok(count) if count.saturating_mul(min_element_size) <= blob.len() - *pos => Ok(count),
+        _ => Err(format!("count {} exceeds the remaining data", count)),
+    }
+}
+
+fn read_bytes<'a>(blob: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let end = pos.checked_add(len).filter(|end| *end <= blob.len()).ok_or("unexpected end of data")?;
+    let bytes = &blob[*pos..end];
+    *pos = end;
+    Ok(bytes)
+}
+
+fn read_bytes32(blob: &[u8], pos: &mut usize) -> Result<[u8; 32], String> {
+    Ok(read_bytes(blob, pos, 32)?.try_into().unwrap())
+}
+
+pub(crate) fn write_prefix(prefix: &TransactionPrefix, out: &mut Vec<u8>) {
+    write_varint(prefix.version, out);
+    write_varint(prefix.unlock_time, out);
+    write_varint(prefix.inputs.len() as u64, out);
+    for input in prefix.inputs.iter() {
+        match input {
+            TxInput::Gen { height } => {
+                out.push(TXIN_GEN);
+                write_varint(*height, out);
+            }
+            TxInput::ToKey { amount, key_offsets, key_image } => {
+                out.push(TXIN_TO_KEY);
+                write_varint(*amount, out);
+                write_varint(key_offsets.len() as u64, out);
+                for offset in key_offsets.iter() {
+                    write_varint(*offset, out);
+                }
+                out.extend_from_slice(key_image.as_bytes());
+            }
+        }
+    }
+    write_varint(prefix.outputs.len() as u64, out);
+    for output in prefix.outputs.iter() {
+        write_varint(output.amount, out);
+        match output.target {
+            TxOutTarget::ToKey { key } => {
+                out.push(TXOUT_TO_KEY);
+                out.extend_from_slice(key.as_bytes());
+            }
+            TxOutTarget::ToTaggedKey { key, view_tag } => {
+                out.push(TXOUT_TO_TAGGED_KEY);
+                out.extend_from_slice(key.as_bytes());
+                out.push(view_tag);
+            }
+        }
+    }
+    write_varint(prefix.extra.len() as u64, out);
+    out.extend_from_slice(&prefix.extra);
 }
 
-/* 
-pub fn check_output() {
+pub(crate) fn write_rct_base(base: &RctBase, out: &mut Vec<u8>) {
+    out.push(base.rct_type as u8);
+    if base.rct_type == RctType::Null {
+        return;
+    }
+    write_varint(base.fee, out);
+    for pseudo_out in base.pseudo_outs.iter() {
+        out.extend_from_slice(pseudo_out.as_bytes());
+    }
+    for amount in base.encrypted_amounts.iter() {
+        match amount {
+            EncryptedAmount::Full { mask, amount } => {
+                out.extend_from_slice(mask);
+                out.extend_from_slice(amount);
+            }
+            EncryptedAmount::Compact { amount } => out.extend_from_slice(amount),
+        }
+    }
+    for commitment in base.commitments.iter() {
+        out.extend_from_slice(commitment.as_bytes());
+    }
 }
-*/
\ No newline at end of file
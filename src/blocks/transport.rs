@@ -0,0 +1,175 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::auth::DigestChallenge;
+use super::nodes::DaemonNode;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// RpcTransport is implemented by anything able to deliver a request to a daemon and return the raw response body
+///
+/// All RPC functions of this module go through the transport of the node, which is [`HttpTransport`](struct.HttpTransport.html) unless
+/// another one is set with [`DaemonNode::with_transport`](struct.DaemonNode.html#method.with_transport)
+pub trait RpcTransport: Send + Sync {
+    /// Sends `body` to the given `path` of the node (e.g. `/json_rpc`, `/get_height`) and returns the response body
+    fn call(&self, node: &DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+/// HttpTransport sends requests over HTTP(S), it is the default transport
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpTransport;
+
+impl RpcTransport for HttpTransport {
+    fn call(&self, node: &DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let url = format!("{}{}", node.base_url(), path);
+        let mut agent = ureq::AgentBuilder::new()
+            .timeout_connect(node.config.connect_timeout)
+            .timeout(node.config.request_timeout);
+        if let Some(proxy) = &node.proxy {
+            agent = agent.proxy(proxy.to_ureq()?);
+        }
+        let agent = agent.build();
+        let request = agent.post(&url).set("Content-Type", "application/json");
+        let response = match (request.clone().send_bytes(body), &node.login) {
+            // Daemons started with `--rpc-login` answer unauthenticated requests with a digest challenge
+            (Err(ureq::Error::Status(401, challenge)), Some(login)) => {
+                let challenge = DigestChallenge::from_headers(&challenge.all("WWW-Authenticate"))?;
+                request
+                    .set("Authorization", &challenge.authorization(login, "POST", path))
+                    .send_bytes(body)
+                    .map_err(|e| match e {
+                        ureq::Error::Status(401, _) => "Daemon rejected the RPC login".to_string(),
+                        e => e.to_string(),
+                    })?
+            }
+            (Err(ureq::Error::Status(401, _)), None) => return Err("Daemon requires an RPC login, see DaemonNode::with_login".to_string()),
+            (response, _) => response.map_err(|e| e.to_string())?,
+        };
+        let mut response_body = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut response_body)
+            .map_err(|e| e.to_string())?;
+        Ok(response_body)
+    }
+}
+
+/// RecordingTransport forwards every call to another transport and appends the full exchange to a file
+///
+/// The file can be served back later with [`ReplayTransport`](struct.ReplayTransport.html), which makes
+/// tests of code talking to a daemon reproducible without network access
+pub struct RecordingTransport {
+    inner: Arc<dyn RpcTransport>,
+    file: Mutex<File>,
+}
+
+impl RecordingTransport {
+    /// Creates a recorder which forwards calls to `inner` and appends them to the file at `path`
+    pub fn new<P: AsRef<Path>>(inner: Arc<dyn RpcTransport>, path: P) -> Result<RecordingTransport, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Error while opening the recording file: {}", e))?;
+        Ok(RecordingTransport {
+            inner,
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl RpcTransport for RecordingTransport {
+    fn call(&self, node: &DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let result = self.inner.call(node, path, body);
+        let entry = match &result {
+            Ok(response) => serde_json::json!({
+                "path": path,
+                "request": hex::encode(body),
+                "response": hex::encode(response),
+            }),
+            Err(e) => serde_json::json!({
+                "path": path,
+                "request": hex::encode(body),
+                "error": e,
+            }),
+        };
+        let mut file = self.file.lock().map_err(|_| "Recording file lock is poisoned".to_string())?;
+        writeln!(file, "{}", entry).map_err(|e| format!("Error while writing the recording file: {}", e))?;
+        result
+    }
+}
+
+/// Recorded responses, keyed by path and request body
+type RecordedCalls = HashMap<(String, Vec<u8>), VecDeque<Result<Vec<u8>, String>>>;
+
+/// ReplayTransport serves responses recorded by [`RecordingTransport`](struct.RecordingTransport.html)
+///
+/// Calls are matched by path and exact request body, identical calls get their recorded responses in recording order.
+/// The node a call is made to is ignored, so recorded sessions can be replayed against any `DaemonNode`.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{get_height, DaemonNode, ReplayTransport};
+/// use std::sync::Arc;
+///
+/// let recording = r#"{"path":"/get_height","request":"7b7d","response":"7b22686569676874223a20333030303030307d"}"#;
+/// let replay = ReplayTransport::from_reader(recording.as_bytes()).unwrap();
+/// let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(replay));
+/// assert_eq!(get_height(node).unwrap(), 3000000);
+/// ```
+pub struct ReplayTransport {
+    calls: Mutex<RecordedCalls>,
+}
+
+impl ReplayTransport {
+    /// Loads a recording from the file at `path`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ReplayTransport, String> {
+        let file = File::open(path).map_err(|e| format!("Error while opening the recording file: {}", e))?;
+        ReplayTransport::from_reader(BufReader::new(file))
+    }
+
+    /// Loads a recording from any reader, one recorded call per line
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<ReplayTransport, String> {
+        let mut calls = RecordedCalls::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| format!("Error while reading the recording: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: serde_json::Value = serde_json::from_str(&line)
+                .map_err(|e| format!("Invalid recording entry at line {}: {}", index + 1, e))?;
+            let path = entry["path"].as_str().unwrap_or("").to_string();
+            let request = hex::decode(entry["request"].as_str().unwrap_or(""))
+                .map_err(|e| format!("Invalid request at line {}: {}", index + 1, e))?;
+            let response = match (entry["response"].as_str(), entry["error"].as_str()) {
+                (Some(response), _) => Ok(hex::decode(response).map_err(|e| format!("Invalid response at line {}: {}", index + 1, e))?),
+                (None, Some(error)) => Err(error.to_string()),
+                (None, None) => return Err(format!("Recording entry at line {} has neither a response nor an error", index + 1)),
+            };
+            calls.entry((path, request)).or_default().push_back(response);
+        }
+        Ok(ReplayTransport {
+            calls: Mutex::new(calls),
+        })
+    }
+}
+
+impl RpcTransport for ReplayTransport {
+    fn call(&self, _node: &DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let mut calls = self.calls.lock().map_err(|_| "Replay lock is poisoned".to_string())?;
+        match calls.get_mut(&(path.to_string(), body.to_vec())).and_then(|queue| queue.pop_front()) {
+            Some(response) => response,
+            None => Err(format!("No recorded response left for {} with request {}", path, String::from_utf8_lossy(body))),
+        }
+    }
+}
@@ -0,0 +1,481 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::block::BlockHash;
+use super::monitor::MonitorHandle;
+use super::rpcs::{parse_bytes32, parse_bytes32_array};
+use super::transactions::{EncryptedAmount, RctBase, RctType, Transaction, TransactionPrefix, TxInput, TxOutTarget, TxOutput, TxSignatures};
+use crate::keys::{Commitment, KeyImage, OneTimeKey};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// Delay between reconnection attempts of a watching subscriber, also the longest a stop request waits
+const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+// ZMTP 3.0 frame flags
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+/// ZmqTopic lists the topics of monerod's ZMQ publisher (`--zmq-pub`) libmonero can decode
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ZmqTopic {
+    /// `json-minimal-chain_main`: hashes of the blocks added to the main chain
+    ChainMain,
+    /// `json-full-txpool_add`: transactions added to the pool
+    TxPoolAdd,
+}
+
+impl ZmqTopic {
+    /// Returns the topic name used by monerod
+    pub fn name(&self) -> &'static str {
+        match self {
+            ZmqTopic::ChainMain => "json-minimal-chain_main",
+            ZmqTopic::TxPoolAdd => "json-full-txpool_add",
+        }
+    }
+}
+
+/// ZmqEvent is a notification published by monerod
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ZmqEvent {
+    /// Blocks were added to the main chain, `ids` being the hashes of the blocks from `first_height`
+    ///
+    /// After a reorganization `first_height` is below the height of the last notified block.
+    ChainMain { first_height: u64, first_prev_id: BlockHash, ids: Vec<BlockHash> },
+    /// Transactions were added to the pool
+    ///
+    /// monerod does not publish the prunable part of the signatures, so the transactions are pruned and identified by
+    /// [`hash_with_prunable_hash`](struct.Transaction.html#method.hash_with_prunable_hash) only. They can be scanned
+    /// after [`to_raw_tx`](struct.Transaction.html#method.to_raw_tx).
+    TxPoolAdd(Vec<Transaction>),
+}
+
+/// ZmqSubscriber receives the notifications of monerod's ZMQ publisher, so new blocks and pool transactions are known
+/// without polling the RPC interface
+///
+/// monerod publishes when started with `--zmq-pub tcp://127.0.0.1:18083`. The subscriber speaks ZMTP 3.0 with the
+/// NULL security mechanism, the publisher should not be exposed beyond trusted networks. Needs the `zmq` feature.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{ZmqEvent, ZmqSubscriber, ZmqTopic};
+///
+/// let mut subscriber = ZmqSubscriber::connect("tcp://127.0.0.1:18083", &[ZmqTopic::ChainMain]).unwrap();
+/// loop {
+///     if let ZmqEvent::ChainMain { first_height, ids, .. } = subscriber.recv().unwrap() {
+///         println!("{} new block(s) from height {}", ids.len(), first_height);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ZmqSubscriber {
+    endpoint: String,
+    topics: Vec<ZmqTopic>,
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl ZmqSubscriber {
+    /// Connects to the publisher at the given endpoint, `tcp://host:port` or `host:port`, and subscribes to the given topics
+    /// Returns the subscriber if succesfull
+    /// Returns an error message if not succesfull
+    pub fn connect(endpoint: &str, topics: &[ZmqTopic]) -> Result<ZmqSubscriber, String> {
+        if topics.is_empty() {
+            return Err("At least one topic is needed".to_string());
+        }
+        let address = endpoint.strip_prefix("tcp://").unwrap_or(endpoint);
+        let address = address
+            .to_socket_addrs()
+            .map_err(|e| format!("Error while resolving {}: {}", endpoint, e))?
+            .next()
+            .ok_or_else(|| format!("Error while resolving {}: no address found", endpoint))?;
+        let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).map_err(|e| format!("Error while connecting to {}: {}", endpoint, e))?;
+        let mut subscriber = ZmqSubscriber {
+            endpoint: endpoint.to_string(),
+            topics: topics.to_vec(),
+            stream,
+            buffer: Vec::new(),
+        };
+        subscriber.handshake().map_err(|e| format!("Error while connecting to {}: {}", endpoint, e))?;
+        Ok(subscriber)
+    }
+
+    /// Waits for the next event
+    /// Returns the event if succesfull
+    /// Returns an error message if the connection failed or the message could not be decoded
+    pub fn recv(&mut self) -> Result<ZmqEvent, String> {
+        loop {
+            if let Some(event) = self.recv_timeout(Duration::from_secs(3600))? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Waits at most `timeout` for the next event
+    /// Returns the event, or `None` if none arrived in time, if succesfull
+    /// Returns an error message if the connection failed or the message could not be decoded
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<ZmqEvent>, String> {
+        match self.next_message(timeout)? {
+            Some(message) => parse_zmq_message(&message).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Receives the events on a background thread and sends them to the returned channel
+    ///
+    /// Errors are sent to the channel as well. After a connection error the subscriber reconnects every half second
+    /// until the publisher is reachable again. Receiving goes on until [`MonitorHandle::stop`](struct.MonitorHandle.html#method.stop)
+    /// is called or the channel is dropped.
+    pub fn watch(self) -> (Receiver<Result<ZmqEvent, String>>, MonitorHandle) {
+        let (sender, receiver) = channel();
+        let (endpoint, topics) = (self.endpoint.clone(), self.topics.clone());
+        let mut subscriber = Some(self);
+        let mut closed = false;
+        let handle = MonitorHandle::spawn(Duration::ZERO, move || {
+            // Nobody listens anymore, the thread idles until stopped
+            if closed {
+                std::thread::sleep(RETRY_INTERVAL);
+                return;
+            }
+            let result = match subscriber.as_mut() {
+                Some(connected) => match connected.next_message(RETRY_INTERVAL) {
+                    Ok(None) => return,
+                    Ok(Some(message)) => parse_zmq_message(&message),
+                    Err(e) => {
+                        subscriber = None;
+                        Err(e)
+                    }
+                },
+                None => match ZmqSubscriber::connect(&endpoint, &topics) {
+                    Ok(connected) => {
+                        subscriber = Some(connected);
+                        return;
+                    }
+                    Err(e) => {
+                        std::thread::sleep(RETRY_INTERVAL);
+                        Err(e)
+                    }
+                },
+            };
+            if sender.send(result).is_err() {
+                subscriber = None;
+                closed = true;
+            }
+        });
+        (receiver, handle)
+    }
+
+    // Waits at most `timeout` for the next message of a subscribed topic, errors are connection errors only
+    fn next_message(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let message = match self.read_message(deadline)? {
+                Some(message) => message,
+                None => return Ok(None),
+            };
+            // Messages of the other topics are never sent, as the publisher filters on the subscriptions
+            if self.topics.iter().any(|topic| message.starts_with(topic.name().as_bytes())) {
+                return Ok(Some(message));
+            }
+        }
+    }
+
+    // Exchanges greetings and READY commands, then sends the subscriptions
+    fn handshake(&mut self) -> Result<(), String> {
+        self.stream.set_write_timeout(Some(CONNECT_TIMEOUT)).map_err(|e| e.to_string())?;
+        let mut greeting = [0u8; 64];
+        greeting[0] = 0xff;
+        greeting[9] = 0x7f;
+        greeting[10] = 3;
+        greeting[12..16].copy_from_slice(b"NULL");
+        self.stream.write_all(&greeting).map_err(|e| e.to_string())?;
+
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        let peer_greeting = self.read_exact(64, deadline)?;
+        if peer_greeting[0] != 0xff || peer_greeting[9] != 0x7f {
+            return Err("peer is not a ZMQ socket".to_string());
+        }
+        if peer_greeting[10] < 3 {
+            return Err(format!("ZMTP version {} is not supported", peer_greeting[10]));
+        }
+        if &peer_greeting[12..32] != b"NULL\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0" {
+            return Err("only the NULL security mechanism is supported".to_string());
+        }
+
+        let mut ready = vec![5];
+        ready.extend_from_slice(b"READY");
+        ready.push(11);
+        ready.extend_from_slice(b"Socket-Type");
+        ready.extend_from_slice(&3u32.to_be_bytes());
+        ready.extend_from_slice(b"SUB");
+        self.write_frame(FLAG_COMMAND, &ready)?;
+        let (flags, command) = self.read_frame(deadline)?.ok_or("timed out waiting for the READY command")?;
+        if flags & FLAG_COMMAND == 0 || !command.starts_with(b"\x05READY") {
+            return Err("peer did not send the READY command".to_string());
+        }
+        let socket_type = command_property(&command[6..], "Socket-Type");
+        if !matches!(socket_type.as_deref(), Some(b"PUB") | Some(b"XPUB")) {
+            return Err("peer is not a ZMQ publisher".to_string());
+        }
+
+        // ZMTP 3.0 subscriptions are messages starting with 1
+        for topic in self.topics.clone() {
+            let mut subscription = vec![1];
+            subscription.extend_from_slice(topic.name().as_bytes());
+            self.write_frame(0, &subscription)?;
+        }
+        Ok(())
+    }
+
+    fn write_frame(&mut self, flags: u8, body: &[u8]) -> Result<(), String> {
+        let mut frame = Vec::with_capacity(body.len() + 9);
+        match u8::try_from(body.len()) {
+            Ok(size) => frame.extend_from_slice(&[flags, size]),
+            Err(_) => {
+                frame.push(flags | FLAG_LONG);
+                frame.extend_from_slice(&(body.len() as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(body);
+        self.stream.write_all(&frame).map_err(|e| format!("Error while writing to {}: {}", self.endpoint, e))
+    }
+
+    // Reads the frames of the next message, skipping commands, and joins them
+    fn read_message(&mut self, deadline: Instant) -> Result<Option<Vec<u8>>, String> {
+        let mut message = Vec::new();
+        loop {
+            let (flags, body) = match self.peek_frame()? {
+                Some(frame) => frame,
+                None => match self.fill(deadline)? {
+                    true => continue,
+                    false => return Ok(None),
+                },
+            };
+            if flags & FLAG_COMMAND != 0 {
+                continue;
+            }
+            message.extend_from_slice(&body);
+            if flags & FLAG_MORE == 0 {
+                return Ok(Some(message));
+            }
+        }
+    }
+
+    fn read_frame(&mut self, deadline: Instant) -> Result<Option<(u8, Vec<u8>)>, String> {
+        loop {
+            if let Some(frame) = self.peek_frame()? {
+                return Ok(Some(frame));
+            }
+            if !self.fill(deadline)? {
+                return Ok(None);
+            }
+        }
+    }
+
+    // Removes the first complete frame from the buffer, if any
+    fn peek_frame(&mut self) -> Result<Option<(u8, Vec<u8>)>, String> {
+        let (flags, header_len) = match self.buffer.first() {
+            Some(flags) if flags & FLAG_LONG != 0 => (*flags, 9),
+            Some(flags) => (*flags, 2),
+            None => return Ok(None),
+        };
+        if self.buffer.len() < header_len {
+            return Ok(None);
+        }
+        let size = match header_len {
+            9 => u64::from_be_bytes(self.buffer[1..9].try_into().unwrap()),
+            _ => self.buffer[1] as u64,
+        };
+        if size > isize::MAX as u64 {
+            return Err(format!("frame of {} bytes is too large", size));
+        }
+        let end = header_len + size as usize;
+        if self.buffer.len() < end {
+            return Ok(None);
+        }
+        let body = self.buffer[header_len..end].to_vec();
+        self.buffer.drain(..end);
+        Ok(Some((flags, body)))
+    }
+
+    fn read_exact(&mut self, len: usize, deadline: Instant) -> Result<Vec<u8>, String> {
+        while self.buffer.len() < len {
+            if !self.fill(deadline)? {
+                return Err("timed out waiting for the peer".to_string());
+            }
+        }
+        Ok(self.buffer.drain(..len).collect())
+    }
+
+    // Reads available bytes into the buffer, returns false if the deadline passed first
+    fn fill(&mut self, deadline: Instant) -> Result<bool, String> {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+        self.stream.set_read_timeout(Some(remaining)).map_err(|e| e.to_string())?;
+        let mut chunk = [0u8; 8192];
+        match self.stream.read(&mut chunk) {
+            Ok(0) => Err(format!("Connection to {} closed", self.endpoint)),
+            Ok(read) => {
+                self.buffer.extend_from_slice(&chunk[..read]);
+                Ok(true)
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => Ok(Instant::now() < deadline),
+            Err(e) => Err(format!("Error while reading from {}: {}", self.endpoint, e)),
+        }
+    }
+}
+
+// Finds a property of a command, properties being a 1-byte name length, the name, a 4-byte value length and the value
+fn command_property(mut properties: &[u8], name: &str) -> Option<Vec<u8>> {
+    while let Some((&name_len, rest)) = properties.split_first() {
+        let name_len = name_len as usize;
+        let value_len = u32::from_be_bytes(rest.get(name_len..name_len + 4)?.try_into().unwrap()) as usize;
+        let value = rest.get(name_len + 4..name_len + 4 + value_len)?;
+        if rest[..name_len].eq_ignore_ascii_case(name.as_bytes()) {
+            return Some(value.to_vec());
+        }
+        properties = &rest[name_len + 4 + value_len..];
+    }
+    None
+}
+
+/// Decodes a message published by monerod, the topic name followed by `:` and the JSON payload
+/// Returns the event if succesfull
+/// Returns an error message if not succesfull
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{parse_zmq_message, ZmqEvent};
+///
+/// let message = format!(r#"json-minimal-chain_main:{{"first_height":3000000,"first_prev_id":"{}","ids":["{}"]}}"#, "aa".repeat(32), "bb".repeat(32));
+/// match parse_zmq_message(message.as_bytes()).unwrap() {
+///     ZmqEvent::ChainMain { first_height, ids, .. } => assert_eq!((first_height, ids.len()), (3000000, 1)),
+///     _ => panic!("unexpected event"),
+/// }
+/// ```
+pub fn parse_zmq_message(message: &[u8]) -> Result<ZmqEvent, String> {
+    let separator = message.iter().position(|&byte| byte == b':').ok_or("Error while parsing the ZMQ message: missing topic")?;
+    let topic = String::from_utf8_lossy(&message[..separator]);
+    let json: serde_json::Value =
+        serde_json::from_slice(&message[separator + 1..]).map_err(|e| format!("Error while parsing the {} message: {}", topic, e))?;
+    let parse = || -> Result<ZmqEvent, String> {
+        match topic.as_ref() {
+            "json-minimal-chain_main" => Ok(ZmqEvent::ChainMain {
+                first_height: json["first_height"].as_u64().ok_or("missing first_height")?,
+                first_prev_id: parse_bytes32(&json["first_prev_id"], BlockHash::from_hex, "first_prev_id")?,
+                ids: parse_bytes32_array(&json["ids"], BlockHash::from_hex, "ids")?,
+            }),
+            "json-full-txpool_add" => Ok(ZmqEvent::TxPoolAdd(
+                json.as_array().ok_or("transactions must be an array")?.iter().map(parse_zmq_transaction).collect::<Result<_, _>>()?,
+            )),
+            _ => Err("unsupported topic".to_string()),
+        }
+    };
+    parse().map_err(|e| format!("Error while parsing the {} message: {}", topic, e))
+}
+
+// Parses a transaction in monerod's JSON serialization, which differs from the one of the RPC interface
+fn parse_zmq_transaction(json: &serde_json::Value) -> Result<Transaction, String> {
+    let version = json["version"].as_u64().ok_or("missing version")?;
+    let mut inputs = Vec::new();
+    for input in json["inputs"].as_array().ok_or("missing inputs")? {
+        inputs.push(match (&input["to_key"], &input["gen"]) {
+            (to_key, _) if to_key.is_object() => TxInput::ToKey {
+                amount: to_key["amount"].as_u64().unwrap_or(0),
+                key_offsets: to_key["key_offsets"].as_array().ok_or("missing key_offsets")?.iter().map(|offset| offset.as_u64().ok_or("invalid key offset")).collect::<Result<_, _>>()?,
+                key_image: parse_bytes32(&to_key["key_image"], KeyImage::from_hex, "key_image")?,
+            },
+            (_, gen) if gen.is_object() => TxInput::Gen {
+                height: gen["height"].as_u64().ok_or("missing height")?,
+            },
+            _ => return Err("unsupported input type".to_string()),
+        });
+    }
+    let mut outputs = Vec::new();
+    for output in json["outputs"].as_array().ok_or("missing outputs")? {
+        let target = match (&output["to_tagged_key"], &output["to_key"]) {
+            (tagged, _) if tagged.is_object() => TxOutTarget::ToTaggedKey {
+                key: parse_bytes32(&tagged["key"], OneTimeKey::from_hex, "output key")?,
+                view_tag: match hex::decode(tagged["view_tag"].as_str().unwrap_or("")).as_deref() {
+                    Ok([view_tag]) => *view_tag,
+                    _ => return Err("invalid view tag".to_string()),
+                },
+            },
+            (_, key) if key.is_object() => TxOutTarget::ToKey {
+                key: parse_bytes32(&key["key"], OneTimeKey::from_hex, "output key")?,
+            },
+            _ => return Err("unsupported output type".to_string()),
+        };
+        outputs.push(TxOutput {
+            amount: output["amount"].as_u64().unwrap_or(0),
+            target,
+        });
+    }
+    // Extra is a hex string in recent versions, an array of bytes before
+    let extra = match &json["extra"] {
+        serde_json::Value::String(extra) => hex::decode(extra).map_err(|e| format!("invalid extra: {}", e))?,
+        extra => extra.as_array().ok_or("missing extra")?.iter().map(|byte| byte.as_u64().filter(|byte| *byte <= 0xff).map(|byte| byte as u8).ok_or("invalid extra")).collect::<Result<_, _>>()?,
+    };
+    let signatures = match version {
+        1 => TxSignatures::V1 { ring_signatures: Vec::new() },
+        _ => {
+            let ringct = &json["ringct"];
+            let rct_type = ringct["type"].as_u64().and_then(|code| u8::try_from(code).ok()).and_then(RctType::from_u8).ok_or("invalid RingCT type")?;
+            let mut encrypted_amounts = Vec::new();
+            let mut commitments = Vec::new();
+            if rct_type != RctType::Null {
+                for encrypted in ringct["encrypted"].as_array().ok_or("missing encrypted amounts")? {
+                    let mask = parse_bytes32(&encrypted["mask"], bytes32_from_hex, "mask")?;
+                    let amount = parse_bytes32(&encrypted["amount"], bytes32_from_hex, "amount")?;
+                    encrypted_amounts.push(match rct_type >= RctType::Bulletproof2 {
+                        true => EncryptedAmount::Compact { amount: amount[..8].try_into().unwrap() },
+                        false => EncryptedAmount::Full { mask, amount },
+                    });
+                }
+                commitments = parse_bytes32_array(&ringct["commitments"], Commitment::from_hex, "commitments")?;
+            }
+            TxSignatures::V2 {
+                base: RctBase {
+                    rct_type,
+                    fee: ringct["fee"].as_u64().unwrap_or(0),
+                    pseudo_outs: match rct_type {
+                        RctType::Simple => parse_bytes32_array(&ringct["prunable"]["pseudo_outs"], Commitment::from_hex, "pseudo_outs")?,
+                        _ => Vec::new(),
+                    },
+                    encrypted_amounts,
+                    commitments,
+                },
+                prunable: Vec::new(),
+            }
+        }
+    };
+    Ok(Transaction {
+        prefix: TransactionPrefix {
+            version,
+            unlock_time: json["unlock_time"].as_u64().unwrap_or(0),
+            inputs,
+            outputs,
+            extra,
+        },
+        signatures,
+    })
+}
+
+fn bytes32_from_hex(hex_str: &str) -> Result<[u8; 32], String> {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hex_str, &mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
@@ -0,0 +1,404 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+#![allow(non_snake_case)]
+
+use super::hash_to_point::hash_to_point;
+use super::keccak::{hash_to_scalar, keccak256};
+use crate::keys::commitment::H_POINT;
+use crate::keys::Commitment;
+use crate::utils::varint::write_varint;
+use curve25519_dalek::{
+    constants::{ED25519_BASEPOINT_POINT, ED25519_BASEPOINT_TABLE},
+    edwards::CompressedEdwardsY,
+    traits::{IsIdentity, MultiscalarMul, VartimeMultiscalarMul},
+    EdwardsPoint, Scalar,
+};
+use rand::RngCore;
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+// Bits proven per amount
+const N: usize = 64;
+// Most amounts proven by an aggregated proof
+const MAX_M: usize = 16;
+// Domain separators of monero-core's config.h
+const HASH_KEY_BULLETPROOF_PLUS_EXPONENT: &[u8] = b"bulletproof_plus";
+const HASH_KEY_BULLETPROOF_PLUS_TRANSCRIPT: &[u8] = b"bulletproof_plus_transcript";
+
+/// BulletproofPlus is an aggregated Bulletproofs+ range proof, proving that up to 16 commitments hide amounts that fit
+/// in 64 bits, the range proof of every RingCT transaction since the v15 hard fork
+///
+/// The commitments are not part of the proof, they are the output commitments of the transaction. Points are stored
+/// multiplied by 1/8, like every point of a transaction signature.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::bulletproof_plus_prove;
+/// use libmonero::keys::commit;
+/// use curve25519_dalek::Scalar;
+///
+/// let masks = [Scalar::from(7u64), Scalar::from(11u64)];
+/// let proof = bulletproof_plus_prove(&[1000, 2500000000000], &masks).unwrap();
+/// let commitments = [commit(1000, &masks[0]), commit(2500000000000, &masks[1])];
+/// assert!(proof.verify(&commitments));
+/// assert!(!proof.verify(&[commit(1001, &masks[0]), commitments[1]]));
+/// // 2 amounts need 7 rounds of 64 bits each
+/// assert_eq!(proof.L.len(), 7);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BulletproofPlus {
+    pub A: [u8; 32],
+    pub A1: [u8; 32],
+    pub B: [u8; 32],
+    pub r1: [u8; 32],
+    pub s1: [u8; 32],
+    pub d1: [u8; 32],
+    /// Left points of the inner product rounds
+    pub L: Vec<[u8; 32]>,
+    /// Right points of the inner product rounds
+    pub R: Vec<[u8; 32]>,
+}
+
+/// Proves that the commitments `commit(amounts[i], masks[i])` hide 64-bit amounts, monero-core's
+/// `bulletproof_plus_PROVE`
+///
+/// Returns the proof if succesfull
+/// Returns an error message if there are no amounts, more than 16 of them or not one mask per amount
+pub fn bulletproof_plus_prove(amounts: &[u64], masks: &[Scalar]) -> Result<BulletproofPlus, String> {
+    if amounts.is_empty() || amounts.len() > MAX_M {
+        return Err(format!("Range proofs prove 1 to {} amounts, got {}", MAX_M, amounts.len()));
+    }
+    if masks.len() != amounts.len() {
+        return Err(format!("Got {} masks for {} amounts", masks.len(), amounts.len()));
+    }
+    let inv_eight = Scalar::from(8u8).invert();
+    let V: Vec<[u8; 32]> = amounts
+        .iter()
+        .zip(masks.iter())
+        .map(|(amount, mask)| (ED25519_BASEPOINT_TABLE * &(mask * inv_eight) + h_point() * (Scalar::from(*amount) * inv_eight)).compress().to_bytes())
+        .collect();
+    // A zero challenge has a negligible probability, the reference prover starts over then
+    loop {
+        if let Some(proof) = try_prove(amounts, masks, &V) {
+            return Ok(proof);
+        }
+    }
+}
+
+fn try_prove(amounts: &[u64], gamma: &[Scalar], V: &[[u8; 32]]) -> Option<BulletproofPlus> {
+    let M = amounts.len().next_power_of_two();
+    let MN = M * N;
+    let inv_eight = Scalar::from(8u8).invert();
+    let (Gi, Hi) = generators();
+    let H = h_point();
+
+    // Bits of the amounts and the bits minus one, the padding amounts are zero
+    let mut aL = vec![Scalar::ZERO; MN];
+    let mut aR = vec![-Scalar::ONE; MN];
+    for (j, amount) in amounts.iter().enumerate() {
+        for i in 0..N {
+            if amount >> i & 1 == 1 {
+                aL[j * N + i] = Scalar::ONE;
+                aR[j * N + i] = Scalar::ZERO;
+            }
+        }
+    }
+
+    let mut transcript = transcript_update(&initial_transcript(), &[hash_to_scalar(&V.concat()).as_bytes()]);
+    let alpha = Zeroizing::new(random_scalar());
+    let A = multiexp(
+        aL.iter().chain(aR.iter()).map(|bit| bit * inv_eight).chain([*alpha * inv_eight]),
+        Gi[..MN].iter().chain(Hi[..MN].iter()).chain([&ED25519_BASEPOINT_POINT]),
+    );
+    let y = transcript_update(&transcript, &[&A]);
+    let z = hash_to_scalar(y.as_bytes());
+    transcript = z;
+    if y == Scalar::ZERO || z == Scalar::ZERO {
+        return None;
+    }
+    let z_squared = z * z;
+
+    let d = d_vector(&z, MN);
+    let y_powers = powers(&y, MN + 2);
+    let mut a: Vec<Scalar> = aL.iter().map(|bit| bit - z).collect();
+    let mut b: Vec<Scalar> = aR.iter().enumerate().map(|(i, bit)| bit + z + d[i] * y_powers[MN - i]).collect();
+    let mut alpha1 = Zeroizing::new(*alpha);
+    let mut z_power = Scalar::ONE;
+    for mask in gamma.iter() {
+        z_power *= z_squared;
+        *alpha1 += z_power * y_powers[MN + 1] * mask;
+    }
+
+    // Inner product rounds, halving the vectors and generators each time
+    let y_inv_powers = powers(&y.invert(), MN);
+    let mut G: Vec<EdwardsPoint> = Gi[..MN].to_vec();
+    let mut Hs: Vec<EdwardsPoint> = Hi[..MN].to_vec();
+    let (mut L, mut R) = (Vec::new(), Vec::new());
+    let mut n = MN;
+    while n > 1 {
+        n /= 2;
+        let cL = weighted_inner_product(&a[..n], &b[n..], &y_powers);
+        let cR = weighted_inner_product(&a[n..].iter().map(|value| value * y_powers[n]).collect::<Vec<_>>(), &b[..n], &y_powers);
+        let (dL, dR) = (Zeroizing::new(random_scalar()), Zeroizing::new(random_scalar()));
+        let round_L = compute_lr(&y_inv_powers[n], &G[n..], &Hs[..n], &a[..n], &b[n..], &cL, &dL);
+        let round_R = compute_lr(&y_powers[n], &G[..n], &Hs[n..], &a[n..], &b[..n], &cR, &dR);
+        let challenge = transcript_update(&transcript, &[&round_L, &round_R]);
+        transcript = challenge;
+        if challenge == Scalar::ZERO {
+            return None;
+        }
+        let challenge_inv = challenge.invert();
+        L.push(round_L);
+        R.push(round_R);
+
+        let g_factor = y_inv_powers[n] * challenge;
+        let a_factor = challenge_inv * y_powers[n];
+        for i in 0..n {
+            G[i] = EdwardsPoint::vartime_multiscalar_mul([challenge_inv, g_factor], [G[i], G[n + i]]);
+            Hs[i] = EdwardsPoint::vartime_multiscalar_mul([challenge, challenge_inv], [Hs[i], Hs[n + i]]);
+            a[i] = a[i] * challenge + a[n + i] * a_factor;
+            b[i] = b[i] * challenge_inv + b[n + i] * challenge;
+        }
+        G.truncate(n);
+        Hs.truncate(n);
+        a.truncate(n);
+        b.truncate(n);
+        *alpha1 += *dL * challenge * challenge + *dR * challenge_inv * challenge_inv;
+    }
+
+    // Final round
+    let (r, s, d_, eta) = (random_scalar(), random_scalar(), random_scalar(), random_scalar());
+    let A1 = multiexp(
+        [r * inv_eight, s * inv_eight, d_ * inv_eight, (r * y * b[0] + s * y * a[0]) * inv_eight],
+        [&G[0], &Hs[0], &ED25519_BASEPOINT_POINT, &H],
+    );
+    let B = multiexp([r * y * s * inv_eight, eta * inv_eight], [&H, &ED25519_BASEPOINT_POINT]);
+    let e = transcript_update(&transcript, &[&A1, &B]);
+    if e == Scalar::ZERO {
+        return None;
+    }
+    Some(BulletproofPlus {
+        A,
+        A1,
+        B,
+        r1: (a[0] * e + r).to_bytes(),
+        s1: (b[0] * e + s).to_bytes(),
+        d1: (eta + d_ * e + *alpha1 * e * e).to_bytes(),
+        L,
+        R,
+    })
+}
+
+impl BulletproofPlus {
+    /// Checks the proof against the commitments it was made for, monero-core's `bulletproof_plus_VERIFY` for a single
+    /// proof
+    pub fn verify(&self, commitments: &[Commitment]) -> bool {
+        let M = commitments.len().next_power_of_two();
+        let MN = M * N;
+        let rounds = MN.trailing_zeros() as usize;
+        if commitments.is_empty() || commitments.len() > MAX_M || self.L.len() != rounds || self.R.len() != rounds {
+            return false;
+        }
+        let scalar = |bytes: &[u8; 32]| Option::<Scalar>::from(Scalar::from_canonical_bytes(*bytes));
+        let (Some(r1), Some(s1), Some(d1)) = (scalar(&self.r1), scalar(&self.s1), scalar(&self.d1)) else {
+            return false;
+        };
+        // Points of the proof are stored multiplied by 1/8
+        let point = |bytes: &[u8; 32]| CompressedEdwardsY(*bytes).decompress().map(|point| point.mul_by_cofactor());
+        // The proven keys are the commitments multiplied by 1/8 too
+        let inv_eight = Scalar::from(8u8).invert();
+        let Some(V_bytes) = commitments
+            .iter()
+            .map(|commitment| CompressedEdwardsY(*commitment.as_bytes()).decompress().map(|point| (point * inv_eight).compress().to_bytes()))
+            .collect::<Option<Vec<[u8; 32]>>>()
+        else {
+            return false;
+        };
+        let Some(V) = V_bytes.iter().map(point).collect::<Option<Vec<_>>>() else {
+            return false;
+        };
+        let (Some(A), Some(A1), Some(B)) = (point(&self.A), point(&self.A1), point(&self.B)) else {
+            return false;
+        };
+        let (Some(L), Some(R)) = (self.L.iter().map(point).collect::<Option<Vec<_>>>(), self.R.iter().map(point).collect::<Option<Vec<_>>>()) else {
+            return false;
+        };
+
+        // Challenges of the transcript
+        let mut transcript = transcript_update(&initial_transcript(), &[hash_to_scalar(&V_bytes.concat()).as_bytes()]);
+        let y = transcript_update(&transcript, &[&self.A]);
+        let z = hash_to_scalar(y.as_bytes());
+        transcript = z;
+        let mut challenges = Vec::with_capacity(rounds);
+        for (round_L, round_R) in self.L.iter().zip(self.R.iter()) {
+            transcript = transcript_update(&transcript, &[round_L, round_R]);
+            challenges.push(transcript);
+        }
+        let e = transcript_update(&transcript, &[&self.A1, &self.B]);
+        if y == Scalar::ZERO || z == Scalar::ZERO || e == Scalar::ZERO || challenges.contains(&Scalar::ZERO) {
+            return false;
+        }
+
+        // e^2 * (A + sum(c^2 L + c^-2 R) + commitments and generators of the statement) + e * A1 + B must equal
+        // the final round opening r1 * e * G' + s1 * e * H' + r1 * y * s1 * H + d1 * G
+        let z_squared = z * z;
+        let e_squared = e * e;
+        let y_powers = powers(&y, MN + 2);
+        let y_inv_powers = powers(&y.invert(), MN);
+        let d = d_vector(&z, MN);
+        let challenges_inv: Vec<Scalar> = challenges.iter().map(|challenge| challenge.invert()).collect();
+        let mut scalars = Vec::with_capacity(2 * MN + 2 * rounds + V.len() + 5);
+        let mut points = Vec::with_capacity(scalars.capacity());
+        let (Gi, Hi) = generators();
+        let mut z_power = Scalar::ONE;
+        for V in V.iter() {
+            z_power *= z_squared;
+            scalars.push(e_squared * z_power * y_powers[MN + 1]);
+            points.push(*V);
+        }
+        let d_sum: Scalar = d.iter().sum();
+        for i in 0..MN {
+            let (mut g, mut h) = (Scalar::ONE, Scalar::ONE);
+            for k in 0..rounds {
+                let n = MN >> (k + 1);
+                if (i >> (rounds - 1 - k)) & 1 == 1 {
+                    g *= challenges[k] * y_inv_powers[n];
+                    h *= challenges_inv[k];
+                } else {
+                    g *= challenges_inv[k];
+                    h *= challenges[k];
+                }
+            }
+            scalars.push(-e_squared * z - r1 * e * g);
+            points.push(Gi[i]);
+            scalars.push(e_squared * (z + d[i] * y_powers[MN - i]) - s1 * e * h);
+            points.push(Hi[i]);
+        }
+        let y_sum: Scalar = y_powers[1..=MN].iter().sum();
+        scalars.push(e_squared * ((z - z_squared) * y_sum - z * y_powers[MN + 1] * d_sum) - r1 * y * s1);
+        points.push(h_point());
+        scalars.push(-d1);
+        points.push(ED25519_BASEPOINT_POINT);
+        scalars.extend([e_squared, e, Scalar::ONE]);
+        points.extend([A, A1, B]);
+        for k in 0..rounds {
+            scalars.push(e_squared * challenges[k] * challenges[k]);
+            points.push(L[k]);
+            scalars.push(e_squared * challenges_inv[k] * challenges_inv[k]);
+            points.push(R[k]);
+        }
+        EdwardsPoint::vartime_multiscalar_mul(scalars, points).is_identity()
+    }
+
+    /// Serializes the proof like in the prunable part of a transaction, the commitments are not included
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32 * (6 + 2 * self.L.len()) + 2);
+        for key in [&self.A, &self.A1, &self.B, &self.r1, &self.s1, &self.d1] {
+            out.extend_from_slice(key);
+        }
+        for points in [&self.L, &self.R] {
+            write_varint(points.len() as u64, &mut out);
+            points.iter().for_each(|point| out.extend_from_slice(point));
+        }
+        out
+    }
+
+    // Keys of the proof in serialization order without the counts, hashed by the message signed by the inputs
+    pub(crate) fn keys(&self) -> Vec<u8> {
+        [&[self.A, self.A1, self.B, self.r1, self.s1, self.d1], self.L.as_slice(), self.R.as_slice()].concat().concat()
+    }
+}
+
+fn h_point() -> EdwardsPoint {
+    CompressedEdwardsY(H_POINT).decompress().unwrap()
+}
+
+// Generators Gi and Hi of monero-core's `get_exponent`, `Hp(Keccak(H || "bulletproof_plus" || varint(index)))`
+// with even indices for Hi and odd ones for Gi
+fn generators() -> &'static (Vec<EdwardsPoint>, Vec<EdwardsPoint>) {
+    static GENERATORS: OnceLock<(Vec<EdwardsPoint>, Vec<EdwardsPoint>)> = OnceLock::new();
+    GENERATORS.get_or_init(|| {
+        let exponent = |index: usize| {
+            let mut data = [H_POINT.as_slice(), HASH_KEY_BULLETPROOF_PLUS_EXPONENT].concat();
+            write_varint(index as u64, &mut data);
+            hash_to_point(&keccak256(&data))
+        };
+        let Gi = (0..N * MAX_M).map(|i| exponent(2 * i + 1)).collect();
+        let Hi = (0..N * MAX_M).map(|i| exponent(2 * i)).collect();
+        (Gi, Hi)
+    })
+}
+
+// Hp(Keccak("bulletproof_plus_transcript")) read as a scalar
+fn initial_transcript() -> Scalar {
+    Scalar::from_bytes_mod_order(hash_to_point(&keccak256(HASH_KEY_BULLETPROOF_PLUS_TRANSCRIPT)).compress().to_bytes())
+}
+
+// Hs(transcript || updates)
+fn transcript_update(transcript: &Scalar, updates: &[&[u8; 32]]) -> Scalar {
+    let mut data = transcript.to_bytes().to_vec();
+    updates.iter().for_each(|update| data.extend_from_slice(*update));
+    hash_to_scalar(&data)
+}
+
+fn random_scalar() -> Scalar {
+    let mut bytes = Zeroizing::new([0u8; 64]);
+    rand::thread_rng().fill_bytes(&mut *bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+// x^0 to x^(count - 1)
+fn powers(x: &Scalar, count: usize) -> Vec<Scalar> {
+    let mut powers = Vec::with_capacity(count);
+    let mut power = Scalar::ONE;
+    for _ in 0..count {
+        powers.push(power);
+        power *= x;
+    }
+    powers
+}
+
+// d[j * N + i] = z^(2 * (j + 1)) * 2^i
+fn d_vector(z: &Scalar, MN: usize) -> Vec<Scalar> {
+    let z_squared = z * z;
+    let mut d = vec![z_squared; MN];
+    for i in 1..MN {
+        d[i] = match i % N {
+            0 => d[i - N] * z_squared,
+            _ => d[i - 1] + d[i - 1],
+        };
+    }
+    d
+}
+
+// sum(a[i] * b[i] * y^(i + 1))
+fn weighted_inner_product(a: &[Scalar], b: &[Scalar], y_powers: &[Scalar]) -> Scalar {
+    a.iter().zip(b.iter()).enumerate().map(|(i, (a, b))| a * b * y_powers[i + 1]).sum()
+}
+
+// sum(a[i] * y * G[i] + b[i] * H[i]) + c * H + d * G, multiplied by 1/8
+fn compute_lr(y: &Scalar, G: &[EdwardsPoint], Hs: &[EdwardsPoint], a: &[Scalar], b: &[Scalar], c: &Scalar, d: &Scalar) -> [u8; 32] {
+    let inv_eight = Scalar::from(8u8).invert();
+    let H = h_point();
+    multiexp(
+        a.iter().map(|a| a * y * inv_eight).chain(b.iter().map(|b| b * inv_eight)).chain([c * inv_eight, d * inv_eight]),
+        G.iter().chain(Hs.iter()).chain([&H, &ED25519_BASEPOINT_POINT]),
+    )
+}
+
+// Constant time multi-scalar multiplication, the scalars hide the amounts and masks
+fn multiexp<'a, S, P>(scalars: S, points: P) -> [u8; 32]
+where
+    S: IntoIterator<Item = Scalar>,
+    P: IntoIterator<Item = &'a EdwardsPoint>,
+{
+    EdwardsPoint::multiscalar_mul(scalars, points).compress().to_bytes()
+}
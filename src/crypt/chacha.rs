@@ -0,0 +1,126 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::cryptonight::{cn_slow_hash, CnVariant};
+use zeroize::{Zeroize, Zeroizing};
+
+// "expand 32-byte k"
+const SIGMA: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+/// Derives the ChaCha key of a wallet file from its password, monero-core's `generate_chacha_key`
+///
+/// The key is the CryptoNight (variant 0) hash of the password, hashed again `kdf_rounds - 1` times. The official
+/// wallets use 1 round unless started with `--kdf-rounds`.
+///
+/// Returns the key if succesfull
+/// Returns an error message if `kdf_rounds` is 0
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::{chacha20, generate_chacha_key};
+///
+/// let key = generate_chacha_key(b"password", 1).unwrap();
+/// let iv = [7u8; 8];
+/// let encrypted = chacha20(b"secret data", &key, &iv);
+/// assert_eq!(chacha20(&encrypted, &key, &iv), b"secret data");
+/// assert!(generate_chacha_key(b"password", 0).is_err());
+/// ```
+pub fn generate_chacha_key(password: &[u8], kdf_rounds: u64) -> Result<Zeroizing<[u8; 32]>, String> {
+    if kdf_rounds == 0 {
+        return Err("KDF rounds must be at least 1".to_string());
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    let mut hash = Zeroizing::new(cn_slow_hash(password, CnVariant::V0)?);
+    for _ in 1..kdf_rounds {
+        let previous = Zeroizing::new(hex::decode(hash.as_str()).map_err(|e| e.to_string())?);
+        hash = Zeroizing::new(cn_slow_hash(&previous, CnVariant::V0)?);
+    }
+    hex::decode_to_slice(hash.as_str(), &mut *key).map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// Encrypts or decrypts `data` with ChaCha8, the cipher of the wallet files written before v0.12
+///
+/// This is the original ChaCha of Bernstein with a 64-bit nonce (IV) and a 64-bit block counter starting at 0, the
+/// same call encrypts and decrypts.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::chacha8;
+///
+/// let keystream = chacha8(&[0u8; 8], &[0u8; 32], &[0u8; 8]);
+/// assert_eq!(hex::encode(keystream), "3e00ef2f895f40d6");
+/// ```
+pub fn chacha8(data: &[u8], key: &[u8; 32], iv: &[u8; 8]) -> Vec<u8> {
+    chacha(data, key, iv, 8)
+}
+
+/// Encrypts or decrypts `data` with ChaCha20, the cipher of the current wallet files, see [`chacha8`](fn.chacha8.html)
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::chacha20;
+///
+/// let keystream = chacha20(&[0u8; 8], &[0u8; 32], &[0u8; 8]);
+/// assert_eq!(hex::encode(keystream), "76b8e0ada0f13d90");
+/// ```
+pub fn chacha20(data: &[u8], key: &[u8; 32], iv: &[u8; 8]) -> Vec<u8> {
+    chacha(data, key, iv, 20)
+}
+
+fn chacha(data: &[u8], key: &[u8; 32], iv: &[u8; 8], rounds: usize) -> Vec<u8> {
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&SIGMA);
+    for (i, word) in key.chunks_exact(4).enumerate() {
+        input[4 + i] = u32::from_le_bytes(word.try_into().unwrap());
+    }
+    input[14] = u32::from_le_bytes(iv[..4].try_into().unwrap());
+    input[15] = u32::from_le_bytes(iv[4..].try_into().unwrap());
+
+    let mut out = Vec::with_capacity(data.len());
+    let mut keystream = Zeroizing::new([0u8; 64]);
+    for (counter, chunk) in data.chunks(64).enumerate() {
+        input[12] = counter as u32;
+        input[13] = ((counter as u64) >> 32) as u32;
+        chacha_block(&input, rounds, &mut keystream);
+        out.extend(chunk.iter().zip(keystream.iter()).map(|(byte, key)| byte ^ key));
+    }
+    input.zeroize();
+    out
+}
+
+fn chacha_block(input: &[u32; 16], rounds: usize, out: &mut [u8; 64]) {
+    let mut x = *input;
+    for _ in 0..rounds / 2 {
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 1, 5, 9, 13);
+        quarter_round(&mut x, 2, 6, 10, 14);
+        quarter_round(&mut x, 3, 7, 11, 15);
+        quarter_round(&mut x, 0, 5, 10, 15);
+        quarter_round(&mut x, 1, 6, 11, 12);
+        quarter_round(&mut x, 2, 7, 8, 13);
+        quarter_round(&mut x, 3, 4, 9, 14);
+    }
+    for (i, word) in x.iter().enumerate() {
+        out[4 * i..4 * i + 4].copy_from_slice(&word.wrapping_add(input[i]).to_le_bytes());
+    }
+    x.zeroize();
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
@@ -0,0 +1,266 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+#![allow(non_snake_case)]
+
+use super::hash_to_point::hash_to_point;
+use super::keccak::hash_to_scalar;
+use crate::keys::{Commitment, KeyImage, OneTimeKey, PrivateKey};
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY, traits::IsIdentity, EdwardsPoint, Scalar};
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+// Domain separators of monero-core's config.h, zero padded to 32 bytes
+const CLSAG_AGG_0: &[u8] = b"CLSAG_agg_0";
+const CLSAG_AGG_1: &[u8] = b"CLSAG_agg_1";
+const CLSAG_ROUND: &[u8] = b"CLSAG_round";
+
+/// Clsag is a Concise Linkable Spontaneous Anonymous Group signature, the ring signature of every RingCT input since
+/// the v15 hard fork
+///
+/// It proves that the signer knows the private key of one of the ring members, that the key image is the one of this
+/// member, and that the member's commitment and the pseudo output commitment hide the same amount, without revealing
+/// which member is spent. The key image and the pseudo output are stored elsewhere in the transaction.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::clsag_sign;
+/// use libmonero::keys::{commit, generate_key_image, Commitment, OneTimeKey, PrivateKey};
+/// use curve25519_dalek::Scalar;
+///
+/// let secret_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let decoy = PrivateKey::from_hex("f7b3beabc9bd6ced864096c0891a8fdf94dc714178a09828775dba01b4df9a08").unwrap();
+/// let (mask, pseudo_mask) = (Scalar::from(7u64), Scalar::from(11u64));
+/// let ring = vec![
+///     (OneTimeKey::from(decoy.public_key()), commit(5, &Scalar::from(3u64))),
+///     (OneTimeKey::from(secret_key.public_key()), commit(1000, &mask)),
+/// ];
+/// let pseudo_out = commit(1000, &pseudo_mask);
+/// let message = [0x42; 32];
+///
+/// let signature = clsag_sign(&message, &ring, 1, &secret_key, &mask, &pseudo_out, &pseudo_mask).unwrap();
+/// let key_image = generate_key_image(&secret_key, &secret_key.public_key());
+/// assert!(signature.verify(&message, &ring, &key_image, &pseudo_out));
+/// assert!(!signature.verify(&[0x43; 32], &ring, &key_image, &pseudo_out));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clsag {
+    /// Responses, one per ring member
+    pub s: Vec<[u8; 32]>,
+    /// Challenge of the first ring member
+    pub c1: [u8; 32],
+    /// Commitment key image `z * Hp(P)` multiplied by 1/8
+    pub D: [u8; 32],
+}
+
+/// Signs `message` with a CLSAG over the ring, monero-core's `CLSAG_Gen`
+///
+/// Arguments:
+/// - `message`: the message signed by every input, `get_pre_mlsag_hash` of the transaction
+/// - `ring`: the one-time keys and commitments of the ring members, ordered like the key offsets of the input
+/// - `real_index`: position of the spent output in the ring
+/// - `secret_key`: one-time private key of the spent output, see [`OwnedOutput::secret_key`](../keys/struct.OwnedOutput.html#method.secret_key)
+/// - `mask`: mask of the commitment of the spent output
+/// - `pseudo_out`: pseudo output commitment of the input, hiding the same amount as the spent output
+/// - `pseudo_mask`: mask of `pseudo_out`
+///
+/// Returns the signature if succesfull
+/// Returns an error message if the spent output is not at `real_index` or the masks do not open the commitments
+pub fn clsag_sign(
+    message: &[u8; 32],
+    ring: &[(OneTimeKey, Commitment)],
+    real_index: usize,
+    secret_key: &PrivateKey,
+    mask: &Scalar,
+    pseudo_out: &Commitment,
+    pseudo_mask: &Scalar,
+) -> Result<Clsag, String> {
+    let n = ring.len();
+    if real_index >= n {
+        return Err(format!("Real index {} is outside of the ring of {} members", real_index, n));
+    }
+    let (P, C) = decompress_ring(ring, pseudo_out).ok_or("Ring keys and commitments must be valid points")?;
+    let p = Zeroizing::new(secret_key.to_scalar());
+    if ED25519_BASEPOINT_TABLE * &*p != P[real_index] {
+        return Err("Secret key does not match the ring member at the real index".to_string());
+    }
+    // C[l] - C_offset = z * G, z is the mask difference between the spent output and the pseudo output
+    let z = Zeroizing::new(mask - pseudo_mask);
+    if ED25519_BASEPOINT_TABLE * &*z != C[real_index] {
+        return Err("Masks do not open the commitment of the spent output and the pseudo output".to_string());
+    }
+    if *z == Scalar::ZERO {
+        return Err("Pseudo output must not reuse the mask of the spent output".to_string());
+    }
+
+    let H = hash_to_point(ring[real_index].0.as_bytes());
+    let I = H * *p;
+    let D = H * *z;
+
+    let mut rng = rand::thread_rng();
+    let mut random_scalar = || {
+        let mut bytes = Zeroizing::new([0u8; 64]);
+        rng.fill_bytes(&mut *bytes);
+        Scalar::from_bytes_mod_order_wide(&bytes)
+    };
+    let a = Zeroizing::new(random_scalar());
+    let mut s: Vec<Scalar> = (0..n).map(|i| if i == real_index { Scalar::ZERO } else { random_scalar() }).collect();
+    let (mu_P, mu_C, c, c1) = clsag_challenges(message, ring, real_index, (&I, &D), pseudo_out, &s, (&(ED25519_BASEPOINT_TABLE * &*a), &(H * *a)))
+        .ok_or("Ring keys and commitments must be valid points")?;
+    s[real_index] = *a - c * (mu_P * *p + mu_C * *z);
+
+    Ok(Clsag {
+        s: s.iter().map(|s| s.to_bytes()).collect(),
+        c1: c1.to_bytes(),
+        D: (D * Scalar::from(8u8).invert()).compress().to_bytes(),
+    })
+}
+
+// Goes around the ring from the real member, which commits to its nonce a with L = a * G and R = a * Hp(P), the other
+// members answering with their response in `s`, monero-core's `CLSAG_Gen` without the final response. Both the key
+// image I and the commitment key image D are given in full.
+//
+// Returns the aggregation coefficients mu_P and mu_C, the challenge the response of the real member answers and the
+// challenge of the first member
+pub(crate) fn clsag_challenges(
+    message: &[u8; 32],
+    ring: &[(OneTimeKey, Commitment)],
+    real_index: usize,
+    (I, D): (&EdwardsPoint, &EdwardsPoint),
+    pseudo_out: &Commitment,
+    s: &[Scalar],
+    (L, R): (&EdwardsPoint, &EdwardsPoint),
+) -> Option<(Scalar, Scalar, Scalar, Scalar)> {
+    let n = ring.len();
+    if real_index >= n || s.len() != n {
+        return None;
+    }
+    let (P, C) = decompress_ring(ring, pseudo_out)?;
+    let D_inv8 = (D * Scalar::from(8u8).invert()).compress().to_bytes();
+    let (mu_P, mu_C) = aggregation_coefficients(ring, &I.compress().to_bytes(), &D_inv8, pseudo_out);
+    let round = round_prefix(ring, pseudo_out, message);
+
+    let mut c = round_challenge(&round, L, R);
+    let mut c1 = Scalar::ZERO;
+    let mut i = (real_index + 1) % n;
+    if i == 0 {
+        c1 = c;
+    }
+    while i != real_index {
+        let (c_p, c_c) = (mu_P * c, mu_C * c);
+        let L = ED25519_BASEPOINT_TABLE * &s[i] + P[i] * c_p + C[i] * c_c;
+        let R = hash_to_point(ring[i].0.as_bytes()) * s[i] + I * c_p + D * c_c;
+        c = round_challenge(&round, &L, &R);
+        i = (i + 1) % n;
+        if i == 0 {
+            c1 = c;
+        }
+    }
+    Some((mu_P, mu_C, c, c1))
+}
+
+impl Clsag {
+    /// Checks the signature of `message` over the ring, monero-core's `verRctCLSAGSimple`
+    ///
+    /// Arguments are the ones of [`clsag_sign`](fn.clsag_sign.html), `key_image` and `pseudo_out` being the ones
+    /// stored in the transaction for this input. Verifiers must also check that the key image is not already on chain.
+    pub fn verify(&self, message: &[u8; 32], ring: &[(OneTimeKey, Commitment)], key_image: &KeyImage, pseudo_out: &Commitment) -> bool {
+        let n = ring.len();
+        if n == 0 || self.s.len() != n {
+            return false;
+        }
+        let Some((P, C)) = decompress_ring(ring, pseudo_out) else {
+            return false;
+        };
+        let (Some(I), Some(D_inv8)) = (decompress(key_image.as_bytes()), decompress(&self.D)) else {
+            return false;
+        };
+        // Key images outside of the prime order subgroup would let the same output be spent several times
+        if !I.is_torsion_free() || I.is_identity() {
+            return false;
+        }
+        let D = D_inv8.mul_by_cofactor();
+        if D.is_identity() {
+            return false;
+        }
+        let Some(c1) = Option::<Scalar>::from(Scalar::from_canonical_bytes(self.c1)) else {
+            return false;
+        };
+        let Some(s) = self.s.iter().map(|s| Option::<Scalar>::from(Scalar::from_canonical_bytes(*s))).collect::<Option<Vec<Scalar>>>() else {
+            return false;
+        };
+
+        let (mu_P, mu_C) = aggregation_coefficients(ring, key_image.as_bytes(), &self.D, pseudo_out);
+        let round = round_prefix(ring, pseudo_out, message);
+        let mut c = c1;
+        for i in 0..n {
+            let (c_p, c_c) = (mu_P * c, mu_C * c);
+            let L = ED25519_BASEPOINT_TABLE * &s[i] + P[i] * c_p + C[i] * c_c;
+            let R = hash_to_point(ring[i].0.as_bytes()) * s[i] + I * c_p + D * c_c;
+            c = round_challenge(&round, &L, &R);
+        }
+        c == c1
+    }
+}
+
+fn decompress(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    CompressedEdwardsY(*bytes).decompress()
+}
+
+// Ring keys and ring commitments minus the pseudo output as points
+fn decompress_ring(ring: &[(OneTimeKey, Commitment)], pseudo_out: &Commitment) -> Option<(Vec<EdwardsPoint>, Vec<EdwardsPoint>)> {
+    let C_offset = decompress(pseudo_out.as_bytes())?;
+    let mut P = Vec::with_capacity(ring.len());
+    let mut C = Vec::with_capacity(ring.len());
+    for (key, commitment) in ring.iter() {
+        P.push(decompress(key.as_bytes())?);
+        C.push(decompress(commitment.as_bytes())? - C_offset);
+    }
+    Some((P, C))
+}
+
+fn domain(separator: &[u8]) -> [u8; 32] {
+    let mut padded = [0u8; 32];
+    padded[..separator.len()].copy_from_slice(separator);
+    padded
+}
+
+// mu_P = Hs(agg_0 || P || C || I || D/8 || C_offset) and mu_C, the same hash with agg_1
+fn aggregation_coefficients(ring: &[(OneTimeKey, Commitment)], key_image: &[u8; 32], D_inv8: &[u8; 32], pseudo_out: &Commitment) -> (Scalar, Scalar) {
+    let mut data = Vec::with_capacity(32 * (2 * ring.len() + 4));
+    data.extend_from_slice(&domain(CLSAG_AGG_0));
+    ring.iter().for_each(|(key, _)| data.extend_from_slice(key.as_bytes()));
+    ring.iter().for_each(|(_, commitment)| data.extend_from_slice(commitment.as_bytes()));
+    data.extend_from_slice(key_image);
+    data.extend_from_slice(D_inv8);
+    data.extend_from_slice(pseudo_out.as_bytes());
+    let mu_P = hash_to_scalar(&data);
+    data[..32].copy_from_slice(&domain(CLSAG_AGG_1));
+    (mu_P, hash_to_scalar(&data))
+}
+
+// round || P || C || C_offset || message, the part of the round hashes shared by every member
+fn round_prefix(ring: &[(OneTimeKey, Commitment)], pseudo_out: &Commitment, message: &[u8; 32]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(32 * (2 * ring.len() + 5));
+    data.extend_from_slice(&domain(CLSAG_ROUND));
+    ring.iter().for_each(|(key, _)| data.extend_from_slice(key.as_bytes()));
+    ring.iter().for_each(|(_, commitment)| data.extend_from_slice(commitment.as_bytes()));
+    data.extend_from_slice(pseudo_out.as_bytes());
+    data.extend_from_slice(message);
+    data
+}
+
+fn round_challenge(prefix: &[u8], L: &EdwardsPoint, R: &EdwardsPoint) -> Scalar {
+    let mut data = Vec::with_capacity(prefix.len() + 64);
+    data.extend_from_slice(prefix);
+    data.extend_from_slice(L.compress().as_bytes());
+    data.extend_from_slice(R.compress().as_bytes());
+    hash_to_scalar(&data)
+}
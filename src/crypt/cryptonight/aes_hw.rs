@@ -0,0 +1,70 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// AES rounds on the AES instructions of the CPU (AES-NI on x86, the cryptography extension on ARMv8).
+// aes_round must only be called once hardware_aes_available returned true.
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{__m128i, _mm_aesenc_si128, _mm_loadu_si128, _mm_storeu_si128};
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{__m128i, _mm_aesenc_si128, _mm_loadu_si128, _mm_storeu_si128};
+#[cfg(target_arch = "aarch64")]
+use std::arch::aarch64::{vaeseq_u8, vaesmcq_u8, vdupq_n_u8, veorq_u8, vld1q_u8, vst1q_u8};
+
+/// Checks if the CPU has the AES instructions `cn_slow_hash` uses instead of the software AES rounds
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::cryptonight::hardware_aes_available;
+///
+/// println!("Hardware AES: {}", hardware_aes_available());
+/// ```
+pub fn hardware_aes_available() -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        std::arch::is_x86_feature_detected!("aes") && std::arch::is_x86_feature_detected!("sse2")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("aes")
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+// Same round as the software aes_round, SubBytes, ShiftRows, MixColumns then the round key
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+#[inline]
+pub(crate) fn aes_round(block: &mut [u8], round_key: &[u8]) {
+    let block: &mut [u8; 16] = block.try_into().unwrap();
+    let round_key: &[u8; 16] = round_key.try_into().unwrap();
+    // SAFETY: only selected by slow_hash after hardware_aes_available confirmed the CPU features
+    unsafe { aes_round_hw(block, round_key) }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "aes,sse2")]
+unsafe fn aes_round_hw(block: &mut [u8; 16], round_key: &[u8; 16]) {
+    // AESENC is exactly the CryptoNight round
+    let state = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+    let key = _mm_loadu_si128(round_key.as_ptr() as *const __m128i);
+    _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, _mm_aesenc_si128(state, key));
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "aes")]
+unsafe fn aes_round_hw(block: &mut [u8; 16], round_key: &[u8; 16]) {
+    // AESE xors its key before SubBytes and ShiftRows, so it runs with a zero key and the round key is xored after AESMC
+    let state = vaeseq_u8(vld1q_u8(block.as_ptr()), vdupq_n_u8(0));
+    let state = veorq_u8(vaesmcq_u8(state), vld1q_u8(round_key.as_ptr()));
+    vst1q_u8(block.as_mut_ptr(), state);
+}
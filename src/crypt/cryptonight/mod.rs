@@ -8,8 +8,11 @@
  *
  */
 
+pub(crate) mod aes_hw;
 pub(crate) mod aesu;
 pub(crate) mod otheru;
+pub(crate) mod random_math;
 pub(crate) mod slow_hash;
 
+pub use aes_hw::hardware_aes_available;
 pub use slow_hash::*;
\ No newline at end of file
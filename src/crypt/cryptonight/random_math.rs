@@ -0,0 +1,258 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// Random math of CryptonightR (variant 4), a program of 60 to 70 instructions over 32-bit registers generated
+// from the block height and executed once per iteration of the memory-hard loop.
+// Registers R0-R3 are variable, R4-R8 are loaded from the loop variables before each execution.
+
+use blake_hash::{Blake256, Digest};
+
+// Minimal latency of the generated program on the abstract CPU, the latency of 15 multiplications
+const TOTAL_LATENCY: usize = 15 * 3;
+const NUM_INSTRUCTIONS_MIN: usize = 60;
+const NUM_INSTRUCTIONS_MAX: usize = 70;
+// Only one ALU can multiply and 3 of the 4 ALUs of modern CPUs are left for the random math
+const ALU_COUNT_MUL: usize = 1;
+const ALU_COUNT: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Opcode {
+    Mul,
+    Add,
+    Sub,
+    Ror,
+    Rol,
+    Xor,
+}
+
+impl Opcode {
+    // Latency on Intel CPUs from Sandy Bridge up to Coffee Lake
+    fn latency(self) -> usize {
+        match self {
+            Opcode::Mul => 3,
+            Opcode::Add | Opcode::Ror | Opcode::Rol => 2,
+            Opcode::Sub | Opcode::Xor => 1,
+        }
+    }
+
+    // Latency on a theoretical ASIC
+    fn asic_latency(self) -> usize {
+        match self {
+            Opcode::Mul => 3,
+            _ => 1,
+        }
+    }
+
+    fn alus(self) -> usize {
+        match self {
+            Opcode::Mul => ALU_COUNT_MUL,
+            _ => ALU_COUNT,
+        }
+    }
+
+    fn is_rotation(self) -> bool {
+        matches!(self, Opcode::Ror | Opcode::Rol)
+    }
+
+    // Index in the original opcode list, recorded in the instruction history of a register
+    fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Instruction {
+    opcode: Opcode,
+    dst: usize,
+    src: usize,
+    constant: u32,
+}
+
+// Random bytes of the generator, refilled with Blake-256 of themselves once used up
+struct RandomData {
+    data: [u8; 32],
+    index: usize,
+}
+
+impl RandomData {
+    fn new(height: u64) -> RandomData {
+        let mut data = [0u8; 32];
+        data[0..8].copy_from_slice(&height.to_le_bytes());
+        data[20] = -38i8 as u8;
+        // Starting past the end hashes the seed before its first use
+        RandomData { data, index: 32 }
+    }
+
+    fn take(&mut self, len: usize) -> &[u8] {
+        if self.index + len > self.data.len() {
+            let hash = Blake256::digest(&self.data[..]);
+            self.data.copy_from_slice(&hash);
+            self.index = 0;
+        }
+        self.index += len;
+        &self.data[self.index - len..self.index]
+    }
+}
+
+// Generates the program of the given height, retrying in the rare cases where R8 ends up unused
+pub(crate) fn generate_program(height: u64) -> Vec<Instruction> {
+    let mut data = RandomData::new(height);
+    loop {
+        let (code, r8_used) = generate_attempt(&mut data);
+        if r8_used && (NUM_INSTRUCTIONS_MIN..=NUM_INSTRUCTIONS_MAX).contains(&code.len()) {
+            return code;
+        }
+    }
+}
+
+fn generate_attempt(data: &mut RandomData) -> (Vec<Instruction>, bool) {
+    let mut code: Vec<Instruction> = Vec::with_capacity(NUM_INSTRUCTIONS_MAX);
+    let mut latency = [0usize; 9];
+    let mut asic_latency = [0usize; 9];
+    // Per register: byte 0 is the value of the destination, byte 1 the last opcode and byte 2 the value of the source.
+    // R4-R8 never change, so they share the same value.
+    let mut inst_data: [u32; 9] = [0, 1, 2, 3, 0xFFFFFF, 0xFFFFFF, 0xFFFFFF, 0xFFFFFF, 0xFFFFFF];
+    let mut alu_busy = [[false; ALU_COUNT]; TOTAL_LATENCY + 1];
+    let mut rotated = [false; 4];
+    let mut rotate_count = 0;
+    let mut num_retries = 0;
+    let mut total_iterations = 0;
+    let mut r8_used = false;
+
+    while latency[0..4].iter().any(|&l| l < TOTAL_LATENCY) && num_retries < 64 {
+        total_iterations += 1;
+        if total_iterations > 256 {
+            break;
+        }
+
+        let c = data.take(1)[0];
+        let opcode = match c & 7 {
+            0..=2 => Opcode::Mul,
+            3 => Opcode::Add,
+            4 => Opcode::Sub,
+            5 => match (data.take(1)[0] as i8) >= 0 {
+                true => Opcode::Ror,
+                false => Opcode::Rol,
+            },
+            _ => Opcode::Xor,
+        };
+        let dst = ((c >> 3) & 3) as usize;
+        let mut src = ((c >> 5) & 7) as usize;
+
+        // ADD, SUB and XOR of a register with itself use R8 instead
+        if matches!(opcode, Opcode::Add | Opcode::Sub | Opcode::Xor) && dst == src {
+            src = 8;
+        }
+        // Two rotations of the same register in a row are a single rotation
+        if opcode.is_rotation() && rotated[dst] {
+            continue;
+        }
+        // Repeating anything but MUL with the same source value can be folded into one instruction
+        if opcode != Opcode::Mul && (inst_data[dst] & 0xFFFF00) == (opcode.code() << 8) + ((inst_data[src] & 255) << 16) {
+            continue;
+        }
+
+        // Find the first cycle with a free ALU for this instruction
+        let mut next_latency = latency[dst].max(latency[src]);
+        let mut alu_index = None;
+        while next_latency < TOTAL_LATENCY {
+            for i in (0..opcode.alus()).rev() {
+                if alu_busy[next_latency][i] {
+                    continue;
+                }
+                // ADD takes two 1-cycle instructions on a real CPU
+                if opcode == Opcode::Add && alu_busy[next_latency + 1][i] {
+                    continue;
+                }
+                // A rotation starts only once the previous one finished
+                if opcode.is_rotation() && next_latency < rotate_count * opcode.latency() {
+                    continue;
+                }
+                alu_index = Some(i);
+                break;
+            }
+            if alu_index.is_some() {
+                break;
+            }
+            next_latency += 1;
+        }
+
+        // Don't leave a register unchanged for more than 7 cycles
+        if next_latency > latency[dst] + 7 {
+            continue;
+        }
+        next_latency += opcode.latency();
+
+        if next_latency > TOTAL_LATENCY {
+            num_retries += 1;
+            continue;
+        }
+        // next_latency stayed below TOTAL_LATENCY, so an ALU was found
+        let alu_index = alu_index.unwrap();
+        if opcode.is_rotation() {
+            rotate_count += 1;
+        }
+        // ALUs are pipelined and only busy during the first cycle of the instruction
+        alu_busy[next_latency - opcode.latency()][alu_index] = true;
+        latency[dst] = next_latency;
+        asic_latency[dst] = asic_latency[dst].max(asic_latency[src]) + opcode.asic_latency();
+        rotated[dst] = opcode.is_rotation();
+        inst_data[dst] = code.len() as u32 + (opcode.code() << 8) + ((inst_data[src] & 255) << 16);
+        if src == 8 {
+            r8_used = true;
+        }
+
+        let mut constant = 0;
+        if opcode == Opcode::Add {
+            alu_busy[next_latency - opcode.latency() + 1][alu_index] = true;
+            constant = u32::from_le_bytes(data.take(4).try_into().unwrap());
+        }
+        code.push(Instruction { opcode, dst, src, constant });
+        if code.len() >= NUM_INSTRUCTIONS_MIN {
+            break;
+        }
+    }
+
+    // An ASIC extracts more parallelism, so pad with ROR, MUL, MUL until one register reaches the latency on an ASIC
+    let prev_code_size = code.len();
+    while code.len() < NUM_INSTRUCTIONS_MAX && asic_latency[0..4].iter().all(|&l| l < TOTAL_LATENCY) {
+        let mut min_idx = 0;
+        let mut max_idx = 0;
+        for i in 1..4 {
+            if asic_latency[i] < asic_latency[min_idx] {
+                min_idx = i;
+            }
+            if asic_latency[i] > asic_latency[max_idx] {
+                max_idx = i;
+            }
+        }
+        let opcode = [Opcode::Ror, Opcode::Mul, Opcode::Mul][(code.len() - prev_code_size) % 3];
+        latency[min_idx] = latency[max_idx] + opcode.latency();
+        asic_latency[min_idx] = asic_latency[max_idx] + opcode.asic_latency();
+        code.push(Instruction { opcode, dst: min_idx, src: max_idx, constant: 0 });
+    }
+
+    (code, r8_used)
+}
+
+pub(crate) fn execute_program(code: &[Instruction], r: &mut [u32; 9]) {
+    for instruction in code {
+        let src = r[instruction.src];
+        let dst = &mut r[instruction.dst];
+        match instruction.opcode {
+            Opcode::Mul => *dst = dst.wrapping_mul(src),
+            Opcode::Add => *dst = dst.wrapping_add(src.wrapping_add(instruction.constant)),
+            Opcode::Sub => *dst = dst.wrapping_sub(src),
+            Opcode::Ror => *dst = dst.rotate_right(src % 32),
+            Opcode::Rol => *dst = dst.rotate_left(src % 32),
+            Opcode::Xor => *dst ^= src,
+        }
+    }
+}
@@ -8,11 +8,54 @@
  *
  */
 
-use sha3::{Keccak256Full, Digest};
-use super::{aesu::derive_key, otheru::{add_pair_u64_2, blake256_hash, groestl256_hash, jh256_hash, mul_pair_u64_2, skein256_hash, turn_to_u64, turn_to_u64_2, turn_to_u8_16, xor_pair_u64_2}};
+use super::{aesu::derive_key, otheru::{add_pair_u64_2, blake256_hash, groestl256_hash, jh256_hash, mul_pair_u64_2, skein256_hash, turn_to_u64, turn_to_u64_2, turn_to_u8_16, xor_pair_u64_2}, random_math::{execute_program, generate_program, Instruction}};
 use crate::crypt::cryptonight::aesu::{aes_round, xor};
+use crate::crypt::keccak::{keccak1600, keccakf};
 
 const SCRATCHPAD_SIZE: usize = 2 * 1024 * 1024; // 2 MiB
+const SCRATCHPAD_BLOCKS: usize = SCRATCHPAD_SIZE / 16;
+
+/// CnVariant is the CryptoNight variant Monero used for proof of work between two hard forks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CnVariant {
+    /// Original CryptoNight, blocks of major version 1 to 6
+    V0,
+    /// CryptoNight v7, blocks of major version 7, tweaking the scratchpad writes with bytes 35..43 of the input
+    V1,
+    /// CryptoNight v8, blocks of major versions 8 and 9, adding a shuffle of the scratchpad, a division and a square root
+    V2,
+    /// CryptonightR, blocks of major versions 10 and 11, replacing the division and square root of v8 with a random
+    /// program generated from the height of the block
+    R {
+        /// Height of the block being hashed
+        height: u64,
+    },
+}
+
+impl CnVariant {
+    /// Returns the variant hashing blocks of the given major version at the given height
+    ///
+    /// Fails for major version 0 and for blocks from major version 12 on, which are hashed with RandomX.
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::crypt::cryptonight::CnVariant;
+    ///
+    /// assert_eq!(CnVariant::from_major_version(7, 1546000).unwrap(), CnVariant::V1);
+    /// assert_eq!(CnVariant::from_major_version(10, 1788000).unwrap(), CnVariant::R { height: 1788000 });
+    /// assert!(CnVariant::from_major_version(12, 1978433).is_err());
+    /// ```
+    pub fn from_major_version(major_version: u64, height: u64) -> Result<CnVariant, String> {
+        match major_version {
+            1..=6 => Ok(CnVariant::V0),
+            7 => Ok(CnVariant::V1),
+            8 | 9 => Ok(CnVariant::V2),
+            10 | 11 => Ok(CnVariant::R { height }),
+            0 => Err("Invalid block major version 0".to_string()),
+            _ => Err(format!("Blocks of major version {} are hashed with RandomX", major_version)),
+        }
+    }
+}
 
 /// EXPERIMENTAL! Main CryptoNight function defined in: <https://web.archive.org/web/20190911221902/https://cryptonote.org/cns/cns008.txt>
 /// 
@@ -25,193 +68,380 @@ const SCRATCHPAD_SIZE: usize = 2 * 1024 * 1024; // 2 MiB
 /// assert_eq!(output, "a084f01d1437a09c6985401b60d43554ae105802c5f5d8a9b3253649c0be6605".to_string());
 /// ```
 pub fn cn_slow_hash_v0(input: &[u8]) -> String {
-    // CryptoNight Step 1: Initialization Of Scratchpad
+    hex::encode(CnHasher::new().slow_hash(input, CnVariant::V0))
+}
 
-    // First, the input is hashed using Keccak [KECCAK] with parameters b =
-    // 1600 and c = 512. The bytes 0..31 of the Keccak final state are
-    // interpreted as an AES-256 key [AES] and expanded to 10 round keys. A
-    // scratchpad of 2097152 bytes (2 MiB) is allocated. The bytes 64..191
-    // are extracted from the Keccak final state and split into 8 blocks of
-    // 16 bytes each. Each block is encrypted using the following procedure:
+/// EXPERIMENTAL! CryptoNight function of any variant Monero used before RandomX
+///
+/// AES rounds run on the AES instructions of the CPU when [`hardware_aes_available`](fn.hardware_aes_available.html),
+/// in software otherwise. Fails if the input of variant 1 is shorter than 43 bytes, the variant reads its tweak from
+/// bytes 35..43.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::cryptonight::{cn_slow_hash, CnVariant};
+///
+/// let input: &[u8] = b"This is a test This is a test This is a test";
+/// let output: String = cn_slow_hash(input, CnVariant::V2).unwrap();
+/// assert_eq!(output, "353fdc068fd47b03c04b9431e005e00b68c2168a3cc7335c8b9b308156591a4f".to_string());
+/// assert!(cn_slow_hash(b"This is a test", CnVariant::V1).is_err());
+/// ```
+pub fn cn_slow_hash(input: &[u8], variant: CnVariant) -> Result<String, String> {
+    check_input(input, variant)?;
+    CnHasher::new().hash(input, variant)
+}
 
-    //    for i = 0..9 do:
-    //        block = aes_round(block, round_keys[i])
-    
-    // Where aes_round function performs a round of AES encryption, which
-    // means that SubBytes, ShiftRows and MixColumns steps are performed on
-    // the block, and the result is XORed with the round key. Note that
-    // unlike in the AES encryption algorithm, the first and the last rounds
-    // are not special. The resulting blocks are written into the first 128
-    // bytes of the scratchpad. Then, these blocks are encrypted again in
-    // the same way, and the result is written into the second 128 bytes of
-    // the scratchpad. Each time 128 bytes are written, they represent the
-    // result of the encryption of the previously written 128 bytes. The
-    // process is repeated until the scratchpad is fully initialized.
-
-    // Step 1A: Initialize the scratchpad with empty data
-    let mut scratchpad = [0u8; SCRATCHPAD_SIZE];
-
-    // Step 1B: Use Keccak256Full to hash the input
-    let mut keccak_hash = [0u8; 200];
-    let mut hasher = Keccak256Full::new();
-    hasher.update(input);
-    keccak_hash.copy_from_slice(&hasher.finalize());
-
-    // Step 1C: Use the first 32 bytes of the Keccak hash as an AES-256 key and expand it into 10 round keys
-    let aes_key = &keccak_hash[0..32];
-    let round_keys = derive_key(aes_key);
-
-    // Step 1D: Use bytes 64..191 of the Keccak hash as 8 blocks of 16 bytes each
-    let mut blocks = [0u8; 128];
-    blocks.copy_from_slice(&keccak_hash[64..192]);
-
-    // Step 1E: Loop until scratchpad is fully initialized
-    for scratchpad_chunk in scratchpad.chunks_exact_mut(blocks.len()) {
-        for block in blocks.chunks_exact_mut(16) {
-            for key in round_keys.chunks_exact(16) {
-                aes_round(block, key);
-            }
-        }
+/// EXPERIMENTAL! Same as [`cn_slow_hash`](fn.cn_slow_hash.html) with software AES rounds whatever the CPU supports,
+/// much slower but useful to check the hardware path
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::cryptonight::{cn_slow_hash, cn_slow_hash_software, CnVariant};
+///
+/// let input: &[u8] = b"This is a test";
+/// assert_eq!(cn_slow_hash_software(input, CnVariant::V0).unwrap(), cn_slow_hash(input, CnVariant::V0).unwrap());
+/// ```
+pub fn cn_slow_hash_software(input: &[u8], variant: CnVariant) -> Result<String, String> {
+    check_input(input, variant)?;
+    Ok(hex::encode(CnHasher::new().slow_hash_with(input, variant, aes_round)))
+}
 
-        scratchpad_chunk.copy_from_slice(&blocks);
+// Variant 1 reads its tweak from bytes 35..43 of the input
+fn check_input(input: &[u8], variant: CnVariant) -> Result<(), String> {
+    if variant == CnVariant::V1 && input.len() < 43 {
+        return Err(format!("CryptoNight variant 1 needs at least 43 bytes of input, got {}", input.len()));
     }
+    Ok(())
+}
 
-    // Cryptonight Step 2: Memory-hard Loop
-
-    // Prior to the main loop, bytes 0..31 and 32..63 of the Keccak state
-    // are XORed, and the resulting 32 bytes are used to initialize
-    // variables a and b, 16 bytes each. These variables are used in the
-    // main loop. The main loop is iterated 524,288 times. When a 16-byte
-    // value needs to be converted into an address in the scratchpad, it is
-    // interpreted as a little-endian integer, and the 21 low-order bits are
-    // used as a byte index. However, the 4 low-order bits of the index are
-    // cleared to ensure the 16-byte alignment. The data is read from and
-    // written to the scratchpad in 16-byte blocks. Each iteration can be
-    // expressed with the following pseudo-code:
-
-    //     scratchpad_address = to_scratchpad_address(a)
-    //     scratchpad[scratchpad_address] = aes_round(scratchpad
-    //     [scratchpad_address], a)
-    //     b, scratchpad[scratchpad_address] = scratchpad[scratchpad_address],
-    //     b xor scratchpad[scratchpad_address]
-    //     scratchpad_address = to_scratchpad_address(b)
-    //     a = 8byte_add(a, 8byte_mul(b, scratchpad[scratchpad_address]))
-    //     a, scratchpad[scratchpad_address] = a xor
-    //     scratchpad[scratchpad_address], a
-
-    // Where, the 8byte_add function represents each of the arguments as a
-    // pair of 64-bit little-endian values and adds them together,
-    // component-wise, modulo 2^64. The result is converted back into 16
-    // bytes.
-
-    // The 8byte_mul function, however, uses only the first 8 bytes of each
-    // argument, which are interpreted as unsigned 64-bit little-endian
-    // integers and multiplied together. The result is converted into 16
-    // bytes, and finally the two 8-byte halves of the result are swapped.
-
-    // Step 2A: Turn [u8; 200] into [[u64; 2]; 131072] for easier access
-    let mut sp_u64_2 = [[0u64; 2]; 131072];
-    for (i, sp_u64_2_chunk) in sp_u64_2.iter_mut().enumerate() {
-        let u64_slice = unsafe {
-            std::slice::from_raw_parts(scratchpad[i * 16..(i + 1) * 16].as_ptr() as *const u64, 2)
-        };
-        sp_u64_2_chunk.copy_from_slice(u64_slice);
+/// CnHasher is a reusable CryptoNight context owning the heap-allocated scratchpad
+///
+/// [`cn_slow_hash`](fn.cn_slow_hash.html) creates one per call, hashing many blobs with the same hasher saves
+/// allocating the 2 MiB scratchpad every time. The program of CryptonightR is kept for the next hash at the same height.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::cryptonight::{CnHasher, CnVariant};
+///
+/// let mut hasher = CnHasher::new();
+/// let output: String = hasher.hash(b"This is a test", CnVariant::V0).unwrap();
+/// assert_eq!(output, "a084f01d1437a09c6985401b60d43554ae105802c5f5d8a9b3253649c0be6605".to_string());
+/// assert!(hasher.hash(b"This is a test", CnVariant::V1).is_err());
+/// ```
+pub struct CnHasher {
+    // 16-byte blocks of the scratchpad as pairs of little-endian u64
+    scratchpad: Box<[[u64; 2]]>,
+    program: Option<(u64, Vec<Instruction>)>,
+}
+
+impl Default for CnHasher {
+    fn default() -> CnHasher {
+        CnHasher::new()
     }
+}
 
-    // Step 2B: Get a and b as described above
-    let a_1: u64 = turn_to_u64(&keccak_hash[0..8]) ^ turn_to_u64(&keccak_hash[32..40]);
-    let a_2: u64 = turn_to_u64(&keccak_hash[8..16]) ^ turn_to_u64(&keccak_hash[40..48]);
-    let b_1: u64 = turn_to_u64(&keccak_hash[16..24]) ^ turn_to_u64(&keccak_hash[48..56]);
-    let b_2: u64 = turn_to_u64(&keccak_hash[24..32]) ^ turn_to_u64(&keccak_hash[56..64]);
-    let mut a: [u64; 2] = [a_1, a_2];
-    let mut b: [u64; 2] = [b_1, b_2];
-
-    // Step 2C: Loop 524,288 times
-    for _ in 0..524_288 {
-        // Step 2C1: First Transfer
-        let addr: usize = (a[0] & 0x1F_FFF0) as usize / 16;
-        let block = &mut turn_to_u8_16(sp_u64_2[addr]);
-        aes_round(block, &turn_to_u8_16(a));
-        sp_u64_2[addr] = turn_to_u64_2(*block);
-        let tmp = b;
-        b = sp_u64_2[addr];
-        let man = xor_pair_u64_2(sp_u64_2[addr], tmp);
-        sp_u64_2[addr] = man;
-
-        // Step 2C2: Second Transfer
-        let addr: usize = (b[0] & 0x1F_FFF0) as usize / 16;
-        let tmp = add_pair_u64_2(a, mul_pair_u64_2(b, sp_u64_2[addr]));
-        a = xor_pair_u64_2(sp_u64_2[addr], tmp);
-        sp_u64_2[addr] = tmp;
+impl CnHasher {
+    /// Allocates a new hasher and its scratchpad
+    pub fn new() -> CnHasher {
+        CnHasher {
+            scratchpad: vec![[0u64; 2]; SCRATCHPAD_BLOCKS].into_boxed_slice(),
+            program: None,
+        }
     }
 
-    // Step 2D: Turn [[u64; 2]; 131072] into [u8; 2097152] for easier access
-    for (i, sp_u64_2_chunk) in sp_u64_2.iter().enumerate() {
-        let u8_slice = unsafe {
-            std::slice::from_raw_parts(sp_u64_2_chunk.as_ptr() as *const u8, 16)
-        };
-        scratchpad[i * 16..(i + 1) * 16].copy_from_slice(u8_slice);
+    /// EXPERIMENTAL! Same as [`cn_slow_hash`](fn.cn_slow_hash.html), reusing the scratchpad of the hasher
+    pub fn hash(&mut self, input: &[u8], variant: CnVariant) -> Result<String, String> {
+        check_input(input, variant)?;
+        Ok(hex::encode(self.slow_hash(input, variant)))
+    }
+
+    // Hashes with the hardware AES rounds when the CPU has them
+    fn slow_hash(&mut self, input: &[u8], variant: CnVariant) -> [u8; 32] {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+        if super::aes_hw::hardware_aes_available() {
+            return self.slow_hash_with(input, variant, super::aes_hw::aes_round);
+        }
+        self.slow_hash_with(input, variant, aes_round)
     }
 
-    // Cryptonight Step 3: Result Calculation
-
-    // After the memory-hard part, bytes 32..63 from the Keccak state are
-    // expanded into 10 AES round keys in the same manner as in the first
-    // part.
-
-    // Bytes 64..191 are extracted from the Keccak state and XORed with the
-    // first 128 bytes of the scratchpad. Then the result is encrypted in
-    // the same manner as in the first part, but using the new keys. The
-    // result is XORed with the second 128 bytes from the scratchpad,
-    // encrypted again, and so on. 
-
-    // After XORing with the last 128 bytes of the scratchpad, the result is
-    // encrypted the last time, and then the bytes 64..191 in the Keccak
-    // state are replaced with the result. Then, the Keccak state is passed
-    // through Keccak-f (the Keccak permutation) with b = 1600. 
-
-    // Then, the 2 low-order bits of the first byte of the state are used to
-    // select a hash function: 0=BLAKE-256 [BLAKE], 1=Groestl-256 [GROESTL],
-    // 2=JH-256 [JH], and 3=Skein-256 [SKEIN]. The chosen hash function is
-    // then applied to the Keccak state, and the resulting hash is the
-    // output of CryptoNight.
-
-    // Step 3A: Encrypt the scratchpad with the new keys
-    let round_keys_buffer = derive_key(&keccak_hash[32..64]);
-    let final_block = &mut keccak_hash[64..192];
-    for scratchpad_chunk in scratchpad.chunks_exact(128) {
-        xor(final_block, scratchpad_chunk);
-        for block in final_block.chunks_exact_mut(16) {
-            for key in round_keys_buffer.chunks_exact(16) {
-                aes_round(block, key);
+    // Program of CryptonightR at the given height, generated once per height
+    fn program(&mut self, height: u64) -> Vec<Instruction> {
+        match &self.program {
+            Some((program_height, program)) if *program_height == height => program.clone(),
+            _ => {
+                let program = generate_program(height);
+                self.program = Some((height, program.clone()));
+                program
             }
         }
     }
 
-    // Step 3B: Turn keccak_hash to [u64; 25] and pass it through Keccak-f, then turn it back to [u8; 200]
-    let mut keccak_state = [0u64; 25];
-    for (index, chunk) in keccak_hash.chunks_exact(8).enumerate() {
-        keccak_state[index] = u64::from_le_bytes(chunk.try_into().unwrap());
+    fn slow_hash_with<F: Fn(&mut [u8], &[u8])>(&mut self, input: &[u8], variant: CnVariant, aes_round: F) -> [u8; 32] {
+        let program = match variant {
+            CnVariant::R { height } => self.program(height),
+            _ => Vec::new(),
+        };
+
+        // CryptoNight Step 1: Initialization Of Scratchpad
+
+        // First, the input is hashed using Keccak [KECCAK] with parameters b =
+        // 1600 and c = 512. The bytes 0..31 of the Keccak final state are
+        // interpreted as an AES-256 key [AES] and expanded to 10 round keys. A
+        // scratchpad of 2097152 bytes (2 MiB) is allocated. The bytes 64..191
+        // are extracted from the Keccak final state and split into 8 blocks of
+        // 16 bytes each. Each block is encrypted using the following procedure:
+
+        //    for i = 0..9 do:
+        //        block = aes_round(block, round_keys[i])
+    
+        // Where aes_round function performs a round of AES encryption, which
+        // means that SubBytes, ShiftRows and MixColumns steps are performed on
+        // the block, and the result is XORed with the round key. Note that
+        // unlike in the AES encryption algorithm, the first and the last rounds
+        // are not special. The resulting blocks are written into the first 128
+        // bytes of the scratchpad. Then, these blocks are encrypted again in
+        // the same way, and the result is written into the second 128 bytes of
+        // the scratchpad. Each time 128 bytes are written, they represent the
+        // result of the encryption of the previously written 128 bytes. The
+        // process is repeated until the scratchpad is fully initialized.
+
+        // Step 1A: Take the scratchpad of the hasher, fully overwritten below. It is kept as [[u64; 2]; 131072]
+        // for easier access in the memory-hard loop, each pair being the little-endian value of 16 bytes.
+        let sp_u64_2 = &mut self.scratchpad[..];
+
+        // Step 1B: Use Keccak256Full to hash the input
+        let mut keccak_hash = keccak1600(input);
+
+        // Step 1C: Use the first 32 bytes of the Keccak hash as an AES-256 key and expand it into 10 round keys
+        let aes_key = &keccak_hash[0..32];
+        let round_keys = derive_key(aes_key);
+
+        // Step 1D: Use bytes 64..191 of the Keccak hash as 8 blocks of 16 bytes each
+        let mut blocks = [0u8; 128];
+        blocks.copy_from_slice(&keccak_hash[64..192]);
+
+        // Step 1E: Loop until scratchpad is fully initialized
+        for scratchpad_chunk in sp_u64_2.chunks_exact_mut(blocks.len() / 16) {
+            for block in blocks.chunks_exact_mut(16) {
+                for key in round_keys.chunks_exact(16) {
+                    aes_round(block, key);
+                }
+            }
+
+            for (sp_block, block) in scratchpad_chunk.iter_mut().zip(blocks.chunks_exact(16)) {
+                *sp_block = turn_to_u64_2(block.try_into().unwrap());
+            }
+        }
+
+        // Cryptonight Step 2: Memory-hard Loop
+
+        // Prior to the main loop, bytes 0..31 and 32..63 of the Keccak state
+        // are XORed, and the resulting 32 bytes are used to initialize
+        // variables a and b, 16 bytes each. These variables are used in the
+        // main loop. The main loop is iterated 524,288 times. When a 16-byte
+        // value needs to be converted into an address in the scratchpad, it is
+        // interpreted as a little-endian integer, and the 21 low-order bits are
+        // used as a byte index. However, the 4 low-order bits of the index are
+        // cleared to ensure the 16-byte alignment. The data is read from and
+        // written to the scratchpad in 16-byte blocks. Each iteration can be
+        // expressed with the following pseudo-code:
+
+        //     scratchpad_address = to_scratchpad_address(a)
+        //     scratchpad[scratchpad_address] = aes_round(scratchpad
+        //     [scratchpad_address], a)
+        //     b, scratchpad[scratchpad_address] = scratchpad[scratchpad_address],
+        //     b xor scratchpad[scratchpad_address]
+        //     scratchpad_address = to_scratchpad_address(b)
+        //     a = 8byte_add(a, 8byte_mul(b, scratchpad[scratchpad_address]))
+        //     a, scratchpad[scratchpad_address] = a xor
+        //     scratchpad[scratchpad_address], a
+
+        // Where, the 8byte_add function represents each of the arguments as a
+        // pair of 64-bit little-endian values and adds them together,
+        // component-wise, modulo 2^64. The result is converted back into 16
+        // bytes.
+
+        // The 8byte_mul function, however, uses only the first 8 bytes of each
+        // argument, which are interpreted as unsigned 64-bit little-endian
+        // integers and multiplied together. The result is converted into 16
+        // bytes, and finally the two 8-byte halves of the result are swapped.
+
+        // Step 2A: Get a and b as described above
+        let a_1: u64 = turn_to_u64(&keccak_hash[0..8]) ^ turn_to_u64(&keccak_hash[32..40]);
+        let a_2: u64 = turn_to_u64(&keccak_hash[8..16]) ^ turn_to_u64(&keccak_hash[40..48]);
+        let b_1: u64 = turn_to_u64(&keccak_hash[16..24]) ^ turn_to_u64(&keccak_hash[48..56]);
+        let b_2: u64 = turn_to_u64(&keccak_hash[24..32]) ^ turn_to_u64(&keccak_hash[56..64]);
+        let mut a: [u64; 2] = [a_1, a_2];
+        let mut b: [u64; 2] = [b_1, b_2];
+
+        // Step 2A1: Variant state
+        // v7 xors the second half of stored values with bytes 192..199 of the Keccak state and 35..42 of the input,
+        // v8 and CryptonightR keep the previous b and read the division, square root and random math registers
+        // from the Keccak state
+        let tweak = match variant {
+            CnVariant::V1 => turn_to_u64(&keccak_hash[192..200]) ^ turn_to_u64(&input[35..43]),
+            _ => 0,
+        };
+        let shuffle = matches!(variant, CnVariant::V2 | CnVariant::R { .. });
+        let mut b_prev: [u64; 2] = [
+            turn_to_u64(&keccak_hash[64..72]) ^ turn_to_u64(&keccak_hash[80..88]),
+            turn_to_u64(&keccak_hash[72..80]) ^ turn_to_u64(&keccak_hash[88..96]),
+        ];
+        let mut division_result: u64 = turn_to_u64(&keccak_hash[96..104]);
+        let mut sqrt_result: u64 = turn_to_u64(&keccak_hash[104..112]);
+        let mut registers = [0u32; 9];
+        for (register, chunk) in registers.iter_mut().zip(keccak_hash[96..112].chunks_exact(4)) {
+            *register = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        // Step 2B: Loop 524,288 times
+        for _ in 0..524_288 {
+            // Step 2B1: First Transfer
+            let addr: usize = (a[0] & 0x1F_FFF0) as usize / 16;
+            let block = &mut turn_to_u8_16(sp_u64_2[addr]);
+            aes_round(block, &turn_to_u8_16(a));
+            let mut c = turn_to_u64_2(*block);
+            if shuffle {
+                shuffle_add(sp_u64_2, addr, &mut c, a, b, b_prev, variant);
+            }
+            sp_u64_2[addr] = xor_pair_u64_2(c, b);
+            if variant == CnVariant::V1 {
+                sp_u64_2[addr][1] = variant1_tweak_byte(sp_u64_2[addr][1]);
+            }
+
+            // Step 2B2: Second Transfer
+            let addr: usize = (c[0] & 0x1F_FFF0) as usize / 16;
+            let mut d = sp_u64_2[addr];
+            let mut a_next = a;
+            match variant {
+                CnVariant::V2 => {
+                    // Integer division and square root, slowing down ASICs by their latency
+                    d[0] ^= division_result ^ (sqrt_result << 32);
+                    let divisor = ((c[0] as u32).wrapping_add((sqrt_result << 1) as u32) | 0x8000_0001) as u64;
+                    division_result = (c[1] / divisor) as u32 as u64 + ((c[1] % divisor) << 32);
+                    let sqrt_input = c[0].wrapping_add(division_result);
+                    sqrt_result = integer_sqrt(sqrt_input);
+                }
+                CnVariant::R { .. } => {
+                    d[0] ^= (registers[0].wrapping_add(registers[1]) as u64) | ((registers[2].wrapping_add(registers[3]) as u64) << 32);
+                    registers[4] = a[0] as u32;
+                    registers[5] = a[1] as u32;
+                    registers[6] = b[0] as u32;
+                    registers[7] = b_prev[0] as u32;
+                    registers[8] = b_prev[1] as u32;
+                    execute_program(&program, &mut registers);
+                    a_next[0] ^= registers[2] as u64 | ((registers[3] as u64) << 32);
+                    a_next[1] ^= registers[0] as u64 | ((registers[1] as u64) << 32);
+                }
+                _ => {}
+            }
+            let mut product = mul_pair_u64_2(c, d);
+            if variant == CnVariant::V2 {
+                sp_u64_2[addr ^ 1] = xor_pair_u64_2(sp_u64_2[addr ^ 1], product);
+                product = xor_pair_u64_2(product, sp_u64_2[addr ^ 2]);
+            }
+            if shuffle {
+                shuffle_add(sp_u64_2, addr, &mut c, a, b, b_prev, variant);
+            }
+            let tmp = add_pair_u64_2(a_next, product);
+            a = xor_pair_u64_2(d, tmp);
+            sp_u64_2[addr] = [tmp[0], tmp[1] ^ tweak];
+            b_prev = b;
+            b = c;
+        }
+
+        // Cryptonight Step 3: Result Calculation
+
+        // After the memory-hard part, bytes 32..63 from the Keccak state are
+        // expanded into 10 AES round keys in the same manner as in the first
+        // part.
+
+        // Bytes 64..191 are extracted from the Keccak state and XORed with the
+        // first 128 bytes of the scratchpad. Then the result is encrypted in
+        // the same manner as in the first part, but using the new keys. The
+        // result is XORed with the second 128 bytes from the scratchpad,
+        // encrypted again, and so on. 
+
+        // After XORing with the last 128 bytes of the scratchpad, the result is
+        // encrypted the last time, and then the bytes 64..191 in the Keccak
+        // state are replaced with the result. Then, the Keccak state is passed
+        // through Keccak-f (the Keccak permutation) with b = 1600. 
+
+        // Then, the 2 low-order bits of the first byte of the state are used to
+        // select a hash function: 0=BLAKE-256 [BLAKE], 1=Groestl-256 [GROESTL],
+        // 2=JH-256 [JH], and 3=Skein-256 [SKEIN]. The chosen hash function is
+        // then applied to the Keccak state, and the resulting hash is the
+        // output of CryptoNight.
+
+        // Step 3A: Encrypt the scratchpad with the new keys
+        let round_keys_buffer = derive_key(&keccak_hash[32..64]);
+        let final_block = &mut keccak_hash[64..192];
+        for scratchpad_chunk in sp_u64_2.chunks_exact(8) {
+            for (block, sp_block) in final_block.chunks_exact_mut(16).zip(scratchpad_chunk) {
+                xor(block, &turn_to_u8_16(*sp_block));
+                for key in round_keys_buffer.chunks_exact(16) {
+                    aes_round(block, key);
+                }
+            }
+        }
+
+        // Step 3B: Turn keccak_hash to [u64; 25] and pass it through Keccak-f, then turn it back to [u8; 200]
+        let mut keccak_state = [0u64; 25];
+        for (index, chunk) in keccak_hash.chunks_exact(8).enumerate() {
+            keccak_state[index] = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        keccakf(&mut keccak_state);
+        for (index, chunk) in keccak_state.iter().enumerate() {
+            keccak_hash[index * 8..(index + 1) * 8].copy_from_slice(&chunk.to_le_bytes());
+        }
+
+        // Step 3C: Use the first byte of the Keccak state to select a hash function
+        let hash_function = keccak_hash[0] & 0x03;
+        let final_byte = match hash_function {
+            0 => blake256_hash(keccak_hash),
+            1 => groestl256_hash(keccak_hash),
+            2 => jh256_hash(keccak_hash),
+            3 => skein256_hash(keccak_hash),
+            x => unreachable!("Hash function {} not implemented", x),
+        };
+    
+        final_byte
     }
-    tiny_keccak::keccakf(&mut keccak_state);
-    for (index, chunk) in keccak_state.iter().enumerate() {
-        keccak_hash[index * 8..(index + 1) * 8].copy_from_slice(&chunk.to_le_bytes());
+}
+
+// v7 tweak of the byte 11 of a value written to the scratchpad, byte 3 of its second half
+fn variant1_tweak_byte(value: u64) -> u64 {
+    const TABLE: u32 = 0x75310;
+    let tmp = (value >> 24) as u8;
+    let index = (((tmp >> 3) & 6) | (tmp & 1)) << 1;
+    value ^ ((((TABLE >> index) & 0x30) as u64) << 24)
+}
+
+// v8 shuffle of the three other 16-byte blocks of the 64-byte line at addr, adding b_prev, a and b to them in
+// rotation. CryptonightR also mixes the old values of the blocks into c.
+fn shuffle_add(scratchpad: &mut [[u64; 2]], addr: usize, c: &mut [u64; 2], a: [u64; 2], b: [u64; 2], b_prev: [u64; 2], variant: CnVariant) {
+    let chunk1 = scratchpad[addr ^ 1];
+    let chunk2 = scratchpad[addr ^ 2];
+    let chunk3 = scratchpad[addr ^ 3];
+    scratchpad[addr ^ 1] = add_pair_u64_2(chunk3, b_prev);
+    scratchpad[addr ^ 3] = add_pair_u64_2(chunk2, a);
+    scratchpad[addr ^ 2] = add_pair_u64_2(chunk1, b);
+    if let CnVariant::R { .. } = variant {
+        *c = xor_pair_u64_2(*c, xor_pair_u64_2(chunk3, xor_pair_u64_2(chunk1, chunk2)));
     }
+}
 
-    // Step 3C: Use the first byte of the Keccak state to select a hash function
-    let hash_function = keccak_hash[0] & 0x03;
-    let final_byte = match hash_function {
-        0 => blake256_hash(keccak_hash),
-        1 => groestl256_hash(keccak_hash),
-        2 => jh256_hash(keccak_hash),
-        3 => skein256_hash(keccak_hash),
-        x => unreachable!("Hash function {} not implemented", x),
-    };
-    
-    // Step 3D: Turn the final byte into a hex string and return
-    let mut final_hex = String::new();
-    for byte in final_byte.iter() {
-        final_hex.push_str(&format!("{:02x}", byte));
+// v8 square root, floor(sqrt(2^64 + input) * 2 - 2^33) computed in double precision then corrected to be exact
+fn integer_sqrt(input: u64) -> u64 {
+    let mut r = ((input as f64 + 18446744073709551616.0).sqrt() * 2.0 - 8589934592.0) as u64;
+    let s = r >> 1;
+    let b = r & 1;
+    let r2 = s.wrapping_mul(s + b).wrapping_add(r << 32);
+    if r2.wrapping_add(b) > input {
+        r = r.wrapping_sub(1);
+    }
+    if r2.wrapping_add(1 << 32) < input.wrapping_sub(s) {
+        r = r.wrapping_add(1);
     }
-    final_hex
-}
\ No newline at end of file
+    r
+}
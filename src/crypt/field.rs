@@ -0,0 +1,180 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// Arithmetic modulo p = 2^255 - 19, needed by the parts of Monero's cryptography which work directly
+// on field elements (hash_to_point) and therefore can't be done with curve25519-dalek's public API.
+// Elements are stored in radix 2^51, like ref10's fe and dalek's FieldElement51.
+
+const LOW_51_BIT_MASK: u64 = (1u64 << 51) - 1;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FieldElement(pub(crate) [u64; 5]);
+
+impl FieldElement {
+    pub(crate) const ZERO: FieldElement = FieldElement([0, 0, 0, 0, 0]);
+    pub(crate) const ONE: FieldElement = FieldElement([1, 0, 0, 0, 0]);
+
+    pub(crate) fn from_u64(value: u64) -> FieldElement {
+        FieldElement([value & LOW_51_BIT_MASK, value >> 51, 0, 0, 0])
+    }
+
+    // Loads all 256 bits of the given little-endian integer, the top bit is reduced (2^255 = 19 mod p) instead of being ignored
+    pub(crate) fn from_bytes_wide(bytes: &[u8; 32]) -> FieldElement {
+        let load8 = |input: &[u8]| -> u64 { u64::from_le_bytes(input[..8].try_into().unwrap()) };
+        let top_bit = (bytes[31] >> 7) as u64;
+        let limbs = [
+            load8(&bytes[0..]) & LOW_51_BIT_MASK,
+            (load8(&bytes[6..]) >> 3) & LOW_51_BIT_MASK,
+            (load8(&bytes[12..]) >> 6) & LOW_51_BIT_MASK,
+            (load8(&bytes[19..]) >> 1) & LOW_51_BIT_MASK,
+            (load8(&bytes[24..]) >> 12) & LOW_51_BIT_MASK,
+        ];
+        let mut fe = FieldElement(limbs);
+        fe.0[0] += 19 * top_bit;
+        fe.reduce()
+    }
+
+    // Carries every limb into the next one so all limbs fit in 51 bits (plus a small excess for the first one)
+    fn reduce(mut self) -> FieldElement {
+        let l = &mut self.0;
+        let c0 = l[0] >> 51;
+        let c1 = l[1] >> 51;
+        let c2 = l[2] >> 51;
+        let c3 = l[3] >> 51;
+        let c4 = l[4] >> 51;
+        l[0] &= LOW_51_BIT_MASK;
+        l[1] &= LOW_51_BIT_MASK;
+        l[2] &= LOW_51_BIT_MASK;
+        l[3] &= LOW_51_BIT_MASK;
+        l[4] &= LOW_51_BIT_MASK;
+        l[0] += c4 * 19;
+        l[1] += c0;
+        l[2] += c1;
+        l[3] += c2;
+        l[4] += c3;
+        self
+    }
+
+    // Returns the canonical little-endian encoding (fully reduced modulo p)
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let mut limbs = self.reduce().0;
+        // Compute q = 1 if the value is >= p, then subtract q * p by adding 19q and dropping bit 255
+        let mut q = (limbs[0] + 19) >> 51;
+        q = (limbs[1] + q) >> 51;
+        q = (limbs[2] + q) >> 51;
+        q = (limbs[3] + q) >> 51;
+        q = (limbs[4] + q) >> 51;
+        limbs[0] += 19 * q;
+        limbs[1] += limbs[0] >> 51;
+        limbs[0] &= LOW_51_BIT_MASK;
+        limbs[2] += limbs[1] >> 51;
+        limbs[1] &= LOW_51_BIT_MASK;
+        limbs[3] += limbs[2] >> 51;
+        limbs[2] &= LOW_51_BIT_MASK;
+        limbs[4] += limbs[3] >> 51;
+        limbs[3] &= LOW_51_BIT_MASK;
+        limbs[4] &= LOW_51_BIT_MASK;
+
+        let mut s = [0u8; 32];
+        let packed: [u64; 4] = [
+            limbs[0] | (limbs[1] << 51),
+            (limbs[1] >> 13) | (limbs[2] << 38),
+            (limbs[2] >> 26) | (limbs[3] << 25),
+            (limbs[3] >> 39) | (limbs[4] << 12),
+        ];
+        for (i, word) in packed.iter().enumerate() {
+            s[i * 8..(i + 1) * 8].copy_from_slice(&word.to_le_bytes());
+        }
+        s
+    }
+
+    pub(crate) fn add(&self, other: &FieldElement) -> FieldElement {
+        let mut out = [0u64; 5];
+        for (i, limb) in out.iter_mut().enumerate() {
+            *limb = self.0[i] + other.0[i];
+        }
+        FieldElement(out).reduce()
+    }
+
+    pub(crate) fn sub(&self, other: &FieldElement) -> FieldElement {
+        // Add 16p before subtracting so limbs never underflow
+        const SIXTEEN_P: [u64; 5] = [36028797018963664, 36028797018963952, 36028797018963952, 36028797018963952, 36028797018963952];
+        let a = self.reduce().0;
+        let b = other.reduce().0;
+        let mut out = [0u64; 5];
+        for i in 0..5 {
+            out[i] = (a[i] + SIXTEEN_P[i]) - b[i];
+        }
+        FieldElement(out).reduce()
+    }
+
+    pub(crate) fn neg(&self) -> FieldElement {
+        FieldElement::ZERO.sub(self)
+    }
+
+    pub(crate) fn mul(&self, other: &FieldElement) -> FieldElement {
+        let a = self.reduce().0;
+        let b = other.reduce().0;
+        let m = |x: u64, y: u64| -> u128 { (x as u128) * (y as u128) };
+        let b1_19 = b[1] * 19;
+        let b2_19 = b[2] * 19;
+        let b3_19 = b[3] * 19;
+        let b4_19 = b[4] * 19;
+        let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+        let mut c1 = m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+        let mut c2 = m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        let mask = LOW_51_BIT_MASK as u128;
+        c1 += c0 >> 51;
+        let mut out0 = (c0 & mask) as u64;
+        c2 += c1 >> 51;
+        let out1 = (c1 & mask) as u64;
+        c3 += c2 >> 51;
+        let out2 = (c2 & mask) as u64;
+        c4 += c3 >> 51;
+        let out3 = (c3 & mask) as u64;
+        let carry = (c4 >> 51) as u64;
+        let out4 = (c4 & mask) as u64;
+        out0 += carry * 19;
+        FieldElement([out0, out1, out2, out3, out4]).reduce()
+    }
+
+    pub(crate) fn square(&self) -> FieldElement {
+        self.mul(self)
+    }
+
+    // Raises the element to the power given as a little-endian integer
+    pub(crate) fn pow(&self, exponent: &[u8; 32]) -> FieldElement {
+        let mut result = FieldElement::ONE;
+        for byte in exponent.iter().rev() {
+            for bit in (0..8).rev() {
+                result = result.square();
+                if (byte >> bit) & 1 == 1 {
+                    result = result.mul(self);
+                }
+            }
+        }
+        result
+    }
+
+    pub(crate) fn invert(&self) -> FieldElement {
+        // p - 2
+        let mut exponent = [0xffu8; 32];
+        exponent[0] = 0xeb;
+        exponent[31] = 0x7f;
+        self.pow(&exponent)
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.to_bytes() == [0u8; 32]
+    }
+}
@@ -0,0 +1,81 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::field::FieldElement;
+use super::keccak::keccak256;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+
+/// Returns `Hp(input)`, Monero's hash to point (`hash_to_ec`) used by key images, CLSAG and the ring proofs
+///
+/// The [`keccak256`](keccak/fn.keccak256.html) hash of the input is mapped to the curve with
+/// [`ge_fromfe_frombytes_vartime`](fn.ge_fromfe_frombytes_vartime.html), then multiplied by the cofactor so that the
+/// point lies in the prime order subgroup.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::{ge_fromfe_frombytes_vartime, hash_to_point};
+/// use libmonero::crypt::keccak::keccak256;
+///
+/// let key = hex::decode("83efb774657700e37291f4b8dd10c839d1c739fd135c07a2fd7382334dafdd6a").unwrap();
+/// assert_eq!(hash_to_point(&key), ge_fromfe_frombytes_vartime(&keccak256(&key)).mul_by_cofactor());
+/// assert!(hash_to_point(&key).is_torsion_free());
+/// ```
+pub fn hash_to_point(input: &[u8]) -> EdwardsPoint {
+    ge_fromfe_frombytes_vartime(&keccak256(input)).mul_by_cofactor()
+}
+
+/// Maps 32 bytes to a curve point like monero-core's `ge_fromfe_frombytes_vartime`, an Elligator 2 variant
+///
+/// The bytes are read as a little-endian integer modulo p, top bit included. The result is not multiplied by the
+/// cofactor, see [`hash_to_point`](fn.hash_to_point.html) for the complete hash to point.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::ge_fromfe_frombytes_vartime;
+///
+/// let bytes = hex::decode("83efb774657700e37291f4b8dd10c839d1c739fd135c07a2fd7382334dafdd6a").unwrap();
+/// let point = ge_fromfe_frombytes_vartime(&bytes.try_into().unwrap());
+/// assert_eq!(hex::encode(point.compress().as_bytes()), "2789ecbaf36e4fcb41c6157228001538b40ca379464b718d830c58caae7ea4ca");
+/// ```
+pub fn ge_fromfe_frombytes_vartime(bytes: &[u8; 32]) -> EdwardsPoint {
+    // Montgomery curve constant A
+    let a = FieldElement::from_u64(486662);
+
+    let u = FieldElement::from_bytes_wide(bytes);
+    let v = u.square().add(&u.square()); // 2u^2
+    let w = v.add(&FieldElement::ONE); // 2u^2 + 1
+    let x = w.square().sub(&a.square().mul(&v)); // w^2 - 2A^2u^2
+
+    // r = (w / x)^((p + 3) / 8) computed as w * x^3 * (w * x^7)^((p - 5) / 8)
+    let x3 = x.square().mul(&x);
+    let wx3 = w.mul(&x3);
+    let wx7 = wx3.mul(&x3).mul(&x);
+    let r = wx3.mul(&wx7.pow(&P_MINUS_5_DIV_8));
+    let rx = r.square().mul(&x);
+
+    // If r^2 * x is neither w nor -w, the point lies on the twist and the sign gets flipped
+    let sign = !w.sub(&rx).is_zero() && !w.add(&rx).is_zero();
+
+    let z = if sign { a.neg() } else { a.neg().mul(&v) };
+    let big_z = z.add(&w);
+    let big_y = z.sub(&w).mul(&big_z.invert());
+
+    let mut compressed = big_y.to_bytes();
+    compressed[31] |= (sign as u8) << 7;
+    CompressedEdwardsY(compressed)
+        .decompress()
+        .expect("ge_fromfe_frombytes_vartime always produces a valid point")
+}
+
+// (p - 5) / 8 = 2^252 - 3
+const P_MINUS_5_DIV_8: [u8; 32] = [
+    0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0f,
+];
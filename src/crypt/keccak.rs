@@ -0,0 +1,100 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use curve25519_dalek::Scalar;
+use sha3::{Digest, Keccak256, Keccak256Full};
+use zeroize::Zeroizing;
+
+/// Returns the Keccak-256 hash of the input, Monero's `cn_fast_hash`
+///
+/// This is the original Keccak padding, not the SHA3-256 of FIPS 202.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::keccak::keccak256;
+///
+/// assert_eq!(hex::encode(keccak256(b"")), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+/// ```
+pub fn keccak256(input: &[u8]) -> [u8; 32] {
+    Keccak256::digest(input).into()
+}
+
+/// Returns the Keccak-256 hash of the hex encoded input, hex encoded
+/// Returns the hash if succesfull
+/// Returns an error message if the input is not hex
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::keccak::keccak256_hex;
+///
+/// assert_eq!(keccak256_hex("").unwrap(), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+/// assert!(keccak256_hex("zz").is_err());
+/// ```
+pub fn keccak256_hex(input: &str) -> Result<String, String> {
+    Ok(hex::encode(keccak256(&decode_hex(input)?)))
+}
+
+/// Returns the whole 200-byte Keccak state after absorbing the input with the padding and rate of Keccak-256, the
+/// state CryptoNight starts from
+///
+/// The first 32 bytes are the [`keccak256`](fn.keccak256.html) hash of the input.
+pub fn keccak1600(input: &[u8]) -> [u8; 200] {
+    let mut hasher = Keccak256Full::new();
+    hasher.update(input);
+    let mut state = [0u8; 200];
+    state.copy_from_slice(&hasher.finalize());
+    state
+}
+
+/// Applies the Keccak-f\[1600\] permutation to a state of 25 little-endian lanes
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::keccak::keccakf;
+///
+/// let mut state = [0u64; 25];
+/// keccakf(&mut state);
+/// assert_eq!(state[0], 0xf1258f7940e1dde7);
+/// ```
+pub fn keccakf(state: &mut [u64; 25]) {
+    tiny_keccak::keccakf(state);
+}
+
+/// Returns `Hs(input)`, the Keccak-256 hash of the input reduced modulo the order of the curve
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::keccak::{hash_to_scalar, keccak256};
+/// use curve25519_dalek::Scalar;
+///
+/// assert_eq!(hash_to_scalar(b"SubAddr\0"), Scalar::from_bytes_mod_order(keccak256(b"SubAddr\0")));
+/// ```
+pub fn hash_to_scalar(input: &[u8]) -> Scalar {
+    let hash = Zeroizing::new(keccak256(input));
+    Scalar::from_bytes_mod_order(*hash)
+}
+
+/// Returns `Hs(input)` of the hex encoded input, hex encoded
+/// Returns the scalar if succesfull
+/// Returns an error message if the input is not hex
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::keccak::hash_to_scalar_hex;
+///
+/// assert_eq!(hash_to_scalar_hex("").unwrap(), "4a078e76cd41a3d3b534b83dc6f2ea2de500b653ca82273b7bfad8045d85a400");
+/// ```
+pub fn hash_to_scalar_hex(input: &str) -> Result<String, String> {
+    Ok(hex::encode(hash_to_scalar(&decode_hex(input)?).as_bytes()))
+}
+
+fn decode_hex(input: &str) -> Result<Vec<u8>, String> {
+    hex::decode(input).map_err(|e| format!("Error while decoding the hex input: {}", e))
+}
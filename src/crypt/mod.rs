@@ -8,7 +8,21 @@
  *
  */
 
+pub(crate) mod bulletproofs_plus;
+pub(crate) mod chacha;
+pub(crate) mod clsag;
 pub(crate) mod ed25519;
+pub(crate) mod field;
+pub(crate) mod hash_to_point;
+
+pub use bulletproofs_plus::*;
+pub use chacha::*;
+pub use clsag::*;
+pub use hash_to_point::*;
 
 /// CryptoNight related functions
-pub mod cryptonight;
\ No newline at end of file
+pub mod cryptonight;
+/// Keccak hashes, the Keccak-f permutation and hashing to scalars
+pub mod keccak;
+/// RandomX related functions
+pub mod randomx;
@@ -0,0 +1,85 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// AES based generators and hash of RandomX, working on 4 columns of 16 bytes. Single AES rounds are used as x86's
+// AESENC and AESDEC perform them, their keys being Blake2b digests of fixed strings.
+
+use super::blake2b::blake2b;
+use aes::hazmat::{cipher_round, equiv_inv_cipher_round};
+use aes::Block;
+
+fn keys<const N: usize>(label: &str) -> [Block; N] {
+    let mut digest = [0u8; 64];
+    blake2b(label.as_bytes(), &mut digest[..N * 16]);
+    std::array::from_fn(|i| Block::clone_from_slice(&digest[i * 16..(i + 1) * 16]))
+}
+
+fn load(bytes: &[u8]) -> [Block; 4] {
+    std::array::from_fn(|i| Block::clone_from_slice(&bytes[i * 16..(i + 1) * 16]))
+}
+
+fn store(state: &[Block; 4], bytes: &mut [u8]) {
+    for (i, column) in state.iter().enumerate() {
+        bytes[i * 16..(i + 1) * 16].copy_from_slice(column);
+    }
+}
+
+// AesGenerator1R: fills the output 64 bytes at a time, one round per column, and updates the 64 bytes state
+pub(crate) fn fill_aes_1rx4(state: &mut [u8; 64], output: &mut [u8]) {
+    let keys: [Block; 4] = keys("RandomX AesGenerator1R keys");
+    let mut columns = load(state);
+    for chunk in output.chunks_exact_mut(64) {
+        equiv_inv_cipher_round(&mut columns[0], &keys[0]);
+        cipher_round(&mut columns[1], &keys[1]);
+        equiv_inv_cipher_round(&mut columns[2], &keys[2]);
+        cipher_round(&mut columns[3], &keys[3]);
+        store(&columns, chunk);
+    }
+    store(&columns, state);
+}
+
+// AesGenerator4R: fills the output 64 bytes at a time, four rounds per column, for generating programs
+pub(crate) fn fill_aes_4rx4(state: &[u8; 64], output: &mut [u8]) {
+    let low_keys: [Block; 4] = keys("RandomX AesGenerator4R keys 0-3");
+    let high_keys: [Block; 4] = keys("RandomX AesGenerator4R keys 4-7");
+    let mut columns = load(state);
+    for chunk in output.chunks_exact_mut(64) {
+        for round in 0..4 {
+            equiv_inv_cipher_round(&mut columns[0], &low_keys[round]);
+            cipher_round(&mut columns[1], &low_keys[round]);
+            equiv_inv_cipher_round(&mut columns[2], &high_keys[round]);
+            cipher_round(&mut columns[3], &high_keys[round]);
+        }
+        store(&columns, chunk);
+    }
+}
+
+// AesHash1R: absorbs the input 64 bytes at a time, then finishes with two extra rounds
+pub(crate) fn hash_aes_1rx4(input: &[u8]) -> [u8; 64] {
+    let initial_state: [Block; 4] = keys("RandomX AesHash1R state");
+    let extra_keys: [Block; 2] = keys("RandomX AesHash1R xkeys");
+    let mut columns = initial_state;
+    for chunk in input.chunks_exact(64) {
+        let input = load(chunk);
+        cipher_round(&mut columns[0], &input[0]);
+        equiv_inv_cipher_round(&mut columns[1], &input[1]);
+        cipher_round(&mut columns[2], &input[2]);
+        equiv_inv_cipher_round(&mut columns[3], &input[3]);
+    }
+    for key in extra_keys.iter() {
+        cipher_round(&mut columns[0], key);
+        equiv_inv_cipher_round(&mut columns[1], key);
+        cipher_round(&mut columns[2], key);
+        equiv_inv_cipher_round(&mut columns[3], key);
+    }
+    let mut hash = [0u8; 64];
+    store(&columns, &mut hash);
+    hash
+}
@@ -0,0 +1,150 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// Memory filling of Argon2d version 1.3 (RFC 9106) on a single lane, as RandomX uses it for its cache: the filled
+// blocks are the result, no tag is computed
+
+use super::blake2b::{blake2b, Blake2b};
+
+pub(crate) const BLOCK_WORDS: usize = 128;
+const BLOCK_SIZE: usize = BLOCK_WORDS * 8;
+const SYNC_POINTS: usize = 4;
+const VERSION: u32 = 0x13;
+const ARGON2D: u32 = 0;
+
+// Fills `memory_kib` blocks of 1 KiB with `iterations` passes and returns them as little endian words
+pub(crate) fn argon2d_fill(password: &[u8], salt: &[u8], memory_kib: u32, iterations: u32) -> Vec<u64> {
+    let lane_length = memory_kib as usize;
+    let segment_length = lane_length / SYNC_POINTS;
+    let mut memory = vec![0u64; lane_length * BLOCK_WORDS];
+
+    let mut hasher = Blake2b::new(64);
+    // Lanes, tag length (none), memory, iterations, version and type
+    for value in [1, 0, memory_kib, iterations, VERSION, ARGON2D] {
+        hasher.update(&value.to_le_bytes());
+    }
+    hasher.update(&(password.len() as u32).to_le_bytes());
+    hasher.update(password);
+    hasher.update(&(salt.len() as u32).to_le_bytes());
+    hasher.update(salt);
+    // No secret nor associated data
+    hasher.update(&0u32.to_le_bytes());
+    hasher.update(&0u32.to_le_bytes());
+    let mut h0 = [0u8; 72];
+    hasher.finalize(&mut h0[..64]);
+
+    for index in 0..2 {
+        h0[64..68].copy_from_slice(&(index as u32).to_le_bytes());
+        let mut block = [0u8; BLOCK_SIZE];
+        blake2b_long(&h0, &mut block);
+        for (word, chunk) in memory[index * BLOCK_WORDS..(index + 1) * BLOCK_WORDS].iter_mut().zip(block.chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+    }
+
+    for pass in 0..iterations as usize {
+        for slice in 0..SYNC_POINTS {
+            let first_index = if pass == 0 && slice == 0 { 2 } else { 0 };
+            for index in first_index..segment_length {
+                let current = slice * segment_length + index;
+                let previous = if current == 0 { lane_length - 1 } else { current - 1 };
+                // Argon2d takes the reference block from the first word of the previous one
+                let pseudo_rand = memory[previous * BLOCK_WORDS] & 0xffffffff;
+                let reference_area = match (pass, slice) {
+                    (0, 0) => index - 1,
+                    (0, _) => slice * segment_length + index - 1,
+                    _ => lane_length - segment_length + index - 1,
+                } as u64;
+                let relative = (pseudo_rand * pseudo_rand) >> 32;
+                let relative = reference_area - 1 - ((reference_area * relative) >> 32);
+                let start = match pass != 0 && slice != SYNC_POINTS - 1 {
+                    true => (slice + 1) * segment_length,
+                    false => 0,
+                };
+                let reference = (start + relative as usize) % lane_length;
+                fill_block(&mut memory, previous, reference, current, pass != 0);
+            }
+        }
+    }
+    memory
+}
+
+// Blake2b with an output longer than 64 bytes, H' of the specification
+fn blake2b_long(input: &[u8], output: &mut [u8]) {
+    let mut hasher = Blake2b::new(64);
+    hasher.update(&(output.len() as u32).to_le_bytes());
+    hasher.update(input);
+    let mut digest = [0u8; 64];
+    hasher.finalize(&mut digest);
+    output[..32].copy_from_slice(&digest[..32]);
+    let mut position = 32;
+    while output.len() - position > 64 {
+        let previous = digest;
+        blake2b(&previous, &mut digest);
+        output[position..position + 32].copy_from_slice(&digest[..32]);
+        position += 32;
+    }
+    let remaining = output.len() - position;
+    blake2b(&digest, &mut output[position..position + remaining]);
+}
+
+fn fill_block(memory: &mut [u64], previous: usize, reference: usize, current: usize, with_xor: bool) {
+    let mut r = [0u64; BLOCK_WORDS];
+    for (i, word) in r.iter_mut().enumerate() {
+        *word = memory[previous * BLOCK_WORDS + i] ^ memory[reference * BLOCK_WORDS + i];
+    }
+    let mut tmp = r;
+    if with_xor {
+        for (i, word) in tmp.iter_mut().enumerate() {
+            *word ^= memory[current * BLOCK_WORDS + i];
+        }
+    }
+    for row in 0..8 {
+        let mut indexes = [0usize; 16];
+        for (i, index) in indexes.iter_mut().enumerate() {
+            *index = 16 * row + i;
+        }
+        blake2_round(&mut r, &indexes);
+    }
+    for column in 0..8 {
+        let mut indexes = [0usize; 16];
+        for (i, index) in indexes.iter_mut().enumerate() {
+            *index = 2 * column + (i / 2) * 16 + i % 2;
+        }
+        blake2_round(&mut r, &indexes);
+    }
+    for (i, word) in memory[current * BLOCK_WORDS..(current + 1) * BLOCK_WORDS].iter_mut().enumerate() {
+        *word = tmp[i] ^ r[i];
+    }
+}
+
+fn blake2_round(v: &mut [u64; BLOCK_WORDS], i: &[usize; 16]) {
+    gb(v, i[0], i[4], i[8], i[12]);
+    gb(v, i[1], i[5], i[9], i[13]);
+    gb(v, i[2], i[6], i[10], i[14]);
+    gb(v, i[3], i[7], i[11], i[15]);
+    gb(v, i[0], i[5], i[10], i[15]);
+    gb(v, i[1], i[6], i[11], i[12]);
+    gb(v, i[2], i[7], i[8], i[13]);
+    gb(v, i[3], i[4], i[9], i[14]);
+}
+
+// Blake2b's G with the additions replaced by the multiply-hardened BlaMka ones
+fn gb(v: &mut [u64; BLOCK_WORDS], a: usize, b: usize, c: usize, d: usize) {
+    let bla_mka = |x: u64, y: u64| x.wrapping_add(y).wrapping_add(2u64.wrapping_mul((x & 0xffffffff) * (y & 0xffffffff)));
+    v[a] = bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
@@ -0,0 +1,124 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::argon2::{argon2d_fill, BLOCK_WORDS};
+use super::superscalar::{generate_superscalar, Blake2Generator, SuperscalarProgram};
+use std::fmt;
+
+const ARGON_MEMORY_KIB: u32 = 262144;
+const ARGON_ITERATIONS: u32 = 3;
+const ARGON_SALT: &[u8] = b"RandomX\x03";
+const CACHE_ACCESSES: usize = 8;
+// Dataset items of 64 bytes, 8 words
+const ITEM_WORDS: usize = 8;
+const CACHE_ITEMS: u64 = ARGON_MEMORY_KIB as u64 * BLOCK_WORDS as u64 / ITEM_WORDS as u64;
+pub(crate) const DATASET_BASE_SIZE: u64 = 2147483648;
+pub(crate) const DATASET_EXTRA_SIZE: u64 = 33554368;
+pub(crate) const DATASET_ITEMS: u64 = (DATASET_BASE_SIZE + DATASET_EXTRA_SIZE) / 64;
+
+/// RandomXCache is the 256 MiB cache derived from a RandomX key, enough for hashing in light mode or for building
+/// a [`RandomXDataset`](struct.RandomXDataset.html) for the full mode
+///
+/// Monero keys RandomX with the hash of a seed block, see [`rx_seed_height`](fn.rx_seed_height.html). Building a
+/// cache takes about a second, it should be kept for all the hashes under the same key.
+pub struct RandomXCache {
+    key: Vec<u8>,
+    memory: Vec<u64>,
+    programs: Vec<SuperscalarProgram>,
+}
+
+impl RandomXCache {
+    /// Builds the cache for the given key
+    pub fn new(key: &[u8]) -> RandomXCache {
+        let memory = argon2d_fill(key, ARGON_SALT, ARGON_MEMORY_KIB, ARGON_ITERATIONS);
+        let mut gen = Blake2Generator::new(key, 0);
+        let programs = (0..CACHE_ACCESSES).map(|_| generate_superscalar(&mut gen)).collect();
+        RandomXCache {
+            key: key.to_vec(),
+            memory,
+            programs,
+        }
+    }
+
+    /// Returns the key the cache was built for
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    // Computes the dataset item of given number with SuperscalarHash
+    pub(crate) fn dataset_item(&self, item_number: u64) -> [u64; ITEM_WORDS] {
+        let r0 = (item_number + 1).wrapping_mul(6364136223846793005);
+        let mut r = [
+            r0,
+            r0 ^ 9298411001130361340,
+            r0 ^ 12065312585734608966,
+            r0 ^ 9306329213124626780,
+            r0 ^ 5281919268842080866,
+            r0 ^ 10536153434571861004,
+            r0 ^ 3398623926847679864,
+            r0 ^ 9549104520008361294,
+        ];
+        let mut register_value = item_number;
+        for program in self.programs.iter() {
+            let offset = (register_value % CACHE_ITEMS) as usize * ITEM_WORDS;
+            program.execute(&mut r);
+            for (word, cached) in r.iter_mut().zip(self.memory[offset..offset + ITEM_WORDS].iter()) {
+                *word ^= cached;
+            }
+            register_value = r[program.address_register];
+        }
+        r
+    }
+}
+
+impl fmt::Debug for RandomXCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RandomXCache").field("key", &hex::encode(&self.key)).finish_non_exhaustive()
+    }
+}
+
+/// RandomXDataset is the 2080 MiB dataset expanded from a [`RandomXCache`](struct.RandomXCache.html), for hashing in
+/// full mode
+///
+/// Full mode hashes several times faster than light mode, as dataset items are read instead of computed, and is the
+/// one for mining. Building the dataset takes minutes on a single thread.
+pub struct RandomXDataset {
+    items: Vec<u64>,
+}
+
+impl RandomXDataset {
+    /// Expands the given cache into a dataset, splitting the work between `threads` threads
+    pub fn new(cache: &RandomXCache, threads: usize) -> RandomXDataset {
+        let mut items = vec![0u64; DATASET_ITEMS as usize * ITEM_WORDS];
+        let items_per_thread = (DATASET_ITEMS as usize).div_ceil(threads.max(1));
+        std::thread::scope(|scope| {
+            for (chunk_index, chunk) in items.chunks_mut(items_per_thread * ITEM_WORDS).enumerate() {
+                scope.spawn(move || {
+                    let first_item = chunk_index * items_per_thread;
+                    for (i, item) in chunk.chunks_exact_mut(ITEM_WORDS).enumerate() {
+                        item.copy_from_slice(&cache.dataset_item((first_item + i) as u64));
+                    }
+                });
+            }
+        });
+        RandomXDataset { items }
+    }
+
+    pub(crate) fn item(&self, item_number: u64) -> &[u64] {
+        let offset = item_number as usize * ITEM_WORDS;
+        &self.items[offset..offset + ITEM_WORDS]
+    }
+}
+
+impl fmt::Debug for RandomXDataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RandomXDataset").field("items", &(self.items.len() / ITEM_WORDS)).finish()
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// IEEE 754 operations under the four rounding modes of RandomX, without touching the floating point environment.
+// Each operation is computed rounded to nearest, the sign of its exact error is found with an error-free
+// transformation and the result is moved by one ulp when the requested direction differs.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RoundingMode {
+    Nearest,
+    Down,
+    Up,
+    TowardZero,
+}
+
+impl RoundingMode {
+    // Mode as encoded by CFROUND, same as the rounding control bits of x86's MXCSR
+    pub(crate) fn from_bits(bits: u64) -> RoundingMode {
+        match bits % 4 {
+            0 => RoundingMode::Nearest,
+            1 => RoundingMode::Down,
+            2 => RoundingMode::Up,
+            _ => RoundingMode::TowardZero,
+        }
+    }
+}
+
+// Result of an operation on finite operands overflowing to the given infinity when rounded to nearest
+fn overflow(infinity: f64, mode: RoundingMode) -> f64 {
+    match (mode, infinity > 0.0) {
+        (RoundingMode::Down, true) | (RoundingMode::TowardZero, true) => f64::MAX,
+        (RoundingMode::Up, false) | (RoundingMode::TowardZero, false) => f64::MIN,
+        _ => infinity,
+    }
+}
+
+// Rounds the nearest result given the sign of (exact - nearest)
+fn round(nearest: f64, error: f64, mode: RoundingMode) -> f64 {
+    if error == 0.0 || !nearest.is_finite() {
+        return nearest;
+    }
+    match mode {
+        RoundingMode::Nearest => nearest,
+        RoundingMode::Down if error < 0.0 => nearest.next_down(),
+        RoundingMode::Up if error > 0.0 => nearest.next_up(),
+        RoundingMode::TowardZero if nearest > 0.0 && error < 0.0 => nearest.next_down(),
+        RoundingMode::TowardZero if nearest < 0.0 && error > 0.0 => nearest.next_up(),
+        _ => nearest,
+    }
+}
+
+pub(crate) fn add(a: f64, b: f64, mode: RoundingMode) -> f64 {
+    let sum = a + b;
+    if sum.is_infinite() && a.is_finite() && b.is_finite() {
+        return overflow(sum, mode);
+    }
+    // An exact zero sum of operands of opposite signs is -0 when rounding down
+    if sum == 0.0 {
+        return match mode == RoundingMode::Down && (a.is_sign_negative() || b.is_sign_negative()) {
+            true => -0.0,
+            false => sum,
+        };
+    }
+    // TwoSum
+    let b_virtual = sum - a;
+    let error = (a - (sum - b_virtual)) + (b - b_virtual);
+    round(sum, error, mode)
+}
+
+pub(crate) fn sub(a: f64, b: f64, mode: RoundingMode) -> f64 {
+    add(a, -b, mode)
+}
+
+pub(crate) fn mul(a: f64, b: f64, mode: RoundingMode) -> f64 {
+    let product = a * b;
+    if product.is_infinite() && a.is_finite() && b.is_finite() {
+        return overflow(product, mode);
+    }
+    round(product, a.mul_add(b, -product), mode)
+}
+
+pub(crate) fn div(a: f64, b: f64, mode: RoundingMode) -> f64 {
+    let quotient = a / b;
+    if quotient.is_infinite() && a.is_finite() && b != 0.0 {
+        return overflow(quotient, mode);
+    }
+    // a - quotient * b has the sign of the error times the sign of b
+    let remainder = (-quotient).mul_add(b, a);
+    round(quotient, remainder * b.signum(), mode)
+}
+
+pub(crate) fn sqrt(a: f64, mode: RoundingMode) -> f64 {
+    let root = a.sqrt();
+    round(root, (-root).mul_add(root, a), mode)
+}
+
@@ -0,0 +1,60 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+pub(crate) mod aes_gen;
+pub(crate) mod argon2;
+pub(crate) mod blake2b;
+pub(crate) mod cache;
+pub(crate) mod float;
+pub(crate) mod superscalar;
+pub(crate) mod vm;
+
+pub use cache::{RandomXCache, RandomXDataset};
+pub use vm::RandomXVm;
+
+const SEEDHASH_EPOCH_BLOCKS: u64 = 2048;
+const SEEDHASH_EPOCH_LAG: u64 = 64;
+
+/// Returns the height of the block whose hash keys RandomX for a block at the given height
+///
+/// The key changes every 2048 blocks, 64 blocks after the seed block so miners can prepare the new dataset.
+///
+/// Example:
+/// ```
+/// use libmonero::crypt::randomx::rx_seed_height;
+///
+/// assert_eq!(rx_seed_height(2112), 0);
+/// assert_eq!(rx_seed_height(2113), 2048);
+/// assert_eq!(rx_seed_height(3000000), 2998272);
+/// ```
+pub fn rx_seed_height(height: u64) -> u64 {
+    if height <= SEEDHASH_EPOCH_BLOCKS + SEEDHASH_EPOCH_LAG {
+        return 0;
+    }
+    (height - SEEDHASH_EPOCH_LAG - 1) & !(SEEDHASH_EPOCH_BLOCKS - 1)
+}
+
+/// Main RandomX (rx/0) function, hashing the input in light mode under the given seed hash
+///
+/// This builds a new [`RandomXCache`](struct.RandomXCache.html) on every call, for hashing more than one input under
+/// the same seed keep the cache and a [`RandomXVm`](struct.RandomXVm.html) instead.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::crypt::randomx::rx_slow_hash;
+///
+/// let output: String = rx_slow_hash(b"test key 000", b"This is a test");
+/// assert_eq!(output, "639183aae1bf4c9a35884cb46b09cad9175f04efd7684e7262a0ac1c2f0b4e3f".to_string());
+/// ```
+pub fn rx_slow_hash(seed_hash: &[u8], input: &[u8]) -> String {
+    let cache = RandomXCache::new(seed_hash);
+    hex::encode(RandomXVm::light(&cache).calculate_hash(input))
+}
+
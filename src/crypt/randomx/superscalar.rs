@@ -0,0 +1,626 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// SuperscalarHash, the random programs turning cache blocks into dataset items. Programs are generated by simulating
+// the decoding and scheduling of their x86 translation on a superscalar CPU, which must be reproduced exactly as the
+// generator output depends on it.
+
+use super::blake2b::blake2b;
+
+const SUPERSCALAR_LATENCY: i32 = 170;
+const CYCLE_MAP_SIZE: usize = SUPERSCALAR_LATENCY as usize + 4;
+const MAX_PROGRAM_SIZE: usize = 3 * SUPERSCALAR_LATENCY as usize + 2;
+const LOOK_FORWARD_CYCLES: i32 = 4;
+const MAX_THROWAWAY_COUNT: i32 = 256;
+// The x86 lea instruction can not have r13 as its base, r5 of the program
+const REGISTER_NEEDS_DISPLACEMENT: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SuperscalarKind {
+    IsubR,
+    IxorR,
+    IaddRs,
+    ImulR,
+    IrorC,
+    IaddC7,
+    IxorC7,
+    IaddC8,
+    IxorC8,
+    IaddC9,
+    IxorC9,
+    ImulhR,
+    IsmulhR,
+    ImulRcp,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SuperscalarInstruction {
+    pub(crate) kind: SuperscalarKind,
+    pub(crate) dst: usize,
+    pub(crate) src: usize,
+    pub(crate) modifier: u8,
+    pub(crate) imm32: u32,
+    // Precomputed for IMUL_RCP
+    pub(crate) reciprocal: u64,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SuperscalarProgram {
+    pub(crate) instructions: Vec<SuperscalarInstruction>,
+    pub(crate) address_register: usize,
+}
+
+impl SuperscalarProgram {
+    pub(crate) fn execute(&self, r: &mut [u64; 8]) {
+        for instruction in self.instructions.iter() {
+            let (dst, src) = (instruction.dst, instruction.src);
+            let imm = instruction.imm32 as i32 as i64 as u64;
+            r[dst] = match instruction.kind {
+                SuperscalarKind::IsubR => r[dst].wrapping_sub(r[src]),
+                SuperscalarKind::IxorR => r[dst] ^ r[src],
+                SuperscalarKind::IaddRs => r[dst].wrapping_add(r[src] << ((instruction.modifier >> 2) % 4)),
+                SuperscalarKind::ImulR => r[dst].wrapping_mul(r[src]),
+                SuperscalarKind::IrorC => r[dst].rotate_right(instruction.imm32),
+                SuperscalarKind::IaddC7 | SuperscalarKind::IaddC8 | SuperscalarKind::IaddC9 => r[dst].wrapping_add(imm),
+                SuperscalarKind::IxorC7 | SuperscalarKind::IxorC8 | SuperscalarKind::IxorC9 => r[dst] ^ imm,
+                SuperscalarKind::ImulhR => ((r[dst] as u128 * r[src] as u128) >> 64) as u64,
+                SuperscalarKind::IsmulhR => ((r[dst] as i64 as i128 * r[src] as i64 as i128) >> 64) as u64,
+                SuperscalarKind::ImulRcp => r[dst].wrapping_mul(instruction.reciprocal),
+            };
+        }
+    }
+}
+
+// Returns 2^x / divisor for the highest x keeping the result below 2^64, the fixed-point reciprocal used by IMUL_RCP
+pub(crate) fn reciprocal(divisor: u32) -> u64 {
+    let divisor = divisor as u64;
+    let p2exp63 = 1u64 << 63;
+    let mut quotient = p2exp63 / divisor;
+    let mut remainder = p2exp63 % divisor;
+    let bits = 64 - divisor.leading_zeros();
+    for _ in 0..bits {
+        if remainder >= divisor - remainder {
+            quotient = quotient.wrapping_mul(2).wrapping_add(1);
+            remainder = remainder.wrapping_mul(2).wrapping_sub(divisor);
+        } else {
+            quotient = quotient.wrapping_mul(2);
+            remainder = remainder.wrapping_mul(2);
+        }
+    }
+    quotient
+}
+
+pub(crate) fn is_zero_or_power_of_2(value: u32) -> bool {
+    value & value.wrapping_sub(1) == 0
+}
+
+// Blake2b-512 based random stream, rehashing its 64 bytes when exhausted
+pub(crate) struct Blake2Generator {
+    data: [u8; 64],
+    index: usize,
+}
+
+impl Blake2Generator {
+    pub(crate) fn new(seed: &[u8], nonce: u32) -> Blake2Generator {
+        let mut data = [0u8; 64];
+        let length = seed.len().min(60);
+        data[..length].copy_from_slice(&seed[..length]);
+        data[60..].copy_from_slice(&nonce.to_le_bytes());
+        Blake2Generator { data, index: 64 }
+    }
+
+    fn get_byte(&mut self) -> u8 {
+        self.check_data(1);
+        self.index += 1;
+        self.data[self.index - 1]
+    }
+
+    fn get_u32(&mut self) -> u32 {
+        self.check_data(4);
+        self.index += 4;
+        u32::from_le_bytes(self.data[self.index - 4..self.index].try_into().unwrap())
+    }
+
+    fn check_data(&mut self, needed: usize) {
+        if self.index + needed > self.data.len() {
+            let data = self.data;
+            blake2b(&data, &mut self.data);
+            self.index = 0;
+        }
+    }
+}
+
+// Execution ports of the simulated CPU
+const P0: u8 = 1;
+const P1: u8 = 2;
+const P5: u8 = 4;
+const P01: u8 = P0 | P1;
+const P05: u8 = P0 | P5;
+const P015: u8 = P0 | P1 | P5;
+
+// An x86 instruction, of one or two uOPs, or none for eliminated moves
+#[derive(Clone, Copy)]
+struct MacroOp {
+    latency: i32,
+    uop1: u8,
+    uop2: u8,
+    dependent: bool,
+}
+
+const fn macro_op(latency: i32, uop1: u8, uop2: u8) -> MacroOp {
+    MacroOp {
+        latency,
+        uop1,
+        uop2,
+        dependent: false,
+    }
+}
+
+const SUB_RR: MacroOp = macro_op(1, P015, 0);
+const XOR_RR: MacroOp = macro_op(1, P015, 0);
+const LEA_SIB: MacroOp = macro_op(1, P01, 0);
+const IMUL_RR: MacroOp = macro_op(3, P1, 0);
+const ROR_RI: MacroOp = macro_op(1, P05, 0);
+const ADD_RI: MacroOp = macro_op(1, P015, 0);
+const XOR_RI: MacroOp = macro_op(1, P015, 0);
+const MOV_RR: MacroOp = macro_op(0, 0, 0);
+const MUL_R: MacroOp = macro_op(4, P1, P5);
+const IMUL_R: MacroOp = macro_op(4, P1, P5);
+const MOV_RI64: MacroOp = macro_op(1, P015, 0);
+const IMUL_RR_DEPENDENT: MacroOp = MacroOp {
+    dependent: true,
+    ..IMUL_RR
+};
+
+// How a program instruction translates to macro-ops and which of them read the source, the destination and write
+// the result
+struct InstructionInfo {
+    kind: Option<SuperscalarKind>,
+    ops: &'static [MacroOp],
+    result_op: i32,
+    dst_op: i32,
+    src_op: i32,
+}
+
+const fn info(kind: SuperscalarKind, op: &'static [MacroOp], src_op: i32) -> InstructionInfo {
+    InstructionInfo {
+        kind: Some(kind),
+        ops: op,
+        result_op: 0,
+        dst_op: 0,
+        src_op,
+    }
+}
+
+const ISUB_R: InstructionInfo = info(SuperscalarKind::IsubR, &[SUB_RR], 0);
+const IXOR_R: InstructionInfo = info(SuperscalarKind::IxorR, &[XOR_RR], 0);
+const IADD_RS: InstructionInfo = info(SuperscalarKind::IaddRs, &[LEA_SIB], 0);
+const IMUL_R_INFO: InstructionInfo = info(SuperscalarKind::ImulR, &[IMUL_RR], 0);
+const IROR_C: InstructionInfo = info(SuperscalarKind::IrorC, &[ROR_RI], -1);
+const IADD_C7: InstructionInfo = info(SuperscalarKind::IaddC7, &[ADD_RI], -1);
+const IXOR_C7: InstructionInfo = info(SuperscalarKind::IxorC7, &[XOR_RI], -1);
+const IADD_C8: InstructionInfo = info(SuperscalarKind::IaddC8, &[ADD_RI], -1);
+const IXOR_C8: InstructionInfo = info(SuperscalarKind::IxorC8, &[XOR_RI], -1);
+const IADD_C9: InstructionInfo = info(SuperscalarKind::IaddC9, &[ADD_RI], -1);
+const IXOR_C9: InstructionInfo = info(SuperscalarKind::IxorC9, &[XOR_RI], -1);
+const IMULH_R: InstructionInfo = InstructionInfo {
+    kind: Some(SuperscalarKind::ImulhR),
+    ops: &[MOV_RR, MUL_R, MOV_RR],
+    result_op: 1,
+    dst_op: 0,
+    src_op: 1,
+};
+const ISMULH_R: InstructionInfo = InstructionInfo {
+    kind: Some(SuperscalarKind::IsmulhR),
+    ops: &[MOV_RR, IMUL_R, MOV_RR],
+    result_op: 1,
+    dst_op: 0,
+    src_op: 1,
+};
+const IMUL_RCP: InstructionInfo = InstructionInfo {
+    kind: Some(SuperscalarKind::ImulRcp),
+    ops: &[MOV_RI64, IMUL_RR_DEPENDENT],
+    result_op: 1,
+    dst_op: 1,
+    src_op: -1,
+};
+const NOP: InstructionInfo = InstructionInfo {
+    kind: None,
+    ops: &[],
+    result_op: 0,
+    dst_op: 0,
+    src_op: 0,
+};
+
+const SLOT_3: [&InstructionInfo; 2] = [&ISUB_R, &IXOR_R];
+const SLOT_3L: [&InstructionInfo; 4] = [&ISUB_R, &IXOR_R, &IMULH_R, &ISMULH_R];
+const SLOT_4: [&InstructionInfo; 2] = [&IROR_C, &IADD_RS];
+const SLOT_7: [&InstructionInfo; 2] = [&IXOR_C7, &IADD_C7];
+const SLOT_8: [&InstructionInfo; 2] = [&IXOR_C8, &IADD_C8];
+const SLOT_9: [&InstructionInfo; 2] = [&IXOR_C9, &IADD_C9];
+
+// Sizes of the instructions decoded in one cycle out of 16 bytes of code
+struct DecoderBuffer {
+    index: i32,
+    counts: &'static [usize],
+}
+
+const BUFFER_484: DecoderBuffer = DecoderBuffer { index: 0, counts: &[4, 8, 4] };
+const BUFFER_7333: DecoderBuffer = DecoderBuffer { index: 1, counts: &[7, 3, 3, 3] };
+const BUFFER_3733: DecoderBuffer = DecoderBuffer { index: 2, counts: &[3, 7, 3, 3] };
+const BUFFER_493: DecoderBuffer = DecoderBuffer { index: 3, counts: &[4, 9, 3] };
+const BUFFER_4444: DecoderBuffer = DecoderBuffer { index: 4, counts: &[4, 4, 4, 4] };
+const BUFFER_3310: DecoderBuffer = DecoderBuffer { index: 5, counts: &[3, 3, 10] };
+const RANDOM_BUFFERS: [&DecoderBuffer; 4] = [&BUFFER_484, &BUFFER_7333, &BUFFER_3733, &BUFFER_493];
+
+fn is_multiplication(kind: Option<SuperscalarKind>) -> bool {
+    matches!(kind, Some(SuperscalarKind::ImulR | SuperscalarKind::ImulhR | SuperscalarKind::IsmulhR | SuperscalarKind::ImulRcp))
+}
+
+fn fetch_next(kind: Option<SuperscalarKind>, cycle: i32, mul_count: i32, gen: &mut Blake2Generator) -> &'static DecoderBuffer {
+    // A 128-bit multiplication decodes to 2 uOPs and needs the 3-3-10 configuration to fit in the 4 uOPs per cycle
+    if matches!(kind, Some(SuperscalarKind::ImulhR | SuperscalarKind::IsmulhR)) {
+        return &BUFFER_3310;
+    }
+    // Keeps the multiplication port saturated
+    if mul_count < cycle + 1 {
+        return &BUFFER_4444;
+    }
+    // IMUL_RCP needs a 4-byte slot for its multiplication first
+    if kind == Some(SuperscalarKind::ImulRcp) {
+        return match gen.get_byte() & 1 {
+            1 => &BUFFER_484,
+            _ => &BUFFER_493,
+        };
+    }
+    RANDOM_BUFFERS[(gen.get_byte() & 3) as usize]
+}
+
+#[derive(Clone, Copy)]
+struct RegisterInfo {
+    latency: i32,
+    last_op_group: Option<SuperscalarKind>,
+    last_op_par: i32,
+}
+
+// Instruction being generated
+struct Candidate {
+    info: &'static InstructionInfo,
+    src: i32,
+    dst: i32,
+    modifier: u8,
+    imm32: u32,
+    op_group: Option<SuperscalarKind>,
+    op_group_par: i32,
+    can_reuse: bool,
+    group_par_is_source: bool,
+}
+
+impl Candidate {
+    fn create_for_slot(&mut self, gen: &mut Blake2Generator, slot_size: usize, fetch_type: i32, is_last: bool) {
+        let info = match slot_size {
+            // The last slot can also hold a 128-bit multiplication
+            3 if is_last => SLOT_3L[(gen.get_byte() & 3) as usize],
+            3 => SLOT_3[(gen.get_byte() & 1) as usize],
+            // The 4-4-4-4 buffer issues multiplications as its first 3 instructions
+            4 if fetch_type == 4 && !is_last => &IMUL_R_INFO,
+            4 => SLOT_4[(gen.get_byte() & 1) as usize],
+            7 => SLOT_7[(gen.get_byte() & 1) as usize],
+            8 => SLOT_8[(gen.get_byte() & 1) as usize],
+            9 => SLOT_9[(gen.get_byte() & 1) as usize],
+            _ => &IMUL_RCP,
+        };
+        self.create(info, gen);
+    }
+
+    fn create(&mut self, info: &'static InstructionInfo, gen: &mut Blake2Generator) {
+        self.info = info;
+        self.src = -1;
+        self.dst = -1;
+        self.can_reuse = false;
+        self.group_par_is_source = false;
+        self.modifier = 0;
+        self.imm32 = 0;
+        let kind = info.kind.unwrap();
+        match kind {
+            SuperscalarKind::IsubR => {
+                self.op_group = Some(SuperscalarKind::IaddRs);
+                self.group_par_is_source = true;
+            }
+            SuperscalarKind::IxorR | SuperscalarKind::ImulR => {
+                self.op_group = Some(kind);
+                self.group_par_is_source = true;
+            }
+            SuperscalarKind::IaddRs => {
+                self.modifier = gen.get_byte();
+                self.op_group = Some(kind);
+                self.group_par_is_source = true;
+            }
+            SuperscalarKind::IrorC => {
+                while self.imm32 == 0 {
+                    self.imm32 = (gen.get_byte() & 63) as u32;
+                }
+                self.op_group = Some(kind);
+                self.op_group_par = -1;
+            }
+            SuperscalarKind::IaddC7 | SuperscalarKind::IaddC8 | SuperscalarKind::IaddC9 => {
+                self.imm32 = gen.get_u32();
+                self.op_group = Some(SuperscalarKind::IaddC7);
+                self.op_group_par = -1;
+            }
+            SuperscalarKind::IxorC7 | SuperscalarKind::IxorC8 | SuperscalarKind::IxorC9 => {
+                self.imm32 = gen.get_u32();
+                self.op_group = Some(SuperscalarKind::IxorC7);
+                self.op_group_par = -1;
+            }
+            SuperscalarKind::ImulhR | SuperscalarKind::IsmulhR => {
+                self.can_reuse = true;
+                self.op_group = Some(kind);
+                self.op_group_par = gen.get_u32() as i32;
+            }
+            SuperscalarKind::ImulRcp => {
+                self.imm32 = gen.get_u32();
+                while is_zero_or_power_of_2(self.imm32) {
+                    self.imm32 = gen.get_u32();
+                }
+                self.op_group = Some(kind);
+                self.op_group_par = -1;
+            }
+        }
+    }
+
+    fn select_destination(&mut self, cycle: i32, allow_chained_mul: bool, registers: &[RegisterInfo; 8], gen: &mut Blake2Generator) -> bool {
+        // The destination must be ready, differ from the source unless allowed, not be multiplied twice in a row,
+        // not repeat its last operation with the same source and not be r5 for IADD_RS
+        let available: Vec<usize> = (0..8)
+            .filter(|&i| {
+                let register = &registers[i];
+                register.latency <= cycle
+                    && (self.can_reuse || i as i32 != self.src)
+                    && (allow_chained_mul || self.op_group != Some(SuperscalarKind::ImulR) || register.last_op_group != Some(SuperscalarKind::ImulR))
+                    && (register.last_op_group != self.op_group || register.last_op_par != self.op_group_par)
+                    && (self.info.kind != Some(SuperscalarKind::IaddRs) || i != REGISTER_NEEDS_DISPLACEMENT)
+            })
+            .collect();
+        match select_register(&available, gen) {
+            Some(register) => {
+                self.dst = register as i32;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn select_source(&mut self, cycle: i32, registers: &[RegisterInfo; 8], gen: &mut Blake2Generator) -> bool {
+        let available: Vec<usize> = (0..8).filter(|&i| registers[i].latency <= cycle).collect();
+        // With only 2 registers available for IADD_RS, r5 has to be the source as it can not be the destination
+        if available.len() == 2 && self.info.kind == Some(SuperscalarKind::IaddRs) && available.contains(&REGISTER_NEEDS_DISPLACEMENT) {
+            self.src = REGISTER_NEEDS_DISPLACEMENT as i32;
+            self.op_group_par = self.src;
+            return true;
+        }
+        match select_register(&available, gen) {
+            Some(register) => {
+                self.src = register as i32;
+                if self.group_par_is_source {
+                    self.op_group_par = self.src;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn to_instruction(&self) -> SuperscalarInstruction {
+        let kind = self.info.kind.unwrap();
+        let dst = self.dst as usize;
+        SuperscalarInstruction {
+            kind,
+            dst,
+            src: if self.src >= 0 { self.src as usize } else { dst },
+            modifier: self.modifier,
+            imm32: self.imm32,
+            reciprocal: match kind {
+                SuperscalarKind::ImulRcp => reciprocal(self.imm32),
+                _ => 0,
+            },
+        }
+    }
+}
+
+fn select_register(available: &[usize], gen: &mut Blake2Generator) -> Option<usize> {
+    match available.len() {
+        0 => None,
+        1 => Some(available[0]),
+        length => Some(available[(gen.get_u32() % length as u32) as usize]),
+    }
+}
+
+// Finds the first cycle from `cycle` where a port allowed for the uOP is free, looking at P5, P0 then P1 so the
+// multiplication port is not taken by instructions which could go elsewhere
+fn schedule_uop(uop: u8, port_busy: &mut [[bool; 3]; CYCLE_MAP_SIZE], mut cycle: i32, commit: bool) -> i32 {
+    while (cycle as usize) < CYCLE_MAP_SIZE {
+        let ports = &mut port_busy[cycle as usize];
+        for (port, slot) in [(P5, 2), (P0, 0), (P1, 1)] {
+            if uop & port != 0 && !ports[slot] {
+                if commit {
+                    ports[slot] = true;
+                }
+                return cycle;
+            }
+        }
+        cycle += 1;
+    }
+    -1
+}
+
+fn schedule_mop(op: &MacroOp, port_busy: &mut [[bool; 3]; CYCLE_MAP_SIZE], mut cycle: i32, dep_cycle: i32, commit: bool) -> i32 {
+    // The multiplication of IMUL_RCP depends on the move of its constant
+    if op.dependent {
+        cycle = cycle.max(dep_cycle);
+    }
+    if op.uop1 == 0 {
+        // Eliminated move
+        return cycle;
+    }
+    if op.uop2 == 0 {
+        return schedule_uop(op.uop1, port_busy, cycle, commit);
+    }
+    // Both uOPs are conservatively required to execute in the same cycle
+    while (cycle as usize) < CYCLE_MAP_SIZE {
+        let cycle1 = schedule_uop(op.uop1, port_busy, cycle, false);
+        let cycle2 = schedule_uop(op.uop2, port_busy, cycle, false);
+        if cycle1 >= 0 && cycle1 == cycle2 {
+            if commit {
+                schedule_uop(op.uop1, port_busy, cycle1, true);
+                schedule_uop(op.uop2, port_busy, cycle2, true);
+            }
+            return cycle1;
+        }
+        cycle += 1;
+    }
+    -1
+}
+
+pub(crate) fn generate_superscalar(gen: &mut Blake2Generator) -> SuperscalarProgram {
+    let mut port_busy = [[false; 3]; CYCLE_MAP_SIZE];
+    let mut registers = [RegisterInfo {
+        latency: 0,
+        last_op_group: None,
+        last_op_par: -1,
+    }; 8];
+    let mut program: Vec<SuperscalarInstruction> = Vec::new();
+    let mut current = Candidate {
+        info: &NOP,
+        src: -1,
+        dst: -1,
+        modifier: 0,
+        imm32: 0,
+        op_group: None,
+        op_group_par: 0,
+        can_reuse: false,
+        group_par_is_source: false,
+    };
+    let mut macro_op_index = 0usize;
+    let mut cycle = 0;
+    let mut dep_cycle = 0;
+    let mut ports_saturated = false;
+    let mut mul_count = 0;
+    let mut throw_away_count = 0;
+
+    // Decodes 16 bytes of code per cycle until an execution port is saturated, which always comes first, or the
+    // program is full
+    let mut decode_cycle = 0;
+    while decode_cycle < SUPERSCALAR_LATENCY && !ports_saturated && program.len() < MAX_PROGRAM_SIZE {
+        let buffer = fetch_next(current.info.kind, decode_cycle, mul_count, gen);
+        let mut buffer_index = 0;
+        while buffer_index < buffer.counts.len() {
+            let top_cycle = cycle;
+            if macro_op_index >= current.info.ops.len() {
+                if ports_saturated || program.len() >= MAX_PROGRAM_SIZE {
+                    break;
+                }
+                // The first macro-op of the new instruction must fit in the current slot
+                current.create_for_slot(gen, buffer.counts[buffer_index], buffer.index, buffer_index + 1 == buffer.counts.len());
+                macro_op_index = 0;
+            }
+            let op = current.info.ops[macro_op_index];
+            let mut schedule_cycle = schedule_mop(&op, &mut port_busy, cycle, dep_cycle, false);
+            if schedule_cycle < 0 {
+                ports_saturated = true;
+                break;
+            }
+
+            // Operands must be ready when the macro-op executes, looking a few cycles forward at most
+            if macro_op_index as i32 == current.info.src_op {
+                let mut forward = 0;
+                while forward < LOOK_FORWARD_CYCLES && !current.select_source(schedule_cycle, &registers, gen) {
+                    schedule_cycle += 1;
+                    cycle += 1;
+                    forward += 1;
+                }
+                if forward == LOOK_FORWARD_CYCLES {
+                    if throw_away_count < MAX_THROWAWAY_COUNT {
+                        throw_away_count += 1;
+                        macro_op_index = current.info.ops.len();
+                        continue;
+                    }
+                    current.info = &NOP;
+                    break;
+                }
+            }
+            if macro_op_index as i32 == current.info.dst_op {
+                let mut forward = 0;
+                while forward < LOOK_FORWARD_CYCLES && !current.select_destination(schedule_cycle, throw_away_count > 0, &registers, gen) {
+                    schedule_cycle += 1;
+                    cycle += 1;
+                    forward += 1;
+                }
+                if forward == LOOK_FORWARD_CYCLES {
+                    if throw_away_count < MAX_THROWAWAY_COUNT {
+                        throw_away_count += 1;
+                        macro_op_index = current.info.ops.len();
+                        continue;
+                    }
+                    current.info = &NOP;
+                    break;
+                }
+            }
+            throw_away_count = 0;
+
+            schedule_cycle = schedule_mop(&op, &mut port_busy, schedule_cycle, schedule_cycle, true);
+            if schedule_cycle < 0 {
+                ports_saturated = true;
+                break;
+            }
+            dep_cycle = schedule_cycle + op.latency;
+
+            if macro_op_index as i32 == current.info.result_op {
+                let register = &mut registers[current.dst as usize];
+                register.latency = dep_cycle;
+                register.last_op_group = current.op_group;
+                register.last_op_par = current.op_group_par;
+            }
+            buffer_index += 1;
+            macro_op_index += 1;
+
+            if schedule_cycle >= SUPERSCALAR_LATENCY {
+                ports_saturated = true;
+            }
+            cycle = top_cycle;
+
+            if macro_op_index >= current.info.ops.len() {
+                program.push(current.to_instruction());
+                mul_count += is_multiplication(current.info.kind) as i32;
+            }
+        }
+        cycle += 1;
+        decode_cycle += 1;
+    }
+
+    // The address register is the one with the longest dependency chain, assuming 1 cycle per operation and
+    // unlimited parallelism
+    let mut asic_latencies = [0; 8];
+    for instruction in program.iter() {
+        let latency_dst = asic_latencies[instruction.dst] + 1;
+        let latency_src = if instruction.dst != instruction.src { asic_latencies[instruction.src] + 1 } else { 0 };
+        asic_latencies[instruction.dst] = latency_dst.max(latency_src);
+    }
+    let mut address_register = 0;
+    for i in 0..8 {
+        if asic_latencies[i] > asic_latencies[address_register] {
+            address_register = i;
+        }
+    }
+    SuperscalarProgram {
+        instructions: program,
+        address_register,
+    }
+}
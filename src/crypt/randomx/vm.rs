@@ -0,0 +1,584 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// The RandomX virtual machine, interpreting the random programs generated from the hashed input. Programs are
+// decoded once into operations with their operands resolved, then executed 2048 times over the scratchpad.
+
+use super::aes_gen::{fill_aes_1rx4, fill_aes_4rx4, hash_aes_1rx4};
+use super::blake2b::blake2b;
+use super::cache::{RandomXCache, RandomXDataset, DATASET_BASE_SIZE, DATASET_EXTRA_SIZE};
+use super::float::{self, RoundingMode};
+use super::superscalar::{is_zero_or_power_of_2, reciprocal};
+use std::fmt;
+
+const PROGRAM_SIZE: usize = 256;
+const PROGRAM_ITERATIONS: usize = 2048;
+const PROGRAM_COUNT: usize = 8;
+const ENTROPY_SIZE: usize = 128;
+const SCRATCHPAD_L1: u64 = 16384;
+const SCRATCHPAD_L2: u64 = 262144;
+const SCRATCHPAD_L3: u64 = 2097152;
+const SCRATCHPAD_L1_MASK: u64 = SCRATCHPAD_L1 - 8;
+const SCRATCHPAD_L2_MASK: u64 = SCRATCHPAD_L2 - 8;
+const SCRATCHPAD_L3_MASK: u64 = SCRATCHPAD_L3 - 8;
+const SCRATCHPAD_L3_MASK64: u64 = SCRATCHPAD_L3 - 64;
+const CACHE_LINE_SIZE: u64 = 64;
+const CACHE_LINE_ALIGN_MASK: u64 = (DATASET_BASE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
+const JUMP_OFFSET: u32 = 8;
+const CONDITION_MASK: u64 = (1 << 8) - 1;
+const STORE_L3_CONDITION: u8 = 14;
+const REGISTER_NEEDS_DISPLACEMENT: usize = 5;
+const MANTISSA_SIZE: u32 = 52;
+const MANTISSA_MASK: u64 = (1 << MANTISSA_SIZE) - 1;
+const DYNAMIC_MANTISSA_MASK: u64 = (1 << (MANTISSA_SIZE + 4)) - 1;
+const SCALE_MASK: u64 = 0x80F0000000000000;
+
+// Cumulative opcode frequencies out of 256, in the order of the specification
+const CEIL_IADD_RS: u8 = 16;
+const CEIL_IADD_M: u8 = CEIL_IADD_RS + 7;
+const CEIL_ISUB_R: u8 = CEIL_IADD_M + 16;
+const CEIL_ISUB_M: u8 = CEIL_ISUB_R + 7;
+const CEIL_IMUL_R: u8 = CEIL_ISUB_M + 16;
+const CEIL_IMUL_M: u8 = CEIL_IMUL_R + 4;
+const CEIL_IMULH_R: u8 = CEIL_IMUL_M + 4;
+const CEIL_IMULH_M: u8 = CEIL_IMULH_R + 1;
+const CEIL_ISMULH_R: u8 = CEIL_IMULH_M + 4;
+const CEIL_ISMULH_M: u8 = CEIL_ISMULH_R + 1;
+const CEIL_IMUL_RCP: u8 = CEIL_ISMULH_M + 8;
+const CEIL_INEG_R: u8 = CEIL_IMUL_RCP + 2;
+const CEIL_IXOR_R: u8 = CEIL_INEG_R + 15;
+const CEIL_IXOR_M: u8 = CEIL_IXOR_R + 5;
+const CEIL_IROR_R: u8 = CEIL_IXOR_M + 8;
+const CEIL_IROL_R: u8 = CEIL_IROR_R + 2;
+const CEIL_ISWAP_R: u8 = CEIL_IROL_R + 4;
+const CEIL_FSWAP_R: u8 = CEIL_ISWAP_R + 4;
+const CEIL_FADD_R: u8 = CEIL_FSWAP_R + 16;
+const CEIL_FADD_M: u8 = CEIL_FADD_R + 5;
+const CEIL_FSUB_R: u8 = CEIL_FADD_M + 16;
+const CEIL_FSUB_M: u8 = CEIL_FSUB_R + 5;
+const CEIL_FSCAL_R: u8 = CEIL_FSUB_M + 6;
+const CEIL_FMUL_R: u8 = CEIL_FSCAL_R + 32;
+const CEIL_FDIV_M: u8 = CEIL_FMUL_R + 4;
+const CEIL_FSQRT_R: u8 = CEIL_FDIV_M + 6;
+const CEIL_CBRANCH: u8 = CEIL_FSQRT_R + 25;
+const CEIL_CFROUND: u8 = CEIL_CBRANCH + 1;
+
+// Second operand of an integer instruction, the immediate replacing the source register when both are the same
+#[derive(Clone, Copy)]
+enum Source {
+    Register(usize),
+    Immediate(u64),
+}
+
+// Scratchpad operand, read at (base + imm) & mask with a zero base when the source is the destination
+#[derive(Clone, Copy)]
+struct Address {
+    base: Option<usize>,
+    imm: u64,
+    mask: u64,
+}
+
+#[derive(Clone, Copy)]
+enum Operation {
+    IaddRs { dst: usize, src: usize, shift: u32, imm: u64 },
+    IaddM { dst: usize, address: Address },
+    IsubR { dst: usize, src: Source },
+    IsubM { dst: usize, address: Address },
+    ImulR { dst: usize, src: Source },
+    ImulM { dst: usize, address: Address },
+    ImulhR { dst: usize, src: usize },
+    ImulhM { dst: usize, address: Address },
+    IsmulhR { dst: usize, src: usize },
+    IsmulhM { dst: usize, address: Address },
+    InegR { dst: usize },
+    IxorR { dst: usize, src: Source },
+    IxorM { dst: usize, address: Address },
+    IrorR { dst: usize, src: Source },
+    IrolR { dst: usize, src: Source },
+    IswapR { dst: usize, src: usize },
+    // Registers 0 to 3 are the f group, 4 to 7 the e group
+    FswapR { dst: usize },
+    FaddR { dst: usize, src: usize },
+    FaddM { dst: usize, address: Address },
+    FsubR { dst: usize, src: usize },
+    FsubM { dst: usize, address: Address },
+    FscalR { dst: usize },
+    FmulR { dst: usize, src: usize },
+    FdivM { dst: usize, address: Address },
+    FsqrtR { dst: usize },
+    Cbranch { dst: usize, imm: u64, mask: u64, target: i32 },
+    Cfround { src: usize, rotate: u32 },
+    Istore { dst: usize, src: usize, imm: u64, mask: u64 },
+    Nop,
+}
+
+fn sign_extend(imm32: u32) -> u64 {
+    imm32 as i32 as i64 as u64
+}
+
+// Decodes the 8-byte instructions of a program, tracking the last instruction modifying each register as the target
+// of the branches
+fn decode_program(program: &[u8]) -> Vec<Operation> {
+    let mut register_usage = [-1i32; 8];
+    let mut operations = Vec::with_capacity(PROGRAM_SIZE);
+    for (i, bytes) in program.chunks_exact(8).enumerate() {
+        let opcode = bytes[0];
+        let dst = bytes[1] as usize % 8;
+        let src = bytes[2] as usize % 8;
+        let modifier = bytes[3];
+        let imm32 = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        let mem_mask = match modifier % 4 {
+            0 => SCRATCHPAD_L2_MASK,
+            _ => SCRATCHPAD_L1_MASK,
+        };
+        let address = match src != dst {
+            true => Address {
+                base: Some(src),
+                imm: sign_extend(imm32),
+                mask: mem_mask,
+            },
+            false => Address {
+                base: None,
+                imm: sign_extend(imm32),
+                mask: SCRATCHPAD_L3_MASK,
+            },
+        };
+        let float_address = Address {
+            base: Some(src),
+            imm: sign_extend(imm32),
+            mask: mem_mask,
+        };
+        let source = match src != dst {
+            true => Source::Register(src),
+            false => Source::Immediate(sign_extend(imm32)),
+        };
+        let operation = match opcode {
+            _ if opcode < CEIL_IADD_RS => Operation::IaddRs {
+                dst,
+                src,
+                shift: ((modifier >> 2) % 4) as u32,
+                imm: match dst {
+                    REGISTER_NEEDS_DISPLACEMENT => sign_extend(imm32),
+                    _ => 0,
+                },
+            },
+            _ if opcode < CEIL_IADD_M => Operation::IaddM { dst, address },
+            _ if opcode < CEIL_ISUB_R => Operation::IsubR { dst, src: source },
+            _ if opcode < CEIL_ISUB_M => Operation::IsubM { dst, address },
+            _ if opcode < CEIL_IMUL_R => Operation::ImulR { dst, src: source },
+            _ if opcode < CEIL_IMUL_M => Operation::ImulM { dst, address },
+            _ if opcode < CEIL_IMULH_R => Operation::ImulhR { dst, src },
+            _ if opcode < CEIL_IMULH_M => Operation::ImulhM { dst, address },
+            _ if opcode < CEIL_ISMULH_R => Operation::IsmulhR { dst, src },
+            _ if opcode < CEIL_ISMULH_M => Operation::IsmulhM { dst, address },
+            _ if opcode < CEIL_IMUL_RCP => match is_zero_or_power_of_2(imm32) {
+                true => Operation::Nop,
+                false => Operation::ImulR {
+                    dst,
+                    src: Source::Immediate(reciprocal(imm32)),
+                },
+            },
+            _ if opcode < CEIL_INEG_R => Operation::InegR { dst },
+            _ if opcode < CEIL_IXOR_R => Operation::IxorR { dst, src: source },
+            _ if opcode < CEIL_IXOR_M => Operation::IxorM { dst, address },
+            _ if opcode < CEIL_IROR_R => Operation::IrorR { dst, src: source },
+            _ if opcode < CEIL_IROL_R => Operation::IrolR { dst, src: source },
+            _ if opcode < CEIL_ISWAP_R => match src != dst {
+                true => Operation::IswapR { dst, src },
+                false => Operation::Nop,
+            },
+            _ if opcode < CEIL_FSWAP_R => Operation::FswapR { dst },
+            _ if opcode < CEIL_FADD_R => Operation::FaddR { dst: dst % 4, src: src % 4 },
+            _ if opcode < CEIL_FADD_M => Operation::FaddM {
+                dst: dst % 4,
+                address: float_address,
+            },
+            _ if opcode < CEIL_FSUB_R => Operation::FsubR { dst: dst % 4, src: src % 4 },
+            _ if opcode < CEIL_FSUB_M => Operation::FsubM {
+                dst: dst % 4,
+                address: float_address,
+            },
+            _ if opcode < CEIL_FSCAL_R => Operation::FscalR { dst: dst % 4 },
+            _ if opcode < CEIL_FMUL_R => Operation::FmulR { dst: dst % 4, src: src % 4 },
+            _ if opcode < CEIL_FDIV_M => Operation::FdivM {
+                dst: dst % 4,
+                address: float_address,
+            },
+            _ if opcode < CEIL_FSQRT_R => Operation::FsqrtR { dst: dst % 4 },
+            _ if opcode < CEIL_CBRANCH => {
+                // Setting the bit under the condition mask limits the number of successive jumps
+                let shift = (modifier >> 4) as u32 + JUMP_OFFSET;
+                let imm = (sign_extend(imm32) | (1 << shift)) & !(1 << (shift - 1));
+                Operation::Cbranch {
+                    dst,
+                    imm,
+                    mask: CONDITION_MASK << shift,
+                    target: register_usage[dst],
+                }
+            }
+            _ if opcode < CEIL_CFROUND => Operation::Cfround { src, rotate: imm32 & 63 },
+            _ => Operation::Istore {
+                dst,
+                src,
+                imm: sign_extend(imm32),
+                mask: match modifier >> 4 {
+                    condition if condition < STORE_L3_CONDITION => mem_mask,
+                    _ => SCRATCHPAD_L3_MASK,
+                },
+            },
+        };
+        match operation {
+            Operation::IaddRs { dst, .. }
+            | Operation::IaddM { dst, .. }
+            | Operation::IsubR { dst, .. }
+            | Operation::IsubM { dst, .. }
+            | Operation::ImulR { dst, .. }
+            | Operation::ImulM { dst, .. }
+            | Operation::ImulhR { dst, .. }
+            | Operation::ImulhM { dst, .. }
+            | Operation::IsmulhR { dst, .. }
+            | Operation::IsmulhM { dst, .. }
+            | Operation::InegR { dst }
+            | Operation::IxorR { dst, .. }
+            | Operation::IxorM { dst, .. }
+            | Operation::IrorR { dst, .. }
+            | Operation::IrolR { dst, .. } => register_usage[dst] = i as i32,
+            Operation::IswapR { dst, src } => {
+                register_usage[dst] = i as i32;
+                register_usage[src] = i as i32;
+            }
+            Operation::Cbranch { .. } => register_usage = [i as i32; 8],
+            _ => {}
+        }
+        operations.push(operation);
+    }
+    operations
+}
+
+fn small_positive_float_bits(entropy: u64) -> u64 {
+    let exponent = ((entropy >> 59) + 1023) & 0x7ff;
+    (exponent << MANTISSA_SIZE) | (entropy & MANTISSA_MASK)
+}
+
+fn float_mask(entropy: u64) -> u64 {
+    let exponent = (0x300 | ((entropy >> 60) << 4)) << MANTISSA_SIZE;
+    (entropy & ((1 << 22) - 1)) | exponent
+}
+
+enum DatasetSource<'a> {
+    Light(&'a RandomXCache),
+    Full(&'a RandomXDataset),
+}
+
+/// RandomXVm hashes inputs with RandomX, computing dataset items from a [`RandomXCache`](struct.RandomXCache.html)
+/// in light mode or reading them from a [`RandomXDataset`](struct.RandomXDataset.html) in full mode
+///
+/// Both modes give the same hashes. A VM holds its own 2 MiB scratchpad and can be reused for any number of hashes,
+/// one VM per thread can share the same cache or dataset.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::crypt::randomx::{RandomXCache, RandomXVm};
+///
+/// let cache = RandomXCache::new(b"test key 000");
+/// let mut vm = RandomXVm::light(&cache);
+/// let hash = vm.calculate_hash(b"This is a test");
+/// assert_eq!(hex::encode(hash), "639183aae1bf4c9a35884cb46b09cad9175f04efd7684e7262a0ac1c2f0b4e3f");
+/// ```
+pub struct RandomXVm<'a> {
+    source: DatasetSource<'a>,
+    scratchpad: Vec<u8>,
+    r: [u64; 8],
+    f: [[f64; 2]; 4],
+    e: [[f64; 2]; 4],
+    a: [[f64; 2]; 4],
+    mx: u64,
+    ma: u64,
+    read_registers: [usize; 4],
+    dataset_offset: u64,
+    e_mask: [u64; 2],
+    rounding: RoundingMode,
+}
+
+impl<'a> RandomXVm<'a> {
+    /// Creates a light mode VM, needing only the 256 MiB cache but hashing several times slower than full mode
+    pub fn light(cache: &'a RandomXCache) -> RandomXVm<'a> {
+        RandomXVm::new(DatasetSource::Light(cache))
+    }
+
+    /// Creates a full mode VM over a dataset built from the cache of the key
+    pub fn full(dataset: &'a RandomXDataset) -> RandomXVm<'a> {
+        RandomXVm::new(DatasetSource::Full(dataset))
+    }
+
+    fn new(source: DatasetSource<'a>) -> RandomXVm<'a> {
+        RandomXVm {
+            source,
+            scratchpad: vec![0u8; SCRATCHPAD_L3 as usize],
+            r: [0; 8],
+            f: [[0.0; 2]; 4],
+            e: [[0.0; 2]; 4],
+            a: [[0.0; 2]; 4],
+            mx: 0,
+            ma: 0,
+            read_registers: [0; 4],
+            dataset_offset: 0,
+            e_mask: [0; 2],
+            rounding: RoundingMode::Nearest,
+        }
+    }
+
+    /// Returns the 32-byte RandomX hash of the input
+    pub fn calculate_hash(&mut self, input: &[u8]) -> [u8; 32] {
+        let mut seed = [0u8; 64];
+        blake2b(input, &mut seed);
+        fill_aes_1rx4(&mut seed, &mut self.scratchpad);
+        self.rounding = RoundingMode::Nearest;
+        for chain in 0..PROGRAM_COUNT {
+            self.run(&seed);
+            if chain + 1 < PROGRAM_COUNT {
+                blake2b(&self.register_file(), &mut seed);
+            }
+        }
+        let a = hash_aes_1rx4(&self.scratchpad);
+        for (register, lanes) in self.a.iter_mut().zip(a.chunks_exact(16)) {
+            *register = [
+                f64::from_le_bytes(lanes[..8].try_into().unwrap()),
+                f64::from_le_bytes(lanes[8..].try_into().unwrap()),
+            ];
+        }
+        let mut hash = [0u8; 32];
+        blake2b(&self.register_file(), &mut hash);
+        hash
+    }
+
+    // Registers r, f, e and a as 256 little endian bytes
+    fn register_file(&self) -> [u8; 256] {
+        let mut file = [0u8; 256];
+        let floats = self.f.iter().chain(self.e.iter()).chain(self.a.iter()).flatten().map(|value| value.to_bits());
+        for (chunk, word) in file.chunks_exact_mut(8).zip(self.r.iter().copied().chain(floats)) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        file
+    }
+
+    fn run(&mut self, seed: &[u8; 64]) {
+        let mut program = [0u8; ENTROPY_SIZE + PROGRAM_SIZE * 8];
+        fill_aes_4rx4(seed, &mut program);
+        let entropy = |i: usize| u64::from_le_bytes(program[i * 8..(i + 1) * 8].try_into().unwrap());
+
+        for (i, register) in self.a.iter_mut().enumerate() {
+            *register = [
+                f64::from_bits(small_positive_float_bits(entropy(2 * i))),
+                f64::from_bits(small_positive_float_bits(entropy(2 * i + 1))),
+            ];
+        }
+        self.ma = entropy(8) & CACHE_LINE_ALIGN_MASK;
+        self.mx = entropy(10) & 0xffffffff;
+        let address_registers = entropy(12);
+        for (i, register) in self.read_registers.iter_mut().enumerate() {
+            *register = 2 * i + ((address_registers >> i) & 1) as usize;
+        }
+        self.dataset_offset = (entropy(13) % (DATASET_EXTRA_SIZE / CACHE_LINE_SIZE + 1)) * CACHE_LINE_SIZE;
+        self.e_mask = [float_mask(entropy(14)), float_mask(entropy(15))];
+
+        let operations = decode_program(&program[ENTROPY_SIZE..]);
+        self.execute(&operations);
+    }
+
+    fn execute(&mut self, operations: &[Operation]) {
+        self.r = [0; 8];
+        let mut sp_addr0 = self.mx;
+        let mut sp_addr1 = self.ma;
+        for _ in 0..PROGRAM_ITERATIONS {
+            let sp_mix = self.r[self.read_registers[0]] ^ self.r[self.read_registers[1]];
+            sp_addr0 = (sp_addr0 ^ sp_mix) & SCRATCHPAD_L3_MASK64;
+            sp_addr1 = (sp_addr1 ^ (sp_mix >> 32)) & SCRATCHPAD_L3_MASK64;
+            for i in 0..8 {
+                self.r[i] ^= self.load64(sp_addr0 + 8 * i as u64);
+            }
+            for i in 0..4 {
+                self.f[i] = self.load_float_pair(sp_addr1 + 8 * i as u64);
+            }
+            for i in 0..4 {
+                let pair = self.load_float_pair(sp_addr1 + 8 * (4 + i) as u64);
+                self.e[i] = self.mask_exponent_mantissa(pair);
+            }
+
+            self.interpret(operations);
+
+            self.mx ^= (self.r[self.read_registers[2]] ^ self.r[self.read_registers[3]]) & 0xffffffff;
+            self.mx &= CACHE_LINE_ALIGN_MASK;
+            self.read_dataset(self.dataset_offset + self.ma);
+            std::mem::swap(&mut self.mx, &mut self.ma);
+
+            for i in 0..8 {
+                let offset = (sp_addr1 + 8 * i as u64) as usize;
+                self.scratchpad[offset..offset + 8].copy_from_slice(&self.r[i].to_le_bytes());
+            }
+            for i in 0..4 {
+                let offset = (sp_addr0 + 16 * i as u64) as usize;
+                for lane in 0..2 {
+                    let value = self.f[i][lane].to_bits() ^ self.e[i][lane].to_bits();
+                    self.f[i][lane] = f64::from_bits(value);
+                    self.scratchpad[offset + 8 * lane..offset + 8 * lane + 8].copy_from_slice(&value.to_le_bytes());
+                }
+            }
+            sp_addr0 = 0;
+            sp_addr1 = 0;
+        }
+    }
+
+    fn read_dataset(&mut self, address: u64) {
+        let item_number = address / CACHE_LINE_SIZE;
+        let item = match self.source {
+            DatasetSource::Light(cache) => cache.dataset_item(item_number),
+            DatasetSource::Full(dataset) => dataset.item(item_number).try_into().unwrap(),
+        };
+        for (register, word) in self.r.iter_mut().zip(item.iter()) {
+            *register ^= word;
+        }
+    }
+
+    fn load64(&self, offset: u64) -> u64 {
+        let offset = offset as usize;
+        u64::from_le_bytes(self.scratchpad[offset..offset + 8].try_into().unwrap())
+    }
+
+    // Two signed 32-bit integers converted to a pair of doubles
+    fn load_float_pair(&self, offset: u64) -> [f64; 2] {
+        let offset = offset as usize;
+        let low = i32::from_le_bytes(self.scratchpad[offset..offset + 4].try_into().unwrap());
+        let high = i32::from_le_bytes(self.scratchpad[offset + 4..offset + 8].try_into().unwrap());
+        [low as f64, high as f64]
+    }
+
+    fn mask_exponent_mantissa(&self, pair: [f64; 2]) -> [f64; 2] {
+        [
+            f64::from_bits((pair[0].to_bits() & DYNAMIC_MANTISSA_MASK) | self.e_mask[0]),
+            f64::from_bits((pair[1].to_bits() & DYNAMIC_MANTISSA_MASK) | self.e_mask[1]),
+        ]
+    }
+
+    fn address(&self, address: &Address) -> u64 {
+        let base = address.base.map_or(0, |register| self.r[register]);
+        base.wrapping_add(address.imm) & address.mask
+    }
+
+    fn source(&self, source: &Source) -> u64 {
+        match *source {
+            Source::Register(register) => self.r[register],
+            Source::Immediate(imm) => imm,
+        }
+    }
+
+    fn interpret(&mut self, operations: &[Operation]) {
+        let mut mode = self.rounding;
+        let mut pc = 0;
+        while pc < operations.len() {
+            match operations[pc] {
+                Operation::IaddRs { dst, src, shift, imm } => {
+                    self.r[dst] = self.r[dst].wrapping_add(self.r[src] << shift).wrapping_add(imm);
+                }
+                Operation::IaddM { dst, address } => {
+                    self.r[dst] = self.r[dst].wrapping_add(self.load64(self.address(&address)));
+                }
+                Operation::IsubR { dst, src } => self.r[dst] = self.r[dst].wrapping_sub(self.source(&src)),
+                Operation::IsubM { dst, address } => {
+                    self.r[dst] = self.r[dst].wrapping_sub(self.load64(self.address(&address)));
+                }
+                Operation::ImulR { dst, src } => self.r[dst] = self.r[dst].wrapping_mul(self.source(&src)),
+                Operation::ImulM { dst, address } => {
+                    self.r[dst] = self.r[dst].wrapping_mul(self.load64(self.address(&address)));
+                }
+                Operation::ImulhR { dst, src } => self.r[dst] = mulh(self.r[dst], self.r[src]),
+                Operation::ImulhM { dst, address } => self.r[dst] = mulh(self.r[dst], self.load64(self.address(&address))),
+                Operation::IsmulhR { dst, src } => self.r[dst] = smulh(self.r[dst], self.r[src]),
+                Operation::IsmulhM { dst, address } => {
+                    self.r[dst] = smulh(self.r[dst], self.load64(self.address(&address)));
+                }
+                Operation::InegR { dst } => self.r[dst] = self.r[dst].wrapping_neg(),
+                Operation::IxorR { dst, src } => self.r[dst] ^= self.source(&src),
+                Operation::IxorM { dst, address } => self.r[dst] ^= self.load64(self.address(&address)),
+                Operation::IrorR { dst, src } => self.r[dst] = self.r[dst].rotate_right((self.source(&src) & 63) as u32),
+                Operation::IrolR { dst, src } => self.r[dst] = self.r[dst].rotate_left((self.source(&src) & 63) as u32),
+                Operation::IswapR { dst, src } => self.r.swap(dst, src),
+                Operation::FswapR { dst } => match dst {
+                    0..=3 => self.f[dst].swap(0, 1),
+                    _ => self.e[dst - 4].swap(0, 1),
+                },
+                Operation::FaddR { dst, src } => self.f[dst] = lanes(self.f[dst], self.a[src], float::add, mode),
+                Operation::FaddM { dst, address } => {
+                    let value = self.load_float_pair(self.address(&address));
+                    self.f[dst] = lanes(self.f[dst], value, float::add, mode);
+                }
+                Operation::FsubR { dst, src } => self.f[dst] = lanes(self.f[dst], self.a[src], float::sub, mode),
+                Operation::FsubM { dst, address } => {
+                    let value = self.load_float_pair(self.address(&address));
+                    self.f[dst] = lanes(self.f[dst], value, float::sub, mode);
+                }
+                Operation::FscalR { dst } => {
+                    for lane in self.f[dst].iter_mut() {
+                        *lane = f64::from_bits(lane.to_bits() ^ SCALE_MASK);
+                    }
+                }
+                Operation::FmulR { dst, src } => self.e[dst] = lanes(self.e[dst], self.a[src], float::mul, mode),
+                Operation::FdivM { dst, address } => {
+                    let value = self.mask_exponent_mantissa(self.load_float_pair(self.address(&address)));
+                    self.e[dst] = lanes(self.e[dst], value, float::div, mode);
+                }
+                Operation::FsqrtR { dst } => {
+                    for lane in self.e[dst].iter_mut() {
+                        *lane = float::sqrt(*lane, mode);
+                    }
+                }
+                Operation::Cbranch { dst, imm, mask, target } => {
+                    self.r[dst] = self.r[dst].wrapping_add(imm);
+                    if self.r[dst] & mask == 0 {
+                        // The loop increment brings the program counter to the instruction after the target
+                        pc = (target + 1) as usize;
+                        continue;
+                    }
+                }
+                Operation::Cfround { src, rotate } => mode = RoundingMode::from_bits(self.r[src].rotate_right(rotate)),
+                Operation::Istore { dst, src, imm, mask } => {
+                    let offset = (self.r[dst].wrapping_add(imm) & mask) as usize;
+                    self.scratchpad[offset..offset + 8].copy_from_slice(&self.r[src].to_le_bytes());
+                }
+                Operation::Nop => {}
+            }
+            pc += 1;
+        }
+        self.rounding = mode;
+    }
+}
+
+// Applies a binary floating point operation to both lanes of a register
+fn lanes(a: [f64; 2], b: [f64; 2], op: fn(f64, f64, RoundingMode) -> f64, mode: RoundingMode) -> [f64; 2] {
+    [op(a[0], b[0], mode), op(a[1], b[1], mode)]
+}
+
+fn mulh(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) >> 64) as u64
+}
+
+fn smulh(a: u64, b: u64) -> u64 {
+    ((a as i64 as i128 * b as i64 as i128) >> 64) as u64
+}
+
+impl fmt::Debug for RandomXVm<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.source {
+            DatasetSource::Light(_) => "light",
+            DatasetSource::Full(_) => "full",
+        };
+        f.debug_struct("RandomXVm").field("mode", &mode).finish_non_exhaustive()
+    }
+}
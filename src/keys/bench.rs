@@ -0,0 +1,214 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::outputs::{derivation_to_scalar, derive_output_pubkey, derive_view_tag, generate_key_derivation};
+use super::commitment::{commit, commitment_mask, ecdh_encode};
+use super::scan::{scan_tx_for_subaddresses, ScanFilter};
+use super::subaddress::SubaddressTable;
+use super::types::{Commitment, KeyImage, OneTimeKey, PrivateKey, ViewPair};
+use crate::blocks::{EncryptedAmount, RawTx, RctBase, RctType, Transaction, TransactionPrefix, TxExtra, TxInput, TxOutTarget, TxOutput, TxSignatures};
+use curve25519_dalek::Scalar;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// BenchConfig describes the synthetic chain generated by [`generate_synthetic_blocks`](fn.generate_synthetic_blocks.html)
+/// and how [`run_scan_benchmark`](fn.run_scan_benchmark.html) scans it
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchConfig {
+    pub blocks: usize,
+    pub txs_per_block: usize,
+    pub outputs_per_tx: usize,
+    /// Share of the outputs paying the scanning wallet, from 0.0 to 1.0
+    pub owned_ratio: f64,
+    /// Gives outputs view tags, so the scanner can skip most foreign outputs after a single hash
+    pub view_tags: bool,
+    /// Scanning threads, blocks being split between them
+    pub threads: usize,
+    /// Seed of the generator, the same seed always gives the same chain
+    pub seed: u64,
+}
+
+impl Default for BenchConfig {
+    /// 100 blocks of 20 transactions with 2 outputs, 1% owned, with view tags, on a single thread
+    fn default() -> BenchConfig {
+        BenchConfig {
+            blocks: 100,
+            txs_per_block: 20,
+            outputs_per_tx: 2,
+            owned_ratio: 0.01,
+            view_tags: true,
+            threads: 1,
+            seed: 0,
+        }
+    }
+}
+
+/// BenchReport is the outcome of a scanning benchmark
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchReport {
+    pub outputs_scanned: u64,
+    pub outputs_owned: u64,
+    pub elapsed: Duration,
+    pub threads: usize,
+    pub view_tags: bool,
+}
+
+impl BenchReport {
+    /// Returns the scanning throughput in outputs per second
+    pub fn outputs_per_second(&self) -> f64 {
+        self.outputs_scanned as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} outputs ({} owned) in {:.3}s on {} thread(s) {} view tags: {:.0} outputs/s",
+            self.outputs_scanned,
+            self.outputs_owned,
+            self.elapsed.as_secs_f64(),
+            self.threads,
+            if self.view_tags { "with" } else { "without" },
+            self.outputs_per_second()
+        )
+    }
+}
+
+/// Generates a synthetic chain of version 2 transactions, grouped by block, whose outputs pay the given view pair in
+/// the configured ratio and random keys otherwise
+///
+/// Owned outputs carry valid encrypted amounts and commitments, so they go through the whole scanning path. Needs
+/// the `bench` feature.
+pub fn generate_synthetic_blocks(view_pair: &ViewPair, config: &BenchConfig) -> Vec<Vec<RawTx>> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut random_scalar = move || -> Scalar {
+        let mut bytes = [0u8; 64];
+        rng.fill(&mut bytes[..]);
+        Scalar::from_bytes_mod_order_wide(&bytes)
+    };
+    let mut owned_rng = StdRng::seed_from_u64(config.seed ^ 0x6f776e6564);
+    let public_view_key = view_pair.view.public_key();
+    (0..config.blocks)
+        .map(|_| {
+            (0..config.txs_per_block)
+                .map(|_| {
+                    let tx_secret_key = PrivateKey::from_bytes_mod_order(random_scalar().to_bytes());
+                    let derivation = generate_key_derivation(&public_view_key, &tx_secret_key);
+                    let mut outputs = Vec::with_capacity(config.outputs_per_tx);
+                    let mut encrypted_amounts = Vec::with_capacity(config.outputs_per_tx);
+                    let mut commitments = Vec::with_capacity(config.outputs_per_tx);
+                    for index in 0..config.outputs_per_tx as u64 {
+                        let (key, view_tag, amount, commitment) = if owned_rng.gen_bool(config.owned_ratio.clamp(0.0, 1.0)) {
+                            let key = OneTimeKey::from(derive_output_pubkey(&derivation, index, &view_pair.spend));
+                            let shared_secret = derivation_to_scalar(&derivation, index);
+                            let amount = 1000000000 + index;
+                            let mask = commitment_mask(&shared_secret);
+                            let encrypted = match ecdh_encode(amount, &mask, &shared_secret, true) {
+                                EncryptedAmount::Compact { amount } => amount,
+                                EncryptedAmount::Full { .. } => unreachable!(),
+                            };
+                            (key, derive_view_tag(&derivation, index), encrypted, commit(amount, &mask))
+                        } else {
+                            let key = OneTimeKey::from(PrivateKey::from_bytes_mod_order(random_scalar().to_bytes()).public_key());
+                            let commitment = Commitment::from_bytes(*PrivateKey::from_bytes_mod_order(random_scalar().to_bytes()).public_key().as_bytes());
+                            (key, random_scalar().as_bytes()[0], random_scalar().as_bytes()[..8].try_into().unwrap(), commitment)
+                        };
+                        outputs.push(TxOutput {
+                            amount: 0,
+                            target: match config.view_tags {
+                                true => TxOutTarget::ToTaggedKey { key, view_tag },
+                                false => TxOutTarget::ToKey { key },
+                            },
+                        });
+                        encrypted_amounts.push(EncryptedAmount::Compact { amount });
+                        commitments.push(commitment);
+                    }
+                    Transaction {
+                        prefix: TransactionPrefix {
+                            version: 2,
+                            unlock_time: 0,
+                            inputs: vec![TxInput::ToKey {
+                                amount: 0,
+                                key_offsets: vec![1; 16],
+                                key_image: KeyImage::from_bytes(random_scalar().to_bytes()),
+                            }],
+                            outputs,
+                            extra: TxExtra::new().with_tx_public_key(&tx_secret_key.public_key()).to_bytes(),
+                        },
+                        signatures: TxSignatures::V2 {
+                            base: RctBase {
+                                rct_type: RctType::BulletproofPlus,
+                                fee: 30000000,
+                                pseudo_outs: Vec::new(),
+                                encrypted_amounts,
+                                commitments,
+                            },
+                            prunable: Vec::new(),
+                        },
+                    }
+                    .to_raw_tx()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Scans the given blocks with the given table on `threads` threads and measures the throughput
+///
+/// Needs the `bench` feature.
+pub fn bench_scanning(table: &SubaddressTable, blocks: &[Vec<RawTx>], threads: usize, view_tags: bool) -> BenchReport {
+    let threads = threads.max(1);
+    let filter = ScanFilter::default();
+    let chunk_size = blocks.len().div_ceil(threads).max(1);
+    let start = Instant::now();
+    let outputs_owned: u64 = std::thread::scope(|scope| {
+        let workers: Vec<_> = blocks
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let filter = &filter;
+                scope.spawn(move || chunk.iter().flatten().map(|tx| scan_tx_for_subaddresses(table, tx, Some(0), filter).len() as u64).sum::<u64>())
+            })
+            .collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).sum()
+    });
+    BenchReport {
+        outputs_scanned: blocks.iter().flatten().map(|tx| tx.vout.len() as u64).sum(),
+        outputs_owned,
+        elapsed: start.elapsed(),
+        threads,
+        view_tags,
+    }
+}
+
+/// Generates a synthetic chain for a random wallet with the given configuration and measures how fast it scans
+///
+/// Needs the `bench` feature.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{run_scan_benchmark, BenchConfig};
+///
+/// let config = BenchConfig { blocks: 4, txs_per_block: 5, owned_ratio: 0.5, threads: 2, ..BenchConfig::default() };
+/// let report = run_scan_benchmark(&config);
+/// assert_eq!(report.outputs_scanned, 40);
+/// assert!(report.outputs_owned > 0);
+/// println!("{}", report);
+/// ```
+pub fn run_scan_benchmark(config: &BenchConfig) -> BenchReport {
+    // Wallet keys come from another stream than the chain
+    let mut rng = StdRng::seed_from_u64(!config.seed);
+    let private_spend_key = PrivateKey::from_bytes_mod_order(rng.gen());
+    let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+    let blocks = generate_synthetic_blocks(&view_pair, config);
+    let table = SubaddressTable::new(&view_pair, 1, 1);
+    bench_scanning(&table, &blocks, config.threads, config.view_tags)
+}
@@ -0,0 +1,122 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::types::Commitment;
+use crate::blocks::EncryptedAmount;
+use crate::crypt::keccak::{hash_to_scalar, keccak256};
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY, Scalar};
+
+// Second generator H of Pedersen commitments, `8 * Hp(G)`
+pub(crate) const H_POINT: [u8; 32] = [
+    0x8b, 0x65, 0x59, 0x70, 0x15, 0x37, 0x99, 0xaf, 0x2a, 0xea, 0xdc, 0x9f, 0xf1, 0xad, 0xd0, 0xea, 0x6c, 0x72, 0x51, 0xd5, 0x41, 0x54, 0xcf, 0xa9, 0x2c, 0x17, 0x3a, 0x0d, 0xd3, 0x9c, 0x1f, 0x94,
+];
+
+/// Returns the Pedersen commitment `mask * G + amount * H` hiding the amount of a RingCT output
+///
+/// Example:
+/// ```
+/// use libmonero::keys::commit;
+/// use curve25519_dalek::Scalar;
+///
+/// // H itself
+/// assert_eq!(commit(1, &Scalar::ZERO).to_hex(), "8b655970153799af2aeadc9ff1add0ea6c7251d54154cfa92c173a0dd39c1f94");
+/// ```
+pub fn commit(amount: u64, mask: &Scalar) -> Commitment {
+    let h = CompressedEdwardsY(H_POINT).decompress().unwrap();
+    Commitment::from_bytes((ED25519_BASEPOINT_TABLE * mask + h * Scalar::from(amount)).compress().to_bytes())
+}
+
+/// Returns the commitment `G + amount * H` of a clear amount, used for fees, coinbase outputs and pre-RingCT inputs
+///
+/// Example:
+/// ```
+/// use libmonero::keys::zero_commit;
+///
+/// // G
+/// assert_eq!(zero_commit(0).to_hex(), "5866666666666666666666666666666666666666666666666666666666666666");
+/// ```
+pub fn zero_commit(amount: u64) -> Commitment {
+    commit(amount, &Scalar::ONE)
+}
+
+/// Returns the commitment mask `Hs("commitment_mask" || shared_secret)` of the outputs using compact encrypted amounts
+///
+/// `shared_secret` is the output's [`derivation_to_scalar`](fn.derivation_to_scalar.html).
+pub fn commitment_mask(shared_secret: &Scalar) -> Scalar {
+    hash_to_scalar(&[b"commitment_mask".as_slice(), shared_secret.as_bytes()].concat())
+}
+
+/// Encrypts the amount and mask of an output for its receiver, monero-core's `ecdhEncode`
+///
+/// `shared_secret` is the output's [`derivation_to_scalar`](fn.derivation_to_scalar.html) and `compact` is true for
+/// RingCT types 4 and above.
+///
+/// In the `compact` scheme the mask is not encrypted, the receiver derives it from the shared secret, so the output's
+/// commitment must use [`commitment_mask(shared_secret)`](fn.commitment_mask.html) as mask.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{commitment_mask, ecdh_decode, ecdh_encode};
+/// use curve25519_dalek::Scalar;
+///
+/// let shared_secret = Scalar::from(42u64);
+/// let mask = commitment_mask(&shared_secret);
+/// let encrypted = ecdh_encode(1000000000000, &mask, &shared_secret, true);
+/// assert_eq!(ecdh_decode(&encrypted, &shared_secret), Ok((1000000000000, mask)));
+/// ```
+pub fn ecdh_encode(amount: u64, mask: &Scalar, shared_secret: &Scalar, compact: bool) -> EncryptedAmount {
+    if compact {
+        let mut encrypted = amount.to_le_bytes();
+        for (byte, key) in encrypted.iter_mut().zip(amount_keystream(shared_secret)) {
+            *byte ^= key;
+        }
+        return EncryptedAmount::Compact { amount: encrypted };
+    }
+    let (mask_secret, amount_secret) = full_secrets(shared_secret);
+    EncryptedAmount::Full {
+        mask: (mask + mask_secret).to_bytes(),
+        amount: (Scalar::from(amount) + amount_secret).to_bytes(),
+    }
+}
+
+/// Decrypts the amount and mask of an output, monero-core's `ecdhDecode`
+/// Returns `(amount, mask)` if succesfull
+/// Returns an error message if the decrypted amount does not fit in 64 bits, which means the shared secret is wrong
+pub fn ecdh_decode(encrypted: &EncryptedAmount, shared_secret: &Scalar) -> Result<(u64, Scalar), String> {
+    match encrypted {
+        EncryptedAmount::Compact { amount } => {
+            let mut amount = *amount;
+            for (byte, key) in amount.iter_mut().zip(amount_keystream(shared_secret)) {
+                *byte ^= key;
+            }
+            Ok((u64::from_le_bytes(amount), commitment_mask(shared_secret)))
+        }
+        EncryptedAmount::Full { mask, amount } => {
+            let (mask_secret, amount_secret) = full_secrets(shared_secret);
+            let bytes = (Scalar::from_bytes_mod_order(*amount) - amount_secret).to_bytes();
+            if bytes[8..].iter().any(|b| *b != 0) {
+                return Err("decoded amount overflows 64 bits".to_string());
+            }
+            Ok((u64::from_le_bytes(bytes[..8].try_into().unwrap()), Scalar::from_bytes_mod_order(*mask) - mask_secret))
+        }
+    }
+}
+
+// First 8 bytes of Keccak256("amount" || shared_secret)
+fn amount_keystream(shared_secret: &Scalar) -> [u8; 8] {
+    keccak256(&[b"amount".as_slice(), shared_secret.as_bytes()].concat())[..8].try_into().unwrap()
+}
+
+// Hs(shared_secret) hiding the mask and Hs(Hs(shared_secret)) hiding the amount
+fn full_secrets(shared_secret: &Scalar) -> (Scalar, Scalar) {
+    let mask_secret = hash_to_scalar(shared_secret.as_bytes());
+    let amount_secret = hash_to_scalar(mask_secret.as_bytes());
+    (mask_secret, amount_secret)
+}
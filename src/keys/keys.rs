@@ -12,22 +12,17 @@
 //!
 //! This module is for everything related to keys, such as generating seeds, deriving keys from seeds, deriving public keys from private keys, and deriving addresses from public keys etc.
 
-use crate::crypt::ed25519::sc_reduce32;
-use crate::mnemonics::original::wordsets::{WordsetOriginal, WORDSETSORIGINAL};
+use super::types::{decode_hex_32, encode_address_bytes, KeyImage, PrivateKey, PublicKey};
+use crate::crypt::cryptonight::cn_slow_hash_v0;
+use crate::crypt::hash_to_point::hash_to_point;
+use crate::utils::Network;
+use crate::mnemonics::original::wordsets::{original_wordsets, WordsetOriginal};
 use crc32fast::Hasher;
-use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, EdwardsPoint, Scalar};
-use rand::Rng;
+use curve25519_dalek::Scalar;
+use rand::{CryptoRng, Rng, RngCore};
 use sha3::{Digest, Keccak256};
+use zeroize::Zeroizing;
 use core::panic;
-use std::ops::Mul;
-use std::vec;
-
-/// Returns cryptographically secure random element of the given array
-fn secure_random_element<'x>(array: &'x [&'x str]) -> &'x str {
-    let mut rng = rand::thread_rng();
-    let random_index = rng.gen_range(0..array.len());
-    array[random_index]
-}
 
 // Returns cryptographically secure random bits of given length
 fn get_random_bits(length: u64) -> Vec<bool> {
@@ -39,71 +34,70 @@ fn get_random_bits(length: u64) -> Vec<bool> {
     bit_array
 }
 
+/// Returns the first `prefix_length` characters (not bytes) of given word, or the whole word if it is shorter
+pub(crate) fn utf8_prefix(word: &str, prefix_length: usize) -> &str {
+    match word.char_indices().nth(prefix_length) {
+        Some((end, _)) => &word[..end],
+        None => word,
+    }
+}
+
 /// Calculates CRC32 checksum index for given array (probably the seed)
-fn get_checksum_index(array: &[&str], prefix_length: usize) -> usize {
-    let mut trimmed_words: String = String::new();
+pub(crate) fn get_checksum_index<S: AsRef<str>>(array: &[S], prefix_length: usize) -> usize {
+    let mut trimmed_words = Zeroizing::new(String::new());
     for word in array {
-        trimmed_words.push_str(&word[0..prefix_length]);
+        trimmed_words.push_str(utf8_prefix(word.as_ref(), prefix_length));
     }
     let mut hasher = Hasher::new();
     hasher.update(trimmed_words.as_bytes());
     usize::try_from(hasher.finalize()).unwrap() % array.len()
 }
 
-/// Generates a cryptographically secure 1626-type (25-word) seed for given language
-fn generate_original_seed(language: &str) -> Vec<&str> {
-    // Check if language is supported
-    if !WORDSETSORIGINAL.iter().any(|x| x.name == language) {
-        panic!("Language not found");
-    }
-    // Generate seed
-    let mut seed: Vec<&str> = Vec::new();
-    let mut prefix_len: usize = 3;
-    for wordset in WORDSETSORIGINAL.iter() {
-        if wordset.name == language {
-            prefix_len = wordset.prefix_len;
-            for _ in 0..24 {
-                let word = secure_random_element(&wordset.words[..]);
-                seed.push(word);
-            }
-            break;
-        } else {
-            continue;
+/// Returns the 1626-type wordset for given language, panics if the language is not supported
+pub(crate) fn get_original_wordset(language: &str) -> &'static WordsetOriginal {
+    original_wordsets()
+        .into_iter()
+        .find(|wordset| wordset.name == language)
+        .unwrap_or_else(|| panic!("Language not found"))
+}
+
+/// Encodes given bytes (a multiple of 4 bytes) as a 1626-type mnemonic, 3 words per 4 bytes, and adds the checksum word
+pub(crate) fn encode_original_words(bytes: &[u8], wordset: &WordsetOriginal) -> Zeroizing<Vec<String>> {
+    let wordset_len = wordset.words.len() as u32;
+    let mut seed: Zeroizing<Vec<String>> = Zeroizing::new(Vec::new());
+    for chunk in bytes.chunks(4) {
+        let mut value = Zeroizing::new([0u8; 4]);
+        value.copy_from_slice(chunk);
+        let x = Zeroizing::new(u32::from_le_bytes(*value));
+        let w1 = *x % wordset_len;
+        let w2 = (*x / wordset_len + w1) % wordset_len;
+        let w3 = (*x / wordset_len / wordset_len + w2) % wordset_len;
+        for word in [w1, w2, w3] {
+            seed.push(wordset.words[word as usize].to_string());
         }
     }
     // Add checksum word
-    let checksum_index = get_checksum_index(&seed, prefix_len);
-    seed.push(seed[checksum_index]);
-    // Finally, return the seed
+    if wordset.prefix_len > 0 {
+        let checksum_index = get_checksum_index(&seed, wordset.prefix_len);
+        let checksum_word = seed[checksum_index].clone();
+        seed.push(checksum_word);
+    }
     seed
 }
 
+/// Generates a cryptographically secure 1626-type (25-word) seed for given language
+fn generate_original_seed<R: RngCore + CryptoRng>(rng: &mut R, language: &str) -> Zeroizing<Vec<String>> {
+    let mut entropy = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(&mut *entropy);
+    generate_seed_from_entropy(&entropy, language)
+}
+
 /// Generates a cryptographically secure 1626-type (13-word) seed for given language
-fn generate_mymonero_seed(language: &str) -> Vec<&str> {
-    // Check if language is supported
-    if !WORDSETSORIGINAL.iter().any(|x| x.name == language) {
-        panic!("Language not found");
-    }
-    // Generate seed
-    let mut seed: Vec<&str> = Vec::new();
-    let mut prefix_len: usize = 3;
-    for wordset in WORDSETSORIGINAL.iter() {
-        if wordset.name == language {
-            prefix_len = wordset.prefix_len;
-            for _ in 0..12 {
-                let word = secure_random_element(&wordset.words[..]);
-                seed.push(word);
-            }
-            break;
-        } else {
-            continue;
-        }
-    }
-    // Add checksum word
-    let checksum_index = get_checksum_index(&seed, prefix_len);
-    seed.push(seed[checksum_index]);
-    // Finally, return the seed
-    seed
+fn generate_mymonero_seed<R: RngCore + CryptoRng>(rng: &mut R, language: &str) -> Zeroizing<Vec<String>> {
+    let wordset = get_original_wordset(language);
+    let mut entropy = Zeroizing::new([0u8; 16]);
+    rng.fill_bytes(&mut *entropy);
+    encode_original_words(&*entropy, wordset)
 }
 
 fn print_seed_pretty(seed: Vec<Vec<bool>>) {
@@ -240,26 +234,63 @@ fn generate_polyseed_seed(language: &str) -> Vec<&str> {
 /// - `polyseed` : (TO BE IMPLEMENTED)
 /// > DISCLAIMER: polyseed is not implemented yet
 ///
+/// `original` and `mymonero` also accept the languages loaded with [`load_wordset`](fn.load_wordset.html).
+///
 /// Example:
 /// ```
 /// use libmonero::keys::generate_seed;
+/// use zeroize::Zeroizing;
 ///
-/// let mnemonic: Vec<String> = generate_seed("en", "original");
+/// let mnemonic: Zeroizing<Vec<String>> = generate_seed("en", "original");
 /// // Not equal to the example below because the seed is generated randomly, but the seed is valid
-/// assert_ne!(mnemonic, vec!["tissue", "raking", "haunted", "huts", "afraid", "volcano", "howls", "liar", "egotistic", "befit", "rounded", "older", "bluntly", "imbalance", "pivot", "exotic", "tuxedo", "amaze", "mostly", "lukewarm", "macro", "vocal", "hounded", "biplane", "rounded"].iter().map(|&s| s.to_string()).collect::<Vec<String>>());
+/// assert_ne!(*mnemonic, vec!["tissue", "raking", "haunted", "huts", "afraid", "volcano", "howls", "liar", "egotistic", "befit", "rounded", "older", "bluntly", "imbalance", "pivot", "exotic", "tuxedo", "amaze", "mostly", "lukewarm", "macro", "vocal", "hounded", "biplane", "rounded"].iter().map(|&s| s.to_string()).collect::<Vec<String>>());
 /// ```
-pub fn generate_seed(language: &str, seed_type: &str) -> Vec<String> {
-    let seed = match seed_type {
-        "original" => generate_original_seed(language),
-        "mymonero" => generate_mymonero_seed(language),
+pub fn generate_seed(language: &str, seed_type: &str) -> Zeroizing<Vec<String>> {
+    generate_seed_with_rng(&mut rand::thread_rng(), language, seed_type)
+}
+
+/// Generates a mnemonic phrase for given language and seed type using the given random number generator
+///
+/// Same as [`generate_seed`](fn.generate_seed.html) but lets the caller provide the randomness, e.g. from a hardware RNG
+///
+/// Example:
+/// ```
+/// use libmonero::keys::generate_seed_with_rng;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let first = generate_seed_with_rng(&mut StdRng::seed_from_u64(42), "en", "mymonero");
+/// let second = generate_seed_with_rng(&mut StdRng::seed_from_u64(42), "en", "mymonero");
+/// assert_eq!(first.len(), 13);
+/// assert_eq!(first, second);
+/// ```
+pub fn generate_seed_with_rng<R: RngCore + CryptoRng>(rng: &mut R, language: &str, seed_type: &str) -> Zeroizing<Vec<String>> {
+    match seed_type {
+        "original" => generate_original_seed(rng, language),
+        "mymonero" => generate_mymonero_seed(rng, language),
         "polyseed" => panic!("Polyseed is not implemented yet"),
         _ => panic!("Invalid seed type"),
-    };
-    let mut seed_string: Vec<String> = Vec::new();
-    for word in seed {
-        seed_string.push(word.to_string());
     }
-    seed_string
+}
+
+/// Generates an original (25-word) mnemonic phrase for given language from the given 32 bytes of entropy
+///
+/// Entropy is reduced modulo the curve order first, like the reference wallet does, so the mnemonic encodes the private spend key.
+/// Same entropy always gives the same mnemonic.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_hex_seed, generate_seed_from_entropy};
+///
+/// let mut entropy = [0u8; 32];
+/// hex::decode_to_slice("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08", &mut entropy).unwrap();
+/// let mnemonic = generate_seed_from_entropy(&entropy, "en");
+/// assert_eq!(mnemonic.len(), 25);
+/// assert_eq!(*derive_hex_seed(mnemonic.to_vec()), "c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08");
+/// ```
+pub fn generate_seed_from_entropy(entropy: &[u8; 32], language: &str) -> Zeroizing<Vec<String>> {
+    let wordset = get_original_wordset(language);
+    let private_spend_key = PrivateKey::from_bytes_mod_order(*entropy);
+    encode_original_words(private_spend_key.as_bytes(), wordset)
 }
 
 /// Swaps endianness of a 4-byte string
@@ -272,19 +303,21 @@ fn swap_endian_4_byte(s: &str) -> String {
 /// Example:
 /// ```
 /// use libmonero::keys::derive_hex_seed;
+/// use zeroize::Zeroizing;
 ///
 /// let mnemonic: Vec<String> = vec!["tissue", "raking", "haunted", "huts", "afraid", "volcano", "howls", "liar", "egotistic", "befit", "rounded", "older", "bluntly", "imbalance", "pivot", "exotic", "tuxedo", "amaze", "mostly", "lukewarm", "macro", "vocal", "hounded", "biplane", "rounded"].iter().map(|s| s.to_string()).collect();
-/// let hex_seed: String = derive_hex_seed(mnemonic);
-/// assert_eq!(hex_seed, "f7b3beabc9bd6ced864096c0891a8fdf94dc714178a09828775dba01b4df9ab8".to_string());
+/// let hex_seed: Zeroizing<String> = derive_hex_seed(mnemonic);
+/// assert_eq!(*hex_seed, "f7b3beabc9bd6ced864096c0891a8fdf94dc714178a09828775dba01b4df9ab8".to_string());
 /// ```
-pub fn derive_hex_seed(mut mnemonic_seed: Vec<String>) -> String {
+pub fn derive_hex_seed(mnemonic_seed: Vec<String>) -> Zeroizing<String> {
+    let mut mnemonic_seed = Zeroizing::new(mnemonic_seed);
     // Find the wordset for the given seed
     let mut the_wordset = &WordsetOriginal {
         name: "x",
         prefix_len: 0,
         words: [""; 1626],
     };
-    for wordset in WORDSETSORIGINAL.iter() {
+    for wordset in original_wordsets() {
         if mnemonic_seed
             .iter()
             .all(|elem| wordset.words.contains(&elem.as_str()))
@@ -305,14 +338,14 @@ pub fn derive_hex_seed(mut mnemonic_seed: Vec<String>) -> String {
     // Get a vector of truncated words
     let mut trunc_words: Vec<&str> = Vec::new();
     for word in the_wordset.words.iter() {
-        trunc_words.push(&word[..the_wordset.prefix_len]);
+        trunc_words.push(utf8_prefix(word, the_wordset.prefix_len));
     }
     if trunc_words.is_empty() {
         panic!("Something went wrong when decoding your private key, please try again");
     }
 
     // Derive hex seed
-    let mut hex_seed = String::new();
+    let mut hex_seed = Zeroizing::new(String::new());
     let wordset_len: usize = the_wordset.words.len();
     for i in (0..mnemonic_seed.len()).step_by(3) {
         let (w1, w2, w3): (usize, usize, usize);
@@ -335,15 +368,15 @@ pub fn derive_hex_seed(mut mnemonic_seed: Vec<String>) -> String {
         } else {
             w1 = trunc_words
                 .iter()
-                .position(|&x| x.starts_with(&mnemonic_seed[i][..the_wordset.prefix_len]))
+                .position(|&x| x == utf8_prefix(&mnemonic_seed[i], the_wordset.prefix_len))
                 .unwrap_or_else(|| panic!("Invalid word in seed, please check your seed"));
             w2 = trunc_words
                 .iter()
-                .position(|&x| x.starts_with(&mnemonic_seed[i + 1][..the_wordset.prefix_len]))
+                .position(|&x| x == utf8_prefix(&mnemonic_seed[i + 1], the_wordset.prefix_len))
                 .unwrap_or_else(|| panic!("Invalid word in seed, please check your seed"));
             w3 = trunc_words
                 .iter()
-                .position(|&x| x.starts_with(&mnemonic_seed[i + 2][..the_wordset.prefix_len]))
+                .position(|&x| x == utf8_prefix(&mnemonic_seed[i + 2], the_wordset.prefix_len))
                 .unwrap_or_else(|| panic!("Invalid word in seed, please check your seed"));
         }
 
@@ -354,82 +387,76 @@ pub fn derive_hex_seed(mut mnemonic_seed: Vec<String>) -> String {
             panic!("Something went wrong when decoding your private key, please try again");
         }
 
-        hex_seed += &swap_endian_4_byte(&format!("{:08x}", x));
+        hex_seed.push_str(&swap_endian_4_byte(&format!("{:08x}", x)));
     }
 
     hex_seed
 }
 
+/// Derives hexadecimal seed from the given mnemonic seed and optional seed passphrase (also known as seed offset)
+///
+/// Uses the same key derivation as the Monero CLI wallet, `seed - cn_slow_hash(passphrase) mod l`,
+/// so seeds protected with a passphrase there can be restored here. Only original (25-word) seeds support passphrases,
+/// `None` or an empty passphrase gives the same result as [`derive_hex_seed`](fn.derive_hex_seed.html).
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_hex_seed, derive_hex_seed_with_passphrase};
+///
+/// let mnemonic: Vec<String> = vec!["tissue", "raking", "haunted", "huts", "afraid", "volcano", "howls", "liar", "egotistic", "befit", "rounded", "older", "bluntly", "imbalance", "pivot", "exotic", "tuxedo", "amaze", "mostly", "lukewarm", "macro", "vocal", "hounded", "biplane", "rounded"].iter().map(|s| s.to_string()).collect();
+/// let hex_seed = derive_hex_seed_with_passphrase(mnemonic.clone(), Some("correct horse battery staple")).unwrap();
+/// assert_eq!(*hex_seed, "4fade0d200d86e9763e13cb35b26e51e5991bbaa509f85c8e129bcfb021ebb05".to_string());
+/// assert_eq!(derive_hex_seed_with_passphrase(mnemonic.clone(), None).unwrap(), derive_hex_seed(mnemonic));
+/// ```
+///
+/// Returns the hexadecimal seed if succesfull
+/// Returns an error message if a passphrase is given for a seed which is not an original (25-word) seed
+pub fn derive_hex_seed_with_passphrase(mnemonic_seed: Vec<String>, passphrase: Option<&str>) -> Result<Zeroizing<String>, String> {
+    let hex_seed = derive_hex_seed(mnemonic_seed);
+    let passphrase = match passphrase {
+        Some(passphrase) if !passphrase.is_empty() => passphrase,
+        _ => return Ok(hex_seed),
+    };
+    if hex_seed.len() != 64 {
+        return Err("Seed passphrases are only supported for original (25-word) seeds".to_string());
+    }
+    let seed_bytes = Zeroizing::new(decode_hex_32(&hex_seed)?);
+    let passphrase_hash = Zeroizing::new(decode_hex_32(&cn_slow_hash_v0(passphrase.as_bytes()))?);
+    let key = Zeroizing::new(Scalar::from_bytes_mod_order(*seed_bytes) - Scalar::from_bytes_mod_order(*passphrase_hash));
+    Ok(Zeroizing::new(hex::encode(key.as_bytes())))
+}
+
 /// Derives private keys for original (25-word) (64-byte hex) type seeds
-fn derive_original_priv_keys(hex_seed: String) -> Vec<String> {
+fn derive_original_priv_keys(hex_seed: &str) -> Zeroizing<Vec<String>> {
     // Turn hex seed into bytes
-    let hex_bytes = hex::decode(hex_seed).unwrap();
-    let mut hex_bytes_array = [0u8; 32];
+    let hex_bytes = Zeroizing::new(hex::decode(hex_seed).unwrap());
+    let mut hex_bytes_array = Zeroizing::new([0u8; 32]);
     hex_bytes_array.copy_from_slice(&hex_bytes);
     // Pass bytes through sc_reduce32 function to get private spend key
-    sc_reduce32(&mut hex_bytes_array);
-    let mut priv_spend_key = String::new();
-    for i in (0..hex_bytes_array.len()).step_by(32) {
-        let mut priv_key = String::new();
-        for byte in hex_bytes_array.iter().skip(i).take(32) {
-            priv_key.push_str(&format!("{:02x}", byte));
-        }
-        priv_spend_key.push_str(&priv_key);
-    }
-    // Turn private spend key into bytes and pass through Keccak256 function
-    let priv_spend_key_bytes = hex::decode(priv_spend_key.clone()).unwrap();
-    let priv_view_key_bytes = Keccak256::digest(priv_spend_key_bytes);
-    let mut priv_view_key_array = [0u8; 32];
-    priv_view_key_array.copy_from_slice(&priv_view_key_bytes);
-    // Pass bytes through sc_reduce32 function to get private view key
-    sc_reduce32(&mut priv_view_key_array as &mut [u8; 32]);
-    let mut priv_view_key = String::new();
-    for i in (0..priv_view_key_array.len()).step_by(32) {
-        let mut priv_key = String::new();
-        for byte in priv_view_key_array.iter().skip(i).take(32) {
-            priv_key.push_str(&format!("{:02x}", byte));
-        }
-        priv_view_key.push_str(&priv_key);
-    }
+    let priv_spend_key = PrivateKey::from_bytes_mod_order(*hex_bytes_array);
+    // Pass private spend key through Keccak256 and sc_reduce32 functions to get private view key
+    let priv_view_key = priv_spend_key.derive_view_key();
     // Finally, return the keys
-    vec![priv_spend_key, priv_view_key]
+    Zeroizing::new(vec![priv_spend_key.to_hex().to_string(), priv_view_key.to_hex().to_string()])
 }
 
 /// Derives private keys for MyMonero (13-word) (32-byte hex) type seeds
-fn derive_mymonero_priv_keys(hex_seed: String) -> Vec<String> {
+fn derive_mymonero_priv_keys(hex_seed: &str) -> Zeroizing<Vec<String>> {
     // Keccak and sc_reduce32 to get private spend key
-    let hex_bytes = hex::decode(hex_seed).unwrap();
-    let priv_spend_key_bytes = Keccak256::digest(&hex_bytes);
-    let mut priv_spend_key_array = [0u8; 32];
-    priv_spend_key_array.copy_from_slice(&priv_spend_key_bytes);
-    sc_reduce32(&mut priv_spend_key_array as &mut [u8; 32]);
-    let mut priv_spend_key = String::new();
-    for i in (0..priv_spend_key_array.len()).step_by(32) {
-        let mut priv_key = String::new();
-        for item in priv_spend_key_array.iter().skip(i).take(32) {
-            priv_key.push_str(&format!("{:02x}", item));
-        }
-        priv_spend_key.push_str(&priv_key);
-    }
+    let hex_bytes = Zeroizing::new(hex::decode(hex_seed).unwrap());
+    let mut priv_spend_key_array = Zeroizing::new([0u8; 32]);
+    priv_spend_key_array.copy_from_slice(&Keccak256::digest(&*hex_bytes));
+    let priv_spend_key = PrivateKey::from_bytes_mod_order(*priv_spend_key_array);
     // Double Keccak and sc_reduce32 of hex_seed to get private view key
-    let priv_view_key_bytes = Keccak256::digest(&hex_bytes);
-    let mut priv_view_key_array = [0u8; 32];
-    priv_view_key_array.copy_from_slice(&priv_view_key_bytes);
+    let mut priv_view_key_array = Zeroizing::new([0u8; 32]);
+    priv_view_key_array.copy_from_slice(&Keccak256::digest(&*hex_bytes));
     // Keccak again
-    let priv_view_key_bytes = Keccak256::digest(priv_view_key_array);
+    let priv_view_key_bytes = Keccak256::digest(*priv_view_key_array);
     priv_view_key_array.copy_from_slice(&priv_view_key_bytes);
     // sc_reduce32
-    sc_reduce32(&mut priv_view_key_array as &mut [u8; 32]);
-    let mut priv_view_key = String::new();
-    for i in (0..priv_view_key_array.len()).step_by(32) {
-        let mut priv_key = String::new();
-        for item in priv_view_key_array.iter().skip(i).take(32) {
-            priv_key.push_str(&format!("{:02x}", item));
-        }
-        priv_view_key.push_str(&priv_key);
-    }
+    let priv_view_key = PrivateKey::from_bytes_mod_order(*priv_view_key_array);
     // Finally, return the keys
-    vec![priv_spend_key, priv_view_key]
+    Zeroizing::new(vec![priv_spend_key.to_hex().to_string(), priv_view_key.to_hex().to_string()])
 }
 
 /// Derives private keys from given hex seed
@@ -439,84 +466,62 @@ fn derive_mymonero_priv_keys(hex_seed: String) -> Vec<String> {
 /// Example:
 /// ```
 /// use libmonero::keys::derive_priv_keys;
+/// use zeroize::Zeroizing;
 ///
 /// let hex_seed: String = "f7b3beabc9bd6ced864096c0891a8fdf94dc714178a09828775dba01b4df9ab8".to_string();
-/// let priv_keys: Vec<String> = derive_priv_keys(hex_seed);
-/// assert_eq!(priv_keys, vec!["c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08", "0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908"].iter().map(|&s| s.to_string()).collect::<Vec<String>>());
+/// let priv_keys: Zeroizing<Vec<String>> = derive_priv_keys(hex_seed);
+/// assert_eq!(*priv_keys, vec!["c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08", "0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908"].iter().map(|&s| s.to_string()).collect::<Vec<String>>());
 /// ```
-pub fn derive_priv_keys(hex_seed: String) -> Vec<String> {
+pub fn derive_priv_keys(hex_seed: String) -> Zeroizing<Vec<String>> {
+    let hex_seed = Zeroizing::new(hex_seed);
     match hex_seed.len() {
-        32 => derive_mymonero_priv_keys(hex_seed),
-        64 => derive_original_priv_keys(hex_seed),
+        32 => derive_mymonero_priv_keys(&hex_seed),
+        64 => derive_original_priv_keys(&hex_seed),
         _ => panic!("Invalid hex seed"),
     }
 }
 
 /// Derives private view key from given private spend key
 ///
+/// The private spend key is reduced modulo the group order first, like any 32 bytes monero-core turns into a scalar.
+///
 /// Example:
 /// ```
 /// use libmonero::keys::derive_priv_vk_from_priv_sk;
+/// use zeroize::Zeroizing;
 ///
 /// let private_spend_key: String = "c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08".to_string();
-/// let private_view_key: String = derive_priv_vk_from_priv_sk(private_spend_key);
-/// assert_eq!(private_view_key, "0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908".to_string());
+/// let private_view_key: Zeroizing<String> = derive_priv_vk_from_priv_sk(private_spend_key).unwrap();
+/// assert_eq!(*private_view_key, "0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908".to_string());
 /// ```
-pub fn derive_priv_vk_from_priv_sk(private_spend_key: String) -> String {
-    // Turn private spend key into bytes and pass through Keccak256 function
-    let priv_spend_key_bytes = hex::decode(private_spend_key.clone()).unwrap();
-    let priv_view_key_bytes = Keccak256::digest(priv_spend_key_bytes);
-    let mut priv_view_key_array = [0u8; 32];
-    priv_view_key_array.copy_from_slice(&priv_view_key_bytes);
-    // Pass bytes through sc_reduce32 function to get private view key
-    sc_reduce32(&mut priv_view_key_array as &mut [u8; 32]);
-    let mut priv_view_key = String::new();
-    for i in (0..priv_view_key_array.len()).step_by(32) {
-        let mut priv_key = String::new();
-        for item in priv_view_key_array.iter().skip(i).take(32) {
-            priv_key.push_str(&format!("{:02x}", item));
-        }
-        priv_view_key.push_str(&priv_key);
-    }
-    // Finally, return the private view key
-    priv_view_key
-}
-
-/// Performs scalar multiplication of the Ed25519 base point by a given scalar, yielding a corresponding point on the elliptic curve
-fn ge_scalar_mult_base(scalar: &Scalar) -> EdwardsPoint {
-    ED25519_BASEPOINT_TABLE.mul(scalar as &Scalar)
+///
+/// Returns the private view key if succesfull
+/// Returns an error message if the private spend key is not 32 bytes of hex
+pub fn derive_priv_vk_from_priv_sk(private_spend_key: String) -> Result<Zeroizing<String>, String> {
+    let private_spend_key = Zeroizing::new(private_spend_key);
+    let private_spend_key = PrivateKey::from_bytes_mod_order(decode_hex_32(&private_spend_key)?);
+    Ok(private_spend_key.derive_view_key().to_hex())
 }
 
 /// Derives public key from given private key (spend or view)
 ///
+/// The private key is reduced modulo the group order first, like any 32 bytes monero-core turns into a scalar.
+///
 /// Example:
 /// ```
 /// use libmonero::keys::derive_pub_key;
 ///
 /// let private_spend_key: String = "c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08".to_string();
-/// let public_spend_key: String = derive_pub_key(private_spend_key);
+/// let public_spend_key: String = derive_pub_key(private_spend_key).unwrap();
 /// assert_eq!(public_spend_key, "e78d891dd2be407f24e6470caad956e1b746ae0b41cd8252f96684090bc05d95".to_string());
 /// ```
-pub fn derive_pub_key(private_key: String) -> String {
-    // Turn private key into bytes
-    let private_key_bytes = hex::decode(private_key.clone()).unwrap();
-    let mut private_key_array = [0u8; 32];
-    private_key_array.copy_from_slice(&private_key_bytes);
-    let key_scalar = Scalar::from_bytes_mod_order(private_key_array);
-    // Scalar multiplication with the base point
-    let result_point = ge_scalar_mult_base(&key_scalar);
-    // The result_point now contains the public key
-    let public_key_bytes = result_point.compress().to_bytes();
-    let mut public_key = String::new();
-    for i in (0..public_key_bytes.len()).step_by(32) {
-        let mut pub_key = String::new();
-        for item in public_key_bytes.iter().skip(i).take(32) {
-            pub_key.push_str(&format!("{:02x}", item));
-        }
-        public_key.push_str(&pub_key);
-    }
-    // Finally, return the public key
-    public_key
+///
+/// Returns the public key if succesfull
+/// Returns an error message if the private key is not 32 bytes of hex
+pub fn derive_pub_key(private_key: String) -> Result<String, String> {
+    let private_key = Zeroizing::new(private_key);
+    let private_key = PrivateKey::from_bytes_mod_order(decode_hex_32(&private_key)?);
+    Ok(private_key.public_key().to_hex())
 }
 
 /// Derives main public address from given public spend key, public view key and network
@@ -524,6 +529,7 @@ pub fn derive_pub_key(private_key: String) -> String {
 /// Networks:
 /// - `0` : Monero Mainnet
 /// - `1` : Monero Testnet
+/// - `2` : Monero Stagenet
 ///
 /// Example:
 /// ```
@@ -531,20 +537,67 @@ pub fn derive_pub_key(private_key: String) -> String {
 ///
 /// let public_spend_key: String = "e78d891dd2be407f24e6470caad956e1b746ae0b41cd8252f96684090bc05d95".to_string();
 /// let public_view_key: String = "157d278aa3aee4e11c5a8243a43a78527a2691009562b8c18654975f1347cb47".to_string();
-/// let public_address: String = derive_address(public_spend_key, public_view_key, 0);
+/// let public_address: String = derive_address(public_spend_key, public_view_key, 0).unwrap();
 /// assert_eq!(public_address, "4AQ3jTJg91yNGTXjo9iWr1ekjBGJ5mM6HEsxKqoKddHnRwJTVJYnyLXeerff6iTys5Eo8dyG87tfqZNS5CcSd7U694YiR8J".to_string());
 /// ```
-pub fn derive_address(public_spend_key: String, public_view_key: String, network: u8) -> String {
-    let network_byte = match network {
-        0 => vec![0x12], // Monero mainnet
-        1 => vec![0x35], // Monero testnet
-        _ => panic!("Invalid network"),
+///
+/// Returns the address if succesfull
+/// Returns an error message if a public key is not 32 bytes of hex or the network is unknown
+pub fn derive_address(public_spend_key: String, public_view_key: String, network: u8) -> Result<String, String> {
+    let network = match network {
+        0 => Network::Mainnet,
+        1 => Network::Testnet,
+        2 => Network::Stagenet,
+        _ => return Err(format!("Invalid network {}", network)),
     };
-    let pub_sk_bytes = hex::decode(public_spend_key.clone()).unwrap();
-    let pub_vk_bytes = hex::decode(public_view_key.clone()).unwrap();
-    let mut data = [&network_byte[..], &pub_sk_bytes[..], &pub_vk_bytes[..]].concat();
-    let hash = Keccak256::digest(&data);
-    data.append(&mut hash[..4].to_vec());
+    // The keys are encoded as given, without checking they are curve points
+    Ok(encode_address_bytes(network, &decode_hex_32(&public_spend_key)?, &decode_hex_32(&public_view_key)?))
+}
 
-    base58_monero::encode(&data).unwrap()
+/// Domain separator for application secrets derived with [`derive_app_secret`](fn.derive_app_secret.html)
+const APP_SECRET_DOMAIN: &[u8] = b"libmonero_app_secret";
+
+/// Derives an application-specific secret from the private spend key of a wallet
+///
+/// The secret is `Hs("libmonero_app_secret" || private_spend_key || len(purpose) || purpose)` where `Hs` is Keccak256
+/// reduced modulo the curve order and `len` is the length of the purpose in bytes as 8-byte little-endian integer.
+/// The same wallet and purpose always give the same secret, different purposes give unrelated secrets and
+/// the secret can't be used to recover the wallet keys. Use a purpose string unique to your application, e.g. `"myapp/lws-auth-token"`.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_app_secret, PrivateKey};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let db_key = derive_app_secret(&private_spend_key, "myapp/database-key");
+/// assert_eq!(db_key, derive_app_secret(&private_spend_key, "myapp/database-key"));
+/// assert_ne!(db_key, derive_app_secret(&private_spend_key, "myapp/lws-auth-token"));
+/// ```
+pub fn derive_app_secret(private_spend_key: &PrivateKey, purpose: &str) -> PrivateKey {
+    let mut hasher = Keccak256::new();
+    hasher.update(APP_SECRET_DOMAIN);
+    hasher.update(private_spend_key.as_bytes());
+    hasher.update((purpose.len() as u64).to_le_bytes());
+    hasher.update(purpose.as_bytes());
+    let mut secret = Zeroizing::new([0u8; 32]);
+    secret.copy_from_slice(&hasher.finalize());
+    PrivateKey::from_bytes_mod_order(*secret)
+}
+
+/// Generates the key image `x * Hp(P)` of the given key pair, where `Hp` is Monero's hash-to-point function
+///
+/// The private key must belong to the public key. To get the key image of an owned output, pass the one-time
+/// private key and the one-time public key (output key) of that output.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{generate_key_image, PrivateKey};
+///
+/// let private_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let key_image = generate_key_image(&private_key, &private_key.public_key());
+/// assert_eq!(key_image, generate_key_image(&private_key, &private_key.public_key()));
+/// ```
+pub fn generate_key_image(private_key: &PrivateKey, public_key: &PublicKey) -> KeyImage {
+    let hashed_point = hash_to_point(public_key.as_bytes());
+    KeyImage::from_bytes((hashed_point * private_key.to_scalar()).compress().to_bytes())
 }
@@ -0,0 +1,149 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::keys::{derive_hex_seed, encode_original_words, get_checksum_index, utf8_prefix};
+use crate::mnemonics::original::wordsets::{original_wordsets, WordsetOriginal};
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// SeedType lists the mnemonic seed types libmonero can restore from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SeedType {
+    /// Original 25-word seed (24 words plus a checksum word)
+    Original,
+    /// MyMonero 13-word seed (12 words plus a checksum word)
+    MyMonero,
+}
+
+/// MnemonicError describes why a mnemonic seed is invalid, word indexes start at 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// Seed does not have 13 or 25 words
+    InvalidWordCount(usize),
+    /// Word is not part of the wordset used by the other words of the seed
+    UnknownWord { index: usize, word: String },
+    /// The group of three words starting at `index` does not encode a valid 32-bit value
+    InvalidWordGroup { index: usize },
+    /// Last word does not match the checksum of the other words
+    InvalidChecksum { index: usize, expected: String },
+    /// Language code does not match any compiled-in or loaded wordset
+    UnsupportedLanguage(String),
+}
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MnemonicError::InvalidWordCount(count) => write!(f, "Seed has {} words, expected 13 or 25", count),
+            MnemonicError::UnknownWord { index, word } => write!(f, "Word {} ({}) is not in the wordset", index + 1, word),
+            MnemonicError::InvalidWordGroup { index } => write!(f, "Words {} to {} do not form a valid group", index + 1, index + 3),
+            MnemonicError::InvalidChecksum { index, expected } => write!(f, "Checksum word {} is invalid, expected {}", index + 1, expected),
+            MnemonicError::UnsupportedLanguage(language) => write!(f, "Language {} is not supported", language),
+        }
+    }
+}
+
+impl std::error::Error for MnemonicError {}
+
+/// Validates a mnemonic seed: word count, wordset membership and checksum word
+///
+/// Returns the seed type if the seed is valid, or an error pointing at the offending word otherwise,
+/// so wallet UIs can validate a seed before attempting a restore
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{validate_mnemonic, MnemonicError, SeedType};
+///
+/// let mut mnemonic: Vec<String> = "five saved himself oust taunts pebbles fibula organs koala copy dying vein damp dauntless code gags copy roster geek toolbox joyous apart unlikely warped taunts".split(' ').map(|s| s.to_string()).collect();
+/// assert_eq!(validate_mnemonic(&mnemonic), Ok(SeedType::Original));
+/// mnemonic[24] = "five".to_string();
+/// assert_eq!(validate_mnemonic(&mnemonic), Err(MnemonicError::InvalidChecksum { index: 24, expected: "taunts".to_string() }));
+/// mnemonic[3] = "monero".to_string();
+/// assert_eq!(validate_mnemonic(&mnemonic), Err(MnemonicError::UnknownWord { index: 3, word: "monero".to_string() }));
+/// ```
+pub fn validate_mnemonic(words: &[String]) -> Result<SeedType, MnemonicError> {
+    let seed_type = match words.len() {
+        13 => SeedType::MyMonero,
+        25 => SeedType::Original,
+        count => return Err(MnemonicError::InvalidWordCount(count)),
+    };
+
+    // Pick the wordset containing all words, or the one matching the longest run of words for the error
+    let mut best: Option<(&WordsetOriginal, usize)> = None;
+    for wordset in original_wordsets() {
+        let matched = words
+            .iter()
+            .position(|word| !wordset.words.contains(&word.as_str()))
+            .unwrap_or(words.len());
+        if best.is_none_or(|(_, best_matched)| matched > best_matched) {
+            best = Some((wordset, matched));
+        }
+    }
+    let (wordset, matched) = best.expect("At least one wordset is available");
+    if matched < words.len() {
+        return Err(MnemonicError::UnknownWord {
+            index: matched,
+            word: words[matched].clone(),
+        });
+    }
+
+    // Every group of three words must encode a 32-bit value
+    let wordset_len = wordset.words.len() as u64;
+    let positions: Vec<u64> = words
+        .iter()
+        .map(|word| wordset.words.iter().position(|x| x == word).unwrap() as u64)
+        .collect();
+    for index in (0..words.len() - 1).step_by(3) {
+        let (w1, w2, w3) = (positions[index], positions[index + 1], positions[index + 2]);
+        let value = w1
+            + wordset_len * (((wordset_len - w1) + w2) % wordset_len)
+            + wordset_len * wordset_len * (((wordset_len - w2) + w3) % wordset_len);
+        if value > u32::MAX as u64 || value % wordset_len != w1 {
+            return Err(MnemonicError::InvalidWordGroup { index });
+        }
+    }
+
+    // Checksum word must match, only its prefix is significant
+    let checksum_position = words.len() - 1;
+    let expected = &words[get_checksum_index(&words[..checksum_position], wordset.prefix_len)];
+    if utf8_prefix(&words[checksum_position], wordset.prefix_len) != utf8_prefix(expected, wordset.prefix_len) {
+        return Err(MnemonicError::InvalidChecksum {
+            index: checksum_position,
+            expected: expected.clone(),
+        });
+    }
+    Ok(seed_type)
+}
+
+/// Converts a valid mnemonic seed to the wordset of another language, keeping its seed type
+///
+/// The seed is decoded and its bytes re-encoded with the target wordset and a new checksum word, so both
+/// mnemonics restore the same wallet. Language codes are the ones of [`generate_seed`](fn.generate_seed.html).
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{convert_mnemonic, derive_hex_seed, validate_mnemonic, SeedType};
+///
+/// let mnemonic: Vec<String> = "five saved himself oust taunts pebbles fibula organs koala copy dying vein damp dauntless code gags copy roster geek toolbox joyous apart unlikely warped taunts".split(' ').map(|s| s.to_string()).collect();
+/// let french = convert_mnemonic(&mnemonic, "fr").unwrap();
+/// assert_eq!(validate_mnemonic(&french), Ok(SeedType::Original));
+/// assert_eq!(derive_hex_seed(french.to_vec()), derive_hex_seed(mnemonic.clone()));
+/// assert_eq!(*convert_mnemonic(&french, "en").unwrap(), mnemonic);
+/// assert!(convert_mnemonic(&mnemonic, "tlh").is_err());
+/// ```
+pub fn convert_mnemonic(words: &[String], target_language: &str) -> Result<Zeroizing<Vec<String>>, MnemonicError> {
+    let wordset = original_wordsets()
+        .into_iter()
+        .find(|wordset| wordset.name == target_language)
+        .ok_or_else(|| MnemonicError::UnsupportedLanguage(target_language.to_string()))?;
+    validate_mnemonic(words)?;
+    let hex_seed = derive_hex_seed(words.to_vec());
+    let seed_bytes = Zeroizing::new(hex::decode(&*hex_seed).unwrap());
+    Ok(encode_original_words(&seed_bytes, wordset))
+}
@@ -8,6 +8,26 @@
  *
  */
 
+#[cfg(feature = "bench")]
+pub(crate) mod bench;
+pub(crate) mod commitment;
 pub(crate) mod keys;
+pub(crate) mod mnemonic;
+pub(crate) mod outputs;
+pub(crate) mod proofs;
+pub(crate) mod scan;
+pub(crate) mod subaddress;
+pub(crate) mod types;
+pub(crate) mod wordlist;
 
-pub use keys::*;
\ No newline at end of file
+#[cfg(feature = "bench")]
+pub use bench::*;
+pub use commitment::*;
+pub use keys::*;
+pub use mnemonic::*;
+pub use outputs::*;
+pub use proofs::*;
+pub use scan::*;
+pub use subaddress::*;
+pub use types::*;
+pub use wordlist::*;
\ No newline at end of file
@@ -0,0 +1,160 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::types::{KeyDerivation, OneTimeKey, PrivateKey, PublicKey, ViewPair};
+use crate::utils::{decode_address, varint::write_varint, AddressInfo, AddressType};
+use crate::crypt::keccak::hash_to_scalar;
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, Scalar};
+use sha3::{Digest, Keccak256};
+use zeroize::Zeroizing;
+
+/// Generates the key derivation `8 * private_key * public_key`
+///
+/// Sender passes the recipient's public view key and the transaction private key,
+/// receiver passes the transaction public key and its private view key. Both get the same derivation.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{generate_key_derivation, PrivateKey};
+///
+/// let tx_private_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let private_view_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let sender = generate_key_derivation(&private_view_key.public_key(), &tx_private_key);
+/// let receiver = generate_key_derivation(&tx_private_key.public_key(), &private_view_key);
+/// assert_eq!(sender, receiver);
+/// ```
+pub fn generate_key_derivation(public_key: &PublicKey, private_key: &PrivateKey) -> KeyDerivation {
+    let point = (public_key.to_point() * private_key.to_scalar()).mul_by_cofactor();
+    KeyDerivation::from_bytes(point.compress().to_bytes())
+}
+
+/// Returns `Hs(derivation || varint(output_index))`, the scalar that turns a public spend key into a one-time output key
+pub fn derivation_to_scalar(derivation: &KeyDerivation, output_index: u64) -> Scalar {
+    let mut data = Zeroizing::new(derivation.as_bytes().to_vec());
+    write_varint(output_index, &mut data);
+    hash_to_scalar(&data)
+}
+
+/// Derives the one-time public key (output key) `Hs(derivation || index) * G + B` for the given public spend key
+pub fn derive_output_pubkey(derivation: &KeyDerivation, output_index: u64, public_spend_key: &PublicKey) -> PublicKey {
+    let scalar = derivation_to_scalar(derivation, output_index);
+    PublicKey::from_point(ED25519_BASEPOINT_TABLE * &scalar + public_spend_key.to_point())
+}
+
+/// Derives the one-time private key `Hs(derivation || index) + b` of an owned output, needed to spend it or generate its key image
+pub fn derive_output_privkey(derivation: &KeyDerivation, output_index: u64, private_spend_key: &PrivateKey) -> PrivateKey {
+    let scalar = derivation_to_scalar(derivation, output_index) + private_spend_key.to_scalar();
+    PrivateKey::from_bytes_mod_order(scalar.to_bytes())
+}
+
+/// Derives the view tag of the output at given index, the first byte of `Keccak256("view_tag" || derivation || varint(index))`
+///
+/// View tags let the receiver skip most of the outputs it does not own without deriving their one-time keys
+pub fn derive_view_tag(derivation: &KeyDerivation, output_index: u64) -> u8 {
+    let mut data = Zeroizing::new(b"view_tag".to_vec());
+    data.extend_from_slice(derivation.as_bytes());
+    write_varint(output_index, &mut data);
+    Keccak256::digest(&*data)[0]
+}
+
+/// Checks if the output with given one-time public key at given index of a transaction belongs to the given view pair
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_output_pubkey, generate_key_derivation, is_output_owned, PrivateKey, ViewPair};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+/// // Sender side
+/// let tx_private_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let derivation = generate_key_derivation(&view_pair.view.public_key(), &tx_private_key);
+/// let output_key = derive_output_pubkey(&derivation, 0, &view_pair.spend).into();
+/// // Receiver side
+/// assert!(is_output_owned(&view_pair, &tx_private_key.public_key(), 0, &output_key));
+/// assert!(!is_output_owned(&view_pair, &tx_private_key.public_key(), 1, &output_key));
+/// ```
+pub fn is_output_owned(view_pair: &ViewPair, tx_public_key: &PublicKey, output_index: u64, output_key: &OneTimeKey) -> bool {
+    let derivation = generate_key_derivation(tx_public_key, &view_pair.view);
+    OneTimeKey::from(derive_output_pubkey(&derivation, output_index, &view_pair.spend)) == *output_key
+}
+
+/// OutputKeys contains what a sender needs to write an output paying a recipient
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputKeys {
+    /// One-time public key of the output
+    pub output_key: OneTimeKey,
+    /// View tag of the output
+    pub view_tag: u8,
+    /// Transaction public key matching the secret key used, `r * G` for standard addresses and `r * D` for subaddresses,
+    /// goes either in the main transaction public key field or in the additional public keys of tx_extra
+    pub tx_public_key: PublicKey,
+}
+
+/// Derives the one-time output key, view tag and matching transaction public key of an output paying the given address
+///
+/// `tx_secret_key` is the transaction secret key, or the additional secret key of this output when the transaction
+/// uses additional public keys (see [`needs_additional_tx_keys`](fn.needs_additional_tx_keys.html)). Change outputs
+/// to the sender's own wallet are derived with the sender's private view key instead, see
+/// [`generate_key_derivation`](fn.generate_key_derivation.html).
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_output_key, is_output_owned, PrivateKey, ViewPair};
+/// use libmonero::utils::Network;
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+/// let tx_secret_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let output = derive_output_key(&view_pair.address(Network::Mainnet), &tx_secret_key, 1).unwrap();
+/// assert_eq!(output.tx_public_key, tx_secret_key.public_key());
+/// assert!(is_output_owned(&view_pair, &output.tx_public_key, 1, &output.output_key));
+/// ```
+pub fn derive_output_key(recipient_address: &str, tx_secret_key: &PrivateKey, output_index: u64) -> Result<OutputKeys, String> {
+    let recipient = decode_address(recipient_address)?;
+    Ok(derive_output_key_for(&recipient, tx_secret_key, output_index))
+}
+
+/// Same as [`derive_output_key`](fn.derive_output_key.html) for an already decoded address
+pub fn derive_output_key_for(recipient: &AddressInfo, tx_secret_key: &PrivateKey, output_index: u64) -> OutputKeys {
+    let derivation = generate_key_derivation(&recipient.public_view_key, tx_secret_key);
+    let tx_public_key = match recipient.address_type {
+        AddressType::Subaddress => PublicKey::from_point(recipient.public_spend_key.to_point() * tx_secret_key.to_scalar()),
+        _ => tx_secret_key.public_key(),
+    };
+    OutputKeys {
+        output_key: derive_output_pubkey(&derivation, output_index, &recipient.public_spend_key).into(),
+        view_tag: derive_view_tag(&derivation, output_index),
+        tx_public_key,
+    }
+}
+
+/// Checks if a transaction paying the given destinations (change excluded) needs one additional public key per output
+///
+/// Additional keys are needed as soon as a subaddress is paid together with any other address, a transaction paying
+/// a single subaddress uses `r * D` as its main transaction public key instead
+///
+/// Example:
+/// ```
+/// use libmonero::keys::needs_additional_tx_keys;
+/// use libmonero::utils::decode_address;
+///
+/// let standard = decode_address("41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT").unwrap();
+/// assert!(!needs_additional_tx_keys(&[standard.clone(), standard]));
+/// ```
+pub fn needs_additional_tx_keys(destinations: &[AddressInfo]) -> bool {
+    let mut unique: Vec<&AddressInfo> = Vec::new();
+    for destination in destinations {
+        if !unique.iter().any(|known| known.public_spend_key == destination.public_spend_key && known.public_view_key == destination.public_view_key) {
+            unique.push(destination);
+        }
+    }
+    let subaddresses = unique.iter().filter(|destination| destination.address_type == AddressType::Subaddress).count();
+    subaddresses > 0 && (unique.len() > subaddresses || subaddresses > 1)
+}
\ No newline at end of file
@@ -0,0 +1,552 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+#![allow(non_snake_case)]
+
+use super::commitment::{commit, ecdh_decode};
+use super::keys::generate_key_image;
+use super::outputs::{derivation_to_scalar, derive_output_pubkey};
+use super::types::{KeyDerivation, KeyImage, OneTimeKey, PrivateKey, PublicKey};
+use crate::blocks::{extra::extract_tx_pub_keys, RctType, Transaction, TxHash, TxSignatures};
+use crate::crypt::hash_to_point::hash_to_point;
+use crate::crypt::keccak::{hash_to_scalar, keccak256};
+use crate::utils::{decode_address, AddressType};
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY, EdwardsPoint, Scalar};
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+const OUTPUT_PROOF_HEADER: &str = "OutputProofV1";
+const OUTPUT_PROOF_DOMAIN: &[u8] = b"libmonero_output_proof";
+const OUT_PROOF_HEADER: &str = "OutProofV2";
+const IN_PROOF_HEADER: &str = "InProofV2";
+const TX_PROOF_DOMAIN: &[u8] = b"TXPROOF_V2";
+// Base58 lengths of the shared secret and signature of every transaction public key in a transaction proof
+const TX_PROOF_SECRET_LEN: usize = 44;
+const TX_PROOF_SIGNATURE_LEN: usize = 88;
+
+/// OutputOwnershipProof proves owning a single on-chain output without revealing any wallet key
+///
+/// The proof shows knowledge of the one-time private key `x` of the output key `P = x * G` and that the included
+/// key image is `x * Hp(P)`, with the same `x`. A third party can thus check both who owns a particular output and,
+/// by looking for the key image on chain, whether it is still unspent. Nothing about the other outputs of the wallet
+/// is revealed.
+///
+/// Proofs are signatures over a message chosen by the verifier, usually a fresh challenge, so they cannot be replayed.
+/// They are shared as text starting with `OutputProofV1`.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{prove_output_ownership, OutputOwnershipProof, PrivateKey};
+///
+/// let output_secret = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let proof = prove_output_ownership(&output_secret, b"audit 2024-05");
+/// assert_eq!(proof.output_key, output_secret.public_key().into());
+/// assert!(proof.verify(b"audit 2024-05"));
+/// assert!(!proof.verify(b"audit 2024-06"));
+///
+/// let shared = proof.to_string();
+/// assert!(shared.starts_with("OutputProofV1"));
+/// assert_eq!(shared.parse::<OutputOwnershipProof>().unwrap(), proof);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputOwnershipProof {
+    /// One-time public key of the proven output, as found on chain
+    pub output_key: OneTimeKey,
+    /// Key image of the proven output, spent once it appears on chain
+    pub key_image: KeyImage,
+    challenge: [u8; 32],
+    response: [u8; 32],
+}
+
+/// Proves owning the output with the given one-time private key, see [`OwnedOutput::secret_key`](struct.OwnedOutput.html#method.secret_key),
+/// by signing the given message
+pub fn prove_output_ownership(output_secret: &PrivateKey, message: &[u8]) -> OutputOwnershipProof {
+    let x = Zeroizing::new(output_secret.to_scalar());
+    let public_key = output_secret.public_key();
+    let output_key = OneTimeKey::from(public_key);
+    let key_image = generate_key_image(output_secret, &public_key);
+    let hashed_key = hash_to_point(output_key.as_bytes());
+
+    let mut nonce_bytes = Zeroizing::new([0u8; 64]);
+    rand::thread_rng().fill_bytes(&mut *nonce_bytes);
+    let k = Zeroizing::new(Scalar::from_bytes_mod_order_wide(&nonce_bytes));
+    let L = ED25519_BASEPOINT_TABLE * &*k;
+    let R = hashed_key * *k;
+    let c = proof_challenge(message, &output_key, &key_image, &L, &R);
+    let r = *k - c * *x;
+    OutputOwnershipProof {
+        output_key,
+        key_image,
+        challenge: c.to_bytes(),
+        response: r.to_bytes(),
+    }
+}
+
+impl OutputOwnershipProof {
+    /// Checks the proof against the message it should sign
+    ///
+    /// Verifiers must also check that [`output_key`](#structfield.output_key) is the key of the output being audited,
+    /// e.g. with [`get_outs`](../blocks/fn.get_outs.html), and look for [`key_image`](#structfield.key_image) on chain to know if it was spent
+    pub fn verify(&self, message: &[u8]) -> bool {
+        let (Some(P), Some(I)) = (decompress(self.output_key.as_bytes()), decompress(self.key_image.as_bytes())) else {
+            return false;
+        };
+        // Key images outside of the prime order subgroup would let the same output show several key images
+        if !I.is_torsion_free() || I == EdwardsPoint::default() {
+            return false;
+        }
+        let (Some(c), Some(r)) = (Scalar::from_canonical_bytes(self.challenge).into(), Scalar::from_canonical_bytes(self.response).into()) else {
+            return false;
+        };
+        let L = ED25519_BASEPOINT_TABLE * &r + P * c;
+        let R = hash_to_point(self.output_key.as_bytes()) * r + I * c;
+        proof_challenge(message, &self.output_key, &self.key_image, &L, &R) == c
+    }
+}
+
+impl fmt::Display for OutputOwnershipProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data = Vec::with_capacity(128);
+        data.extend_from_slice(self.output_key.as_bytes());
+        data.extend_from_slice(self.key_image.as_bytes());
+        data.extend_from_slice(&self.challenge);
+        data.extend_from_slice(&self.response);
+        write!(f, "{}{}", OUTPUT_PROOF_HEADER, base58_monero::encode(&data).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl FromStr for OutputOwnershipProof {
+    type Err = String;
+
+    /// Parses a proof shared as text, the proof still has to be verified
+    fn from_str(proof: &str) -> Result<OutputOwnershipProof, String> {
+        let encoded = proof.strip_prefix(OUTPUT_PROOF_HEADER).ok_or("Output proof must start with OutputProofV1")?;
+        let data = base58_monero::decode(encoded).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+        if data.len() != 128 {
+            return Err(format!("Output proof must hold 128 bytes, got {}", data.len()));
+        }
+        let part = |i: usize| -> [u8; 32] { data[i * 32..(i + 1) * 32].try_into().unwrap() };
+        Ok(OutputOwnershipProof {
+            output_key: OneTimeKey::from_bytes(part(0)),
+            key_image: KeyImage::from_bytes(part(1)),
+            challenge: part(2),
+            response: part(3),
+        })
+    }
+}
+
+/// Signature is a Schnorr signature `(c, r)` of a 32-byte hash, monero-core's `crypto::signature`
+///
+/// It signs transaction sets, key image exports and the proofs of the reference wallet.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{generate_signature, PrivateKey, Signature};
+///
+/// let private_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let signature = generate_signature(&[7; 32], &private_key);
+/// assert!(signature.verify(&[7; 32], &private_key.public_key()));
+/// assert!(!signature.verify(&[8; 32], &private_key.public_key()));
+/// assert_eq!(Signature::from_bytes(signature.to_bytes()), signature);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub c: [u8; 32],
+    pub r: [u8; 32],
+}
+
+/// Signs a hash with the given private key, monero-core's `crypto::generate_signature`
+pub fn generate_signature(hash: &[u8; 32], private_key: &PrivateKey) -> Signature {
+    let public_key = private_key.public_key();
+    let x = Zeroizing::new(private_key.to_scalar());
+    loop {
+        let mut nonce_bytes = Zeroizing::new([0u8; 64]);
+        rand::thread_rng().fill_bytes(&mut *nonce_bytes);
+        let k = Zeroizing::new(Scalar::from_bytes_mod_order_wide(&nonce_bytes));
+        let commitment = (ED25519_BASEPOINT_TABLE * &*k).compress();
+        let c = hash_to_scalar(&[hash.as_slice(), public_key.as_bytes(), commitment.as_bytes()].concat());
+        let r = *k - c * *x;
+        if c != Scalar::ZERO && r != Scalar::ZERO {
+            return Signature { c: c.to_bytes(), r: r.to_bytes() };
+        }
+    }
+}
+
+impl Signature {
+    /// Checks the signature of a hash against the public key, monero-core's `crypto::check_signature`
+    pub fn verify(&self, hash: &[u8; 32], public_key: &PublicKey) -> bool {
+        let Some(P) = decompress(public_key.as_bytes()) else {
+            return false;
+        };
+        let (Some(c), Some(r)) = (Option::<Scalar>::from(Scalar::from_canonical_bytes(self.c)), Option::<Scalar>::from(Scalar::from_canonical_bytes(self.r))) else {
+            return false;
+        };
+        let commitment = (ED25519_BASEPOINT_TABLE * &r + P * c).compress();
+        c != Scalar::ZERO && hash_to_scalar(&[hash.as_slice(), public_key.as_bytes(), commitment.as_bytes()].concat()) == c
+    }
+
+    /// Returns `c || r`, the layout of monero-core
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.c);
+        bytes[32..].copy_from_slice(&self.r);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; 64]) -> Signature {
+        Signature {
+            c: bytes[..32].try_into().unwrap(),
+            r: bytes[32..].try_into().unwrap(),
+        }
+    }
+}
+
+/// Signs a hash with a ring of public keys and the key image of the member at `real_index`, monero-core's
+/// `crypto::generate_ring_signature`
+///
+/// Ring signatures signed the inputs of transactions before RingCT, the reference wallet still uses one-member rings
+/// to sign the key images of its exports and reserve proofs.
+///
+/// Returns one signature per ring member if succesfull
+/// Returns an error message if `real_index` is out of the ring or the private key does not match its member
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{check_ring_signature, generate_key_image, generate_ring_signature, PrivateKey};
+///
+/// let private_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let other = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let key_image = generate_key_image(&private_key, &private_key.public_key());
+/// let ring = [other.public_key(), private_key.public_key()];
+/// let signatures = generate_ring_signature(&[7; 32], &key_image, &ring, &private_key, 1).unwrap();
+/// assert!(check_ring_signature(&[7; 32], &key_image, &ring, &signatures));
+/// assert!(!check_ring_signature(&[8; 32], &key_image, &ring, &signatures));
+/// ```
+pub fn generate_ring_signature(hash: &[u8; 32], key_image: &KeyImage, ring: &[PublicKey], private_key: &PrivateKey, real_index: usize) -> Result<Vec<Signature>, String> {
+    if ring.get(real_index) != Some(&private_key.public_key()) {
+        return Err("Private key does not match the ring member".to_string());
+    }
+    let I = decompress(key_image.as_bytes()).ok_or("Invalid key image")?;
+    let x = Zeroizing::new(private_key.to_scalar());
+    let mut rng = rand::thread_rng();
+    let mut random_scalar = || {
+        let mut bytes = Zeroizing::new([0u8; 64]);
+        rng.fill_bytes(&mut *bytes);
+        Scalar::from_bytes_mod_order_wide(&bytes)
+    };
+
+    // Members other than the real one get random c and r, the real one answers the challenge left over
+    let mut data = hash.to_vec();
+    let mut signatures = Vec::with_capacity(ring.len());
+    let mut sum = Scalar::ZERO;
+    let k = Zeroizing::new(random_scalar());
+    for (i, member) in ring.iter().enumerate() {
+        let hashed_key = hash_to_point(member.as_bytes());
+        let (L, R) = match i == real_index {
+            true => {
+                signatures.push((Scalar::ZERO, Scalar::ZERO));
+                (ED25519_BASEPOINT_TABLE * &*k, hashed_key * *k)
+            }
+            false => {
+                let P = decompress(member.as_bytes()).ok_or(format!("Ring member {} is not a valid point", i))?;
+                let (c, r) = (random_scalar(), random_scalar());
+                sum += c;
+                signatures.push((c, r));
+                (ED25519_BASEPOINT_TABLE * &r + P * c, hashed_key * r + I * c)
+            }
+        };
+        data.extend_from_slice(L.compress().as_bytes());
+        data.extend_from_slice(R.compress().as_bytes());
+    }
+    let c = hash_to_scalar(&data) - sum;
+    signatures[real_index] = (c, *k - c * *x);
+    Ok(signatures.into_iter().map(|(c, r)| Signature { c: c.to_bytes(), r: r.to_bytes() }).collect())
+}
+
+/// Checks a ring signature made by [`generate_ring_signature`](fn.generate_ring_signature.html), monero-core's
+/// `crypto::check_ring_signature`
+pub fn check_ring_signature(hash: &[u8; 32], key_image: &KeyImage, ring: &[PublicKey], signatures: &[Signature]) -> bool {
+    if ring.is_empty() || ring.len() != signatures.len() {
+        return false;
+    }
+    let Some(I) = decompress(key_image.as_bytes()) else {
+        return false;
+    };
+    if !I.is_torsion_free() {
+        return false;
+    }
+    let mut data = hash.to_vec();
+    let mut sum = Scalar::ZERO;
+    for (member, signature) in ring.iter().zip(signatures.iter()) {
+        let Some(P) = decompress(member.as_bytes()) else {
+            return false;
+        };
+        let (Some(c), Some(r)) = (Option::<Scalar>::from(Scalar::from_canonical_bytes(signature.c)), Option::<Scalar>::from(Scalar::from_canonical_bytes(signature.r))) else {
+            return false;
+        };
+        data.extend_from_slice((ED25519_BASEPOINT_TABLE * &r + P * c).compress().as_bytes());
+        data.extend_from_slice((hash_to_point(member.as_bytes()) * r + I * c).compress().as_bytes());
+        sum += c;
+    }
+    hash_to_scalar(&data) == sum
+}
+
+/// TxProofKeys is the secret proving a transaction with [`generate_tx_proof`](fn.generate_tx_proof.html)
+#[derive(Clone, PartialEq, Eq)]
+pub enum TxProofKeys {
+    /// Transaction secret key and additional secret keys of the sender, see [`SignedTx`](../wallet/struct.SignedTx.html),
+    /// giving an `OutProofV2`
+    Sender { tx_secret_key: PrivateKey, additional_secret_keys: Vec<PrivateKey> },
+    /// Private view key of the recipient, giving an `InProofV2`
+    Recipient { private_view_key: PrivateKey },
+}
+
+/// Proves that a transaction paid an address, monero-wallet-cli's `get_tx_proof`
+///
+/// The sender, knowing the secret keys of the transaction, or the recipient, knowing the private view key, proves the
+/// secret shared by every transaction public key and the address. Anyone holding the transaction can then compute the
+/// amount received by the address with [`check_tx_proof`](fn.check_tx_proof.html) without learning any key. The
+/// message, usually an order reference, binds the proof to its purpose.
+///
+/// Returns the proof, starting with `OutProofV2` or `InProofV2`, if succesfull
+/// Returns an error message if the address is invalid or the keys match neither the transaction nor the address
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::parse_transaction_blob;
+/// use libmonero::keys::{check_tx_proof, derive_output_key, generate_tx_proof, PrivateKey, TxProofKeys, ViewPair};
+/// use libmonero::utils::Network;
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+/// let address = view_pair.address(Network::Mainnet);
+///
+/// // Miner transaction paying 10000000 piconeros to the address
+/// let tx_secret_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let output = derive_output_key(&address, &tx_secret_key, 0).unwrap();
+/// let blob = format!("029c0801ffe8070180ade20403{}{:02x}2101{}00", output.output_key, output.view_tag, output.tx_public_key.to_hex());
+/// let tx = parse_transaction_blob(&hex::decode(blob).unwrap()).unwrap();
+/// let tx_hash = tx.hash().unwrap();
+///
+/// let keys = TxProofKeys::Sender { tx_secret_key, additional_secret_keys: Vec::new() };
+/// let proof = generate_tx_proof(&tx_hash, &tx, &address, b"order 1234", &keys).unwrap();
+/// assert!(proof.starts_with("OutProofV2"));
+/// assert_eq!(check_tx_proof(&tx_hash, &tx, &address, b"order 1234", &proof), Ok(10000000));
+/// assert!(check_tx_proof(&tx_hash, &tx, &address, b"order 1235", &proof).is_err());
+///
+/// let keys = TxProofKeys::Recipient { private_view_key: view_pair.view.clone() };
+/// let proof = generate_tx_proof(&tx_hash, &tx, &address, b"order 1234", &keys).unwrap();
+/// assert!(proof.starts_with("InProofV2"));
+/// assert_eq!(check_tx_proof(&tx_hash, &tx, &address, b"order 1234", &proof), Ok(10000000));
+/// ```
+pub fn generate_tx_proof(tx_hash: &TxHash, tx: &Transaction, address: &str, message: &[u8], keys: &TxProofKeys) -> Result<String, String> {
+    let recipient = decode_address(address)?;
+    let B = (recipient.address_type == AddressType::Subaddress).then(|| recipient.public_spend_key.to_point());
+    let view = recipient.public_view_key.to_point();
+    let (main_keys, additional_keys) = extract_tx_pub_keys(&tx.prefix.extra);
+    let tx_public_key = main_keys.first().ok_or("Transaction has no public key")?;
+    let tx_keys: Vec<&[u8; 32]> = std::iter::once(tx_public_key).chain(additional_keys.iter()).collect();
+    let base_times = |secret: &Scalar| match B {
+        Some(B) => B * secret,
+        None => ED25519_BASEPOINT_TABLE * secret,
+    };
+
+    // Statements (R, A, secret) proving D = secret * A, with R = secret * G, or secret * B for subaddresses
+    let (header, statements) = match keys {
+        TxProofKeys::Sender { tx_secret_key, additional_secret_keys } => {
+            if additional_secret_keys.len() != additional_keys.len() {
+                return Err(format!("Transaction has {} additional public keys, got {} additional secret keys", additional_keys.len(), additional_secret_keys.len()));
+            }
+            let mut statements = Vec::with_capacity(tx_keys.len());
+            let mut matching = false;
+            for (secret, tx_key) in std::iter::once(tx_secret_key).chain(additional_secret_keys.iter()).zip(tx_keys.iter()) {
+                let r = Zeroizing::new(secret.to_scalar());
+                let R = base_times(&r);
+                // Keys of the outputs paying other addresses don't match R, their signatures are ignored by verifiers
+                matching |= R.compress().as_bytes() == *tx_key;
+                statements.push((R, view, r));
+            }
+            if !matching {
+                return Err("Secret keys do not match the transaction public keys for this address".to_string());
+            }
+            (OUT_PROOF_HEADER, statements)
+        }
+        TxProofKeys::Recipient { private_view_key } => {
+            let a = Zeroizing::new(private_view_key.to_scalar());
+            if base_times(&a) != view {
+                return Err("Private view key does not match the address".to_string());
+            }
+            let mut statements = Vec::with_capacity(tx_keys.len());
+            for (i, tx_key) in tx_keys.iter().enumerate() {
+                let A = decompress(tx_key).ok_or(format!("Transaction public key {} is not a valid point", i))?;
+                statements.push((view, A, a.clone()));
+            }
+            (IN_PROOF_HEADER, statements)
+        }
+    };
+
+    let prefix_hash = tx_proof_hash(tx_hash, message);
+    let mut proof = header.to_string();
+    for (R, A, secret) in statements {
+        let (D, signature) = sign_tx_proof(&prefix_hash, &R, &A, B.as_ref(), &secret);
+        proof += &base58_monero::encode(&D).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+        proof += &base58_monero::encode(&signature.to_bytes()).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+    }
+    Ok(proof)
+}
+
+/// Checks a proof made by [`generate_tx_proof`](fn.generate_tx_proof.html) or monero-wallet-cli's `get_tx_proof`, for
+/// the transaction with the given hash, the address and the message, monero-wallet-cli's `check_tx_proof`
+///
+/// Only outputs whose decoded amount matches their commitment are counted. As the reference wallet does, shared secrets
+/// whose signature fails are ignored as long as one of the signatures holds.
+///
+/// Returns the amount in piconeros received by the address, 0 if it received nothing, if succesfull
+/// Returns an error message if the proof is malformed or does not prove the transaction for this address and message
+pub fn check_tx_proof(tx_hash: &TxHash, tx: &Transaction, address: &str, message: &[u8], proof: &str) -> Result<u64, String> {
+    let recipient = decode_address(address)?;
+    let B = (recipient.address_type == AddressType::Subaddress).then_some(recipient.public_spend_key.as_bytes());
+    let view = recipient.public_view_key.as_bytes();
+    let (outbound, encoded) = match (proof.strip_prefix(OUT_PROOF_HEADER), proof.strip_prefix(IN_PROOF_HEADER)) {
+        (Some(encoded), _) => (true, encoded),
+        (_, Some(encoded)) => (false, encoded),
+        _ if proof.starts_with("OutProofV1") || proof.starts_with("InProofV1") => return Err("Version 1 transaction proofs are not supported".to_string()),
+        _ => return Err("Transaction proof must start with OutProofV2 or InProofV2".to_string()),
+    };
+    let entry_len = TX_PROOF_SECRET_LEN + TX_PROOF_SIGNATURE_LEN;
+    if encoded.is_empty() || !encoded.is_ascii() || encoded.len() % entry_len != 0 {
+        return Err("Transaction proof has an invalid length".to_string());
+    }
+    let (main_keys, additional_keys) = extract_tx_pub_keys(&tx.prefix.extra);
+    let tx_public_key = main_keys.first().ok_or("Transaction has no public key")?;
+    let tx_keys: Vec<&[u8; 32]> = std::iter::once(tx_public_key).chain(additional_keys.iter()).collect();
+    if encoded.len() / entry_len != tx_keys.len() {
+        return Err(format!("Transaction proof holds {} signatures, the transaction has {} public keys", encoded.len() / entry_len, tx_keys.len()));
+    }
+
+    let prefix_hash = tx_proof_hash(tx_hash, message);
+    let mut derivations = Vec::with_capacity(tx_keys.len());
+    for (tx_key, entry) in tx_keys.iter().zip(encoded.as_bytes().chunks(entry_len)) {
+        let (secret, signature) = entry.split_at(TX_PROOF_SECRET_LEN);
+        let decode = |part: &[u8]| base58_monero::decode(std::str::from_utf8(part).unwrap()).map_err(|e| format!("Invalid base58 encoding: {}", e));
+        let D: [u8; 32] = decode(secret)?.try_into().map_err(|_| "Shared secret must hold 32 bytes")?;
+        let signature = Signature::from_bytes(decode(signature)?.try_into().map_err(|_| "Signature must hold 64 bytes")?);
+        let (R, A) = if outbound { (*tx_key, view) } else { (view, *tx_key) };
+        // The derivation is 8 * D, as computed by the recipient from the transaction public key
+        let derivation = match check_tx_proof_signature(&prefix_hash, R, A, B, &D, &signature) {
+            true => decompress(&D).map(|D| KeyDerivation::from_bytes(D.mul_by_cofactor().compress().to_bytes())),
+            false => None,
+        };
+        derivations.push(derivation);
+    }
+    if derivations.iter().all(Option::is_none) {
+        return Err("Transaction proof does not match the transaction, address and message".to_string());
+    }
+    Ok(received_amount(tx, &recipient.public_spend_key, &derivations))
+}
+
+// Hs(domain || Keccak(message) || P || I || L || R)
+fn proof_challenge(message: &[u8], output_key: &OneTimeKey, key_image: &KeyImage, L: &EdwardsPoint, R: &EdwardsPoint) -> Scalar {
+    let mut hasher = Keccak256::new();
+    hasher.update(OUTPUT_PROOF_DOMAIN);
+    hasher.update(Keccak256::digest(message));
+    hasher.update(output_key.as_bytes());
+    hasher.update(key_image.as_bytes());
+    hasher.update(L.compress().as_bytes());
+    hasher.update(R.compress().as_bytes());
+    Scalar::from_bytes_mod_order(hasher.finalize().into())
+}
+
+fn decompress(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    CompressedEdwardsY(*bytes).decompress()
+}
+
+// Keccak(tx_hash || message), the hash signed by transaction proofs
+fn tx_proof_hash(tx_hash: &TxHash, message: &[u8]) -> [u8; 32] {
+    keccak256(&[tx_hash.as_bytes().as_slice(), message].concat())
+}
+
+// Hs(prefix_hash || D || X || Y || Keccak("TXPROOF_V2") || R || A || B), B being zero for standard addresses,
+// monero-core's `crypto::generate_tx_proof` version 2
+fn tx_proof_challenge(prefix_hash: &[u8; 32], R: &[u8; 32], A: &[u8; 32], B: Option<&[u8; 32]>, D: &[u8; 32], X: &EdwardsPoint, Y: &EdwardsPoint) -> Scalar {
+    let separator = keccak256(TX_PROOF_DOMAIN);
+    hash_to_scalar(&[prefix_hash.as_slice(), D, X.compress().as_bytes(), Y.compress().as_bytes(), &separator, R, A, B.unwrap_or(&[0; 32])].concat())
+}
+
+// Signs the proof of D = secret * A for R = secret * G, or secret * B for subaddresses, returning D and the signature,
+// monero-core's `crypto::generate_tx_proof`
+pub(crate) fn sign_tx_proof(prefix_hash: &[u8; 32], R: &EdwardsPoint, A: &EdwardsPoint, B: Option<&EdwardsPoint>, secret: &Scalar) -> ([u8; 32], Signature) {
+    let D = (A * secret).compress().to_bytes();
+    let mut nonce_bytes = Zeroizing::new([0u8; 64]);
+    rand::thread_rng().fill_bytes(&mut *nonce_bytes);
+    let k = Zeroizing::new(Scalar::from_bytes_mod_order_wide(&nonce_bytes));
+    let X = match B {
+        Some(B) => B * *k,
+        None => ED25519_BASEPOINT_TABLE * &*k,
+    };
+    let Y = A * *k;
+    let B = B.map(|B| B.compress().to_bytes());
+    let c = tx_proof_challenge(prefix_hash, &R.compress().to_bytes(), &A.compress().to_bytes(), B.as_ref(), &D, &X, &Y);
+    (D, Signature { c: c.to_bytes(), r: (*k - c * secret).to_bytes() })
+}
+
+// Checks the signature proving D = secret * A for R = secret * G, or secret * B for subaddresses, monero-core's
+// `crypto::check_tx_proof`
+pub(crate) fn check_tx_proof_signature(prefix_hash: &[u8; 32], R: &[u8; 32], A: &[u8; 32], B: Option<&[u8; 32]>, D: &[u8; 32], signature: &Signature) -> bool {
+    let (Some(R_point), Some(A_point), Some(D_point)) = (decompress(R), decompress(A), decompress(D)) else {
+        return false;
+    };
+    let (Some(c), Some(r)) = (Option::<Scalar>::from(Scalar::from_canonical_bytes(signature.c)), Option::<Scalar>::from(Scalar::from_canonical_bytes(signature.r))) else {
+        return false;
+    };
+    let X = match B.map(decompress) {
+        Some(Some(B_point)) => R_point * c + B_point * r,
+        Some(None) => return false,
+        None => R_point * c + ED25519_BASEPOINT_TABLE * &r,
+    };
+    let Y = D_point * c + A_point * r;
+    tx_proof_challenge(prefix_hash, R, A, B, D, &X, &Y) == c
+}
+
+// Sums the amounts of the outputs sent to the public spend key, found with the main derivation or the additional
+// derivation of their index
+fn received_amount(tx: &Transaction, public_spend_key: &PublicKey, derivations: &[Option<KeyDerivation>]) -> u64 {
+    let mut received = 0u64;
+    for (index, output) in tx.prefix.outputs.iter().enumerate() {
+        let candidates = derivations[0].iter().chain(derivations.get(index + 1).and_then(Option::as_ref));
+        for derivation in candidates {
+            if OneTimeKey::from(derive_output_pubkey(derivation, index as u64, public_spend_key)) != output.key() {
+                continue;
+            }
+            if let Some(amount) = output_amount(tx, index, derivation) {
+                received = received.saturating_add(amount);
+            }
+            break;
+        }
+    }
+    received
+}
+
+// Amount of the output at the given index, decoded with its derivation and checked against its commitment for RingCT
+// outputs, `None` if the decoded amount does not match
+pub(crate) fn output_amount(tx: &Transaction, index: usize, derivation: &KeyDerivation) -> Option<u64> {
+    match &tx.signatures {
+        TxSignatures::V2 { base, .. } if base.rct_type != RctType::Null => {
+            let shared_secret = derivation_to_scalar(derivation, index as u64);
+            let (amount, mask) = ecdh_decode(base.encrypted_amounts.get(index)?, &shared_secret).ok()?;
+            let commitment = base.commitments.get(index)?;
+            (commit(amount, &mask).as_bytes() == commitment.as_bytes()).then_some(amount)
+        }
+        _ => tx.prefix.outputs.get(index).map(|output| output.amount),
+    }
+}
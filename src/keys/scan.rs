@@ -0,0 +1,331 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::commitment::{commit, ecdh_decode};
+use super::outputs::{derivation_to_scalar, derive_view_tag, generate_key_derivation};
+use super::subaddress::{subaddress_secret, SubaddressTable};
+use super::types::{KeyDerivation, OneTimeKey, PrivateKey, PublicKey};
+use crate::blocks::{extra::extract_tx_pub_keys, EncryptedAmount, RawTx, TxHash};
+use crate::utils::{Discrepancy, DiscrepancyKind, DiscrepancyLog};
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, Scalar};
+
+
+/// OwnedOutput is an output of a transaction that belongs to the scanning wallet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedOutput {
+    /// Index of the output in the transaction
+    pub index: u64,
+    /// Decoded amount in piconeros
+    pub amount: u64,
+    /// One-time public key of the output
+    pub output_key: OneTimeKey,
+    /// Transaction public key the output was derived from, either the main or the additional key of the output
+    pub tx_public_key: PublicKey,
+    /// Output of a version 1 transaction, referenced by its clear amount instead of being a RingCT output
+    pub pre_rct: bool,
+    /// Account and subaddress index that received the output, `(0, 0)` for the primary address
+    pub subaddress: (u32, u32),
+}
+
+impl OwnedOutput {
+    /// Derives the one-time private key of the output, `Hs(8aR || i) + b` plus the subaddress secret for outputs
+    /// received by a subaddress, needed to spend it, generate its key image or prove owning it
+    pub fn secret_key(&self, private_view_key: &PrivateKey, private_spend_key: &PrivateKey) -> PrivateKey {
+        let derivation = generate_key_derivation(&self.tx_public_key, private_view_key);
+        let mut secret = derivation_to_scalar(&derivation, self.index) + private_spend_key.to_scalar();
+        if self.subaddress != (0, 0) {
+            secret += subaddress_secret(private_view_key, self.subaddress.0, self.subaddress.1);
+        }
+        PrivateKey::from_bytes_mod_order(secret.to_bytes())
+    }
+}
+
+/// ScanFilter restricts scanning to a height range, a set of accounts or a minimum credited amount
+///
+/// The default filter keeps everything. Excluded heights skip the transaction entirely and excluded accounts skip
+/// the output before its amount is decoded, so narrow filters save most of the scanning work.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::ScanFilter;
+///
+/// let filter = ScanFilter::default().with_height_range(3000000, Some(3100000)).with_accounts(&[1]).with_min_amount(1000000);
+/// assert!(filter.includes_height(Some(3050000)));
+/// assert!(!filter.includes_height(Some(2999999)));
+/// assert!(!filter.includes_height(None));
+/// assert!(filter.includes_account(1));
+/// assert!(!filter.includes_account(0));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanFilter {
+    /// First height to scan
+    pub start_height: u64,
+    /// Last height to scan, inclusive, `None` to scan up to the tip and the transaction pool
+    pub end_height: Option<u64>,
+    /// Accounts to scan, `None` to scan all accounts
+    pub accounts: Option<Vec<u32>>,
+    /// Outputs crediting less than this amount, in piconeros, are ignored
+    pub min_amount: u64,
+}
+
+impl ScanFilter {
+    /// Restricts scanning to the heights from `start_height` to `end_height`, inclusive
+    pub fn with_height_range(mut self, start_height: u64, end_height: Option<u64>) -> ScanFilter {
+        self.start_height = start_height;
+        self.end_height = end_height;
+        self
+    }
+
+    /// Restricts scanning to the given accounts
+    pub fn with_accounts(mut self, accounts: &[u32]) -> ScanFilter {
+        self.accounts = Some(accounts.to_vec());
+        self
+    }
+
+    /// Ignores outputs crediting less than `min_amount` piconeros
+    pub fn with_min_amount(mut self, min_amount: u64) -> ScanFilter {
+        self.min_amount = min_amount;
+        self
+    }
+
+    /// Checks if a transaction at the given height should be scanned, `None` being a transaction in the pool
+    pub fn includes_height(&self, height: Option<u64>) -> bool {
+        match (height, self.end_height) {
+            (Some(height), Some(end_height)) => height >= self.start_height && height <= end_height,
+            (Some(height), None) => height >= self.start_height,
+            (None, end_height) => end_height.is_none(),
+        }
+    }
+
+    /// Checks if outputs received by the given account should be kept
+    pub fn includes_account(&self, account: u32) -> bool {
+        match &self.accounts {
+            Some(accounts) => accounts.contains(&account),
+            None => true,
+        }
+    }
+}
+
+/// Scans a transaction for outputs belonging to the given private view key and public spend key
+///
+/// Uses view tags when present, decodes RingCT amounts of all RingCT types and returns owned outputs in transaction order.
+/// Outputs whose data is malformed or whose commitment does not match the decoded amount are skipped, see
+/// [`scan_tx_with_diagnostics`](fn.scan_tx_with_diagnostics.html) to get them reported.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::parse_transaction_json;
+/// use libmonero::keys::{scan_tx_for_outputs, PrivateKey, PublicKey};
+///
+/// let view_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let public_spend_key = PublicKey::from_hex("03970285bf0724d75e0f50bca9a9ea0e8db5091b69403dc944465f8936bde787").unwrap();
+/// let tx = parse_transaction_json(r#"{"version": 2, "vin": [], "vout": [], "extra": [], "rct_signatures": {"type": 6}}"#).unwrap();
+/// assert!(scan_tx_for_outputs(&view_key, &public_spend_key, &tx).is_empty());
+/// ```
+pub fn scan_tx_for_outputs(view_key: &PrivateKey, public_spend_key: &PublicKey, tx: &RawTx) -> Vec<OwnedOutput> {
+    let lookup = |spend: &PublicKey| (spend == public_spend_key).then_some((0, 0));
+    scan_tx(view_key, &lookup, tx, &ScanFilter::default(), &ScanContext { log: None, tx_hash: None, height: None })
+}
+
+/// Scans a transaction included at the given height, `None` for the pool, for outputs received by any subaddress
+/// of the table and kept by the filter
+///
+/// Outputs are returned in transaction order with the subaddress that received them.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::parse_transaction_json;
+/// use libmonero::keys::{scan_tx_for_subaddresses, PrivateKey, ScanFilter, SubaddressTable, ViewPair};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+/// let table = SubaddressTable::new(&view_pair, 1, 10);
+/// let tx = parse_transaction_json(r#"{"version": 2, "vin": [], "vout": [], "extra": [], "rct_signatures": {"type": 6}}"#).unwrap();
+/// assert!(scan_tx_for_subaddresses(&table, &tx, Some(3000000), &ScanFilter::default()).is_empty());
+/// ```
+pub fn scan_tx_for_subaddresses(table: &SubaddressTable, tx: &RawTx, height: Option<u64>, filter: &ScanFilter) -> Vec<OwnedOutput> {
+    if !filter.includes_height(height) {
+        return Vec::new();
+    }
+    let lookup = |spend: &PublicKey| table.lookup(spend);
+    scan_tx(&table.view_pair().view, &lookup, tx, filter, &ScanContext { log: None, tx_hash: None, height: None })
+}
+
+/// Same as [`scan_tx_for_subaddresses`](fn.scan_tx_for_subaddresses.html), recording the data of the transaction with
+/// given hash that fails validation in the given log
+///
+/// Invalid keys, malformed view tags and encrypted amounts, and amount commitments not matching the decoded amount
+/// are recorded as [`Discrepancy`](../utils/struct.Discrepancy.html) and the affected outputs are skipped.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::{parse_transaction_json, TxHash};
+/// use libmonero::keys::{scan_tx_with_diagnostics, PrivateKey, ScanFilter, SubaddressTable, ViewPair};
+/// use libmonero::utils::{DiscrepancyKind, DiscrepancyLog};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+/// let table = SubaddressTable::new(&view_pair, 1, 10);
+/// let json = format!(r#"{{"version": 2, "vin": [], "vout": [{{"amount": 0, "target": {{"tagged_key": {{"key": "{}", "view_tag": "01"}}}}}}], "extra": [], "rct_signatures": {{"type": 6}}}}"#, "02".repeat(32));
+/// let tx = parse_transaction_json(&json).unwrap();
+/// let log = DiscrepancyLog::default();
+/// assert!(scan_tx_with_diagnostics(&table, TxHash::from_bytes([1; 32]), &tx, Some(3000000), &ScanFilter::default(), &log).is_empty());
+/// assert_eq!(log.records()[0].kind, DiscrepancyKind::InvalidOutputKey);
+/// ```
+pub fn scan_tx_with_diagnostics(table: &SubaddressTable, tx_hash: TxHash, tx: &RawTx, height: Option<u64>, filter: &ScanFilter, log: &DiscrepancyLog) -> Vec<OwnedOutput> {
+    if !filter.includes_height(height) {
+        return Vec::new();
+    }
+    let lookup = |spend: &PublicKey| table.lookup(spend);
+    let context = ScanContext {
+        log: Some(log),
+        tx_hash: Some(tx_hash),
+        height,
+    };
+    scan_tx(&table.view_pair().view, &lookup, tx, filter, &context)
+}
+
+// Scans a transaction for outputs sent to the public spend keys known to `lookup`, for watchers keeping their own
+// mapping of spend keys
+pub(crate) fn scan_tx_with_lookup(view_key: &PrivateKey, lookup: &dyn Fn(&PublicKey) -> Option<(u32, u32)>, tx: &RawTx) -> Vec<OwnedOutput> {
+    scan_tx(view_key, lookup, tx, &ScanFilter::default(), &ScanContext { log: None, tx_hash: None, height: None })
+}
+
+// Context of the transaction being scanned, for the discrepancies found in it
+struct ScanContext<'a> {
+    log: Option<&'a DiscrepancyLog>,
+    tx_hash: Option<TxHash>,
+    height: Option<u64>,
+}
+
+impl ScanContext<'_> {
+    fn report(&self, kind: DiscrepancyKind, output_index: Option<u64>, detail: String) {
+        if let Some(log) = self.log {
+            log.record(Discrepancy {
+                kind,
+                tx_hash: self.tx_hash,
+                height: self.height,
+                output_index,
+                detail,
+            });
+        }
+    }
+}
+
+// Scans the outputs of a transaction, `lookup` maps the public spend key an output was sent to to its subaddress
+fn scan_tx(view_key: &PrivateKey, lookup: &dyn Fn(&PublicKey) -> Option<(u32, u32)>, tx: &RawTx, filter: &ScanFilter, context: &ScanContext) -> Vec<OwnedOutput> {
+    let (main_keys, additional_keys) = extract_tx_pub_keys(&tx.extra);
+    let main: Vec<(PublicKey, KeyDerivation)> = main_keys
+        .iter()
+        .filter_map(|key| match PublicKey::from_bytes(*key) {
+            Ok(key) => Some(key),
+            Err(_) => {
+                context.report(DiscrepancyKind::InvalidTxPublicKey, None, format!("transaction public key {} is not a curve point", hex::encode(key)));
+                None
+            }
+        })
+        .map(|key| (key, generate_key_derivation(&key, view_key)))
+        .collect();
+    let mut owned = Vec::new();
+    for (index, vout) in tx.vout.iter().enumerate() {
+        let output_index = Some(index as u64);
+        let output_key = vout.target.tagged_key.key;
+        let output_point = match output_key.to_public_key() {
+            Ok(key) => key.to_point(),
+            Err(_) => {
+                context.report(DiscrepancyKind::InvalidOutputKey, output_index, format!("output key {} is not a curve point", output_key));
+                continue;
+            }
+        };
+        let encoded_tag = &vout.target.tagged_key.view_tag;
+        let view_tag = match hex::decode(encoded_tag) {
+            Ok(tag) if tag.len() == 1 => Some(tag[0]),
+            Ok(tag) if tag.is_empty() => None,
+            _ => {
+                context.report(DiscrepancyKind::MalformedViewTag, output_index, format!("view tag \"{}\" is not a single byte", encoded_tag));
+                None
+            }
+        };
+        let additional = additional_keys.get(index).and_then(|key| match PublicKey::from_bytes(*key) {
+            Ok(key) => Some((key, generate_key_derivation(&key, view_key))),
+            Err(_) => {
+                context.report(DiscrepancyKind::InvalidTxPublicKey, output_index, format!("additional public key {} is not a curve point", hex::encode(key)));
+                None
+            }
+        });
+        for (tx_public_key, derivation) in main.iter().chain(additional.iter()) {
+            if let Some(tag) = view_tag {
+                if derive_view_tag(derivation, index as u64) != tag {
+                    continue;
+                }
+            }
+            // P - Hs(derivation || index) * G is the public spend key the output was sent to
+            let scalar = derivation_to_scalar(derivation, index as u64);
+            let spend = PublicKey::from_point(output_point - ED25519_BASEPOINT_TABLE * &scalar);
+            let subaddress = match lookup(&spend) {
+                Some(subaddress) => subaddress,
+                None => continue,
+            };
+            if !filter.includes_account(subaddress.0) {
+                break;
+            }
+            let amount = match decode_amount(tx, index, derivation) {
+                Ok(amount) => amount,
+                Err(e) => {
+                    context.report(DiscrepancyKind::MalformedEncryptedAmount, output_index, e);
+                    break;
+                }
+            };
+            if let Err(e) = check_commitment(tx, index, amount) {
+                context.report(DiscrepancyKind::CommitmentMismatch, output_index, e);
+                break;
+            }
+            if amount.0 >= filter.min_amount {
+                owned.push(OwnedOutput {
+                    index: index as u64,
+                    amount: amount.0,
+                    output_key,
+                    tx_public_key: *tx_public_key,
+                    pre_rct: tx.version == 1,
+                    subaddress,
+                });
+            }
+            break;
+        }
+    }
+    owned
+}
+
+// Decodes the amount of an output, either in clear (RingCT type 0) or encrypted in ecdhInfo, along with the mask of
+// its commitment for RingCT outputs
+fn decode_amount(tx: &RawTx, index: usize, derivation: &KeyDerivation) -> Result<(u64, Option<Scalar>), String> {
+    let rct_type = tx.rct_signatures.type_int;
+    if rct_type == 0 {
+        return Ok((tx.vout[index].amount, None));
+    }
+    let ecdh = tx.rct_signatures.ecdh_info.get(index).ok_or("encrypted amount is missing")?;
+    let encrypted = EncryptedAmount::from_ecdh_info(ecdh, rct_type >= 4)?;
+    let (amount, mask) = ecdh_decode(&encrypted, &derivation_to_scalar(derivation, index as u64))?;
+    Ok((amount, Some(mask)))
+}
+
+// Checks that the commitment of a RingCT output is `mask * G + amount * H` for the decoded amount and mask, so a
+// sender can't credit more than the output is worth
+fn check_commitment(tx: &RawTx, index: usize, (amount, mask): (u64, Option<Scalar>)) -> Result<(), String> {
+    let mask = match mask {
+        Some(mask) => mask,
+        None => return Ok(()),
+    };
+    let commitment = tx.rct_signatures.out_pk.get(index).ok_or("amount commitment is missing")?;
+    if commit(amount, &mask).as_bytes() != commitment.as_bytes() {
+        return Err(format!("commitment {} does not match the decoded amount {}", commitment, amount));
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::types::{PrivateKey, PublicKey, ViewPair};
+use crate::utils::{AddressInfo, AddressType, Network};
+use crate::crypt::keccak::hash_to_scalar;
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, Scalar};
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+/// Returns the subaddress secret `Hs("SubAddr\0" || private_view_key || account || index)` of the given subaddress,
+/// its public spend key is `B + secret * G`
+pub fn subaddress_secret(private_view_key: &PrivateKey, account: u32, index: u32) -> Scalar {
+    let mut data = Zeroizing::new(b"SubAddr\0".to_vec());
+    data.extend_from_slice(private_view_key.as_bytes());
+    data.extend_from_slice(&account.to_le_bytes());
+    data.extend_from_slice(&index.to_le_bytes());
+    hash_to_scalar(&data)
+}
+
+/// Derives the public spend and view keys `(D, C)` of the given subaddress, `(0, 0)` is the main address and gives
+/// the wallet's own public keys
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_subaddress_keys, PrivateKey, ViewPair};
+///
+/// let private_view_key = PrivateKey::from_hex("77916d0cd56ed1920aef6ca56d8a41bac915b68e4c46a589e0956e27a7b77404").unwrap();
+/// let private_spend_key = PrivateKey::from_hex("8163466f1883598e6dd14027b8da727057165da91485834314f5500a65846f09").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_view_key);
+/// let (spend, view) = derive_subaddress_keys(&view_pair, 2, 18);
+/// assert_eq!(spend.to_hex(), "c25179ddef2ca4728fb691dd71561dc9f2e7e6b2a14284a4fe5441d7757aea02");
+/// assert_eq!(view.to_hex(), "601782bdde614e9ba664048a27b7407df4b76ae2e50a85fcc168a4c1766b3edf");
+/// ```
+pub fn derive_subaddress_keys(view_pair: &ViewPair, account: u32, index: u32) -> (PublicKey, PublicKey) {
+    if account == 0 && index == 0 {
+        return (view_pair.spend, view_pair.view.public_key());
+    }
+    let secret = subaddress_secret(&view_pair.view, account, index);
+    let spend = view_pair.spend.to_point() + ED25519_BASEPOINT_TABLE * &secret;
+    let view = spend * view_pair.view.to_scalar();
+    (PublicKey::from_point(spend), PublicKey::from_point(view))
+}
+
+/// Derives the address of the given subaddress, `(0, 0)` gives the main address
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_subaddress, PrivateKey, ViewPair};
+/// use libmonero::utils::Network;
+///
+/// let private_view_key = PrivateKey::from_hex("77916d0cd56ed1920aef6ca56d8a41bac915b68e4c46a589e0956e27a7b77404").unwrap();
+/// let private_spend_key = PrivateKey::from_hex("8163466f1883598e6dd14027b8da727057165da91485834314f5500a65846f09").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_view_key);
+/// assert_eq!(derive_subaddress(&view_pair, Network::Mainnet, 2, 18), "89pMNxzcCo5LAPZDX4qaTeanA6ZiS3VRdUbeKHzbDZkD1Q3YsDDfmXbT2zyjLeHWuuN4vxKne8kNpjH3cMk7nmhwSALCxsd");
+/// ```
+pub fn derive_subaddress(view_pair: &ViewPair, network: Network, account: u32, index: u32) -> String {
+    let (public_spend_key, public_view_key) = derive_subaddress_keys(view_pair, account, index);
+    let address_type = match account == 0 && index == 0 {
+        true => AddressType::Standard,
+        false => AddressType::Subaddress,
+    };
+    AddressInfo {
+        network,
+        address_type,
+        public_spend_key,
+        public_view_key,
+        payment_id: None,
+    }
+    .to_address()
+    .unwrap()
+}
+
+/// SubaddressTable maps the public spend keys of a wallet's subaddresses to their `(account, index)`, which is how
+/// scanning finds the subaddress an output was sent to
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_subaddress_keys, PrivateKey, SubaddressTable, ViewPair};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+/// let table = SubaddressTable::new(&view_pair, 2, 50);
+/// assert_eq!(table.len(), 100);
+/// let (spend, _) = derive_subaddress_keys(&view_pair, 1, 49);
+/// assert_eq!(table.lookup(&spend), Some((1, 49)));
+/// assert_eq!(table.lookup(&view_pair.spend), Some((0, 0)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SubaddressTable {
+    view_pair: ViewPair,
+    keys: HashMap<[u8; 32], (u32, u32)>,
+}
+
+impl SubaddressTable {
+    /// Creates a table holding the first `indexes_per_account` subaddresses of the first `accounts` accounts
+    ///
+    /// Wallets usually keep a lookahead of 50 unused subaddresses per account and 50 unused accounts
+    pub fn new(view_pair: &ViewPair, accounts: u32, indexes_per_account: u32) -> SubaddressTable {
+        let mut table = SubaddressTable {
+            view_pair: view_pair.clone(),
+            keys: HashMap::new(),
+        };
+        for account in 0..accounts {
+            table.extend(account, indexes_per_account);
+        }
+        table
+    }
+
+    /// Adds the subaddresses of the given account up to index `indexes - 1`
+    pub fn extend(&mut self, account: u32, indexes: u32) {
+        for index in 0..indexes {
+            self.insert(account, index);
+        }
+    }
+
+    /// Adds a single subaddress
+    pub fn insert(&mut self, account: u32, index: u32) {
+        let (spend, _) = derive_subaddress_keys(&self.view_pair, account, index);
+        self.keys.insert(*spend.as_bytes(), (account, index));
+    }
+
+    /// Returns the `(account, index)` of the subaddress with the given public spend key
+    pub fn lookup(&self, public_spend_key: &PublicKey) -> Option<(u32, u32)> {
+        self.keys.get(public_spend_key.as_bytes()).copied()
+    }
+
+    /// Returns the view pair the subaddresses are derived from
+    pub fn view_pair(&self) -> &ViewPair {
+        &self.view_pair
+    }
+
+    /// Returns the number of subaddresses in the table
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Checks if the table holds no subaddress
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
@@ -0,0 +1,338 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use crate::crypt::ed25519::sc_reduce32;
+use crate::utils::{address_prefix, AddressType, Network};
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY, EdwardsPoint, Scalar};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Decodes a 64-character hex string into 32 bytes
+pub(crate) fn decode_hex_32(hex_str: &str) -> Result<[u8; 32], String> {
+    let bytes = Zeroizing::new(hex::decode(hex_str).map_err(|e| format!("Invalid hex string: {}", e))?);
+    if bytes.len() != 32 {
+        return Err(format!("Invalid length: expected 32 bytes, got {}", bytes.len()));
+    }
+    let mut array = [0u8; 32];
+    array.copy_from_slice(&bytes);
+    Ok(array)
+}
+
+/// PrivateKey is a (reduced) Ed25519 scalar, used as private spend or private view key
+///
+/// The key is wiped from memory when dropped
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct PrivateKey([u8; 32]);
+
+impl PrivateKey {
+    /// Creates a private key from its 32-byte little-endian representation, which must be a reduced scalar
+    pub fn from_bytes(bytes: [u8; 32]) -> Result<PrivateKey, String> {
+        if Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes)).is_none() {
+            return Err("Private key is not a reduced scalar".to_string());
+        }
+        Ok(PrivateKey(bytes))
+    }
+
+    /// Creates a private key by reducing the given 32 bytes modulo the curve order
+    pub fn from_bytes_mod_order(mut bytes: [u8; 32]) -> PrivateKey {
+        sc_reduce32(&mut bytes);
+        PrivateKey(bytes)
+    }
+
+    /// Creates a private key from a 64-character hex string
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::keys::PrivateKey;
+    ///
+    /// let private_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+    /// assert_eq!(*private_key.to_hex(), "c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08");
+    /// assert!(PrivateKey::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").is_err());
+    /// ```
+    pub fn from_hex(hex_str: &str) -> Result<PrivateKey, String> {
+        PrivateKey::from_bytes(decode_hex_32(hex_str)?)
+    }
+
+    /// Returns the private key as a hex string, which is wiped from memory when dropped
+    pub fn to_hex(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(self.0))
+    }
+
+    /// Returns the raw bytes of the private key
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Returns the private key as a scalar
+    pub fn to_scalar(&self) -> Scalar {
+        Scalar::from_bytes_mod_order(self.0)
+    }
+
+    /// Derives the public key of this private key
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::keys::PrivateKey;
+    ///
+    /// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+    /// assert_eq!(private_spend_key.public_key().to_hex(), "e78d891dd2be407f24e6470caad956e1b746ae0b41cd8252f96684090bc05d95");
+    /// ```
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_point(ED25519_BASEPOINT_TABLE * &self.to_scalar())
+    }
+
+    /// Derives the private view key, treating this key as a private spend key
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::keys::PrivateKey;
+    ///
+    /// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+    /// assert_eq!(*private_spend_key.derive_view_key().to_hex(), "0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908");
+    /// ```
+    pub fn derive_view_key(&self) -> PrivateKey {
+        let mut view_key = Zeroizing::new([0u8; 32]);
+        view_key.copy_from_slice(&Keccak256::digest(self.0));
+        PrivateKey::from_bytes_mod_order(*view_key)
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret material
+        f.write_str("PrivateKey(..)")
+    }
+}
+
+/// PublicKey is a valid Ed25519 point, used as public spend or public view key
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey {
+    bytes: [u8; 32],
+    point: EdwardsPoint,
+}
+
+impl PublicKey {
+    /// Creates a public key from its compressed 32-byte representation, fails if it isn't a valid point
+    pub fn from_bytes(bytes: [u8; 32]) -> Result<PublicKey, String> {
+        match CompressedEdwardsY(bytes).decompress() {
+            Some(point) => Ok(PublicKey { bytes, point }),
+            None => Err("Public key is not a valid curve point".to_string()),
+        }
+    }
+
+    /// Creates a public key from a 64-character hex string
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::keys::PublicKey;
+    ///
+    /// let public_key = PublicKey::from_hex("e78d891dd2be407f24e6470caad956e1b746ae0b41cd8252f96684090bc05d95").unwrap();
+    /// assert_eq!(public_key.to_hex(), "e78d891dd2be407f24e6470caad956e1b746ae0b41cd8252f96684090bc05d95");
+    /// ```
+    pub fn from_hex(hex_str: &str) -> Result<PublicKey, String> {
+        PublicKey::from_bytes(decode_hex_32(hex_str)?)
+    }
+
+    /// Creates a public key from a curve point
+    pub fn from_point(point: EdwardsPoint) -> PublicKey {
+        PublicKey {
+            bytes: point.compress().to_bytes(),
+            point,
+        }
+    }
+
+    /// Returns the public key as a hex string
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.bytes)
+    }
+
+    /// Returns the compressed bytes of the public key
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+
+    /// Returns the public key as a curve point
+    pub fn to_point(&self) -> EdwardsPoint {
+        self.point
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PublicKey({})", self.to_hex())
+    }
+}
+
+/// KeyPair holds a private key together with its public key
+#[derive(Clone, Debug)]
+pub struct KeyPair {
+    pub private: PrivateKey,
+    pub public: PublicKey,
+}
+
+impl KeyPair {
+    /// Creates a key pair from the given private key
+    pub fn from_private(private: PrivateKey) -> KeyPair {
+        let public = private.public_key();
+        KeyPair { private, public }
+    }
+}
+
+/// ViewPair holds the keys needed to detect incoming outputs: public spend key and private view key
+#[derive(Clone, Debug)]
+pub struct ViewPair {
+    pub spend: PublicKey,
+    pub view: PrivateKey,
+}
+
+impl ViewPair {
+    /// Creates a view pair from the given public spend key and private view key
+    pub fn new(spend: PublicKey, view: PrivateKey) -> ViewPair {
+        ViewPair { spend, view }
+    }
+
+    /// Returns the main address of this view pair for the given network
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::keys::{PrivateKey, ViewPair};
+    /// use libmonero::utils::Network;
+    ///
+    /// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+    /// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+    /// assert_eq!(view_pair.address(Network::Mainnet), "4AQ3jTJg91yNGTXjo9iWr1ekjBGJ5mM6HEsxKqoKddHnRwJTVJYnyLXeerff6iTys5Eo8dyG87tfqZNS5CcSd7U694YiR8J");
+    /// assert!(view_pair.address(Network::Stagenet).starts_with('5'));
+    /// ```
+    pub fn address(&self, network: Network) -> String {
+        encode_address(network, &self.spend, &self.view.public_key())
+    }
+}
+
+// Defines a public 32-byte value type with the same constructors and accessors as KeyImage, along with hex Debug and Display
+macro_rules! bytes32_newtype {
+    ($(#[$meta:meta])* $name:ident, $what:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name([u8; 32]);
+
+        impl $name {
+            #[doc = concat!("Creates a ", $what, " from its 32-byte representation")]
+            pub fn from_bytes(bytes: [u8; 32]) -> $name {
+                $name(bytes)
+            }
+
+            #[doc = concat!("Creates a ", $what, " from a 64-character hex string")]
+            pub fn from_hex(hex_str: &str) -> Result<$name, String> {
+                Ok($name($crate::keys::types::decode_hex_32(hex_str)?))
+            }
+
+            #[doc = concat!("Returns the ", $what, " as a hex string")]
+            pub fn to_hex(&self) -> String {
+                hex::encode(self.0)
+            }
+
+            #[doc = concat!("Returns the raw bytes of the ", $what)]
+            pub fn as_bytes(&self) -> &[u8; 32] {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}({})", stringify!($name), self.to_hex())
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.to_hex())
+            }
+        }
+    };
+}
+
+pub(crate) use bytes32_newtype;
+
+bytes32_newtype!(
+    /// KeyImage is the linking tag `x * Hp(P)` of a key pair, every output can only be spent once because its key image gets recorded on chain
+    KeyImage,
+    "key image"
+);
+
+bytes32_newtype!(
+    /// OneTimeKey is the one-time public key `P = Hs(8rA || i) * G + B` of a transaction output
+    ///
+    /// It is kept as raw bytes since keys read from the chain are not guaranteed to be valid points,
+    /// use [`to_public_key`](#method.to_public_key) to get a curve point
+    OneTimeKey,
+    "one-time key"
+);
+
+impl OneTimeKey {
+    /// Returns the one-time key as a public key, fails if it isn't a valid point
+    pub fn to_public_key(&self) -> Result<PublicKey, String> {
+        PublicKey::from_bytes(self.0)
+    }
+}
+
+impl From<PublicKey> for OneTimeKey {
+    fn from(public_key: PublicKey) -> OneTimeKey {
+        OneTimeKey(*public_key.as_bytes())
+    }
+}
+
+bytes32_newtype!(
+    /// Commitment is the Pedersen commitment `C = mask * G + amount * H` hiding the amount of a RingCT output
+    Commitment,
+    "commitment"
+);
+
+/// KeyDerivation is the shared secret `8 * r * A` (sender side) or `8 * a * R` (receiver side) between a transaction and a recipient
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct KeyDerivation([u8; 32]);
+
+impl KeyDerivation {
+    /// Creates a key derivation from its compressed 32-byte representation
+    pub fn from_bytes(bytes: [u8; 32]) -> KeyDerivation {
+        KeyDerivation(bytes)
+    }
+
+    /// Returns the raw bytes of the key derivation
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Returns the key derivation as a hex string
+    pub fn to_hex(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for KeyDerivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Derivations allow detecting outputs, treat them as secret
+        f.write_str("KeyDerivation(..)")
+    }
+}
+
+/// Encodes the main address of the given public keys for given network
+pub(crate) fn encode_address(network: Network, public_spend_key: &PublicKey, public_view_key: &PublicKey) -> String {
+    encode_address_bytes(network, public_spend_key.as_bytes(), public_view_key.as_bytes())
+}
+
+/// Encodes the main address of the given public key bytes for given network
+pub(crate) fn encode_address_bytes(network: Network, public_spend_key: &[u8; 32], public_view_key: &[u8; 32]) -> String {
+    let mut data = [&[address_prefix(network, AddressType::Standard)][..], &public_spend_key[..], &public_view_key[..]].concat();
+    let hash = Keccak256::digest(&data);
+    data.append(&mut hash[..4].to_vec());
+
+    base58_monero::encode(&data).unwrap()
+}
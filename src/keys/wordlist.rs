@@ -0,0 +1,152 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::keys::utf8_prefix;
+use crate::mnemonics::original::wordsets::{original_wordsets, register_original_wordset, unregister_original_wordset};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+// Number of words of an original-type wordset
+const ORIGINAL_WORDSET_LEN: usize = 1626;
+
+/// WordlistError describes why a wordset could not be loaded, word indexes start at 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WordlistError {
+    /// Language code is empty or contains whitespace
+    InvalidLanguage(String),
+    /// Wordset does not have 1626 words
+    InvalidWordCount(usize),
+    /// Word is empty or contains whitespace
+    InvalidWord { index: usize },
+    /// Word appears twice in the wordset
+    DuplicateWord { index: usize, word: String },
+    /// Word has the same prefix as an earlier word, so the checksum could not tell them apart
+    DuplicatePrefix { index: usize, word: String, other: String },
+    /// Wordset could not be read or is not UTF-8
+    Read(String),
+}
+
+impl fmt::Display for WordlistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordlistError::InvalidLanguage(language) => write!(f, "Language code {:?} is invalid", language),
+            WordlistError::InvalidWordCount(count) => write!(f, "Wordset has {} words, expected {}", count, ORIGINAL_WORDSET_LEN),
+            WordlistError::InvalidWord { index } => write!(f, "Word {} is empty or contains whitespace", index + 1),
+            WordlistError::DuplicateWord { index, word } => write!(f, "Word {} ({}) appears twice", index + 1, word),
+            WordlistError::DuplicatePrefix { index, word, other } => write!(f, "Word {} ({}) has the same prefix as {}", index + 1, word, other),
+            WordlistError::Read(reason) => write!(f, "Wordset could not be read: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for WordlistError {}
+
+/// Loads an original-type (1626-word) wordset at runtime, so languages missing from the compiled-in wordsets can be
+/// used without a new release of libmonero
+///
+/// The words must be unique, non-empty and without whitespace, and when `prefix_len` is not 0 their first `prefix_len`
+/// characters must be unique too, as the checksum word only compares prefixes. A `prefix_len` of 0 gives seeds without
+/// a checksum word.
+///
+/// A wordset loaded with the code of a compiled-in language replaces it, loading the same code again replaces the
+/// previously loaded wordset. Once loaded, the language code works with every seed function, e.g.
+/// [`generate_seed`](fn.generate_seed.html), [`derive_hex_seed`](fn.derive_hex_seed.html) or
+/// [`convert_mnemonic`](fn.convert_mnemonic.html). Loaded words are kept in memory until the process exits.
+///
+/// Returns `()` if succesfull
+/// Returns an error describing the first invalid word if not succesfull
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_hex_seed, generate_seed, load_wordset, validate_mnemonic, WordlistError};
+///
+/// let words: Vec<String> = (0..1626).map(|i| format!("w{:04}", i)).collect();
+/// let words: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+/// load_wordset("x-digits", 5, &words).unwrap();
+///
+/// let mnemonic = generate_seed("x-digits", "original");
+/// assert!(validate_mnemonic(&mnemonic).is_ok());
+/// assert_eq!(derive_hex_seed(mnemonic.to_vec()).len(), 64);
+///
+/// assert_eq!(load_wordset("x-digits", 5, &words[1..]), Err(WordlistError::InvalidWordCount(1625)));
+/// ```
+pub fn load_wordset(language: &str, prefix_len: usize, words: &[&str]) -> Result<(), WordlistError> {
+    if language.is_empty() || language.chars().any(char::is_whitespace) {
+        return Err(WordlistError::InvalidLanguage(language.to_string()));
+    }
+    let words: &[&str; ORIGINAL_WORDSET_LEN] = words.try_into().map_err(|_| WordlistError::InvalidWordCount(words.len()))?;
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut prefixes: HashMap<&str, usize> = HashMap::new();
+    for (index, word) in words.iter().enumerate() {
+        if word.is_empty() || word.chars().any(char::is_whitespace) {
+            return Err(WordlistError::InvalidWord { index });
+        }
+        if seen.insert(word, index).is_some() {
+            return Err(WordlistError::DuplicateWord {
+                index,
+                word: word.to_string(),
+            });
+        }
+        if prefix_len > 0 {
+            if let Some(other) = prefixes.insert(utf8_prefix(word, prefix_len), index) {
+                return Err(WordlistError::DuplicatePrefix {
+                    index,
+                    word: word.to_string(),
+                    other: words[other].to_string(),
+                });
+            }
+        }
+    }
+    register_original_wordset(language, prefix_len, words);
+    Ok(())
+}
+
+/// Loads an original-type wordset from UTF-8 text with one word per line, like the wordlists of the Monero repository
+///
+/// Surrounding whitespace and blank lines are ignored, see [`load_wordset`](fn.load_wordset.html) for the rules
+/// the words must follow.
+///
+/// Returns `()` if succesfull
+/// Returns an error if the text is not UTF-8 or the wordset is invalid
+pub fn load_wordset_from_bytes(language: &str, prefix_len: usize, bytes: &[u8]) -> Result<(), WordlistError> {
+    let text = std::str::from_utf8(bytes).map_err(|e| WordlistError::Read(e.to_string()))?;
+    let words: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    load_wordset(language, prefix_len, &words)
+}
+
+/// Loads an original-type wordset from a file with one word per line, see
+/// [`load_wordset_from_bytes`](fn.load_wordset_from_bytes.html)
+///
+/// Returns `()` if succesfull
+/// Returns an error if the file can not be read or the wordset is invalid
+pub fn load_wordset_from_file<P: AsRef<Path>>(language: &str, prefix_len: usize, path: P) -> Result<(), WordlistError> {
+    let bytes = std::fs::read(path.as_ref()).map_err(|e| WordlistError::Read(format!("{}: {}", path.as_ref().display(), e)))?;
+    load_wordset_from_bytes(language, prefix_len, &bytes)
+}
+
+/// Removes a wordset loaded at runtime, a compiled-in wordset it replaced is used again
+///
+/// Returns false if no wordset was loaded for this language code.
+pub fn unload_wordset(language: &str) -> bool {
+    unregister_original_wordset(language)
+}
+
+/// Returns the language codes of the available original-type wordsets, compiled-in and loaded
+///
+/// Example:
+/// ```
+/// use libmonero::keys::wordset_languages;
+///
+/// assert!(wordset_languages().contains(&"en".to_string()));
+/// ```
+pub fn wordset_languages() -> Vec<String> {
+    original_wordsets().iter().map(|wordset| wordset.name.to_string()).collect()
+}
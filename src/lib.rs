@@ -21,28 +21,777 @@
 //! ## Structs, Functions And All Usable Items
 //! 
 //! - Blocks
+//!     - Async client (`async` feature)
+//!         - [`AsyncHttpTransport`](blocks/struct.AsyncHttpTransport.html)
+//!             - [`new() -> AsyncHttpTransport`](blocks/struct.AsyncHttpTransport.html#method.new)
+//!         - [`AsyncRpcTransport`](blocks/trait.AsyncRpcTransport.html)
+//!         - [`DaemonClient`](blocks/struct.DaemonClient.html)
+//!             - [`new(node: DaemonNode) -> DaemonClient`](blocks/struct.DaemonClient.html#method.new)
+//!             - [`with_config(node: DaemonNode, config: RpcConfig) -> DaemonClient`](blocks/struct.DaemonClient.html#method.with_config)
+//!             - [`get_alternate_chains() -> Result<Vec<AltChain>, String>`](blocks/struct.DaemonClient.html#method.get_alternate_chains)
+//!             - [`get_block_by_hash(hash: BlockHash) -> Result<Block, String>`](blocks/struct.DaemonClient.html#method.get_block_by_hash)
+//!             - [`get_block_from_height(block_height: u64) -> Result<Block, String>`](blocks/struct.DaemonClient.html#method.get_block_from_height)
+//!             - [`get_blocks_bin(block_ids: Vec<BlockHash>, start_height: u64, prune: bool) -> Result<BlocksBinResult, String>`](blocks/struct.DaemonClient.html#method.get_blocks_bin)
+//!             - [`get_block_count() -> Result<u64, String>`](blocks/struct.DaemonClient.html#method.get_block_count)
+//!             - [`get_block_header_by_hash(hash: BlockHash) -> Result<BlockHeader, String>`](blocks/struct.DaemonClient.html#method.get_block_header_by_hash)
+//!             - [`get_block_header_by_height(block_height: u64) -> Result<BlockHeader, String>`](blocks/struct.DaemonClient.html#method.get_block_header_by_height)
+//!             - [`get_block_headers_range(start_height: u64, end_height: u64) -> Result<Vec<BlockHeader>, String>`](blocks/struct.DaemonClient.html#method.get_block_headers_range)
+//!             - [`get_block_template(wallet_address: String, reserve_size: u64) -> Result<BlockTemplate, String>`](blocks/struct.DaemonClient.html#method.get_block_template)
+//!             - [`get_fee_estimate(grace_blocks: u64) -> Result<FeeEstimate, String>`](blocks/struct.DaemonClient.html#method.get_fee_estimate)
+//!             - [`get_global_output_indexes(hash: TxHash) -> Result<Vec<u64>, String>`](blocks/struct.DaemonClient.html#method.get_global_output_indexes)
+//!             - [`get_fork(chain: AltChain) -> Result<Fork, String>`](blocks/struct.DaemonClient.html#method.get_fork)
+//!             - [`get_height() -> Result<u64, String>`](blocks/struct.DaemonClient.html#method.get_height)
+//!             - [`get_height_info() -> Result<HeightInfo, String>`](blocks/struct.DaemonClient.html#method.get_height_info)
+//!             - [`get_info() -> Result<DaemonInfo, String>`](blocks/struct.DaemonClient.html#method.get_info)
+//!             - [`get_last_block_header() -> Result<BlockHeader, String>`](blocks/struct.DaemonClient.html#method.get_last_block_header)
+//!             - [`get_output_distribution(amounts: Vec<u64>, from_height: u64, to_height: u64) -> Result<Vec<OutputDistribution>, String>`](blocks/struct.DaemonClient.html#method.get_output_distribution)
+//!             - [`get_output_histogram(amounts: Vec<u64>, unlocked: bool) -> Result<Vec<OutputHistogramEntry>, String>`](blocks/struct.DaemonClient.html#method.get_output_histogram)
+//!             - [`get_outs(indices: Vec<u64>) -> Result<Vec<OutputEntry>, String>`](blocks/struct.DaemonClient.html#method.get_outs)
+//!             - [`get_outs_by_amount(amount: u64, indices: Vec<u64>) -> Result<Vec<OutputEntry>, String>`](blocks/struct.DaemonClient.html#method.get_outs_by_amount)
+//!             - [`get_outs_bin(indices: Vec<u64>) -> Result<Vec<OutputEntry>, String>`](blocks/struct.DaemonClient.html#method.get_outs_bin)
+//!             - [`get_transaction(hash: TxHash) -> Result<Transaction, String>`](blocks/struct.DaemonClient.html#method.get_transaction)
+//!             - [`get_transaction_from_hash(hash: TxHash) -> Result<RawTx, String>`](blocks/struct.DaemonClient.html#method.get_transaction_from_hash)
+//!             - [`get_transaction_pool() -> Result<Vec<PoolTx>, String>`](blocks/struct.DaemonClient.html#method.get_transaction_pool)
+//!             - [`get_transaction_pool_hashes() -> Result<Vec<TxHash>, String>`](blocks/struct.DaemonClient.html#method.get_transaction_pool_hashes)
+//!             - [`get_transaction_status(hash: TxHash) -> Result<TxStatus, String>`](blocks/struct.DaemonClient.html#method.get_transaction_status)
+//!             - [`get_txpool_backlog() -> Result<Vec<TxBacklogEntry>, String>`](blocks/struct.DaemonClient.html#method.get_txpool_backlog)
+//!             - [`height_at(timestamp: u64, network: Network) -> Result<u64, String>`](blocks/struct.DaemonClient.html#method.height_at)
+//!             - [`is_key_image_spent(key_images: Vec<KeyImage>) -> Result<Vec<KeyImageStatus>, String>`](blocks/struct.DaemonClient.html#method.is_key_image_spent)
+//!             - [`node() -> &DaemonNode`](blocks/struct.DaemonClient.html#method.node)
+//!             - [`send_raw_transaction(tx_as_hex: String, do_not_relay: bool) -> Result<SendTxResult, String>`](blocks/struct.DaemonClient.html#method.send_raw_transaction)
+//!             - [`submit_block(block_blob: Vec<u8>) -> Result<(), String>`](blocks/struct.DaemonClient.html#method.submit_block)
+//!             - [`watch_height(poll_interval: Duration) -> TipStream`](blocks/struct.DaemonClient.html#method.watch_height)
+//!             - [`with_transport(transport: Arc<dyn AsyncRpcTransport>) -> DaemonClient`](blocks/struct.DaemonClient.html#method.with_transport)
+//!         - [`RpcFuture`](blocks/type.RpcFuture.html)
+//!         - [`TipStream`](blocks/struct.TipStream.html)
+//!             - [`next() -> ChainTip`](blocks/struct.TipStream.html#method.next)
+//!     - ZMQ notifications (`zmq` feature)
+//!         - [`parse_zmq_message(message: &[u8]) -> Result<ZmqEvent, String>`](blocks/fn.parse_zmq_message.html)
+//!         - [`ZmqEvent`](blocks/enum.ZmqEvent.html)
+//!         - [`ZmqSubscriber`](blocks/struct.ZmqSubscriber.html)
+//!             - [`connect(endpoint: &str, topics: &[ZmqTopic]) -> Result<ZmqSubscriber, String>`](blocks/struct.ZmqSubscriber.html#method.connect)
+//!             - [`recv() -> Result<ZmqEvent, String>`](blocks/struct.ZmqSubscriber.html#method.recv)
+//!             - [`recv_timeout(timeout: Duration) -> Result<Option<ZmqEvent>, String>`](blocks/struct.ZmqSubscriber.html#method.recv_timeout)
+//!             - [`watch() -> (Receiver<Result<ZmqEvent, String>>, MonitorHandle)`](blocks/struct.ZmqSubscriber.html#method.watch)
+//!         - [`ZmqTopic`](blocks/enum.ZmqTopic.html)
+//!             - [`name() -> &'static str`](blocks/enum.ZmqTopic.html#method.name)
+//!     - Block parsing
+//!         - [`get_block_hash(blob: &[u8]) -> Result<BlockHash, String>`](blocks/fn.get_block_hash.html)
+//!         - [`Block`](blocks/struct.Block.html)
+//!             - [`parse_blob() -> Result<ParsedBlock, String>`](blocks/struct.Block.html#method.parse_blob)
+//!         - [`ParsedBlock`](blocks/struct.ParsedBlock.html)
+//!             - [`from_blob(blob: &[u8]) -> Result<ParsedBlock, String>`](blocks/struct.ParsedBlock.html#method.from_blob)
+//!             - [`hash() -> BlockHash`](blocks/struct.ParsedBlock.html#method.hash)
+//!             - [`hashing_blob() -> Vec<u8>`](blocks/struct.ParsedBlock.html#method.hashing_blob)
+//!             - [`to_blob() -> Vec<u8>`](blocks/struct.ParsedBlock.html#method.to_blob)
+//!     - Chain monitoring
+//!         - [`ChainAlarm`](blocks/enum.ChainAlarm.html)
+//!         - [`ChainMonitor`](blocks/struct.ChainMonitor.html)
+//!             - [`new(max_lag: u64) -> ChainMonitor`](blocks/struct.ChainMonitor.html#method.new)
+//!             - [`check() -> Vec<ChainAlarm>`](blocks/struct.ChainMonitor.html#method.check)
+//!             - [`observe(node: &str, height: u64, top_hash: &BlockHash)`](blocks/struct.ChainMonitor.html#method.observe)
+//!             - [`observe_error(node: &str, error: &str)`](blocks/struct.ChainMonitor.html#method.observe_error)
+//!             - [`poll(nodes: &[DaemonNode]) -> Vec<ChainAlarm>`](blocks/struct.ChainMonitor.html#method.poll)
+//!             - [`watch(nodes: Vec<DaemonNode>, interval: Duration, on_alarms: F) -> MonitorHandle`](blocks/struct.ChainMonitor.html#method.watch)
+//!         - [`MonitorHandle`](blocks/struct.MonitorHandle.html)
+//!             - [`stop()`](blocks/struct.MonitorHandle.html#method.stop)
+//!     - Chain tip watching
+//!         - [`watch_height(node: DaemonNode, poll_interval: Duration) -> impl Iterator<Item = ChainTip>`](blocks/fn.watch_height.html)
+//!         - [`ChainTip`](blocks/enum.ChainTip.html)
+//!         - [`TipWatcher`](blocks/struct.TipWatcher.html)
+//!             - [`new(depth: usize) -> TipWatcher`](blocks/struct.TipWatcher.html#method.new)
+//!             - [`hash_at(height: u64) -> Option<BlockHash>`](blocks/struct.TipWatcher.html#method.hash_at)
+//!             - [`poll(node: &DaemonNode) -> Result<Vec<ChainTip>, String>`](blocks/struct.TipWatcher.html#method.poll)
+//!             - [`tip() -> Option<(u64, BlockHash)>`](blocks/struct.TipWatcher.html#method.tip)
+//!             - [`update(top_height: u64, top_hash: BlockHash, links: &[(BlockHash, BlockHash)]) -> Result<Vec<ChainTip>, String>`](blocks/struct.TipWatcher.html#method.update)
+//!     - Double spend watching
+//!         - [`DoubleSpendAlert`](blocks/enum.DoubleSpendAlert.html)
+//!         - [`DoubleSpendWatcher`](blocks/struct.DoubleSpendWatcher.html)
+//!             - [`new() -> DoubleSpendWatcher`](blocks/struct.DoubleSpendWatcher.html#method.new)
+//!             - [`check(pool: &[PoolTx]) -> Vec<DoubleSpendAlert>`](blocks/struct.DoubleSpendWatcher.html#method.check)
+//!             - [`check_key_images(pool: &[(TxHash, Vec<KeyImage>, bool)]) -> Vec<DoubleSpendAlert>`](blocks/struct.DoubleSpendWatcher.html#method.check_key_images)
+//!             - [`poll(node: &DaemonNode) -> Result<Vec<DoubleSpendAlert>, String>`](blocks/struct.DoubleSpendWatcher.html#method.poll)
+//!             - [`run(node: DaemonNode, interval: Duration, on_alerts: F) -> MonitorHandle`](blocks/struct.DoubleSpendWatcher.html#method.run)
+//!             - [`unwatch(hash: &TxHash)`](blocks/struct.DoubleSpendWatcher.html#method.unwatch)
+//!             - [`watch(hash: TxHash, key_images: Vec<KeyImage>)`](blocks/struct.DoubleSpendWatcher.html#method.watch)
+//!             - [`watch_tx(hash: TxHash, tx: &RawTx)`](blocks/struct.DoubleSpendWatcher.html#method.watch_tx)
+//!             - [`watched() -> Vec<TxHash>`](blocks/struct.DoubleSpendWatcher.html#method.watched)
+//!     - Epee portable storage
+//!         - [`epee_from_bytes(data: &[u8]) -> Result<EpeeObject, String>`](blocks/fn.epee_from_bytes.html)
+//!         - [`epee_get(object: &EpeeObject, name: &str) -> Option<&EpeeValue>`](blocks/fn.epee_get.html)
+//!         - [`epee_to_bytes(root: &EpeeObject) -> Result<Vec<u8>, String>`](blocks/fn.epee_to_bytes.html)
+//!         - [`EpeeObject`](blocks/type.EpeeObject.html)
+//!         - [`EpeeValue`](blocks/enum.EpeeValue.html)
+//!     - Fees
+//!         - [`estimate_fee(priority: FeePriority, inputs: usize, outputs: usize, extra_size: usize, node: DaemonNode) -> Result<u64, String>`](blocks/fn.estimate_fee.html)
+//!         - [`estimate_tx_weight(inputs: usize, outputs: usize, ring_size: usize, extra_size: usize) -> u64`](blocks/fn.estimate_tx_weight.html)
+//!         - [`estimate_backlog_blocks(backlog: &[TxBacklogEntry], fee_per_byte: u64, block_weight_limit: u64) -> u64`](blocks/fn.estimate_backlog_blocks.html)
+//!         - [`suggest_priority(backlog: &[TxBacklogEntry], base_fee_per_byte: u64, block_weight_limit: u64, recent_block_weights: &[u64]) -> FeePriority`](blocks/fn.suggest_priority.html)
+//!         - [`FeeEstimate`](blocks/struct.FeeEstimate.html)
+//!             - [`fee_for_weight(weight: u64, priority: FeePriority) -> u64`](blocks/struct.FeeEstimate.html#method.fee_for_weight)
+//!             - [`fee_per_byte(priority: FeePriority) -> u64`](blocks/struct.FeeEstimate.html#method.fee_per_byte)
+//!         - [`FeePriority`](blocks/enum.FeePriority.html)
+//!     - Hashes
+//!         - [`BlockHash`](blocks/struct.BlockHash.html)
+//!         - [`TxHash`](blocks/struct.TxHash.html)
+//!     - Height estimation
+//!         - [`height_at(timestamp: u64, network: Network) -> u64`](blocks/fn.height_at.html)
+//!         - [`height_at_with_node(timestamp: u64, network: Network, node: DaemonNode) -> Result<u64, String>`](blocks/fn.height_at_with_node.html)
+//!         - [`timestamp_at(height: u64, network: Network) -> u64`](blocks/fn.timestamp_at.html)
+//!         - [`BLOCK_TARGET_SECONDS`](blocks/constant.BLOCK_TARGET_SECONDS.html)
+//!     - Mining
+//!         - [`get_block_template(wallet_address: &str, reserve_size: u64, node: DaemonNode) -> Result<BlockTemplate, String>`](blocks/fn.get_block_template.html)
+//!         - [`meets_difficulty(pow_hash: &[u8; 32], difficulty: u64) -> bool`](blocks/fn.meets_difficulty.html)
+//!         - [`nonce_ranges(workers: u32) -> Vec<RangeInclusive<u32>>`](blocks/fn.nonce_ranges.html)
+//!         - [`submit_block(block_blob: &[u8], node: DaemonNode) -> Result<(), String>`](blocks/fn.submit_block.html)
+//!         - [`BlockTemplate`](blocks/struct.BlockTemplate.html)
+//!             - [`refresh_hashing_blob() -> Result<(), String>`](blocks/struct.BlockTemplate.html#method.refresh_hashing_blob)
+//!             - [`set_extra_nonce(extra_nonce: &[u8]) -> Result<(), String>`](blocks/struct.BlockTemplate.html#method.set_extra_nonce)
+//!             - [`set_nonce(nonce: u32) -> Result<(), String>`](blocks/struct.BlockTemplate.html#method.set_nonce)
+//!             - [`set_reserved(data: &[u8]) -> Result<(), String>`](blocks/struct.BlockTemplate.html#method.set_reserved)
+//!         - [`ExtraNonceCounter`](blocks/struct.ExtraNonceCounter.html)
+//!             - [`new(prefix: &[u8], reserve_size: usize) -> Result<ExtraNonceCounter, String>`](blocks/struct.ExtraNonceCounter.html#method.new)
+//!             - [`next_extra_nonce() -> Result<Vec<u8>, String>`](blocks/struct.ExtraNonceCounter.html#method.next_extra_nonce)
 //!     - Nodes
+//!         - [`CancelToken`](blocks/struct.CancelToken.html)
+//!             - [`new() -> CancelToken`](blocks/struct.CancelToken.html#method.new)
+//!             - [`cancel()`](blocks/struct.CancelToken.html#method.cancel)
+//!             - [`is_cancelled() -> bool`](blocks/struct.CancelToken.html#method.is_cancelled)
 //!         - [`DaemonNode`](blocks/struct.DaemonNode.html)
 //!             - [`cake_wallet_default()`](blocks/struct.DaemonNode.html#method.cake_wallet_default)
 //!             - [`new(url: String, port: u16, tls: bool)`](blocks/struct.DaemonNode.html#method.new)
+//!             - [`base_url() -> String`](blocks/struct.DaemonNode.html#method.base_url)
+//!             - [`effective_trust(untrusted: bool) -> TrustLevel`](blocks/struct.DaemonNode.html#method.effective_trust)
+//!             - [`local_socket(path: P) -> DaemonNode`](blocks/struct.DaemonNode.html#method.local_socket)
+//!             - [`requires_cross_check(operation: RpcOperation, untrusted: bool) -> bool`](blocks/struct.DaemonNode.html#method.requires_cross_check)
 //!             - [`stack_wallet_default()`](blocks/struct.DaemonNode.html#method.stack_wallet_default)
+//!             - [`with_cancel(cancel: CancelToken) -> DaemonNode`](blocks/struct.DaemonNode.html#method.with_cancel)
+//!             - [`with_config(config: RpcConfig) -> DaemonNode`](blocks/struct.DaemonNode.html#method.with_config)
+//!             - [`with_login(username: &str, password: &str) -> DaemonNode`](blocks/struct.DaemonNode.html#method.with_login)
+//!             - [`with_priority(priority: RpcPriority) -> DaemonNode`](blocks/struct.DaemonNode.html#method.with_priority)
+//!             - [`with_proxy(proxy: ProxyConfig) -> DaemonNode`](blocks/struct.DaemonNode.html#method.with_proxy)
+//!             - [`with_transport(transport: Arc<dyn RpcTransport>) -> DaemonNode`](blocks/struct.DaemonNode.html#method.with_transport)
+//!             - [`with_trust(trust: TrustLevel) -> DaemonNode`](blocks/struct.DaemonNode.html#method.with_trust)
+//!         - [`NodeHealth`](blocks/struct.NodeHealth.html)
+//!         - [`NodePool`](blocks/struct.NodePool.html)
+//!             - [`new(nodes: Vec<DaemonNode>) -> Result<NodePool, String>`](blocks/struct.NodePool.html#method.new)
+//!             - [`check_health() -> Vec<ChainAlarm>`](blocks/struct.NodePool.html#method.check_health)
+//!             - [`health() -> Vec<NodeHealth>`](blocks/struct.NodePool.html#method.health)
+//!             - [`node() -> DaemonNode`](blocks/struct.NodePool.html#method.node)
+//!             - [`watch(interval: Duration, on_alarms: F) -> MonitorHandle`](blocks/struct.NodePool.html#method.watch)
+//!             - [`with_max_concurrent(max_concurrent: usize) -> NodePool`](blocks/struct.NodePool.html#method.with_max_concurrent)
+//!             - [`with_max_lag(max_lag: u64) -> NodePool`](blocks/struct.NodePool.html#method.with_max_lag)
+//!         - [`ProxyConfig`](blocks/struct.ProxyConfig.html)
+//!             - [`socks5(host: &str, port: u16) -> ProxyConfig`](blocks/struct.ProxyConfig.html#method.socks5)
+//!             - [`tor() -> ProxyConfig`](blocks/struct.ProxyConfig.html#method.tor)
+//!             - [`with_auth(username: &str, password: &str) -> ProxyConfig`](blocks/struct.ProxyConfig.html#method.with_auth)
+//!         - [`RpcConfig`](blocks/struct.RpcConfig.html)
+//!             - [`lan() -> RpcConfig`](blocks/struct.RpcConfig.html#method.lan)
+//!             - [`retry_delay(retry: u32) -> Duration`](blocks/struct.RpcConfig.html#method.retry_delay)
+//!             - [`tor() -> RpcConfig`](blocks/struct.RpcConfig.html#method.tor)
+//!         - [`RpcLogin`](blocks/struct.RpcLogin.html)
+//!         - [`RpcOperation`](blocks/enum.RpcOperation.html)
+//!         - [`RpcPriority`](blocks/enum.RpcPriority.html)
+//!         - [`TrustLevel`](blocks/enum.TrustLevel.html)
+//!     - Privacy
+//!         - [`absolute_offsets(relative: &[u64]) -> Result<Vec<u64>, String>`](blocks/fn.absolute_offsets.html)
+//!         - [`build_privacy_report(tx: &RawTx, real_positions: &[usize], change_index: Option<usize>, previous_rings: &[Vec<u64>], current_height: u64, output_height: F) -> Result<TxPrivacyReport, String>`](blocks/fn.build_privacy_report.html)
+//!         - [`relative_offsets(absolute: &[u64]) -> Result<Vec<u64>, String>`](blocks/fn.relative_offsets.html)
+//!         - [`InputPrivacy`](blocks/struct.InputPrivacy.html)
+//!         - [`TxPrivacyReport`](blocks/struct.TxPrivacyReport.html)
+//!             - [`overlapping_inputs() -> Vec<usize>`](blocks/struct.TxPrivacyReport.html#method.overlapping_inputs)
+//!             - [`youngest_real_inputs() -> Vec<usize>`](blocks/struct.TxPrivacyReport.html#method.youngest_real_inputs)
 //!     - RPCs
+//!         - [`get_alternate_chains(node: DaemonNode) -> Vec<AltChain>`](blocks/fn.get_alternate_chains.html)
+//!         - [`get_block_by_hash(hash: BlockHash, node: DaemonNode) -> Block`](blocks/fn.get_block_by_hash.html)
+//!         - [`get_blocks_bin(block_ids: &[BlockHash], start_height: u64, prune: bool, node: DaemonNode) -> BlocksBinResult`](blocks/fn.get_blocks_bin.html)
+//!         - [`get_block_count(node: DaemonNode) -> u64`](blocks/fn.get_block_count.html)
+//!         - [`get_block_header_by_hash(hash: BlockHash, node: DaemonNode) -> BlockHeader`](blocks/fn.get_block_header_by_hash.html)
+//!         - [`get_block_header_by_height(block_height: u64, node: DaemonNode) -> BlockHeader`](blocks/fn.get_block_header_by_height.html)
+//!         - [`get_block_headers_range(start_height: u64, end_height: u64, node: DaemonNode) -> Vec<BlockHeader>`](blocks/fn.get_block_headers_range.html)
+//!         - [`get_fee_estimate(grace_blocks: u64, node: DaemonNode) -> FeeEstimate`](blocks/fn.get_fee_estimate.html)
+//!         - [`get_global_output_indexes(hash: TxHash, node: DaemonNode) -> Vec<u64>`](blocks/fn.get_global_output_indexes.html)
+//!         - [`get_fork(chain: &AltChain, node: DaemonNode) -> Fork`](blocks/fn.get_fork.html)
 //!         - [`get_height(node: DaemonNode) -> u64`](blocks/fn.get_height.html)
+//!         - [`get_height_info(node: DaemonNode) -> HeightInfo`](blocks/fn.get_height_info.html)
+//!         - [`get_info(node: DaemonNode) -> DaemonInfo`](blocks/fn.get_info.html)
+//!         - [`get_last_block_header(node: DaemonNode) -> BlockHeader`](blocks/fn.get_last_block_header.html)
 //!         - [`get_block_from_height(node: DaemonNode, height: u64) -> Block`](blocks/fn.get_block_from_height.html)
-//!         - [`get_transaction_from_hash(node: DaemonNode, hash: &str) -> RawTx`](blocks/fn.get_transaction_from_hash.html)
+//!         - [`get_output_distribution(amounts: &[u64], from_height: u64, to_height: u64, node: DaemonNode) -> Vec<OutputDistribution>`](blocks/fn.get_output_distribution.html)
+//!         - [`get_output_histogram(amounts: &[u64], unlocked: bool, node: DaemonNode) -> Vec<OutputHistogramEntry>`](blocks/fn.get_output_histogram.html)
+//!         - [`get_outs(indices: &[u64], node: DaemonNode) -> Vec<OutputEntry>`](blocks/fn.get_outs.html)
+//!         - [`get_outs_by_amount(amount: u64, indices: &[u64], node: DaemonNode) -> Vec<OutputEntry>`](blocks/fn.get_outs_by_amount.html)
+//!         - [`get_outs_bin(indices: &[u64], node: DaemonNode) -> Vec<OutputEntry>`](blocks/fn.get_outs_bin.html)
+//!         - [`get_txpool_backlog(node: DaemonNode) -> Vec<TxBacklogEntry>`](blocks/fn.get_txpool_backlog.html)
+//!         - [`get_transaction(hash: TxHash, node: DaemonNode) -> Transaction`](blocks/fn.get_transaction.html)
+//!         - [`get_transaction_from_hash(hash: TxHash, node: DaemonNode) -> RawTx`](blocks/fn.get_transaction_from_hash.html)
+//!         - [`get_transaction_pool(node: DaemonNode) -> Vec<PoolTx>`](blocks/fn.get_transaction_pool.html)
+//!         - [`get_transaction_pool_hashes(node: DaemonNode) -> Vec<TxHash>`](blocks/fn.get_transaction_pool_hashes.html)
+//!         - [`get_transaction_status(hash: TxHash, node: DaemonNode) -> TxStatus`](blocks/fn.get_transaction_status.html)
+//!         - [`is_key_image_spent(key_images: &[KeyImage], node: DaemonNode) -> Vec<KeyImageStatus>`](blocks/fn.is_key_image_spent.html)
+//!         - [`AltChain`](blocks/struct.AltChain.html)
+//!             - [`fork_height() -> u64`](blocks/struct.AltChain.html#method.fork_height)
+//!         - [`BlockCompleteEntry`](blocks/struct.BlockCompleteEntry.html)
+//!         - [`BlocksBinResult`](blocks/struct.BlocksBinResult.html)
+//!         - [`Fork`](blocks/struct.Fork.html)
+//!         - [`KeyImageStatus`](blocks/enum.KeyImageStatus.html)
+//!         - [`OutputDistribution`](blocks/struct.OutputDistribution.html)
+//!         - [`OutputEntry`](blocks/struct.OutputEntry.html)
+//!         - [`OutputHistogramEntry`](blocks/struct.OutputHistogramEntry.html)
+//!         - [`PoolTx`](blocks/struct.PoolTx.html)
+//!         - [`parse_transaction_json(json: &str) -> Result<RawTx, String>`](blocks/fn.parse_transaction_json.html)
+//!         - [`send_raw_transaction(tx_as_hex: String, do_not_relay: bool, node: DaemonNode) -> SendTxResult`](blocks/fn.send_raw_transaction.html)
+//!         - [`SendTxResult`](blocks/struct.SendTxResult.html)
+//!             - [`rejection_reasons() -> Vec<&str>`](blocks/struct.SendTxResult.html#method.rejection_reasons)
+//!     - Transaction parsing
+//!         - [`parse_transaction_blob(blob: &[u8]) -> Result<Transaction, String>`](blocks/fn.parse_transaction_blob.html)
+//!         - [`EncryptedAmount`](blocks/enum.EncryptedAmount.html)
+//!             - [`from_ecdh_info(ecdh: &EcdhInfo, compact: bool) -> Result<EncryptedAmount, String>`](blocks/enum.EncryptedAmount.html#method.from_ecdh_info)
+//!             - [`to_ecdh_info() -> EcdhInfo`](blocks/enum.EncryptedAmount.html#method.to_ecdh_info)
+//!         - [`PaymentId`](blocks/enum.PaymentId.html)
+//!         - [`RctBase`](blocks/struct.RctBase.html)
+//!         - [`RctType`](blocks/enum.RctType.html)
+//!             - [`from_u8(code: u8) -> Option<RctType>`](blocks/enum.RctType.html#method.from_u8)
+//!         - [`Transaction`](blocks/struct.Transaction.html)
+//!             - [`additional_public_keys() -> Vec<PublicKey>`](blocks/struct.Transaction.html#method.additional_public_keys)
+//!             - [`extra() -> Result<TxExtra, String>`](blocks/struct.Transaction.html#method.extra)
+//!             - [`fee() -> u64`](blocks/struct.Transaction.html#method.fee)
+//!             - [`from_blob(blob: &[u8]) -> Result<Transaction, String>`](blocks/struct.Transaction.html#method.from_blob)
+//!             - [`hash() -> Result<TxHash, String>`](blocks/struct.Transaction.html#method.hash)
+//!             - [`hash_with_prunable_hash(prunable_hash: &[u8; 32]) -> TxHash`](blocks/struct.Transaction.html#method.hash_with_prunable_hash)
+//!             - [`is_coinbase() -> bool`](blocks/struct.Transaction.html#method.is_coinbase)
+//!             - [`is_pruned() -> bool`](blocks/struct.Transaction.html#method.is_pruned)
+//!             - [`key_images() -> Vec<KeyImage>`](blocks/struct.Transaction.html#method.key_images)
+//!             - [`payment_id() -> Option<PaymentId>`](blocks/struct.Transaction.html#method.payment_id)
+//!             - [`prefix_hash() -> [u8; 32]`](blocks/struct.Transaction.html#method.prefix_hash)
+//!             - [`to_blob() -> Vec<u8>`](blocks/struct.Transaction.html#method.to_blob)
+//!             - [`to_raw_tx() -> RawTx`](blocks/struct.Transaction.html#method.to_raw_tx)
+//!             - [`tx_public_key() -> Option<PublicKey>`](blocks/struct.Transaction.html#method.tx_public_key)
+//!             - [`tx_public_keys() -> Vec<PublicKey>`](blocks/struct.Transaction.html#method.tx_public_keys)
+//!         - [`TransactionPrefix`](blocks/struct.TransactionPrefix.html)
+//!         - [`TxInput`](blocks/enum.TxInput.html)
+//!         - [`TxOutTarget`](blocks/enum.TxOutTarget.html)
+//!         - [`TxOutput`](blocks/struct.TxOutput.html)
+//!             - [`key() -> OneTimeKey`](blocks/struct.TxOutput.html#method.key)
+//!             - [`view_tag() -> Option<u8>`](blocks/struct.TxOutput.html#method.view_tag)
+//!         - [`TxSignatures`](blocks/enum.TxSignatures.html)
+//!     - Transaction tracking
+//!         - [`TxState`](blocks/enum.TxState.html)
+//!         - [`TxTracker`](blocks/struct.TxTracker.html)
+//!             - [`new(hash: TxHash, confirmations: u64) -> TxTracker`](blocks/struct.TxTracker.html#method.new)
+//!             - [`poll(node: &DaemonNode) -> Result<Option<TxState>, String>`](blocks/struct.TxTracker.html#method.poll)
+//!             - [`track(node: &DaemonNode, interval: Duration, on_change: F) -> Result<TxState, String>`](blocks/struct.TxTracker.html#method.track)
+//!             - [`update(status: &TxStatus) -> Option<TxState>`](blocks/struct.TxTracker.html#method.update)
+//!             - [`with_max_missing_polls(max_missing_polls: u32) -> TxTracker`](blocks/struct.TxTracker.html#method.with_max_missing_polls)
+//!     - Tx extra
+//!         - [`check_tx_extra(extra: &[u8], outputs: usize) -> Vec<ExtraIssue>`](blocks/fn.check_tx_extra.html)
+//!         - [`ExtraField`](blocks/enum.ExtraField.html)
+//!         - [`ExtraIssue`](blocks/enum.ExtraIssue.html)
+//!             - [`severity() -> Severity`](blocks/enum.ExtraIssue.html#method.severity)
+//!         - [`MAX_TX_EXTRA_SIZE`](blocks/constant.MAX_TX_EXTRA_SIZE.html)
+//!         - [`TxExtra`](blocks/struct.TxExtra.html)
+//!             - [`new() -> TxExtra`](blocks/struct.TxExtra.html#method.new)
+//!             - [`additional_public_keys() -> Vec<PublicKey>`](blocks/struct.TxExtra.html#method.additional_public_keys)
+//!             - [`check(outputs: usize) -> Result<Vec<ExtraIssue>, String>`](blocks/struct.TxExtra.html#method.check)
+//!             - [`from_bytes(extra: &[u8]) -> Result<TxExtra, String>`](blocks/struct.TxExtra.html#method.from_bytes)
+//!             - [`nonce() -> Option<&[u8]>`](blocks/struct.TxExtra.html#method.nonce)
+//!             - [`payment_id() -> Option<PaymentId>`](blocks/struct.TxExtra.html#method.payment_id)
+//!             - [`to_bytes() -> Vec<u8>`](blocks/struct.TxExtra.html#method.to_bytes)
+//!             - [`tx_public_key() -> Option<PublicKey>`](blocks/struct.TxExtra.html#method.tx_public_key)
+//!             - [`with_additional_public_keys(keys: &[PublicKey]) -> TxExtra`](blocks/struct.TxExtra.html#method.with_additional_public_keys)
+//!             - [`with_nonce(nonce: Vec<u8>) -> Result<TxExtra, String>`](blocks/struct.TxExtra.html#method.with_nonce)
+//!             - [`with_padding(count: usize) -> Result<TxExtra, String>`](blocks/struct.TxExtra.html#method.with_padding)
+//!             - [`with_payment_id(payment_id: PaymentId) -> TxExtra`](blocks/struct.TxExtra.html#method.with_payment_id)
+//!             - [`with_tx_public_key(tx_public_key: &PublicKey) -> TxExtra`](blocks/struct.TxExtra.html#method.with_tx_public_key)
+//!     - Transports
+//!         - [`HttpTransport`](blocks/struct.HttpTransport.html)
+//!         - [`LocalSocketTransport`](blocks/struct.LocalSocketTransport.html)
+//!             - [`new(path: P) -> LocalSocketTransport`](blocks/struct.LocalSocketTransport.html#method.new)
+//!         - [`RecordingTransport`](blocks/struct.RecordingTransport.html)
+//!             - [`new(inner: Arc<dyn RpcTransport>, path: P) -> Result<RecordingTransport, String>`](blocks/struct.RecordingTransport.html#method.new)
+//!         - [`ReplayTransport`](blocks/struct.ReplayTransport.html)
+//!             - [`from_file(path: P) -> Result<ReplayTransport, String>`](blocks/struct.ReplayTransport.html#method.from_file)
+//!             - [`from_reader(reader: R) -> Result<ReplayTransport, String>`](blocks/struct.ReplayTransport.html#method.from_reader)
+//!         - [`RpcTransport`](blocks/trait.RpcTransport.html)
 //! - Crypt
+//!     - [`bulletproof_plus_prove(amounts: &[u64], masks: &[Scalar]) -> Result<BulletproofPlus, String>`](crypt/fn.bulletproof_plus_prove.html)
+//!     - [`chacha20(data: &[u8], key: &[u8; 32], iv: &[u8; 8]) -> Vec<u8>`](crypt/fn.chacha20.html)
+//!     - [`chacha8(data: &[u8], key: &[u8; 32], iv: &[u8; 8]) -> Vec<u8>`](crypt/fn.chacha8.html)
+//!     - [`clsag_sign(message: &[u8; 32], ring: &[(OneTimeKey, Commitment)], real_index: usize, secret_key: &PrivateKey, mask: &Scalar, pseudo_out: &Commitment, pseudo_mask: &Scalar) -> Result<Clsag, String>`](crypt/fn.clsag_sign.html)
+//!     - [`ge_fromfe_frombytes_vartime(bytes: &[u8; 32]) -> EdwardsPoint`](crypt/fn.ge_fromfe_frombytes_vartime.html)
+//!     - [`generate_chacha_key(password: &[u8], kdf_rounds: u64) -> Result<Zeroizing<[u8; 32]>, String>`](crypt/fn.generate_chacha_key.html)
+//!     - [`hash_to_point(input: &[u8]) -> EdwardsPoint`](crypt/fn.hash_to_point.html)
+//!     - [`BulletproofPlus`](crypt/struct.BulletproofPlus.html)
+//!         - [`to_bytes() -> Vec<u8>`](crypt/struct.BulletproofPlus.html#method.to_bytes)
+//!         - [`verify(commitments: &[Commitment]) -> bool`](crypt/struct.BulletproofPlus.html#method.verify)
+//!     - [`Clsag`](crypt/struct.Clsag.html)
+//!         - [`verify(message: &[u8; 32], ring: &[(OneTimeKey, Commitment)], key_image: &KeyImage, pseudo_out: &Commitment) -> bool`](crypt/struct.Clsag.html#method.verify)
 //!     - [`cryptonight`](crypt/cryptonight/index.html)
-//!         - [`cn_slow_hash_original(input: &[u8]) -> String`](crypt/cryptonight/fn.cn_slow_hash_original.html) - EXPERIMENTAL!
+//!         - [`cn_slow_hash_v0(input: &[u8]) -> String`](crypt/cryptonight/fn.cn_slow_hash_v0.html) - EXPERIMENTAL!
+//!         - [`cn_slow_hash(input: &[u8], variant: CnVariant) -> Result<String, String>`](crypt/cryptonight/fn.cn_slow_hash.html) - EXPERIMENTAL!
+//!         - [`cn_slow_hash_software(input: &[u8], variant: CnVariant) -> Result<String, String>`](crypt/cryptonight/fn.cn_slow_hash_software.html) - EXPERIMENTAL!
+//!         - [`hardware_aes_available() -> bool`](crypt/cryptonight/fn.hardware_aes_available.html)
+//!         - [`CnHasher`](crypt/cryptonight/struct.CnHasher.html)
+//!             - [`new() -> CnHasher`](crypt/cryptonight/struct.CnHasher.html#method.new)
+//!             - [`hash(input: &[u8], variant: CnVariant) -> Result<String, String>`](crypt/cryptonight/struct.CnHasher.html#method.hash) - EXPERIMENTAL!
+//!         - [`CnVariant`](crypt/cryptonight/enum.CnVariant.html)
+//!             - [`from_major_version(major_version: u64, height: u64) -> Result<CnVariant, String>`](crypt/cryptonight/enum.CnVariant.html#method.from_major_version)
+//!     - [`keccak`](crypt/keccak/index.html)
+//!         - [`hash_to_scalar(input: &[u8]) -> Scalar`](crypt/keccak/fn.hash_to_scalar.html)
+//!         - [`hash_to_scalar_hex(input: &str) -> Result<String, String>`](crypt/keccak/fn.hash_to_scalar_hex.html)
+//!         - [`keccak1600(input: &[u8]) -> [u8; 200]`](crypt/keccak/fn.keccak1600.html)
+//!         - [`keccak256(input: &[u8]) -> [u8; 32]`](crypt/keccak/fn.keccak256.html)
+//!         - [`keccak256_hex(input: &str) -> Result<String, String>`](crypt/keccak/fn.keccak256_hex.html)
+//!         - [`keccakf(state: &mut [u64; 25])`](crypt/keccak/fn.keccakf.html)
+//!     - [`randomx`](crypt/randomx/index.html)
+//!         - [`rx_seed_height(height: u64) -> u64`](crypt/randomx/fn.rx_seed_height.html)
+//!         - [`rx_slow_hash(seed_hash: &[u8], input: &[u8]) -> String`](crypt/randomx/fn.rx_slow_hash.html)
+//!         - [`RandomXCache`](crypt/randomx/struct.RandomXCache.html)
+//!             - [`new(key: &[u8]) -> RandomXCache`](crypt/randomx/struct.RandomXCache.html#method.new)
+//!             - [`key() -> &[u8]`](crypt/randomx/struct.RandomXCache.html#method.key)
+//!         - [`RandomXDataset`](crypt/randomx/struct.RandomXDataset.html)
+//!             - [`new(cache: &RandomXCache, threads: usize) -> RandomXDataset`](crypt/randomx/struct.RandomXDataset.html#method.new)
+//!         - [`RandomXVm`](crypt/randomx/struct.RandomXVm.html)
+//!             - [`light(cache: &RandomXCache) -> RandomXVm`](crypt/randomx/struct.RandomXVm.html#method.light)
+//!             - [`full(dataset: &RandomXDataset) -> RandomXVm`](crypt/randomx/struct.RandomXVm.html#method.full)
+//!             - [`calculate_hash(input: &[u8]) -> [u8; 32]`](crypt/randomx/struct.RandomXVm.html#method.calculate_hash)
 //! - Keys
-//!     - [`derive_address(public_spend_key: String, public_view_key: String, network: i8) -> String`](keys/fn.derive_address.html)
-//!     - [`derive_hex_seed(mnemonic_seed: Vec<String>) -> String`](keys/fn.derive_hex_seed.html)
-//!     - [`derive_priv_keys(hex_seed: String) -> Vec<String>`](keys/fn.derive_priv_keys.html)
-//!     - [`derive_priv_vk_from_priv_sk(private_spend_key: String) -> String`](keys/fn.derive_priv_vk_from_priv_sk.html)
-//!     - [`derive_pub_key(private_key: String) -> String`](keys/fn.derive_pub_key.html)
-//!     - [`generate_seed(language: &str, seed_type: &str) -> Vec<String>`](keys/fn.generate_seed.html)
+//!     - Scanning benchmark (`bench` feature)
+//!         - [`bench_scanning(table: &SubaddressTable, blocks: &[Vec<RawTx>], threads: usize, view_tags: bool) -> BenchReport`](keys/fn.bench_scanning.html)
+//!         - [`generate_synthetic_blocks(view_pair: &ViewPair, config: &BenchConfig) -> Vec<Vec<RawTx>>`](keys/fn.generate_synthetic_blocks.html)
+//!         - [`run_scan_benchmark(config: &BenchConfig) -> BenchReport`](keys/fn.run_scan_benchmark.html)
+//!         - [`BenchConfig`](keys/struct.BenchConfig.html)
+//!         - [`BenchReport`](keys/struct.BenchReport.html)
+//!             - [`outputs_per_second() -> f64`](keys/struct.BenchReport.html#method.outputs_per_second)
+//!     - [`check_ring_signature(hash: &[u8; 32], key_image: &KeyImage, ring: &[PublicKey], signatures: &[Signature]) -> bool`](keys/fn.check_ring_signature.html)
+//!     - [`check_tx_proof(tx_hash: &TxHash, tx: &Transaction, address: &str, message: &[u8], proof: &str) -> Result<u64, String>`](keys/fn.check_tx_proof.html)
+//!     - [`commit(amount: u64, mask: &Scalar) -> Commitment`](keys/fn.commit.html)
+//!     - [`commitment_mask(shared_secret: &Scalar) -> Scalar`](keys/fn.commitment_mask.html)
+//!     - [`convert_mnemonic(words: &[String], target_language: &str) -> Result<Zeroizing<Vec<String>>, MnemonicError>`](keys/fn.convert_mnemonic.html)
+//!     - [`derivation_to_scalar(derivation: &KeyDerivation, output_index: u64) -> Scalar`](keys/fn.derivation_to_scalar.html)
+//!     - [`derive_address(public_spend_key: String, public_view_key: String, network: u8) -> Result<String, String>`](keys/fn.derive_address.html)
+//!     - [`derive_app_secret(private_spend_key: &PrivateKey, purpose: &str) -> PrivateKey`](keys/fn.derive_app_secret.html)
+//!     - [`derive_hex_seed(mnemonic_seed: Vec<String>) -> Zeroizing<String>`](keys/fn.derive_hex_seed.html)
+//!     - [`derive_hex_seed_with_passphrase(mnemonic_seed: Vec<String>, passphrase: Option<&str>) -> Result<Zeroizing<String>, String>`](keys/fn.derive_hex_seed_with_passphrase.html)
+//!     - [`derive_output_key(recipient_address: &str, tx_secret_key: &PrivateKey, output_index: u64) -> Result<OutputKeys, String>`](keys/fn.derive_output_key.html)
+//!     - [`derive_output_key_for(recipient: &AddressInfo, tx_secret_key: &PrivateKey, output_index: u64) -> OutputKeys`](keys/fn.derive_output_key_for.html)
+//!     - [`derive_output_privkey(derivation: &KeyDerivation, output_index: u64, private_spend_key: &PrivateKey) -> PrivateKey`](keys/fn.derive_output_privkey.html)
+//!     - [`derive_output_pubkey(derivation: &KeyDerivation, output_index: u64, public_spend_key: &PublicKey) -> PublicKey`](keys/fn.derive_output_pubkey.html)
+//!     - [`derive_priv_keys(hex_seed: String) -> Zeroizing<Vec<String>>`](keys/fn.derive_priv_keys.html)
+//!     - [`derive_priv_vk_from_priv_sk(private_spend_key: String) -> Result<Zeroizing<String>, String>`](keys/fn.derive_priv_vk_from_priv_sk.html)
+//!     - [`derive_pub_key(private_key: String) -> Result<String, String>`](keys/fn.derive_pub_key.html)
+//!     - [`derive_subaddress(view_pair: &ViewPair, network: Network, account: u32, index: u32) -> String`](keys/fn.derive_subaddress.html)
+//!     - [`derive_subaddress_keys(view_pair: &ViewPair, account: u32, index: u32) -> (PublicKey, PublicKey)`](keys/fn.derive_subaddress_keys.html)
+//!     - [`derive_view_tag(derivation: &KeyDerivation, output_index: u64) -> u8`](keys/fn.derive_view_tag.html)
+//!     - [`ecdh_decode(encrypted: &EncryptedAmount, shared_secret: &Scalar) -> Result<(u64, Scalar), String>`](keys/fn.ecdh_decode.html)
+//!     - [`ecdh_encode(amount: u64, mask: &Scalar, shared_secret: &Scalar, compact: bool) -> EncryptedAmount`](keys/fn.ecdh_encode.html)
+//!     - [`generate_key_derivation(public_key: &PublicKey, private_key: &PrivateKey) -> KeyDerivation`](keys/fn.generate_key_derivation.html)
+//!     - [`generate_key_image(private_key: &PrivateKey, public_key: &PublicKey) -> KeyImage`](keys/fn.generate_key_image.html)
+//!     - [`generate_ring_signature(hash: &[u8; 32], key_image: &KeyImage, ring: &[PublicKey], private_key: &PrivateKey, real_index: usize) -> Result<Vec<Signature>, String>`](keys/fn.generate_ring_signature.html)
+//!     - [`generate_seed(language: &str, seed_type: &str) -> Zeroizing<Vec<String>>`](keys/fn.generate_seed.html)
+//!     - [`generate_seed_from_entropy(entropy: &[u8; 32], language: &str) -> Zeroizing<Vec<String>>`](keys/fn.generate_seed_from_entropy.html)
+//!     - [`generate_seed_with_rng(rng: &mut R, language: &str, seed_type: &str) -> Zeroizing<Vec<String>>`](keys/fn.generate_seed_with_rng.html)
+//!     - [`generate_signature(hash: &[u8; 32], private_key: &PrivateKey) -> Signature`](keys/fn.generate_signature.html)
+//!     - [`generate_tx_proof(tx_hash: &TxHash, tx: &Transaction, address: &str, message: &[u8], keys: &TxProofKeys) -> Result<String, String>`](keys/fn.generate_tx_proof.html)
+//!     - [`is_output_owned(view_pair: &ViewPair, tx_public_key: &PublicKey, output_index: u64, output_key: &OneTimeKey) -> bool`](keys/fn.is_output_owned.html)
+//!     - [`load_wordset(language: &str, prefix_len: usize, words: &[&str]) -> Result<(), WordlistError>`](keys/fn.load_wordset.html)
+//!     - [`load_wordset_from_bytes(language: &str, prefix_len: usize, bytes: &[u8]) -> Result<(), WordlistError>`](keys/fn.load_wordset_from_bytes.html)
+//!     - [`load_wordset_from_file<P: AsRef<Path>>(language: &str, prefix_len: usize, path: P) -> Result<(), WordlistError>`](keys/fn.load_wordset_from_file.html)
+//!     - [`needs_additional_tx_keys(destinations: &[AddressInfo]) -> bool`](keys/fn.needs_additional_tx_keys.html)
+//!     - [`prove_output_ownership(output_secret: &PrivateKey, message: &[u8]) -> OutputOwnershipProof`](keys/fn.prove_output_ownership.html)
+//!     - [`scan_tx_for_outputs(view_key: &PrivateKey, public_spend_key: &PublicKey, tx: &RawTx) -> Vec<OwnedOutput>`](keys/fn.scan_tx_for_outputs.html)
+//!     - [`scan_tx_for_subaddresses(table: &SubaddressTable, tx: &RawTx, height: Option<u64>, filter: &ScanFilter) -> Vec<OwnedOutput>`](keys/fn.scan_tx_for_subaddresses.html)
+//!     - [`scan_tx_with_diagnostics(table: &SubaddressTable, tx_hash: TxHash, tx: &RawTx, height: Option<u64>, filter: &ScanFilter, log: &DiscrepancyLog) -> Vec<OwnedOutput>`](keys/fn.scan_tx_with_diagnostics.html)
+//!     - [`subaddress_secret(private_view_key: &PrivateKey, account: u32, index: u32) -> Scalar`](keys/fn.subaddress_secret.html)
+//!     - [`unload_wordset(language: &str) -> bool`](keys/fn.unload_wordset.html)
+//!     - [`validate_mnemonic(words: &[String]) -> Result<SeedType, MnemonicError>`](keys/fn.validate_mnemonic.html)
+//!     - [`wordset_languages() -> Vec<String>`](keys/fn.wordset_languages.html)
+//!     - [`zero_commit(amount: u64) -> Commitment`](keys/fn.zero_commit.html)
+//!     - [`Commitment`](keys/struct.Commitment.html)
+//!     - [`KeyDerivation`](keys/struct.KeyDerivation.html)
+//!     - [`KeyImage`](keys/struct.KeyImage.html)
+//!     - [`KeyPair`](keys/struct.KeyPair.html)
+//!     - [`MnemonicError`](keys/enum.MnemonicError.html)
+//!     - [`OneTimeKey`](keys/struct.OneTimeKey.html)
+//!         - [`to_public_key() -> Result<PublicKey, String>`](keys/struct.OneTimeKey.html#method.to_public_key)
+//!     - [`OutputKeys`](keys/struct.OutputKeys.html)
+//!     - [`OutputOwnershipProof`](keys/struct.OutputOwnershipProof.html)
+//!         - [`verify(message: &[u8]) -> bool`](keys/struct.OutputOwnershipProof.html#method.verify)
+//!     - [`OwnedOutput`](keys/struct.OwnedOutput.html)
+//!         - [`secret_key(private_view_key: &PrivateKey, private_spend_key: &PrivateKey) -> PrivateKey`](keys/struct.OwnedOutput.html#method.secret_key)
+//!     - [`PrivateKey`](keys/struct.PrivateKey.html)
+//!         - [`derive_view_key() -> PrivateKey`](keys/struct.PrivateKey.html#method.derive_view_key)
+//!         - [`from_hex(hex_str: &str) -> Result<PrivateKey, String>`](keys/struct.PrivateKey.html#method.from_hex)
+//!         - [`public_key() -> PublicKey`](keys/struct.PrivateKey.html#method.public_key)
+//!     - [`PublicKey`](keys/struct.PublicKey.html)
+//!         - [`from_hex(hex_str: &str) -> Result<PublicKey, String>`](keys/struct.PublicKey.html#method.from_hex)
+//!     - [`ScanFilter`](keys/struct.ScanFilter.html)
+//!         - [`includes_account(account: u32) -> bool`](keys/struct.ScanFilter.html#method.includes_account)
+//!         - [`includes_height(height: Option<u64>) -> bool`](keys/struct.ScanFilter.html#method.includes_height)
+//!         - [`with_accounts(accounts: &[u32]) -> ScanFilter`](keys/struct.ScanFilter.html#method.with_accounts)
+//!         - [`with_height_range(start_height: u64, end_height: Option<u64>) -> ScanFilter`](keys/struct.ScanFilter.html#method.with_height_range)
+//!         - [`with_min_amount(min_amount: u64) -> ScanFilter`](keys/struct.ScanFilter.html#method.with_min_amount)
+//!     - [`SeedType`](keys/enum.SeedType.html)
+//!     - [`Signature`](keys/struct.Signature.html)
+//!         - [`from_bytes(bytes: [u8; 64]) -> Signature`](keys/struct.Signature.html#method.from_bytes)
+//!         - [`to_bytes() -> [u8; 64]`](keys/struct.Signature.html#method.to_bytes)
+//!         - [`verify(hash: &[u8; 32], public_key: &PublicKey) -> bool`](keys/struct.Signature.html#method.verify)
+//!     - [`SubaddressTable`](keys/struct.SubaddressTable.html)
+//!         - [`extend(account: u32, indexes: u32)`](keys/struct.SubaddressTable.html#method.extend)
+//!         - [`insert(account: u32, index: u32)`](keys/struct.SubaddressTable.html#method.insert)
+//!         - [`lookup(public_spend_key: &PublicKey) -> Option<(u32, u32)>`](keys/struct.SubaddressTable.html#method.lookup)
+//!         - [`new(view_pair: &ViewPair, accounts: u32, indexes_per_account: u32) -> SubaddressTable`](keys/struct.SubaddressTable.html#method.new)
+//!     - [`TxProofKeys`](keys/enum.TxProofKeys.html)
+//!     - [`ViewPair`](keys/struct.ViewPair.html)
+//!         - [`address(network: Network) -> String`](keys/struct.ViewPair.html#method.address)
+//!     - [`WordlistError`](keys/enum.WordlistError.html)
 //! - Utils
 //! 
+//!     - [`classify_addr(address: &str) -> Option<(Network, AddressType)>`](utils/fn.classify_addr.html)
 //!     - [`is_valid_addr(address: &str) -> bool`](utils/fn.is_valid_addr.html)
+//!     - Addresses
+//!         - [`decode_address(address: &str) -> Result<AddressInfo, String>`](utils/fn.decode_address.html)
+//!         - [`validate_addresses(addresses: &[&str]) -> Vec<ValidationResult>`](utils/fn.validate_addresses.html)
+//!         - [`validate_addresses_parallel(addresses: &[&str], threads: usize) -> Vec<ValidationResult>`](utils/fn.validate_addresses_parallel.html)
+//!         - [`AddressInfo`](utils/struct.AddressInfo.html)
+//!             - [`to_address() -> Result<String, String>`](utils/struct.AddressInfo.html#method.to_address)
+//!         - [`AddressType`](utils/enum.AddressType.html)
+//!         - [`Network`](utils/enum.Network.html)
+//!         - [`ValidationResult`](utils/enum.ValidationResult.html)
+//!             - [`error() -> Option<&str>`](utils/enum.ValidationResult.html#method.error)
+//!             - [`info() -> Option<&AddressInfo>`](utils/enum.ValidationResult.html#method.info)
+//!             - [`is_valid() -> bool`](utils/enum.ValidationResult.html#method.is_valid)
+//!     - Amounts
+//!         - [`PiconeroAmount`](utils/struct.PiconeroAmount.html)
+//!             - [`from_piconeros(piconeros: u64) -> PiconeroAmount`](utils/struct.PiconeroAmount.html#method.from_piconeros)
+//!             - [`from_xmr_str(amount: &str) -> Result<PiconeroAmount, String>`](utils/struct.PiconeroAmount.html#method.from_xmr_str)
+//!             - [`as_piconeros() -> u64`](utils/struct.PiconeroAmount.html#method.as_piconeros)
+//!             - [`checked_add(other: PiconeroAmount) -> Option<PiconeroAmount>`](utils/struct.PiconeroAmount.html#method.checked_add)
+//!             - [`checked_div(divisor: u64) -> Option<PiconeroAmount>`](utils/struct.PiconeroAmount.html#method.checked_div)
+//!             - [`checked_mul(factor: u64) -> Option<PiconeroAmount>`](utils/struct.PiconeroAmount.html#method.checked_mul)
+//!             - [`checked_sub(other: PiconeroAmount) -> Option<PiconeroAmount>`](utils/struct.PiconeroAmount.html#method.checked_sub)
+//!             - [`checked_sum<I: IntoIterator<Item = PiconeroAmount>>(amounts: I) -> Option<PiconeroAmount>`](utils/struct.PiconeroAmount.html#method.checked_sum)
+//!             - [`to_xmr_string() -> String`](utils/struct.PiconeroAmount.html#method.to_xmr_string)
+//!     - Destination policies
+//!         - [`DestinationPolicy`](utils/struct.DestinationPolicy.html)
+//!             - [`new() -> DestinationPolicy`](utils/struct.DestinationPolicy.html#method.new)
+//!             - [`check(destinations: &[(String, u64)]) -> Vec<PolicyFinding>`](utils/struct.DestinationPolicy.html#method.check)
+//!             - [`enforce(destinations: &[(String, u64)]) -> Result<Vec<PolicyFinding>, String>`](utils/struct.DestinationPolicy.html#method.enforce)
+//!             - [`forbid(address_type: AddressType, message: &str) -> DestinationPolicy`](utils/struct.DestinationPolicy.html#method.forbid)
+//!             - [`warn_on(address_type: AddressType, message: &str) -> DestinationPolicy`](utils/struct.DestinationPolicy.html#method.warn_on)
+//!             - [`with_max_amount(address_type: AddressType, amount: u64) -> DestinationPolicy`](utils/struct.DestinationPolicy.html#method.with_max_amount)
+//!             - [`with_min_amount(address_type: AddressType, amount: u64) -> DestinationPolicy`](utils/struct.DestinationPolicy.html#method.with_min_amount)
+//!             - [`with_network(network: Network) -> DestinationPolicy`](utils/struct.DestinationPolicy.html#method.with_network)
+//!             - [`with_rule(rule: R) -> DestinationPolicy`](utils/struct.DestinationPolicy.html#method.with_rule)
+//!         - [`DestinationRule`](utils/trait.DestinationRule.html)
+//!         - [`PolicyFinding`](utils/struct.PolicyFinding.html)
+//!         - [`Severity`](utils/enum.Severity.html)
+//!     - Diagnostics
+//!         - [`Discrepancy`](utils/struct.Discrepancy.html)
+//!         - [`DiscrepancyKind`](utils/enum.DiscrepancyKind.html)
+//!         - [`DiscrepancyLog`](utils/struct.DiscrepancyLog.html)
+//!             - [`new(capacity: usize) -> DiscrepancyLog`](utils/struct.DiscrepancyLog.html#method.new)
+//!             - [`drain() -> Vec<Discrepancy>`](utils/struct.DiscrepancyLog.html#method.drain)
+//!             - [`dropped() -> u64`](utils/struct.DiscrepancyLog.html#method.dropped)
+//!             - [`record(discrepancy: Discrepancy)`](utils/struct.DiscrepancyLog.html#method.record)
+//!             - [`records() -> Vec<Discrepancy>`](utils/struct.DiscrepancyLog.html#method.records)
+//!     - Errors
+//!         - [`Context`](utils/trait.Context.html)
+//!             - [`context(context: impl Into<String>) -> Result<T, Error>`](utils/trait.Context.html#tymethod.context)
+//!             - [`with_context(context: F) -> Result<T, Error>`](utils/trait.Context.html#tymethod.with_context)
+//!         - [`Error`](utils/struct.Error.html)
+//!             - [`code() -> u32`](utils/struct.Error.html#method.code)
+//!             - [`context() -> impl Iterator<Item = &str>`](utils/struct.Error.html#method.context)
+//!             - [`kind() -> &ErrorKind`](utils/struct.Error.html#method.kind)
+//!             - [`root_cause() -> &dyn std::error::Error`](utils/struct.Error.html#method.root_cause)
+//!             - [`with_context(context: impl Into<String>) -> Error`](utils/struct.Error.html#method.with_context)
+//!         - [`ErrorKind`](utils/enum.ErrorKind.html)
+//!         - [`KeyError`](utils/enum.KeyError.html)
+//!         - [`ParseError`](utils/enum.ParseError.html)
+//!         - [`RpcError`](utils/enum.RpcError.html)
+//!             - [`from_json_rpc(response: &serde_json::Value) -> Option<RpcError>`](utils/enum.RpcError.html#method.from_json_rpc)
+//!         - [`WalletError`](utils/enum.WalletError.html)
+//!     - OpenAlias
+//!         - [`looks_like_openalias(destination: &str) -> bool`](utils/fn.looks_like_openalias.html)
+//!         - [`OpenAliasRecord`](utils/struct.OpenAliasRecord.html)
+//!         - [`OpenAliasResolver`](utils/trait.OpenAliasResolver.html)
+//!         - [`parse_openalias_record(txt: &str) -> Option<OpenAliasRecord>`](utils/fn.parse_openalias_record.html)
+//!         - [`resolve_destination(destination: &str, resolver: &R, confirm: C) -> Result<String, String>`](utils/fn.resolve_destination.html)
+//!         - [`resolve_openalias(destination: &str, resolver: &R) -> Result<ResolvedDestination, String>`](utils/fn.resolve_openalias.html)
+//!         - [`ResolvedDestination`](utils/struct.ResolvedDestination.html)
+//!     - OpenAlias lookups (`openalias` feature)
+//!         - [`DnsResolver`](utils/struct.DnsResolver.html)
+//!             - [`new(server: SocketAddr) -> DnsResolver`](utils/struct.DnsResolver.html#method.new)
+//!             - [`system() -> Result<DnsResolver, String>`](utils/struct.DnsResolver.html#method.system)
+//!             - [`with_timeout(timeout: Duration) -> DnsResolver`](utils/struct.DnsResolver.html#method.with_timeout)
+//!     - Payment IDs
+//!         - [`derive_payment_id(merchant_secret: &[u8], customer_id: &str) -> [u8; 8]`](utils/fn.derive_payment_id.html)
+//!         - [`make_integrated_address(address: &str, payment_id: [u8; 8]) -> Result<String, String>`](utils/fn.make_integrated_address.html)
+//!         - [`PaymentIdRegistry`](utils/struct.PaymentIdRegistry.html)
+//!             - [`new(merchant_secret: &[u8]) -> PaymentIdRegistry`](utils/struct.PaymentIdRegistry.html#method.new)
+//!             - [`integrated_address(address: &str, customer_id: &str) -> Result<String, String>`](utils/struct.PaymentIdRegistry.html#method.integrated_address)
+//!             - [`lookup(payment_id: &[u8; 8]) -> Option<&str>`](utils/struct.PaymentIdRegistry.html#method.lookup)
+//!             - [`register(customer_id: &str) -> Result<[u8; 8], String>`](utils/struct.PaymentIdRegistry.html#method.register)
+//!     - Payment requests
+//!         - [`make_uri(address: &str, amount: Option<u64>, description: Option<&str>, recipient_name: Option<&str>) -> Result<String, String>`](utils/fn.make_uri.html)
+//!         - [`parse_uri(uri: &str) -> Result<PaymentRequest, String>`](utils/fn.parse_uri.html)
+//!         - [`PaymentRequest`](utils/struct.PaymentRequest.html)
+//!     - Serialization
+//!         - [`read_varint(data: &[u8], pos: &mut usize) -> Result<u64, String>`](utils/fn.read_varint.html)
+//!         - [`write_varint(value: u64, out: &mut Vec<u8>)`](utils/fn.write_varint.html)
+//! - Wallet
+//!     - Auto-lock
+//!         - [`AutoLockKeyStore`](wallet/struct.AutoLockKeyStore.html)
+//!             - [`new<P: PassphrasePrompt>(keys_file: Vec<u8>, idle_timeout: Duration, prompt: P) -> AutoLockKeyStore`](wallet/struct.AutoLockKeyStore.html#method.new)
+//!             - [`is_locked() -> bool`](wallet/struct.AutoLockKeyStore.html#method.is_locked)
+//!             - [`lock()`](wallet/struct.AutoLockKeyStore.html#method.lock)
+//!             - [`lock_if_idle() -> bool`](wallet/struct.AutoLockKeyStore.html#method.lock_if_idle)
+//!             - [`start_timer(check_interval: Duration) -> MonitorHandle`](wallet/struct.AutoLockKeyStore.html#method.start_timer)
+//!             - [`with_kdf_rounds(kdf_rounds: u64) -> AutoLockKeyStore`](wallet/struct.AutoLockKeyStore.html#method.with_kdf_rounds)
+//!         - [`PassphrasePrompt`](wallet/trait.PassphrasePrompt.html)
+//!     - Building transactions
+//!         - [`DecoySource`](wallet/trait.DecoySource.html)
+//!         - [`GammaDecoys`](wallet/struct.GammaDecoys.html)
+//!         - [`PreviewInput`](wallet/struct.PreviewInput.html)
+//!         - [`SignedTx`](wallet/struct.SignedTx.html)
+//!             - [`to_blob() -> Vec<u8>`](wallet/struct.SignedTx.html#method.to_blob)
+//!             - [`to_hex() -> String`](wallet/struct.SignedTx.html#method.to_hex)
+//!         - [`TxBuilder`](wallet/struct.TxBuilder.html)
+//!             - [`new(outputs: Vec<SnapshotOutput>) -> TxBuilder`](wallet/struct.TxBuilder.html#method.new)
+//!             - [`approve(preview: &TxPreview, history: &[SnapshotTransfer], now: u64) -> Result<(), SpendingLimitError>`](wallet/struct.TxBuilder.html#method.approve)
+//!             - [`dry_run(node: DaemonNode) -> Result<TxPreview, String>`](wallet/struct.TxBuilder.html#method.dry_run)
+//!             - [`sign(preview: &TxPreview, signer: &Signer) -> Result<SignedTx, String>`](wallet/struct.TxBuilder.html#method.sign)
+//!             - [`sweep_all(destination: &str, node: DaemonNode) -> Result<Vec<TxPreview>, String>`](wallet/struct.TxBuilder.html#method.sweep_all)
+//!             - [`sweep_single(key_image: &KeyImage, destination: &str, node: DaemonNode) -> Result<TxPreview, String>`](wallet/struct.TxBuilder.html#method.sweep_single)
+//!             - [`sweep_split(destinations: &[(String, u32)], node: DaemonNode) -> Result<Vec<TxPreview>, String>`](wallet/struct.TxBuilder.html#method.sweep_split)
+//!             - [`sweep_unmixable(destination: &str, node: DaemonNode) -> Result<Vec<TxPreview>, String>`](wallet/struct.TxBuilder.html#method.sweep_unmixable)
+//!             - [`with_account(account: u32) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_account)
+//!             - [`with_change_policy(change_policy: ChangePolicy) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_change_policy)
+//!             - [`with_coin_selector<C: CoinSelector>(selector: C) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_coin_selector)
+//!             - [`with_decoy_source<D: DecoySource>(decoys: D) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_decoy_source)
+//!             - [`with_destination(address: &str, amount: u64) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_destination)
+//!             - [`with_key_images(key_images: &[(OneTimeKey, KeyImage)]) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_key_images)
+//!             - [`with_openalias_resolver<R: OpenAliasResolver, C: Fn(&ResolvedDestination) -> bool>(resolver: R, confirm: C) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_openalias_resolver)
+//!             - [`with_pinned_output(tx_hash: TxHash, index: u64) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_pinned_output)
+//!             - [`with_policy(policy: DestinationPolicy) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_policy)
+//!             - [`with_priority(priority: FeePriority) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_priority)
+//!             - [`with_ring_size(ring_size: usize) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_ring_size)
+//!             - [`with_spending_history(history: &[SnapshotTransfer], now: u64) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_spending_history)
+//!             - [`with_spending_policy(spending_policy: SpendingPolicy) -> TxBuilder`](wallet/struct.TxBuilder.html#method.with_spending_policy)
+//!         - [`TxPreview`](wallet/struct.TxPreview.html)
+//!         - [`UniformDecoys`](wallet/struct.UniformDecoys.html)
+//!         - [`DEFAULT_RING_SIZE`](wallet/constant.DEFAULT_RING_SIZE.html)
+//!         - [`MAX_TX_OUTPUTS`](wallet/constant.MAX_TX_OUTPUTS.html)
+//!         - [`MAX_TX_WEIGHT`](wallet/constant.MAX_TX_WEIGHT.html)
+//!     - Change
+//!         - [`ChangePolicy`](wallet/enum.ChangePolicy.html)
+//!             - [`change_subaddress(inputs: &[(u32, u32)]) -> Result<(u32, u32), String>`](wallet/enum.ChangePolicy.html#method.change_subaddress)
+//!     - Coin selection
+//!         - [`AvoidDust`](wallet/struct.AvoidDust.html)
+//!         - [`CoinSelector`](wallet/trait.CoinSelector.html)
+//!         - [`LargestFirst`](wallet/struct.LargestFirst.html)
+//!         - [`RandomCoins`](wallet/struct.RandomCoins.html)
+//!     - Cold signing
+//!         - [`export_outputs(outputs: &[SnapshotOutput], offset: usize, view_pair: &ViewPair, node: DaemonNode) -> Result<Vec<u8>, String>`](wallet/fn.export_outputs.html)
+//!         - [`export_unsigned_txset(previews: &[TxPreview], view_pair: &ViewPair) -> Result<Vec<u8>, String>`](wallet/fn.export_unsigned_txset.html)
+//!         - [`import_outputs(data: &[u8], view_pair: &ViewPair) -> Result<(usize, Vec<SnapshotOutput>), String>`](wallet/fn.import_outputs.html)
+//!         - [`import_signed_txset(data: &[u8], private_view_key: &PrivateKey) -> Result<SignedTxSet, String>`](wallet/fn.import_signed_txset.html)
+//!         - [`SignedTxSet`](wallet/struct.SignedTxSet.html)
+//!             - [`from_bytes(data: &[u8], private_view_key: &PrivateKey) -> Result<SignedTxSet, String>`](wallet/struct.SignedTxSet.html#method.from_bytes)
+//!             - [`to_bytes(private_view_key: &PrivateKey) -> Result<Vec<u8>, String>`](wallet/struct.SignedTxSet.html#method.to_bytes)
+//!         - [`UnsignedTxSet`](wallet/struct.UnsignedTxSet.html)
+//!             - [`new(txs: Vec<TxPreview>, view_pair: &ViewPair) -> Result<UnsignedTxSet, String>`](wallet/struct.UnsignedTxSet.html#method.new)
+//!             - [`from_bytes(data: &[u8], view_pair: &ViewPair) -> Result<UnsignedTxSet, String>`](wallet/struct.UnsignedTxSet.html#method.from_bytes)
+//!             - [`sign(signer: &Signer) -> Result<SignedTxSet, String>`](wallet/struct.UnsignedTxSet.html#method.sign)
+//!             - [`to_bytes(private_view_key: &PrivateKey) -> Result<Vec<u8>, String>`](wallet/struct.UnsignedTxSet.html#method.to_bytes)
+//!         - [`OUTPUT_EXPORT_PREFIX`](wallet/constant.OUTPUT_EXPORT_PREFIX.html)
+//!         - [`SIGNED_TX_PREFIX`](wallet/constant.SIGNED_TX_PREFIX.html)
+//!         - [`UNSIGNED_TX_PREFIX`](wallet/constant.UNSIGNED_TX_PREFIX.html)
+//!     - Fixture wallets (`fixtures` feature)
+//!         - [`FixtureBlock`](wallet/struct.FixtureBlock.html)
+//!         - [`FixtureConfig`](wallet/struct.FixtureConfig.html)
+//!         - [`FixtureWallet`](wallet/struct.FixtureWallet.html)
+//!             - [`generate(config: &FixtureConfig) -> FixtureWallet`](wallet/struct.FixtureWallet.html#method.generate)
+//!             - [`chain_height() -> u64`](wallet/struct.FixtureWallet.html#method.chain_height)
+//!             - [`subaddress_table() -> SubaddressTable`](wallet/struct.FixtureWallet.html#method.subaddress_table)
+//!             - [`view_pair() -> ViewPair`](wallet/struct.FixtureWallet.html#method.view_pair)
+//!     - Key image import
+//!         - [`import_key_images(data: &[u8], outputs: &[SnapshotOutput], view_pair: &ViewPair) -> Result<Vec<(OneTimeKey, KeyImage)>, String>`](wallet/fn.import_key_images.html)
+//!         - [`KeyImageImportError`](wallet/enum.KeyImageImportError.html)
+//!         - [`KeyImageImportSession`](wallet/struct.KeyImageImportSession.html)
+//!             - [`new() -> KeyImageImportSession`](wallet/struct.KeyImageImportSession.html#method.new)
+//!             - [`import(response: &KeyImageResponse) -> Result<Vec<(OneTimeKey, KeyImage)>, KeyImageImportError>`](wallet/struct.KeyImageImportSession.html#method.import)
+//!             - [`pending() -> Vec<u64>`](wallet/struct.KeyImageImportSession.html#method.pending)
+//!             - [`request(outputs: &[SnapshotOutput]) -> KeyImageRequest`](wallet/struct.KeyImageImportSession.html#method.request)
+//!             - [`session_id() -> [u8; 16]`](wallet/struct.KeyImageImportSession.html#method.session_id)
+//!         - [`KeyImageRequest`](wallet/struct.KeyImageRequest.html)
+//!             - [`from_json(request: &str) -> Result<KeyImageRequest, String>`](wallet/struct.KeyImageRequest.html#method.from_json)
+//!             - [`respond(private_view_key: &PrivateKey, private_spend_key: &PrivateKey) -> Result<KeyImageResponse, String>`](wallet/struct.KeyImageRequest.html#method.respond)
+//!             - [`to_json() -> String`](wallet/struct.KeyImageRequest.html#method.to_json)
+//!         - [`KeyImageResponse`](wallet/struct.KeyImageResponse.html)
+//!             - [`from_json(response: &str) -> Result<KeyImageResponse, String>`](wallet/struct.KeyImageResponse.html#method.from_json)
+//!             - [`to_json() -> String`](wallet/struct.KeyImageResponse.html#method.to_json)
+//!         - [`KEY_IMAGE_EXPORT_PREFIX`](wallet/constant.KEY_IMAGE_EXPORT_PREFIX.html)
+//!     - Keys files
+//!         - [`KeysFile`](wallet/struct.KeysFile.html)
+//!             - [`new(network: Network, private_spend_key: PrivateKey, creation_timestamp: u64) -> KeysFile`](wallet/struct.KeysFile.html#method.new)
+//!             - [`address() -> String`](wallet/struct.KeysFile.html#method.address)
+//!             - [`create<P: AsRef<Path>>(path: P, password: &[u8]) -> Result<(), String>`](wallet/struct.KeysFile.html#method.create)
+//!             - [`from_bytes(data: &[u8], password: &[u8], kdf_rounds: u64) -> Result<KeysFile, String>`](wallet/struct.KeysFile.html#method.from_bytes)
+//!             - [`from_file<P: AsRef<Path>>(path: P, password: &[u8], kdf_rounds: u64) -> Result<KeysFile, String>`](wallet/struct.KeysFile.html#method.from_file)
+//!             - [`open<P: AsRef<Path>>(path: P, password: &[u8]) -> Result<KeysFile, String>`](wallet/struct.KeysFile.html#method.open)
+//!             - [`to_bytes(password: &[u8], kdf_rounds: u64) -> Result<Vec<u8>, String>`](wallet/struct.KeysFile.html#method.to_bytes)
+//!             - [`to_file<P: AsRef<Path>>(path: P, password: &[u8], kdf_rounds: u64) -> Result<(), String>`](wallet/struct.KeysFile.html#method.to_file)
+//!             - [`view_pair() -> ViewPair`](wallet/struct.KeysFile.html#method.view_pair)
+//!             - [`watch_only(network: Network, public_spend_key: PublicKey, private_view_key: PrivateKey, creation_timestamp: u64) -> KeysFile`](wallet/struct.KeysFile.html#method.watch_only)
+//!             - [`with_refresh_height(refresh_height: u64) -> KeysFile`](wallet/struct.KeysFile.html#method.with_refresh_height)
+//!     - Multisig
+//!         - [`MultisigAccount`](wallet/struct.MultisigAccount.html)
+//!             - [`new(private_spend_key: &PrivateKey, private_view_key: &PrivateKey, threshold: u32, total: u32) -> Result<MultisigAccount, String>`](wallet/struct.MultisigAccount.html#method.new)
+//!             - [`address(network: Network) -> Result<String, String>`](wallet/struct.MultisigAccount.html#method.address)
+//!             - [`clsag_context(output: &OwnedOutput, message: &[u8; 32], ring: &[(OneTimeKey, Commitment)], key_image: &KeyImage, mask: &Scalar, pseudo_mask: &Scalar) -> Result<MultisigClsagContext, String>`](wallet/struct.MultisigAccount.html#method.clsag_context)
+//!             - [`combine_key_image(output: &OwnedOutput, partials: &[MultisigPartialKeyImage]) -> Result<KeyImage, String>`](wallet/struct.MultisigAccount.html#method.combine_key_image)
+//!             - [`commit_nonces(output_key: &OneTimeKey) -> Result<MultisigNonces, String>`](wallet/struct.MultisigAccount.html#method.commit_nonces)
+//!             - [`is_ready() -> bool`](wallet/struct.MultisigAccount.html#method.is_ready)
+//!             - [`kex_rounds_complete() -> u32`](wallet/struct.MultisigAccount.html#method.kex_rounds_complete)
+//!             - [`kex_rounds_required() -> u32`](wallet/struct.MultisigAccount.html#method.kex_rounds_required)
+//!             - [`multisig_seed() -> Result<Zeroizing<String>, String>`](wallet/struct.MultisigAccount.html#method.multisig_seed)
+//!             - [`next_kex_message() -> &str`](wallet/struct.MultisigAccount.html#method.next_kex_message)
+//!             - [`partial_key_image(output_key: &OneTimeKey) -> Result<MultisigPartialKeyImage, String>`](wallet/struct.MultisigAccount.html#method.partial_key_image)
+//!             - [`partial_sign(context: &MultisigClsagContext, nonces: MultisigNonces, commitments: &[MultisigNonceCommitment]) -> Result<MultisigPartialSignature, String>`](wallet/struct.MultisigAccount.html#method.partial_sign)
+//!             - [`signer_key() -> PublicKey`](wallet/struct.MultisigAccount.html#method.signer_key)
+//!             - [`signers() -> &[PublicKey]`](wallet/struct.MultisigAccount.html#method.signers)
+//!             - [`threshold() -> u32`](wallet/struct.MultisigAccount.html#method.threshold)
+//!             - [`update(messages: &[String]) -> Result<(), String>`](wallet/struct.MultisigAccount.html#method.update)
+//!             - [`view_pair() -> Result<ViewPair, String>`](wallet/struct.MultisigAccount.html#method.view_pair)
+//!         - [`MultisigClsagContext`](wallet/struct.MultisigClsagContext.html)
+//!             - [`combine(commitments: &[MultisigNonceCommitment], partials: &[MultisigPartialSignature]) -> Result<Clsag, String>`](wallet/struct.MultisigClsagContext.html#method.combine)
+//!         - [`MultisigNonceCommitment`](wallet/struct.MultisigNonceCommitment.html)
+//!         - [`MultisigNonces`](wallet/struct.MultisigNonces.html)
+//!             - [`commitment() -> MultisigNonceCommitment`](wallet/struct.MultisigNonces.html#method.commitment)
+//!         - [`MultisigPartialKeyImage`](wallet/struct.MultisigPartialKeyImage.html)
+//!         - [`MultisigPartialSignature`](wallet/struct.MultisigPartialSignature.html)
+//!     - Pre-RingCT outputs
+//!         - [`WalletSnapshot`](wallet/struct.WalletSnapshot.html)
+//!             - [`pre_rct_outputs(account: Option<u32>) -> Vec<&SnapshotOutput>`](wallet/struct.WalletSnapshot.html#method.pre_rct_outputs)
+//!             - [`unmixable_outputs(account: Option<u32>, histogram: &[OutputHistogramEntry], ring_size: usize) -> Vec<&SnapshotOutput>`](wallet/struct.WalletSnapshot.html#method.unmixable_outputs)
+//!     - Proofs
+//!         - [`check_reserve_proof(address: &str, message: &[u8], proof: &str, node: DaemonNode) -> Result<ReserveProofCheck, String>`](wallet/fn.check_reserve_proof.html)
+//!         - [`check_spend_proof(tx_hash: &TxHash, message: &[u8], proof: &str, node: DaemonNode) -> Result<bool, String>`](wallet/fn.check_spend_proof.html)
+//!         - [`get_tx_rings(tx: &Transaction, node: DaemonNode) -> Result<Vec<Vec<OneTimeKey>>, String>`](wallet/fn.get_tx_rings.html)
+//!         - [`verify_message(address: &str, message: &[u8], signature: &str) -> Result<bool, String>`](wallet/fn.verify_message.html)
+//!         - [`ReserveProofCheck`](wallet/struct.ReserveProofCheck.html)
+//!     - Receipts
+//!         - [`PaymentReceipt`](wallet/struct.PaymentReceipt.html)
+//!             - [`from_transfer(transfer: &SnapshotTransfer, destination: &str, amount: u64) -> Result<PaymentReceipt, String>`](wallet/struct.PaymentReceipt.html#method.from_transfer)
+//!             - [`from_json(receipt: &str) -> Result<PaymentReceipt, String>`](wallet/struct.PaymentReceipt.html#method.from_json)
+//!             - [`to_json() -> String`](wallet/struct.PaymentReceipt.html#method.to_json)
+//!             - [`verify(tx: &Transaction) -> Result<u64, String>`](wallet/struct.PaymentReceipt.html#method.verify)
+//!             - [`with_description(description: &str) -> PaymentReceipt`](wallet/struct.PaymentReceipt.html#method.with_description)
+//!             - [`with_proof(proof: &str, message: &str) -> PaymentReceipt`](wallet/struct.PaymentReceipt.html#method.with_proof)
+//!             - [`with_tx_proof(tx: &Transaction, message: &str, keys: &TxProofKeys) -> Result<PaymentReceipt, String>`](wallet/struct.PaymentReceipt.html#method.with_tx_proof)
+//!     - Signing with a keystore
+//!         - [`KeyStore`](wallet/trait.KeyStore.html)
+//!         - [`MemoryKeyStore`](wallet/struct.MemoryKeyStore.html)
+//!             - [`new(private_spend_key: PrivateKey) -> MemoryKeyStore`](wallet/struct.MemoryKeyStore.html#method.new)
+//!         - [`Signer`](wallet/struct.Signer.html)
+//!             - [`new<K: KeyStore>(keystore: K, private_view_key: PrivateKey) -> Result<Signer, String>`](wallet/struct.Signer.html#method.new)
+//!             - [`export_key_images(outputs: &[SnapshotOutput], offset: usize) -> Result<Vec<u8>, String>`](wallet/struct.Signer.html#method.export_key_images)
+//!             - [`get_reserve_proof(outputs: &[SnapshotOutput], message: &[u8]) -> Result<String, String>`](wallet/struct.Signer.html#method.get_reserve_proof)
+//!             - [`get_spend_proof(tx_hash: &TxHash, tx: &Transaction, rings: &[Vec<OneTimeKey>], outputs: &[SnapshotOutput], message: &[u8]) -> Result<String, String>`](wallet/struct.Signer.html#method.get_spend_proof)
+//!             - [`key_image(output: &OwnedOutput) -> Result<KeyImage, String>`](wallet/struct.Signer.html#method.key_image)
+//!             - [`key_images(outputs: &[OwnedOutput]) -> Result<Vec<KeyImage>, String>`](wallet/struct.Signer.html#method.key_images)
+//!             - [`prove_output_ownership(output: &OwnedOutput, message: &[u8]) -> Result<OutputOwnershipProof, String>`](wallet/struct.Signer.html#method.prove_output_ownership)
+//!             - [`respond_key_images(request: &KeyImageRequest) -> Result<KeyImageResponse, String>`](wallet/struct.Signer.html#method.respond_key_images)
+//!             - [`sign_clsag(output: &OwnedOutput, message: &[u8; 32], ring: &[(OneTimeKey, Commitment)], real_index: usize, mask: &Scalar, pseudo_mask: &Scalar) -> Result<Clsag, String>`](wallet/struct.Signer.html#method.sign_clsag)
+//!             - [`sign_message(message: &[u8], account: u32, index: u32) -> Result<String, String>`](wallet/struct.Signer.html#method.sign_message)
+//!             - [`view_pair() -> &ViewPair`](wallet/struct.Signer.html#method.view_pair)
+//!     - Snapshots
+//!         - [`SnapshotOutput`](wallet/struct.SnapshotOutput.html)
+//!             - [`from_owned(output: &OwnedOutput, tx_hash: TxHash, height: u64, subaddress: (u32, u32), unlock_time: u64) -> SnapshotOutput`](wallet/struct.SnapshotOutput.html#method.from_owned)
+//!         - [`SnapshotTransfer`](wallet/struct.SnapshotTransfer.html)
+//!         - [`SubaddressLabel`](wallet/struct.SubaddressLabel.html)
+//!         - [`WalletSnapshot`](wallet/struct.WalletSnapshot.html)
+//!             - [`new(network: Network, primary_address: &str, synced_height: u64) -> WalletSnapshot`](wallet/struct.WalletSnapshot.html#method.new)
+//!             - [`balance(account: Option<u32>) -> u64`](wallet/struct.WalletSnapshot.html#method.balance)
+//!             - [`from_json(snapshot: &str) -> Result<WalletSnapshot, String>`](wallet/struct.WalletSnapshot.html#method.from_json)
+//!             - [`label(account: u32, index: u32) -> Option<&str>`](wallet/struct.WalletSnapshot.html#method.label)
+//!             - [`to_json() -> String`](wallet/struct.WalletSnapshot.html#method.to_json)
+//!         - [`SNAPSHOT_VERSION`](wallet/constant.SNAPSHOT_VERSION.html)
+//!     - Spending limits
+//!         - [`ApprovalHook`](wallet/trait.ApprovalHook.html)
+//!         - [`SpendingLimitError`](wallet/enum.SpendingLimitError.html)
+//!         - [`SpendingPolicy`](wallet/struct.SpendingPolicy.html)
+//!             - [`new() -> SpendingPolicy`](wallet/struct.SpendingPolicy.html#method.new)
+//!             - [`check(preview: &TxPreview, history: &[SnapshotTransfer], now: u64) -> Result<(), SpendingLimitError>`](wallet/struct.SpendingPolicy.html#method.check)
+//!             - [`with_approval<H: ApprovalHook>(hook: H) -> SpendingPolicy`](wallet/struct.SpendingPolicy.html#method.with_approval)
+//!             - [`with_daily_limit(amount: u64) -> SpendingPolicy`](wallet/struct.SpendingPolicy.html#method.with_daily_limit)
+//!             - [`with_max_per_tx(amount: u64) -> SpendingPolicy`](wallet/struct.SpendingPolicy.html#method.with_max_per_tx)
+//!     - Unlock times
+//!         - [`SnapshotOutput`](wallet/struct.SnapshotOutput.html)
+//!             - [`is_spendable_at(chain_height: u64, timestamp: u64) -> bool`](wallet/struct.SnapshotOutput.html#method.is_spendable_at)
+//!             - [`unlock_height(network: Network) -> u64`](wallet/struct.SnapshotOutput.html#method.unlock_height)
+//!         - [`WalletSnapshot`](wallet/struct.WalletSnapshot.html)
+//!             - [`locked_at(account: Option<u32>, chain_height: u64, timestamp: u64) -> Vec<&SnapshotOutput>`](wallet/struct.WalletSnapshot.html#method.locked_at)
+//!             - [`spendable_at(account: Option<u32>, chain_height: u64, timestamp: u64) -> Vec<&SnapshotOutput>`](wallet/struct.WalletSnapshot.html#method.spendable_at)
+//!             - [`unlock_schedule(account: Option<u32>) -> Vec<(u64, u64)>`](wallet/struct.WalletSnapshot.html#method.unlock_schedule)
+//!             - [`unlocked_balance_at(account: Option<u32>, chain_height: u64, timestamp: u64) -> u64`](wallet/struct.WalletSnapshot.html#method.unlocked_balance_at)
+//!         - [`SPENDABLE_AGE`](wallet/constant.SPENDABLE_AGE.html)
+//!     - Wallets
+//!         - [`Wallet`](wallet/struct.Wallet.html)
+//!             - [`from_keys(network: Network, private_spend_key: PrivateKey) -> Wallet`](wallet/struct.Wallet.html#method.from_keys)
+//!             - [`from_keys_file(keys_file: &KeysFile) -> Wallet`](wallet/struct.Wallet.html#method.from_keys_file)
+//!             - [`from_mnemonic(network: Network, mnemonic: &[String]) -> Result<Wallet, String>`](wallet/struct.Wallet.html#method.from_mnemonic)
+//!             - [`view_only(network: Network, public_spend_key: PublicKey, private_view_key: PrivateKey) -> Wallet`](wallet/struct.Wallet.html#method.view_only)
+//!             - [`account_balance(account: u32) -> u64`](wallet/struct.Wallet.html#method.account_balance)
+//!             - [`address() -> &str`](wallet/struct.Wallet.html#method.address)
+//!             - [`balance() -> u64`](wallet/struct.Wallet.html#method.balance)
+//!             - [`clear_pending_spends()`](wallet/struct.Wallet.html#method.clear_pending_spends)
+//!             - [`export_key_images() -> Result<Vec<u8>, String>`](wallet/struct.Wallet.html#method.export_key_images)
+//!             - [`export_outputs(node: DaemonNode) -> Result<Vec<u8>, String>`](wallet/struct.Wallet.html#method.export_outputs)
+//!             - [`get_reserve_proof(account_minreserve: Option<(u32, u64)>, message: &[u8]) -> Result<String, String>`](wallet/struct.Wallet.html#method.get_reserve_proof)
+//!             - [`get_spend_proof(tx_hash: &TxHash, message: &[u8], node: DaemonNode) -> Result<String, String>`](wallet/struct.Wallet.html#method.get_spend_proof)
+//!             - [`import_key_image_file(data: &[u8], node: &DaemonNode) -> Result<usize, String>`](wallet/struct.Wallet.html#method.import_key_image_file)
+//!             - [`import_key_images(key_images: &[(OneTimeKey, KeyImage)], node: &DaemonNode) -> Result<usize, String>`](wallet/struct.Wallet.html#method.import_key_images)
+//!             - [`import_outputs(data: &[u8]) -> Result<usize, String>`](wallet/struct.Wallet.html#method.import_outputs)
+//!             - [`is_view_only() -> bool`](wallet/struct.Wallet.html#method.is_view_only)
+//!             - [`key_image(output_key: &OneTimeKey) -> Option<KeyImage>`](wallet/struct.Wallet.html#method.key_image)
+//!             - [`network() -> Network`](wallet/struct.Wallet.html#method.network)
+//!             - [`outputs() -> &[SnapshotOutput]`](wallet/struct.Wallet.html#method.outputs)
+//!             - [`pending_spends() -> Vec<OneTimeKey>`](wallet/struct.Wallet.html#method.pending_spends)
+//!             - [`scan_blocks(batch: &BlocksBinResult) -> Result<SyncProgress, String>`](wallet/struct.Wallet.html#method.scan_blocks)
+//!             - [`scan_tx(tx_hash: TxHash, tx: &RawTx, height: u64) -> Vec<SnapshotOutput>`](wallet/struct.Wallet.html#method.scan_tx)
+//!             - [`send(address_or_uri: &str, amount: Option<u64>, priority: FeePriority, node: &DaemonNode) -> Result<SignedTx, String>`](wallet/struct.Wallet.html#method.send)
+//!             - [`sign_message(message: &[u8], account: u32, index: u32) -> Result<String, String>`](wallet/struct.Wallet.html#method.sign_message)
+//!             - [`snapshot() -> &WalletSnapshot`](wallet/struct.Wallet.html#method.snapshot)
+//!             - [`subaddress(account: u32, index: u32) -> String`](wallet/struct.Wallet.html#method.subaddress)
+//!             - [`subaddress_balances() -> BTreeMap<(u32, u32), u64>`](wallet/struct.Wallet.html#method.subaddress_balances)
+//!             - [`sweep_unmixable(priority: FeePriority, node: &DaemonNode) -> Result<Vec<SignedTx>, String>`](wallet/struct.Wallet.html#method.sweep_unmixable)
+//!             - [`sync<F>(node: &DaemonNode, from_height: Option<u64>, on_progress: F) -> Result<u64, String>`](wallet/struct.Wallet.html#method.sync)
+//!             - [`synced_height() -> u64`](wallet/struct.Wallet.html#method.synced_height)
+//!             - [`tx_builder() -> TxBuilder`](wallet/struct.Wallet.html#method.tx_builder)
+//!             - [`unlocked_balance() -> u64`](wallet/struct.Wallet.html#method.unlocked_balance)
+//!             - [`view_pair() -> &ViewPair`](wallet/struct.Wallet.html#method.view_pair)
+//!             - [`with_lookahead(accounts: u32, indexes: u32) -> Wallet`](wallet/struct.Wallet.html#method.with_lookahead)
+//!             - [`with_openalias_resolver<R: OpenAliasResolver, C: Fn(&ResolvedDestination) -> bool>(resolver: R, confirm: C) -> Wallet`](wallet/struct.Wallet.html#method.with_openalias_resolver)
+//!             - [`with_restore_height(restore_height: u64) -> Wallet`](wallet/struct.Wallet.html#method.with_restore_height)
+//!             - [`with_spending_policy(spending_policy: SpendingPolicy) -> Wallet`](wallet/struct.Wallet.html#method.with_spending_policy)
+//!         - [`SyncProgress`](wallet/struct.SyncProgress.html)
+//!         - [`DEFAULT_LOOKAHEAD`](wallet/constant.DEFAULT_LOOKAHEAD.html)
+//!     - Watching addresses
+//!         - [`AddressWatcher`](wallet/struct.AddressWatcher.html)
+//!             - [`new(network: Network, start_height: u64) -> AddressWatcher`](wallet/struct.AddressWatcher.html#method.new)
+//!             - [`addresses() -> &[String]`](wallet/struct.AddressWatcher.html#method.addresses)
+//!             - [`height() -> u64`](wallet/struct.AddressWatcher.html#method.height)
+//!             - [`poll(node: &DaemonNode) -> Result<Vec<WatchEvent>, String>`](wallet/struct.AddressWatcher.html#method.poll)
+//!             - [`run<F>(node: DaemonNode, interval: Duration, on_events: F) -> MonitorHandle`](wallet/struct.AddressWatcher.html#method.run)
+//!             - [`scan_blocks(batch: &BlocksBinResult) -> Result<Vec<WatchEvent>, String>`](wallet/struct.AddressWatcher.html#method.scan_blocks)
+//!             - [`scan_pool(pool: &[PoolTx]) -> Vec<WatchEvent>`](wallet/struct.AddressWatcher.html#method.scan_pool)
+//!             - [`scan_tx(tx_hash: TxHash, tx: &RawTx, height: Option<u64>) -> Vec<WatchedOutput>`](wallet/struct.AddressWatcher.html#method.scan_tx)
+//!             - [`with_address(address: &str, private_view_key: &PrivateKey) -> Result<AddressWatcher, String>`](wallet/struct.AddressWatcher.html#method.with_address)
+//!             - [`with_pool(pool: bool) -> AddressWatcher`](wallet/struct.AddressWatcher.html#method.with_pool)
+//!         - [`WatchEvent`](wallet/enum.WatchEvent.html)
+//!         - [`WatchedOutput`](wallet/struct.WatchedOutput.html)
 
 
 pub(crate) mod mnemonics {
@@ -79,7 +828,5 @@ pub mod blocks;
 pub mod keys;
 /// Utility functions like address validation
 pub mod utils;
-
-// Will be added in the future
-// pub mod wallet;
-// pub use wallet::*;
\ No newline at end of file
+/// Wallet data functions
+pub mod wallet;
\ No newline at end of file
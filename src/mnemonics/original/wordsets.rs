@@ -39,4 +39,39 @@ pub(crate) static WORDSETSORIGINAL : [WordsetOriginal; 8] = [
     PORTUGUESEORIGINAL,
     RUSSIANORIGINAL,
     // SPANISHORIGINAL, // Broken
-];
\ No newline at end of file
+];
+// Wordsets loaded at runtime, see keys::load_wordset
+// Loaded words are leaked so every wordset can be borrowed for 'static like the compiled-in ones
+static LOADEDWORDSETSORIGINAL: std::sync::RwLock<Vec<&'static WordsetOriginal>> = std::sync::RwLock::new(Vec::new());
+
+// Returns the available original-type wordsets: compiled-in ones first, replaced by the loaded wordset of the same
+// name if any, then the loaded wordsets of new languages
+pub(crate) fn original_wordsets() -> Vec<&'static WordsetOriginal> {
+    let loaded = LOADEDWORDSETSORIGINAL.read().unwrap_or_else(|e| e.into_inner());
+    let mut wordsets: Vec<&'static WordsetOriginal> = WORDSETSORIGINAL
+        .iter()
+        .map(|wordset| loaded.iter().copied().find(|x| x.name == wordset.name).unwrap_or(wordset))
+        .collect();
+    wordsets.extend(loaded.iter().copied().filter(|x| !WORDSETSORIGINAL.iter().any(|wordset| wordset.name == x.name)));
+    wordsets
+}
+
+// Registers a loaded wordset, replacing the previously loaded one of the same name
+pub(crate) fn register_original_wordset(name: &str, prefix_len: usize, words: &[&str; 1626]) {
+    let wordset: &'static WordsetOriginal = Box::leak(Box::new(WordsetOriginal {
+        name: Box::leak(name.to_string().into_boxed_str()),
+        prefix_len,
+        words: words.map(|word| &*Box::leak(word.to_string().into_boxed_str())),
+    }));
+    let mut loaded = LOADEDWORDSETSORIGINAL.write().unwrap_or_else(|e| e.into_inner());
+    loaded.retain(|x| x.name != name);
+    loaded.push(wordset);
+}
+
+// Removes a loaded wordset, returns false if no wordset of this name was loaded
+pub(crate) fn unregister_original_wordset(name: &str) -> bool {
+    let mut loaded = LOADEDWORDSETSORIGINAL.write().unwrap_or_else(|e| e.into_inner());
+    let count = loaded.len();
+    loaded.retain(|x| x.name != name);
+    loaded.len() != count
+}
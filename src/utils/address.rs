@@ -0,0 +1,264 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use crate::keys::PublicKey;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE58_FULL_BLOCK_LEN: usize = 11;
+// Encoded length of blocks of 0 to 8 bytes
+const BASE58_BLOCK_LENS: [usize; 9] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+/// Network is the Monero network an address belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Stagenet,
+}
+
+/// AddressType is the kind of a Monero address
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressType {
+    /// Primary address of a wallet
+    Standard,
+    /// Primary address with an embedded 8-byte payment ID
+    Integrated,
+    /// Subaddress of a wallet
+    Subaddress,
+}
+
+/// AddressInfo contains everything encoded in a Monero address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressInfo {
+    pub network: Network,
+    pub address_type: AddressType,
+    pub public_spend_key: PublicKey,
+    pub public_view_key: PublicKey,
+    /// Payment ID of integrated addresses
+    pub payment_id: Option<[u8; 8]>,
+}
+
+// Address prefixes of every network and address type
+const ADDRESS_PREFIXES: [(u8, Network, AddressType); 9] = [
+    (18, Network::Mainnet, AddressType::Standard),
+    (19, Network::Mainnet, AddressType::Integrated),
+    (42, Network::Mainnet, AddressType::Subaddress),
+    (53, Network::Testnet, AddressType::Standard),
+    (54, Network::Testnet, AddressType::Integrated),
+    (63, Network::Testnet, AddressType::Subaddress),
+    (24, Network::Stagenet, AddressType::Standard),
+    (25, Network::Stagenet, AddressType::Integrated),
+    (36, Network::Stagenet, AddressType::Subaddress),
+];
+
+/// Returns the address prefix of the given network and address type
+pub(crate) fn address_prefix(network: Network, address_type: AddressType) -> u8 {
+    ADDRESS_PREFIXES
+        .iter()
+        .find(|(_, n, t)| *n == network && *t == address_type)
+        .map(|(prefix, _, _)| *prefix)
+        .unwrap()
+}
+
+impl AddressInfo {
+    /// Encodes the address, integrated addresses must have a payment ID and other addresses must not
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::utils::decode_address;
+    ///
+    /// let address = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H";
+    /// assert_eq!(decode_address(address).unwrap().to_address().unwrap(), address);
+    /// ```
+    pub fn to_address(&self) -> Result<String, String> {
+        let mut data = vec![address_prefix(self.network, self.address_type)];
+        data.extend_from_slice(self.public_spend_key.as_bytes());
+        data.extend_from_slice(self.public_view_key.as_bytes());
+        match (self.address_type, self.payment_id) {
+            (AddressType::Integrated, Some(payment_id)) => data.extend_from_slice(&payment_id),
+            (AddressType::Integrated, None) => return Err("Integrated address needs a payment ID".to_string()),
+            (_, Some(_)) => return Err("Only integrated addresses have a payment ID".to_string()),
+            (_, None) => {}
+        }
+        let checksum = Keccak256::digest(&data);
+        data.extend_from_slice(&checksum[..4]);
+        base58_monero::encode(&data).map_err(|e| format!("Invalid base58 encoding: {}", e))
+    }
+}
+
+/// Decodes the given address and returns its network, type, public keys and payment ID
+///
+/// Checksum, prefix, length and public keys are all verified, an error message is returned if any of them is invalid
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{decode_address, AddressType, Network};
+///
+/// let info = decode_address("41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT").unwrap();
+/// assert_eq!(info.network, Network::Mainnet);
+/// assert_eq!(info.address_type, AddressType::Standard);
+/// assert_eq!(info.public_spend_key.to_hex(), "03970285bf0724d75e0f50bca9a9ea0e8db5091b69403dc944465f8936bde787");
+/// assert_eq!(info.public_view_key.to_hex(), "528a736a5079dc9536edb5b6fa0a5209ce820b9734fc0785024670b3d3ba4c69");
+/// assert_eq!(info.payment_id, None);
+/// ```
+pub fn decode_address(address: &str) -> Result<AddressInfo, String> {
+    decode_address_with(address, &mut Vec::with_capacity(77))
+}
+
+/// ValidationResult is the outcome of validating one address of a batch with [`validate_addresses`](fn.validate_addresses.html)
+// Most addresses of a batch are valid, boxing them would only add an allocation per address
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationResult {
+    /// Address is valid, with everything it encodes
+    Valid(AddressInfo),
+    /// Address is invalid, with the reason as returned by [`decode_address`](fn.decode_address.html)
+    Invalid(String),
+}
+
+impl ValidationResult {
+    /// Checks if the address is valid
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ValidationResult::Valid(_))
+    }
+
+    /// Returns the decoded address if it is valid
+    pub fn info(&self) -> Option<&AddressInfo> {
+        match self {
+            ValidationResult::Valid(info) => Some(info),
+            ValidationResult::Invalid(_) => None,
+        }
+    }
+
+    /// Returns why the address is invalid, if it is
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            ValidationResult::Valid(_) => None,
+            ValidationResult::Invalid(error) => Some(error),
+        }
+    }
+}
+
+/// Validates a batch of addresses, e.g. the destinations of pending withdrawals, and returns one result per address in
+/// the same order
+///
+/// Decoding reuses the same buffer for the whole batch and addresses appearing several times are only checked once,
+/// which matters as checking the public keys is most of the cost. See [`validate_addresses_parallel`](fn.validate_addresses_parallel.html)
+/// for large batches.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{validate_addresses, Network};
+///
+/// let results = validate_addresses(&[
+///     "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT",
+///     "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wU",
+/// ]);
+/// assert_eq!(results[0].info().unwrap().network, Network::Mainnet);
+/// assert!(!results[1].is_valid());
+/// ```
+pub fn validate_addresses(addresses: &[&str]) -> Vec<ValidationResult> {
+    validate_addresses_parallel(addresses, 1)
+}
+
+/// Validates a batch of addresses on `threads` threads, see [`validate_addresses`](fn.validate_addresses.html)
+pub fn validate_addresses_parallel(addresses: &[&str], threads: usize) -> Vec<ValidationResult> {
+    let mut unique: Vec<&str> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::with_capacity(addresses.len());
+    let indexes: Vec<usize> = addresses
+        .iter()
+        .map(|address| {
+            *positions.entry(address).or_insert_with(|| {
+                unique.push(address);
+                unique.len() - 1
+            })
+        })
+        .collect();
+
+    let validate_chunk = |chunk: &[&str]| -> Vec<ValidationResult> {
+        let mut buffer = Vec::with_capacity(77);
+        chunk
+            .iter()
+            .map(|address| match decode_address_with(address, &mut buffer) {
+                Ok(info) => ValidationResult::Valid(info),
+                Err(e) => ValidationResult::Invalid(e),
+            })
+            .collect()
+    };
+    let threads = threads.clamp(1, unique.len().max(1));
+    let results: Vec<ValidationResult> = match threads {
+        1 => validate_chunk(&unique),
+        _ => std::thread::scope(|scope| {
+            let workers: Vec<_> = unique.chunks(unique.len().div_ceil(threads)).map(|chunk| scope.spawn(move || validate_chunk(chunk))).collect();
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        }),
+    };
+    indexes.into_iter().map(|index| results[index].clone()).collect()
+}
+
+// Decodes an address using the given buffer for its bytes
+fn decode_address_with(address: &str, data: &mut Vec<u8>) -> Result<AddressInfo, String> {
+    decode_base58(address, data).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+    if data.len() < 5 {
+        return Err("Address is too short".to_string());
+    }
+    let (payload, checksum) = data.split_at(data.len() - 4);
+    if Keccak256::digest(payload)[..4] != *checksum {
+        return Err("Invalid address checksum".to_string());
+    }
+    let (network, address_type) = ADDRESS_PREFIXES
+        .iter()
+        .find(|(prefix, _, _)| *prefix == payload[0])
+        .map(|(_, network, address_type)| (*network, *address_type))
+        .ok_or_else(|| format!("Unknown address prefix {}", payload[0]))?;
+    let expected_len = match address_type {
+        AddressType::Integrated => 1 + 64 + 8,
+        _ => 1 + 64,
+    };
+    if payload.len() != expected_len {
+        return Err(format!("Invalid address length {}, expected {}", payload.len(), expected_len));
+    }
+    let public_spend_key = PublicKey::from_bytes(payload[1..33].try_into().unwrap())
+        .map_err(|e| format!("Invalid public spend key: {}", e))?;
+    let public_view_key = PublicKey::from_bytes(payload[33..65].try_into().unwrap())
+        .map_err(|e| format!("Invalid public view key: {}", e))?;
+    let payment_id = match address_type {
+        AddressType::Integrated => Some(payload[65..73].try_into().unwrap()),
+        _ => None,
+    };
+    Ok(AddressInfo {
+        network,
+        address_type,
+        public_spend_key,
+        public_view_key,
+        payment_id,
+    })
+}
+
+// Monero's base58: blocks of 8 bytes are encoded as 11 characters, the last block being shorter
+fn decode_base58(encoded: &str, data: &mut Vec<u8>) -> Result<(), String> {
+    data.clear();
+    let encoded = encoded.as_bytes();
+    for block in encoded.chunks(BASE58_FULL_BLOCK_LEN) {
+        let size = BASE58_BLOCK_LENS.iter().position(|len| *len == block.len()).ok_or("invalid length")?;
+        let mut value: u64 = 0;
+        for character in block {
+            let digit = BASE58_ALPHABET.iter().position(|c| c == character).ok_or_else(|| format!("invalid character {}", *character as char))?;
+            value = value.checked_mul(58).and_then(|value| value.checked_add(digit as u64)).ok_or("overflow")?;
+        }
+        if size < 8 && value >> (8 * size) != 0 {
+            return Err("overflow".to_string());
+        }
+        data.extend_from_slice(&value.to_be_bytes()[8 - size..]);
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use std::fmt;
+use std::str::FromStr;
+
+// Decimal places of XMR, monero-core's `CRYPTONOTE_DISPLAY_DECIMAL_POINT`
+const DECIMALS: usize = 12;
+
+/// PiconeroAmount is an amount of XMR counted in piconeros, the atomic unit of Monero (10^-12 XMR)
+///
+/// Amounts are always kept as integers, conversions from and to XMR strings are exact and arithmetic is checked, so
+/// no amount is ever rounded or wrapped around without the caller noticing. Display writes the shortest exact XMR
+/// amount, or exactly the given number of decimal places, truncating the others, with a precision such as `{:.4}`.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::PiconeroAmount;
+///
+/// let amount = PiconeroAmount::from_xmr_str("1.234").unwrap();
+/// assert_eq!(amount.as_piconeros(), 1234000000000);
+/// assert_eq!(amount.to_xmr_string(), "1.234");
+/// assert_eq!(format!("{:.2} XMR", amount), "1.23 XMR");
+/// assert_eq!(amount.checked_add(PiconeroAmount::from_piconeros(1)).unwrap().to_string(), "1.234000000001");
+/// assert_eq!(amount.checked_sub(PiconeroAmount::ONE_XMR.checked_mul(2).unwrap()), None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PiconeroAmount(u64);
+
+impl PiconeroAmount {
+    /// No XMR
+    pub const ZERO: PiconeroAmount = PiconeroAmount(0);
+    /// One XMR, 10^12 piconeros
+    pub const ONE_XMR: PiconeroAmount = PiconeroAmount(1_000_000_000_000);
+    /// Largest amount a transaction output can hold
+    pub const MAX: PiconeroAmount = PiconeroAmount(u64::MAX);
+
+    /// Creates an amount from a number of piconeros
+    pub const fn from_piconeros(piconeros: u64) -> PiconeroAmount {
+        PiconeroAmount(piconeros)
+    }
+
+    /// Returns the amount in piconeros
+    pub const fn as_piconeros(&self) -> u64 {
+        self.0
+    }
+
+    /// Parses a decimal amount of XMR such as `1.234`, `0.5` or `.5`
+    ///
+    /// Returns the amount if succesfull
+    /// Returns an error message if the string is not a plain decimal number, has more than 12 decimals or the amount
+    /// does not fit in 64 bits
+    pub fn from_xmr_str(amount: &str) -> Result<PiconeroAmount, String> {
+        let invalid = || format!("Invalid amount {}", amount);
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        if (whole.is_empty() && fraction.is_empty()) || fraction.len() > DECIMALS || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let whole: u64 = match whole.is_empty() {
+            true => 0,
+            false => whole.parse().map_err(|_| invalid())?,
+        };
+        let fraction: u64 = match fraction.is_empty() {
+            true => 0,
+            false => format!("{:0<width$}", fraction, width = DECIMALS).parse().map_err(|_| invalid())?,
+        };
+        whole.checked_mul(PiconeroAmount::ONE_XMR.0).and_then(|whole| whole.checked_add(fraction)).map(PiconeroAmount).ok_or_else(invalid)
+    }
+
+    /// Returns the shortest exact decimal amount of XMR, without trailing zeros
+    pub fn to_xmr_string(&self) -> String {
+        let fraction = format!("{:0width$}", self.0 % PiconeroAmount::ONE_XMR.0, width = DECIMALS);
+        let fraction = fraction.trim_end_matches('0');
+        match fraction.is_empty() {
+            true => (self.0 / PiconeroAmount::ONE_XMR.0).to_string(),
+            false => format!("{}.{}", self.0 / PiconeroAmount::ONE_XMR.0, fraction),
+        }
+    }
+
+    /// Adds two amounts, returns None on overflow
+    pub fn checked_add(self, other: PiconeroAmount) -> Option<PiconeroAmount> {
+        self.0.checked_add(other.0).map(PiconeroAmount)
+    }
+
+    /// Subtracts an amount, returns None if it is larger than this one
+    pub fn checked_sub(self, other: PiconeroAmount) -> Option<PiconeroAmount> {
+        self.0.checked_sub(other.0).map(PiconeroAmount)
+    }
+
+    /// Multiplies the amount, returns None on overflow
+    pub fn checked_mul(self, factor: u64) -> Option<PiconeroAmount> {
+        self.0.checked_mul(factor).map(PiconeroAmount)
+    }
+
+    /// Divides the amount rounding down, returns None if `divisor` is 0
+    pub fn checked_div(self, divisor: u64) -> Option<PiconeroAmount> {
+        self.0.checked_div(divisor).map(PiconeroAmount)
+    }
+
+    /// Adds up amounts, returns None on overflow
+    pub fn checked_sum<I: IntoIterator<Item = PiconeroAmount>>(amounts: I) -> Option<PiconeroAmount> {
+        amounts.into_iter().try_fold(PiconeroAmount::ZERO, PiconeroAmount::checked_add)
+    }
+}
+
+impl fmt::Display for PiconeroAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(decimals) = f.precision() else {
+            return f.pad(&self.to_xmr_string());
+        };
+        let whole = self.0 / PiconeroAmount::ONE_XMR.0;
+        let fraction = format!("{:0width$}", self.0 % PiconeroAmount::ONE_XMR.0, width = DECIMALS);
+        let amount = match decimals {
+            0 => whole.to_string(),
+            _ => format!("{}.{:0<width$}", whole, &fraction[..decimals.min(DECIMALS)], width = decimals),
+        };
+        // Padding only, the precision was used up for the decimal places
+        f.pad_integral(true, "", &amount)
+    }
+}
+
+impl FromStr for PiconeroAmount {
+    type Err = String;
+
+    fn from_str(amount: &str) -> Result<PiconeroAmount, String> {
+        PiconeroAmount::from_xmr_str(amount)
+    }
+}
+
+impl From<u64> for PiconeroAmount {
+    fn from(piconeros: u64) -> PiconeroAmount {
+        PiconeroAmount(piconeros)
+    }
+}
+
+impl From<PiconeroAmount> for u64 {
+    fn from(amount: PiconeroAmount) -> u64 {
+        amount.0
+    }
+}
@@ -0,0 +1,158 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use crate::blocks::TxHash;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Kind of data that failed validation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DiscrepancyKind {
+    /// One-time output key that is not a valid curve point
+    InvalidOutputKey,
+    /// Transaction public key of tx_extra that is not a valid curve point
+    InvalidTxPublicKey,
+    /// View tag that is not a single byte
+    MalformedViewTag,
+    /// Encrypted amount missing, of the wrong size or decoding to more than 64 bits
+    MalformedEncryptedAmount,
+    /// Amount commitment missing or not matching the decoded amount and mask
+    CommitmentMismatch,
+}
+
+/// Discrepancy is a diagnostic record of data that failed validation, with enough context to report it upstream
+///
+/// Discrepancies are never fatal: the offending data is skipped and a record is added to the
+/// [`DiscrepancyLog`](struct.DiscrepancyLog.html) given to the operation, if any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub kind: DiscrepancyKind,
+    /// Transaction holding the data, if known
+    pub tx_hash: Option<TxHash>,
+    /// Height of the block holding the transaction, `None` for the pool or if unknown
+    pub height: Option<u64>,
+    /// Index of the output holding the data, if the data belongs to an output
+    pub output_index: Option<u64>,
+    /// Human readable description, with the offending value when it is public
+    pub detail: String,
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.kind)?;
+        if let Some(tx_hash) = &self.tx_hash {
+            write!(f, " in tx {}", tx_hash)?;
+        }
+        if let Some(output_index) = self.output_index {
+            write!(f, " output {}", output_index)?;
+        }
+        if let Some(height) = self.height {
+            write!(f, " at height {}", height)?;
+        }
+        write!(f, ": {}", self.detail)
+    }
+}
+
+/// DiscrepancyLog collects the discrepancies found by the operations it is given to
+///
+/// Clones share the same records, so a log can be given to scanning threads and read from elsewhere. At most
+/// `capacity` records are kept, the oldest ones being dropped first.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{Discrepancy, DiscrepancyKind, DiscrepancyLog};
+///
+/// let log = DiscrepancyLog::new(2);
+/// for index in 0..3 {
+///     log.record(Discrepancy {
+///         kind: DiscrepancyKind::InvalidOutputKey,
+///         tx_hash: None,
+///         height: Some(3000000),
+///         output_index: Some(index),
+///         detail: "not a curve point".to_string(),
+///     });
+/// }
+/// assert_eq!(log.len(), 2);
+/// assert_eq!(log.dropped(), 1);
+/// let records = log.drain();
+/// assert_eq!(records[0].to_string(), "InvalidOutputKey output 1 at height 3000000: not a curve point");
+/// assert!(log.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DiscrepancyLog {
+    state: Arc<Mutex<LogState>>,
+}
+
+#[derive(Debug)]
+struct LogState {
+    records: VecDeque<Discrepancy>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl DiscrepancyLog {
+    /// Creates an empty log keeping at most `capacity` records
+    pub fn new(capacity: usize) -> DiscrepancyLog {
+        DiscrepancyLog {
+            state: Arc::new(Mutex::new(LogState {
+                records: VecDeque::new(),
+                capacity,
+                dropped: 0,
+            })),
+        }
+    }
+
+    /// Adds a record, dropping the oldest one if the log is full
+    pub fn record(&self, discrepancy: Discrepancy) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            state.dropped += 1;
+            return;
+        }
+        if state.records.len() == state.capacity {
+            state.records.pop_front();
+            state.dropped += 1;
+        }
+        state.records.push_back(discrepancy);
+    }
+
+    /// Returns a copy of the kept records, oldest first
+    pub fn records(&self) -> Vec<Discrepancy> {
+        self.state.lock().unwrap().records.iter().cloned().collect()
+    }
+
+    /// Removes and returns the kept records, oldest first
+    pub fn drain(&self) -> Vec<Discrepancy> {
+        self.state.lock().unwrap().records.drain(..).collect()
+    }
+
+    /// Returns the number of kept records
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().records.len()
+    }
+
+    /// Checks if no record is kept
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of records dropped because the log was full
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+}
+
+impl Default for DiscrepancyLog {
+    /// Creates a log keeping the last 1000 records
+    fn default() -> DiscrepancyLog {
+        DiscrepancyLog::new(1000)
+    }
+}
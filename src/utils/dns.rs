@@ -0,0 +1,264 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::openalias::OpenAliasResolver;
+use rand::Rng;
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const TYPE_CNAME: u16 = 5;
+const TYPE_TXT: u16 = 16;
+const CLASS_IN: u16 = 1;
+
+// Header flags
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_TRUNCATED: u16 = 0x0200;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+const FLAG_AUTHENTIC_DATA: u16 = 0x0020;
+const RCODE_NXDOMAIN: u16 = 3;
+
+/// DnsResolver looks up the TXT records of OpenAlias domains like monero-core's `tools::DNSResolver`
+///
+/// Lookups go over UDP to a recursive resolver that validates DNSSEC, and fall back to TCP when the answer is too large.
+/// Records owned by another name than the queried domain or the CNAMEs it points to are rejected.
+///
+/// Queries set the DNSSEC OK and authentic data flags, and the answer counts as validated when the resolver sets the
+/// authentic data flag in return. Unlike monero-core, which validates with its own copy of libunbound, the flag is only
+/// as trustworthy as the resolver and the path to it, so the resolver should run on the same host or a trusted network.
+/// Needs the `openalias` feature.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::utils::{resolve_openalias, DnsResolver};
+///
+/// let resolver = DnsResolver::system().unwrap();
+/// let resolved = resolve_openalias("donate.getmonero.org", &resolver).unwrap();
+/// println!("{} ({:?}), DNSSEC valid: {}", resolved.record.address, resolved.record.description, resolved.dnssec_valid);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DnsResolver {
+    server: SocketAddr,
+    timeout: Duration,
+}
+
+impl DnsResolver {
+    /// Creates a resolver querying the given server, e.g. `127.0.0.1:53`
+    pub fn new(server: SocketAddr) -> DnsResolver {
+        DnsResolver { server, timeout: DEFAULT_TIMEOUT }
+    }
+
+    /// Creates a resolver querying the first name server of `/etc/resolv.conf`
+    ///
+    /// Returns the resolver if succesfull
+    /// Returns an error message if the file can't be read or lists no name server
+    pub fn system() -> Result<DnsResolver, String> {
+        let conf = std::fs::read_to_string(RESOLV_CONF).map_err(|e| format!("Error while reading {}: {}", RESOLV_CONF, e))?;
+        conf.lines()
+            .filter_map(|line| line.trim().strip_prefix("nameserver"))
+            .find_map(|server| server.trim().parse::<IpAddr>().ok())
+            .map(|ip| DnsResolver::new(SocketAddr::new(ip, 53)))
+            .ok_or(format!("No name server found in {}", RESOLV_CONF))
+    }
+
+    /// Sets how long a lookup may take, 5 seconds by default
+    pub fn with_timeout(mut self, timeout: Duration) -> DnsResolver {
+        self.timeout = timeout;
+        self
+    }
+
+    fn query_udp(&self, id: u16, query: &[u8]) -> Result<Vec<u8>, String> {
+        let local: SocketAddr = match self.server {
+            SocketAddr::V4(_) => ([0u8; 4], 0).into(),
+            SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+        };
+        let socket = UdpSocket::bind(local).map_err(|e| format!("Error while opening a UDP socket: {}", e))?;
+        socket.connect(self.server).map_err(|e| format!("Error while connecting to {}: {}", self.server, e))?;
+        socket.send(query).map_err(|e| format!("Error while querying {}: {}", self.server, e))?;
+        let deadline = Instant::now() + self.timeout;
+        let mut buffer = vec![0u8; 65535];
+        loop {
+            let remaining = deadline.checked_duration_since(Instant::now()).filter(|remaining| !remaining.is_zero());
+            socket.set_read_timeout(Some(remaining.ok_or(format!("DNS server {} did not answer in time", self.server))?)).map_err(|e| e.to_string())?;
+            let len = socket.recv(&mut buffer).map_err(|e| format!("DNS server {} did not answer: {}", self.server, e))?;
+            // Stray datagrams of earlier queries are skipped
+            if len >= 2 && buffer[..2] == id.to_be_bytes() {
+                buffer.truncate(len);
+                return Ok(buffer);
+            }
+        }
+    }
+
+    fn query_tcp(&self, query: &[u8]) -> Result<Vec<u8>, String> {
+        let mut stream = TcpStream::connect_timeout(&self.server, self.timeout).map_err(|e| format!("Error while connecting to {}: {}", self.server, e))?;
+        stream.set_read_timeout(Some(self.timeout)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(self.timeout)).map_err(|e| e.to_string())?;
+        let mut message = (query.len() as u16).to_be_bytes().to_vec();
+        message.extend_from_slice(query);
+        stream.write_all(&message).map_err(|e| format!("Error while querying {}: {}", self.server, e))?;
+        let mut len = [0u8; 2];
+        stream.read_exact(&mut len).map_err(|e| format!("DNS server {} did not answer: {}", self.server, e))?;
+        let mut response = vec![0u8; u16::from_be_bytes(len) as usize];
+        stream.read_exact(&mut response).map_err(|e| format!("DNS server {} did not answer: {}", self.server, e))?;
+        Ok(response)
+    }
+}
+
+impl OpenAliasResolver for DnsResolver {
+    fn resolve_txt(&self, domain: &str) -> Result<(Vec<String>, bool), String> {
+        let id: u16 = rand::thread_rng().gen();
+        let query = build_txt_query(id, domain)?;
+        match parse_txt_response(id, domain, &self.query_udp(id, &query)?)? {
+            Some(answer) => Ok(answer),
+            None => parse_txt_response(id, domain, &self.query_tcp(&query)?)?.ok_or("DNS answer over TCP is truncated".to_string()),
+        }
+    }
+}
+
+// Query for the TXT records of a domain, with recursion desired, the authentic data flag and an EDNS0 record setting
+// the DNSSEC OK flag
+fn build_txt_query(id: u16, domain: &str) -> Result<Vec<u8>, String> {
+    let domain = domain.trim_end_matches('.');
+    if domain.is_empty() || domain.len() > 253 {
+        return Err(format!("Invalid domain {}", domain));
+    }
+    let mut query = Vec::with_capacity(domain.len() + 29);
+    query.extend_from_slice(&id.to_be_bytes());
+    query.extend_from_slice(&(FLAG_RECURSION_DESIRED | FLAG_AUTHENTIC_DATA).to_be_bytes());
+    // One question, no answer or authority records, one additional record
+    query.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 1]);
+    for label in domain.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid domain {}", domain));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&TYPE_TXT.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    // OPT record: root owner, type 41, 4096-byte UDP payload, no extended code, version 0, DNSSEC OK, no options
+    query.extend_from_slice(&[0, 0, 41, 0x10, 0x00, 0, 0, 0x80, 0x00, 0, 0]);
+    Ok(query)
+}
+
+// Returns the TXT records of the answer and its authentic data flag, None if the answer is truncated. Every record must
+// be owned by the queried domain or a CNAME it leads to, a record about another name would otherwise be reported as
+// validated along with the authentic data flag.
+fn parse_txt_response(id: u16, domain: &str, response: &[u8]) -> Result<Option<(Vec<String>, bool)>, String> {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    let mut pos = 0;
+    let header = take(response, &mut pos, 12)?;
+    let flags = u16::from_be_bytes([header[2], header[3]]);
+    if header[..2] != id.to_be_bytes() || flags & FLAG_RESPONSE == 0 {
+        return Err("DNS server sent an answer to another query".to_string());
+    }
+    if flags & FLAG_TRUNCATED != 0 {
+        return Ok(None);
+    }
+    let authentic = flags & FLAG_AUTHENTIC_DATA != 0;
+    match flags & 0x000f {
+        0 => {}
+        RCODE_NXDOMAIN => return Ok(Some((Vec::new(), authentic))),
+        rcode => return Err(format!("DNS server answered with error code {}", rcode)),
+    }
+    let questions = u16::from_be_bytes([header[4], header[5]]);
+    let answers = u16::from_be_bytes([header[6], header[7]]);
+    for _ in 0..questions {
+        if read_name(response, &mut pos)? != domain {
+            return Err("DNS server sent an answer to another query".to_string());
+        }
+        take(response, &mut pos, 4)?;
+    }
+    let mut records = Vec::with_capacity(answers as usize);
+    for _ in 0..answers {
+        let owner = read_name(response, &mut pos)?;
+        let fixed = take(response, &mut pos, 10)?;
+        let (record_type, class) = (u16::from_be_bytes([fixed[0], fixed[1]]), u16::from_be_bytes([fixed[2], fixed[3]]));
+        let mut data_pos = pos;
+        let data = take(response, &mut pos, u16::from_be_bytes([fixed[8], fixed[9]]) as usize)?;
+        // The target of a CNAME may point back into the rest of the message
+        let target = match record_type == TYPE_CNAME && class == CLASS_IN {
+            true => Some(read_name(response, &mut data_pos)?),
+            false => None,
+        };
+        records.push((owner, record_type, class, data, target));
+    }
+
+    // Follows the CNAMEs from the queried domain, in whatever order the records come
+    let mut names = vec![domain];
+    while let Some(target) = records
+        .iter()
+        .find(|(owner, .., target)| names.contains(owner) && target.as_ref().is_some_and(|target| !names.contains(target)))
+        .and_then(|(.., target)| target.clone())
+    {
+        names.push(target);
+    }
+    let mut texts = Vec::new();
+    for (owner, record_type, class, data, _) in records {
+        if !names.contains(&owner) {
+            return Err(format!("DNS answer holds a record for {}, which was not queried", owner));
+        }
+        // Other records, such as the CNAMEs leading to the TXT records or their signatures, are skipped
+        if record_type != TYPE_TXT || class != CLASS_IN {
+            continue;
+        }
+        // A TXT record is a sequence of length-prefixed strings, which OpenAlias records are split in when long
+        let mut text = Vec::with_capacity(data.len());
+        let mut data_pos = 0;
+        while data_pos < data.len() {
+            let len = take(data, &mut data_pos, 1)?[0] as usize;
+            text.extend_from_slice(take(data, &mut data_pos, len)?);
+        }
+        texts.push(String::from_utf8_lossy(&text).into_owned());
+    }
+    Ok(Some((texts, authentic)))
+}
+
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let bytes = data.get(*pos..*pos + len).ok_or("DNS answer is truncated")?;
+    *pos += len;
+    Ok(bytes)
+}
+
+// Reads a lowercase domain name, a sequence of labels possibly ending with a pointer to an earlier name. Pointers must
+// point backwards, so that a crafted answer can't loop.
+fn read_name(data: &[u8], pos: &mut usize) -> Result<String, String> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut cursor = *pos;
+    let mut jumped = false;
+    loop {
+        let len = take(data, &mut cursor, 1)?[0];
+        match len {
+            0 => break,
+            len if len & 0xc0 == 0xc0 => {
+                let target = (((len & 0x3f) as usize) << 8) | take(data, &mut cursor, 1)?[0] as usize;
+                if !jumped {
+                    *pos = cursor;
+                    jumped = true;
+                }
+                if target >= cursor - 2 {
+                    return Err("DNS answer holds an invalid name pointer".to_string());
+                }
+                cursor = target;
+            }
+            len if len & 0xc0 == 0 => {
+                labels.push(String::from_utf8_lossy(take(data, &mut cursor, len as usize)?).to_ascii_lowercase());
+            }
+            _ => return Err("DNS answer uses an unknown label type".to_string()),
+        }
+    }
+    if !jumped {
+        *pos = cursor;
+    }
+    Ok(labels.join("."))
+}
@@ -0,0 +1,485 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// Crate-wide error hierarchy. Each subsystem has its own error enum with stable numeric codes, grouped by
+// thousands: 1xxx keys, 2xxx RPC, 3xxx parsing, 4xxx wallet. Codes are never reused or renumbered, new
+// variants take the next free code of their group.
+
+use crate::keys::{MnemonicError, WordlistError};
+use crate::wallet::{KeyImageImportError, SpendingLimitError};
+use std::error::Error as StdError;
+use std::fmt;
+
+/// KeyError describes an invalid key, address or seed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyError {
+    /// Key is not valid hex
+    InvalidHex,
+    /// Key does not have the expected number of bytes
+    InvalidLength { expected: usize, got: usize },
+    /// Public key is not a point of the curve
+    InvalidPoint,
+    /// Private key is not a reduced scalar
+    NonCanonicalScalar,
+    /// Address could not be decoded
+    InvalidAddress(String),
+    /// Mnemonic seed is invalid
+    Mnemonic(MnemonicError),
+    /// Wordset could not be loaded
+    Wordlist(WordlistError),
+}
+
+impl KeyError {
+    /// Returns the stable code of the error, between 1000 and 1999
+    pub fn code(&self) -> u32 {
+        match self {
+            KeyError::InvalidHex => 1001,
+            KeyError::InvalidLength { .. } => 1002,
+            KeyError::InvalidPoint => 1003,
+            KeyError::NonCanonicalScalar => 1004,
+            KeyError::InvalidAddress(_) => 1005,
+            KeyError::Mnemonic(error) => match error {
+                MnemonicError::InvalidWordCount(_) => 1101,
+                MnemonicError::UnknownWord { .. } => 1102,
+                MnemonicError::InvalidWordGroup { .. } => 1103,
+                MnemonicError::InvalidChecksum { .. } => 1104,
+                MnemonicError::UnsupportedLanguage(_) => 1105,
+            },
+            KeyError::Wordlist(error) => match error {
+                WordlistError::InvalidLanguage(_) => 1201,
+                WordlistError::InvalidWordCount(_) => 1202,
+                WordlistError::InvalidWord { .. } => 1203,
+                WordlistError::DuplicateWord { .. } => 1204,
+                WordlistError::DuplicatePrefix { .. } => 1205,
+                WordlistError::Read(_) => 1206,
+            },
+        }
+    }
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::InvalidHex => write!(f, "Key is not valid hex"),
+            KeyError::InvalidLength { expected, got } => write!(f, "Key has {} bytes, expected {}", got, expected),
+            KeyError::InvalidPoint => write!(f, "Public key is not a valid point"),
+            KeyError::NonCanonicalScalar => write!(f, "Private key is not a reduced scalar"),
+            KeyError::InvalidAddress(reason) => write!(f, "Invalid address: {}", reason),
+            KeyError::Mnemonic(_) => write!(f, "Invalid mnemonic seed"),
+            KeyError::Wordlist(_) => write!(f, "Invalid wordset"),
+        }
+    }
+}
+
+impl StdError for KeyError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            KeyError::Mnemonic(error) => Some(error),
+            KeyError::Wordlist(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<MnemonicError> for KeyError {
+    fn from(error: MnemonicError) -> KeyError {
+        KeyError::Mnemonic(error)
+    }
+}
+
+impl From<WordlistError> for KeyError {
+    fn from(error: WordlistError) -> KeyError {
+        KeyError::Wordlist(error)
+    }
+}
+
+/// ParseError describes data that could not be decoded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Field is not valid hex
+    InvalidHex { field: String },
+    /// Field does not have the expected number of bytes
+    InvalidLength { field: String, expected: usize, got: usize },
+    /// Data is not valid JSON
+    InvalidJson(String),
+    /// Required field is missing
+    MissingField(String),
+    /// Field is present but its value is invalid
+    InvalidField { field: String, reason: String },
+    /// Varint is truncated, too long or not minimally encoded
+    InvalidVarint,
+    /// Bytes are left after the end of the structure
+    TrailingBytes(usize),
+}
+
+impl ParseError {
+    /// Returns the stable code of the error, between 3000 and 3999
+    pub fn code(&self) -> u32 {
+        match self {
+            ParseError::InvalidHex { .. } => 3001,
+            ParseError::InvalidLength { .. } => 3002,
+            ParseError::InvalidJson(_) => 3003,
+            ParseError::MissingField(_) => 3004,
+            ParseError::InvalidField { .. } => 3005,
+            ParseError::InvalidVarint => 3006,
+            ParseError::TrailingBytes(_) => 3007,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidHex { field } => write!(f, "Field {} is not valid hex", field),
+            ParseError::InvalidLength { field, expected, got } => write!(f, "Field {} has {} bytes, expected {}", field, got, expected),
+            ParseError::InvalidJson(reason) => write!(f, "Invalid JSON: {}", reason),
+            ParseError::MissingField(field) => write!(f, "Field {} is missing", field),
+            ParseError::InvalidField { field, reason } => write!(f, "Field {} is invalid: {}", field, reason),
+            ParseError::InvalidVarint => write!(f, "Invalid varint"),
+            ParseError::TrailingBytes(count) => write!(f, "{} bytes left after the end of the data", count),
+        }
+    }
+}
+
+impl StdError for ParseError {}
+
+/// RpcError describes a failed call to a daemon
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RpcError {
+    /// Request did not reach the daemon or no response came back
+    Transport(String),
+    /// Daemon answered with a JSON-RPC error object
+    Daemon { code: i64, message: String },
+    /// Daemon answered with a status other than OK, BUSY while syncing for instance
+    Status(String),
+    /// Call was cancelled with its [`CancelToken`](../blocks/struct.CancelToken.html)
+    Cancelled,
+    /// Response could not be decoded
+    InvalidResponse(ParseError),
+}
+
+impl RpcError {
+    /// Returns the stable code of the error, between 2000 and 2999
+    pub fn code(&self) -> u32 {
+        match self {
+            RpcError::Transport(_) => 2001,
+            RpcError::Daemon { .. } => 2002,
+            RpcError::Status(_) => 2003,
+            RpcError::Cancelled => 2004,
+            RpcError::InvalidResponse(_) => 2005,
+        }
+    }
+
+    /// Returns the error reported by a JSON-RPC response, if any
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::utils::RpcError;
+    ///
+    /// let response = serde_json::json!({"error": {"code": -2, "message": "Failed to get block"}});
+    /// assert_eq!(RpcError::from_json_rpc(&response), Some(RpcError::Daemon { code: -2, message: "Failed to get block".to_string() }));
+    /// let response = serde_json::json!({"result": {"status": "BUSY"}});
+    /// assert_eq!(RpcError::from_json_rpc(&response), Some(RpcError::Status("BUSY".to_string())));
+    /// assert_eq!(RpcError::from_json_rpc(&serde_json::json!({"result": {"status": "OK"}})), None);
+    /// ```
+    pub fn from_json_rpc(response: &serde_json::Value) -> Option<RpcError> {
+        if let Some(message) = response["error"]["message"].as_str() {
+            let code = response["error"]["code"].as_i64().unwrap_or(0);
+            return Some(RpcError::Daemon { code, message: message.to_string() });
+        }
+        match response["result"]["status"].as_str() {
+            Some(status) if status != "OK" => Some(RpcError::Status(status.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Transport(message) => write!(f, "{}", message),
+            RpcError::Daemon { message, .. } => write!(f, "{}", message),
+            RpcError::Status(status) => write!(f, "{}", status),
+            RpcError::Cancelled => write!(f, "RPC call cancelled"),
+            RpcError::InvalidResponse(_) => write!(f, "Invalid response"),
+        }
+    }
+}
+
+impl StdError for RpcError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            RpcError::InvalidResponse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for RpcError {
+    fn from(error: ParseError) -> RpcError {
+        RpcError::InvalidResponse(error)
+    }
+}
+
+/// WalletError describes a wallet operation that was refused
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WalletError {
+    /// Keys of the wallet are invalid
+    Key(KeyError),
+    /// Snapshot or exported data could not be decoded
+    InvalidData(ParseError),
+    /// Response of the cold wallet to a key image request was refused
+    KeyImageImport(KeyImageImportError),
+    /// Transaction was refused by the spending policy
+    SpendingLimit(SpendingLimitError),
+}
+
+impl WalletError {
+    /// Returns the stable code of the error, between 4000 and 4999
+    pub fn code(&self) -> u32 {
+        match self {
+            WalletError::Key(_) => 4001,
+            WalletError::InvalidData(_) => 4002,
+            WalletError::KeyImageImport(error) => match error {
+                KeyImageImportError::WrongSession => 4101,
+                KeyImageImportError::UnknownRequest(_) => 4102,
+                KeyImageImportError::AlreadyImported(_) => 4103,
+                KeyImageImportError::Superseded { .. } => 4104,
+                KeyImageImportError::OutputMismatch { .. } => 4105,
+                KeyImageImportError::InvalidProof { .. } => 4106,
+            },
+            WalletError::SpendingLimit(error) => match error {
+                SpendingLimitError::PerTransaction { .. } => 4201,
+                SpendingLimitError::Daily { .. } => 4202,
+                SpendingLimitError::Rejected(_) => 4203,
+            },
+        }
+    }
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletError::Key(_) => write!(f, "Invalid wallet keys"),
+            WalletError::InvalidData(_) => write!(f, "Invalid wallet data"),
+            WalletError::KeyImageImport(_) => write!(f, "Key image import refused"),
+            WalletError::SpendingLimit(_) => write!(f, "Spending policy refused the transaction"),
+        }
+    }
+}
+
+impl StdError for WalletError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            WalletError::Key(error) => Some(error),
+            WalletError::InvalidData(error) => Some(error),
+            WalletError::KeyImageImport(error) => Some(error),
+            WalletError::SpendingLimit(error) => Some(error),
+        }
+    }
+}
+
+impl From<KeyError> for WalletError {
+    fn from(error: KeyError) -> WalletError {
+        WalletError::Key(error)
+    }
+}
+
+impl From<KeyImageImportError> for WalletError {
+    fn from(error: KeyImageImportError) -> WalletError {
+        WalletError::KeyImageImport(error)
+    }
+}
+
+impl From<SpendingLimitError> for WalletError {
+    fn from(error: SpendingLimitError) -> WalletError {
+        WalletError::SpendingLimit(error)
+    }
+}
+
+/// ErrorKind is the subsystem error an [`Error`](struct.Error.html) wraps
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Error of the keys, codes 1xxx
+    Key(KeyError),
+    /// Error of a daemon call, codes 2xxx
+    Rpc(RpcError),
+    /// Error decoding data, codes 3xxx
+    Parse(ParseError),
+    /// Error of a wallet operation, codes 4xxx
+    Wallet(WalletError),
+}
+
+impl ErrorKind {
+    fn error(&self) -> &(dyn StdError + 'static) {
+        match self {
+            ErrorKind::Key(error) => error,
+            ErrorKind::Rpc(error) => error,
+            ErrorKind::Parse(error) => error,
+            ErrorKind::Wallet(error) => error,
+        }
+    }
+}
+
+/// Error is a subsystem error with the context it went through, outermost first
+///
+/// Display prints the context then the error, separated by colons. [`source`](#method.source) goes down the chain
+/// of subsystem errors and [`code`](#method.code) gives the stable code of the outermost one, for FFI and RPC
+/// servers mapping failures without matching strings.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::MnemonicError;
+/// use libmonero::utils::{Context, Error, ErrorKind, KeyError};
+///
+/// let validation: Result<(), MnemonicError> = Err(MnemonicError::InvalidWordCount(12));
+/// let error: Error = validation.map_err(KeyError::from).context("Restoring wallet").unwrap_err();
+/// assert_eq!(error.code(), 1101);
+/// assert_eq!(error.to_string(), "Restoring wallet: Invalid mnemonic seed");
+/// assert!(matches!(error.kind(), ErrorKind::Key(KeyError::Mnemonic(_))));
+/// assert_eq!(error.root_cause().to_string(), "Seed has 12 words, expected 13 or 25");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    context: Vec<String>,
+}
+
+impl Error {
+    /// Returns the subsystem error
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Returns the stable code of the subsystem error
+    pub fn code(&self) -> u32 {
+        match &self.kind {
+            ErrorKind::Key(error) => error.code(),
+            ErrorKind::Rpc(error) => error.code(),
+            ErrorKind::Parse(error) => error.code(),
+            ErrorKind::Wallet(error) => error.code(),
+        }
+    }
+
+    /// Returns the context the error went through, outermost first
+    pub fn context(&self) -> impl Iterator<Item = &str> {
+        self.context.iter().rev().map(|context| context.as_str())
+    }
+
+    /// Adds an outer context to the error
+    pub fn with_context(mut self, context: impl Into<String>) -> Error {
+        self.context.push(context.into());
+        self
+    }
+
+    /// Returns the innermost error of the source chain
+    pub fn root_cause(&self) -> &(dyn StdError + 'static) {
+        let mut error = self.kind.error();
+        while let Some(source) = error.source() {
+            error = source;
+        }
+        error
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for context in self.context() {
+            write!(f, "{}: ", context)?;
+        }
+        write!(f, "{}", self.kind.error())
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.kind.error().source()
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error { kind, context: Vec::new() }
+    }
+}
+
+impl From<KeyError> for Error {
+    fn from(error: KeyError) -> Error {
+        ErrorKind::Key(error).into()
+    }
+}
+
+impl From<RpcError> for Error {
+    fn from(error: RpcError) -> Error {
+        ErrorKind::Rpc(error).into()
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Error {
+        ErrorKind::Parse(error).into()
+    }
+}
+
+impl From<WalletError> for Error {
+    fn from(error: WalletError) -> Error {
+        ErrorKind::Wallet(error).into()
+    }
+}
+
+impl From<MnemonicError> for Error {
+    fn from(error: MnemonicError) -> Error {
+        KeyError::from(error).into()
+    }
+}
+
+impl From<WordlistError> for Error {
+    fn from(error: WordlistError) -> Error {
+        KeyError::from(error).into()
+    }
+}
+
+impl From<KeyImageImportError> for Error {
+    fn from(error: KeyImageImportError) -> Error {
+        WalletError::from(error).into()
+    }
+}
+
+impl From<SpendingLimitError> for Error {
+    fn from(error: SpendingLimitError) -> Error {
+        WalletError::from(error).into()
+    }
+}
+
+// Functions still returning String errors keep working with ? on functions returning Error
+impl From<Error> for String {
+    fn from(error: Error) -> String {
+        error.to_string()
+    }
+}
+
+/// Context adds context to the error of a result, converting it to an [`Error`](struct.Error.html)
+pub trait Context<T> {
+    /// Adds the given context to the error
+    fn context(self, context: impl Into<String>) -> Result<T, Error>;
+
+    /// Adds the context returned by the closure to the error, only called on errors
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, context: F) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, Error> {
+        self.map_err(|error| error.into().with_context(context))
+    }
+
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, context: F) -> Result<T, Error> {
+        self.map_err(|error| error.into().with_context(context()))
+    }
+}
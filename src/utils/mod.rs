@@ -8,6 +8,28 @@
  *
  */
 
+pub(crate) mod address;
+pub(crate) mod amount;
+pub(crate) mod diagnostics;
+#[cfg(feature = "openalias")]
+pub(crate) mod dns;
+pub(crate) mod errors;
+pub(crate) mod openalias;
+pub(crate) mod payment_id;
+pub(crate) mod policy;
+pub(crate) mod uri;
 pub(crate) mod utils;
+pub(crate) mod varint;
 
-pub use utils::*;
\ No newline at end of file
+pub use address::*;
+pub use amount::*;
+pub use diagnostics::*;
+#[cfg(feature = "openalias")]
+pub use dns::*;
+pub use errors::*;
+pub use openalias::*;
+pub use payment_id::*;
+pub use policy::*;
+pub use uri::*;
+pub use utils::*;
+pub use varint::*;
\ No newline at end of file
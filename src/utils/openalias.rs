@@ -0,0 +1,183 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::address::decode_address;
+use super::utils::is_valid_addr;
+
+/// OpenAliasRecord contains the fields of an `oa1:xmr` TXT record
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenAliasRecord {
+    pub address: String,
+    pub recipient_name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// ResolvedDestination is what gets shown to the user before sending to an OpenAlias destination
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedDestination {
+    /// Destination as the user entered it, e.g. `donate.getmonero.org`
+    pub alias: String,
+    /// Record the alias resolved to
+    pub record: OpenAliasRecord,
+    /// Whether the DNS answer was validated with DNSSEC
+    pub dnssec_valid: bool,
+}
+
+/// OpenAliasResolver is implemented by anything able to look up the TXT records of a domain
+pub trait OpenAliasResolver {
+    /// Returns the TXT records of the given domain and whether the answer was validated with DNSSEC
+    fn resolve_txt(&self, domain: &str) -> Result<(Vec<String>, bool), String>;
+}
+
+/// Checks if the given destination looks like an OpenAlias (a domain or an email-like address) rather than a Monero address
+///
+/// Example:
+/// ```
+/// use libmonero::utils::looks_like_openalias;
+///
+/// assert!(looks_like_openalias("donate.getmonero.org"));
+/// assert!(looks_like_openalias("donate@getmonero.org"));
+/// assert!(!looks_like_openalias("42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB"));
+/// ```
+pub fn looks_like_openalias(destination: &str) -> bool {
+    let domain = openalias_domain(destination);
+    !is_valid_addr(destination)
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+/// Turns an email-like alias into the domain that holds its records (`user@domain` becomes `user.domain`)
+fn openalias_domain(destination: &str) -> String {
+    destination.trim().replacen('@', ".", 1)
+}
+
+/// Parses an `oa1:xmr` TXT record, returns None if the record isn't a Monero OpenAlias record
+///
+/// Example:
+/// ```
+/// use libmonero::utils::parse_openalias_record;
+///
+/// let record = parse_openalias_record("oa1:xmr recipient_address=42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB; recipient_name=Monero Development; tx_description=Donation;").unwrap();
+/// assert_eq!(record.recipient_name, Some("Monero Development".to_string()));
+/// assert_eq!(record.description, Some("Donation".to_string()));
+/// ```
+pub fn parse_openalias_record(txt: &str) -> Option<OpenAliasRecord> {
+    let body = txt.trim().strip_prefix("oa1:xmr")?;
+    let mut address = None;
+    let mut recipient_name = None;
+    let mut description = None;
+    for field in body.split(';') {
+        let field = field.trim();
+        if let Some((key, value)) = field.split_once('=') {
+            let value = value.trim().to_string();
+            match key.trim() {
+                "recipient_address" => address = Some(value),
+                "recipient_name" => recipient_name = Some(value),
+                "tx_description" => description = Some(value),
+                _ => continue,
+            }
+        }
+    }
+    Some(OpenAliasRecord {
+        address: address?,
+        recipient_name,
+        description,
+    })
+}
+
+/// Looks up the `oa1:xmr` record of an OpenAlias destination such as `donate.getmonero.org` or `donate@getmonero.org`
+///
+/// The recipient address must decode, see [`decode_address`](fn.decode_address.html). Whether the answer was validated
+/// with DNSSEC is reported rather than enforced, wallets should warn before using addresses of unvalidated answers.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{resolve_openalias, OpenAliasResolver};
+///
+/// struct StaticResolver;
+/// impl OpenAliasResolver for StaticResolver {
+///     fn resolve_txt(&self, _domain: &str) -> Result<(Vec<String>, bool), String> {
+///         Ok((vec!["oa1:xmr recipient_address=42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB; tx_description=Donation;".to_string()], false))
+///     }
+/// }
+///
+/// let resolved = resolve_openalias("donate.example.org", &StaticResolver).unwrap();
+/// assert_eq!(resolved.record.description.as_deref(), Some("Donation"));
+/// assert!(!resolved.dnssec_valid);
+/// ```
+///
+/// Returns the resolved destination if succesfull
+/// Returns an error message if the destination does not look like an OpenAlias, the lookup fails, there is no
+/// `oa1:xmr` record or several conflicting ones, or the recipient address is invalid
+pub fn resolve_openalias<R>(destination: &str, resolver: &R) -> Result<ResolvedDestination, String>
+where
+    R: OpenAliasResolver + ?Sized,
+{
+    if !looks_like_openalias(destination) {
+        return Err(format!("Destination is not an OpenAlias: {}", destination));
+    }
+    let domain = openalias_domain(destination);
+    let (records, dnssec_valid) = resolver.resolve_txt(&domain)?;
+    let mut found: Vec<OpenAliasRecord> = records.iter().filter_map(|txt| parse_openalias_record(txt)).collect();
+    found.dedup();
+    let record = match found.len() {
+        0 => return Err(format!("No oa1:xmr record found for {}", domain)),
+        1 => found.remove(0),
+        _ => return Err(format!("Multiple conflicting oa1:xmr records found for {}", domain)),
+    };
+    decode_address(&record.address).map_err(|e| format!("OpenAlias record of {} contains an invalid address: {}", domain, e))?;
+    Ok(ResolvedDestination {
+        alias: destination.to_string(),
+        record,
+        dnssec_valid,
+    })
+}
+
+/// Resolves a send destination into a Monero address
+///
+/// Plain addresses are returned unchanged. OpenAlias destinations are looked up with the given resolver, see
+/// [`resolve_openalias`](fn.resolve_openalias.html), and the `confirm` callback is always called with the resolved
+/// address and DNSSEC status before the address is used. Resolution fails if the callback returns false, if there is no
+/// valid `oa1:xmr` record or if there are several conflicting ones.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{resolve_destination, OpenAliasResolver};
+///
+/// struct StaticResolver;
+/// impl OpenAliasResolver for StaticResolver {
+///     fn resolve_txt(&self, _domain: &str) -> Result<(Vec<String>, bool), String> {
+///         Ok((vec!["oa1:xmr recipient_address=42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB;".to_string()], true))
+///     }
+/// }
+///
+/// let address = resolve_destination("donate@example.org", &StaticResolver, |resolved| resolved.dnssec_valid).unwrap();
+/// assert_eq!(address, "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB");
+/// assert!(resolve_destination("donate@example.org", &StaticResolver, |_| false).is_err());
+/// ```
+pub fn resolve_destination<R, C>(destination: &str, resolver: &R, confirm: C) -> Result<String, String>
+where
+    R: OpenAliasResolver + ?Sized,
+    C: FnOnce(&ResolvedDestination) -> bool,
+{
+    if is_valid_addr(destination) {
+        return Ok(destination.to_string());
+    }
+    if !looks_like_openalias(destination) {
+        return Err(format!("Destination is neither a Monero address nor an OpenAlias: {}", destination));
+    }
+    let resolved = resolve_openalias(destination, resolver)?;
+    if !confirm(&resolved) {
+        return Err("Resolved OpenAlias address was not confirmed".to_string());
+    }
+    Ok(resolved.record.address)
+}
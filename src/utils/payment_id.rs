@@ -0,0 +1,149 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::address::{decode_address, AddressInfo, AddressType};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+/// Domain separator for payment IDs derived with [`derive_payment_id`](fn.derive_payment_id.html)
+const PAYMENT_ID_DOMAIN: &[u8] = b"libmonero_payment_id";
+
+/// Derives the 8-byte payment ID of a customer from a merchant secret
+///
+/// The payment ID is the first 8 bytes of `Keccak256("libmonero_payment_id" || len(secret) || secret || len(customer_id) || customer_id)`
+/// where `len` is the length in bytes as 8-byte little-endian integer. The same secret and customer always give the
+/// same payment ID, so nothing has to be stored to recognize incoming payments, and payment IDs can't be linked to
+/// customers without the secret. A good secret is [`derive_app_secret`](../keys/fn.derive_app_secret.html) of the
+/// merchant wallet. Collisions must still be checked, see [`PaymentIdRegistry`](struct.PaymentIdRegistry.html).
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_app_secret, PrivateKey};
+/// use libmonero::utils::derive_payment_id;
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let secret = derive_app_secret(&private_spend_key, "myshop/payment-ids");
+/// let payment_id = derive_payment_id(secret.as_bytes(), "customer-42");
+/// assert_eq!(payment_id, derive_payment_id(secret.as_bytes(), "customer-42"));
+/// assert_ne!(payment_id, derive_payment_id(secret.as_bytes(), "customer-43"));
+/// ```
+pub fn derive_payment_id(merchant_secret: &[u8], customer_id: &str) -> [u8; 8] {
+    let mut hasher = Keccak256::new();
+    hasher.update(PAYMENT_ID_DOMAIN);
+    hasher.update((merchant_secret.len() as u64).to_le_bytes());
+    hasher.update(merchant_secret);
+    hasher.update((customer_id.len() as u64).to_le_bytes());
+    hasher.update(customer_id.as_bytes());
+    hasher.finalize()[..8].try_into().unwrap()
+}
+
+/// Builds the integrated address combining the given standard address with a payment ID
+///
+/// Subaddresses and integrated addresses can't carry a payment ID, an error message is returned for them
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{decode_address, make_integrated_address, AddressType};
+///
+/// let address = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+/// let integrated = make_integrated_address(address, [1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+/// let info = decode_address(&integrated).unwrap();
+/// assert_eq!(info.address_type, AddressType::Integrated);
+/// assert_eq!(info.payment_id, Some([1, 2, 3, 4, 5, 6, 7, 8]));
+/// ```
+pub fn make_integrated_address(address: &str, payment_id: [u8; 8]) -> Result<String, String> {
+    let info = decode_address(address)?;
+    if info.address_type != AddressType::Standard {
+        return Err(format!("Only standard addresses can be integrated, got a {:?} address", info.address_type));
+    }
+    AddressInfo {
+        address_type: AddressType::Integrated,
+        payment_id: Some(payment_id),
+        ..info
+    }
+    .to_address()
+}
+
+/// PaymentIdRegistry hands out deterministic payment IDs to customers and detects collisions between them
+///
+/// Two customers may derive the same 8-byte payment ID (about one chance in four billion per pair), payments could
+/// then not be told apart. The registry refuses to register the second customer, who should then be given another
+/// identifier or a subaddress. The null payment ID, which wallets treat as no payment ID, is refused as well.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{decode_address, PaymentIdRegistry};
+///
+/// let address = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+/// let mut registry = PaymentIdRegistry::new(b"merchant secret");
+/// let integrated = registry.integrated_address(address, "customer-42").unwrap();
+/// let payment_id = decode_address(&integrated).unwrap().payment_id.unwrap();
+/// assert_eq!(registry.lookup(&payment_id), Some("customer-42"));
+/// assert_eq!(registry.register("customer-42").unwrap(), payment_id);
+/// ```
+pub struct PaymentIdRegistry {
+    merchant_secret: Zeroizing<Vec<u8>>,
+    customers: HashMap<[u8; 8], String>,
+}
+
+impl PaymentIdRegistry {
+    /// Creates an empty registry deriving payment IDs from the given merchant secret
+    pub fn new(merchant_secret: &[u8]) -> PaymentIdRegistry {
+        PaymentIdRegistry {
+            merchant_secret: Zeroizing::new(merchant_secret.to_vec()),
+            customers: HashMap::new(),
+        }
+    }
+
+    /// Derives and registers the payment ID of the given customer, registering a customer again returns the same payment ID
+    ///
+    /// Returns an error message if the payment ID is null or already belongs to another customer
+    pub fn register(&mut self, customer_id: &str) -> Result<[u8; 8], String> {
+        let payment_id = derive_payment_id(&self.merchant_secret, customer_id);
+        if payment_id == [0; 8] {
+            return Err(format!("Payment ID of customer {} is null", customer_id));
+        }
+        match self.customers.get(&payment_id) {
+            Some(known) if known != customer_id => Err(format!(
+                "Payment ID {} of customer {} collides with customer {}",
+                hex::encode(payment_id),
+                customer_id,
+                known
+            )),
+            Some(_) => Ok(payment_id),
+            None => {
+                self.customers.insert(payment_id, customer_id.to_string());
+                Ok(payment_id)
+            }
+        }
+    }
+
+    /// Registers the given customer and returns the integrated address of the standard address with their payment ID
+    pub fn integrated_address(&mut self, address: &str, customer_id: &str) -> Result<String, String> {
+        let payment_id = self.register(customer_id)?;
+        make_integrated_address(address, payment_id)
+    }
+
+    /// Returns the customer the given payment ID was registered for
+    pub fn lookup(&self, payment_id: &[u8; 8]) -> Option<&str> {
+        self.customers.get(payment_id).map(|customer| customer.as_str())
+    }
+
+    /// Returns the number of registered customers
+    pub fn len(&self) -> usize {
+        self.customers.len()
+    }
+
+    /// Checks if no customer is registered
+    pub fn is_empty(&self) -> bool {
+        self.customers.is_empty()
+    }
+}
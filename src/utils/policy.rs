@@ -0,0 +1,172 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::address::{decode_address, AddressInfo, AddressType, Network};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Severity of a policy finding, errors must block the payment while warnings should be shown to the user
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// PolicyFinding is a single warning or error raised for a destination
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyFinding {
+    /// Index of the destination in the checked list
+    pub index: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// DestinationRule is a custom check run by a [`DestinationPolicy`](struct.DestinationPolicy.html) on every valid destination
+pub trait DestinationRule: Send + Sync {
+    /// Returns a finding if the payment of `amount` piconeros to the given address breaks the rule
+    fn check(&self, address: &AddressInfo, amount: u64) -> Option<(Severity, String)>;
+}
+
+impl<F> DestinationRule for F
+where
+    F: Fn(&AddressInfo, u64) -> Option<(Severity, String)> + Send + Sync,
+{
+    fn check(&self, address: &AddressInfo, amount: u64) -> Option<(Severity, String)> {
+        self(address, amount)
+    }
+}
+
+/// DestinationPolicy centralizes the checks an application wants to run on payment destinations before sending
+///
+/// Invalid addresses are always errors. By default there are no other checks, they are enabled with the builder methods.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{AddressType, DestinationPolicy, Network, Severity};
+///
+/// let policy = DestinationPolicy::new()
+///     .with_network(Network::Mainnet)
+///     .with_max_amount(AddressType::Standard, 10_000_000_000_000)
+///     .warn_on(AddressType::Integrated, "Integrated addresses are deprecated, ask the recipient for a subaddress");
+/// let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+/// assert!(policy.check(&[(address.to_string(), 1_000_000_000_000)]).is_empty());
+/// let findings = policy.check(&[(address.to_string(), 20_000_000_000_000)]);
+/// assert_eq!(findings[0].severity, Severity::Error);
+/// ```
+#[derive(Clone, Default)]
+pub struct DestinationPolicy {
+    network: Option<Network>,
+    min_amounts: HashMap<AddressType, u64>,
+    max_amounts: HashMap<AddressType, u64>,
+    warnings: HashMap<AddressType, String>,
+    forbidden: HashMap<AddressType, String>,
+    rules: Vec<Arc<dyn DestinationRule>>,
+}
+
+impl DestinationPolicy {
+    /// Creates a policy that only rejects invalid addresses
+    pub fn new() -> DestinationPolicy {
+        DestinationPolicy::default()
+    }
+
+    /// Rejects addresses of any other network
+    pub fn with_network(mut self, network: Network) -> DestinationPolicy {
+        self.network = Some(network);
+        self
+    }
+
+    /// Rejects payments below `amount` piconeros to addresses of given type
+    pub fn with_min_amount(mut self, address_type: AddressType, amount: u64) -> DestinationPolicy {
+        self.min_amounts.insert(address_type, amount);
+        self
+    }
+
+    /// Rejects payments above `amount` piconeros to addresses of given type
+    pub fn with_max_amount(mut self, address_type: AddressType, amount: u64) -> DestinationPolicy {
+        self.max_amounts.insert(address_type, amount);
+        self
+    }
+
+    /// Raises a warning with given message for every payment to an address of given type
+    pub fn warn_on(mut self, address_type: AddressType, message: &str) -> DestinationPolicy {
+        self.warnings.insert(address_type, message.to_string());
+        self
+    }
+
+    /// Rejects every payment to an address of given type with given message
+    pub fn forbid(mut self, address_type: AddressType, message: &str) -> DestinationPolicy {
+        self.forbidden.insert(address_type, message.to_string());
+        self
+    }
+
+    /// Adds a custom rule, closures taking the decoded address and the amount can be used directly
+    pub fn with_rule<R: DestinationRule + 'static>(mut self, rule: R) -> DestinationPolicy {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Checks the given destinations (address and amount in piconeros) and returns every finding, in destination order
+    pub fn check(&self, destinations: &[(String, u64)]) -> Vec<PolicyFinding> {
+        let mut findings = Vec::new();
+        for (index, (address, amount)) in destinations.iter().enumerate() {
+            let mut push = |severity: Severity, message: String| {
+                findings.push(PolicyFinding { index, severity, message });
+            };
+            let info = match decode_address(address) {
+                Ok(info) => info,
+                Err(e) => {
+                    push(Severity::Error, format!("Invalid address: {}", e));
+                    continue;
+                }
+            };
+            if let Some(network) = self.network {
+                if info.network != network {
+                    push(Severity::Error, format!("Address is for {:?}, expected {:?}", info.network, network));
+                }
+            }
+            if let Some(message) = self.forbidden.get(&info.address_type) {
+                push(Severity::Error, message.clone());
+            }
+            if let Some(message) = self.warnings.get(&info.address_type) {
+                push(Severity::Warning, message.clone());
+            }
+            if let Some(min) = self.min_amounts.get(&info.address_type) {
+                if amount < min {
+                    push(Severity::Error, format!("Amount {} is below the minimum of {} for {:?} addresses", amount, min, info.address_type));
+                }
+            }
+            if let Some(max) = self.max_amounts.get(&info.address_type) {
+                if amount > max {
+                    push(Severity::Error, format!("Amount {} is above the maximum of {} for {:?} addresses", amount, max, info.address_type));
+                }
+            }
+            for rule in self.rules.iter() {
+                if let Some((severity, message)) = rule.check(&info, *amount) {
+                    push(severity, message);
+                }
+            }
+        }
+        findings
+    }
+
+    /// Checks the given destinations and returns the errors only, as a single message, or the warnings if there is no error
+    pub fn enforce(&self, destinations: &[(String, u64)]) -> Result<Vec<PolicyFinding>, String> {
+        let findings = self.check(destinations);
+        let errors: Vec<String> = findings
+            .iter()
+            .filter(|finding| finding.severity == Severity::Error)
+            .map(|finding| format!("Destination {}: {}", finding.index, finding.message))
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors.join(", "));
+        }
+        Ok(findings)
+    }
+}
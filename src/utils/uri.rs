@@ -0,0 +1,157 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::address::{decode_address, AddressInfo};
+use super::amount::PiconeroAmount;
+
+const URI_SCHEME: &str = "monero:";
+
+/// PaymentRequest is a payment request decoded from a `monero:` URI, see [`parse_uri`](fn.parse_uri.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentRequest {
+    /// Address to pay, as written in the URI
+    pub address: String,
+    /// Everything the address encodes
+    pub info: AddressInfo,
+    /// Requested amount in piconeros, `tx_amount`
+    pub amount: Option<u64>,
+    /// Name of the recipient, `recipient_name`
+    pub recipient_name: Option<String>,
+    /// Description of the payment, `tx_description`
+    pub description: Option<String>,
+    /// Parameters the scheme does not define, in order, which wallets should show rather than drop silently
+    pub unknown_parameters: Vec<(String, String)>,
+}
+
+/// Builds a `monero:` URI requesting a payment to `address`, monero-core's `wallet2::make_uri`
+///
+/// Parameters come in the order of the reference wallet, amounts are written in XMR with all 12 decimals and texts are
+/// percent-encoded. An amount of 0 is left out like a missing one.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::make_uri;
+///
+/// let address = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+/// let uri = make_uri(address, Some(1500000000000), Some("Order #42"), Some("Coffee shop")).unwrap();
+/// assert_eq!(uri, format!("monero:{}?tx_amount=1.500000000000&recipient_name=Coffee%20shop&tx_description=Order%20%2342", address));
+/// ```
+///
+/// Returns the URI if succesfull
+/// Returns an error message if the address is invalid
+pub fn make_uri(address: &str, amount: Option<u64>, description: Option<&str>, recipient_name: Option<&str>) -> Result<String, String> {
+    decode_address(address)?;
+    let mut parameters = Vec::new();
+    if let Some(amount) = amount.filter(|amount| *amount > 0) {
+        parameters.push(format!("tx_amount={:.12}", PiconeroAmount::from_piconeros(amount)));
+    }
+    if let Some(recipient_name) = recipient_name.filter(|name| !name.is_empty()) {
+        parameters.push(format!("recipient_name={}", percent_encode(recipient_name)));
+    }
+    if let Some(description) = description.filter(|description| !description.is_empty()) {
+        parameters.push(format!("tx_description={}", percent_encode(description)));
+    }
+    Ok(match parameters.is_empty() {
+        true => format!("{}{}", URI_SCHEME, address),
+        false => format!("{}{}?{}", URI_SCHEME, address, parameters.join("&")),
+    })
+}
+
+/// Decodes a `monero:` URI into a payment request, monero-core's `wallet2::parse_uri`
+///
+/// The address is fully validated, see [`decode_address`](fn.decode_address.html), the network it belongs to is left
+/// for the caller to check. Parameters the scheme does not define are kept in
+/// [`unknown_parameters`](struct.PaymentRequest.html#structfield.unknown_parameters).
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{parse_uri, Network};
+///
+/// let request = parse_uri("monero:41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT?tx_amount=0.25&tx_description=Order%20%2342").unwrap();
+/// assert_eq!(request.info.network, Network::Mainnet);
+/// assert_eq!(request.amount, Some(250000000000));
+/// assert_eq!(request.description.as_deref(), Some("Order #42"));
+/// assert_eq!(request.recipient_name, None);
+/// ```
+///
+/// Returns the payment request if succesfull
+/// Returns an error message if the URI does not use the `monero:` scheme, the address is invalid, a parameter is
+/// malformed or given twice, or the URI carries a standalone payment ID
+pub fn parse_uri(uri: &str) -> Result<PaymentRequest, String> {
+    let remainder = uri.strip_prefix(URI_SCHEME).ok_or(format!("URI must start with {}", URI_SCHEME))?;
+    let (address, query) = match remainder.split_once('?') {
+        Some((address, query)) => (address, Some(query)),
+        None => (remainder, None),
+    };
+    let info = decode_address(address).map_err(|e| format!("Invalid address in URI: {}", e))?;
+    let mut request = PaymentRequest {
+        address: address.to_string(),
+        info,
+        amount: None,
+        recipient_name: None,
+        description: None,
+        unknown_parameters: Vec::new(),
+    };
+
+    for parameter in query.into_iter().flat_map(|query| query.split('&')).filter(|parameter| !parameter.is_empty()) {
+        let (name, value) = parameter.split_once('=').ok_or(format!("URI parameter {} has no value", parameter))?;
+        let duplicate = || format!("URI parameter {} is given twice", name);
+        match name {
+            "tx_amount" => {
+                if request.amount.replace(PiconeroAmount::from_xmr_str(value)?.as_piconeros()).is_some() {
+                    return Err(duplicate());
+                }
+            }
+            "recipient_name" => {
+                if request.recipient_name.replace(percent_decode(value)?).is_some() {
+                    return Err(duplicate());
+                }
+            }
+            "tx_description" => {
+                if request.description.replace(percent_decode(value)?).is_some() {
+                    return Err(duplicate());
+                }
+            }
+            "tx_payment_id" => return Err("Standalone payment IDs are not supported, use an integrated address".to_string()),
+            _ => request.unknown_parameters.push((percent_decode(name)?, percent_decode(value)?)),
+        }
+    }
+    Ok(request)
+}
+
+// Percent-encodes everything but the unreserved characters of RFC 3986, like epee's `conver_to_url_format`
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// Decodes percent-encoded UTF-8, a plus sign stays a plus sign as for epee's `convert_from_url_format`
+fn percent_decode(text: &str) -> Result<String, String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit)).ok_or(format!("Invalid percent-encoding in {}", text))?;
+            decoded.push(u8::from_str_radix(std::str::from_utf8(hex).unwrap(), 16).unwrap());
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| format!("URI parameter {} is not valid UTF-8", text))
+}
@@ -12,9 +12,12 @@
 //! 
 //! This module contains utility functions like address validation etc.
 
-use regex::Regex;
+use super::address::{decode_address, AddressType, Network};
 
 /// Checks if the given address is valid, returns true if it is, false otherwise
+///
+/// Standard, integrated and subaddresses of mainnet, testnet and stagenet are accepted,
+/// their prefix, length, checksum and public keys are all verified
 /// 
 /// Example:
 /// ```
@@ -23,11 +26,25 @@ use regex::Regex;
 /// let result: bool = is_valid_addr("42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB");
 /// assert_eq!(result, true);
 /// 
+/// let result_subaddress: bool = is_valid_addr("888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H");
+/// assert_eq!(result_subaddress, true);
+/// 
 /// let result_invalid: bool = is_valid_addr("12342XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB");
 /// assert_eq!(result_invalid, false);
 /// ```
 pub fn is_valid_addr(address: &str) -> bool {
-    // TODO: Refactor for a better Monero address validation based on eliptic curve
-    let r = Regex::new(r"^[48][0-9AB][1-9A-HJ-NP-Za-km-z]{93}$").unwrap();
-    r.is_match(address)
+    decode_address(address).is_ok()
+}
+
+/// Returns the network and type of the given address, or None if the address is invalid
+///
+/// Example:
+/// ```
+/// use libmonero::utils::{classify_addr, AddressType, Network};
+///
+/// let result = classify_addr("888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H");
+/// assert_eq!(result, Some((Network::Mainnet, AddressType::Subaddress)));
+/// ```
+pub fn classify_addr(address: &str) -> Option<(Network, AddressType)> {
+    decode_address(address).ok().map(|info| (info.network, info.address_type))
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+/// Appends `value` to `out` as a Monero varint: 7 bits per byte, least significant group first, the high bit set on
+/// every byte but the last one
+///
+/// Varints encode the integers of blocks and transactions (versions, heights, key offsets, amounts) and the fields
+/// of tx_extra. A u64 takes between 1 and 10 bytes.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::write_varint;
+///
+/// let mut out = Vec::new();
+/// write_varint(300, &mut out);
+/// write_varint(5, &mut out);
+/// assert_eq!(out, vec![0xac, 0x02, 0x05]);
+/// ```
+pub fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads a Monero varint from `data` starting at `pos`, advancing `pos` past it, see
+/// [`write_varint`](fn.write_varint.html)
+///
+/// Returns the integer if succesfull
+/// Returns an error message if the data ends inside the varint, the varint overflows u64 or has trailing zero groups,
+/// which monero-core refuses as a non-canonical encoding
+///
+/// Example:
+/// ```
+/// use libmonero::utils::read_varint;
+///
+/// let data = [0xac, 0x02, 0x05];
+/// let mut pos = 0;
+/// assert_eq!(read_varint(&data, &mut pos), Ok(300));
+/// assert_eq!(read_varint(&data, &mut pos), Ok(5));
+/// assert_eq!(pos, 3);
+/// assert!(read_varint(&data, &mut pos).is_err());
+/// assert!(read_varint(&[0x80, 0x00], &mut 0).is_err());
+/// ```
+pub fn read_varint(data: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut value = 0u64;
+    let mut shift = 0u32;
+    loop {
+        let byte = *data.get(*pos).ok_or("Unexpected end of data while reading a varint")?;
+        *pos += 1;
+        if shift == 63 && byte > 1 {
+            return Err("Varint overflows u64".to_string());
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            if byte == 0 && shift != 0 {
+                return Err("Varint is not canonically encoded".to_string());
+            }
+            return Ok(value);
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err("Varint overflows u64".to_string());
+        }
+    }
+}
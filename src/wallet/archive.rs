@@ -0,0 +1,186 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+// Binary archive of monero-core's serialization library, the format of the files the reference wallet exchanges
+// between its online and offline instances. Integers are little endian unless they are varint fields, containers
+// start with their varint length, pairs and tuples with their varint arity, versioned objects with a varint version.
+
+use crate::crypt::keccak::keccak256;
+use crate::crypt::{chacha20, generate_chacha_key};
+use crate::keys::{generate_signature, PrivateKey, Signature};
+use crate::utils::varint::{read_varint, write_varint};
+use rand::RngCore;
+
+// Flag of exported transfers spending RingCT outputs
+pub(crate) const TRANSFER_FLAG_RCT: u8 = 1 << 2;
+
+// Output of the wallet as exported by `export_outputs`, monero-core's `exported_transfer_details`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ExportedTransfer {
+    pub(crate) output_key: [u8; 32],
+    pub(crate) internal_output_index: u64,
+    pub(crate) global_output_index: u64,
+    pub(crate) tx_public_key: [u8; 32],
+    pub(crate) flags: u8,
+    pub(crate) amount: u64,
+    pub(crate) additional_tx_keys: Vec<[u8; 32]>,
+    pub(crate) subaddress: (u32, u32),
+}
+
+impl ExportedTransfer {
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        write_varint(1, out);
+        out.extend_from_slice(&self.output_key);
+        write_varint(self.internal_output_index, out);
+        write_varint(self.global_output_index, out);
+        out.extend_from_slice(&self.tx_public_key);
+        out.push(self.flags);
+        write_varint(self.amount, out);
+        write_keys(&self.additional_tx_keys, out);
+        write_varint(self.subaddress.0 as u64, out);
+        write_varint(self.subaddress.1 as u64, out);
+    }
+
+    pub(crate) fn read(data: &[u8], pos: &mut usize) -> Result<ExportedTransfer, String> {
+        let version = read_varint(data, pos)?;
+        if version != 1 {
+            return Err(format!("Unsupported exported output version {}", version));
+        }
+        Ok(ExportedTransfer {
+            output_key: read_key(data, pos)?,
+            internal_output_index: read_varint(data, pos)?,
+            global_output_index: read_varint(data, pos)?,
+            tx_public_key: read_key(data, pos)?,
+            flags: read_bytes(data, pos, 1)?[0],
+            amount: read_varint(data, pos)?,
+            additional_tx_keys: read_keys(data, pos)?,
+            subaddress: (read_varint_u32(data, pos)?, read_varint_u32(data, pos)?),
+        })
+    }
+}
+
+// Tuple of the first exported transfer, the number of transfers of the wallet and the exported transfers
+pub(crate) fn write_transfers(offset: u64, total: u64, transfers: &[ExportedTransfer], out: &mut Vec<u8>) {
+    write_varint(3, out);
+    write_varint(offset, out);
+    write_varint(total, out);
+    write_varint(transfers.len() as u64, out);
+    transfers.iter().for_each(|transfer| transfer.write(out));
+}
+
+pub(crate) fn read_transfers(data: &[u8], pos: &mut usize) -> Result<(u64, u64, Vec<ExportedTransfer>), String> {
+    read_arity(data, pos, 3)?;
+    let offset = read_varint(data, pos)?;
+    let total = read_varint(data, pos)?;
+    let count = read_count(data, pos, 32 + 32 + 5)?;
+    let transfers = (0..count).map(|_| ExportedTransfer::read(data, pos)).collect::<Result<Vec<_>, String>>()?;
+    Ok((offset, total, transfers))
+}
+
+pub(crate) fn write_keys(keys: &[[u8; 32]], out: &mut Vec<u8>) {
+    write_varint(keys.len() as u64, out);
+    keys.iter().for_each(|key| out.extend_from_slice(key));
+}
+
+pub(crate) fn write_string(bytes: &[u8], out: &mut Vec<u8>) {
+    write_varint(bytes.len() as u64, out);
+    out.extend_from_slice(bytes);
+}
+
+pub(crate) fn read_bytes<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let end = pos.checked_add(len).filter(|end| *end <= data.len()).ok_or("Unexpected end of data")?;
+    let bytes = &data[*pos..end];
+    *pos = end;
+    Ok(bytes)
+}
+
+pub(crate) fn read_key(data: &[u8], pos: &mut usize) -> Result<[u8; 32], String> {
+    Ok(read_bytes(data, pos, 32)?.try_into().unwrap())
+}
+
+pub(crate) fn read_keys(data: &[u8], pos: &mut usize) -> Result<Vec<[u8; 32]>, String> {
+    let count = read_count(data, pos, 32)?;
+    (0..count).map(|_| read_key(data, pos)).collect()
+}
+
+pub(crate) fn read_string(data: &[u8], pos: &mut usize) -> Result<Vec<u8>, String> {
+    let len = read_count(data, pos, 1)?;
+    Ok(read_bytes(data, pos, len)?.to_vec())
+}
+
+pub(crate) fn read_bool(data: &[u8], pos: &mut usize) -> Result<bool, String> {
+    match read_bytes(data, pos, 1)?[0] {
+        0 => Ok(false),
+        1 => Ok(true),
+        value => Err(format!("Invalid boolean {}", value)),
+    }
+}
+
+pub(crate) fn read_u32(data: &[u8], pos: &mut usize) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(read_bytes(data, pos, 4)?.try_into().unwrap()))
+}
+
+pub(crate) fn read_u64(data: &[u8], pos: &mut usize) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(read_bytes(data, pos, 8)?.try_into().unwrap()))
+}
+
+pub(crate) fn read_varint_u32(data: &[u8], pos: &mut usize) -> Result<u32, String> {
+    u32::try_from(read_varint(data, pos)?).map_err(|_| "Varint does not fit in 32 bits".to_string())
+}
+
+// Reads a container length, rejecting lengths that could not fit in the remaining data given the minimum element size
+pub(crate) fn read_count(data: &[u8], pos: &mut usize, min_element_size: usize) -> Result<usize, String> {
+    let count = read_varint(data, pos)?;
+    match usize::try_from(count) {
+        Ok(count) if count.saturating_mul(min_element_size) <= data.len() - *pos => Ok(count),
+        _ => Err(format!("Length {} exceeds the remaining data", count)),
+    }
+}
+
+// Reads the arity of a pair or tuple
+pub(crate) fn read_arity(data: &[u8], pos: &mut usize, arity: u64) -> Result<(), String> {
+    match read_varint(data, pos)? {
+        found if found == arity => Ok(()),
+        found => Err(format!("Expected {} elements, got {}", arity, found)),
+    }
+}
+
+pub(crate) fn read_version(data: &[u8], pos: &mut usize, max_version: u64, name: &str) -> Result<u64, String> {
+    match read_varint(data, pos)? {
+        version if version <= max_version => Ok(version),
+        version => Err(format!("Unsupported {} version {}", name, version)),
+    }
+}
+
+// Encryption of the reference wallet's `encrypt_with_view_secret_key`: ChaCha20 with a key derived from the private
+// view key, then a signature by the view key of `iv || ciphertext`, appended to it
+pub(crate) fn encrypt_with_view_key(plaintext: &[u8], private_view_key: &PrivateKey) -> Result<Vec<u8>, String> {
+    let key = generate_chacha_key(private_view_key.as_bytes(), 1)?;
+    let mut iv = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut iv);
+    let mut data = iv.to_vec();
+    data.extend(chacha20(plaintext, &key, &iv));
+    let signature = generate_signature(&keccak256(&data), private_view_key);
+    data.extend_from_slice(&signature.to_bytes());
+    Ok(data)
+}
+
+pub(crate) fn decrypt_with_view_key(data: &[u8], private_view_key: &PrivateKey) -> Result<Vec<u8>, String> {
+    if data.len() < 8 + 64 {
+        return Err("Encrypted data is too short".to_string());
+    }
+    let (signed, signature) = data.split_at(data.len() - 64);
+    if !Signature::from_bytes(signature.try_into().unwrap()).verify(&keccak256(signed), &private_view_key.public_key()) {
+        return Err("Data was not encrypted with the view key of this wallet".to_string());
+    }
+    let key = generate_chacha_key(private_view_key.as_bytes(), 1)?;
+    let (iv, ciphertext) = signed.split_at(8);
+    Ok(chacha20(ciphertext, &key, iv.try_into().unwrap()))
+}
@@ -0,0 +1,150 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::keys_file::KeysFile;
+use super::signer::KeyStore;
+use crate::blocks::MonitorHandle;
+use crate::keys::PrivateKey;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// PassphrasePrompt asks the user for the wallet passphrase when an [`AutoLockKeyStore`](struct.AutoLockKeyStore.html)
+/// is locked
+///
+/// Prompts the user can cancel return an error, which is passed on to the caller of the signing operation. Closures
+/// returning the passphrase implement the trait.
+pub trait PassphrasePrompt: Send + Sync {
+    /// Returns the passphrase entered by the user, or an error if none was entered
+    fn passphrase(&self) -> Result<Zeroizing<String>, String>;
+}
+
+impl<F> PassphrasePrompt for F
+where
+    F: Fn() -> Result<Zeroizing<String>, String> + Send + Sync,
+{
+    fn passphrase(&self) -> Result<Zeroizing<String>, String> {
+        self()
+    }
+}
+
+/// AutoLockKeyStore keeps the spend key decrypted in memory while the wallet is in use, and wipes it after an idle
+/// period, for kiosks and point of sale terminals left unattended
+///
+/// The key is decrypted from the `.keys` file of the wallet, see [`KeysFile`](struct.KeysFile.html). Once locked,
+/// the next operation of the [`Signer`](struct.Signer.html) using the keystore asks the passphrase again through the
+/// [`PassphrasePrompt`](trait.PassphrasePrompt.html), the operation fails if the prompt or the decryption fails.
+/// Every operation restarts the idle period.
+///
+/// Idle keys are wiped by [`lock_if_idle`](#method.lock_if_idle), which [`start_timer`](#method.start_timer) calls
+/// on a background thread. Clones share the same key, so a clone can be given to the signer and another one kept to
+/// lock the wallet.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::PrivateKey;
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::{AutoLockKeyStore, KeyStore, KeysFile};
+/// use std::time::Duration;
+/// use zeroize::Zeroizing;
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let keys_file = KeysFile::new(Network::Mainnet, private_spend_key.clone(), 1700000000).to_bytes(b"1234", 1).unwrap();
+/// let keystore = AutoLockKeyStore::new(keys_file, Duration::from_secs(120), || Ok(Zeroizing::new("1234".to_string())));
+/// assert!(keystore.is_locked());
+/// assert_eq!(keystore.load_spend_key().unwrap(), private_spend_key);
+/// assert!(!keystore.is_locked());
+/// keystore.lock();
+/// assert!(keystore.is_locked());
+/// ```
+#[derive(Clone)]
+pub struct AutoLockKeyStore {
+    keys_file: Arc<Vec<u8>>,
+    kdf_rounds: u64,
+    idle_timeout: Duration,
+    prompt: Arc<dyn PassphrasePrompt>,
+    // Decrypted spend key and the time it was last used
+    unlocked: Arc<Mutex<Option<(PrivateKey, Instant)>>>,
+}
+
+impl fmt::Debug for AutoLockKeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoLockKeyStore")
+            .field("idle_timeout", &self.idle_timeout)
+            .field("locked", &self.is_locked())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AutoLockKeyStore {
+    /// Creates a locked keystore over the content of a `.keys` file, locking again after `idle_timeout` without use
+    pub fn new<P: PassphrasePrompt + 'static>(keys_file: Vec<u8>, idle_timeout: Duration, prompt: P) -> AutoLockKeyStore {
+        AutoLockKeyStore {
+            keys_file: Arc::new(keys_file),
+            kdf_rounds: 1,
+            idle_timeout,
+            prompt: Arc::new(prompt),
+            unlocked: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Sets the `--kdf-rounds` the keys file was written with, 1 by default
+    pub fn with_kdf_rounds(mut self, kdf_rounds: u64) -> AutoLockKeyStore {
+        self.kdf_rounds = kdf_rounds;
+        self
+    }
+
+    /// Checks if the spend key is wiped, the next operation then asks the passphrase
+    pub fn is_locked(&self) -> bool {
+        self.unlocked.lock().unwrap().is_none()
+    }
+
+    /// Wipes the spend key now
+    pub fn lock(&self) {
+        self.unlocked.lock().unwrap().take();
+    }
+
+    /// Wipes the spend key if it was not used for the idle timeout, and returns whether the keystore is locked
+    pub fn lock_if_idle(&self) -> bool {
+        let mut unlocked = self.unlocked.lock().unwrap();
+        if unlocked.as_ref().is_some_and(|(_, last_used)| last_used.elapsed() >= self.idle_timeout) {
+            unlocked.take();
+        }
+        unlocked.is_none()
+    }
+
+    /// Calls [`lock_if_idle`](#method.lock_if_idle) every `check_interval` on a background thread, until
+    /// [`MonitorHandle::stop`](../blocks/struct.MonitorHandle.html#method.stop) is called
+    pub fn start_timer(&self, check_interval: Duration) -> MonitorHandle {
+        let keystore = self.clone();
+        MonitorHandle::spawn(check_interval, move || {
+            keystore.lock_if_idle();
+        })
+    }
+}
+
+impl KeyStore for AutoLockKeyStore {
+    fn load_spend_key(&self) -> Result<PrivateKey, String> {
+        let mut unlocked = self.unlocked.lock().unwrap();
+        if let Some((private_spend_key, last_used)) = unlocked.as_mut() {
+            if last_used.elapsed() < self.idle_timeout {
+                *last_used = Instant::now();
+                return Ok(private_spend_key.clone());
+            }
+        }
+        unlocked.take();
+        let passphrase = self.prompt.passphrase()?;
+        let keys_file = KeysFile::from_bytes(&self.keys_file, passphrase.as_bytes(), self.kdf_rounds)?;
+        let private_spend_key = keys_file.private_spend_key.ok_or("Keys file of a watch-only wallet has no spend key")?;
+        *unlocked = Some((private_spend_key.clone(), Instant::now()));
+        Ok(private_spend_key)
+    }
+}
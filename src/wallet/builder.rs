@@ -0,0 +1,1113 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::change::ChangePolicy;
+use super::coin_selection::{CoinSelector, LargestFirst};
+use super::limits::{SpendingLimitError, SpendingPolicy};
+use super::signer::Signer;
+use super::snapshot::{SnapshotOutput, SnapshotTransfer};
+use super::unlock::SPENDABLE_AGE;
+use crate::blocks::fees::FEE_ESTIMATE_GRACE_BLOCKS;
+use crate::blocks::transactions::write_rct_base;
+use crate::blocks::{
+    estimate_tx_weight, get_fee_estimate, get_global_output_indexes, get_last_block_header, get_output_distribution, get_output_histogram, get_outs,
+    get_outs_by_amount, relative_offsets, DaemonNode, FeePriority, OutputDistribution, OutputEntry, PaymentId, RctBase, RctType, Transaction,
+    TransactionPrefix, TxExtra, TxHash, TxInput, TxOutTarget, TxOutput, TxSignatures,
+};
+use crate::crypt::bulletproof_plus_prove;
+use crate::crypt::keccak::keccak256;
+use crate::keys::{
+    commit, commitment_mask, derivation_to_scalar, derive_output_pubkey, derive_subaddress_keys, derive_view_tag, ecdh_encode, generate_key_derivation,
+    needs_additional_tx_keys, zero_commit, Commitment, KeyDerivation, KeyImage, OneTimeKey, OwnedOutput, PrivateKey, PublicKey,
+};
+use crate::utils::varint::write_varint;
+use crate::utils::{
+    decode_address, looks_like_openalias, resolve_destination, AddressInfo, AddressType, DestinationPolicy, OpenAliasResolver, PolicyFinding, ResolvedDestination,
+};
+use curve25519_dalek::Scalar;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, Gamma};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Ring size enforced by consensus since the 15th hard fork
+pub const DEFAULT_RING_SIZE: usize = 16;
+/// Largest transaction weight accepted by consensus, half of the minimal full reward zone minus the coinbase reserve
+pub const MAX_TX_WEIGHT: u64 = 149400;
+/// Most outputs a transaction can have, the limit of an aggregated Bulletproof+
+pub const MAX_TX_OUTPUTS: usize = 16;
+
+// Decoy requests made for a ring before giving up when the daemon keeps returning locked outputs
+const MAX_DECOY_ROUNDS: usize = 10;
+// Parameters of the reference wallet's gamma distribution of the log of output ages in seconds
+const GAMMA_SHAPE: f64 = 19.28;
+const GAMMA_SCALE: f64 = 1.0 / 1.61;
+// Seconds an output is locked for, and the window recent picks are spread over
+const DEFAULT_UNLOCK_TIME: f64 = 1200.0;
+const RECENT_SPEND_WINDOW: u64 = 1800;
+const BLOCK_TIME: f64 = 120.0;
+const BLOCKS_IN_A_YEAR: usize = 86400 * 365 / 120;
+// Picks made per decoy before giving up, bad picks fall out of the spendable outputs or in empty blocks
+const MAX_PICKS_PER_DECOY: usize = 1000;
+
+/// DecoySource picks the decoys of the rings built by a [`TxBuilder`](struct.TxBuilder.html)
+pub trait DecoySource: Send + Sync {
+    /// Returns `count` distinct global indices of RingCT outputs, none of them in `exclude`, picked from the given
+    /// cumulative distribution of RingCT outputs over the blocks
+    fn pick_decoys(&self, distribution: &OutputDistribution, exclude: &BTreeSet<u64>, count: usize) -> Result<Vec<u64>, String>;
+}
+
+/// UniformDecoys picks decoys uniformly among all spendable RingCT outputs
+///
+/// Real spends are mostly recent outputs, so rings of uniform decoys do not look like the reference wallet's ones.
+/// It is good enough for previews but should not be used to sign transactions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UniformDecoys;
+
+impl DecoySource for UniformDecoys {
+    fn pick_decoys(&self, distribution: &OutputDistribution, exclude: &BTreeSet<u64>, count: usize) -> Result<Vec<u64>, String> {
+        let spendable = spendable_outputs(distribution);
+        let available = spendable.saturating_sub(exclude.iter().filter(|index| **index < spendable).count() as u64);
+        if available < count as u64 {
+            return Err(format!("Not enough spendable outputs on chain for {} decoys", count));
+        }
+        let mut rng = rand::thread_rng();
+        let mut picked = BTreeSet::new();
+        while picked.len() < count {
+            let index = rng.gen_range(0..spendable);
+            if !exclude.contains(&index) {
+                picked.insert(index);
+            }
+        }
+        Ok(picked.into_iter().collect())
+    }
+}
+
+/// GammaDecoys picks decoys like the reference wallet, the default decoy source of a [`TxBuilder`](struct.TxBuilder.html)
+///
+/// The age of each decoy in seconds is drawn so that its logarithm follows a gamma distribution fitted on real spends,
+/// then turned into an output index using the average time between outputs of the last year. The block at that index
+/// is taken and one of its outputs is picked uniformly. Rings built this way can not be told apart from the ones of
+/// the reference wallet.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::OutputDistribution;
+/// use libmonero::wallet::{DecoySource, GammaDecoys};
+/// use std::collections::BTreeSet;
+///
+/// // Two years of blocks with 20 outputs each
+/// let distribution = OutputDistribution { amount: 0, start_height: 0, base: 0, distribution: (1..=525600).map(|height| height * 20).collect() };
+/// let decoys = GammaDecoys.pick_decoys(&distribution, &BTreeSet::from([10511000]), 15).unwrap();
+/// assert_eq!(decoys.len(), 15);
+/// assert!(decoys.iter().all(|index| *index < 10511800 && *index != 10511000));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GammaDecoys;
+
+impl DecoySource for GammaDecoys {
+    fn pick_decoys(&self, distribution: &OutputDistribution, exclude: &BTreeSet<u64>, count: usize) -> Result<Vec<u64>, String> {
+        let offsets = &distribution.distribution;
+        // Outputs of all blocks but the last SPENDABLE_AGE ones, as counted by the reference wallet
+        let Some(spendable_offsets) = offsets.len().checked_sub(SPENDABLE_AGE as usize).map(|end| &offsets[..end]) else {
+            return Err("No spendable RingCT outputs on chain".to_string());
+        };
+        let spendable = spendable_offsets.last().copied().unwrap_or(distribution.base);
+        if spendable <= distribution.base {
+            return Err("No spendable RingCT outputs on chain".to_string());
+        }
+        let available = (spendable - distribution.base).saturating_sub(exclude.iter().filter(|index| (distribution.base..spendable).contains(*index)).count() as u64);
+        if available < count as u64 {
+            return Err(format!("Not enough spendable outputs on chain for {} decoys", count));
+        }
+        // Same assumption as the reference wallet, a constant block time over the last year
+        let blocks_to_consider = offsets.len().min(BLOCKS_IN_A_YEAR);
+        let first_considered = match offsets.len() - blocks_to_consider {
+            0 => distribution.base,
+            skipped => offsets[skipped - 1],
+        };
+        let outputs_to_consider = (offsets[offsets.len() - 1] - first_considered).max(1);
+        let average_output_time = BLOCK_TIME * blocks_to_consider as f64 / outputs_to_consider as f64;
+
+        let gamma = Gamma::new(GAMMA_SHAPE, GAMMA_SCALE).map_err(|error| error.to_string())?;
+        let mut rng = rand::thread_rng();
+        let mut picked = BTreeSet::new();
+        for _ in 0..count * MAX_PICKS_PER_DECOY {
+            if picked.len() == count {
+                break;
+            }
+            let mut age = gamma.sample(&mut rng).exp();
+            if age > DEFAULT_UNLOCK_TIME {
+                age -= DEFAULT_UNLOCK_TIME;
+            } else {
+                age = rng.gen_range(0..RECENT_SPEND_WINDOW) as f64;
+            }
+            let age_in_outputs = (age / average_output_time) as u64;
+            if age_in_outputs >= spendable - distribution.base {
+                continue;
+            }
+            // First block whose cumulative count reaches the output, the reference wallet's lower bound
+            let output_index = spendable - 1 - age_in_outputs;
+            let block = spendable_offsets.partition_point(|offset| *offset < output_index);
+            let block_start = match block {
+                0 => distribution.base,
+                block => spendable_offsets[block - 1],
+            };
+            let block_outputs = spendable_offsets[block] - block_start;
+            if block_outputs == 0 {
+                continue;
+            }
+            let index = block_start + rng.gen_range(0..block_outputs);
+            if !exclude.contains(&index) {
+                picked.insert(index);
+            }
+        }
+        if picked.len() < count {
+            return Err(format!("Could not pick {} decoys", count));
+        }
+        Ok(picked.into_iter().collect())
+    }
+}
+
+// Number of RingCT outputs at least SPENDABLE_AGE blocks deep
+fn spendable_outputs(distribution: &OutputDistribution) -> u64 {
+    match distribution.distribution.len().checked_sub(SPENDABLE_AGE as usize) {
+        Some(index) => distribution.distribution[index],
+        None => distribution.base,
+    }
+}
+
+/// PreviewInput is an input selected by a [`TxBuilder`](struct.TxBuilder.html) dry run, with its ring
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreviewInput {
+    pub output: SnapshotOutput,
+    /// Global index of the output among RingCT outputs, or among the outputs of its amount for pre-RingCT outputs
+    pub global_index: u64,
+    /// Ring members and their global index, sorted by index, the spent output included
+    pub ring: Vec<(u64, OutputEntry)>,
+}
+
+/// TxPreview is everything a transaction would contain, returned by [`TxBuilder::dry_run`](struct.TxBuilder.html#method.dry_run)
+/// for confirmation screens
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxPreview {
+    pub inputs: Vec<PreviewInput>,
+    /// Address and amount in piconeros of each payment
+    pub destinations: Vec<(String, u64)>,
+    /// Sum of the destination amounts
+    pub amount: u64,
+    pub fee: u64,
+    pub change: u64,
+    /// Account and subaddress index receiving the change
+    pub change_subaddress: (u32, u32),
+    /// Estimated weight of the transaction, see [`estimate_tx_weight`](../blocks/fn.estimate_tx_weight.html)
+    pub weight: u64,
+    pub fee_per_byte: u64,
+    /// Warnings of the destination policy
+    pub warnings: Vec<PolicyFinding>,
+}
+
+/// SignedTx is a transaction signed by [`TxBuilder::sign`](struct.TxBuilder.html#method.sign), ready to be broadcast
+#[derive(Clone, Debug)]
+pub struct SignedTx {
+    pub transaction: Transaction,
+    pub tx_hash: TxHash,
+    /// Transaction secret key, needed to prove the payments
+    pub tx_secret_key: PrivateKey,
+    /// Secret key of the additional public key of every output, empty if the transaction has none
+    pub additional_secret_keys: Vec<PrivateKey>,
+    /// Address and amount in piconeros of each payment, the change excluded
+    pub destinations: Vec<(String, u64)>,
+    pub fee: u64,
+}
+
+impl SignedTx {
+    /// Returns the transaction in its consensus binary format
+    pub fn to_blob(&self) -> Vec<u8> {
+        self.transaction.to_blob()
+    }
+
+    /// Returns the transaction blob in hex, as taken by [`send_raw_transaction`](../blocks/fn.send_raw_transaction.html)
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_blob())
+    }
+}
+
+// Resolver of OpenAlias destinations and the callback confirming every resolved address, always set together
+#[derive(Clone)]
+pub(crate) struct OpenAliasLookup {
+    resolver: Arc<dyn OpenAliasResolver + Send + Sync>,
+    confirm: Arc<dyn Fn(&ResolvedDestination) -> bool + Send + Sync>,
+}
+
+impl OpenAliasLookup {
+    pub(crate) fn new<R, C>(resolver: R, confirm: C) -> OpenAliasLookup
+    where
+        R: OpenAliasResolver + Send + Sync + 'static,
+        C: Fn(&ResolvedDestination) -> bool + Send + Sync + 'static,
+    {
+        OpenAliasLookup { resolver: Arc::new(resolver), confirm: Arc::new(confirm) }
+    }
+}
+
+impl fmt::Debug for OpenAliasLookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OpenAliasLookup(..)")
+    }
+}
+
+/// TxBuilder builds a transaction spending outputs of one account of the wallet
+///
+/// Inputs are selected largest first until they cover the destinations and the fee, which grows with every input.
+/// [`dry_run`](#method.dry_run) selects the inputs and their rings for confirmation, then [`sign`](#method.sign)
+/// turns the preview into a signed RingCT transaction with CLSAG ring signatures and a Bulletproofs+ range proof.
+///
+/// Destinations may also be OpenAlias domains such as `donate.getmonero.org`, which are resolved when building once a
+/// resolver is given with [`with_openalias_resolver`](#method.with_openalias_resolver).
+///
+/// Example:
+/// ```no_run
+/// use libmonero::blocks::{DaemonNode, FeePriority};
+/// use libmonero::wallet::{TxBuilder, WalletSnapshot};
+///
+/// let snapshot = WalletSnapshot::from_json(&std::fs::read_to_string("wallet.json").unwrap()).unwrap();
+/// let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+/// let preview = TxBuilder::new(snapshot.outputs.clone())
+///     .with_destination(address, 1_000_000_000_000)
+///     .with_priority(FeePriority::Normal)
+///     .dry_run(DaemonNode::cake_wallet_default())
+///     .unwrap();
+/// println!("Fee: {}, change: {}", preview.fee, preview.change);
+/// ```
+#[derive(Clone)]
+pub struct TxBuilder {
+    outputs: Vec<SnapshotOutput>,
+    account: u32,
+    destinations: Vec<(String, u64)>,
+    priority: FeePriority,
+    change_policy: ChangePolicy,
+    policy: DestinationPolicy,
+    spending_policy: SpendingPolicy,
+    ring_size: usize,
+    decoys: Arc<dyn DecoySource>,
+    selector: Arc<dyn CoinSelector>,
+    pinned: Vec<(TxHash, u64)>,
+    key_images: HashMap<OneTimeKey, KeyImage>,
+    openalias: Option<OpenAliasLookup>,
+    spending_history: Option<(Vec<SnapshotTransfer>, u64)>,
+}
+
+impl TxBuilder {
+    /// Creates a builder spending from the given wallet outputs, spent and locked ones are skipped
+    pub fn new(outputs: Vec<SnapshotOutput>) -> TxBuilder {
+        TxBuilder {
+            outputs,
+            account: 0,
+            destinations: Vec::new(),
+            priority: FeePriority::Normal,
+            change_policy: ChangePolicy::default(),
+            policy: DestinationPolicy::new(),
+            spending_policy: SpendingPolicy::new(),
+            ring_size: DEFAULT_RING_SIZE,
+            decoys: Arc::new(GammaDecoys),
+            selector: Arc::new(LargestFirst),
+            pinned: Vec::new(),
+            key_images: HashMap::new(),
+            openalias: None,
+            spending_history: None,
+        }
+    }
+
+    /// Spends from the given account instead of account 0
+    pub fn with_account(mut self, account: u32) -> TxBuilder {
+        self.account = account;
+        self
+    }
+
+    /// Adds a payment of `amount` piconeros to the given address, or to the OpenAlias domain given to
+    /// [`with_openalias_resolver`](#method.with_openalias_resolver)
+    pub fn with_destination(mut self, address: &str, amount: u64) -> TxBuilder {
+        self.destinations.push((address.to_string(), amount));
+        self
+    }
+
+    pub fn with_priority(mut self, priority: FeePriority) -> TxBuilder {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_change_policy(mut self, change_policy: ChangePolicy) -> TxBuilder {
+        self.change_policy = change_policy;
+        self
+    }
+
+    /// Checks the destinations with the given policy, its errors stop the build and its warnings end up in the preview
+    pub fn with_policy(mut self, policy: DestinationPolicy) -> TxBuilder {
+        self.policy = policy;
+        self
+    }
+
+    /// Enforces the given spending limits and approval hooks when signing, see [`approve`](#method.approve)
+    pub fn with_spending_policy(mut self, spending_policy: SpendingPolicy) -> TxBuilder {
+        self.spending_policy = spending_policy;
+        self
+    }
+
+    /// Gives the wallet history and the current Unix time the daily limit of the spending policy is computed from when
+    /// signing, [`sign`](#method.sign) fails without them if the policy has a daily limit
+    pub fn with_spending_history(mut self, history: &[SnapshotTransfer], now: u64) -> TxBuilder {
+        self.spending_history = Some((history.to_vec(), now));
+        self
+    }
+
+    pub fn with_ring_size(mut self, ring_size: usize) -> TxBuilder {
+        self.ring_size = ring_size;
+        self
+    }
+
+    /// Picks the decoys with the given source instead of [`GammaDecoys`](struct.GammaDecoys.html)
+    pub fn with_decoy_source<D: DecoySource + 'static>(mut self, decoys: D) -> TxBuilder {
+        self.decoys = Arc::new(decoys);
+        self
+    }
+
+    /// Selects the inputs with the given selector instead of [`LargestFirst`](struct.LargestFirst.html)
+    pub fn with_coin_selector<C: CoinSelector + 'static>(mut self, selector: C) -> TxBuilder {
+        self.selector = Arc::new(selector);
+        self
+    }
+
+    /// Always spends output `index` of the given transaction, the coin selector only adds inputs when pinned outputs
+    /// do not cover the payment
+    pub fn with_pinned_output(mut self, tx_hash: TxHash, index: u64) -> TxBuilder {
+        self.pinned.push((tx_hash, index));
+        self
+    }
+
+    /// Adds the key images of outputs, needed by [`sweep_single`](#method.sweep_single)
+    pub fn with_key_images(mut self, key_images: &[(OneTimeKey, KeyImage)]) -> TxBuilder {
+        self.key_images.extend(key_images.iter().copied());
+        self
+    }
+
+    /// Resolves destinations that look like OpenAlias domains with the given resolver, see
+    /// [`resolve_destination`](../utils/fn.resolve_destination.html)
+    ///
+    /// `confirm` is called with every resolved address and its DNSSEC status, the build fails unless it returns true.
+    /// Without a resolver, OpenAlias destinations are refused.
+    ///
+    /// Example:
+    /// ```no_run
+    /// use libmonero::blocks::DaemonNode;
+    /// use libmonero::utils::OpenAliasResolver;
+    /// use libmonero::wallet::{TxBuilder, WalletSnapshot};
+    ///
+    /// struct StaticResolver;
+    /// impl OpenAliasResolver for StaticResolver {
+    ///     fn resolve_txt(&self, _domain: &str) -> Result<(Vec<String>, bool), String> {
+    ///         Ok((vec!["oa1:xmr recipient_address=42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB;".to_string()], true))
+    ///     }
+    /// }
+    ///
+    /// let snapshot = WalletSnapshot::from_json(&std::fs::read_to_string("wallet.json").unwrap()).unwrap();
+    /// let preview = TxBuilder::new(snapshot.outputs.clone())
+    ///     .with_destination("donate@getmonero.org", 1_000_000_000_000)
+    ///     .with_openalias_resolver(StaticResolver, |resolved| resolved.dnssec_valid)
+    ///     .dry_run(DaemonNode::cake_wallet_default())
+    ///     .unwrap();
+    /// println!("Paying {}", preview.destinations[0].0);
+    /// ```
+    pub fn with_openalias_resolver<R, C>(mut self, resolver: R, confirm: C) -> TxBuilder
+    where
+        R: OpenAliasResolver + Send + Sync + 'static,
+        C: Fn(&ResolvedDestination) -> bool + Send + Sync + 'static,
+    {
+        self.openalias = Some(OpenAliasLookup::new(resolver, confirm));
+        self
+    }
+
+    pub(crate) fn with_openalias_lookup(mut self, openalias: Option<OpenAliasLookup>) -> TxBuilder {
+        self.openalias = openalias;
+        self
+    }
+
+    /// Selects the inputs, fetches their rings and computes the fee and change from the given daemon, without signing
+    /// or broadcasting anything
+    /// Returns the preview of the transaction as a TxPreview struct if succesfull
+    /// Returns an error message if not succesfull
+    pub fn dry_run(&self, node: DaemonNode) -> Result<TxPreview, String> {
+        if self.destinations.iter().any(|(_, amount)| *amount == 0) {
+            return Err("Destination amounts must not be zero".to_string());
+        }
+        let destinations = self.resolve_destinations(&self.destinations)?;
+        let (addresses, warnings) = self.check_destinations(&destinations)?;
+        let amount = destinations
+            .iter()
+            .try_fold(0u64, |sum, (_, amount)| sum.checked_add(*amount))
+            .ok_or("Destination amounts overflow")?;
+
+        let candidates = self.spendable_outputs(&node, false)?;
+        // Change always gets an output, so that payments to a single destination still have 2 outputs
+        let outputs = (destinations.len() + 1).max(2);
+        if outputs > MAX_TX_OUTPUTS {
+            return Err(format!("Too many destinations: {}, at most {}", destinations.len(), MAX_TX_OUTPUTS - 1));
+        }
+        let extra_size = estimate_extra_size(&addresses, outputs);
+        let estimate = get_fee_estimate(FEE_ESTIMATE_GRACE_BLOCKS, node.clone())?;
+        let fee_for_inputs = |inputs: usize| estimate.fee_for_weight(estimate_tx_weight(inputs, outputs, self.ring_size, extra_size), self.priority);
+
+        let mut pinned = Vec::with_capacity(self.pinned.len());
+        for (tx_hash, index) in self.pinned.iter() {
+            let output = candidates
+                .iter()
+                .find(|output| output.tx_hash == *tx_hash && output.index == *index)
+                .ok_or(format!("Pinned output {} of transaction {} is not spendable", index, tx_hash))?;
+            if !pinned.contains(output) {
+                pinned.push(*output);
+            }
+        }
+        let others: Vec<&SnapshotOutput> = candidates.iter().filter(|output| !pinned.contains(output)).copied().collect();
+        let others_owned: Vec<SnapshotOutput> = others.iter().map(|output| (*output).clone()).collect();
+        let pinned_owned: Vec<SnapshotOutput> = pinned.iter().map(|output| (*output).clone()).collect();
+        let positions = self.selector.select(&others_owned, &pinned_owned, amount, &fee_for_inputs)?;
+        let mut selected = pinned;
+        for position in positions {
+            let output = *others.get(position).ok_or("Coin selector picked an unknown output")?;
+            if selected.contains(&output) {
+                return Err("Coin selector picked an output twice".to_string());
+            }
+            selected.push(output);
+        }
+        let total: u64 = selected.iter().map(|output| output.amount).sum();
+        let weight = estimate_tx_weight(selected.len(), outputs, self.ring_size, extra_size);
+        let fee = estimate.fee_for_weight(weight, self.priority);
+        if selected.is_empty() || total < amount.saturating_add(fee) {
+            return Err(format!("Selected inputs of {} do not cover {} plus the fee of {}", total, amount, fee));
+        }
+        let subaddresses: Vec<(u32, u32)> = selected.iter().map(|output| output.subaddress).collect();
+        let change_subaddress = self.change_policy.change_subaddress(&subaddresses)?;
+
+        let distribution = self.ringct_distribution(&node)?;
+        Ok(TxPreview {
+            inputs: self.preview_inputs(&selected, &distribution, &node)?,
+            destinations,
+            amount,
+            fee,
+            change: total - amount - fee,
+            change_subaddress,
+            weight,
+            fee_per_byte: estimate.fee_per_byte(self.priority),
+            warnings,
+        })
+    }
+
+    /// Sweeps the whole unlocked balance of the account to several addresses, split by ratio, without signing or
+    /// broadcasting anything
+    ///
+    /// Every address gets its share of each transaction, after the fee, so the whole sweep follows the ratios up to the
+    /// rounding which goes to the first destination. A single transaction is used unless the inputs do not fit in the
+    /// maximum transaction weight. The builder's own destinations must be empty, the other settings apply.
+    ///
+    /// Arguments:
+    /// - `destinations`: address and share of each destination, for example percentages, at most 15 destinations
+    /// - `node`: the daemon to fetch the chain data from
+    ///
+    /// Returns the previews of the sweep transactions as a Vec<TxPreview> if succesfull
+    /// Returns an error message if not succesfull
+    ///
+    /// Example:
+    /// ```no_run
+    /// use libmonero::blocks::DaemonNode;
+    /// use libmonero::wallet::{TxBuilder, WalletSnapshot};
+    ///
+    /// let snapshot = WalletSnapshot::from_json(&std::fs::read_to_string("wallet.json").unwrap()).unwrap();
+    /// let treasury = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+    /// let payroll = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+    /// let destinations = [(treasury.to_string(), 70), (payroll.to_string(), 30)];
+    /// let previews = TxBuilder::new(snapshot.outputs.clone()).sweep_split(&destinations, DaemonNode::cake_wallet_default()).unwrap();
+    /// println!("Sweeping in {} transactions", previews.len());
+    /// ```
+    pub fn sweep_split(&self, destinations: &[(String, u32)], node: DaemonNode) -> Result<Vec<TxPreview>, String> {
+        let candidates = self.spendable_outputs(&node, false)?;
+        if candidates.is_empty() {
+            return Err("No unlocked funds to sweep".to_string());
+        }
+        let distribution = self.ringct_distribution(&node)?;
+        self.sweep(candidates, destinations, &node, |selected| self.preview_inputs(selected, &distribution, &node))
+    }
+
+    /// Sweeps the whole unlocked balance of the account to a single address, without signing or broadcasting anything
+    ///
+    /// Same as [`sweep_split`](#method.sweep_split) with a single destination: the fee is taken from the swept amount
+    /// and the inputs are spread over several transactions when they do not fit in one. Each transaction gets a second
+    /// output of zero to the wallet, like the reference wallet does, so that sweeps look like payments with change.
+    ///
+    /// Returns the previews of the sweep transactions as a Vec<TxPreview> if succesfull
+    /// Returns an error message if not succesfull
+    pub fn sweep_all(&self, destination: &str, node: DaemonNode) -> Result<Vec<TxPreview>, String> {
+        self.sweep_split(&[(destination.to_string(), 1)], node)
+    }
+
+    /// Sweeps the single output with the given key image to an address, without signing or broadcasting anything
+    ///
+    /// The key images of the outputs must be given with [`with_key_images`](#method.with_key_images). Churning an
+    /// output this way breaks the links an observer could draw from the way it was received.
+    ///
+    /// Returns the preview of the sweep transaction as a TxPreview struct if succesfull
+    /// Returns an error message if the output is unknown, spent, locked or does not cover the fee
+    ///
+    /// Example:
+    /// ```no_run
+    /// use libmonero::blocks::DaemonNode;
+    /// use libmonero::keys::PrivateKey;
+    /// use libmonero::utils::Network;
+    /// use libmonero::wallet::Wallet;
+    ///
+    /// let node = DaemonNode::cake_wallet_default();
+    /// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+    /// let mut wallet = Wallet::from_keys(Network::Mainnet, private_spend_key).with_restore_height(3000000);
+    /// wallet.sync(&node, None, |_| {}).unwrap();
+    /// let output = wallet.outputs().iter().find(|output| output.spent_height.is_none()).unwrap();
+    /// let key_image = wallet.key_image(&output.output_key).unwrap();
+    /// let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+    /// let preview = wallet.tx_builder().sweep_single(&key_image, address, node).unwrap();
+    /// println!("Sweeping {} with a fee of {}", preview.amount, preview.fee);
+    /// ```
+    pub fn sweep_single(&self, key_image: &KeyImage, destination: &str, node: DaemonNode) -> Result<TxPreview, String> {
+        let output_key = self
+            .key_images
+            .iter()
+            .find(|(_, known)| *known == key_image)
+            .map(|(output_key, _)| *output_key)
+            .ok_or(format!("No output with key image {}", key_image))?;
+        let candidates: Vec<&SnapshotOutput> = self.spendable_outputs(&node, false)?.into_iter().filter(|output| output.output_key == output_key).collect();
+        if candidates.is_empty() {
+            return Err(format!("Output with key image {} is spent or locked", key_image));
+        }
+        let distribution = self.ringct_distribution(&node)?;
+        let mut previews = self.sweep(candidates, &[(destination.to_string(), 1)], &node, |selected| self.preview_inputs(selected, &distribution, &node))?;
+        Ok(previews.remove(0))
+    }
+
+    /// Sweeps the unmixable pre-RingCT outputs of the account to an address, without signing or broadcasting anything,
+    /// monero-wallet-cli's `sweep_unmixable`
+    ///
+    /// Pre-RingCT outputs can only be mixed with outputs of the same amount. Those whose amount has fewer outputs on
+    /// chain than the ring size, as told by [`get_output_histogram`](../blocks/fn.get_output_histogram.html), can never
+    /// fill a ring, so consensus lets them be spent in smaller rings, as long as all rings of the transaction have the
+    /// same size. Each transaction gets rings as large as the unlocked outputs of its rarest amount allow, the members
+    /// being picked at random among the outputs of the same amount fetched with
+    /// [`get_outs_by_amount`](../blocks/fn.get_outs_by_amount.html). As in [`sweep_all`](#method.sweep_all), the fee
+    /// is taken from the swept amount and the inputs are spread over several transactions when they do not fit in one.
+    ///
+    /// Returns the previews of the sweep transactions as a Vec<TxPreview> if succesfull
+    /// Returns an error message if the account has no unlocked unmixable output, they do not cover the fee or the node
+    /// fails
+    ///
+    /// Example:
+    /// ```no_run
+    /// use libmonero::blocks::DaemonNode;
+    /// use libmonero::wallet::{TxBuilder, WalletSnapshot};
+    ///
+    /// let snapshot = WalletSnapshot::from_json(&std::fs::read_to_string("wallet.json").unwrap()).unwrap();
+    /// let previews = TxBuilder::new(snapshot.outputs.clone()).sweep_unmixable(&snapshot.primary_address, DaemonNode::cake_wallet_default()).unwrap();
+    /// println!("Moving {} pre-RingCT outputs", previews.iter().map(|preview| preview.inputs.len()).sum::<usize>());
+    /// ```
+    pub fn sweep_unmixable(&self, destination: &str, node: DaemonNode) -> Result<Vec<TxPreview>, String> {
+        let pre_rct = self.spendable_outputs(&node, true)?;
+        let mut amounts: Vec<u64> = pre_rct.iter().map(|output| output.amount).collect();
+        amounts.sort_unstable();
+        amounts.dedup();
+        let histogram = match amounts.is_empty() {
+            true => Vec::new(),
+            false => get_output_histogram(&amounts, true, node.clone())?,
+        };
+        // Consensus counts every output of the amount, locked ones included
+        let total_instances = |amount: u64| histogram.iter().find(|entry| entry.amount == amount).map_or(0, |entry| entry.total_instances);
+        let candidates: Vec<&SnapshotOutput> = pre_rct.into_iter().filter(|output| total_instances(output.amount) < self.ring_size as u64).collect();
+        if candidates.is_empty() {
+            return Err("No unlocked unmixable outputs to sweep".to_string());
+        }
+        self.sweep(candidates, &[(destination.to_string(), 1)], &node, |selected| self.unmixable_inputs(selected, total_instances, &node))
+    }
+
+    fn sweep<F>(&self, candidates: Vec<&SnapshotOutput>, destinations: &[(String, u32)], node: &DaemonNode, preview_inputs: F) -> Result<Vec<TxPreview>, String>
+    where
+        F: Fn(&[&SnapshotOutput]) -> Result<Vec<PreviewInput>, String>,
+    {
+        if !self.destinations.is_empty() {
+            return Err("Builder destinations are not allowed in a sweep".to_string());
+        }
+        if destinations.len() >= MAX_TX_OUTPUTS {
+            return Err(format!("Too many destinations: {}, at most {}", destinations.len(), MAX_TX_OUTPUTS - 1));
+        }
+        if destinations.iter().any(|(_, ratio)| *ratio == 0) {
+            return Err("Destination ratios must not be zero".to_string());
+        }
+        let ratios_sum: u128 = destinations.iter().map(|(_, ratio)| *ratio as u128).sum();
+        let shares = self.resolve_destinations(&destinations.iter().map(|(address, ratio)| (address.clone(), *ratio as u64)).collect::<Vec<_>>())?;
+        let (addresses, warnings) = self.check_destinations(&shares)?;
+
+        // No change, but transactions have at least 2 outputs
+        let outputs = destinations.len().max(2);
+        let extra_size = estimate_extra_size(&addresses, outputs);
+        let max_inputs = (1..=candidates.len())
+            .take_while(|inputs| estimate_tx_weight(*inputs, outputs, self.ring_size, extra_size) <= MAX_TX_WEIGHT)
+            .last()
+            .ok_or("Transaction weight limit is too low for a single input")?;
+        // Spread the inputs evenly over as few transactions as possible
+        let transactions = candidates.len().div_ceil(max_inputs);
+        let per_transaction = candidates.len().div_ceil(transactions);
+
+        let estimate = get_fee_estimate(FEE_ESTIMATE_GRACE_BLOCKS, node.clone())?;
+        let mut previews = Vec::with_capacity(transactions);
+        for selected in candidates.chunks(per_transaction) {
+            let inputs = preview_inputs(selected)?;
+            let ring_size = inputs.iter().map(|input| input.ring.len()).max().unwrap_or(self.ring_size);
+            let total: u64 = selected.iter().map(|output| output.amount).sum();
+            let weight = estimate_tx_weight(selected.len(), outputs, ring_size, extra_size);
+            let fee = estimate.fee_for_weight(weight, self.priority);
+            let amount = total.checked_sub(fee).ok_or(format!("Inputs of {} do not cover the fee of {}", total, fee))?;
+            let mut split: Vec<(String, u64)> = shares
+                .iter()
+                .map(|(address, ratio)| (address.clone(), (amount as u128 * *ratio as u128 / ratios_sum) as u64))
+                .collect();
+            split[0].1 += amount - split.iter().map(|(_, share)| share).sum::<u64>();
+            if split.iter().any(|(_, share)| *share == 0) {
+                return Err(format!("Inputs of {} are too small to be split", total));
+            }
+            let subaddresses: Vec<(u32, u32)> = selected.iter().map(|output| output.subaddress).collect();
+            previews.push(TxPreview {
+                inputs,
+                destinations: split,
+                amount,
+                fee,
+                change: 0,
+                change_subaddress: self.change_policy.change_subaddress(&subaddresses)?,
+                weight,
+                fee_per_byte: estimate.fee_per_byte(self.priority),
+                warnings: warnings.clone(),
+            });
+        }
+        Ok(previews)
+    }
+
+    /// Checks a preview of this builder against its spending policy, before asking the user to confirm it
+    ///
+    /// `history` is the wallet history the daily limit is computed from and `now` the current Unix time.
+    /// [`sign`](#method.sign) runs the same check again with the history given to
+    /// [`with_spending_history`](#method.with_spending_history).
+    pub fn approve(&self, preview: &TxPreview, history: &[SnapshotTransfer], now: u64) -> Result<(), SpendingLimitError> {
+        self.spending_policy.check(preview, history, now)
+    }
+
+    /// Signs a preview of this builder, returned by [`dry_run`](#method.dry_run) or [`sweep_split`](#method.sweep_split),
+    /// with the spend key of the signer, without broadcasting anything
+    ///
+    /// Outputs are shuffled and get one-time keys with view tags, the change goes to the preview's change subaddress
+    /// of the signer's wallet. Transactions with 2 outputs carry an encrypted dummy payment ID unless an integrated
+    /// address is paid, like the reference wallet. Nothing is signed unless the preview passes the spending policy, see
+    /// [`approve`](#method.approve).
+    ///
+    /// Returns the signed transaction as a SignedTx struct if succesfull
+    /// Returns an error message if the spending policy refuses the transaction, the inputs do not belong to the signer,
+    /// the keystore refuses or the preview is inconsistent
+    ///
+    /// Example:
+    /// ```no_run
+    /// use libmonero::blocks::{send_raw_transaction, DaemonNode};
+    /// use libmonero::keys::PrivateKey;
+    /// use libmonero::wallet::{MemoryKeyStore, Signer, TxBuilder, WalletSnapshot};
+    ///
+    /// let snapshot = WalletSnapshot::from_json(&std::fs::read_to_string("wallet.json").unwrap()).unwrap();
+    /// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+    /// let signer = Signer::new(MemoryKeyStore::new(private_spend_key.clone()), private_spend_key.derive_view_key()).unwrap();
+    /// let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+    /// let builder = TxBuilder::new(snapshot.outputs.clone()).with_destination(address, 1_000_000_000_000);
+    /// let preview = builder.dry_run(DaemonNode::cake_wallet_default()).unwrap();
+    /// let signed = builder.sign(&preview, &signer).unwrap();
+    /// send_raw_transaction(signed.to_hex(), false, DaemonNode::cake_wallet_default()).unwrap();
+    /// ```
+    pub fn sign(&self, preview: &TxPreview, signer: &Signer) -> Result<SignedTx, String> {
+        if preview.inputs.is_empty() {
+            return Err("Transaction has no input".to_string());
+        }
+        let inputs_total = preview.inputs.iter().try_fold(0u64, |sum, input| sum.checked_add(input.output.amount)).ok_or("Input amounts overflow")?;
+        let outputs_total = preview.destinations.iter().try_fold(preview.change, |sum, (_, amount)| sum.checked_add(*amount));
+        if outputs_total.and_then(|total| total.checked_add(preview.fee)) != Some(inputs_total) {
+            return Err("Inputs do not match the destinations, change and fee of the preview".to_string());
+        }
+        let (history, now) = match &self.spending_history {
+            Some((history, now)) => (history.as_slice(), *now),
+            None if self.spending_policy.has_daily_limit() => {
+                return Err("The daily limit of the spending policy needs the wallet history, see with_spending_history".to_string());
+            }
+            None => (&[][..], 0),
+        };
+        self.spending_policy.check(preview, history, now).map_err(|e| e.to_string())?;
+        let (addresses, _) = self.check_destinations(&preview.destinations)?;
+        if preview.destinations.len() + 1 > MAX_TX_OUTPUTS {
+            return Err(format!("Too many destinations: {}, at most {}", preview.destinations.len(), MAX_TX_OUTPUTS - 1));
+        }
+
+        // Spent outputs with their ring, sorted by key image in decreasing order like the reference wallet
+        let view_pair = signer.view_pair();
+        let mut inputs = Vec::with_capacity(preview.inputs.len());
+        for input in preview.inputs.iter() {
+            let real_index = input
+                .ring
+                .iter()
+                .position(|(index, entry)| *index == input.global_index && entry.key == input.output.output_key)
+                .ok_or(format!("Ring of output {} of transaction {} does not contain it", input.output.index, input.output.tx_hash))?;
+            let ring: Vec<(OneTimeKey, Commitment)> = input.ring.iter().map(|(_, entry)| (entry.key, entry.mask)).collect();
+            let mask = output_mask(&view_pair.view, &input.output, &ring[real_index].1)?;
+            let output = OwnedOutput {
+                index: input.output.index,
+                amount: input.output.amount,
+                output_key: input.output.output_key,
+                tx_public_key: input.output.tx_public_key,
+                pre_rct: input.output.pre_rct,
+                subaddress: input.output.subaddress,
+            };
+            let offsets: Vec<u64> = input.ring.iter().map(|(index, _)| *index).collect();
+            inputs.push((output, offsets, ring, real_index, mask));
+        }
+        let owned: Vec<OwnedOutput> = inputs.iter().map(|(output, ..)| output.clone()).collect();
+        let key_images = signer.key_images(&owned)?;
+        let mut inputs: Vec<_> = inputs.into_iter().zip(key_images).collect();
+        inputs.sort_by(|(_, a), (_, b)| b.as_bytes().cmp(a.as_bytes()));
+
+        // Change to the signer's own subaddress, outputs in random order
+        let (account, index) = preview.change_subaddress;
+        let (public_spend_key, public_view_key) = derive_subaddress_keys(view_pair, account, index);
+        let change = AddressInfo {
+            network: addresses[0].network,
+            address_type: if (account, index) == (0, 0) { AddressType::Standard } else { AddressType::Subaddress },
+            public_spend_key,
+            public_view_key,
+            payment_id: None,
+        };
+        let mut rng = rand::thread_rng();
+        let mut outputs: Vec<(&AddressInfo, u64, bool)> = addresses.iter().zip(preview.destinations.iter()).map(|(info, (_, amount))| (info, *amount, false)).collect();
+        // Sweeps to several destinations have no change, a single destination gets a zero change output
+        if preview.change > 0 || preview.destinations.len() < 2 {
+            outputs.push((&change, preview.change, true));
+        }
+        outputs.shuffle(&mut rng);
+
+        // Transactions paying a single subaddress use r * D as public key, subaddresses along other addresses need
+        // one additional public key per output
+        let tx_secret_key = random_private_key(&mut rng);
+        let additional = needs_additional_tx_keys(&addresses);
+        let tx_public_key = match addresses.iter().find(|info| info.address_type == AddressType::Subaddress) {
+            Some(subaddress) if !additional => PublicKey::from_point(subaddress.public_spend_key.to_point() * tx_secret_key.to_scalar()),
+            _ => tx_secret_key.public_key(),
+        };
+        let additional_secret_keys: Vec<PrivateKey> = match additional {
+            true => outputs.iter().map(|_| random_private_key(&mut rng)).collect(),
+            false => Vec::new(),
+        };
+        let mut tx_outputs = Vec::with_capacity(outputs.len());
+        let mut additional_public_keys = Vec::with_capacity(additional_secret_keys.len());
+        let mut amounts = Vec::with_capacity(outputs.len());
+        let mut masks = Vec::with_capacity(outputs.len());
+        let mut encrypted_amounts = Vec::with_capacity(outputs.len());
+        for (position, (info, amount, is_change)) in outputs.iter().enumerate() {
+            let output_index = position as u64;
+            let secret_key = match info.address_type {
+                AddressType::Subaddress if additional => &additional_secret_keys[position],
+                _ => &tx_secret_key,
+            };
+            // The change of transactions without additional keys is derived by the receiving side, a * R
+            let derivation = match *is_change && !additional {
+                true => generate_key_derivation(&tx_public_key, &view_pair.view),
+                false => generate_key_derivation(&info.public_view_key, secret_key),
+            };
+            if let Some(secret_key) = additional_secret_keys.get(position) {
+                additional_public_keys.push(match info.address_type {
+                    AddressType::Subaddress => PublicKey::from_point(info.public_spend_key.to_point() * secret_key.to_scalar()),
+                    _ => secret_key.public_key(),
+                });
+            }
+            let shared_secret = derivation_to_scalar(&derivation, output_index);
+            let mask = commitment_mask(&shared_secret);
+            tx_outputs.push(TxOutput {
+                amount: 0,
+                target: TxOutTarget::ToTaggedKey {
+                    key: derive_output_pubkey(&derivation, output_index, &info.public_spend_key).into(),
+                    view_tag: derive_view_tag(&derivation, output_index),
+                },
+            });
+            encrypted_amounts.push(ecdh_encode(*amount, &mask, &shared_secret, true));
+            amounts.push(*amount);
+            masks.push(mask);
+        }
+
+        let mut extra = TxExtra::new().with_tx_public_key(&tx_public_key);
+        if additional {
+            extra = extra.with_additional_public_keys(&additional_public_keys);
+        }
+        let payment_id = match addresses.iter().find_map(|info| info.payment_id.map(|payment_id| (info, payment_id))) {
+            Some((info, payment_id)) => Some((info, payment_id)),
+            None if outputs.len() == 2 => Some((&addresses[0], [0; 8])),
+            None => None,
+        };
+        if let Some((info, payment_id)) = payment_id {
+            let derivation = generate_key_derivation(&info.public_view_key, &tx_secret_key);
+            extra = extra.with_payment_id(PaymentId::Encrypted(encrypt_payment_id(payment_id, &derivation)));
+        }
+
+        // Pseudo outputs hide the input amounts with masks summing to the output masks, so that inputs and outputs
+        // balance out with the fee
+        let proof = bulletproof_plus_prove(&amounts, &masks)?;
+        let mut pseudo_masks: Vec<Zeroizing<Scalar>> = (1..inputs.len()).map(|_| Zeroizing::new(random_scalar(&mut rng))).collect();
+        let last = masks.iter().sum::<Scalar>() - pseudo_masks.iter().map(|mask| **mask).sum::<Scalar>();
+        pseudo_masks.push(Zeroizing::new(last));
+        let pseudo_outs: Vec<Commitment> = inputs.iter().zip(pseudo_masks.iter()).map(|((input, _), mask)| commit(input.0.amount, mask)).collect();
+
+        let prefix = TransactionPrefix {
+            version: 2,
+            unlock_time: 0,
+            inputs: inputs
+                .iter()
+                .map(|((output, offsets, ..), key_image)| {
+                    Ok(TxInput::ToKey {
+                        // Pre-RingCT outputs are only mixed with outputs of their clear amount
+                        amount: if output.pre_rct { output.amount } else { 0 },
+                        key_offsets: relative_offsets(offsets)?,
+                        key_image: *key_image,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            outputs: tx_outputs,
+            extra: extra.to_bytes(),
+        };
+        let base = RctBase {
+            rct_type: RctType::BulletproofPlus,
+            fee: preview.fee,
+            pseudo_outs: Vec::new(),
+            encrypted_amounts,
+            commitments: amounts.iter().zip(masks.iter()).map(|(amount, mask)| commit(*amount, mask)).collect(),
+        };
+        let mut transaction = Transaction { prefix, signatures: TxSignatures::V2 { base: base.clone(), prunable: Vec::new() } };
+        let mut rct_base = Vec::new();
+        write_rct_base(&base, &mut rct_base);
+        let message = keccak256(&[transaction.prefix_hash(), keccak256(&rct_base), keccak256(&proof.keys())].concat());
+
+        // Prunable part: the range proof, the ring signatures and the pseudo outputs
+        let mut prunable = Vec::new();
+        write_varint(1, &mut prunable);
+        prunable.extend_from_slice(&proof.to_bytes());
+        for (((output, _, ring, real_index, mask), _), pseudo_mask) in inputs.iter().zip(pseudo_masks.iter()) {
+            let signature = signer.sign_clsag(output, &message, ring, *real_index, mask, pseudo_mask)?;
+            signature.s.iter().for_each(|s| prunable.extend_from_slice(s));
+            prunable.extend_from_slice(&signature.c1);
+            prunable.extend_from_slice(&signature.D);
+        }
+        pseudo_outs.iter().for_each(|pseudo_out| prunable.extend_from_slice(pseudo_out.as_bytes()));
+        transaction.signatures = TxSignatures::V2 { base, prunable };
+
+        Ok(SignedTx {
+            tx_hash: transaction.hash()?,
+            transaction,
+            tx_secret_key,
+            additional_secret_keys,
+            destinations: preview.destinations.clone(),
+            fee: preview.fee,
+        })
+    }
+
+    // Replaces OpenAlias destinations with the addresses they resolve to, once confirmed
+    fn resolve_destinations(&self, destinations: &[(String, u64)]) -> Result<Vec<(String, u64)>, String> {
+        let mut resolved = Vec::with_capacity(destinations.len());
+        for (destination, amount) in destinations.iter() {
+            if !looks_like_openalias(destination) {
+                resolved.push((destination.clone(), *amount));
+                continue;
+            }
+            let openalias = self.openalias.as_ref().ok_or(format!("Destination {} is an OpenAlias, but no resolver was given", destination))?;
+            let address = resolve_destination(destination, openalias.resolver.as_ref(), |resolved| (openalias.confirm)(resolved))?;
+            resolved.push((address, *amount));
+        }
+        Ok(resolved)
+    }
+
+    // Checks the destinations with the destination policy, returns the decoded addresses and the policy warnings
+    fn check_destinations(&self, destinations: &[(String, u64)]) -> Result<(Vec<AddressInfo>, Vec<PolicyFinding>), String> {
+        if destinations.is_empty() {
+            return Err("Transaction has no destination".to_string());
+        }
+        if self.ring_size < 2 {
+            return Err(format!("Invalid ring size: {}", self.ring_size));
+        }
+        let warnings = self.policy.enforce(destinations)?;
+        let addresses = destinations
+            .iter()
+            .map(|(address, _)| decode_address(address))
+            .collect::<Result<Vec<AddressInfo>, String>>()?;
+        if addresses.iter().filter(|info| info.address_type == AddressType::Integrated).count() > 1 {
+            return Err("Only one integrated address can be paid per transaction".to_string());
+        }
+        Ok((addresses, warnings))
+    }
+
+    // Unspent RingCT or pre-RingCT outputs of the account that can be spent in the next block, largest first
+    fn spendable_outputs(&self, node: &DaemonNode, pre_rct: bool) -> Result<Vec<&SnapshotOutput>, String> {
+        let header = get_last_block_header(node.clone())?;
+        let chain_height = header.height + 1;
+        let mut candidates: Vec<&SnapshotOutput> = self
+            .outputs
+            .iter()
+            .filter(|output| output.subaddress.0 == self.account && output.spent_height.is_none() && output.pre_rct == pre_rct)
+            .filter(|output| output.is_spendable_at(chain_height, header.timestamp))
+            .collect();
+        candidates.sort_by_key(|output| std::cmp::Reverse(output.amount));
+        Ok(candidates)
+    }
+
+    fn ringct_distribution(&self, node: &DaemonNode) -> Result<OutputDistribution, String> {
+        get_output_distribution(&[0], 0, 0, node.clone())?
+            .into_iter()
+            .next()
+            .ok_or("Error while getting the output distribution: no RingCT distribution".to_string())
+    }
+
+    fn preview_inputs(&self, selected: &[&SnapshotOutput], distribution: &OutputDistribution, node: &DaemonNode) -> Result<Vec<PreviewInput>, String> {
+        let mut inputs = Vec::with_capacity(selected.len());
+        for output in selected {
+            let indexes = get_global_output_indexes(output.tx_hash, node.clone())?;
+            let global_index = *indexes
+                .get(output.index as usize)
+                .ok_or(format!("Transaction {} has no output {}", output.tx_hash, output.index))?;
+            let ring = self.build_ring(output, global_index, distribution, node)?;
+            inputs.push(PreviewInput { output: (*output).clone(), global_index, ring });
+        }
+        Ok(inputs)
+    }
+
+    // Fetches the real output and unlocked decoys until the ring is full
+    fn build_ring(&self, output: &SnapshotOutput, global_index: u64, distribution: &OutputDistribution, node: &DaemonNode) -> Result<Vec<(u64, OutputEntry)>, String> {
+        let real = get_outs(&[global_index], node.clone())?[0];
+        if real.key != output.output_key {
+            return Err(format!("Global output {} is not output {} of transaction {}", global_index, output.index, output.tx_hash));
+        }
+        let mut ring = BTreeMap::from([(global_index, real)]);
+        let mut used = BTreeSet::from([global_index]);
+        for _ in 0..MAX_DECOY_ROUNDS {
+            if ring.len() >= self.ring_size {
+                break;
+            }
+            let picked = self.decoys.pick_decoys(distribution, &used, self.ring_size - ring.len())?;
+            if picked.iter().any(|index| used.contains(index)) {
+                return Err("Decoy source picked an excluded output".to_string());
+            }
+            used.extend(picked.iter().copied());
+            let entries = get_outs(&picked, node.clone())?;
+            ring.extend(picked.into_iter().zip(entries).filter(|(_, entry)| entry.unlocked));
+        }
+        if ring.len() < self.ring_size {
+            return Err(format!("Could not find {} unlocked decoys", self.ring_size - 1));
+        }
+        Ok(ring.into_iter().collect())
+    }
+
+    // Builds the rings of pre-RingCT outputs among the unlocked outputs of their amount, all of the size the rarest
+    // amount allows
+    fn unmixable_inputs<T>(&self, selected: &[&SnapshotOutput], total_instances: T, node: &DaemonNode) -> Result<Vec<PreviewInput>, String>
+    where
+        T: Fn(u64) -> u64,
+    {
+        let mut unlocked: BTreeMap<u64, Vec<(u64, OutputEntry)>> = BTreeMap::new();
+        for output in selected {
+            if unlocked.contains_key(&output.amount) {
+                continue;
+            }
+            let indices: Vec<u64> = (0..total_instances(output.amount)).collect();
+            let entries = get_outs_by_amount(output.amount, &indices, node.clone())?;
+            unlocked.insert(output.amount, indices.into_iter().zip(entries).filter(|(_, entry)| entry.unlocked).collect());
+        }
+        let ring_size = unlocked.values().map(|entries| entries.len()).min().unwrap_or(0).min(self.ring_size);
+        if ring_size == 0 {
+            return Err("Outputs to sweep are not unlocked on chain".to_string());
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut inputs = Vec::with_capacity(selected.len());
+        for output in selected {
+            let indexes = get_global_output_indexes(output.tx_hash, node.clone())?;
+            let global_index = *indexes
+                .get(output.index as usize)
+                .ok_or(format!("Transaction {} has no output {}", output.tx_hash, output.index))?;
+            let members = &unlocked[&output.amount];
+            let real = members
+                .iter()
+                .find(|(index, _)| *index == global_index)
+                .ok_or(format!("Output {} of transaction {} is not unlocked on chain", output.index, output.tx_hash))?;
+            if real.1.key != output.output_key {
+                return Err(format!("Output {} of amount {} is not output {} of transaction {}", global_index, output.amount, output.index, output.tx_hash));
+            }
+            let decoys: Vec<&(u64, OutputEntry)> = members.iter().filter(|(index, _)| *index != global_index).collect();
+            let mut ring: Vec<(u64, OutputEntry)> = decoys.choose_multiple(&mut rng, ring_size - 1).map(|member| **member).collect();
+            ring.push(*real);
+            ring.sort_by_key(|(index, _)| *index);
+            inputs.push(PreviewInput { output: (*output).clone(), global_index, ring });
+        }
+        Ok(inputs)
+    }
+}
+
+// Size of the tx_extra of the transaction: the transaction public key, the additional public keys needed when paying
+// subaddresses along other addresses, and the encrypted payment ID, a dummy one in 2 output transactions
+pub(crate) fn estimate_extra_size(addresses: &[AddressInfo], outputs: usize) -> usize {
+    let subaddresses = addresses.iter().filter(|info| info.address_type == AddressType::Subaddress).count();
+    let mut size = 1 + 32;
+    if subaddresses > 0 && (subaddresses < addresses.len() || subaddresses > 1) {
+        size += 2 + 32 * outputs;
+    }
+    if outputs == 2 || addresses.iter().any(|info| info.address_type == AddressType::Integrated) {
+        size += 2 + 1 + 8;
+    }
+    size
+}
+
+// Mask of the commitment of an owned output, derived from the shared secret since RingCT type 4, 1 for the
+// commitments of clear amounts of miner transactions
+pub(crate) fn output_mask(private_view_key: &PrivateKey, output: &SnapshotOutput, commitment: &Commitment) -> Result<Scalar, String> {
+    let derivation = generate_key_derivation(&output.tx_public_key, private_view_key);
+    let mask = commitment_mask(&derivation_to_scalar(&derivation, output.index));
+    if commit(output.amount, &mask) == *commitment {
+        return Ok(mask);
+    }
+    if zero_commit(output.amount) == *commitment {
+        return Ok(Scalar::ONE);
+    }
+    Err(format!("Commitment of output {} of transaction {} does not match its amount", output.index, output.tx_hash))
+}
+
+// Payment ID XORed with Keccak256(derivation || 0x8d), monero-core's `encrypt_payment_id`
+fn encrypt_payment_id(payment_id: [u8; 8], derivation: &KeyDerivation) -> [u8; 8] {
+    let key = keccak256(&[derivation.as_bytes().as_slice(), &[0x8d]].concat());
+    let mut encrypted = payment_id;
+    encrypted.iter_mut().zip(key.iter()).for_each(|(byte, key)| *byte ^= key);
+    encrypted
+}
+
+fn random_scalar<R: RngCore>(rng: &mut R) -> Scalar {
+    let mut bytes = Zeroizing::new([0u8; 64]);
+    rng.fill_bytes(&mut *bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+fn random_private_key<R: RngCore>(rng: &mut R) -> PrivateKey {
+    PrivateKey::from_bytes_mod_order(random_scalar(rng).to_bytes())
+}
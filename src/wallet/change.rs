@@ -0,0 +1,61 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+/// ChangePolicy decides which subaddress of the sending account receives the change of a transaction
+///
+/// Change always stays in the account of the spent inputs, the policies only differ in the subaddress index,
+/// which changes what an observer of the recipient's side can link together:
+/// - `SameAsInputs` keeps the funds of each subaddress separate as long as a transaction spends from a single one
+/// - `AccountMain` matches the reference wallet and gathers all change on index 0 of the account
+/// - `FreshIndex` sends every change to a new, never used index, so that change outputs never share a subaddress
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ChangePolicy {
+    /// Subaddress of the spent inputs when they all come from the same one, index 0 of the account otherwise
+    SameAsInputs,
+    /// Index 0 of the account
+    #[default]
+    AccountMain,
+    /// The given unused subaddress index of the account
+    FreshIndex(u32),
+}
+
+impl ChangePolicy {
+    /// Returns the `(account, index)` of the subaddress receiving the change of a transaction spending inputs
+    /// received on the given subaddresses
+    ///
+    /// Fails if there is no input or if inputs come from several accounts, which wallets never mix in one transaction
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::wallet::ChangePolicy;
+    ///
+    /// let inputs = [(1, 4), (1, 4)];
+    /// assert_eq!(ChangePolicy::SameAsInputs.change_subaddress(&inputs), Ok((1, 4)));
+    /// assert_eq!(ChangePolicy::SameAsInputs.change_subaddress(&[(1, 4), (1, 5)]), Ok((1, 0)));
+    /// assert_eq!(ChangePolicy::AccountMain.change_subaddress(&inputs), Ok((1, 0)));
+    /// assert_eq!(ChangePolicy::FreshIndex(12).change_subaddress(&inputs), Ok((1, 12)));
+    /// assert!(ChangePolicy::AccountMain.change_subaddress(&[(0, 1), (1, 4)]).is_err());
+    /// ```
+    pub fn change_subaddress(&self, inputs: &[(u32, u32)]) -> Result<(u32, u32), String> {
+        let (account, index) = *inputs.first().ok_or("Transaction has no input")?;
+        if inputs.iter().any(|(input_account, _)| *input_account != account) {
+            return Err("Inputs come from several accounts".to_string());
+        }
+        match self {
+            ChangePolicy::SameAsInputs => match inputs.iter().all(|(_, input_index)| *input_index == index) {
+                true => Ok((account, index)),
+                false => Ok((account, 0)),
+            },
+            ChangePolicy::AccountMain => Ok((account, 0)),
+            ChangePolicy::FreshIndex(0) => Err("Fresh change index must not be the account's main index".to_string()),
+            ChangePolicy::FreshIndex(fresh) => Ok((account, *fresh)),
+        }
+    }
+}
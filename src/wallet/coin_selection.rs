@@ -0,0 +1,130 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::snapshot::SnapshotOutput;
+use rand::seq::SliceRandom;
+
+/// CoinSelector picks the inputs of the transactions built by a [`TxBuilder`](struct.TxBuilder.html)
+///
+/// Outputs pinned with [`with_pinned_output`](struct.TxBuilder.html#method.with_pinned_output) are always spent, the
+/// selector only adds inputs to them. Closures with the same arguments implement the trait.
+pub trait CoinSelector: Send + Sync {
+    /// Returns the positions in `candidates` of the outputs to spend along the `pinned` ones, so that all inputs cover
+    /// `amount` plus `fee(inputs)`, the fee of a transaction with that many inputs
+    ///
+    /// Candidates are the unlocked and unspent outputs of the account, largest first, pinned ones excluded
+    fn select(&self, candidates: &[SnapshotOutput], pinned: &[SnapshotOutput], amount: u64, fee: &dyn Fn(usize) -> u64) -> Result<Vec<usize>, String>;
+}
+
+impl<F> CoinSelector for F
+where
+    F: Fn(&[SnapshotOutput], &[SnapshotOutput], u64, &dyn Fn(usize) -> u64) -> Result<Vec<usize>, String> + Send + Sync,
+{
+    fn select(&self, candidates: &[SnapshotOutput], pinned: &[SnapshotOutput], amount: u64, fee: &dyn Fn(usize) -> u64) -> Result<Vec<usize>, String> {
+        self(candidates, pinned, amount, fee)
+    }
+}
+
+/// LargestFirst spends the largest outputs first, so that transactions have as few inputs as possible
+///
+/// This is the default selector of the [`TxBuilder`](struct.TxBuilder.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LargestFirst;
+
+impl CoinSelector for LargestFirst {
+    fn select(&self, candidates: &[SnapshotOutput], pinned: &[SnapshotOutput], amount: u64, fee: &dyn Fn(usize) -> u64) -> Result<Vec<usize>, String> {
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by_key(|position| std::cmp::Reverse(candidates[*position].amount));
+        take_until_covered(order, candidates, pinned, amount, fee)
+    }
+}
+
+/// RandomCoins spends outputs in random order, so that the inputs of a transaction say nothing about the amounts
+/// held by the wallet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RandomCoins;
+
+impl CoinSelector for RandomCoins {
+    fn select(&self, candidates: &[SnapshotOutput], pinned: &[SnapshotOutput], amount: u64, fee: &dyn Fn(usize) -> u64) -> Result<Vec<usize>, String> {
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.shuffle(&mut rand::thread_rng());
+        take_until_covered(order, candidates, pinned, amount, fee)
+    }
+}
+
+/// AvoidDust spends the largest outputs first and never spends dust, outputs below `dust_threshold` or worth less
+/// than the fee of the input spending them
+///
+/// Consolidating dust costs more than it brings and links the dust to the other inputs, which is how dusting attacks
+/// trace wallets. Dust stays in the wallet until it is swept on purpose.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::TxHash;
+/// use libmonero::keys::{OneTimeKey, PrivateKey};
+/// use libmonero::wallet::{AvoidDust, CoinSelector, SnapshotOutput};
+///
+/// let tx_secret = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let output = |index: u64, amount: u64| SnapshotOutput {
+///     tx_hash: TxHash::from_bytes([0xaa; 32]),
+///     height: 3000000,
+///     index,
+///     amount,
+///     output_key: OneTimeKey::from_bytes([index as u8; 32]),
+///     tx_public_key: tx_secret.public_key(),
+///     subaddress: (0, 0),
+///     unlock_time: 0,
+///     spent_height: None,
+///     pre_rct: false,
+/// };
+/// let candidates = [output(0, 5_000_000_000), output(1, 20_000_000), output(2, 1_000)];
+/// // Each input adds 30000000 to the fee
+/// let fee = |inputs: usize| 30_000_000 * inputs as u64;
+/// assert_eq!(AvoidDust { dust_threshold: 0 }.select(&candidates, &[], 4_900_000_000, &fee), Ok(vec![0]));
+/// // Spending the second output would cost more than it brings
+/// assert!(AvoidDust { dust_threshold: 0 }.select(&candidates, &[], 4_980_000_000, &fee).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AvoidDust {
+    /// Outputs below this amount are never spent, even when they pay for their own input
+    pub dust_threshold: u64,
+}
+
+impl CoinSelector for AvoidDust {
+    fn select(&self, candidates: &[SnapshotOutput], pinned: &[SnapshotOutput], amount: u64, fee: &dyn Fn(usize) -> u64) -> Result<Vec<usize>, String> {
+        // Fee added by one more input, the first one of a transaction is the most expensive
+        let inputs = pinned.len().max(1);
+        let input_fee = fee(inputs + 1).saturating_sub(fee(inputs));
+        let mut order: Vec<usize> = (0..candidates.len())
+            .filter(|position| candidates[*position].amount >= self.dust_threshold && candidates[*position].amount > input_fee)
+            .collect();
+        order.sort_by_key(|position| std::cmp::Reverse(candidates[*position].amount));
+        take_until_covered(order, candidates, pinned, amount, fee)
+    }
+}
+
+// Adds the candidates in the given order until the inputs cover the amount and the fee
+fn take_until_covered(order: Vec<usize>, candidates: &[SnapshotOutput], pinned: &[SnapshotOutput], amount: u64, fee: &dyn Fn(usize) -> u64) -> Result<Vec<usize>, String> {
+    let covered = |inputs: usize, total: u64| inputs > 0 && total >= amount.saturating_add(fee(inputs));
+    let mut total: u64 = pinned.iter().map(|output| output.amount).sum();
+    let mut selected = Vec::new();
+    if covered(pinned.len(), total) {
+        return Ok(selected);
+    }
+    for position in order {
+        selected.push(position);
+        total += candidates[position].amount;
+        if covered(pinned.len() + selected.len(), total) {
+            return Ok(selected);
+        }
+    }
+    let available: u64 = candidates.iter().chain(pinned.iter()).map(|output| output.amount).sum();
+    Err(format!("Not enough unlocked funds: {} available, {} needed plus fee", available, amount))
+}
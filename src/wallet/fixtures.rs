@@ -0,0 +1,378 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::snapshot::{SnapshotOutput, SnapshotTransfer, SubaddressLabel, WalletSnapshot};
+use crate::blocks::{BlockHash, ParsedBlock, RctBase, RctType, Transaction, TransactionPrefix, TxExtra, TxHash, TxInput, TxOutTarget, TxOutput, TxSignatures};
+use crate::keys::{
+    commit, commitment_mask, derivation_to_scalar, derive_hex_seed, derive_output_key, derive_subaddress, ecdh_encode, generate_key_derivation,
+    generate_seed_from_entropy, KeyImage, OneTimeKey, PrivateKey, PublicKey, SubaddressTable, ViewPair,
+};
+use crate::utils::{decode_address, AddressType, Network};
+use curve25519_dalek::Scalar;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use zeroize::Zeroizing;
+
+// Block reward of the tail emission, paid by every fixture miner transaction
+const FIXTURE_BLOCK_REWARD: u64 = 600000000000;
+// Fee of every fixture transaction
+const FIXTURE_FEE: u64 = 30720000;
+// Size of the random bytes standing for the prunable signatures of fixture transactions
+const FIXTURE_PRUNABLE_SIZE: usize = 1024;
+
+/// FixtureConfig describes the wallet and chain generated by [`FixtureWallet::generate`](struct.FixtureWallet.html#method.generate)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureConfig {
+    pub network: Network,
+    /// Seed of the generator, the same configuration always gives the same wallet and chain
+    pub seed: u64,
+    /// Height of the first generated block
+    pub start_height: u64,
+    /// Timestamp of the first generated block, the next ones follow every 2 minutes
+    pub start_timestamp: u64,
+    pub blocks: usize,
+    /// Number of first blocks whose miner transaction pays the wallet
+    pub mined_blocks: usize,
+    /// Number of transactions paying the wallet, spread over the blocks and cycling through its subaddresses
+    pub incoming_transfers: usize,
+    /// Accounts and subaddresses per account receiving the incoming transfers
+    pub accounts: u32,
+    pub subaddresses_per_account: u32,
+    /// Transactions per block not paying the wallet
+    pub foreign_txs_per_block: usize,
+}
+
+impl Default for FixtureConfig {
+    /// Stagenet wallet with 20 blocks from height 1000, the first one mined by the wallet, 6 incoming transfers over
+    /// 2 accounts of 2 subaddresses and one foreign transaction per block
+    fn default() -> FixtureConfig {
+        FixtureConfig {
+            network: Network::Stagenet,
+            seed: 0,
+            start_height: 1000,
+            start_timestamp: 1700000000,
+            blocks: 20,
+            mined_blocks: 1,
+            incoming_transfers: 6,
+            accounts: 2,
+            subaddresses_per_account: 2,
+            foreign_txs_per_block: 1,
+        }
+    }
+}
+
+/// FixtureBlock is a generated block with its transactions, miner transaction excluded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureBlock {
+    pub height: u64,
+    pub block: ParsedBlock,
+    pub transactions: Vec<Transaction>,
+}
+
+/// FixtureWallet is a deterministic wallet with a chain of blocks paying it, for the unit tests of code consuming
+/// libmonero types
+///
+/// Keys, addresses and mnemonic are consistent, and the blocks and transactions serialize to blobs that parse back
+/// with [`ParsedBlock::from_blob`](../blocks/struct.ParsedBlock.html#method.from_blob) and
+/// [`parse_transaction_blob`](../blocks/fn.parse_transaction_blob.html). Outputs paying the wallet carry valid
+/// one-time keys, view tags, encrypted amounts and commitments, so scanning finds exactly the outputs of
+/// [`snapshot`](#structfield.snapshot). Ring members, key images and signatures are random bytes: fixtures look
+/// funded but can not be verified nor spent on a real network. Needs the `fixtures` feature.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::scan_tx_for_subaddresses;
+/// use libmonero::keys::ScanFilter;
+/// use libmonero::wallet::{FixtureConfig, FixtureWallet};
+///
+/// let fixture = FixtureWallet::generate(&FixtureConfig::default());
+/// assert_eq!(fixture, FixtureWallet::generate(&FixtureConfig::default()));
+/// assert!(fixture.primary_address.starts_with('5'));
+///
+/// let table = fixture.subaddress_table();
+/// let found: usize = fixture
+///     .blocks
+///     .iter()
+///     .flat_map(|block| std::iter::once(&block.block.miner_tx).chain(block.transactions.iter()))
+///     .map(|tx| scan_tx_for_subaddresses(&table, &tx.to_raw_tx(), None, &ScanFilter::default()).len())
+///     .sum();
+/// assert_eq!(found, fixture.snapshot.outputs.len());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureWallet {
+    pub network: Network,
+    /// Original (25-word) English mnemonic of the wallet
+    pub mnemonic: Zeroizing<Vec<String>>,
+    pub private_spend_key: PrivateKey,
+    pub private_view_key: PrivateKey,
+    pub primary_address: String,
+    pub blocks: Vec<FixtureBlock>,
+    /// What a wallet scanning the blocks should find: outputs, incoming transfers and subaddresses
+    pub snapshot: WalletSnapshot,
+}
+
+impl FixtureWallet {
+    /// Generates the wallet and its chain, see [`FixtureConfig`](struct.FixtureConfig.html)
+    pub fn generate(config: &FixtureConfig) -> FixtureWallet {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut entropy = Zeroizing::new([0u8; 32]);
+        rng.fill(&mut entropy[..]);
+        let mnemonic = generate_seed_from_entropy(&entropy, "en");
+        let private_spend_key = PrivateKey::from_hex(&derive_hex_seed(mnemonic.to_vec())).unwrap();
+        let private_view_key = private_spend_key.derive_view_key();
+        let view_pair = ViewPair::new(private_spend_key.public_key(), private_view_key.clone());
+        let primary_address = derive_subaddress(&view_pair, config.network, 0, 0);
+
+        let mut snapshot = WalletSnapshot::new(config.network, &primary_address, config.start_height + config.blocks as u64);
+        for account in 0..config.accounts.max(1) {
+            for index in 0..config.subaddresses_per_account.max(1) {
+                snapshot.subaddresses.push(SubaddressLabel {
+                    account,
+                    index,
+                    address: derive_subaddress(&view_pair, config.network, account, index),
+                    label: format!("Fixture {}/{}", account, index),
+                });
+            }
+        }
+        let mut generator = Generator { rng, network: config.network };
+
+        let mut blocks = Vec::with_capacity(config.blocks);
+        let mut prev_id = BlockHash::from_bytes(generator.bytes());
+        let mut incoming = 0;
+        for block_index in 0..config.blocks {
+            let height = config.start_height + block_index as u64;
+            let timestamp = config.start_timestamp + 120 * block_index as u64;
+
+            let mut transactions = Vec::new();
+            // Incoming transfers are spread evenly, several can share a block
+            while incoming < config.incoming_transfers && incoming * config.blocks / config.incoming_transfers.max(1) == block_index {
+                let label = &snapshot.subaddresses[incoming % snapshot.subaddresses.len()];
+                let amount = generator.rng.gen_range(10000000000..10000000000000);
+                let (subaddress, address) = ((label.account, label.index), label.address.clone());
+                let tx = generator.transfer(&address, amount);
+                let tx_hash = tx.hash().unwrap();
+                snapshot.outputs.push(snapshot_output(&tx, tx_hash, height, 0, amount, subaddress));
+                snapshot.transfers.push(incoming_transfer(tx_hash, height, timestamp, subaddress.0, amount));
+                transactions.push(tx);
+                incoming += 1;
+            }
+            for _ in 0..config.foreign_txs_per_block {
+                let address = generator.foreign_address();
+                let amount = generator.rng.gen_range(10000000000..10000000000000);
+                transactions.push(generator.transfer(&address, amount));
+            }
+
+            let miner_address = match block_index < config.mined_blocks {
+                true => primary_address.clone(),
+                false => generator.foreign_address(),
+            };
+            let miner_tx = generator.miner_transaction(&miner_address, height);
+            if block_index < config.mined_blocks {
+                let tx_hash = miner_tx.hash().unwrap();
+                snapshot.outputs.push(snapshot_output(&miner_tx, tx_hash, height, 0, FIXTURE_BLOCK_REWARD, (0, 0)));
+                snapshot.transfers.push(incoming_transfer(tx_hash, height, timestamp, 0, FIXTURE_BLOCK_REWARD));
+            }
+
+            let block = ParsedBlock {
+                major_version: 16,
+                minor_version: 16,
+                timestamp,
+                prev_id,
+                nonce: generator.rng.gen(),
+                miner_tx,
+                tx_hashes: transactions.iter().map(|tx| tx.hash().unwrap()).collect(),
+            };
+            prev_id = block.hash();
+            blocks.push(FixtureBlock { height, block, transactions });
+        }
+
+        FixtureWallet {
+            network: config.network,
+            mnemonic,
+            private_spend_key,
+            private_view_key,
+            primary_address,
+            blocks,
+            snapshot,
+        }
+    }
+
+    /// Returns the view pair of the wallet
+    pub fn view_pair(&self) -> ViewPair {
+        ViewPair::new(self.private_spend_key.public_key(), self.private_view_key.clone())
+    }
+
+    /// Returns a subaddress table holding every subaddress of [`snapshot`](#structfield.snapshot), ready for scanning
+    pub fn subaddress_table(&self) -> SubaddressTable {
+        let mut table = SubaddressTable::new(&self.view_pair(), 0, 0);
+        for label in self.snapshot.subaddresses.iter() {
+            table.insert(label.account, label.index);
+        }
+        table
+    }
+
+    /// Returns the height of the chain after the last generated block
+    pub fn chain_height(&self) -> u64 {
+        self.blocks.last().map_or(self.snapshot.synced_height, |block| block.height + 1)
+    }
+}
+
+// Deterministic source of keys and transactions
+struct Generator {
+    rng: StdRng,
+    network: Network,
+}
+
+impl Generator {
+    fn bytes(&mut self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        self.rng.fill(&mut bytes);
+        bytes
+    }
+
+    fn private_key(&mut self) -> PrivateKey {
+        let mut bytes = [0u8; 64];
+        self.rng.fill(&mut bytes[..]);
+        PrivateKey::from_bytes_mod_order(Scalar::from_bytes_mod_order_wide(&bytes).to_bytes())
+    }
+
+    fn public_key(&mut self) -> PublicKey {
+        self.private_key().public_key()
+    }
+
+    fn foreign_address(&mut self) -> String {
+        let spend_key = self.private_key();
+        derive_subaddress(&ViewPair::new(spend_key.public_key(), spend_key.derive_view_key()), self.network, 0, 0)
+    }
+
+    // Version 2 miner transaction paying the block reward to `address`, locked for 60 blocks
+    fn miner_transaction(&mut self, address: &str, height: u64) -> Transaction {
+        let tx_secret_key = self.private_key();
+        let keys = derive_output_key(address, &tx_secret_key, 0).unwrap();
+        Transaction {
+            prefix: TransactionPrefix {
+                version: 2,
+                unlock_time: height + 60,
+                inputs: vec![TxInput::Gen { height }],
+                outputs: vec![TxOutput {
+                    amount: FIXTURE_BLOCK_REWARD,
+                    target: TxOutTarget::ToTaggedKey {
+                        key: keys.output_key,
+                        view_tag: keys.view_tag,
+                    },
+                }],
+                extra: TxExtra::new().with_tx_public_key(&keys.tx_public_key).to_bytes(),
+            },
+            signatures: TxSignatures::V2 {
+                base: RctBase {
+                    rct_type: RctType::Null,
+                    fee: 0,
+                    pseudo_outs: Vec::new(),
+                    encrypted_amounts: Vec::new(),
+                    commitments: Vec::new(),
+                },
+                prunable: Vec::new(),
+            },
+        }
+    }
+
+    // Bulletproofs+ transaction with a 16-member ring input, paying `amount` to `address` at index 0 and a change output
+    // to a foreign address, with additional public keys when `address` is a subaddress
+    fn transfer(&mut self, address: &str, amount: u64) -> Transaction {
+        let change_address = self.foreign_address();
+        let subaddress = decode_address(address).unwrap().address_type == AddressType::Subaddress;
+        let tx_secret_key = self.private_key();
+        let secret_keys = match subaddress {
+            true => vec![self.private_key(), self.private_key()],
+            false => vec![tx_secret_key.clone(), tx_secret_key.clone()],
+        };
+        let mut outputs = Vec::new();
+        let mut encrypted_amounts = Vec::new();
+        let mut commitments = Vec::new();
+        let mut additional_keys = Vec::new();
+        let change = self.rng.gen_range(1000000000..1000000000000);
+        for (index, (destination, value)) in [(address, amount), (change_address.as_str(), change)].into_iter().enumerate() {
+            let keys = derive_output_key(destination, &secret_keys[index], index as u64).unwrap();
+            let recipient = decode_address(destination).unwrap();
+            let derivation = generate_key_derivation(&recipient.public_view_key, &secret_keys[index]);
+            let shared_secret = derivation_to_scalar(&derivation, index as u64);
+            let mask = commitment_mask(&shared_secret);
+            outputs.push(TxOutput {
+                amount: 0,
+                target: TxOutTarget::ToTaggedKey {
+                    key: keys.output_key,
+                    view_tag: keys.view_tag,
+                },
+            });
+            encrypted_amounts.push(ecdh_encode(value, &mask, &shared_secret, true));
+            commitments.push(commit(value, &mask));
+            additional_keys.push(keys.tx_public_key);
+        }
+        let mut extra = TxExtra::new().with_tx_public_key(&tx_secret_key.public_key());
+        if subaddress {
+            extra = extra.with_additional_public_keys(&additional_keys);
+        }
+        let mut key_offsets: Vec<u64> = (0..16).map(|_| self.rng.gen_range(1..50000)).collect();
+        key_offsets[0] += 1000000;
+        let mut prunable = vec![0u8; FIXTURE_PRUNABLE_SIZE];
+        self.rng.fill(&mut prunable[..]);
+        Transaction {
+            prefix: TransactionPrefix {
+                version: 2,
+                unlock_time: 0,
+                inputs: vec![TxInput::ToKey {
+                    amount: 0,
+                    key_offsets,
+                    key_image: KeyImage::from_bytes(*self.public_key().as_bytes()),
+                }],
+                outputs,
+                extra: extra.to_bytes(),
+            },
+            signatures: TxSignatures::V2 {
+                base: RctBase {
+                    rct_type: RctType::BulletproofPlus,
+                    fee: FIXTURE_FEE,
+                    pseudo_outs: Vec::new(),
+                    encrypted_amounts,
+                    commitments,
+                },
+                prunable,
+            },
+        }
+    }
+}
+
+fn snapshot_output(tx: &Transaction, tx_hash: TxHash, height: u64, index: usize, amount: u64, subaddress: (u32, u32)) -> SnapshotOutput {
+    let extra = tx.extra().unwrap();
+    SnapshotOutput {
+        tx_hash,
+        height,
+        index: index as u64,
+        amount,
+        output_key: OneTimeKey::from_bytes(*tx.prefix.outputs[index].key().as_bytes()),
+        tx_public_key: extra.additional_public_keys().get(index).copied().or(extra.tx_public_key()).unwrap(),
+        subaddress,
+        unlock_time: tx.prefix.unlock_time,
+        spent_height: None,
+        pre_rct: false,
+    }
+}
+
+fn incoming_transfer(tx_hash: TxHash, height: u64, timestamp: u64, account: u32, amount: u64) -> SnapshotTransfer {
+    SnapshotTransfer {
+        tx_hash,
+        height: Some(height),
+        timestamp,
+        account,
+        incoming: amount,
+        outgoing: 0,
+        fee: 0,
+        payment_id: None,
+    }
+}
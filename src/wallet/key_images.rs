@@ -0,0 +1,368 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::archive::{decrypt_with_view_key, encrypt_with_view_key, read_bytes, read_key, read_u32};
+use super::snapshot::{output_from_json, output_to_json, SnapshotOutput};
+use crate::keys::{check_ring_signature, prove_output_ownership, KeyImage, OneTimeKey, OutputOwnershipProof, OwnedOutput, PrivateKey, PublicKey, Signature, ViewPair};
+use rand::RngCore;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+const KEY_IMAGE_EXCHANGE_VERSION: u64 = 1;
+const KEY_IMAGE_EXCHANGE_DOMAIN: &[u8] = b"libmonero_key_image_exchange";
+
+/// Magic starting the key image files of monero-wallet-cli's `export_key_images`
+pub const KEY_IMAGE_EXPORT_PREFIX: &[u8] = b"Monero key image export\x03";
+
+/// KeyImageImportError describes why a [`KeyImageResponse`](struct.KeyImageResponse.html) was refused, the session
+/// is left unchanged
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyImageImportError {
+    /// Response was made for a request of another session
+    WrongSession,
+    /// No request with this sequence number was issued by the session
+    UnknownRequest(u64),
+    /// Response to this request was already imported
+    AlreadyImported(u64),
+    /// Response to a newer request was imported first, this one may carry outdated key images
+    Superseded { sequence: u64, imported: u64 },
+    /// Output at this position of the response is not the one requested, or is missing
+    OutputMismatch { index: usize },
+    /// Ownership proof at this position of the response does not verify
+    InvalidProof { index: usize },
+}
+
+impl fmt::Display for KeyImageImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyImageImportError::WrongSession => write!(f, "Response belongs to another session"),
+            KeyImageImportError::UnknownRequest(sequence) => write!(f, "Request {} was not issued by this session", sequence),
+            KeyImageImportError::AlreadyImported(sequence) => write!(f, "Response to request {} was already imported", sequence),
+            KeyImageImportError::Superseded { sequence, imported } => {
+                write!(f, "Request {} is older than the already imported request {}", sequence, imported)
+            }
+            KeyImageImportError::OutputMismatch { index } => write!(f, "Output {} does not match the request", index),
+            KeyImageImportError::InvalidProof { index } => write!(f, "Proof of output {} is invalid", index),
+        }
+    }
+}
+
+impl std::error::Error for KeyImageImportError {}
+
+/// KeyImageRequest asks the cold wallet holding the spend key for the key images of outputs seen by a view-only wallet
+///
+/// It is created by [`KeyImageImportSession::request`](struct.KeyImageImportSession.html#method.request), carried to
+/// the cold wallet as JSON and answered with [`respond`](#method.respond).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyImageRequest {
+    /// Random identifier of the session that issued the request
+    pub session_id: [u8; 16],
+    /// Number of the request in its session, starting at 0
+    pub sequence: u64,
+    pub outputs: Vec<SnapshotOutput>,
+}
+
+impl KeyImageRequest {
+    /// Generates the key images of the requested outputs along with proofs binding them to this request
+    ///
+    /// Fails if an output does not belong to the wallet of the given keys.
+    pub fn respond(&self, private_view_key: &PrivateKey, private_spend_key: &PrivateKey) -> Result<KeyImageResponse, String> {
+        let message = exchange_message(&self.session_id, self.sequence);
+        let mut proofs = Vec::with_capacity(self.outputs.len());
+        for (i, output) in self.outputs.iter().enumerate() {
+            let owned = OwnedOutput {
+                index: output.index,
+                amount: output.amount,
+                output_key: output.output_key,
+                tx_public_key: output.tx_public_key,
+                pre_rct: output.pre_rct,
+                subaddress: output.subaddress,
+            };
+            let secret = owned.secret_key(private_view_key, private_spend_key);
+            if OneTimeKey::from(secret.public_key()) != output.output_key {
+                return Err(format!("Output {} does not belong to the wallet", i));
+            }
+            proofs.push(prove_output_ownership(&secret, &message));
+        }
+        Ok(KeyImageResponse {
+            session_id: self.session_id,
+            sequence: self.sequence,
+            proofs,
+        })
+    }
+
+    /// Serializes the request as JSON
+    pub fn to_json(&self) -> String {
+        json!({
+            "version": KEY_IMAGE_EXCHANGE_VERSION,
+            "session_id": hex::encode(self.session_id),
+            "sequence": self.sequence,
+            "outputs": self.outputs.iter().map(output_to_json).collect::<Vec<Value>>(),
+        })
+        .to_string()
+    }
+
+    /// Parses a request written by [`to_json`](#method.to_json)
+    pub fn from_json(request: &str) -> Result<KeyImageRequest, String> {
+        let json = parse_exchange(request, "request")?;
+        let outputs = json["outputs"].as_array().ok_or("Request outputs are missing")?;
+        Ok(KeyImageRequest {
+            session_id: session_id_field(&json)?,
+            sequence: json["sequence"].as_u64().ok_or("Request sequence is missing")?,
+            outputs: outputs.iter().map(output_from_json).collect::<Result<Vec<SnapshotOutput>, String>>()?,
+        })
+    }
+}
+
+/// KeyImageResponse carries the key images generated by the cold wallet for a [`KeyImageRequest`](struct.KeyImageRequest.html),
+/// one [`OutputOwnershipProof`](../keys/struct.OutputOwnershipProof.html) per requested output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyImageResponse {
+    pub session_id: [u8; 16],
+    pub sequence: u64,
+    pub proofs: Vec<OutputOwnershipProof>,
+}
+
+impl KeyImageResponse {
+    /// Serializes the response as JSON
+    pub fn to_json(&self) -> String {
+        json!({
+            "version": KEY_IMAGE_EXCHANGE_VERSION,
+            "session_id": hex::encode(self.session_id),
+            "sequence": self.sequence,
+            "proofs": self.proofs.iter().map(|proof| proof.to_string()).collect::<Vec<String>>(),
+        })
+        .to_string()
+    }
+
+    /// Parses a response written by [`to_json`](#method.to_json), its proofs are checked on import
+    pub fn from_json(response: &str) -> Result<KeyImageResponse, String> {
+        let json = parse_exchange(response, "response")?;
+        let proofs = json["proofs"].as_array().ok_or("Response proofs are missing")?;
+        Ok(KeyImageResponse {
+            session_id: session_id_field(&json)?,
+            sequence: json["sequence"].as_u64().ok_or("Response sequence is missing")?,
+            proofs: proofs
+                .iter()
+                .map(|proof| proof.as_str().ok_or("Response proof is not a string")?.parse())
+                .collect::<Result<Vec<OutputOwnershipProof>, String>>()?,
+        })
+    }
+}
+
+/// KeyImageImportSession pairs the key image requests of a view-only wallet with the responses of its cold wallet
+///
+/// Every request gets the session's random identifier and the next sequence number, and the cold wallet's proofs sign
+/// both. A response is imported only if it answers a pending request of this session for exactly the requested
+/// outputs, once, and only if no newer request was answered before, so an old or repeated file carried back from the
+/// cold wallet cannot overwrite fresher key images.
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::TxHash;
+/// use libmonero::keys::{derive_output_key, PrivateKey, ViewPair};
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::{KeyImageImportError, KeyImageImportSession, SnapshotOutput};
+///
+/// let spend = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view = spend.derive_view_key();
+/// let address = ViewPair::new(spend.public_key(), spend.derive_view_key()).address(Network::Mainnet);
+/// let tx_secret = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let keys = derive_output_key(&address, &tx_secret, 0).unwrap();
+/// let output = SnapshotOutput {
+///     tx_hash: TxHash::from_bytes([0xaa; 32]),
+///     height: 3000000,
+///     index: 0,
+///     amount: 1000000000000,
+///     output_key: keys.output_key,
+///     tx_public_key: keys.tx_public_key,
+///     subaddress: (0, 0),
+///     unlock_time: 0,
+///     spent_height: None,
+///     pre_rct: false,
+/// };
+///
+/// let mut session = KeyImageImportSession::new();
+/// let request = session.request(&[output.clone()]);
+/// // On the cold wallet
+/// let response = request.respond(&view, &spend).unwrap();
+/// // Back on the view-only wallet
+/// let imported = session.import(&response).unwrap();
+/// assert_eq!(imported[0].0, output.output_key);
+/// assert_eq!(session.import(&response), Err(KeyImageImportError::AlreadyImported(0)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct KeyImageImportSession {
+    session_id: [u8; 16],
+    next_sequence: u64,
+    pending: BTreeMap<u64, Vec<OneTimeKey>>,
+    imported: BTreeSet<u64>,
+}
+
+impl KeyImageImportSession {
+    /// Starts a session with a random identifier
+    pub fn new() -> KeyImageImportSession {
+        let mut session_id = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut session_id);
+        KeyImageImportSession {
+            session_id,
+            next_sequence: 0,
+            pending: BTreeMap::new(),
+            imported: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the identifier written in the requests of the session
+    pub fn session_id(&self) -> [u8; 16] {
+        self.session_id
+    }
+
+    /// Returns the sequence numbers of the requests still waiting for a response
+    pub fn pending(&self) -> Vec<u64> {
+        self.pending.keys().copied().collect()
+    }
+
+    /// Creates the next request of the session for the given outputs
+    pub fn request(&mut self, outputs: &[SnapshotOutput]) -> KeyImageRequest {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.pending.insert(sequence, outputs.iter().map(|output| output.output_key).collect());
+        KeyImageRequest {
+            session_id: self.session_id,
+            sequence,
+            outputs: outputs.to_vec(),
+        }
+    }
+
+    /// Checks the response against its request and returns the key image of every requested output, in request order
+    ///
+    /// Requests older than the imported one are dropped, their responses are refused from then on.
+    pub fn import(&mut self, response: &KeyImageResponse) -> Result<Vec<(OneTimeKey, KeyImage)>, KeyImageImportError> {
+        if response.session_id != self.session_id {
+            return Err(KeyImageImportError::WrongSession);
+        }
+        let sequence = response.sequence;
+        if self.imported.contains(&sequence) {
+            return Err(KeyImageImportError::AlreadyImported(sequence));
+        }
+        if let Some(&imported) = self.imported.last() {
+            if sequence < imported {
+                return Err(KeyImageImportError::Superseded { sequence, imported });
+            }
+        }
+        let output_keys = self.pending.get(&sequence).ok_or(KeyImageImportError::UnknownRequest(sequence))?;
+
+        let message = exchange_message(&self.session_id, sequence);
+        for index in 0..output_keys.len().max(response.proofs.len()) {
+            let (Some(output_key), Some(proof)) = (output_keys.get(index), response.proofs.get(index)) else {
+                return Err(KeyImageImportError::OutputMismatch { index });
+            };
+            if proof.output_key != *output_key {
+                return Err(KeyImageImportError::OutputMismatch { index });
+            }
+            if !proof.verify(&message) {
+                return Err(KeyImageImportError::InvalidProof { index });
+            }
+        }
+
+        self.pending.retain(|&pending, _| pending > sequence);
+        self.imported.insert(sequence);
+        Ok(response.proofs.iter().map(|proof| (proof.output_key, proof.key_image)).collect())
+    }
+}
+
+impl Default for KeyImageImportSession {
+    fn default() -> KeyImageImportSession {
+        KeyImageImportSession::new()
+    }
+}
+
+/// Imports a key image file exported by monero-wallet-cli or [`Signer::export_key_images`](struct.Signer.html#method.export_key_images)
+///
+/// Key images of the file are those of the wallet outputs from the offset written in the file, in the order the
+/// outputs were received, each signed by its output key. Pass the result to
+/// [`Wallet::import_key_images`](struct.Wallet.html#method.import_key_images).
+///
+/// Arguments:
+/// - `data`: the content of the file
+/// - `outputs`: all outputs of the wallet, spent ones included, in the order they were received
+/// - `view_pair`: the public spend key and private view key of the wallet
+///
+/// Returns the output key and key image of every output of the file if succesfull
+/// Returns an error message if the file was exported by another wallet, refers to outputs the wallet does not know or a
+/// signature does not verify
+pub fn import_key_images(data: &[u8], outputs: &[SnapshotOutput], view_pair: &ViewPair) -> Result<Vec<(OneTimeKey, KeyImage)>, String> {
+    let encrypted = data.strip_prefix(KEY_IMAGE_EXPORT_PREFIX).ok_or("Data is not a key image export")?;
+    let plaintext = decrypt_with_view_key(encrypted, &view_pair.view)?;
+    let pos = &mut 0;
+    let offset = read_u32(&plaintext, pos)? as usize;
+    if read_key(&plaintext, pos)? != *view_pair.spend.as_bytes() || read_key(&plaintext, pos)? != *view_pair.view.public_key().as_bytes() {
+        return Err("Key images were exported by another wallet".to_string());
+    }
+    if (plaintext.len() - *pos) % 96 != 0 {
+        return Err("Key image export has a truncated entry".to_string());
+    }
+    let count = (plaintext.len() - *pos) / 96;
+    let outputs = outputs.get(offset..).filter(|outputs| outputs.len() >= count).ok_or(format!(
+        "Key image export covers outputs {} to {}, the wallet knows {} outputs",
+        offset,
+        offset + count,
+        outputs.len()
+    ))?;
+    let mut key_images = Vec::with_capacity(count);
+    for (i, output) in outputs.iter().take(count).enumerate() {
+        let key_image = KeyImage::from_bytes(read_key(&plaintext, pos)?);
+        let signature = Signature::from_bytes(read_bytes(&plaintext, pos, 64)?.try_into().unwrap());
+        let output_key = PublicKey::from_bytes(*output.output_key.as_bytes())?;
+        if !check_ring_signature(key_image.as_bytes(), &key_image, &[output_key], &[signature]) {
+            return Err(format!("Signature of the key image of output {} does not verify", offset + i));
+        }
+        key_images.push((output.output_key, key_image));
+    }
+    Ok(key_images)
+}
+
+// Key image file of the reference wallet: the offset of the first output as 4 little endian bytes, the public spend
+// and view keys, then every key image with its signature, encrypted with the view key
+pub(crate) fn write_key_image_export(view_pair: &ViewPair, offset: u32, key_images: &[(KeyImage, Signature)]) -> Result<Vec<u8>, String> {
+    let mut plaintext = offset.to_le_bytes().to_vec();
+    plaintext.extend_from_slice(view_pair.spend.as_bytes());
+    plaintext.extend_from_slice(view_pair.view.public_key().as_bytes());
+    for (key_image, signature) in key_images.iter() {
+        plaintext.extend_from_slice(key_image.as_bytes());
+        plaintext.extend_from_slice(&signature.to_bytes());
+    }
+    let mut data = KEY_IMAGE_EXPORT_PREFIX.to_vec();
+    data.extend(encrypt_with_view_key(&plaintext, &view_pair.view)?);
+    Ok(data)
+}
+
+// Message signed by the proofs of a response: domain || session ID || sequence
+fn exchange_message(session_id: &[u8; 16], sequence: u64) -> Vec<u8> {
+    let mut message = KEY_IMAGE_EXCHANGE_DOMAIN.to_vec();
+    message.extend_from_slice(session_id);
+    message.extend_from_slice(&sequence.to_le_bytes());
+    message
+}
+
+fn parse_exchange(exchange: &str, kind: &str) -> Result<Value, String> {
+    let json: Value = serde_json::from_str(exchange).map_err(|e| format!("Error while parsing the key image {} JSON: {}", kind, e))?;
+    let version = json["version"].as_u64().ok_or(format!("Key image {} version is missing", kind))?;
+    if version > KEY_IMAGE_EXCHANGE_VERSION {
+        return Err(format!("Unsupported key image {} version {}", kind, version));
+    }
+    Ok(json)
+}
+
+fn session_id_field(json: &Value) -> Result<[u8; 16], String> {
+    let session_id = hex::decode(json["session_id"].as_str().ok_or("Session ID is missing")?).map_err(|e| format!("Invalid session ID: {}", e))?;
+    session_id.try_into().map_err(|_| "Session ID must be 16 bytes".to_string())
+}
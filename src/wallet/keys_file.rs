@@ -0,0 +1,320 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use crate::blocks::rpcs::extract_json_blob;
+use crate::blocks::{epee_from_bytes, epee_get, epee_to_bytes, EpeeObject, EpeeValue};
+use crate::crypt::{chacha20, chacha8, generate_chacha_key};
+use crate::keys::{derive_subaddress, PrivateKey, PublicKey, ViewPair};
+use crate::utils::{read_varint, write_varint, Network};
+use rand::RngCore;
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+// Appended to the file key to derive the key encrypting the secret keys in memory, monero-core's HASH_KEY_MEMORY
+const HASH_KEY_MEMORY: u8 = b'k';
+
+/// KeysFile holds the keys stored in the `.keys` file of the official wallets (monero-wallet-cli,
+/// monero-wallet-rpc and the GUI)
+///
+/// The file is the account of the wallet serialized with epee, wrapped in a JSON object with the wallet settings and
+/// encrypted with ChaCha20 under a key derived from the password by
+/// [`generate_chacha_key`](../crypt/fn.generate_chacha_key.html). Files written before v0.12 used ChaCha8 and are
+/// read too. Only the keys, the network, the refresh height and the seed language are kept, the other settings get
+/// their default values when the official wallets open a file written by libmonero.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::PrivateKey;
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::KeysFile;
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let keys_file = KeysFile::new(Network::Mainnet, private_spend_key, 1700000000).with_refresh_height(3000000);
+/// let bytes = keys_file.to_bytes(b"hunter2", 1).unwrap();
+///
+/// assert_eq!(KeysFile::from_bytes(&bytes, b"hunter2", 1).unwrap(), keys_file);
+/// assert!(KeysFile::from_bytes(&bytes, b"hunter3", 1).is_err());
+///
+/// // Files of the official wallets are opened and created with the password alone
+/// let path = std::env::temp_dir().join(format!("libmonero_doc_{}.keys", std::process::id()));
+/// keys_file.create(&path, b"hunter2").unwrap();
+/// assert!(keys_file.create(&path, b"hunter2").is_err());
+/// let opened = KeysFile::open(&path, b"hunter2").unwrap();
+/// std::fs::remove_file(&path).unwrap();
+/// assert_eq!(opened.refresh_height, 3000000);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeysFile {
+    pub network: Network,
+    /// Private spend key, `None` for watch-only wallets
+    pub private_spend_key: Option<PrivateKey>,
+    pub private_view_key: PrivateKey,
+    pub public_spend_key: PublicKey,
+    /// Unix timestamp of the creation of the wallet
+    pub creation_timestamp: u64,
+    /// Height the wallet scans from, its creation or restore height
+    pub refresh_height: u64,
+    /// Language of the mnemonic seed as named by the official wallets, e.g. `English`
+    pub seed_language: String,
+}
+
+impl KeysFile {
+    /// Creates the keys file of a wallet from its private spend key, the view key being derived from it
+    pub fn new(network: Network, private_spend_key: PrivateKey, creation_timestamp: u64) -> KeysFile {
+        KeysFile {
+            network,
+            private_view_key: private_spend_key.derive_view_key(),
+            public_spend_key: private_spend_key.public_key(),
+            private_spend_key: Some(private_spend_key),
+            creation_timestamp,
+            refresh_height: 0,
+            seed_language: "English".to_string(),
+        }
+    }
+
+    /// Creates the keys file of a watch-only wallet
+    pub fn watch_only(network: Network, public_spend_key: PublicKey, private_view_key: PrivateKey, creation_timestamp: u64) -> KeysFile {
+        KeysFile {
+            network,
+            private_spend_key: None,
+            private_view_key,
+            public_spend_key,
+            creation_timestamp,
+            refresh_height: 0,
+            seed_language: "English".to_string(),
+        }
+    }
+
+    /// Sets the height the wallet scans from, 0 (the whole chain) by default
+    pub fn with_refresh_height(mut self, refresh_height: u64) -> KeysFile {
+        self.refresh_height = refresh_height;
+        self
+    }
+
+    /// Returns the public spend key and private view key of the wallet
+    pub fn view_pair(&self) -> ViewPair {
+        ViewPair::new(self.public_spend_key, self.private_view_key.clone())
+    }
+
+    /// Returns the primary address of the wallet
+    pub fn address(&self) -> String {
+        derive_subaddress(&self.view_pair(), self.network, 0, 0)
+    }
+
+    /// Decrypts and parses a `.keys` file, `kdf_rounds` being the `--kdf-rounds` the wallet was created with (1 by
+    /// default)
+    ///
+    /// Returns the keys if succesfull
+    /// Returns an error message if the password is wrong or the file is invalid
+    pub fn from_bytes(data: &[u8], password: &[u8], kdf_rounds: u64) -> Result<KeysFile, String> {
+        // keys_file_data: the IV, then the encrypted account data as a varint sized string
+        let iv: [u8; 8] = data.get(..8).ok_or("Keys file is too short")?.try_into().unwrap();
+        let mut pos = 8;
+        let size = usize::try_from(read_varint(data, &mut pos)?).map_err(|_| "Keys file data is too large")?;
+        if data.len() - pos != size {
+            return Err(format!("Keys file data has {} bytes, expected {}", data.len() - pos, size));
+        }
+        let encrypted = &data[pos..];
+        let key = generate_chacha_key(password, kdf_rounds)?;
+        let (key_data, settings) = [chacha20(encrypted, &key, &iv), chacha8(encrypted, &key, &iv)]
+            .into_iter()
+            .map(Zeroizing::new)
+            .find_map(|account_data| parse_account_data(&account_data))
+            .ok_or("Wrong password or invalid keys file")?;
+
+        let account = epee_from_bytes(&key_data)?;
+        let keys = object_field(&account, "m_keys")?;
+        let address = object_field(keys, "m_account_address")?;
+        let public_spend_key = PublicKey::from_bytes(key_field(address, "m_spend_public_key")?)?;
+        let public_view_key = PublicKey::from_bytes(key_field(address, "m_view_public_key")?)?;
+        let mut spend_secret = Zeroizing::new(key_field(keys, "m_spend_secret_key")?);
+        let mut view_secret = Zeroizing::new(key_field(keys, "m_view_secret_key")?);
+        if settings["encrypted_secret_keys"].as_u64() == Some(1) {
+            let encryption_iv = match epee_get(keys, "m_encryption_iv").and_then(EpeeValue::as_blob) {
+                Some(blob) => blob.try_into().map_err(|_| "Keys file encryption IV must be 8 bytes")?,
+                None => [0u8; 8],
+            };
+            let key_stream = secret_keys_stream(&key, &encryption_iv)?;
+            spend_secret.iter_mut().chain(view_secret.iter_mut()).zip(key_stream.iter()).for_each(|(byte, key)| *byte ^= key);
+        }
+
+        let private_view_key = PrivateKey::from_bytes(*view_secret)?;
+        if private_view_key.public_key() != public_view_key {
+            return Err("Private view key of the keys file does not match its address".to_string());
+        }
+        let watch_only = settings["watch_only"].as_u64() == Some(1) || spend_secret.iter().all(|byte| *byte == 0);
+        let private_spend_key = match watch_only {
+            true => None,
+            false => {
+                let private_spend_key = PrivateKey::from_bytes(*spend_secret)?;
+                if private_spend_key.public_key() != public_spend_key {
+                    return Err("Private spend key of the keys file does not match its address".to_string());
+                }
+                Some(private_spend_key)
+            }
+        };
+        let network = match settings["nettype"].as_u64() {
+            None | Some(0) => Network::Mainnet,
+            Some(1) => Network::Testnet,
+            Some(2) => Network::Stagenet,
+            Some(other) => return Err(format!("Keys file network type {} is unknown", other)),
+        };
+        Ok(KeysFile {
+            network,
+            private_spend_key,
+            private_view_key,
+            public_spend_key,
+            creation_timestamp: epee_get(&account, "m_creation_timestamp").and_then(EpeeValue::as_u64).unwrap_or(0),
+            refresh_height: settings["refresh_height"].as_u64().unwrap_or(0),
+            seed_language: settings["seed_language"].as_str().unwrap_or("English").to_string(),
+        })
+    }
+
+    /// Encrypts the keys as a `.keys` file the official wallets can open with the same password and `kdf_rounds`
+    ///
+    /// Returns the file content if succesfull
+    /// Returns an error message if `kdf_rounds` is 0
+    pub fn to_bytes(&self, password: &[u8], kdf_rounds: u64) -> Result<Vec<u8>, String> {
+        let blob = |bytes: &[u8]| EpeeValue::Blob(bytes.to_vec());
+        let spend_secret = self.private_spend_key.as_ref().map_or([0u8; 32], |key| *key.as_bytes());
+        let account: EpeeObject = vec![
+            (
+                "m_keys".to_string(),
+                EpeeValue::Object(vec![
+                    (
+                        "m_account_address".to_string(),
+                        EpeeValue::Object(vec![
+                            ("m_spend_public_key".to_string(), blob(self.public_spend_key.as_bytes())),
+                            ("m_view_public_key".to_string(), blob(self.private_view_key.public_key().as_bytes())),
+                        ]),
+                    ),
+                    ("m_spend_secret_key".to_string(), blob(&spend_secret)),
+                    ("m_view_secret_key".to_string(), blob(self.private_view_key.as_bytes())),
+                    ("m_multisig_keys".to_string(), blob(&[])),
+                    ("m_encryption_iv".to_string(), blob(&[0u8; 8])),
+                ]),
+            ),
+            ("m_creation_timestamp".to_string(), EpeeValue::U64(self.creation_timestamp)),
+        ];
+        let key_data = Zeroizing::new(epee_to_bytes(&account)?);
+        let nettype = match self.network {
+            Network::Mainnet => 0,
+            Network::Testnet => 1,
+            Network::Stagenet => 2,
+        };
+
+        // The official wallets write key_data as a JSON string holding the raw bytes, escaping only what JSON needs
+        let mut json = Zeroizing::new(b"{\"key_data\":\"".to_vec());
+        write_json_bytes(&key_data, &mut json);
+        json.extend_from_slice(b"\",\"seed_language\":");
+        json.extend_from_slice(Value::from(self.seed_language.as_str()).to_string().as_bytes());
+        let watch_only = self.private_spend_key.is_none() as u8;
+        let settings = format!(
+            ",\"key_on_device\":0,\"watch_only\":{},\"multisig\":0,\"refresh_height\":{},\"nettype\":{},\"encrypted_secret_keys\":0}}",
+            watch_only, self.refresh_height, nettype
+        );
+        json.extend_from_slice(settings.as_bytes());
+
+        let key = generate_chacha_key(password, kdf_rounds)?;
+        let mut iv = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut iv);
+        let encrypted = chacha20(&json, &key, &iv);
+        let mut out = iv.to_vec();
+        write_varint(encrypted.len() as u64, &mut out);
+        out.extend_from_slice(&encrypted);
+        Ok(out)
+    }
+
+    /// Reads a `.keys` file, see [`from_bytes`](#method.from_bytes)
+    ///
+    /// Returns the keys if succesfull
+    /// Returns an error message if the file can not be read, the password is wrong or the file is invalid
+    pub fn from_file<P: AsRef<Path>>(path: P, password: &[u8], kdf_rounds: u64) -> Result<KeysFile, String> {
+        let data = std::fs::read(path.as_ref()).map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        KeysFile::from_bytes(&data, password, kdf_rounds)
+    }
+
+    /// Writes the keys as a `.keys` file, see [`to_bytes`](#method.to_bytes)
+    ///
+    /// Returns `()` if succesfull
+    /// Returns an error message if the file can not be written or `kdf_rounds` is 0
+    pub fn to_file<P: AsRef<Path>>(&self, path: P, password: &[u8], kdf_rounds: u64) -> Result<(), String> {
+        let data = self.to_bytes(password, kdf_rounds)?;
+        std::fs::write(path.as_ref(), data).map_err(|e| format!("{}: {}", path.as_ref().display(), e))
+    }
+
+    /// Opens the `.keys` file of an official wallet created with the default KDF rounds, see [`from_file`](#method.from_file)
+    ///
+    /// Returns the keys if succesfull
+    /// Returns an error message if the file can not be read, the password is wrong or the file is invalid
+    pub fn open<P: AsRef<Path>>(path: P, password: &[u8]) -> Result<KeysFile, String> {
+        KeysFile::from_file(path, password, 1)
+    }
+
+    /// Creates a `.keys` file the official wallets can open with the password, refusing to overwrite an existing
+    /// file like they do
+    ///
+    /// Returns `()` if succesfull
+    /// Returns an error message if the file exists or can not be written
+    pub fn create<P: AsRef<Path>>(&self, path: P, password: &[u8]) -> Result<(), String> {
+        let data = self.to_bytes(password, 1)?;
+        let error = |e: std::io::Error| format!("{}: {}", path.as_ref().display(), e);
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path.as_ref()).map_err(error)?;
+        file.write_all(&data).map_err(error)
+    }
+}
+
+// Splits the decrypted account data into the epee serialized account and the JSON settings, None if the decryption
+// key was wrong. Files older than the JSON wrapper hold the serialized account only.
+fn parse_account_data(account_data: &[u8]) -> Option<(Zeroizing<Vec<u8>>, Value)> {
+    if let Ok(settings) = serde_json::from_str::<Value>(&String::from_utf8_lossy(account_data)) {
+        if settings.is_object() {
+            return Some((Zeroizing::new(extract_json_blob(account_data, "key_data")?), settings));
+        }
+    }
+    epee_from_bytes(account_data).ok().map(|_| (Zeroizing::new(account_data.to_vec()), Value::Null))
+}
+
+// Key stream the secret keys are XORed with when the wallet encrypts them, monero-core's `get_key_stream`
+fn secret_keys_stream(file_key: &[u8; 32], iv: &[u8; 8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    let mut data = Zeroizing::new(file_key.to_vec());
+    data.push(HASH_KEY_MEMORY);
+    let key = generate_chacha_key(&data, 1)?;
+    Ok(Zeroizing::new(chacha20(&[0u8; 64], &key, iv)))
+}
+
+// Escapes bytes into a JSON string like rapidjson, bytes above 0x7f are left as is
+fn write_json_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    for byte in bytes {
+        match byte {
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            0x08 => out.extend_from_slice(b"\\b"),
+            0x0c => out.extend_from_slice(b"\\f"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            0x00..=0x1f => out.extend_from_slice(format!("\\u{:04X}", byte).as_bytes()),
+            _ => out.push(*byte),
+        }
+    }
+}
+
+fn object_field<'a>(object: &'a EpeeObject, name: &str) -> Result<&'a EpeeObject, String> {
+    epee_get(object, name).and_then(EpeeValue::as_object).ok_or(format!("Keys file field {} is missing or invalid", name))
+}
+
+fn key_field(object: &EpeeObject, name: &str) -> Result<[u8; 32], String> {
+    let blob = epee_get(object, name).and_then(EpeeValue::as_blob).ok_or(format!("Keys file field {} is missing or invalid", name))?;
+    blob.try_into().map_err(|_| format!("Keys file field {} must be 32 bytes", name))
+}
@@ -0,0 +1,69 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::snapshot::{SnapshotOutput, WalletSnapshot};
+use crate::blocks::OutputHistogramEntry;
+
+impl WalletSnapshot {
+    /// Returns the unspent pre-RingCT outputs of the given account (all accounts if `None`)
+    ///
+    /// These outputs come from version 1 transactions, mostly received before 2017, and keep their clear denominated amount
+    pub fn pre_rct_outputs(&self, account: Option<u32>) -> Vec<&SnapshotOutput> {
+        self.unspent(account).filter(|output| output.pre_rct).collect()
+    }
+
+    /// Returns the unspent pre-RingCT outputs of the given account (all accounts if `None`) that cannot be spent in a
+    /// ring of `ring_size` members, because fewer outputs of the same amount are unlocked on chain
+    ///
+    /// `histogram` is the answer of [`get_output_histogram`](../blocks/fn.get_output_histogram.html) for the amounts of
+    /// [`pre_rct_outputs`](#method.pre_rct_outputs), amounts missing from it have no output on chain. Unmixable outputs
+    /// can only be moved by a sweep spending them with smaller rings, see
+    /// [`TxBuilder::sweep_unmixable`](struct.TxBuilder.html#method.sweep_unmixable).
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::{OutputHistogramEntry, TxHash};
+    /// use libmonero::keys::{OneTimeKey, PublicKey};
+    /// use libmonero::utils::Network;
+    /// use libmonero::wallet::{SnapshotOutput, WalletSnapshot};
+    ///
+    /// let mut snapshot = WalletSnapshot::new(Network::Mainnet, "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT", 3000000);
+    /// for amount in [20000000000, 1234567] {
+    ///     snapshot.outputs.push(SnapshotOutput {
+    ///         tx_hash: TxHash::default(),
+    ///         height: 900000,
+    ///         index: 0,
+    ///         amount,
+    ///         output_key: OneTimeKey::default(),
+    ///         tx_public_key: PublicKey::from_hex("528a736a5079dc9536edb5b6fa0a5209ce820b9734fc0785024670b3d3ba4c69").unwrap(),
+    ///         subaddress: (0, 0),
+    ///         unlock_time: 0,
+    ///         spent_height: None,
+    ///         pre_rct: true,
+    ///     });
+    /// }
+    /// let histogram = vec![OutputHistogramEntry { amount: 20000000000, total_instances: 900000, unlocked_instances: 900000, recent_instances: 0 }];
+    /// let unmixable = snapshot.unmixable_outputs(None, &histogram, 16);
+    /// assert_eq!(unmixable.len(), 1);
+    /// assert_eq!(unmixable[0].amount, 1234567);
+    /// ```
+    pub fn unmixable_outputs(&self, account: Option<u32>, histogram: &[OutputHistogramEntry], ring_size: usize) -> Vec<&SnapshotOutput> {
+        self.unspent(account)
+            .filter(|output| output.pre_rct)
+            .filter(|output| {
+                let unlocked = histogram
+                    .iter()
+                    .find(|entry| entry.amount == output.amount)
+                    .map_or(0, |entry| entry.unlocked_instances);
+                unlocked < ring_size as u64
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::builder::TxPreview;
+use super::snapshot::SnapshotTransfer;
+use std::fmt;
+use std::sync::Arc;
+
+// Length of the rolling window of the daily limit
+const DAY_SECONDS: u64 = 24 * 60 * 60;
+
+/// SpendingLimitError describes why a [`SpendingPolicy`](struct.SpendingPolicy.html) refused a transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpendingLimitError {
+    /// Transaction spends more than the per transaction limit
+    PerTransaction { amount: u64, limit: u64 },
+    /// Transaction would bring the spendings of the last 24 hours above the daily limit
+    Daily { spent: u64, amount: u64, limit: u64 },
+    /// An approval hook refused the transaction with this reason
+    Rejected(String),
+}
+
+impl fmt::Display for SpendingLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpendingLimitError::PerTransaction { amount, limit } => write!(f, "Transaction spends {}, above the limit of {}", amount, limit),
+            SpendingLimitError::Daily { spent, amount, limit } => {
+                write!(f, "Transaction spends {} after {} in the last 24 hours, above the daily limit of {}", amount, spent, limit)
+            }
+            SpendingLimitError::Rejected(reason) => write!(f, "Transaction was not approved: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SpendingLimitError {}
+
+/// ApprovalHook is asked to approve every transaction allowed by the limits of a [`SpendingPolicy`](struct.SpendingPolicy.html)
+pub trait ApprovalHook: Send + Sync {
+    /// Returns an error with the reason of the refusal if the transaction must not be signed
+    fn approve(&self, preview: &TxPreview) -> Result<(), String>;
+}
+
+impl<F> ApprovalHook for F
+where
+    F: Fn(&TxPreview) -> Result<(), String> + Send + Sync,
+{
+    fn approve(&self, preview: &TxPreview) -> Result<(), String> {
+        self(preview)
+    }
+}
+
+/// SpendingPolicy holds the controls a custodial deployment enforces on every transaction before it is signed
+///
+/// Spent amounts include the fee, on both the transaction and the history. The daily limit is a rolling window of the
+/// last 24 hours over the outgoing transfers of the wallet history, pool transactions included. By default nothing is
+/// limited, the limits and hooks are added with the builder methods.
+///
+/// Example:
+/// ```
+/// use libmonero::wallet::{SpendingLimitError, SpendingPolicy, TxPreview};
+///
+/// let policy = SpendingPolicy::new()
+///     .with_max_per_tx(10_000_000_000_000)
+///     .with_daily_limit(50_000_000_000_000)
+///     .with_approval(|preview: &TxPreview| match preview.destinations.len() {
+///         1 => Ok(()),
+///         _ => Err("Batch payments need a second signer".to_string()),
+///     });
+/// let preview = TxPreview {
+///     inputs: Vec::new(),
+///     destinations: vec![("4...".to_string(), 20_000_000_000_000)],
+///     amount: 20_000_000_000_000,
+///     fee: 30_000_000,
+///     change: 0,
+///     change_subaddress: (0, 0),
+///     weight: 1536,
+///     fee_per_byte: 20000,
+///     warnings: Vec::new(),
+/// };
+/// assert!(matches!(policy.check(&preview, &[], 1700000000), Err(SpendingLimitError::PerTransaction { .. })));
+/// ```
+#[derive(Clone, Default)]
+pub struct SpendingPolicy {
+    max_per_tx: Option<u64>,
+    daily_limit: Option<u64>,
+    approvals: Vec<Arc<dyn ApprovalHook>>,
+}
+
+impl fmt::Debug for SpendingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpendingPolicy")
+            .field("max_per_tx", &self.max_per_tx)
+            .field("daily_limit", &self.daily_limit)
+            .field("approvals", &self.approvals.len())
+            .finish()
+    }
+}
+
+impl SpendingPolicy {
+    /// Creates a policy without any limit or hook
+    pub fn new() -> SpendingPolicy {
+        SpendingPolicy::default()
+    }
+
+    /// Refuses transactions spending more than `amount` piconeros, fee included
+    pub fn with_max_per_tx(mut self, amount: u64) -> SpendingPolicy {
+        self.max_per_tx = Some(amount);
+        self
+    }
+
+    /// Refuses transactions bringing the spendings of the last 24 hours above `amount` piconeros, fees included
+    pub fn with_daily_limit(mut self, amount: u64) -> SpendingPolicy {
+        self.daily_limit = Some(amount);
+        self
+    }
+
+    /// Adds a hook that must approve every transaction, closures taking the preview can be used directly
+    ///
+    /// Hooks are called in the order they were added, only for transactions within the limits.
+    pub fn with_approval<H: ApprovalHook + 'static>(mut self, hook: H) -> SpendingPolicy {
+        self.approvals.push(Arc::new(hook));
+        self
+    }
+
+    /// Checks a transaction against the limits then the approval hooks
+    ///
+    /// Arguments:
+    /// - `preview`: the transaction, see [`TxBuilder::dry_run`](struct.TxBuilder.html#method.dry_run)
+    /// - `history`: the wallet history the daily limit is computed from
+    /// - `now`: current Unix time
+    pub fn check(&self, preview: &TxPreview, history: &[SnapshotTransfer], now: u64) -> Result<(), SpendingLimitError> {
+        let amount = preview.amount.saturating_add(preview.fee);
+        if let Some(limit) = self.max_per_tx {
+            if amount > limit {
+                return Err(SpendingLimitError::PerTransaction { amount, limit });
+            }
+        }
+        if let Some(limit) = self.daily_limit {
+            let spent = self.spent_since(history, now.saturating_sub(DAY_SECONDS));
+            if spent.saturating_add(amount) > limit {
+                return Err(SpendingLimitError::Daily { spent, amount, limit });
+            }
+        }
+        for hook in self.approvals.iter() {
+            hook.approve(preview).map_err(SpendingLimitError::Rejected)?;
+        }
+        Ok(())
+    }
+
+    // Checks if the policy has a daily limit, which needs the wallet history
+    pub(crate) fn has_daily_limit(&self) -> bool {
+        self.daily_limit.is_some()
+    }
+
+    // Outgoing amounts and fees of the transfers made after `since`
+    fn spent_since(&self, history: &[SnapshotTransfer], since: u64) -> u64 {
+        history
+            .iter()
+            .filter(|transfer| transfer.outgoing > 0 && transfer.timestamp > since)
+            .map(|transfer| transfer.outgoing.saturating_add(transfer.fee))
+            .fold(0, u64::saturating_add)
+    }
+}
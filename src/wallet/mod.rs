@@ -8,6 +8,44 @@
  *
  */
 
+pub(crate) mod archive;
+pub(crate) mod auto_lock;
+pub(crate) mod builder;
+pub(crate) mod change;
+pub(crate) mod coin_selection;
+#[cfg(feature = "fixtures")]
+pub(crate) mod fixtures;
+pub(crate) mod key_images;
+pub(crate) mod keys_file;
+pub(crate) mod legacy;
+pub(crate) mod limits;
+pub(crate) mod multisig;
+pub(crate) mod outputs_file;
+pub(crate) mod proofs;
+pub(crate) mod receipt;
+pub(crate) mod signer;
+pub(crate) mod snapshot;
+pub(crate) mod txset;
+pub(crate) mod unlock;
 pub(crate) mod wallet;
+pub(crate) mod watch;
 
-pub use wallet::*;
\ No newline at end of file
+pub use auto_lock::*;
+pub use builder::*;
+pub use change::*;
+pub use coin_selection::*;
+#[cfg(feature = "fixtures")]
+pub use fixtures::*;
+pub use key_images::*;
+pub use keys_file::*;
+pub use limits::*;
+pub use multisig::*;
+pub use outputs_file::*;
+pub use proofs::*;
+pub use receipt::*;
+pub use signer::*;
+pub use snapshot::*;
+pub use txset::*;
+pub use unlock::*;
+pub use wallet::*;
+pub use watch::*;
@@ -0,0 +1,846 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+#![allow(non_snake_case)]
+
+use super::archive::{read_bytes, read_key, read_keys, read_varint_u32, write_keys};
+use crate::crypt::clsag::clsag_challenges;
+use crate::crypt::keccak::{hash_to_scalar, keccak256};
+use crate::crypt::{hash_to_point, Clsag};
+use crate::keys::{commit, derive_subaddress, generate_signature, Commitment, KeyImage, OneTimeKey, OwnedOutput, PrivateKey, PublicKey, Signature, ViewPair};
+use crate::utils::varint::write_varint;
+use crate::utils::Network;
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, traits::Identity, EdwardsPoint, Scalar};
+use rand::RngCore;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use zeroize::Zeroizing;
+
+const KEX_MSG_ROUND1_MAGIC: &str = "MultisigxV2R1";
+const KEX_MSG_ROUNDN_MAGIC: &str = "MultisigxV2Rn";
+// Blinding of the keys of a signer before they are shared, monero-core's `HASH_KEY_MULTISIG`, zero padded to 32 bytes
+const MULTISIG_DOMAIN: &[u8] = b"Multisig";
+// Domain of the aggregation coefficients of the final keys, monero-core's `HASH_KEY_MULTISIG_KEY_AGGREGATION`
+const KEY_AGGREGATION_DOMAIN: &[u8] = b"Multisig_key_agg";
+// Domain of the factor merging the two nonces of every signer
+const BINONCE_DOMAIN: &[u8] = b"Multisig_binonce_merge_factor";
+// Most signers monero-core accepts, `config::MULTISIG_MAX_SIGNERS`
+const MAX_SIGNERS: u32 = 16;
+
+// Signer keys as compressed bytes, ordered like monero-core orders them
+type SignerSet = BTreeSet<[u8; 32]>;
+
+// Key exchange message of one signer, monero-core's `multisig_kex_msg`
+struct KexMessage {
+    round: u32,
+    signing_pubkey: PublicKey,
+    // Shares of the common view key sent in the first round
+    msg_privkey: Option<PrivateKey>,
+    // Keys recommended in the other rounds
+    msg_pubkeys: Vec<PublicKey>,
+}
+
+/// MultisigAccount is the share of one signer in an M-of-N multisig wallet, monero-core's `multisig::multisig_account`
+///
+/// Every signer starts from its own spend and view keys and sends the message of
+/// [`next_kex_message`](#method.next_kex_message) to the others, then [`update`](#method.update)s with the messages of
+/// all of them, round after round, until the account [`is_ready`](#method.is_ready). Rounds and messages are the ones of
+/// monero-wallet-cli's `prepare_multisig`, `make_multisig` and `exchange_multisig_keys`, `N - M + 1` rounds followed by
+/// a round checking that every signer computed the same multisig key. The keys of every signer are blinded before being
+/// shared, so the wallets they come from stay unlinkable to the multisig wallet.
+///
+/// Once ready, the account holds the common view key and shares of the multisig spend key, such that any `M` signers
+/// together hold all of them. Spending goes through [`partial_key_image`](#method.partial_key_image) to build key
+/// images and through [`MultisigClsagContext`](struct.MultisigClsagContext.html) to sign inputs. The reference
+/// wallet's `export_multisig_info` and multisig transaction set files are boost archives and are not produced.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::PrivateKey;
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::MultisigAccount;
+///
+/// let spend_keys = [[1u8; 32], [2u8; 32], [3u8; 32]].map(PrivateKey::from_bytes_mod_order);
+/// let mut accounts: Vec<MultisigAccount> = spend_keys.iter()
+///     .map(|key| MultisigAccount::new(key, &key.derive_view_key(), 2, 3).unwrap())
+///     .collect();
+/// while !accounts[0].is_ready() {
+///     let messages: Vec<String> = accounts.iter().map(|account| account.next_kex_message().to_string()).collect();
+///     accounts.iter_mut().for_each(|account| account.update(&messages).unwrap());
+/// }
+/// let address = accounts[0].address(Network::Mainnet).unwrap();
+/// assert!(accounts.iter().all(|account| account.address(Network::Mainnet).unwrap() == address));
+/// ```
+pub struct MultisigAccount {
+    threshold: u32,
+    total: u32,
+    base_privkey: PrivateKey,
+    base_pubkey: PublicKey,
+    base_common_privkey: PrivateKey,
+    signers: Vec<PublicKey>,
+    common_privkey: Option<PrivateKey>,
+    // Shares of the multisig spend key held by this signer, with every signer holding them
+    multisig_keys: Vec<(PrivateKey, SignerSet)>,
+    // Public shares of the multisig spend key with their aggregation coefficients applied, sorted
+    group_keys: Vec<PublicKey>,
+    multisig_pubkey: Option<PublicKey>,
+    kex_rounds_complete: u32,
+    // Keys this signer recommended in its last message, with the signers sharing them
+    kex_keys: BTreeMap<[u8; 32], SignerSet>,
+    next_kex_message: String,
+}
+
+impl MultisigAccount {
+    /// Starts the key exchange of a `threshold`-of-`total` multisig wallet from the keys of one signer
+    ///
+    /// The keys should be the ones of a fresh wallet, used for nothing else.
+    ///
+    /// Returns the account if succesfull
+    /// Returns an error message if the threshold is 0 or above `total`, or `total` is below 2 or above 16
+    pub fn new(private_spend_key: &PrivateKey, private_view_key: &PrivateKey, threshold: u32, total: u32) -> Result<MultisigAccount, String> {
+        if !(2..=MAX_SIGNERS).contains(&total) {
+            return Err(format!("Multisig wallets need 2 to {} signers, got {}", MAX_SIGNERS, total));
+        }
+        if threshold == 0 || threshold > total {
+            return Err(format!("Threshold must be between 1 and {}, got {}", total, threshold));
+        }
+        let base_privkey = blinded_key(private_spend_key.as_bytes());
+        let base_common_privkey = blinded_key(private_view_key.as_bytes());
+        let base_pubkey = base_privkey.public_key();
+        let next_kex_message = write_kex_message(1, &base_privkey, &[], Some(&base_common_privkey))?;
+        Ok(MultisigAccount {
+            threshold,
+            total,
+            base_privkey,
+            base_pubkey,
+            base_common_privkey,
+            signers: Vec::new(),
+            common_privkey: None,
+            multisig_keys: Vec::new(),
+            group_keys: Vec::new(),
+            multisig_pubkey: None,
+            kex_rounds_complete: 0,
+            kex_keys: BTreeMap::new(),
+            next_kex_message,
+        })
+    }
+
+    /// Returns the number of signers needed to spend
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Returns the key identifying this signer in key exchange messages and signing rounds
+    pub fn signer_key(&self) -> PublicKey {
+        self.base_pubkey
+    }
+
+    /// Returns the keys of every signer sorted, empty until the first round is complete
+    pub fn signers(&self) -> &[PublicKey] {
+        &self.signers
+    }
+
+    /// Returns the number of key exchange rounds, the final verification round included
+    pub fn kex_rounds_required(&self) -> u32 {
+        self.total - self.threshold + 2
+    }
+
+    /// Returns the number of key exchange rounds already processed
+    pub fn kex_rounds_complete(&self) -> u32 {
+        self.kex_rounds_complete
+    }
+
+    /// Returns whether the key exchange is complete and the account can receive and spend
+    pub fn is_ready(&self) -> bool {
+        self.kex_rounds_complete == self.kex_rounds_required()
+    }
+
+    /// Returns the message to send to the other signers for the next round
+    ///
+    /// Once the key exchange is complete this stays the message of the verification round, which late signers may
+    /// still need.
+    pub fn next_kex_message(&self) -> &str {
+        &self.next_kex_message
+    }
+
+    /// Processes the messages of the other signers for the current round
+    ///
+    /// `messages` holds the message of every other signer for the round, the message of this signer may be included.
+    ///
+    /// Returns nothing if succesfull
+    /// Returns an error message if the key exchange is complete, a message is invalid, of another round or comes from
+    /// an unknown signer, a signer is missing or the messages do not agree with each other
+    pub fn update(&mut self, messages: &[String]) -> Result<(), String> {
+        if self.is_ready() {
+            return Err("Key exchange is already complete".to_string());
+        }
+        let round = self.kex_rounds_complete + 1;
+        let mut received: BTreeMap<[u8; 32], KexMessage> = BTreeMap::new();
+        for message in messages {
+            let message = read_kex_message(message)?;
+            if message.round != round {
+                return Err(format!("Got a key exchange message of round {} in round {}", message.round, round));
+            }
+            if message.signing_pubkey == self.base_pubkey {
+                continue;
+            }
+            if round > 1 && !self.signers.contains(&message.signing_pubkey) {
+                return Err(format!("Key exchange message from unknown signer {}", message.signing_pubkey.to_hex()));
+            }
+            let signer = *message.signing_pubkey.as_bytes();
+            if let Some(previous) = received.get(&signer) {
+                if previous.msg_privkey != message.msg_privkey || previous.msg_pubkeys != message.msg_pubkeys {
+                    return Err(format!("Signer {} sent conflicting key exchange messages", message.signing_pubkey.to_hex()));
+                }
+            }
+            received.insert(signer, message);
+        }
+        if received.len() as u32 + 1 != self.total {
+            return Err(format!("Round {} needs the messages of the {} other signers, got {}", round, self.total - 1, received.len()));
+        }
+
+        let main_rounds = self.kex_rounds_required() - 1;
+        if round > main_rounds {
+            let multisig_pubkey = self.multisig_pubkey.ok_or("Multisig key is missing")?;
+            if let Some((_, message)) = received.iter().find(|(_, message)| message.msg_pubkeys != [multisig_pubkey]) {
+                return Err(format!("Signer {} computed another multisig key", message.signing_pubkey.to_hex()));
+            }
+            self.kex_rounds_complete = round;
+            return Ok(());
+        }
+
+        if round == 1 {
+            let mut shares: Vec<[u8; 32]> = received.values().filter_map(|message| message.msg_privkey.as_ref()).map(|key| *key.as_bytes()).collect();
+            shares.push(*self.base_common_privkey.as_bytes());
+            shares.sort_unstable();
+            let shares = Zeroizing::new(shares.concat());
+            self.common_privkey = Some(PrivateKey::from_bytes_mod_order(hash_to_scalar(&shares).to_bytes()));
+            let mut signers: Vec<PublicKey> = received.values().map(|message| message.signing_pubkey).collect();
+            signers.push(self.base_pubkey);
+            signers.sort_unstable_by_key(|key| *key.as_bytes());
+            self.signers = signers;
+            if main_rounds == 1 {
+                self.multisig_keys = vec![(self.base_privkey.clone(), SignerSet::from([*self.base_pubkey.as_bytes()]))];
+            }
+        }
+
+        // Keys of this round the signer did not help making, with the signers recommending them
+        let mut origins: BTreeMap<[u8; 32], (PublicKey, SignerSet)> = BTreeMap::new();
+        for (signer, message) in received.iter() {
+            let keys = match round {
+                1 => vec![message.signing_pubkey],
+                _ => message.msg_pubkeys.clone(),
+            };
+            for key in keys.into_iter().filter(|key| !self.kex_keys.contains_key(key.as_bytes())) {
+                origins.entry(*key.as_bytes()).or_insert((key, SignerSet::new())).1.insert(*signer);
+            }
+        }
+        let others = self.total as usize - 1;
+        if origins.len() != binomial(others, round as usize) || origins.values().any(|(_, signers)| signers.len() != round as usize) {
+            return Err(format!("Key exchange messages of round {} do not agree with each other", round));
+        }
+
+        let next_keys = if round == main_rounds {
+            self.aggregate(origins.values().map(|(key, _)| *key).collect())?;
+            vec![self.multisig_pubkey.ok_or("Multisig key is missing")?]
+        } else {
+            let mut kex_keys = BTreeMap::new();
+            let mut multisig_keys = Vec::new();
+            for (key, signers) in origins.into_values() {
+                let mut holders = signers;
+                holders.insert(*self.base_pubkey.as_bytes());
+                let derivation = (key.to_point() * self.base_privkey.to_scalar()).mul_by_cofactor();
+                if round + 1 == main_rounds {
+                    let privkey = blinded_key(derivation.compress().as_bytes());
+                    kex_keys.insert(*privkey.public_key().as_bytes(), holders.clone());
+                    multisig_keys.push((privkey, holders));
+                } else {
+                    kex_keys.insert(derivation.compress().to_bytes(), holders);
+                }
+            }
+            self.kex_keys = kex_keys;
+            self.multisig_keys = multisig_keys;
+            self.kex_keys.keys().map(|key| PublicKey::from_bytes(*key)).collect::<Result<Vec<PublicKey>, String>>()?
+        };
+        self.kex_rounds_complete = round;
+        self.next_kex_message = write_kex_message(round + 1, &self.base_privkey, &next_keys, None)?;
+        Ok(())
+    }
+
+    /// Returns the view pair of the multisig wallet, to scan for its outputs like any other wallet
+    ///
+    /// Returns the view pair if succesfull
+    /// Returns an error message if the key exchange is not complete
+    pub fn view_pair(&self) -> Result<ViewPair, String> {
+        match (self.multisig_pubkey, &self.common_privkey, self.is_ready()) {
+            (Some(spend), Some(view), true) => Ok(ViewPair::new(spend, view.clone())),
+            _ => Err("Key exchange is not complete".to_string()),
+        }
+    }
+
+    /// Returns the primary address of the multisig wallet on the given network
+    ///
+    /// Returns the address if succesfull
+    /// Returns an error message if the key exchange is not complete
+    pub fn address(&self, network: Network) -> Result<String, String> {
+        Ok(derive_subaddress(&self.view_pair()?, network, 0, 0))
+    }
+
+    /// Exports the keys of this signer as a hex string, in the layout of monero-wallet-cli's `export_multisig_seed`
+    ///
+    /// The layout is the threshold and number of signers as 32-bit little-endian integers, the base spend key of the
+    /// signer, the multisig spend key, the common view key and its public key, the shares of the multisig spend key
+    /// held by the signer and the keys of every signer.
+    ///
+    /// Returns the seed if succesfull
+    /// Returns an error message if the key exchange is not complete
+    pub fn multisig_seed(&self) -> Result<Zeroizing<String>, String> {
+        let view_pair = self.view_pair()?;
+        let mut data = Zeroizing::new(Vec::with_capacity(8 + 32 * (4 + self.multisig_keys.len() + self.signers.len())));
+        data.extend_from_slice(&self.threshold.to_le_bytes());
+        data.extend_from_slice(&self.total.to_le_bytes());
+        data.extend_from_slice(self.base_privkey.as_bytes());
+        data.extend_from_slice(view_pair.spend.as_bytes());
+        data.extend_from_slice(view_pair.view.as_bytes());
+        data.extend_from_slice(view_pair.view.public_key().as_bytes());
+        self.multisig_keys.iter().for_each(|(key, _)| data.extend_from_slice(key.as_bytes()));
+        self.signers.iter().for_each(|key| data.extend_from_slice(key.as_bytes()));
+        Ok(Zeroizing::new(hex::encode(&*data)))
+    }
+
+    /// Computes the shares of the key image of an output held by this signer
+    ///
+    /// Key images of multisig outputs need shares from at least `threshold` signers, combined with
+    /// [`combine_key_image`](#method.combine_key_image).
+    ///
+    /// Returns the shares if succesfull
+    /// Returns an error message if the key exchange is not complete
+    pub fn partial_key_image(&self, output_key: &OneTimeKey) -> Result<MultisigPartialKeyImage, String> {
+        self.view_pair()?;
+        let H = hash_to_point(output_key.as_bytes());
+        Ok(MultisigPartialKeyImage {
+            signer: self.base_pubkey,
+            output_key: *output_key,
+            shares: self
+                .multisig_keys
+                .iter()
+                .map(|(key, _)| (key.public_key(), KeyImage::from_bytes((H * key.to_scalar()).compress().to_bytes())))
+                .collect(),
+        })
+    }
+
+    /// Combines the key image shares of several signers into the key image of an output of the multisig wallet
+    ///
+    /// Shares are not proven, a wrong share gives a wrong key image which the final signature then fails to sign for.
+    ///
+    /// Returns the key image if succesfull
+    /// Returns an error message if the key exchange is not complete, the output does not belong to the multisig
+    /// wallet or the signers giving shares do not hold every share of the multisig spend key
+    pub fn combine_key_image(&self, output: &OwnedOutput, partials: &[MultisigPartialKeyImage]) -> Result<KeyImage, String> {
+        let output_secret = self.output_secret(output)?;
+        let H = hash_to_point(output.output_key.as_bytes());
+        let mut key_image = H * output_secret;
+        for group_key in self.group_keys.iter() {
+            let share = partials
+                .iter()
+                .filter(|partial| partial.output_key == output.output_key)
+                .flat_map(|partial| partial.shares.iter())
+                .find(|(key, _)| key == group_key)
+                .ok_or(format!("No signer gave the key image share of {}", group_key.to_hex()))?;
+            key_image += key_image_point(&share.1)?;
+        }
+        Ok(KeyImage::from_bytes(key_image.compress().to_bytes()))
+    }
+
+    /// Draws the nonces of this signer for signing the input spending `output_key`
+    ///
+    /// Every signer of the input shares the [`commitment`](struct.MultisigNonces.html#method.commitment) of its nonces
+    /// before any partial signature is made. Nonces sign a single input once.
+    ///
+    /// Returns the nonces if succesfull
+    /// Returns an error message if the key exchange is not complete or `output_key` is not a valid point
+    pub fn commit_nonces(&self, output_key: &OneTimeKey) -> Result<MultisigNonces, String> {
+        self.view_pair()?;
+        output_key.to_public_key()?;
+        let H = hash_to_point(output_key.as_bytes());
+        let mut rng = rand::thread_rng();
+        let secrets = Zeroizing::new([random_scalar(&mut rng), random_scalar(&mut rng)]);
+        let commitment = MultisigNonceCommitment {
+            signer: self.base_pubkey,
+            output_key: *output_key,
+            nonces_g: secrets.map(|secret| PublicKey::from_point(ED25519_BASEPOINT_TABLE * &secret)),
+            nonces_hp: secrets.map(|secret| PublicKey::from_point(H * secret)),
+        };
+        Ok(MultisigNonces { secrets, commitment })
+    }
+
+    /// Prepares the signature of the input spending `output`, for every signer to [`partial_sign`](#method.partial_sign)
+    ///
+    /// The responses of the other ring members are drawn at random. The pseudo output commitment of the input is
+    /// [`commit(output.amount, pseudo_mask)`](../keys/fn.commit.html), as for [`clsag_sign`](../crypt/fn.clsag_sign.html).
+    ///
+    /// Arguments:
+    /// - `output`: the spent output of the multisig wallet
+    /// - `message`: the message signed by every input, `get_pre_mlsag_hash` of the transaction
+    /// - `ring`: the one-time keys and commitments of the ring members, the spent output among them
+    /// - `key_image`: key image of the output, see [`combine_key_image`](#method.combine_key_image)
+    /// - `mask`: mask of the commitment of the spent output
+    /// - `pseudo_mask`: mask of the pseudo output commitment
+    ///
+    /// Returns the context if succesfull
+    /// Returns an error message if the key exchange is not complete, the output does not belong to the multisig wallet
+    /// or is not in the ring, or the masks do not open the commitments
+    pub fn clsag_context(
+        &self,
+        output: &OwnedOutput,
+        message: &[u8; 32],
+        ring: &[(OneTimeKey, Commitment)],
+        key_image: &KeyImage,
+        mask: &Scalar,
+        pseudo_mask: &Scalar,
+    ) -> Result<MultisigClsagContext, String> {
+        let output_secret = Zeroizing::new(self.output_secret(output)?);
+        let real_index = ring.iter().position(|(key, _)| *key == output.output_key).ok_or("Spent output is not in the ring")?;
+        if commit(output.amount, mask) != ring[real_index].1 {
+            return Err("Mask does not open the commitment of the spent output".to_string());
+        }
+        let mask_difference = Zeroizing::new(mask - pseudo_mask);
+        if *mask_difference == Scalar::ZERO {
+            return Err("Pseudo output must not reuse the mask of the spent output".to_string());
+        }
+        let mut rng = rand::thread_rng();
+        Ok(MultisigClsagContext {
+            message: *message,
+            ring: ring.to_vec(),
+            real_index,
+            pseudo_out: commit(output.amount, pseudo_mask),
+            key_image: *key_image,
+            responses: (0..ring.len()).map(|i| if i == real_index { [0u8; 32] } else { random_scalar(&mut rng).to_bytes() }).collect(),
+            output_secret: output_secret.to_bytes(),
+            mask_difference: mask_difference.to_bytes(),
+        })
+    }
+
+    /// Signs this signer's part of an input
+    ///
+    /// Arguments:
+    /// - `context`: the input to sign, as prepared by one of the signers
+    /// - `nonces`: the nonces of this signer for the input, used up by signing
+    /// - `commitments`: the nonce commitments of every signer of the input, this one included
+    ///
+    /// Returns the partial signature if succesfull
+    /// Returns an error message if the key exchange is not complete, the context is invalid or not for this wallet,
+    /// the nonces are for another input or the signers are fewer than the threshold
+    pub fn partial_sign(&self, context: &MultisigClsagContext, nonces: MultisigNonces, commitments: &[MultisigNonceCommitment]) -> Result<MultisigPartialSignature, String> {
+        let multisig_pubkey = self.view_pair()?.spend;
+        let output_secret = context.output_secret()?;
+        let real_key = context.ring.get(context.real_index).ok_or("Real index is outside of the ring")?.0;
+        if real_key.as_bytes() != (ED25519_BASEPOINT_TABLE * &output_secret + multisig_pubkey.to_point()).compress().as_bytes() {
+            return Err("Spent output does not belong to the multisig wallet".to_string());
+        }
+        if nonces.commitment.output_key != real_key {
+            return Err("Nonces are for another input".to_string());
+        }
+        if !commitments.contains(&nonces.commitment) {
+            return Err("Nonce commitments miss the ones of this signer".to_string());
+        }
+        let signing_set = signing_set(commitments)?;
+        if signing_set.len() < self.threshold as usize {
+            return Err(format!("Signing needs {} signers, got {}", self.threshold, signing_set.len()));
+        }
+        if let Some(signer) = signing_set.iter().find(|signer| !self.signers.iter().any(|key| key.as_bytes() == *signer)) {
+            return Err(format!("Unknown signer {}", hex::encode(signer)));
+        }
+
+        let (mu_P, _, c, _, b) = context.challenges(commitments)?;
+        // Every share of the multisig spend key is used by the first signer holding it
+        let mut secret = Zeroizing::new(Scalar::ZERO);
+        for (key, holders) in self.multisig_keys.iter() {
+            if holders.intersection(&signing_set).next() == Some(self.base_pubkey.as_bytes()) {
+                *secret += key.to_scalar();
+            }
+        }
+        let response = nonces.secrets[0] + b * nonces.secrets[1] - c * mu_P * *secret;
+        Ok(MultisigPartialSignature {
+            signer: self.base_pubkey,
+            response: response.to_bytes(),
+        })
+    }
+
+    // Part of the one-time private key of an output every signer knows, Hs(8aR || i) plus the subaddress secret
+    fn output_secret(&self, output: &OwnedOutput) -> Result<Scalar, String> {
+        let view_pair = self.view_pair()?;
+        let secret = output.secret_key(&view_pair.view, &PrivateKey::from_bytes_mod_order([0u8; 32])).to_scalar();
+        if output.output_key.as_bytes() != (ED25519_BASEPOINT_TABLE * &secret + view_pair.spend.to_point()).compress().as_bytes() {
+            return Err("Output does not belong to the multisig wallet".to_string());
+        }
+        Ok(secret)
+    }
+
+    // Weights every final key with its aggregation coefficient and sums them into the multisig spend key,
+    // monero-core's `generate_multisig_aggregate_key`
+    fn aggregate(&mut self, received_keys: Vec<PublicKey>) -> Result<(), String> {
+        let mut keys: Vec<PublicKey> = received_keys;
+        keys.extend(self.multisig_keys.iter().map(|(key, _)| key.public_key()));
+        keys.sort_unstable_by_key(|key| *key.as_bytes());
+        keys.dedup();
+        let expected = binomial(self.total as usize, (self.total - self.threshold + 1) as usize);
+        if keys.len() != expected {
+            return Err(format!("Key exchange gave {} multisig key shares, expected {}", keys.len(), expected));
+        }
+
+        let mut data = Vec::with_capacity(32 * (keys.len() + 1) + KEY_AGGREGATION_DOMAIN.len());
+        data.extend_from_slice(KEY_AGGREGATION_DOMAIN);
+        keys.iter().for_each(|key| data.extend_from_slice(key.as_bytes()));
+        let coefficient = |key: &PublicKey| {
+            let mut data = data.clone();
+            data.extend_from_slice(key.as_bytes());
+            hash_to_scalar(&data)
+        };
+
+        let mut multisig_pubkey = EdwardsPoint::identity();
+        let mut group_keys = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            let group_key = key.to_point() * coefficient(key);
+            multisig_pubkey += group_key;
+            group_keys.push(PublicKey::from_point(group_key));
+        }
+        for (privkey, _) in self.multisig_keys.iter_mut() {
+            let mu = coefficient(&privkey.public_key());
+            *privkey = PrivateKey::from_bytes_mod_order((privkey.to_scalar() * mu).to_bytes());
+        }
+        group_keys.sort_unstable_by_key(|key| *key.as_bytes());
+        self.group_keys = group_keys;
+        self.multisig_pubkey = Some(PublicKey::from_point(multisig_pubkey));
+        self.kex_keys.clear();
+        Ok(())
+    }
+}
+
+impl fmt::Debug for MultisigAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret material
+        f.debug_struct("MultisigAccount")
+            .field("threshold", &self.threshold)
+            .field("total", &self.total)
+            .field("signer_key", &self.base_pubkey)
+            .field("kex_rounds_complete", &self.kex_rounds_complete)
+            .field("multisig_pubkey", &self.multisig_pubkey)
+            .finish_non_exhaustive()
+    }
+}
+
+/// MultisigPartialKeyImage holds the key image shares of an output known to one signer, see
+/// [`MultisigAccount::partial_key_image`](struct.MultisigAccount.html#method.partial_key_image)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultisigPartialKeyImage {
+    /// Key of the signer giving the shares
+    pub signer: PublicKey,
+    /// One-time key of the output
+    pub output_key: OneTimeKey,
+    /// Share of the multisig spend key and its key image share
+    pub shares: Vec<(PublicKey, KeyImage)>,
+}
+
+/// MultisigNonceCommitment is the public part of the nonces of a signer for one input, shared with the other signers
+/// before signing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultisigNonceCommitment {
+    /// Key of the signer
+    pub signer: PublicKey,
+    /// One-time key of the spent output
+    pub output_key: OneTimeKey,
+    /// Both nonces multiplied by the base point
+    pub nonces_g: [PublicKey; 2],
+    /// Both nonces multiplied by the hash to point of the spent output's key
+    pub nonces_hp: [PublicKey; 2],
+}
+
+/// MultisigNonces are the secret nonces of a signer for one input, see
+/// [`MultisigAccount::commit_nonces`](struct.MultisigAccount.html#method.commit_nonces)
+///
+/// The nonces are wiped from memory when dropped
+pub struct MultisigNonces {
+    secrets: Zeroizing<[Scalar; 2]>,
+    commitment: MultisigNonceCommitment,
+}
+
+impl MultisigNonces {
+    /// Returns the commitment to share with the other signers
+    pub fn commitment(&self) -> MultisigNonceCommitment {
+        self.commitment
+    }
+}
+
+impl fmt::Debug for MultisigNonces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret material
+        f.debug_struct("MultisigNonces").field("commitment", &self.commitment).finish_non_exhaustive()
+    }
+}
+
+/// MultisigPartialSignature is the response of one signer to the challenge of an input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultisigPartialSignature {
+    /// Key of the signer
+    pub signer: PublicKey,
+    /// Partial response of the spent ring member
+    pub response: [u8; 32],
+}
+
+/// MultisigClsagContext holds everything the signers of a multisig input agree on before signing it, see
+/// [`MultisigAccount::clsag_context`](struct.MultisigAccount.html#method.clsag_context)
+///
+/// Signing takes two rounds: every signer shares the commitment of its nonces, then its
+/// [partial signature](struct.MultisigAccount.html#method.partial_sign), which [`combine`](#method.combine) merges into
+/// the CLSAG of the input. The two nonces of every signer are merged with a factor depending on all commitments, so a
+/// signer choosing its commitments last cannot bias the signature.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{commit, OneTimeKey, OwnedOutput, PrivateKey};
+/// use libmonero::wallet::{MultisigAccount, MultisigClsagContext};
+/// use curve25519_dalek::Scalar;
+///
+/// let spend_keys = [[1u8; 32], [2u8; 32], [3u8; 32]].map(PrivateKey::from_bytes_mod_order);
+/// let mut accounts: Vec<MultisigAccount> = spend_keys.iter()
+///     .map(|key| MultisigAccount::new(key, &key.derive_view_key(), 2, 3).unwrap())
+///     .collect();
+/// while !accounts[0].is_ready() {
+///     let messages: Vec<String> = accounts.iter().map(|account| account.next_kex_message().to_string()).collect();
+///     accounts.iter_mut().for_each(|account| account.update(&messages).unwrap());
+/// }
+///
+/// // An output received by the multisig wallet, and a decoy
+/// let view_pair = accounts[0].view_pair().unwrap();
+/// let tx_secret_key = PrivateKey::from_bytes_mod_order([9u8; 32]);
+/// let tx_public_key = tx_secret_key.public_key();
+/// let derivation = libmonero::keys::generate_key_derivation(&view_pair.view.public_key(), &tx_secret_key);
+/// let output_key = libmonero::keys::derive_output_pubkey(&derivation, 0, &view_pair.spend);
+/// let output = OwnedOutput { index: 0, amount: 1000, output_key: output_key.into(), tx_public_key, pre_rct: false, subaddress: (0, 0) };
+/// let decoy = PrivateKey::from_bytes_mod_order([8u8; 32]).public_key();
+/// let (mask, pseudo_mask) = (Scalar::from(7u64), Scalar::from(11u64));
+/// let ring = vec![(OneTimeKey::from(decoy), commit(5, &Scalar::from(3u64))), (output.output_key, commit(1000, &mask))];
+///
+/// // Signers 0 and 2 spend it
+/// let partials = [accounts[0].partial_key_image(&output.output_key).unwrap(), accounts[2].partial_key_image(&output.output_key).unwrap()];
+/// let key_image = accounts[0].combine_key_image(&output, &partials).unwrap();
+/// let context = accounts[0].clsag_context(&output, &[0x42; 32], &ring, &key_image, &mask, &pseudo_mask).unwrap();
+/// let nonces = [accounts[0].commit_nonces(&output.output_key).unwrap(), accounts[2].commit_nonces(&output.output_key).unwrap()];
+/// let commitments = [nonces[0].commitment(), nonces[1].commitment()];
+/// let [first, second] = nonces;
+/// let signatures = [
+///     accounts[0].partial_sign(&context, first, &commitments).unwrap(),
+///     accounts[2].partial_sign(&context, second, &commitments).unwrap(),
+/// ];
+/// let clsag = context.combine(&commitments, &signatures).unwrap();
+/// assert!(clsag.verify(&[0x42; 32], &ring, &key_image, &context.pseudo_out));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultisigClsagContext {
+    /// The message signed by every input
+    pub message: [u8; 32],
+    /// One-time keys and commitments of the ring members
+    pub ring: Vec<(OneTimeKey, Commitment)>,
+    /// Position of the spent output in the ring
+    pub real_index: usize,
+    /// Pseudo output commitment of the input
+    pub pseudo_out: Commitment,
+    /// Key image of the spent output
+    pub key_image: KeyImage,
+    /// Responses of the ring members, the one of the spent output being filled in by the signers
+    pub responses: Vec<[u8; 32]>,
+    /// Part of the one-time private key of the spent output known to every signer, without the multisig spend key
+    pub output_secret: [u8; 32],
+    /// Mask of the spent output's commitment minus the mask of the pseudo output
+    pub mask_difference: [u8; 32],
+}
+
+impl MultisigClsagContext {
+    /// Merges the partial signatures of every signer into the CLSAG of the input, and checks it
+    ///
+    /// Returns the signature if succesfull
+    /// Returns an error message if the context is invalid, the partial signatures do not come from the signers of the
+    /// commitments or do not combine into a valid signature
+    pub fn combine(&self, commitments: &[MultisigNonceCommitment], partials: &[MultisigPartialSignature]) -> Result<Clsag, String> {
+        let signing_set = signing_set(commitments)?;
+        let signers: SignerSet = partials.iter().map(|partial| *partial.signer.as_bytes()).collect();
+        if signers != signing_set || partials.len() != signers.len() {
+            return Err("Partial signatures must come from the signers of the nonce commitments, once each".to_string());
+        }
+        let (mu_P, mu_C, c, c1, _) = self.challenges(commitments)?;
+        let mut response = Scalar::ZERO;
+        for partial in partials {
+            response += Option::<Scalar>::from(Scalar::from_canonical_bytes(partial.response)).ok_or("Partial response is not a reduced scalar")?;
+        }
+        response -= c * (mu_P * self.output_secret()? + mu_C * canonical(&self.mask_difference)?);
+
+        let mut s = self.responses.clone();
+        s[self.real_index] = response.to_bytes();
+        let D = hash_to_point(self.ring[self.real_index].0.as_bytes()) * canonical(&self.mask_difference)?;
+        let clsag = Clsag {
+            s,
+            c1: c1.to_bytes(),
+            D: (D * Scalar::from(8u8).invert()).compress().to_bytes(),
+        };
+        if !clsag.verify(&self.message, &self.ring, &self.key_image, &self.pseudo_out) {
+            return Err("Partial signatures do not combine into a valid signature".to_string());
+        }
+        Ok(clsag)
+    }
+
+    fn output_secret(&self) -> Result<Scalar, String> {
+        canonical(&self.output_secret)
+    }
+
+    // Merges the nonces of every signer and goes around the ring, returning mu_P, mu_C, the challenge of the spent
+    // output, the challenge of the first member and the binonce merge factor
+    fn challenges(&self, commitments: &[MultisigNonceCommitment]) -> Result<(Scalar, Scalar, Scalar, Scalar, Scalar), String> {
+        let n = self.ring.len();
+        if self.real_index >= n || self.responses.len() != n {
+            return Err("Context must hold one response per ring member and a real index inside the ring".to_string());
+        }
+        let real_key = self.ring[self.real_index].0;
+        if commitments.iter().any(|commitment| commitment.output_key != real_key) {
+            return Err("Nonce commitments are for another input".to_string());
+        }
+        let s = self.responses.iter().map(canonical).collect::<Result<Vec<Scalar>, String>>()?;
+        let I = PublicKey::from_bytes(*self.key_image.as_bytes()).map_err(|_| "Key image is not a valid point")?.to_point();
+        let D = hash_to_point(real_key.as_bytes()) * canonical(&self.mask_difference)?;
+
+        let mut nonces = [EdwardsPoint::identity(); 4];
+        for commitment in commitments {
+            nonces[0] += commitment.nonces_g[0].to_point();
+            nonces[1] += commitment.nonces_g[1].to_point();
+            nonces[2] += commitment.nonces_hp[0].to_point();
+            nonces[3] += commitment.nonces_hp[1].to_point();
+        }
+        let mut data = Vec::with_capacity(BINONCE_DOMAIN.len() + 32 * (2 * n + 8));
+        data.extend_from_slice(BINONCE_DOMAIN);
+        data.extend_from_slice(&self.message);
+        self.ring.iter().for_each(|(key, commitment)| {
+            data.extend_from_slice(key.as_bytes());
+            data.extend_from_slice(commitment.as_bytes());
+        });
+        data.extend_from_slice(self.pseudo_out.as_bytes());
+        data.extend_from_slice(self.key_image.as_bytes());
+        data.extend_from_slice(D.compress().as_bytes());
+        nonces.iter().for_each(|nonce| data.extend_from_slice(nonce.compress().as_bytes()));
+        let b = hash_to_scalar(&data);
+
+        let (L, R) = (nonces[0] + nonces[1] * b, nonces[2] + nonces[3] * b);
+        let (mu_P, mu_C, c, c1) = clsag_challenges(&self.message, &self.ring, self.real_index, (&I, &D), &self.pseudo_out, &s, (&L, &R))
+            .ok_or("Ring keys and commitments must be valid points")?;
+        Ok((mu_P, mu_C, c, c1, b))
+    }
+}
+
+// Signers of an input, from their nonce commitments
+fn signing_set(commitments: &[MultisigNonceCommitment]) -> Result<SignerSet, String> {
+    let signers: SignerSet = commitments.iter().map(|commitment| *commitment.signer.as_bytes()).collect();
+    if signers.len() != commitments.len() {
+        return Err("Every signer must give exactly one nonce commitment".to_string());
+    }
+    Ok(signers)
+}
+
+// Hs(key || "Multisig"), monero-core's `get_multisig_blinded_secret_key`
+fn blinded_key(key: &[u8; 32]) -> PrivateKey {
+    let mut data = Zeroizing::new([0u8; 64]);
+    data[..32].copy_from_slice(key);
+    data[32..32 + MULTISIG_DOMAIN.len()].copy_from_slice(MULTISIG_DOMAIN);
+    PrivateKey::from_bytes_mod_order(hash_to_scalar(&*data).to_bytes())
+}
+
+fn canonical(bytes: &[u8; 32]) -> Result<Scalar, String> {
+    Option::<Scalar>::from(Scalar::from_canonical_bytes(*bytes)).ok_or("Context scalars must be reduced".to_string())
+}
+
+fn key_image_point(share: &KeyImage) -> Result<EdwardsPoint, String> {
+    Ok(PublicKey::from_bytes(*share.as_bytes()).map_err(|_| "Key image share is not a valid point")?.to_point())
+}
+
+fn random_scalar<R: RngCore>(rng: &mut R) -> Scalar {
+    let mut bytes = Zeroizing::new([0u8; 64]);
+    rng.fill_bytes(&mut *bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |result, i| result * (n - i) / (i + 1))
+}
+
+// keccak(magic || round || signing key || view key share or recommended keys), signed by the signing key
+fn kex_message_hash(magic: &str, round: u32, signing_pubkey: &PublicKey, msg_privkey: Option<&PrivateKey>, msg_pubkeys: &[PublicKey]) -> [u8; 32] {
+    let mut data = Zeroizing::new(Vec::with_capacity(magic.len() + 4 + 32 * (msg_pubkeys.len() + 2)));
+    data.extend_from_slice(magic.as_bytes());
+    data.extend_from_slice(&round.to_le_bytes());
+    data.extend_from_slice(signing_pubkey.as_bytes());
+    if let Some(privkey) = msg_privkey {
+        data.extend_from_slice(privkey.as_bytes());
+    }
+    msg_pubkeys.iter().for_each(|key| data.extend_from_slice(key.as_bytes()));
+    keccak256(&data)
+}
+
+fn write_kex_message(round: u32, signing_privkey: &PrivateKey, msg_pubkeys: &[PublicKey], msg_privkey: Option<&PrivateKey>) -> Result<String, String> {
+    let signing_pubkey = signing_privkey.public_key();
+    let magic = if round == 1 { KEX_MSG_ROUND1_MAGIC } else { KEX_MSG_ROUNDN_MAGIC };
+    let signature = generate_signature(&kex_message_hash(magic, round, &signing_pubkey, msg_privkey, msg_pubkeys), signing_privkey);
+    let mut data = Zeroizing::new(Vec::new());
+    match msg_privkey {
+        Some(privkey) => data.extend_from_slice(privkey.as_bytes()),
+        None => {
+            write_varint(round as u64, &mut data);
+            write_keys(&msg_pubkeys.iter().map(|key| *key.as_bytes()).collect::<Vec<[u8; 32]>>(), &mut data);
+        }
+    }
+    data.extend_from_slice(signing_pubkey.as_bytes());
+    data.extend_from_slice(&signature.to_bytes());
+    Ok(format!("{}{}", magic, base58_monero::encode(&data).map_err(|e| format!("Invalid base58 encoding: {}", e))?))
+}
+
+fn read_kex_message(message: &str) -> Result<KexMessage, String> {
+    let (round1, encoded) = match (message.strip_prefix(KEX_MSG_ROUND1_MAGIC), message.strip_prefix(KEX_MSG_ROUNDN_MAGIC)) {
+        (Some(encoded), _) => (true, encoded),
+        (_, Some(encoded)) => (false, encoded),
+        _ => return Err("Key exchange message must start with MultisigxV2R1 or MultisigxV2Rn".to_string()),
+    };
+    let data = Zeroizing::new(base58_monero::decode(encoded).map_err(|e| format!("Invalid base58 encoding: {}", e))?);
+    let mut pos = 0;
+    let (round, msg_privkey, msg_pubkeys) = if round1 {
+        (1, Some(PrivateKey::from_bytes(read_key(&data, &mut pos)?)?), Vec::new())
+    } else {
+        let round = read_varint_u32(&data, &mut pos)?;
+        if !(2..=MAX_SIGNERS + 1).contains(&round) {
+            return Err(format!("Invalid key exchange round {}", round));
+        }
+        let keys = read_keys(&data, &mut pos)?.into_iter().map(PublicKey::from_bytes).collect::<Result<Vec<PublicKey>, String>>()?;
+        (round, None, keys)
+    };
+    let signing_pubkey = PublicKey::from_bytes(read_key(&data, &mut pos)?)?;
+    let signature = Signature::from_bytes(read_bytes(&data, &mut pos, 64)?.try_into().unwrap());
+    if pos != data.len() {
+        return Err("Key exchange message has trailing bytes".to_string());
+    }
+    let magic = if round1 { KEX_MSG_ROUND1_MAGIC } else { KEX_MSG_ROUNDN_MAGIC };
+    if !signature.verify(&kex_message_hash(magic, round, &signing_pubkey, msg_privkey.as_ref(), &msg_pubkeys), &signing_pubkey) {
+        return Err("Key exchange message has an invalid signature".to_string());
+    }
+    Ok(KexMessage {
+        round,
+        signing_pubkey,
+        msg_privkey,
+        msg_pubkeys,
+    })
+}
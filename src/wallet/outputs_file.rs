@@ -0,0 +1,128 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::archive::{decrypt_with_view_key, encrypt_with_view_key, read_key, read_transfers, write_transfers, ExportedTransfer, TRANSFER_FLAG_RCT};
+use super::snapshot::SnapshotOutput;
+use crate::blocks::{get_global_output_indexes, DaemonNode, TxHash};
+use crate::keys::{derive_output_pubkey, derive_subaddress_keys, generate_key_derivation, OneTimeKey, PublicKey, ViewPair};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// Magic starting the `outputs` files of monero-wallet-cli's `export_outputs`
+pub const OUTPUT_EXPORT_PREFIX: &[u8] = b"Monero output export\x04";
+
+// Flag of exported transfers already spent
+const TRANSFER_FLAG_SPENT: u8 = 1;
+
+/// Exports wallet outputs in the format of monero-wallet-cli's `export_outputs`, for an offline wallet holding the
+/// spend key to import with [`import_outputs`](fn.import_outputs.html)
+///
+/// The offline wallet never syncs: it learns its outputs from the file, generates their key images for the online
+/// view-only wallet and signs the transactions it builds. Global output indexes are fetched from the node.
+///
+/// Arguments:
+/// - `outputs`: all outputs of the wallet, spent ones included, in the order they were received
+/// - `offset`: position of the first output to export, 0 to export them all
+/// - `view_pair`: the public spend key and private view key of the wallet
+/// - `node`: the daemon to fetch the global output indexes from
+///
+/// Returns the content of the file if succesfull
+/// Returns an error message if the offset is past the outputs or the node fails
+pub fn export_outputs(outputs: &[SnapshotOutput], offset: usize, view_pair: &ViewPair, node: DaemonNode) -> Result<Vec<u8>, String> {
+    let exported = outputs.get(offset..).ok_or(format!("Offset {} is past the {} outputs", offset, outputs.len()))?;
+    let mut indexes: HashMap<TxHash, Vec<u64>> = HashMap::new();
+    let mut transfers = Vec::with_capacity(exported.len());
+    for output in exported.iter() {
+        let tx_indexes = match indexes.entry(output.tx_hash) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(get_global_output_indexes(output.tx_hash, node.clone())?),
+        };
+        let global_index = *tx_indexes
+            .get(output.index as usize)
+            .ok_or(format!("Transaction {} has no output {}", output.tx_hash, output.index))?;
+        let mut flags = if output.pre_rct { 0 } else { TRANSFER_FLAG_RCT };
+        if output.spent_height.is_some() {
+            flags |= TRANSFER_FLAG_SPENT;
+        }
+        transfers.push(ExportedTransfer {
+            output_key: *output.output_key.as_bytes(),
+            internal_output_index: output.index,
+            global_output_index: global_index,
+            tx_public_key: *output.tx_public_key.as_bytes(),
+            flags,
+            amount: output.amount,
+            additional_tx_keys: Vec::new(),
+            subaddress: output.subaddress,
+        });
+    }
+
+    // Public keys of the wallet, then the tuple of the offset, the number of outputs and the exported outputs
+    let mut plaintext = view_pair.spend.as_bytes().to_vec();
+    plaintext.extend_from_slice(view_pair.view.public_key().as_bytes());
+    write_transfers(offset as u64, outputs.len() as u64, &transfers, &mut plaintext);
+    let mut data = OUTPUT_EXPORT_PREFIX.to_vec();
+    data.extend(encrypt_with_view_key(&plaintext, &view_pair.view)?);
+    Ok(data)
+}
+
+/// Imports an `outputs` file exported by monero-wallet-cli or [`export_outputs`](fn.export_outputs.html)
+///
+/// The file does not hold the transaction hash, height and unlock time of the outputs, imported outputs have zero
+/// hashes and heights and no unlock time, and spent outputs a spent height of 0.
+///
+/// Arguments:
+/// - `data`: the content of the file
+/// - `view_pair`: the public spend key and private view key of the wallet
+///
+/// Returns the position of the first output among all outputs of the exporting wallet and the outputs if succesfull
+/// Returns an error message if the file was exported by another wallet or an output does not belong to it
+pub fn import_outputs(data: &[u8], view_pair: &ViewPair) -> Result<(usize, Vec<SnapshotOutput>), String> {
+    let encrypted = data.strip_prefix(OUTPUT_EXPORT_PREFIX).ok_or("Data is not an output export")?;
+    let plaintext = decrypt_with_view_key(encrypted, &view_pair.view)?;
+    let pos = &mut 0;
+    if read_key(&plaintext, pos)? != *view_pair.spend.as_bytes() || read_key(&plaintext, pos)? != *view_pair.view.public_key().as_bytes() {
+        return Err("Outputs were exported by another wallet".to_string());
+    }
+    let (offset, total, transfers) = read_transfers(&plaintext, pos)?;
+    if *pos != plaintext.len() {
+        return Err("Trailing data after the exported outputs".to_string());
+    }
+    if offset.checked_add(transfers.len() as u64).is_none_or(|end| end > total) {
+        return Err(format!("Output export holds {} outputs from {}, of {} outputs", transfers.len(), offset, total));
+    }
+
+    let mut outputs = Vec::with_capacity(transfers.len());
+    for (i, transfer) in transfers.iter().enumerate() {
+        // Outputs of transactions with additional public keys are derived from the key of their index
+        let (major, minor) = transfer.subaddress;
+        let (public_spend_key, _) = derive_subaddress_keys(view_pair, major, minor);
+        let tx_public_key = std::iter::once(&transfer.tx_public_key)
+            .chain(transfer.additional_tx_keys.get(transfer.internal_output_index as usize))
+            .filter_map(|key| PublicKey::from_bytes(*key).ok())
+            .find(|key| {
+                let derivation = generate_key_derivation(key, &view_pair.view);
+                *derive_output_pubkey(&derivation, transfer.internal_output_index, &public_spend_key).as_bytes() == transfer.output_key
+            })
+            .ok_or(format!("Output {} does not belong to this wallet", offset + i as u64))?;
+        outputs.push(SnapshotOutput {
+            tx_hash: TxHash::from_bytes([0; 32]),
+            height: 0,
+            index: transfer.internal_output_index,
+            amount: transfer.amount,
+            output_key: OneTimeKey::from_bytes(transfer.output_key),
+            tx_public_key,
+            subaddress: transfer.subaddress,
+            unlock_time: 0,
+            spent_height: if transfer.flags & TRANSFER_FLAG_SPENT != 0 { Some(0) } else { None },
+            pre_rct: transfer.flags & TRANSFER_FLAG_RCT == 0,
+        });
+    }
+    Ok((offset as usize, outputs))
+}
@@ -0,0 +1,346 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::archive::{read_arity, read_bytes, read_count, read_key, read_version};
+use crate::blocks::extra::extract_tx_pub_keys;
+use crate::blocks::{absolute_offsets, get_outs, get_transaction, is_key_image_spent, DaemonNode, KeyImageStatus, Transaction, TxHash, TxInput};
+use crate::crypt::keccak::keccak256;
+use crate::keys::proofs::{check_tx_proof_signature, output_amount, sign_tx_proof};
+use crate::keys::{check_ring_signature, derivation_to_scalar, generate_ring_signature, generate_signature, KeyDerivation, KeyImage, OneTimeKey, PrivateKey, PublicKey, Signature, ViewPair};
+use crate::utils::varint::{read_varint, write_varint};
+use crate::utils::{decode_address, AddressType};
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use std::collections::{BTreeMap, HashSet};
+use zeroize::Zeroizing;
+
+const SPEND_PROOF_HEADER: &str = "SpendProofV1";
+const RESERVE_PROOF_HEADER: &str = "ReserveProofV2";
+const MESSAGE_SIGNATURE_V1_HEADER: &str = "SigV1";
+const MESSAGE_SIGNATURE_V2_HEADER: &str = "SigV2";
+// Domain of message signatures, monero-core's `HASH_KEY_MESSAGE_SIGNING` with its terminating null byte
+const MESSAGE_SIGNATURE_DOMAIN: &[u8] = b"MoneroMessageSignature\0";
+// Mode of message signatures made with the spend key, signatures made with the view key use 1
+const MESSAGE_SIGNATURE_SPEND_MODE: u8 = 0;
+// Base58 length of a ring signature member of a spend proof
+const SPEND_PROOF_SIGNATURE_LEN: usize = 88;
+// Serialized size of a reserve proof entry without its varint fields
+const RESERVE_PROOF_ENTRY_LEN: usize = 32 * 3 + 64 * 2;
+
+/// ReserveProofCheck is the outcome of a valid reserve proof, see [`check_reserve_proof`](fn.check_reserve_proof.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReserveProofCheck {
+    /// Sum of the proven outputs in piconeros
+    pub total: u64,
+    /// Sum of the proven outputs spent since, on chain or in the pool
+    pub spent: u64,
+}
+
+// Signatures of the spend keys of the subaddresses receiving the outputs of a reserve proof, by spend key
+type SubaddressSignatures = BTreeMap<[u8; 32], Signature>;
+
+// Output proven by a reserve proof, monero-core's `wallet2::reserve_proof_entry`
+struct ReserveProofEntry {
+    tx_hash: TxHash,
+    index_in_tx: u64,
+    shared_secret: [u8; 32],
+    key_image: KeyImage,
+    shared_secret_signature: Signature,
+    key_image_signature: Signature,
+}
+
+/// Gets the one-time keys of the ring members of every input of a transaction, as needed to make and check spend proofs
+///
+/// Returns the ring of every input in order if succesfull
+/// Returns an error message if an input spends pre-RingCT outputs or the node fails
+pub fn get_tx_rings(tx: &Transaction, node: DaemonNode) -> Result<Vec<Vec<OneTimeKey>>, String> {
+    let mut indices = Vec::new();
+    let mut sizes = Vec::new();
+    for input in tx.prefix.inputs.iter() {
+        if let TxInput::ToKey { amount, key_offsets, .. } = input {
+            if *amount != 0 {
+                return Err("Inputs spending pre-RingCT outputs are not supported".to_string());
+            }
+            indices.extend(absolute_offsets(key_offsets)?);
+            sizes.push(key_offsets.len());
+        }
+    }
+    if indices.is_empty() {
+        return Ok(Vec::new());
+    }
+    let outputs = get_outs(&indices, node)?;
+    if outputs.len() != indices.len() {
+        return Err(format!("Node returned {} ring members, expected {}", outputs.len(), indices.len()));
+    }
+    let mut keys = outputs.into_iter().map(|output| output.key);
+    Ok(sizes.iter().map(|size| keys.by_ref().take(*size).collect()).collect())
+}
+
+/// Checks a proof made by [`Wallet::get_spend_proof`](struct.Wallet.html#method.get_spend_proof) or
+/// monero-wallet-cli's `get_spend_proof` for the transaction with the given hash and the message, monero-wallet-cli's
+/// `check_spend_proof`
+///
+/// Returns whether the proof signs every input of the transaction if succesfull
+/// Returns an error message if the proof is malformed, the transaction has no inputs or the node fails
+pub fn check_spend_proof(tx_hash: &TxHash, message: &[u8], proof: &str, node: DaemonNode) -> Result<bool, String> {
+    let tx = get_transaction(*tx_hash, node.clone())?;
+    let rings = get_tx_rings(&tx, node)?;
+    verify_spend_proof(tx_hash, &tx, &rings, message, proof)
+}
+
+/// Checks a proof made by [`Wallet::get_reserve_proof`](struct.Wallet.html#method.get_reserve_proof) or
+/// monero-wallet-cli's `get_reserve_proof` for the primary address and the message, monero-wallet-cli's
+/// `check_reserve_proof`
+///
+/// The proven outputs and the key images telling whether they are spent are fetched from the node.
+///
+/// Returns the total and spent amounts of the proven outputs if succesfull
+/// Returns an error message if the proof is malformed, does not prove outputs of the address for this message or the
+/// node fails
+pub fn check_reserve_proof(address: &str, message: &[u8], proof: &str, node: DaemonNode) -> Result<ReserveProofCheck, String> {
+    let owner = decode_address(address)?;
+    if owner.address_type == AddressType::Subaddress {
+        return Err("Reserve proofs are made for primary addresses".to_string());
+    }
+    let (entries, subaddress_signatures) = read_reserve_proof(proof)?;
+    let key_images: Vec<KeyImage> = entries.iter().map(|entry| entry.key_image).collect();
+    if key_images.iter().collect::<HashSet<_>>().len() != key_images.len() {
+        return Err("Reserve proof holds the same output twice".to_string());
+    }
+    let prefix_hash = reserve_proof_hash(message, &owner.public_spend_key, &owner.public_view_key, &key_images);
+    if !subaddress_signatures.contains_key(owner.public_spend_key.as_bytes()) {
+        return Err("Reserve proof does not hold the address".to_string());
+    }
+    for (public_spend_key, signature) in subaddress_signatures.iter() {
+        if !PublicKey::from_bytes(*public_spend_key).is_ok_and(|key| signature.verify(&prefix_hash, &key)) {
+            return Err(format!("Signature of subaddress spend key {} does not verify", hex::encode(public_spend_key)));
+        }
+    }
+
+    let statuses = is_key_image_spent(&key_images, node.clone())?;
+    let mut check = ReserveProofCheck { total: 0, spent: 0 };
+    for (entry, status) in entries.iter().zip(statuses) {
+        let tx = get_transaction(entry.tx_hash, node.clone())?;
+        let amount = verify_reserve_entry(&prefix_hash, owner.public_view_key.as_bytes(), &tx, entry, &subaddress_signatures)?;
+        check.total = check.total.saturating_add(amount);
+        if status != KeyImageStatus::Unspent {
+            check.spent = check.spent.saturating_add(amount);
+        }
+    }
+    Ok(check)
+}
+
+/// Verifies a message signature made by [`Wallet::sign_message`](struct.Wallet.html#method.sign_message) or
+/// monero-wallet-cli's `sign` with the spend key of the address, monero-wallet-cli's `verify`
+///
+/// Both `SigV2` signatures, which also sign the address, and older `SigV1` signatures of the message alone are
+/// accepted. Signatures made with the view key (`sign --view-key`) prove nothing about spending and are refused.
+///
+/// Returns whether the signature is valid if succesfull
+/// Returns an error message if the address or the signature is malformed
+pub fn verify_message(address: &str, message: &[u8], signature: &str) -> Result<bool, String> {
+    let signer = decode_address(address)?;
+    let (hash, encoded) = match (signature.strip_prefix(MESSAGE_SIGNATURE_V1_HEADER), signature.strip_prefix(MESSAGE_SIGNATURE_V2_HEADER)) {
+        (Some(encoded), _) => (keccak256(message), encoded),
+        (_, Some(encoded)) => (message_hash(message, &signer.public_spend_key, &signer.public_view_key), encoded),
+        _ => return Err("Message signature must start with SigV1 or SigV2".to_string()),
+    };
+    let bytes = base58_monero::decode(encoded).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+    let signature = Signature::from_bytes(bytes.try_into().map_err(|_| "Signature must hold 64 bytes")?);
+    Ok(signature.verify(&hash, &signer.public_spend_key))
+}
+
+// Signs the message for the address with the given keys, in the SigV2 format
+pub(crate) fn write_message_signature(message: &[u8], signing_key: &PrivateKey, public_spend_key: &PublicKey, public_view_key: &PublicKey) -> Result<String, String> {
+    let signature = generate_signature(&message_hash(message, public_spend_key, public_view_key), signing_key);
+    Ok(format!("{}{}", MESSAGE_SIGNATURE_V2_HEADER, base58_monero::encode(&signature.to_bytes()).map_err(|e| format!("Invalid base58 encoding: {}", e))?))
+}
+
+// Signs every input of the transaction with the one-time private key of the output it spends, found among the secrets
+// by key image
+pub(crate) fn write_spend_proof(tx_hash: &TxHash, tx: &Transaction, rings: &[Vec<OneTimeKey>], secrets: &[(KeyImage, PrivateKey)], message: &[u8]) -> Result<String, String> {
+    let key_images = tx.key_images();
+    if key_images.is_empty() {
+        return Err("Transaction has no inputs to prove".to_string());
+    }
+    if rings.len() != key_images.len() {
+        return Err(format!("Got {} rings for {} inputs", rings.len(), key_images.len()));
+    }
+    let prefix_hash = spend_proof_hash(tx_hash, message);
+    let mut proof = SPEND_PROOF_HEADER.to_string();
+    for (i, (key_image, ring)) in key_images.iter().zip(rings).enumerate() {
+        let (_, secret) = secrets.iter().find(|(spent, _)| spent == key_image).ok_or(format!("Input {} does not spend an output of the wallet", i))?;
+        let ring = ring.iter().map(OneTimeKey::to_public_key).collect::<Result<Vec<_>, _>>()?;
+        let public_key = secret.public_key();
+        let real_index = ring.iter().position(|member| *member == public_key).ok_or(format!("Ring of input {} does not hold the spent output", i))?;
+        for signature in generate_ring_signature(&prefix_hash, key_image, &ring, secret, real_index)? {
+            proof += &base58_monero::encode(&signature.to_bytes()).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+        }
+    }
+    Ok(proof)
+}
+
+// Proves the outputs, given with their one-time private key and key image, and signs the spend keys of the
+// subaddresses that received them
+pub(crate) fn write_reserve_proof(
+    view_pair: &ViewPair,
+    outputs: &[(TxHash, u64, PublicKey, PrivateKey, KeyImage)],
+    subaddress_keys: &[PrivateKey],
+    message: &[u8],
+) -> Result<String, String> {
+    let key_images: Vec<KeyImage> = outputs.iter().map(|(.., key_image)| *key_image).collect();
+    let view_public_key = view_pair.view.public_key();
+    let prefix_hash = reserve_proof_hash(message, &view_pair.spend, &view_public_key, &key_images);
+    let a = Zeroizing::new(view_pair.view.to_scalar());
+
+    let mut data = Vec::new();
+    write_varint(outputs.len() as u64, &mut data);
+    for (tx_hash, index, tx_public_key, secret, key_image) in outputs.iter() {
+        let (shared_secret, shared_secret_signature) = sign_tx_proof(&prefix_hash, &view_public_key.to_point(), &tx_public_key.to_point(), None, &a);
+        let key_image_signature = generate_ring_signature(&prefix_hash, key_image, &[secret.public_key()], secret, 0)?[0];
+        // Version of the entry
+        write_varint(0, &mut data);
+        data.extend_from_slice(tx_hash.as_bytes());
+        write_varint(*index, &mut data);
+        data.extend_from_slice(&shared_secret);
+        data.extend_from_slice(key_image.as_bytes());
+        data.extend_from_slice(&shared_secret_signature.to_bytes());
+        data.extend_from_slice(&key_image_signature.to_bytes());
+    }
+    write_varint(subaddress_keys.len() as u64, &mut data);
+    for key in subaddress_keys.iter() {
+        write_varint(2, &mut data);
+        data.extend_from_slice(key.public_key().as_bytes());
+        data.extend_from_slice(&generate_signature(&prefix_hash, key).to_bytes());
+    }
+    Ok(format!("{}{}", RESERVE_PROOF_HEADER, base58_monero::encode(&data).map_err(|e| format!("Invalid base58 encoding: {}", e))?))
+}
+
+fn verify_spend_proof(tx_hash: &TxHash, tx: &Transaction, rings: &[Vec<OneTimeKey>], message: &[u8], proof: &str) -> Result<bool, String> {
+    let encoded = proof.strip_prefix(SPEND_PROOF_HEADER).ok_or("Spend proof must start with SpendProofV1")?;
+    let key_images = tx.key_images();
+    if key_images.is_empty() {
+        return Err("Transaction has no inputs to prove".to_string());
+    }
+    let members: usize = rings.iter().map(Vec::len).sum();
+    if !encoded.is_ascii() || encoded.len() != members * SPEND_PROOF_SIGNATURE_LEN {
+        return Err(format!("Spend proof must hold {} signatures", members));
+    }
+    let signatures = encoded
+        .as_bytes()
+        .chunks(SPEND_PROOF_SIGNATURE_LEN)
+        .map(|chunk| {
+            let bytes = base58_monero::decode(std::str::from_utf8(chunk).unwrap()).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+            Ok(Signature::from_bytes(bytes.try_into().map_err(|_| "Signature must hold 64 bytes")?))
+        })
+        .collect::<Result<Vec<Signature>, String>>()?;
+
+    let prefix_hash = spend_proof_hash(tx_hash, message);
+    let mut offset = 0;
+    for (key_image, ring) in key_images.iter().zip(rings) {
+        let Ok(ring_keys) = ring.iter().map(OneTimeKey::to_public_key).collect::<Result<Vec<_>, _>>() else {
+            return Ok(false);
+        };
+        if !check_ring_signature(&prefix_hash, key_image, &ring_keys, &signatures[offset..offset + ring.len()]) {
+            return Ok(false);
+        }
+        offset += ring.len();
+    }
+    Ok(true)
+}
+
+fn read_reserve_proof(proof: &str) -> Result<(Vec<ReserveProofEntry>, SubaddressSignatures), String> {
+    let encoded = proof.strip_prefix(RESERVE_PROOF_HEADER).ok_or("Reserve proof must start with ReserveProofV2")?;
+    let data = base58_monero::decode(encoded).map_err(|e| format!("Invalid base58 encoding: {}", e))?;
+    let pos = &mut 0;
+    let count = read_count(&data, pos, RESERVE_PROOF_ENTRY_LEN)?;
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        read_version(&data, pos, 0, "reserve proof entry")?;
+        let tx_hash = TxHash::from_bytes(read_key(&data, pos)?);
+        let index_in_tx = read_varint(&data, pos)?;
+        let shared_secret = read_key(&data, pos)?;
+        let key_image = KeyImage::from_bytes(read_key(&data, pos)?);
+        let shared_secret_signature = Signature::from_bytes(read_bytes(&data, pos, 64)?.try_into().unwrap());
+        let key_image_signature = Signature::from_bytes(read_bytes(&data, pos, 64)?.try_into().unwrap());
+        entries.push(ReserveProofEntry { tx_hash, index_in_tx, shared_secret, key_image, shared_secret_signature, key_image_signature });
+    }
+    let count = read_count(&data, pos, 32 + 64)?;
+    let mut subaddress_signatures = BTreeMap::new();
+    for _ in 0..count {
+        read_arity(&data, pos, 2)?;
+        let public_spend_key = read_key(&data, pos)?;
+        subaddress_signatures.insert(public_spend_key, Signature::from_bytes(read_bytes(&data, pos, 64)?.try_into().unwrap()));
+    }
+    if *pos != data.len() {
+        return Err("Reserve proof has trailing data".to_string());
+    }
+    Ok((entries, subaddress_signatures))
+}
+
+// Checks an entry of a reserve proof against its transaction and returns the amount of the proven output
+fn verify_reserve_entry(
+    prefix_hash: &[u8; 32],
+    public_view_key: &[u8; 32],
+    tx: &Transaction,
+    entry: &ReserveProofEntry,
+    subaddress_signatures: &SubaddressSignatures,
+) -> Result<u64, String> {
+    let name = format!("Output {} of transaction {}", entry.index_in_tx, entry.tx_hash.to_hex());
+    let index = usize::try_from(entry.index_in_tx).ok().filter(|index| *index < tx.prefix.outputs.len()).ok_or(format!("{} does not exist", name))?;
+    let output_key = tx.prefix.outputs[index].key();
+    let (main_keys, additional_keys) = extract_tx_pub_keys(&tx.prefix.extra);
+    let tx_public_key = main_keys.first().ok_or(format!("Transaction {} has no public key", entry.tx_hash.to_hex()))?;
+    let check = |tx_key: &[u8; 32]| check_tx_proof_signature(prefix_hash, public_view_key, tx_key, None, &entry.shared_secret, &entry.shared_secret_signature);
+    let additional_key = (additional_keys.len() == tx.prefix.outputs.len()).then(|| &additional_keys[index]);
+    if !(check(tx_public_key) || additional_key.is_some_and(check)) {
+        return Err(format!("Shared secret signature of {} does not verify", name));
+    }
+    let output_public_key = output_key.to_public_key()?;
+    if !check_ring_signature(prefix_hash, &entry.key_image, &[output_public_key], &[entry.key_image_signature]) {
+        return Err(format!("Key image signature of {} does not verify", name));
+    }
+
+    // The derivation is 8 * D, as computed by the owner from the transaction public key, and the output key minus
+    // Hs(derivation || index) * G is the spend key of the subaddress that received it
+    let shared_secret = PublicKey::from_bytes(entry.shared_secret)?;
+    let derivation = KeyDerivation::from_bytes(shared_secret.to_point().mul_by_cofactor().compress().to_bytes());
+    let public_spend_key = output_public_key.to_point() - ED25519_BASEPOINT_TABLE * &derivation_to_scalar(&derivation, entry.index_in_tx);
+    if !subaddress_signatures.contains_key(public_spend_key.compress().as_bytes()) {
+        return Err(format!("{} was not received by the address", name));
+    }
+    output_amount(tx, index, &derivation).ok_or(format!("Amount of {} does not match its commitment", name))
+}
+
+// Keccak(transaction hash || message)
+fn spend_proof_hash(tx_hash: &TxHash, message: &[u8]) -> [u8; 32] {
+    let mut data = tx_hash.as_bytes().to_vec();
+    data.extend_from_slice(message);
+    keccak256(&data)
+}
+
+// Keccak(message || public spend key || public view key || key images)
+fn reserve_proof_hash(message: &[u8], public_spend_key: &PublicKey, public_view_key: &PublicKey, key_images: &[KeyImage]) -> [u8; 32] {
+    let mut data = message.to_vec();
+    data.extend_from_slice(public_spend_key.as_bytes());
+    data.extend_from_slice(public_view_key.as_bytes());
+    key_images.iter().for_each(|key_image| data.extend_from_slice(key_image.as_bytes()));
+    keccak256(&data)
+}
+
+// Keccak(domain || public spend key || public view key || mode || varint message length || message)
+fn message_hash(message: &[u8], public_spend_key: &PublicKey, public_view_key: &PublicKey) -> [u8; 32] {
+    let mut data = MESSAGE_SIGNATURE_DOMAIN.to_vec();
+    data.extend_from_slice(public_spend_key.as_bytes());
+    data.extend_from_slice(public_view_key.as_bytes());
+    data.push(MESSAGE_SIGNATURE_SPEND_MODE);
+    write_varint(message.len() as u64, &mut data);
+    data.extend_from_slice(message);
+    keccak256(&data)
+}
@@ -0,0 +1,192 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::snapshot::SnapshotTransfer;
+use crate::blocks::{Transaction, TxHash};
+use crate::keys::{check_tx_proof, generate_tx_proof, TxProofKeys};
+use crate::utils::decode_address;
+use serde_json::{json, Value};
+
+/// PaymentReceipt is the data of a payment receipt, for applications to render as a PDF, a printout or a web page
+///
+/// It is built from an outgoing transfer of the wallet history, and carries the transaction proof that lets the
+/// recipient or a third party check the payment against the blockchain with [`verify`](#method.verify)
+///
+/// Example:
+/// ```
+/// use libmonero::blocks::parse_transaction_blob;
+/// use libmonero::keys::{derive_output_key, PrivateKey, TxProofKeys, ViewPair};
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::{PaymentReceipt, SnapshotTransfer};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let destination = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key()).address(Network::Mainnet);
+///
+/// // Transaction paying 10000000 piconeros to the destination
+/// let tx_secret_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+/// let output = derive_output_key(&destination, &tx_secret_key, 0).unwrap();
+/// let blob = format!("029c0801ffe8070180ade20403{}{:02x}2101{}00", output.output_key, output.view_tag, output.tx_public_key.to_hex());
+/// let tx = parse_transaction_blob(&hex::decode(blob).unwrap()).unwrap();
+///
+/// let transfer = SnapshotTransfer {
+///     tx_hash: tx.hash().unwrap(),
+///     height: Some(3000000),
+///     timestamp: 1700000000,
+///     account: 0,
+///     incoming: 0,
+///     outgoing: 10000000,
+///     fee: 30000000,
+///     payment_id: None,
+/// };
+/// let keys = TxProofKeys::Sender { tx_secret_key, additional_secret_keys: Vec::new() };
+/// let receipt = PaymentReceipt::from_transfer(&transfer, &destination, 10000000)
+///     .unwrap()
+///     .with_description("Invoice #42")
+///     .with_tx_proof(&tx, "Invoice #42", &keys)
+///     .unwrap();
+/// assert_eq!(receipt.verify(&tx), Ok(10000000));
+/// assert_eq!(PaymentReceipt::from_json(&receipt.to_json()).unwrap(), receipt);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentReceipt {
+    /// Hash of the transaction that made the payment
+    pub tx_hash: TxHash,
+    /// Address that was paid
+    pub destination: String,
+    /// Amount paid to the destination, in piconeros
+    pub amount: u64,
+    /// Fee of the whole transaction, in piconeros
+    pub fee: u64,
+    /// Unix time of the transaction
+    pub timestamp: u64,
+    /// Height of the block containing the transaction, `None` while in pool
+    pub height: Option<u64>,
+    /// Payment ID in hex, if any
+    pub payment_id: Option<String>,
+    /// Free text given by the sender, e.g. the invoice being paid
+    pub description: Option<String>,
+    /// Transaction proof of the payment, checked with the transaction hash, destination and message
+    pub proof: Option<String>,
+    /// Message the proof was made with
+    pub message: Option<String>,
+}
+
+impl PaymentReceipt {
+    /// Creates the receipt of the payment of `amount` to `destination` made by the given transfer
+    ///
+    /// Fails if the destination is not a valid address or if the amount is above what the transfer sent
+    pub fn from_transfer(transfer: &SnapshotTransfer, destination: &str, amount: u64) -> Result<PaymentReceipt, String> {
+        decode_address(destination)?;
+        if amount > transfer.outgoing {
+            return Err(format!("Receipt amount {} is above the {} sent by the transfer", amount, transfer.outgoing));
+        }
+        Ok(PaymentReceipt {
+            tx_hash: transfer.tx_hash,
+            destination: destination.to_string(),
+            amount,
+            fee: transfer.fee,
+            timestamp: transfer.timestamp,
+            height: transfer.height,
+            payment_id: transfer.payment_id.clone(),
+            description: None,
+            proof: None,
+            message: None,
+        })
+    }
+
+    /// Sets the description of the receipt
+    pub fn with_description(mut self, description: &str) -> PaymentReceipt {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the transaction proof of the receipt and the message it was made with, e.g. made by monero-wallet-cli's
+    /// `get_tx_proof`
+    pub fn with_proof(mut self, proof: &str, message: &str) -> PaymentReceipt {
+        self.proof = Some(proof.to_string());
+        self.message = Some(message.to_string());
+        self
+    }
+
+    /// Proves the payment with [`generate_tx_proof`](../keys/fn.generate_tx_proof.html) and sets the proof and its
+    /// message
+    ///
+    /// `tx` is the transaction of the receipt, `keys` the secret keys of the transaction known to the sender or the
+    /// private view key of the destination.
+    ///
+    /// Returns the receipt with its proof if succesfull
+    /// Returns an error message if the transaction is not the one of the receipt or the keys do not match it
+    pub fn with_tx_proof(self, tx: &Transaction, message: &str, keys: &TxProofKeys) -> Result<PaymentReceipt, String> {
+        self.check_tx_hash(tx)?;
+        let proof = generate_tx_proof(&self.tx_hash, tx, &self.destination, message.as_bytes(), keys)?;
+        Ok(self.with_proof(&proof, message))
+    }
+
+    /// Checks the proof of the receipt against its transaction, as fetched from the blockchain, with
+    /// [`check_tx_proof`](../keys/fn.check_tx_proof.html)
+    ///
+    /// Returns the amount the transaction paid to the destination if succesfull
+    /// Returns an error message if the receipt has no proof, the proof does not hold or the transaction paid the
+    /// destination less than the amount of the receipt
+    pub fn verify(&self, tx: &Transaction) -> Result<u64, String> {
+        let proof = self.proof.as_ref().ok_or("Receipt has no proof")?;
+        self.check_tx_hash(tx)?;
+        let message = self.message.as_deref().unwrap_or("");
+        let received = check_tx_proof(&self.tx_hash, tx, &self.destination, message.as_bytes(), proof)?;
+        if received < self.amount {
+            return Err(format!("Transaction paid {} to the destination, less than the {} of the receipt", received, self.amount));
+        }
+        Ok(received)
+    }
+
+    // Checks that the transaction is the one of the receipt, pruned transactions can not be hashed and are trusted
+    fn check_tx_hash(&self, tx: &Transaction) -> Result<(), String> {
+        match tx.hash() {
+            Ok(tx_hash) if tx_hash != self.tx_hash => Err(format!("Transaction {} is not the transaction {} of the receipt", tx_hash, self.tx_hash)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Serializes the receipt as JSON
+    pub fn to_json(&self) -> String {
+        json!({
+            "tx_hash": self.tx_hash.to_hex(),
+            "destination": self.destination,
+            "amount": self.amount,
+            "fee": self.fee,
+            "timestamp": self.timestamp,
+            "height": self.height,
+            "payment_id": self.payment_id,
+            "description": self.description,
+            "proof": self.proof,
+            "message": self.message,
+        })
+        .to_string()
+    }
+
+    /// Parses a receipt written by [`to_json`](#method.to_json)
+    pub fn from_json(receipt: &str) -> Result<PaymentReceipt, String> {
+        let json: Value = serde_json::from_str(receipt).map_err(|e| format!("Error while parsing the receipt JSON: {}", e))?;
+        let field = |name: &str| json[name].as_u64().ok_or(format!("Receipt field {} is missing or invalid", name));
+        let optional_string = |name: &str| json[name].as_str().map(|value| value.to_string());
+        Ok(PaymentReceipt {
+            tx_hash: TxHash::from_hex(json["tx_hash"].as_str().ok_or("Receipt field tx_hash is missing or invalid")?)?,
+            destination: optional_string("destination").ok_or("Receipt field destination is missing or invalid")?,
+            amount: field("amount")?,
+            fee: field("fee")?,
+            timestamp: field("timestamp")?,
+            height: json["height"].as_u64(),
+            payment_id: optional_string("payment_id"),
+            description: optional_string("description"),
+            proof: optional_string("proof"),
+            message: optional_string("message"),
+        })
+    }
+}
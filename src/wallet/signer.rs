@@ -0,0 +1,308 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::key_images::{write_key_image_export, KeyImageRequest, KeyImageResponse};
+use super::proofs::{write_message_signature, write_reserve_proof, write_spend_proof};
+use super::snapshot::SnapshotOutput;
+use crate::blocks::{Transaction, TxHash};
+use crate::crypt::{clsag_sign, Clsag};
+use crate::keys::{
+    commit, generate_key_image, generate_ring_signature, prove_output_ownership, subaddress_secret, Commitment, KeyImage, OneTimeKey, OutputOwnershipProof, OwnedOutput,
+    PrivateKey, PublicKey, ViewPair,
+};
+use curve25519_dalek::Scalar;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// KeyStore is the platform storage holding the private spend key of a [`Signer`](struct.Signer.html)
+///
+/// Mobile and desktop platforms implement it over their OS keystore: Android Keystore, the iOS Secure Enclave or a
+/// TPM. These can not compute Ed25519 operations themselves, so the spend key is kept encrypted at rest with a
+/// key that never leaves the hardware, and `load_spend_key` decrypts it for a single signing operation. The signer
+/// drops the returned key, which wipes it, as soon as the operation is done.
+///
+/// Keystores requiring user authentication (biometrics, PIN) refuse by returning an error, which is passed on to
+/// the caller of the signing operation. Closures returning the key implement the trait.
+pub trait KeyStore: Send + Sync {
+    /// Returns the private spend key, or an error if the keystore refuses to release it
+    fn load_spend_key(&self) -> Result<PrivateKey, String>;
+}
+
+impl<F> KeyStore for F
+where
+    F: Fn() -> Result<PrivateKey, String> + Send + Sync,
+{
+    fn load_spend_key(&self) -> Result<PrivateKey, String> {
+        self()
+    }
+}
+
+/// MemoryKeyStore keeps the private spend key in process memory, for platforms without a keystore and for tests
+#[derive(Clone, Debug)]
+pub struct MemoryKeyStore {
+    private_spend_key: PrivateKey,
+}
+
+impl MemoryKeyStore {
+    /// Creates a keystore holding the given private spend key
+    pub fn new(private_spend_key: PrivateKey) -> MemoryKeyStore {
+        MemoryKeyStore { private_spend_key }
+    }
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn load_spend_key(&self) -> Result<PrivateKey, String> {
+        Ok(self.private_spend_key.clone())
+    }
+}
+
+/// Signer performs the operations needing the private spend key without exposing it, the key stays in a
+/// [`KeyStore`](trait.KeyStore.html) and is loaded for the duration of each operation only
+///
+/// The signer keeps the private view key and the public spend key, so scanning and building transactions work as with
+/// a view-only wallet, and every operation loads the spend key once, even for many outputs.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::PrivateKey;
+/// use libmonero::wallet::{MemoryKeyStore, Signer};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let private_view_key = private_spend_key.derive_view_key();
+/// let signer = Signer::new(MemoryKeyStore::new(private_spend_key.clone()), private_view_key.clone()).unwrap();
+/// assert_eq!(signer.view_pair().spend, private_spend_key.public_key());
+///
+/// // The key stays in the keystore, a closure can stand for a platform keystore refusing to release it
+/// let locked = Signer::new(|| Err("User authentication required".to_string()), private_view_key);
+/// assert!(locked.is_err());
+/// ```
+#[derive(Clone)]
+pub struct Signer {
+    keystore: Arc<dyn KeyStore>,
+    view_pair: ViewPair,
+}
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signer").field("view_pair", &self.view_pair).finish_non_exhaustive()
+    }
+}
+
+impl Signer {
+    /// Creates a signer over the given keystore, loading the spend key once to derive the public spend key
+    ///
+    /// Returns the signer if succesfull
+    /// Returns the error of the keystore if not succesfull
+    pub fn new<K: KeyStore + 'static>(keystore: K, private_view_key: PrivateKey) -> Result<Signer, String> {
+        let public_spend_key = keystore.load_spend_key()?.public_key();
+        Ok(Signer {
+            keystore: Arc::new(keystore),
+            view_pair: ViewPair::new(public_spend_key, private_view_key),
+        })
+    }
+
+    /// Returns the public spend key and private view key of the signer, to scan for outputs
+    pub fn view_pair(&self) -> &ViewPair {
+        &self.view_pair
+    }
+
+    /// Returns the key image of an owned output
+    ///
+    /// Returns the key image if succesfull
+    /// Returns an error message if the keystore refuses or the output does not belong to the signer
+    pub fn key_image(&self, output: &OwnedOutput) -> Result<KeyImage, String> {
+        self.key_images(std::slice::from_ref(output)).map(|mut key_images| key_images.remove(0))
+    }
+
+    /// Returns the key images of owned outputs, in order, loading the spend key once
+    ///
+    /// Returns the key images if succesfull
+    /// Returns an error message if the keystore refuses or an output does not belong to the signer
+    pub fn key_images(&self, outputs: &[OwnedOutput]) -> Result<Vec<KeyImage>, String> {
+        let private_spend_key = self.keystore.load_spend_key()?;
+        outputs
+            .iter()
+            .map(|output| {
+                let secret = self.output_secret(output, &private_spend_key)?;
+                Ok(generate_key_image(&secret, &secret.public_key()))
+            })
+            .collect()
+    }
+
+    /// Proves owning an output, see [`prove_output_ownership`](../keys/fn.prove_output_ownership.html)
+    ///
+    /// Returns the proof if succesfull
+    /// Returns an error message if the keystore refuses or the output does not belong to the signer
+    pub fn prove_output_ownership(&self, output: &OwnedOutput, message: &[u8]) -> Result<OutputOwnershipProof, String> {
+        let private_spend_key = self.keystore.load_spend_key()?;
+        Ok(prove_output_ownership(&self.output_secret(output, &private_spend_key)?, message))
+    }
+
+    /// Signs the input spending `output` with a CLSAG, see [`clsag_sign`](../crypt/fn.clsag_sign.html)
+    ///
+    /// The pseudo output commitment of the input is [`commit(output.amount, pseudo_mask)`](../keys/fn.commit.html).
+    ///
+    /// Returns the signature if succesfull
+    /// Returns an error message if the keystore refuses, the output does not belong to the signer or is not the ring
+    /// member at `real_index`, or the masks do not open the commitments
+    pub fn sign_clsag(
+        &self,
+        output: &OwnedOutput,
+        message: &[u8; 32],
+        ring: &[(OneTimeKey, Commitment)],
+        real_index: usize,
+        mask: &Scalar,
+        pseudo_mask: &Scalar,
+    ) -> Result<Clsag, String> {
+        let pseudo_out = commit(output.amount, pseudo_mask);
+        let private_spend_key = self.keystore.load_spend_key()?;
+        let secret = self.output_secret(output, &private_spend_key)?;
+        clsag_sign(message, ring, real_index, &secret, mask, &pseudo_out, pseudo_mask)
+    }
+
+    /// Answers the key image request of a view-only wallet, see
+    /// [`KeyImageRequest::respond`](struct.KeyImageRequest.html#method.respond)
+    ///
+    /// Returns the response if succesfull
+    /// Returns an error message if the keystore refuses or an output does not belong to the signer
+    pub fn respond_key_images(&self, request: &KeyImageRequest) -> Result<KeyImageResponse, String> {
+        let private_spend_key = self.keystore.load_spend_key()?;
+        if private_spend_key.public_key() != self.view_pair.spend {
+            return Err("Keystore returned another spend key".to_string());
+        }
+        request.respond(&self.view_pair.view, &private_spend_key)
+    }
+
+    /// Exports the key images of the wallet outputs from `offset` in the format of monero-wallet-cli's
+    /// `export_key_images`, for its view-only copy to import with [`import_key_images`](fn.import_key_images.html)
+    ///
+    /// Each key image is signed by the one-time key of its output, the file is encrypted with the view key.
+    ///
+    /// Arguments:
+    /// - `outputs`: all outputs of the wallet, spent ones included, in the order they were received
+    /// - `offset`: position of the first output to export, 0 to export them all
+    ///
+    /// Returns the content of the file if succesfull
+    /// Returns an error message if the keystore refuses, the offset is past the outputs or an output does not belong to the signer
+    pub fn export_key_images(&self, outputs: &[SnapshotOutput], offset: usize) -> Result<Vec<u8>, String> {
+        let exported = outputs.get(offset..).ok_or(format!("Offset {} is past the {} outputs", offset, outputs.len()))?;
+        let offset = u32::try_from(offset).map_err(|_| format!("Offset {} does not fit in 32 bits", offset))?;
+        let private_spend_key = self.keystore.load_spend_key()?;
+        let mut key_images = Vec::with_capacity(exported.len());
+        for output in exported.iter() {
+            let secret = self.snapshot_output_secret(output, &private_spend_key)?;
+            let public_key = secret.public_key();
+            let key_image = generate_key_image(&secret, &public_key);
+            let signature = generate_ring_signature(key_image.as_bytes(), &key_image, &[public_key], &secret, 0)?;
+            key_images.push((key_image, signature[0]));
+        }
+        write_key_image_export(&self.view_pair, offset, &key_images)
+    }
+
+    /// Proves spending the wallet outputs a transaction spends, monero-wallet-cli's `get_spend_proof`
+    ///
+    /// Every input is signed with a ring signature over its ring, checked with
+    /// [`check_spend_proof`](fn.check_spend_proof.html).
+    ///
+    /// Arguments:
+    /// - `tx_hash` and `tx`: the transaction to prove
+    /// - `rings`: the ring member keys of every input, see [`get_tx_rings`](fn.get_tx_rings.html)
+    /// - `outputs`: wallet outputs including those the transaction spends
+    /// - `message`: message the proof signs, usually a challenge of the verifier
+    ///
+    /// Returns the proof, starting with `SpendProofV1`, if succesfull
+    /// Returns an error message if the keystore refuses or an input does not spend one of the outputs
+    pub fn get_spend_proof(&self, tx_hash: &TxHash, tx: &Transaction, rings: &[Vec<OneTimeKey>], outputs: &[SnapshotOutput], message: &[u8]) -> Result<String, String> {
+        let private_spend_key = self.keystore.load_spend_key()?;
+        let key_images = tx.key_images();
+        let mut secrets = Vec::new();
+        for output in outputs.iter() {
+            let secret = self.snapshot_output_secret(output, &private_spend_key)?;
+            let key_image = generate_key_image(&secret, &secret.public_key());
+            if key_images.contains(&key_image) {
+                secrets.push((key_image, secret));
+            }
+        }
+        write_spend_proof(tx_hash, tx, rings, &secrets, message)
+    }
+
+    /// Proves owning the given unspent outputs, monero-wallet-cli's `get_reserve_proof`
+    ///
+    /// The proof reveals the outputs, their amounts and key images, and is checked with
+    /// [`check_reserve_proof`](fn.check_reserve_proof.html) against the primary address.
+    ///
+    /// Returns the proof, starting with `ReserveProofV2`, if succesfull
+    /// Returns an error message if the keystore refuses or an output does not belong to the signer
+    pub fn get_reserve_proof(&self, outputs: &[SnapshotOutput], message: &[u8]) -> Result<String, String> {
+        let private_spend_key = self.keystore.load_spend_key()?;
+        let mut proven = Vec::with_capacity(outputs.len());
+        let mut subaddresses = BTreeSet::from([(0, 0)]);
+        for output in outputs.iter() {
+            let secret = self.snapshot_output_secret(output, &private_spend_key)?;
+            let key_image = generate_key_image(&secret, &secret.public_key());
+            proven.push((output.tx_hash, output.index, output.tx_public_key, secret, key_image));
+            subaddresses.insert(output.subaddress);
+        }
+        let subaddress_keys: Vec<PrivateKey> = subaddresses.into_iter().map(|(account, index)| self.subaddress_spend_key(&private_spend_key, account, index)).collect();
+        write_reserve_proof(&self.view_pair, &proven, &subaddress_keys, message)
+    }
+
+    /// Signs a message with the spend key of the primary address or a subaddress in the `SigV2` format of
+    /// monero-wallet-cli's `sign`, checked with [`verify_message`](fn.verify_message.html)
+    ///
+    /// Returns the signature, starting with `SigV2`, if succesfull
+    /// Returns an error message if the keystore refuses
+    pub fn sign_message(&self, message: &[u8], account: u32, index: u32) -> Result<String, String> {
+        let private_spend_key = self.keystore.load_spend_key()?;
+        let signing_key = self.subaddress_spend_key(&private_spend_key, account, index);
+        let public_spend_key = signing_key.public_key();
+        // The view key of a subaddress is a * D for its spend key D
+        let public_view_key = match (account, index) {
+            (0, 0) => self.view_pair.view.public_key(),
+            _ => PublicKey::from_point(public_spend_key.to_point() * self.view_pair.view.to_scalar()),
+        };
+        write_message_signature(message, &signing_key, &public_spend_key, &public_view_key)
+    }
+
+    // Private spend key of a subaddress, b + m for its subaddress secret m, b for the primary address
+    fn subaddress_spend_key(&self, private_spend_key: &PrivateKey, account: u32, index: u32) -> PrivateKey {
+        match (account, index) {
+            (0, 0) => private_spend_key.clone(),
+            _ => {
+                let b = Zeroizing::new(private_spend_key.to_scalar());
+                PrivateKey::from_bytes_mod_order((*b + subaddress_secret(&self.view_pair.view, account, index)).to_bytes())
+            }
+        }
+    }
+
+    // One-time private key of a snapshot output, see `output_secret`
+    fn snapshot_output_secret(&self, output: &SnapshotOutput, private_spend_key: &PrivateKey) -> Result<PrivateKey, String> {
+        let owned = OwnedOutput {
+            index: output.index,
+            amount: output.amount,
+            output_key: output.output_key,
+            tx_public_key: output.tx_public_key,
+            pre_rct: output.pre_rct,
+            subaddress: output.subaddress,
+        };
+        self.output_secret(&owned, private_spend_key)
+    }
+
+    // One-time private key of the output, checked against its output key so a wrong keystore key is not used silently
+    fn output_secret(&self, output: &OwnedOutput, private_spend_key: &PrivateKey) -> Result<PrivateKey, String> {
+        let secret = output.secret_key(&self.view_pair.view, private_spend_key);
+        if OneTimeKey::from(secret.public_key()) != output.output_key {
+            return Err(format!("Output {} of transaction key {} does not belong to the signer", output.index, output.tx_public_key.to_hex()));
+        }
+        Ok(secret)
+    }
+}
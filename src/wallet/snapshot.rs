@@ -0,0 +1,267 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use crate::blocks::TxHash;
+use crate::keys::{OneTimeKey, OwnedOutput, PublicKey};
+use crate::utils::Network;
+use serde_json::{json, Value};
+
+/// Version of the snapshot format written by [`WalletSnapshot::to_json`](struct.WalletSnapshot.html#method.to_json)
+pub const SNAPSHOT_VERSION: u64 = 1;
+
+/// SnapshotOutput is an output received by the wallet, without any secret
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotOutput {
+    pub tx_hash: TxHash,
+    /// Height of the block containing the transaction
+    pub height: u64,
+    /// Index of the output in the transaction
+    pub index: u64,
+    /// Amount in piconeros
+    pub amount: u64,
+    pub output_key: OneTimeKey,
+    pub tx_public_key: PublicKey,
+    /// Account and subaddress index that received the output, `(0, 0)` for the primary address
+    pub subaddress: (u32, u32),
+    pub unlock_time: u64,
+    /// Height of the block spending the output, if spent
+    pub spent_height: Option<u64>,
+    /// Output of a version 1 transaction, see [`OwnedOutput::pre_rct`](../keys/struct.OwnedOutput.html#structfield.pre_rct)
+    pub pre_rct: bool,
+}
+
+impl SnapshotOutput {
+    /// Creates a snapshot output from an output found by [`scan_tx_for_outputs`](../keys/fn.scan_tx_for_outputs.html)
+    pub fn from_owned(output: &OwnedOutput, tx_hash: TxHash, height: u64, subaddress: (u32, u32), unlock_time: u64) -> SnapshotOutput {
+        SnapshotOutput {
+            tx_hash,
+            height,
+            index: output.index,
+            amount: output.amount,
+            output_key: output.output_key,
+            tx_public_key: output.tx_public_key,
+            subaddress,
+            unlock_time,
+            spent_height: None,
+            pre_rct: output.pre_rct,
+        }
+    }
+}
+
+/// SnapshotTransfer is an entry of the wallet history
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotTransfer {
+    pub tx_hash: TxHash,
+    /// Height of the block containing the transaction, `None` while in pool
+    pub height: Option<u64>,
+    pub timestamp: u64,
+    pub account: u32,
+    /// Amount received by the account in this transaction, in piconeros
+    pub incoming: u64,
+    /// Amount spent by the account in this transaction, change excluded, in piconeros
+    pub outgoing: u64,
+    pub fee: u64,
+    /// Payment ID in hex, if any
+    pub payment_id: Option<String>,
+}
+
+/// SubaddressLabel is a subaddress of the wallet with its user given label
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubaddressLabel {
+    pub account: u32,
+    pub index: u32,
+    pub address: String,
+    pub label: String,
+}
+
+/// WalletSnapshot is a portable export of the wallet's derived, non-secret data
+///
+/// It contains the received outputs, the history and the subaddress labels, but no key that can spend or view funds,
+/// so it can be given to a read-only analytics process. It is serialized as JSON.
+///
+/// Example:
+/// ```
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::{SubaddressLabel, WalletSnapshot};
+///
+/// let mut snapshot = WalletSnapshot::new(Network::Mainnet, "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT", 3000000);
+/// snapshot.subaddresses.push(SubaddressLabel { account: 0, index: 0, address: snapshot.primary_address.clone(), label: "Primary".to_string() });
+/// let restored = WalletSnapshot::from_json(&snapshot.to_json()).unwrap();
+/// assert_eq!(restored, snapshot);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WalletSnapshot {
+    pub network: Network,
+    pub primary_address: String,
+    /// Height the wallet was synced to when the snapshot was taken
+    pub synced_height: u64,
+    pub outputs: Vec<SnapshotOutput>,
+    pub transfers: Vec<SnapshotTransfer>,
+    pub subaddresses: Vec<SubaddressLabel>,
+}
+
+impl WalletSnapshot {
+    /// Creates an empty snapshot
+    pub fn new(network: Network, primary_address: &str, synced_height: u64) -> WalletSnapshot {
+        WalletSnapshot {
+            network,
+            primary_address: primary_address.to_string(),
+            synced_height,
+            outputs: Vec::new(),
+            transfers: Vec::new(),
+            subaddresses: Vec::new(),
+        }
+    }
+
+    /// Returns the sum of unspent outputs, of all accounts if `account` is `None`
+    pub fn balance(&self, account: Option<u32>) -> u64 {
+        self.unspent(account).map(|output| output.amount).sum()
+    }
+
+    // Unspent outputs of the given account, all accounts if `None`
+    pub(crate) fn unspent(&self, account: Option<u32>) -> impl Iterator<Item = &SnapshotOutput> {
+        self.outputs
+            .iter()
+            .filter(move |output| output.spent_height.is_none() && account.is_none_or(|account| output.subaddress.0 == account))
+    }
+
+    /// Returns the label of the given subaddress, if any
+    pub fn label(&self, account: u32, index: u32) -> Option<&str> {
+        self.subaddresses
+            .iter()
+            .find(|subaddress| subaddress.account == account && subaddress.index == index)
+            .map(|subaddress| subaddress.label.as_str())
+    }
+
+    /// Serializes the snapshot as JSON
+    pub fn to_json(&self) -> String {
+        json!({
+            "version": SNAPSHOT_VERSION,
+            "network": network_name(self.network),
+            "primary_address": self.primary_address,
+            "synced_height": self.synced_height,
+            "outputs": self.outputs.iter().map(output_to_json).collect::<Vec<Value>>(),
+            "transfers": self.transfers.iter().map(|transfer| json!({
+                "tx_hash": transfer.tx_hash.to_hex(),
+                "height": transfer.height,
+                "timestamp": transfer.timestamp,
+                "account": transfer.account,
+                "incoming": transfer.incoming,
+                "outgoing": transfer.outgoing,
+                "fee": transfer.fee,
+                "payment_id": transfer.payment_id,
+            })).collect::<Vec<Value>>(),
+            "subaddresses": self.subaddresses.iter().map(|subaddress| json!({
+                "account": subaddress.account,
+                "index": subaddress.index,
+                "address": subaddress.address,
+                "label": subaddress.label,
+            })).collect::<Vec<Value>>(),
+        })
+        .to_string()
+    }
+
+    /// Parses a snapshot written by [`to_json`](#method.to_json)
+    pub fn from_json(snapshot: &str) -> Result<WalletSnapshot, String> {
+        let json: Value = serde_json::from_str(snapshot).map_err(|e| format!("Error while parsing the snapshot JSON: {}", e))?;
+        let version = json["version"].as_u64().ok_or("Snapshot version is missing")?;
+        if version > SNAPSHOT_VERSION {
+            return Err(format!("Unsupported snapshot version {}", version));
+        }
+        let network = match json["network"].as_str() {
+            Some("mainnet") => Network::Mainnet,
+            Some("testnet") => Network::Testnet,
+            Some("stagenet") => Network::Stagenet,
+            _ => return Err("Invalid snapshot network".to_string()),
+        };
+        let mut result = WalletSnapshot::new(network, str_field(&json, "primary_address")?, u64_field(&json, "synced_height")?);
+        for output in array_field(&json, "outputs")? {
+            result.outputs.push(output_from_json(output)?);
+        }
+        for transfer in array_field(&json, "transfers")? {
+            result.transfers.push(SnapshotTransfer {
+                tx_hash: TxHash::from_hex(str_field(transfer, "tx_hash")?)?,
+                height: transfer["height"].as_u64(),
+                timestamp: u64_field(transfer, "timestamp")?,
+                account: u32_field(transfer, "account")?,
+                incoming: u64_field(transfer, "incoming")?,
+                outgoing: u64_field(transfer, "outgoing")?,
+                fee: u64_field(transfer, "fee")?,
+                payment_id: transfer["payment_id"].as_str().map(|payment_id| payment_id.to_string()),
+            });
+        }
+        for subaddress in array_field(&json, "subaddresses")? {
+            result.subaddresses.push(SubaddressLabel {
+                account: u32_field(subaddress, "account")?,
+                index: u32_field(subaddress, "index")?,
+                address: str_field(subaddress, "address")?.to_string(),
+                label: str_field(subaddress, "label")?.to_string(),
+            });
+        }
+        Ok(result)
+    }
+}
+
+pub(crate) fn output_to_json(output: &SnapshotOutput) -> Value {
+    json!({
+        "tx_hash": output.tx_hash.to_hex(),
+        "height": output.height,
+        "index": output.index,
+        "amount": output.amount,
+        "output_key": output.output_key.to_hex(),
+        "tx_public_key": output.tx_public_key.to_hex(),
+        "account": output.subaddress.0,
+        "subaddress": output.subaddress.1,
+        "unlock_time": output.unlock_time,
+        "spent_height": output.spent_height,
+        "pre_rct": output.pre_rct,
+    })
+}
+
+pub(crate) fn output_from_json(output: &Value) -> Result<SnapshotOutput, String> {
+    Ok(SnapshotOutput {
+        tx_hash: TxHash::from_hex(str_field(output, "tx_hash")?)?,
+        height: u64_field(output, "height")?,
+        index: u64_field(output, "index")?,
+        amount: u64_field(output, "amount")?,
+        output_key: OneTimeKey::from_hex(str_field(output, "output_key")?)?,
+        tx_public_key: PublicKey::from_hex(str_field(output, "tx_public_key")?)?,
+        subaddress: (u32_field(output, "account")?, u32_field(output, "subaddress")?),
+        unlock_time: u64_field(output, "unlock_time")?,
+        spent_height: output["spent_height"].as_u64(),
+        // Missing in snapshots written before pre-RCT outputs were tracked
+        pre_rct: output["pre_rct"].as_bool().unwrap_or(false),
+    })
+}
+
+// Name of the network in snapshots
+fn network_name(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "mainnet",
+        Network::Testnet => "testnet",
+        Network::Stagenet => "stagenet",
+    }
+}
+
+fn str_field<'a>(json: &'a Value, name: &str) -> Result<&'a str, String> {
+    json[name].as_str().ok_or(format!("Snapshot field {} is missing or invalid", name))
+}
+
+fn u64_field(json: &Value, name: &str) -> Result<u64, String> {
+    json[name].as_u64().ok_or(format!("Snapshot field {} is missing or invalid", name))
+}
+
+fn u32_field(json: &Value, name: &str) -> Result<u32, String> {
+    u32::try_from(u64_field(json, name)?).map_err(|_| format!("Snapshot field {} is out of range", name))
+}
+
+fn array_field<'a>(json: &'a Value, name: &str) -> Result<&'a Vec<Value>, String> {
+    json[name].as_array().ok_or(format!("Snapshot field {} is missing or invalid", name))
+}
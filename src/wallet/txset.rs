@@ -0,0 +1,664 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::archive::{
+    decrypt_with_view_key, encrypt_with_view_key, read_arity, read_bool, read_bytes, read_count, read_key, read_keys, read_string, read_transfers, read_u32,
+    read_u64, read_varint_u32, read_version, write_keys, write_string, write_transfers, ExportedTransfer, TRANSFER_FLAG_RCT,
+};
+use super::builder::{estimate_extra_size, output_mask, PreviewInput, SignedTx, TxBuilder, TxPreview};
+use super::signer::Signer;
+use super::snapshot::SnapshotOutput;
+use crate::blocks::transactions::read_transaction;
+use crate::blocks::{estimate_tx_weight, OutputEntry, TxHash, TxInput};
+use crate::keys::{derive_output_pubkey, derive_subaddress_keys, generate_key_derivation, Commitment, KeyImage, OneTimeKey, OwnedOutput, PrivateKey, PublicKey, ViewPair};
+use crate::utils::varint::{read_varint, write_varint};
+use crate::utils::{decode_address, AddressInfo, AddressType};
+use std::collections::BTreeSet;
+
+/// Magic starting the `unsigned_monero_tx` files of monero-wallet-cli
+pub const UNSIGNED_TX_PREFIX: &[u8] = b"Monero unsigned tx set\x05";
+/// Magic starting the `signed_monero_tx` files of monero-wallet-cli
+pub const SIGNED_TX_PREFIX: &[u8] = b"Monero signed tx set\x05";
+
+// Subaddresses searched for the change address of an account, the reference wallet sends change to index 0
+const CHANGE_SEARCH_DEPTH: u32 = 1000;
+
+/// UnsignedTxSet is a set of transactions to sign on an offline wallet, monero-wallet-cli's `unsigned_monero_tx`
+///
+/// The online view-only wallet exports the previews of its transactions with
+/// [`export_unsigned_txset`](fn.export_unsigned_txset.html), the offline wallet reads them with
+/// [`from_bytes`](#method.from_bytes), checks them and [`sign`](#method.sign)s them. Files are encrypted with a key
+/// derived from the private view key, so both wallets must share it. The rings are chosen by the online wallet.
+///
+/// Example:
+/// ```no_run
+/// use libmonero::keys::{PrivateKey, ViewPair};
+/// use libmonero::wallet::{MemoryKeyStore, Signer, UnsignedTxSet};
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let signer = Signer::new(MemoryKeyStore::new(private_spend_key.clone()), private_spend_key.derive_view_key()).unwrap();
+/// let unsigned = UnsignedTxSet::from_bytes(&std::fs::read("unsigned_monero_tx").unwrap(), signer.view_pair()).unwrap();
+/// for tx in unsigned.txs.iter() {
+///     println!("Paying {:?} with a fee of {}", tx.destinations, tx.fee);
+/// }
+/// let signed = unsigned.sign(&signer).unwrap();
+/// std::fs::write("signed_monero_tx", signed.to_bytes(&signer.view_pair().view).unwrap()).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsignedTxSet {
+    /// Transactions to sign, rebuilt from the construction data of the set
+    pub txs: Vec<TxPreview>,
+    construction_data: Vec<ConstructionData>,
+    transfers: Vec<ExportedTransfer>,
+}
+
+impl UnsignedTxSet {
+    /// Creates the set of the given previews, built by [`TxBuilder::dry_run`](struct.TxBuilder.html#method.dry_run) on
+    /// a view-only wallet of the given view pair
+    ///
+    /// Returns the set if succesfull
+    /// Returns an error message if a preview has no input, an invalid destination or an input not owned by the view pair
+    pub fn new(txs: Vec<TxPreview>, view_pair: &ViewPair) -> Result<UnsignedTxSet, String> {
+        // Spent outputs are exported once, in order of appearance, construction data refers to them by position
+        let mut inputs: Vec<&PreviewInput> = Vec::new();
+        for input in txs.iter().flat_map(|tx| tx.inputs.iter()) {
+            if !inputs.iter().any(|known| known.output.output_key == input.output.output_key) {
+                inputs.push(input);
+            }
+        }
+        let transfers = inputs
+            .iter()
+            .map(|input| ExportedTransfer {
+                output_key: *input.output.output_key.as_bytes(),
+                internal_output_index: input.output.index,
+                global_output_index: input.global_index,
+                tx_public_key: *input.output.tx_public_key.as_bytes(),
+                flags: if input.output.pre_rct { 0 } else { TRANSFER_FLAG_RCT },
+                amount: input.output.amount,
+                additional_tx_keys: Vec::new(),
+                subaddress: input.output.subaddress,
+            })
+            .collect();
+        let construction_data = txs
+            .iter()
+            .map(|tx| ConstructionData::from_preview(tx, &inputs, view_pair))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(UnsignedTxSet { txs, construction_data, transfers })
+    }
+
+    /// Returns the set in the format of monero-wallet-cli's `unsigned_monero_tx` files, encrypted with the private view key
+    ///
+    /// Returns the file content if succesfull
+    /// Returns an error message if the encryption key can not be derived
+    pub fn to_bytes(&self, private_view_key: &PrivateKey) -> Result<Vec<u8>, String> {
+        let mut plaintext = Vec::new();
+        write_varint(2, &mut plaintext);
+        write_varint(self.construction_data.len() as u64, &mut plaintext);
+        self.construction_data.iter().for_each(|data| data.write(&mut plaintext));
+        write_transfers(0, self.transfers.len() as u64, &self.transfers, &mut plaintext);
+        let mut data = UNSIGNED_TX_PREFIX.to_vec();
+        data.extend(encrypt_with_view_key(&plaintext, private_view_key)?);
+        Ok(data)
+    }
+
+    /// Reads a set from the content of an `unsigned_monero_tx` file, with the view pair of the wallet it spends from
+    ///
+    /// Returns the set if succesfull
+    /// Returns an error message if the file is not an unsigned transaction set of this wallet, or if a transaction
+    /// spends outputs the wallet does not own
+    pub fn from_bytes(data: &[u8], view_pair: &ViewPair) -> Result<UnsignedTxSet, String> {
+        let encrypted = data.strip_prefix(UNSIGNED_TX_PREFIX).ok_or("Data is not an unsigned transaction set")?;
+        let plaintext = decrypt_with_view_key(encrypted, &view_pair.view)?;
+        let pos = &mut 0;
+        if read_version(&plaintext, pos, 2, "unsigned transaction set")? != 2 {
+            return Err("Unsigned transaction sets older than version 2 are not supported".to_string());
+        }
+        let count = read_count(&plaintext, pos, 1)?;
+        let construction_data = (0..count).map(|_| ConstructionData::read(&plaintext, pos)).collect::<Result<Vec<_>, String>>()?;
+        let (offset, _, transfers) = read_transfers(&plaintext, pos)?;
+        if *pos != plaintext.len() {
+            return Err("Trailing data after the unsigned transaction set".to_string());
+        }
+        let txs = construction_data
+            .iter()
+            .map(|data| data.to_preview(&transfers, offset, view_pair))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(UnsignedTxSet { txs, construction_data, transfers })
+    }
+
+    /// Signs every transaction of the set with the given signer, as [`TxBuilder::sign`](struct.TxBuilder.html#method.sign) does
+    ///
+    /// The signed set carries the key images of the spent outputs back to the online wallet.
+    ///
+    /// Returns the signed set if succesfull
+    /// Returns an error message if a transaction can not be signed
+    pub fn sign(&self, signer: &Signer) -> Result<SignedTxSet, String> {
+        let builder = TxBuilder::new(Vec::new());
+        let txs = self.txs.iter().map(|tx| builder.sign(tx, signer)).collect::<Result<Vec<_>, String>>()?;
+        let mut owned: Vec<OwnedOutput> = Vec::with_capacity(self.transfers.len());
+        for input in self.txs.iter().flat_map(|tx| tx.inputs.iter()) {
+            if !owned.iter().any(|output| output.output_key == input.output.output_key) {
+                owned.push(OwnedOutput {
+                    index: input.output.index,
+                    amount: input.output.amount,
+                    output_key: input.output.output_key,
+                    tx_public_key: input.output.tx_public_key,
+                    pre_rct: input.output.pre_rct,
+                    subaddress: input.output.subaddress,
+                });
+            }
+        }
+        let key_images = owned.iter().map(|output| output.output_key).zip(signer.key_images(&owned)?).collect();
+        Ok(SignedTxSet { txs, key_images, construction_data: self.construction_data.clone() })
+    }
+}
+
+/// SignedTxSet is a set of transactions signed by an offline wallet, monero-wallet-cli's `signed_monero_tx`
+///
+/// The online wallet reads it with [`import_signed_txset`](fn.import_signed_txset.html) and broadcasts the
+/// transactions with [`send_raw_transaction`](../blocks/fn.send_raw_transaction.html).
+#[derive(Clone, Debug)]
+pub struct SignedTxSet {
+    pub txs: Vec<SignedTx>,
+    /// Key image of every output spent by the set, marking the outputs spent in view-only wallets
+    pub key_images: Vec<(OneTimeKey, KeyImage)>,
+    construction_data: Vec<ConstructionData>,
+}
+
+impl SignedTxSet {
+    /// Returns the set in the format of monero-wallet-cli's `signed_monero_tx` files, encrypted with the private view key
+    ///
+    /// Returns the file content if succesfull
+    /// Returns an error message if the encryption key can not be derived
+    pub fn to_bytes(&self, private_view_key: &PrivateKey) -> Result<Vec<u8>, String> {
+        let mut plaintext = Vec::new();
+        write_varint(0, &mut plaintext);
+        write_varint(self.txs.len() as u64, &mut plaintext);
+        for (tx, data) in self.txs.iter().zip(self.construction_data.iter()) {
+            write_pending_tx(tx, data, &mut plaintext);
+        }
+        write_varint(self.key_images.len() as u64, &mut plaintext);
+        self.key_images.iter().for_each(|(_, key_image)| plaintext.extend_from_slice(key_image.as_bytes()));
+        write_varint(self.key_images.len() as u64, &mut plaintext);
+        for (output_key, key_image) in self.key_images.iter() {
+            write_varint(2, &mut plaintext);
+            plaintext.extend_from_slice(output_key.as_bytes());
+            plaintext.extend_from_slice(key_image.as_bytes());
+        }
+        let mut data = SIGNED_TX_PREFIX.to_vec();
+        data.extend(encrypt_with_view_key(&plaintext, private_view_key)?);
+        Ok(data)
+    }
+
+    /// Reads a set from the content of a `signed_monero_tx` file, with the private view key of the wallet it spends from
+    ///
+    /// Returns the set if succesfull
+    /// Returns an error message if the file is not a signed transaction set of this wallet
+    pub fn from_bytes(data: &[u8], private_view_key: &PrivateKey) -> Result<SignedTxSet, String> {
+        let encrypted = data.strip_prefix(SIGNED_TX_PREFIX).ok_or("Data is not a signed transaction set")?;
+        let plaintext = decrypt_with_view_key(encrypted, private_view_key)?;
+        let pos = &mut 0;
+        read_version(&plaintext, pos, 0, "signed transaction set")?;
+        let count = read_count(&plaintext, pos, 1)?;
+        let mut txs = Vec::with_capacity(count);
+        let mut construction_data = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (tx, data) = read_pending_tx(&plaintext, pos)?;
+            txs.push(tx);
+            construction_data.push(data);
+        }
+        // Key images of every output of the signing wallet, the spent ones are also mapped to their output below
+        let count = read_count(&plaintext, pos, 32)?;
+        read_bytes(&plaintext, pos, count * 32)?;
+        let count = read_count(&plaintext, pos, 65)?;
+        let mut key_images = Vec::with_capacity(count);
+        for _ in 0..count {
+            read_arity(&plaintext, pos, 2)?;
+            key_images.push((OneTimeKey::from_bytes(read_key(&plaintext, pos)?), KeyImage::from_bytes(read_key(&plaintext, pos)?)));
+        }
+        if *pos != plaintext.len() {
+            return Err("Trailing data after the signed transaction set".to_string());
+        }
+        Ok(SignedTxSet { txs, key_images, construction_data })
+    }
+}
+
+/// Exports transaction previews of a view-only wallet as an `unsigned_monero_tx` file for an offline wallet to sign
+///
+/// Arguments:
+/// - `previews`: the transactions, as returned by [`TxBuilder::dry_run`](struct.TxBuilder.html#method.dry_run)
+/// - `view_pair`: the public spend key and private view key of the wallet
+///
+/// Returns the content of the file if succesfull
+/// Returns an error message if a preview is invalid
+pub fn export_unsigned_txset(previews: &[TxPreview], view_pair: &ViewPair) -> Result<Vec<u8>, String> {
+    UnsignedTxSet::new(previews.to_vec(), view_pair)?.to_bytes(&view_pair.view)
+}
+
+/// Imports a `signed_monero_tx` file produced by an offline wallet from an [`UnsignedTxSet`](struct.UnsignedTxSet.html)
+///
+/// Arguments:
+/// - `data`: the content of the file
+/// - `private_view_key`: the private view key of the wallet
+///
+/// Returns the signed transactions and the key images of the spent outputs as a SignedTxSet if succesfull
+/// Returns an error message if the file is not a signed transaction set of this wallet
+pub fn import_signed_txset(data: &[u8], private_view_key: &PrivateKey) -> Result<SignedTxSet, String> {
+    SignedTxSet::from_bytes(data, private_view_key)
+}
+
+// Input of a transaction with its ring, monero-core's `tx_source_entry`
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SourceEntry {
+    // Global index, key and commitment of each ring member
+    ring: Vec<(u64, [u8; 32], [u8; 32])>,
+    real_output: usize,
+    tx_public_key: [u8; 32],
+    additional_tx_keys: Vec<[u8; 32]>,
+    index_in_tx: u64,
+    amount: u64,
+    rct: bool,
+    mask: [u8; 32],
+}
+
+impl SourceEntry {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_varint(self.ring.len() as u64, out);
+        for (index, key, commitment) in self.ring.iter() {
+            write_varint(2, out);
+            write_varint(*index, out);
+            out.extend_from_slice(key);
+            out.extend_from_slice(commitment);
+        }
+        out.extend_from_slice(&(self.real_output as u64).to_le_bytes());
+        out.extend_from_slice(&self.tx_public_key);
+        write_keys(&self.additional_tx_keys, out);
+        out.extend_from_slice(&self.index_in_tx.to_le_bytes());
+        out.extend_from_slice(&self.amount.to_le_bytes());
+        out.push(self.rct as u8);
+        out.extend_from_slice(&self.mask);
+        // Multisig k, L, R and partial key image, unused
+        out.extend_from_slice(&[0; 4 * 32]);
+    }
+
+    fn read(data: &[u8], pos: &mut usize) -> Result<SourceEntry, String> {
+        let count = read_count(data, pos, 66)?;
+        let mut ring = Vec::with_capacity(count);
+        for _ in 0..count {
+            read_arity(data, pos, 2)?;
+            ring.push((read_varint(data, pos)?, read_key(data, pos)?, read_key(data, pos)?));
+        }
+        let real_output = read_u64(data, pos)?;
+        if real_output >= ring.len() as u64 {
+            return Err(format!("Real output {} is out of a ring of {}", real_output, ring.len()));
+        }
+        let source = SourceEntry {
+            ring,
+            real_output: real_output as usize,
+            tx_public_key: read_key(data, pos)?,
+            additional_tx_keys: read_keys(data, pos)?,
+            index_in_tx: read_u64(data, pos)?,
+            amount: read_u64(data, pos)?,
+            rct: read_bool(data, pos)?,
+            mask: read_key(data, pos)?,
+        };
+        read_bytes(data, pos, 4 * 32)?;
+        Ok(source)
+    }
+}
+
+// Payment of a transaction, monero-core's `tx_destination_entry`
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DestinationEntry {
+    address: String,
+    amount: u64,
+    public_spend_key: [u8; 32],
+    public_view_key: [u8; 32],
+    is_subaddress: bool,
+    is_integrated: bool,
+}
+
+impl DestinationEntry {
+    fn new(address: String, amount: u64, info: &AddressInfo) -> DestinationEntry {
+        DestinationEntry {
+            address,
+            amount,
+            public_spend_key: *info.public_spend_key.as_bytes(),
+            public_view_key: *info.public_view_key.as_bytes(),
+            is_subaddress: info.address_type == AddressType::Subaddress,
+            is_integrated: info.address_type == AddressType::Integrated,
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        write_string(self.address.as_bytes(), out);
+        write_varint(self.amount, out);
+        out.extend_from_slice(&self.public_spend_key);
+        out.extend_from_slice(&self.public_view_key);
+        out.push(self.is_subaddress as u8);
+        out.push(self.is_integrated as u8);
+    }
+
+    fn read(data: &[u8], pos: &mut usize) -> Result<DestinationEntry, String> {
+        Ok(DestinationEntry {
+            address: String::from_utf8(read_string(data, pos)?).map_err(|_| "Destination address is not UTF-8".to_string())?,
+            amount: read_varint(data, pos)?,
+            public_spend_key: read_key(data, pos)?,
+            public_view_key: read_key(data, pos)?,
+            is_subaddress: read_bool(data, pos)?,
+            is_integrated: read_bool(data, pos)?,
+        })
+    }
+}
+
+fn write_destinations(destinations: &[DestinationEntry], out: &mut Vec<u8>) {
+    write_varint(destinations.len() as u64, out);
+    destinations.iter().for_each(|destination| destination.write(out));
+}
+
+fn read_destinations(data: &[u8], pos: &mut usize) -> Result<Vec<DestinationEntry>, String> {
+    let count = read_count(data, pos, 67)?;
+    (0..count).map(|_| DestinationEntry::read(data, pos)).collect()
+}
+
+fn write_indices(indices: &[u64], out: &mut Vec<u8>) {
+    write_varint(indices.len() as u64, out);
+    indices.iter().for_each(|index| write_varint(*index, out));
+}
+
+fn read_indices(data: &[u8], pos: &mut usize) -> Result<Vec<u64>, String> {
+    let count = read_count(data, pos, 1)?;
+    (0..count).map(|_| read_varint(data, pos)).collect()
+}
+
+// Everything the signing wallet needs to build a transaction, monero-core's `tx_construction_data`
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ConstructionData {
+    sources: Vec<SourceEntry>,
+    change: DestinationEntry,
+    // Destinations and the change output
+    splitted: Vec<DestinationEntry>,
+    // Position of the spent outputs among the exported transfers
+    selected_transfers: Vec<u64>,
+    extra: Vec<u8>,
+    destinations: Vec<DestinationEntry>,
+    account: u32,
+    subaddress_indices: BTreeSet<u32>,
+}
+
+impl ConstructionData {
+    fn from_preview(preview: &TxPreview, transfers: &[&PreviewInput], view_pair: &ViewPair) -> Result<ConstructionData, String> {
+        if preview.inputs.is_empty() {
+            return Err("Transaction has no input".to_string());
+        }
+        let mut sources = Vec::with_capacity(preview.inputs.len());
+        let mut selected_transfers = Vec::with_capacity(preview.inputs.len());
+        for input in preview.inputs.iter() {
+            let real_output = input
+                .ring
+                .iter()
+                .position(|(index, entry)| *index == input.global_index && entry.key == input.output.output_key)
+                .ok_or(format!("Ring of output {} of transaction {} does not contain it", input.output.index, input.output.tx_hash))?;
+            sources.push(SourceEntry {
+                ring: input.ring.iter().map(|(index, entry)| (*index, *entry.key.as_bytes(), *entry.mask.as_bytes())).collect(),
+                real_output,
+                tx_public_key: *input.output.tx_public_key.as_bytes(),
+                additional_tx_keys: Vec::new(),
+                index_in_tx: input.output.index,
+                amount: input.output.amount,
+                rct: !input.output.pre_rct,
+                mask: output_mask(&view_pair.view, &input.output, &input.ring[real_output].1.mask)?.to_bytes(),
+            });
+            let position = transfers.iter().position(|transfer| transfer.output.output_key == input.output.output_key).unwrap();
+            selected_transfers.push(position as u64);
+        }
+
+        let mut extra = Vec::new();
+        let mut destinations = Vec::with_capacity(preview.destinations.len());
+        for (address, amount) in preview.destinations.iter() {
+            let info = decode_address(address)?;
+            // Payment IDs of integrated addresses go in the extra nonce, encrypted by the signing wallet
+            if let Some(payment_id) = info.payment_id {
+                extra.extend_from_slice(&[0x02, 9, 0x01]);
+                extra.extend_from_slice(&payment_id);
+            }
+            destinations.push(DestinationEntry::new(address.clone(), *amount, &info));
+        }
+        let (account, index) = preview.change_subaddress;
+        let (public_spend_key, public_view_key) = derive_subaddress_keys(view_pair, account, index);
+        let change = DestinationEntry {
+            address: String::new(),
+            amount: preview.change,
+            public_spend_key: *public_spend_key.as_bytes(),
+            public_view_key: *public_view_key.as_bytes(),
+            is_subaddress: (account, index) != (0, 0),
+            is_integrated: false,
+        };
+        let mut splitted = destinations.clone();
+        if preview.change > 0 || destinations.len() < 2 {
+            splitted.push(change.clone());
+        }
+        Ok(ConstructionData {
+            sources,
+            change,
+            splitted,
+            selected_transfers,
+            extra,
+            destinations,
+            account: preview.inputs[0].output.subaddress.0,
+            subaddress_indices: preview.inputs.iter().map(|input| input.output.subaddress.1).collect(),
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        write_varint(1, out);
+        write_varint(self.sources.len() as u64, out);
+        self.sources.iter().for_each(|source| source.write(out));
+        self.change.write(out);
+        write_destinations(&self.splitted, out);
+        write_indices(&self.selected_transfers, out);
+        write_string(&self.extra, out);
+        // Unlock time, RingCT, Bulletproofs+ configuration and view tags
+        out.extend_from_slice(&0u64.to_le_bytes());
+        out.push(1);
+        out.extend_from_slice(&[0, 3, 4]);
+        out.push(1);
+        write_destinations(&self.destinations, out);
+        out.extend_from_slice(&self.account.to_le_bytes());
+        write_varint(self.subaddress_indices.len() as u64, out);
+        self.subaddress_indices.iter().for_each(|index| write_varint(*index as u64, out));
+    }
+
+    fn read(data: &[u8], pos: &mut usize) -> Result<ConstructionData, String> {
+        if read_version(data, pos, 1, "transaction construction data")? != 1 {
+            return Err("Transactions without view tags are not supported".to_string());
+        }
+        let count = read_count(data, pos, 66)?;
+        let sources = (0..count).map(|_| SourceEntry::read(data, pos)).collect::<Result<Vec<_>, String>>()?;
+        let change = DestinationEntry::read(data, pos)?;
+        let splitted = read_destinations(data, pos)?;
+        let selected_transfers = read_indices(data, pos)?;
+        let extra = read_string(data, pos)?;
+        if read_u64(data, pos)? != 0 {
+            return Err("Transactions with an unlock time are not supported".to_string());
+        }
+        if !read_bool(data, pos)? {
+            return Err("Transactions without RingCT are not supported".to_string());
+        }
+        read_version(data, pos, 0, "RingCT configuration")?;
+        let (range_proof_type, bulletproof_version) = (read_varint(data, pos)?, read_varint(data, pos)?);
+        if range_proof_type != 3 || bulletproof_version != 4 {
+            return Err("Transactions without Bulletproofs+ are not supported".to_string());
+        }
+        read_bool(data, pos)?;
+        let destinations = read_destinations(data, pos)?;
+        let account = read_u32(data, pos)?;
+        let count = read_count(data, pos, 1)?;
+        let subaddress_indices = (0..count).map(|_| read_varint_u32(data, pos)).collect::<Result<BTreeSet<_>, String>>()?;
+        Ok(ConstructionData { sources, change, splitted, selected_transfers, extra, destinations, account, subaddress_indices })
+    }
+
+    // Rebuilds the preview of the transaction, checking the spent outputs belong to the view pair
+    fn to_preview(&self, transfers: &[ExportedTransfer], offset: u64, view_pair: &ViewPair) -> Result<TxPreview, String> {
+        if self.sources.len() != self.selected_transfers.len() {
+            return Err(format!("Transaction spends {} outputs but selects {}", self.sources.len(), self.selected_transfers.len()));
+        }
+        let mut inputs = Vec::with_capacity(self.sources.len());
+        for (source, selected) in self.sources.iter().zip(self.selected_transfers.iter()) {
+            let transfer = selected
+                .checked_sub(offset)
+                .and_then(|position| transfers.get(position as usize))
+                .ok_or(format!("Transaction spends unknown output {}", selected))?;
+            let (global_index, output_key, _) = source.ring[source.real_output];
+            if transfer.output_key != output_key {
+                return Err(format!("Output {} does not match the spent output", selected));
+            }
+            // Outputs of transactions with additional public keys are derived from the key of their index
+            let (major, minor) = transfer.subaddress;
+            let (public_spend_key, _) = derive_subaddress_keys(view_pair, major, minor);
+            let tx_public_key = std::iter::once(&source.tx_public_key)
+                .chain(source.additional_tx_keys.get(source.index_in_tx as usize))
+                .filter_map(|key| PublicKey::from_bytes(*key).ok())
+                .find(|key| *derive_output_pubkey(&generate_key_derivation(key, &view_pair.view), source.index_in_tx, &public_spend_key).as_bytes() == output_key)
+                .ok_or(format!("Output {} does not belong to this wallet", selected))?;
+            let output = SnapshotOutput {
+                tx_hash: TxHash::from_bytes([0; 32]),
+                height: 0,
+                index: source.index_in_tx,
+                amount: source.amount,
+                output_key: OneTimeKey::from_bytes(output_key),
+                tx_public_key,
+                subaddress: transfer.subaddress,
+                unlock_time: 0,
+                spent_height: None,
+                pre_rct: !source.rct,
+            };
+            let ring = source
+                .ring
+                .iter()
+                .map(|(index, key, commitment)| {
+                    let entry = OutputEntry {
+                        height: 0,
+                        key: OneTimeKey::from_bytes(*key),
+                        mask: Commitment::from_bytes(*commitment),
+                        txid: TxHash::from_bytes([0; 32]),
+                        unlocked: true,
+                    };
+                    (*index, entry)
+                })
+                .collect();
+            inputs.push(PreviewInput { output, global_index, ring });
+        }
+
+        if self.destinations.iter().any(|destination| destination.address.is_empty()) {
+            return Err("Destination address is missing".to_string());
+        }
+        let destinations: Vec<(String, u64)> = self.destinations.iter().map(|destination| (destination.address.clone(), destination.amount)).collect();
+        let amount = destinations.iter().try_fold(0u64, |sum, (_, amount)| sum.checked_add(*amount)).ok_or("Destination amounts overflow")?;
+        let inputs_total = inputs.iter().try_fold(0u64, |sum, input| sum.checked_add(input.output.amount)).ok_or("Input amounts overflow")?;
+        let outputs_total = self.splitted.iter().try_fold(0u64, |sum, destination| sum.checked_add(destination.amount)).ok_or("Output amounts overflow")?;
+        let fee = inputs_total.checked_sub(outputs_total).ok_or("Outputs exceed the inputs")?;
+        if outputs_total != amount.saturating_add(self.change.amount) {
+            return Err("Outputs do not match the destinations and change".to_string());
+        }
+        let change_subaddress = self.change_subaddress(view_pair)?;
+
+        let addresses = destinations.iter().map(|(address, _)| decode_address(address)).collect::<Result<Vec<_>, String>>()?;
+        let outputs = (destinations.len() + 1).max(2);
+        let ring_size = inputs.iter().map(|input| input.ring.len()).max().unwrap_or(0);
+        let weight = estimate_tx_weight(inputs.len(), outputs, ring_size, estimate_extra_size(&addresses, outputs));
+        Ok(TxPreview {
+            inputs,
+            destinations,
+            amount,
+            fee,
+            change: self.change.amount,
+            change_subaddress,
+            weight,
+            fee_per_byte: fee / weight.max(1),
+            warnings: Vec::new(),
+        })
+    }
+
+    // Finds the subaddress of the change among the subaddresses of the spending account
+    fn change_subaddress(&self, view_pair: &ViewPair) -> Result<(u32, u32), String> {
+        let candidates = std::iter::once((0, 0))
+            .chain(self.subaddress_indices.iter().map(|index| (self.account, *index)))
+            .chain((0..CHANGE_SEARCH_DEPTH).map(|index| (self.account, index)));
+        for (account, index) in candidates {
+            let (public_spend_key, public_view_key) = derive_subaddress_keys(view_pair, account, index);
+            if *public_spend_key.as_bytes() == self.change.public_spend_key && *public_view_key.as_bytes() == self.change.public_view_key {
+                return Ok((account, index));
+            }
+        }
+        Err("Change does not go to this wallet".to_string())
+    }
+}
+
+// Signed transaction with its construction data, monero-core's `pending_tx`
+fn write_pending_tx(tx: &SignedTx, data: &ConstructionData, out: &mut Vec<u8>) {
+    write_varint(1, out);
+    out.extend(tx.to_blob());
+    // Dust, fee and whether the dust was added to the fee
+    out.extend_from_slice(&0u64.to_le_bytes());
+    out.extend_from_slice(&tx.fee.to_le_bytes());
+    out.push(0);
+    data.change.write(out);
+    write_indices(&data.selected_transfers, out);
+    let key_images: String = tx
+        .transaction
+        .prefix
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            TxInput::ToKey { key_image, .. } => Some(format!("<{}> ", hex::encode(key_image.as_bytes()))),
+            _ => None,
+        })
+        .collect();
+    write_string(key_images.as_bytes(), out);
+    out.extend_from_slice(tx.tx_secret_key.as_bytes());
+    write_varint(tx.additional_secret_keys.len() as u64, out);
+    tx.additional_secret_keys.iter().for_each(|key| out.extend_from_slice(key.as_bytes()));
+    write_destinations(&data.destinations, out);
+    data.write(out);
+    // Multisig signatures and the entropy of multisig transaction keys
+    write_varint(0, out);
+    out.extend_from_slice(&[0; 32]);
+}
+
+fn read_pending_tx(data: &[u8], pos: &mut usize) -> Result<(SignedTx, ConstructionData), String> {
+    let version = read_version(data, pos, 1, "pending transaction")?;
+    let transaction = read_transaction(data, pos)?;
+    read_u64(data, pos)?;
+    let fee = read_u64(data, pos)?;
+    read_bool(data, pos)?;
+    DestinationEntry::read(data, pos)?;
+    read_indices(data, pos)?;
+    read_string(data, pos)?;
+    let tx_secret_key = PrivateKey::from_bytes(read_key(data, pos)?)?;
+    let additional_secret_keys = read_keys(data, pos)?.into_iter().map(PrivateKey::from_bytes).collect::<Result<Vec<_>, String>>()?;
+    let destinations = read_destinations(data, pos)?;
+    let construction_data = ConstructionData::read(data, pos)?;
+    if read_count(data, pos, 1)? != 0 {
+        return Err("Multisig transactions are not supported".to_string());
+    }
+    if version >= 1 {
+        read_bytes(data, pos, 32)?;
+    }
+    let tx = SignedTx {
+        tx_hash: transaction.hash()?,
+        transaction,
+        tx_secret_key,
+        additional_secret_keys,
+        destinations: destinations.into_iter().map(|destination| (destination.address, destination.amount)).collect(),
+        fee,
+    };
+    Ok((tx, construction_data))
+}
@@ -0,0 +1,144 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use super::snapshot::{SnapshotOutput, WalletSnapshot};
+use crate::blocks::{height_at, BLOCK_TARGET_SECONDS};
+use crate::utils::Network;
+
+/// Number of blocks an output has to wait after its block before it can be spent
+pub const SPENDABLE_AGE: u64 = 10;
+
+// Unlock times below this value are block heights, above it Unix timestamps
+const MAX_BLOCK_NUMBER: u64 = 500000000;
+
+impl SnapshotOutput {
+    /// Checks if the output can be spent in a transaction built when the chain has `chain_height` blocks at Unix time `timestamp`
+    ///
+    /// Uses the consensus rules of the reference wallet: the output must be [`SPENDABLE_AGE`](constant.SPENDABLE_AGE.html)
+    /// blocks deep and its unlock time, either a block height or a timestamp, must be reached
+    pub fn is_spendable_at(&self, chain_height: u64, timestamp: u64) -> bool {
+        if self.height + SPENDABLE_AGE > chain_height {
+            return false;
+        }
+        if self.unlock_time < MAX_BLOCK_NUMBER {
+            chain_height >= self.unlock_time
+        } else {
+            timestamp + BLOCK_TARGET_SECONDS >= self.unlock_time
+        }
+    }
+
+    /// Returns the first chain height at which the output can be spent
+    ///
+    /// Timestamp unlock times are converted to a height with [`height_at`](../blocks/fn.height_at.html), so the result is
+    /// an estimate for them
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::TxHash;
+    /// use libmonero::keys::{OneTimeKey, PublicKey};
+    /// use libmonero::utils::Network;
+    /// use libmonero::wallet::SnapshotOutput;
+    ///
+    /// let mut output = SnapshotOutput {
+    ///     tx_hash: TxHash::default(),
+    ///     height: 3000000,
+    ///     index: 0,
+    ///     amount: 600000000000,
+    ///     output_key: OneTimeKey::default(),
+    ///     tx_public_key: PublicKey::from_hex("528a736a5079dc9536edb5b6fa0a5209ce820b9734fc0785024670b3d3ba4c69").unwrap(),
+    ///     subaddress: (0, 0),
+    ///     unlock_time: 0,
+    ///     spent_height: None,
+    ///     pre_rct: false,
+    /// };
+    /// assert_eq!(output.unlock_height(Network::Mainnet), 3000010);
+    /// // Coinbase outputs are locked for 60 blocks
+    /// output.unlock_time = 3000060;
+    /// assert_eq!(output.unlock_height(Network::Mainnet), 3000060);
+    /// assert!(!output.is_spendable_at(3000059, 0));
+    /// assert!(output.is_spendable_at(3000060, 0));
+    /// ```
+    pub fn unlock_height(&self, network: Network) -> u64 {
+        let lock_height = if self.unlock_time < MAX_BLOCK_NUMBER {
+            self.unlock_time
+        } else {
+            height_at(self.unlock_time.saturating_sub(BLOCK_TARGET_SECONDS), network) + 1
+        };
+        lock_height.max(self.height + SPENDABLE_AGE)
+    }
+}
+
+impl WalletSnapshot {
+    /// Returns the unspent outputs of the given account (all accounts if `None`) that can be spent when the chain has
+    /// `chain_height` blocks at Unix time `timestamp`
+    pub fn spendable_at(&self, account: Option<u32>, chain_height: u64, timestamp: u64) -> Vec<&SnapshotOutput> {
+        self.unspent(account).filter(|output| output.is_spendable_at(chain_height, timestamp)).collect()
+    }
+
+    /// Returns the unspent outputs of the given account (all accounts if `None`) that are still locked when the chain has
+    /// `chain_height` blocks at Unix time `timestamp`
+    pub fn locked_at(&self, account: Option<u32>, chain_height: u64, timestamp: u64) -> Vec<&SnapshotOutput> {
+        self.unspent(account).filter(|output| !output.is_spendable_at(chain_height, timestamp)).collect()
+    }
+
+    /// Returns the sum of the outputs returned by [`spendable_at`](#method.spendable_at)
+    pub fn unlocked_balance_at(&self, account: Option<u32>, chain_height: u64, timestamp: u64) -> u64 {
+        self.spendable_at(account, chain_height, timestamp).iter().map(|output| output.amount).sum()
+    }
+
+    /// Projects the unlocked balance of the given account (all accounts if `None`) over time, assuming no output is spent
+    ///
+    /// Returns `(chain_height, unlocked_balance)` steps sorted by height: the first one is the balance unlocked at
+    /// `synced_height`, each following one the balance once the chain reaches the given height. Heights of timestamp
+    /// locked outputs are estimated, see [`SnapshotOutput::unlock_height`](struct.SnapshotOutput.html#method.unlock_height).
+    ///
+    /// Example:
+    /// ```
+    /// use libmonero::blocks::TxHash;
+    /// use libmonero::keys::{OneTimeKey, PublicKey};
+    /// use libmonero::utils::Network;
+    /// use libmonero::wallet::{SnapshotOutput, WalletSnapshot};
+    ///
+    /// let mut snapshot = WalletSnapshot::new(Network::Mainnet, "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT", 3000005);
+    /// for (height, unlock_time) in [(2000000, 0), (3000000, 0), (3000000, 3000060)] {
+    ///     snapshot.outputs.push(SnapshotOutput {
+    ///         tx_hash: TxHash::default(),
+    ///         height,
+    ///         index: 0,
+    ///         amount: 1000,
+    ///         output_key: OneTimeKey::default(),
+    ///         tx_public_key: PublicKey::from_hex("528a736a5079dc9536edb5b6fa0a5209ce820b9734fc0785024670b3d3ba4c69").unwrap(),
+    ///         subaddress: (0, 0),
+    ///         unlock_time,
+    ///         spent_height: None,
+    ///         pre_rct: false,
+    ///     });
+    /// }
+    /// assert_eq!(snapshot.unlock_schedule(None), vec![(3000005, 1000), (3000010, 2000), (3000060, 3000)]);
+    /// ```
+    pub fn unlock_schedule(&self, account: Option<u32>) -> Vec<(u64, u64)> {
+        let mut unlocks: Vec<(u64, u64)> = self
+            .unspent(account)
+            .map(|output| (output.unlock_height(self.network).max(self.synced_height), output.amount))
+            .collect();
+        unlocks.sort_unstable();
+        let mut schedule = vec![(self.synced_height, 0)];
+        for (height, amount) in unlocks {
+            let last = schedule.last_mut().unwrap();
+            if last.0 == height {
+                last.1 += amount;
+            } else {
+                let balance = last.1 + amount;
+                schedule.push((height, balance));
+            }
+        }
+        schedule
+    }
+}
@@ -8,76 +8,696 @@
  *
  */
 
-//! # Wallet
-//! 
-//! This module contains everything about wallets
+use super::builder::{OpenAliasLookup, SignedTx, TxBuilder, TxPreview};
+use super::key_images::import_key_images;
+use super::keys_file::KeysFile;
+use super::outputs_file::{export_outputs, import_outputs};
+use super::proofs::get_tx_rings;
+use super::signer::{MemoryKeyStore, Signer};
+use super::limits::SpendingPolicy;
+use super::snapshot::{SnapshotOutput, SnapshotTransfer, WalletSnapshot};
+use super::watch::genesis_hash;
+use crate::blocks::{
+    get_blocks_bin, get_transaction, is_key_image_spent, parse_transaction_blob, send_raw_transaction, BlockHash, BlocksBinResult, DaemonNode, FeePriority,
+    KeyImageStatus, ParsedBlock, RawTx, TxHash,
+};
+use crate::keys::{
+    derive_hex_seed, derive_priv_keys, derive_subaddress, generate_key_image, scan_tx_for_subaddresses, validate_mnemonic, KeyImage, OneTimeKey, OwnedOutput, PrivateKey,
+    PublicKey, ScanFilter, SubaddressTable, ViewPair,
+};
+use crate::utils::{decode_address, parse_uri, DestinationPolicy, Network, OpenAliasResolver, ResolvedDestination};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use std::collections::HashMap;
-use crate::keys;
+// Number of scanned blocks remembered to detect reorganizations
+const RECENT_BLOCKS: usize = 100;
 
-/// Wallet struct contains all the information about a wallet
+/// Number of unused accounts and subaddresses per account scanned for by default, see [`Wallet::with_lookahead`](struct.Wallet.html#method.with_lookahead)
+pub const DEFAULT_LOOKAHEAD: (u32, u32) = (5, 20);
+
+/// SyncProgress is reported by [`Wallet::sync`](struct.Wallet.html#method.sync) after every batch of blocks
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// Height of the next block to scan
+    pub synced_height: u64,
+    /// Height of the chain of the daemon
+    pub chain_height: u64,
+    /// Outputs received in the batch
+    pub received: Vec<SnapshotOutput>,
+    /// One-time keys of the owned outputs spent in the batch
+    pub spent: Vec<OneTimeKey>,
+    /// Height from which blocks were replaced, if the batch reorganized the chain scanned before
+    pub reorg: Option<u64>,
+}
+
+/// Wallet tracks the outputs owned by a view pair, their key images and spent status
+///
+/// Wallets created from a private spend key or a mnemonic generate the key image of every received output and mark
+/// it spent when a scanned transaction uses it. View-only wallets detect incoming outputs only, their key images come
+/// from the wallet holding the spend key through [`import_key_images`](#method.import_key_images).
+///
+/// The outputs are kept in a [`WalletSnapshot`](struct.WalletSnapshot.html), so the snapshot and unlock time helpers
+/// work on them too.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::PrivateKey;
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::Wallet;
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let wallet = Wallet::from_keys(Network::Mainnet, private_spend_key).with_restore_height(3000000);
+/// assert_eq!(wallet.address(), "4AQ3jTJg91yNGTXjo9iWr1ekjBGJ5mM6HEsxKqoKddHnRwJTVJYnyLXeerff6iTys5Eo8dyG87tfqZNS5CcSd7U694YiR8J");
+/// assert_eq!(wallet.synced_height(), 3000000);
+/// assert_eq!(wallet.balance(), 0);
+/// assert!(!wallet.is_view_only());
+/// ```
+#[derive(Clone, Debug)]
 pub struct Wallet {
-    pub mnemonic: Vec<String>,
-    pub hex_seed: String,
-    pub priv_sk: String,
-    pub priv_vk: String,
-    pub pub_sk: String,
-    pub pub_vk: String,
-    pub main_address: String,
-    pub sub_adresses: Vec<String>,
-    pub transactions: Vec<Transaction>,
-    pub main_node: DaemonNode,
-    pub processes: HashMap<String, String>
+    network: Network,
+    private_spend_key: Option<PrivateKey>,
+    table: SubaddressTable,
+    lookahead: (u32, u32),
+    // Number of subaddresses in the table for each account
+    table_extents: Vec<u32>,
+    snapshot: WalletSnapshot,
+    // Key images of the owned outputs, mapped to their one-time key
+    key_images: HashMap<KeyImage, OneTimeKey>,
+    recent: VecDeque<(u64, BlockHash)>,
+    openalias: Option<OpenAliasLookup>,
+    spending_policy: SpendingPolicy,
+    // One-time keys of the outputs spent by transactions broadcast with send and not scanned yet
+    pending_spends: HashSet<OneTimeKey>,
 }
 
-/// Wallet implementation
 impl Wallet {
-    /// Creates a new wallet with given parameters
-    pub fn new(language: &str, seed_type: &str, network: u8, default_node: DaemonNode) -> Wallet {
-        let mnemonic = keys::generate_seed(language, seed_type);
-        let hex_seed = keys::derive_hex_seed(mnemonic.clone());
-        let priv_keys = keys::derive_priv_keys(hex_seed.clone());
-        let priv_sk = &priv_keys[0];
-        let priv_vk = &priv_keys[1];
-        let pub_sk = keys::derive_pub_key(priv_sk.to_string());
-        let pub_vk = keys::derive_pub_key(priv_vk.to_string());
-        let address = keys::derive_address(pub_sk.clone(), pub_vk.clone(), network);
-        Wallet {
-            mnemonic,
-            hex_seed,
-            priv_sk: priv_sk.to_string(),
-            priv_vk: priv_vk.to_string(),
-            pub_sk,
-            pub_vk,
-            main_address: address,
-            sub_adresses: Vec::new(),
-            transactions: Vec::new(),
-            main_node: default_node,
-            processes: HashMap::new()
-        }
-    }
-
-    /// Opens a wallet with given mnemonic and network
-    pub fn open_wallet(mnemonic: Vec<String>, network: u8, default_node: DaemonNode) -> Wallet {
-        let hex_seed = keys::derive_hex_seed(mnemonic.clone());
-        let priv_keys = keys::derive_priv_keys(hex_seed.clone());
-        let priv_sk = &priv_keys[0];
-        let priv_vk = &priv_keys[1];
-        let pub_sk = keys::derive_pub_key(priv_sk.to_string());
-        let pub_vk = keys::derive_pub_key(priv_vk.to_string());
-        let address = keys::derive_address(pub_sk.clone(), pub_vk.clone(), network);
-        Wallet {
-            mnemonic,
-            hex_seed,
-            priv_sk: priv_sk.to_string(),
-            priv_vk: priv_vk.to_string(),
-            pub_sk,
-            pub_vk,
-            main_address: address,
-            sub_adresses: Vec::new(),
-            transactions: Vec::new(),
-            main_node: default_node,
-            processes: HashMap::new()
-        }
-    }
-}
\ No newline at end of file
+    /// Creates a wallet from its private spend key, the view key being derived from it
+    pub fn from_keys(network: Network, private_spend_key: PrivateKey) -> Wallet {
+        let private_view_key = private_spend_key.derive_view_key();
+        Wallet::with_keys(network, private_spend_key.public_key(), private_view_key, Some(private_spend_key))
+    }
+
+    /// Creates a view-only wallet from its public spend key and private view key
+    pub fn view_only(network: Network, public_spend_key: PublicKey, private_view_key: PrivateKey) -> Wallet {
+        Wallet::with_keys(network, public_spend_key, private_view_key, None)
+    }
+
+    /// Creates a wallet from an original (25 words) or MyMonero (13 words) mnemonic seed
+    ///
+    /// Returns the wallet if succesfull
+    /// Returns an error message if the mnemonic is invalid
+    pub fn from_mnemonic(network: Network, mnemonic: &[String]) -> Result<Wallet, String> {
+        validate_mnemonic(mnemonic).map_err(|e| e.to_string())?;
+        let hex_seed = derive_hex_seed(mnemonic.to_vec());
+        let keys = derive_priv_keys(hex_seed.to_string());
+        let private_spend_key = PrivateKey::from_hex(&keys[0])?;
+        let private_view_key = PrivateKey::from_hex(&keys[1])?;
+        Ok(Wallet::with_keys(network, private_spend_key.public_key(), private_view_key, Some(private_spend_key)))
+    }
+
+    /// Creates a wallet from the keys of a `.keys` file, restored from its refresh height
+    pub fn from_keys_file(keys_file: &KeysFile) -> Wallet {
+        Wallet::with_keys(keys_file.network, keys_file.public_spend_key, keys_file.private_view_key.clone(), keys_file.private_spend_key.clone())
+            .with_restore_height(keys_file.refresh_height)
+    }
+
+    fn with_keys(network: Network, public_spend_key: PublicKey, private_view_key: PrivateKey, private_spend_key: Option<PrivateKey>) -> Wallet {
+        let view_pair = ViewPair::new(public_spend_key, private_view_key);
+        let address = derive_subaddress(&view_pair, network, 0, 0);
+        let mut wallet = Wallet {
+            network,
+            private_spend_key,
+            table: SubaddressTable::new(&view_pair, 0, 0),
+            lookahead: DEFAULT_LOOKAHEAD,
+            table_extents: Vec::new(),
+            snapshot: WalletSnapshot::new(network, &address, 0),
+            key_images: HashMap::new(),
+            recent: VecDeque::new(),
+            openalias: None,
+            spending_policy: SpendingPolicy::new(),
+            pending_spends: HashSet::new(),
+        };
+        wallet.extend_table(0, 0);
+        wallet
+    }
+
+    /// Sets the height the wallet is synced to, outputs received before it are not found
+    pub fn with_restore_height(mut self, restore_height: u64) -> Wallet {
+        self.snapshot.synced_height = restore_height;
+        self
+    }
+
+    /// Scans for `accounts` accounts and `indexes` subaddresses per account past the last ones that received an
+    /// output, [`DEFAULT_LOOKAHEAD`](constant.DEFAULT_LOOKAHEAD.html) by default
+    ///
+    /// Like the official wallets, the lookahead moves on whenever a subaddress near its end receives an output.
+    pub fn with_lookahead(mut self, accounts: u32, indexes: u32) -> Wallet {
+        self.lookahead = (accounts.max(1), indexes.max(1));
+        self.extend_table(0, 0);
+        self
+    }
+
+    /// Lets [`send`](#method.send) and [`tx_builder`](#method.tx_builder) pay OpenAlias domains, see
+    /// [`TxBuilder::with_openalias_resolver`](struct.TxBuilder.html#method.with_openalias_resolver)
+    pub fn with_openalias_resolver<R, C>(mut self, resolver: R, confirm: C) -> Wallet
+    where
+        R: OpenAliasResolver + Send + Sync + 'static,
+        C: Fn(&ResolvedDestination) -> bool + Send + Sync + 'static,
+    {
+        self.openalias = Some(OpenAliasLookup::new(resolver, confirm));
+        self
+    }
+
+    /// Enforces the given spending limits and approval hooks on the transactions signed by [`send`](#method.send) and
+    /// [`tx_builder`](#method.tx_builder) builders, the daily limit counting the transfers of the wallet history
+    pub fn with_spending_policy(mut self, spending_policy: SpendingPolicy) -> Wallet {
+        self.spending_policy = spending_policy;
+        self
+    }
+
+    // Adds the subaddresses of the lookahead past the given one to the table
+    fn extend_table(&mut self, account: u32, index: u32) {
+        let (accounts, indexes) = self.lookahead;
+        let last_account = account.saturating_add(accounts - 1);
+        while self.table_extents.len() <= last_account as usize {
+            self.table_extents.push(0);
+        }
+        for (current, extent) in self.table_extents.iter_mut().enumerate() {
+            let current = current as u32;
+            let wanted = if current == account { index.saturating_add(indexes) } else { indexes };
+            while *extent < wanted {
+                self.table.insert(current, *extent);
+                *extent += 1;
+            }
+        }
+    }
+
+    /// Returns the network of the wallet
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns the public spend key and private view key of the wallet
+    pub fn view_pair(&self) -> &ViewPair {
+        self.table.view_pair()
+    }
+
+    /// Checks if the wallet has no private spend key
+    pub fn is_view_only(&self) -> bool {
+        self.private_spend_key.is_none()
+    }
+
+    /// Returns the primary address of the wallet
+    pub fn address(&self) -> &str {
+        &self.snapshot.primary_address
+    }
+
+    /// Returns the address of the given subaddress, `(0, 0)` being the primary address
+    pub fn subaddress(&self, account: u32, index: u32) -> String {
+        derive_subaddress(self.view_pair(), self.network, account, index)
+    }
+
+    /// Returns the height of the next block to scan
+    pub fn synced_height(&self) -> u64 {
+        self.snapshot.synced_height
+    }
+
+    /// Returns the owned outputs, spent ones included, in the order they were received
+    pub fn outputs(&self) -> &[SnapshotOutput] {
+        &self.snapshot.outputs
+    }
+
+    /// Returns the key image of the owned output with the given one-time key, if known
+    pub fn key_image(&self, output_key: &OneTimeKey) -> Option<KeyImage> {
+        self.key_images.iter().find(|(_, key)| *key == output_key).map(|(key_image, _)| *key_image)
+    }
+
+    /// Returns a builder spending the outputs of the wallet, with the known key images of the outputs and the OpenAlias
+    /// resolver of the wallet, its spending policy and history
+    ///
+    /// Its destination policy refuses addresses of other networks, resolved OpenAlias addresses included. Replacing it
+    /// with [`TxBuilder::with_policy`](struct.TxBuilder.html#method.with_policy) should keep
+    /// [`DestinationPolicy::with_network`](../utils/struct.DestinationPolicy.html#method.with_network).
+    ///
+    /// Outputs spent by transactions broadcast with [`send`](#method.send) are left out until the transaction is scanned.
+    pub fn tx_builder(&self) -> TxBuilder {
+        let key_images: Vec<(OneTimeKey, KeyImage)> = self.key_images.iter().map(|(key_image, output_key)| (*output_key, *key_image)).collect();
+        let outputs = self.snapshot.outputs.iter().filter(|output| !self.pending_spends.contains(&output.output_key)).cloned().collect();
+        TxBuilder::new(outputs)
+            .with_key_images(&key_images)
+            .with_openalias_lookup(self.openalias.clone())
+            .with_policy(DestinationPolicy::new().with_network(self.network))
+            .with_spending_policy(self.spending_policy.clone())
+            .with_spending_history(&self.snapshot.transfers, unix_now())
+    }
+
+    /// Returns the one-time keys of the outputs spent by transactions broadcast with [`send`](#method.send) that were
+    /// not scanned yet
+    pub fn pending_spends(&self) -> Vec<OneTimeKey> {
+        self.pending_spends.iter().copied().collect()
+    }
+
+    /// Makes the outputs spent by transactions broadcast with [`send`](#method.send) spendable again, for transactions
+    /// that left the pool without being mined
+    pub fn clear_pending_spends(&mut self) {
+        self.pending_spends.clear();
+    }
+
+    /// Pays an address, an OpenAlias domain or a `monero:` payment request URI from account 0 and broadcasts the
+    /// transaction, all in one call
+    ///
+    /// URIs are decoded with [`parse_uri`](../utils/fn.parse_uri.html), their amount is used when `amount` is None and
+    /// must match it otherwise. OpenAlias domains need [`with_openalias_resolver`](#method.with_openalias_resolver).
+    /// The transaction is then built by [`tx_builder`](#method.tx_builder) with its defaults: the fee of the given
+    /// priority as estimated by [`estimate_fee`](../blocks/fn.estimate_fee.html), rings of 16 members picked by
+    /// [`GammaDecoys`](struct.GammaDecoys.html) and change back to the spent subaddress. It is signed with the spend
+    /// key of the wallet and broadcast with [`send_raw_transaction`](../blocks/fn.send_raw_transaction.html). The spent
+    /// outputs are then left out of later transactions, see [`pending_spends`](#method.pending_spends), and marked
+    /// spent once the transaction is scanned.
+    ///
+    /// The transaction must pass the spending policy given to [`with_spending_policy`](#method.with_spending_policy)
+    /// before being signed. Once broadcast, it is added to the history of the snapshot, in pool until scanned, so the
+    /// daily limit counts it.
+    ///
+    /// Example:
+    /// ```no_run
+    /// use libmonero::blocks::{DaemonNode, FeePriority};
+    /// use libmonero::keys::PrivateKey;
+    /// use libmonero::utils::Network;
+    /// use libmonero::wallet::Wallet;
+    ///
+    /// let node = DaemonNode::cake_wallet_default();
+    /// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+    /// let mut wallet = Wallet::from_keys(Network::Mainnet, private_spend_key).with_restore_height(3000000);
+    /// wallet.sync(&node, None, |_| {}).unwrap();
+    /// let uri = "monero:42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB?tx_amount=0.25";
+    /// let sent = wallet.send(uri, None, FeePriority::Normal, &node).unwrap();
+    /// println!("Sent {} with a fee of {}", sent.tx_hash, sent.fee);
+    /// ```
+    ///
+    /// Returns the broadcast transaction if succesfull
+    /// Returns an error message if the wallet is view-only, the destination or amount is invalid or of another network,
+    /// the funds do not cover the payment, the spending policy refuses it, the node fails or rejects the transaction
+    pub fn send(&mut self, address_or_uri: &str, amount: Option<u64>, priority: FeePriority, node: &DaemonNode) -> Result<SignedTx, String> {
+        let private_spend_key = self.private_spend_key.clone().ok_or("View-only wallets can not send")?;
+        let (destination, amount) = match address_or_uri.starts_with("monero:") {
+            true => {
+                let request = parse_uri(address_or_uri)?;
+                if request.info.network != self.network {
+                    return Err(format!("Payment request is for {:?}, the wallet is on {:?}", request.info.network, self.network));
+                }
+                let amount = match (amount, request.amount) {
+                    (Some(amount), Some(requested)) if amount != requested => {
+                        return Err(format!("Amount {} differs from the requested amount {}", amount, requested));
+                    }
+                    (amount, requested) => amount.or(requested),
+                };
+                (request.address, amount)
+            }
+            false => (address_or_uri.to_string(), amount),
+        };
+        let amount = amount.ok_or("No amount given to send")?;
+
+        let builder = self.tx_builder().with_destination(&destination, amount).with_priority(priority);
+        let preview = builder.dry_run(node.clone())?;
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key), self.view_pair().view.clone())?;
+        self.broadcast(&builder, &preview, &signer, node)
+    }
+
+    /// Moves the unmixable pre-RingCT outputs of account 0 to the primary address and broadcasts the transactions,
+    /// monero-wallet-cli's `sweep_unmixable`
+    ///
+    /// The transactions are built by [`TxBuilder::sweep_unmixable`](struct.TxBuilder.html#method.sweep_unmixable) with
+    /// the fee of the given priority, then signed and broadcast like [`send`](#method.send) does. Once swept, the
+    /// outputs are RingCT outputs that can be spent with full rings.
+    ///
+    /// Returns the broadcast transactions if succesfull
+    /// Returns an error message if the wallet is view-only, has no unlocked unmixable output, the spending policy
+    /// refuses a transaction, the node fails or rejects a transaction, the ones broadcast before being kept
+    pub fn sweep_unmixable(&mut self, priority: FeePriority, node: &DaemonNode) -> Result<Vec<SignedTx>, String> {
+        let private_spend_key = self.private_spend_key.clone().ok_or("View-only wallets can not sweep")?;
+        let builder = self.tx_builder().with_priority(priority);
+        let previews = builder.sweep_unmixable(self.address(), node.clone())?;
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key), self.view_pair().view.clone())?;
+        previews.iter().map(|preview| self.broadcast(&builder, preview, &signer, node)).collect()
+    }
+
+    // Signs a preview with the current history for the spending policy, broadcasts it and records it as pending
+    fn broadcast(&mut self, builder: &TxBuilder, preview: &TxPreview, signer: &Signer, node: &DaemonNode) -> Result<SignedTx, String> {
+        let signed = builder.clone().with_spending_history(&self.snapshot.transfers, unix_now()).sign(preview, signer)?;
+        let result = send_raw_transaction(signed.to_hex(), false, node.clone())?;
+        if !result.accepted {
+            return Err(format!("Transaction was rejected: {}", result.rejection_reasons().join(", ")));
+        }
+        self.pending_spends.extend(preview.inputs.iter().map(|input| input.output.output_key));
+        self.snapshot.transfers.push(SnapshotTransfer {
+            tx_hash: signed.tx_hash,
+            height: None,
+            timestamp: unix_now(),
+            account: 0,
+            incoming: 0,
+            outgoing: preview.amount,
+            fee: preview.fee,
+            payment_id: decode_address(&preview.destinations[0].0).ok().and_then(|info| info.payment_id).map(hex::encode),
+        });
+        Ok(signed)
+    }
+
+    /// Returns the snapshot holding the outputs of the wallet, see [`WalletSnapshot`](struct.WalletSnapshot.html)
+    pub fn snapshot(&self) -> &WalletSnapshot {
+        &self.snapshot
+    }
+
+    /// Scans a transaction included at the given height: outputs it spends are marked spent, outputs it sends to the
+    /// wallet are recorded
+    ///
+    /// An output key received twice is recorded once, the later copies can never be spent. Returns the new outputs.
+    pub fn scan_tx(&mut self, tx_hash: TxHash, tx: &RawTx, height: u64) -> Vec<SnapshotOutput> {
+        self.scan_tx_into(tx_hash, tx, height, &mut Vec::new())
+    }
+
+    // Scans a transaction, adding the keys of the outputs it spends to `spent`
+    fn scan_tx_into(&mut self, tx_hash: TxHash, tx: &RawTx, height: u64, spent: &mut Vec<OneTimeKey>) -> Vec<SnapshotOutput> {
+        for transfer in self.snapshot.transfers.iter_mut().filter(|transfer| transfer.tx_hash == tx_hash && transfer.height.is_none()) {
+            transfer.height = Some(height);
+        }
+        for input in tx.vin.iter() {
+            let Some(output_key) = self.key_images.get(&input.key.k_image) else { continue };
+            if let Some(output) = self.snapshot.outputs.iter_mut().find(|output| output.output_key == *output_key && output.spent_height.is_none()) {
+                output.spent_height = Some(height);
+                self.pending_spends.remove(&output.output_key);
+                spent.push(output.output_key);
+            }
+        }
+        let mut received = Vec::new();
+        for owned in scan_tx_for_subaddresses(&self.table, tx, Some(height), &ScanFilter::default()) {
+            if self.snapshot.outputs.iter().any(|output| output.output_key == owned.output_key) {
+                continue;
+            }
+            if let Some(private_spend_key) = &self.private_spend_key {
+                let secret = owned.secret_key(&self.view_pair().view, private_spend_key);
+                self.key_images.insert(generate_key_image(&secret, &secret.public_key()), owned.output_key);
+            }
+            let output = SnapshotOutput::from_owned(&owned, tx_hash, height, owned.subaddress, tx.unlock_time);
+            self.extend_table(owned.subaddress.0, owned.subaddress.1);
+            self.snapshot.outputs.push(output.clone());
+            received.push(output);
+        }
+        self.snapshot.synced_height = self.snapshot.synced_height.max(height + 1);
+        received
+    }
+
+    /// Records the key images of owned outputs, as returned by
+    /// [`KeyImageImportSession::import`](struct.KeyImageImportSession.html#method.import), and returns how many
+    /// matched an output
+    ///
+    /// Transactions scanned before the import could not be matched to the outputs they spend, so the spent status of
+    /// the imported key images of unspent outputs is asked to the node, as monero-wallet-cli does. Outputs spent on
+    /// the chain are marked spent at the last scanned block, since the node does not tell which block spent them;
+    /// outputs spent in the pool are marked spent once their transaction is scanned.
+    ///
+    /// Returns the number of imported key images if succesfull
+    /// Returns an error message if the node fails, nothing is imported then
+    pub fn import_key_images(&mut self, key_images: &[(OneTimeKey, KeyImage)], node: &DaemonNode) -> Result<usize, String> {
+        let imported: Vec<(OneTimeKey, KeyImage)> =
+            key_images.iter().filter(|(output_key, _)| self.snapshot.outputs.iter().any(|output| output.output_key == *output_key)).copied().collect();
+        let unspent: Vec<(OneTimeKey, KeyImage)> = imported
+            .iter()
+            .filter(|(output_key, _)| self.snapshot.outputs.iter().any(|output| output.output_key == *output_key && output.spent_height.is_none()))
+            .copied()
+            .collect();
+        if !unspent.is_empty() {
+            let statuses = is_key_image_spent(&unspent.iter().map(|(_, key_image)| *key_image).collect::<Vec<KeyImage>>(), node.clone())?;
+            let spent_height = self.snapshot.synced_height.saturating_sub(1);
+            for ((output_key, _), status) in unspent.iter().zip(statuses) {
+                if status != KeyImageStatus::SpentInChain {
+                    continue;
+                }
+                for output in self.snapshot.outputs.iter_mut().filter(|output| output.output_key == *output_key && output.spent_height.is_none()) {
+                    output.spent_height = Some(spent_height);
+                }
+            }
+        }
+        for (output_key, key_image) in imported.iter() {
+            self.key_images.insert(*key_image, *output_key);
+        }
+        Ok(imported.len())
+    }
+
+    /// Exports the key images of all outputs in the format of monero-wallet-cli's `export_key_images`, for a view-only
+    /// copy of the wallet, see [`Signer::export_key_images`](struct.Signer.html#method.export_key_images)
+    ///
+    /// Returns the content of the file if succesfull
+    /// Returns an error message if the wallet is view-only
+    pub fn export_key_images(&self) -> Result<Vec<u8>, String> {
+        let private_spend_key = self.private_spend_key.clone().ok_or("View-only wallets have no key images to export")?;
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key), self.view_pair().view.clone())?;
+        signer.export_key_images(&self.snapshot.outputs, 0)
+    }
+
+    /// Imports a key image file exported by monero-wallet-cli or [`export_key_images`](#method.export_key_images) and
+    /// returns how many key images matched an output, see [`import_key_images`](fn.import_key_images.html)
+    ///
+    /// Both wallets must have received the outputs in the same order, as they do when synced from the same height.
+    /// Outputs already spent are found as in [`Wallet::import_key_images`](#method.import_key_images).
+    ///
+    /// Returns the number of imported key images if succesfull
+    /// Returns an error message if the file belongs to another wallet, does not match its outputs or the node fails
+    pub fn import_key_image_file(&mut self, data: &[u8], node: &DaemonNode) -> Result<usize, String> {
+        let key_images = import_key_images(data, &self.snapshot.outputs, self.view_pair())?;
+        self.import_key_images(&key_images, node)
+    }
+
+    /// Proves that the wallet spent the outputs spent by a transaction, fetching it and its rings from the node, see
+    /// [`Signer::get_spend_proof`](struct.Signer.html#method.get_spend_proof)
+    ///
+    /// Returns the proof, starting with `SpendProofV1`, if succesfull
+    /// Returns an error message if the wallet is view-only, did not make the transaction or the node fails
+    pub fn get_spend_proof(&self, tx_hash: &TxHash, message: &[u8], node: DaemonNode) -> Result<String, String> {
+        let private_spend_key = self.private_spend_key.clone().ok_or("View-only wallets can not make spend proofs")?;
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key), self.view_pair().view.clone())?;
+        let tx = get_transaction(*tx_hash, node.clone())?;
+        let rings = get_tx_rings(&tx, node)?;
+        signer.get_spend_proof(tx_hash, &tx, &rings, &self.snapshot.outputs, message)
+    }
+
+    /// Proves owning unspent outputs, monero-wallet-cli's `get_reserve_proof`, see
+    /// [`Signer::get_reserve_proof`](struct.Signer.html#method.get_reserve_proof)
+    ///
+    /// Without `account_minreserve` all unspent outputs are proven. With an account and an amount, only the fewest
+    /// largest outputs of the account covering the amount are, as the reference wallet does.
+    ///
+    /// Returns the proof, starting with `ReserveProofV2`, if succesfull
+    /// Returns an error message if the wallet is view-only, the amount is 0 or exceeds the account balance
+    pub fn get_reserve_proof(&self, account_minreserve: Option<(u32, u64)>, message: &[u8]) -> Result<String, String> {
+        let private_spend_key = self.private_spend_key.clone().ok_or("View-only wallets can not make reserve proofs")?;
+        let mut selected: Vec<SnapshotOutput> = self.snapshot.unspent(account_minreserve.map(|(account, _)| account)).cloned().collect();
+        if let Some((account, amount)) = account_minreserve {
+            if amount == 0 {
+                return Err("Minimum reserve amount must be positive".to_string());
+            }
+            if amount > self.account_balance(account) {
+                return Err(format!("Account {} holds less than the minimum reserve amount", account));
+            }
+            selected.sort_by_key(|output| std::cmp::Reverse(output.amount));
+            // Largest outputs first, dropping those the next largest output alone covers
+            while selected.len() >= 2 && selected[1].amount >= amount {
+                selected.remove(0);
+            }
+            // The account balance covers the amount, so the outputs do
+            let mut total = 0u64;
+            let mut count = 0;
+            while total < amount {
+                total += selected[count].amount;
+                count += 1;
+            }
+            selected.truncate(count);
+        }
+        if selected.is_empty() {
+            return Err("Wallet has no unspent outputs to prove".to_string());
+        }
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key), self.view_pair().view.clone())?;
+        signer.get_reserve_proof(&selected, message)
+    }
+
+    /// Signs a message with the spend key of the primary address or a subaddress, monero-wallet-cli's `sign`, see
+    /// [`Signer::sign_message`](struct.Signer.html#method.sign_message)
+    ///
+    /// Returns the signature, starting with `SigV2`, if succesfull
+    /// Returns an error message if the wallet is view-only
+    pub fn sign_message(&self, message: &[u8], account: u32, index: u32) -> Result<String, String> {
+        let private_spend_key = self.private_spend_key.clone().ok_or("View-only wallets can not sign messages")?;
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key), self.view_pair().view.clone())?;
+        signer.sign_message(message, account, index)
+    }
+
+    /// Exports all outputs in the format of monero-wallet-cli's `export_outputs`, for an offline copy of the wallet,
+    /// see [`export_outputs`](fn.export_outputs.html)
+    ///
+    /// Returns the content of the file if succesfull
+    /// Returns an error message if the node fails
+    pub fn export_outputs(&self, node: DaemonNode) -> Result<Vec<u8>, String> {
+        export_outputs(&self.snapshot.outputs, 0, self.view_pair(), node)
+    }
+
+    /// Imports an `outputs` file exported by the online copy of the wallet, see [`import_outputs`](fn.import_outputs.html),
+    /// and returns how many outputs it holds
+    ///
+    /// Outputs from the offset of the file are replaced by the imported ones, so that outputs keep the order of the
+    /// exporting wallet, and their key images are generated unless the wallet is view-only. This is how an offline
+    /// wallet learns its outputs without syncing.
+    ///
+    /// Returns the number of imported outputs if succesfull
+    /// Returns an error message if the file belongs to another wallet or starts after the known outputs
+    pub fn import_outputs(&mut self, data: &[u8]) -> Result<usize, String> {
+        let (offset, outputs) = import_outputs(data, self.view_pair())?;
+        if offset > self.snapshot.outputs.len() {
+            return Err(format!("Output export starts at output {}, the wallet knows {} outputs", offset, self.snapshot.outputs.len()));
+        }
+        self.snapshot.outputs.truncate(offset);
+        for output in outputs.iter() {
+            if let Some(private_spend_key) = &self.private_spend_key {
+                let owned = OwnedOutput {
+                    index: output.index,
+                    amount: output.amount,
+                    output_key: output.output_key,
+                    tx_public_key: output.tx_public_key,
+                    pre_rct: output.pre_rct,
+                    subaddress: output.subaddress,
+                };
+                let secret = owned.secret_key(&self.view_pair().view, private_spend_key);
+                self.key_images.insert(generate_key_image(&secret, &secret.public_key()), output.output_key);
+            }
+            self.extend_table(output.subaddress.0, output.subaddress.1);
+        }
+        self.snapshot.outputs.extend(outputs.iter().cloned());
+        let outputs_keys: Vec<OneTimeKey> = self.snapshot.outputs.iter().map(|output| output.output_key).collect();
+        self.key_images.retain(|_, output_key| outputs_keys.contains(output_key));
+        Ok(outputs.len())
+    }
+
+    /// Scans a batch of [`get_blocks_bin`](../blocks/fn.get_blocks_bin.html), skipping blocks already scanned
+    ///
+    /// A block replacing one of the last 100 scanned blocks rolls the wallet back to its height first: outputs
+    /// received from there are dropped and outputs spent from there are unspent again, until the new blocks are
+    /// scanned.
+    ///
+    /// Returns what the batch changed if succesfull
+    /// Returns an error message if a block or transaction can not be parsed, nothing is scanned then
+    pub fn scan_blocks(&mut self, batch: &BlocksBinResult) -> Result<SyncProgress, String> {
+        let mut blocks = Vec::with_capacity(batch.blocks.len());
+        for (offset, entry) in batch.blocks.iter().enumerate() {
+            let height = batch.start_height + offset as u64;
+            let block = ParsedBlock::from_blob(&entry.block)?;
+            if entry.txs.len() != block.tx_hashes.len() {
+                return Err(format!("Block {} has {} transactions, expected {}", height, entry.txs.len(), block.tx_hashes.len()));
+            }
+            let mut txs = vec![(block.miner_tx.hash()?, block.miner_tx.to_raw_tx())];
+            for (blob, tx_hash) in entry.txs.iter().zip(block.tx_hashes.iter()) {
+                txs.push((*tx_hash, parse_transaction_blob(blob)?.to_raw_tx()));
+            }
+            blocks.push((height, block.hash(), txs));
+        }
+
+        let mut progress = SyncProgress { chain_height: batch.current_height, ..SyncProgress::default() };
+        for (height, hash, txs) in blocks {
+            match self.recent.iter().find(|(known, _)| *known == height) {
+                Some((_, known)) if *known == hash => continue,
+                Some(_) => {
+                    self.rollback(height);
+                    progress.received.retain(|output| output.height < height);
+                    progress.reorg = Some(progress.reorg.map_or(height, |reorg| reorg.min(height)));
+                }
+                None if height < self.snapshot.synced_height => continue,
+                None => {}
+            }
+            for (tx_hash, tx) in txs.iter() {
+                let received = self.scan_tx_into(*tx_hash, tx, height, &mut progress.spent);
+                progress.received.extend(received);
+            }
+            self.recent.push_back((height, hash));
+            if self.recent.len() > RECENT_BLOCKS {
+                self.recent.pop_front();
+            }
+            self.snapshot.synced_height = height + 1;
+        }
+        progress.synced_height = self.snapshot.synced_height;
+        Ok(progress)
+    }
+
+    /// Syncs the wallet with the chain of the given node up to its top, through the binary `get_blocks.bin` endpoint
+    ///
+    /// Scanning starts at `from_height`, or resumes at [`synced_height`](#method.synced_height) if `None`. Outputs
+    /// already known are not recorded twice when rescanning. `on_progress` is called after every batch of blocks,
+    /// reorganizations are handled as in [`scan_blocks`](#method.scan_blocks).
+    ///
+    /// Returns the synced height if succesfull
+    /// Returns an error message if the node fails, the batches scanned before are kept
+    pub fn sync<F>(&mut self, node: &DaemonNode, from_height: Option<u64>, mut on_progress: F) -> Result<u64, String>
+    where
+        F: FnMut(&SyncProgress),
+    {
+        if let Some(from_height) = from_height {
+            self.recent.retain(|(height, _)| *height < from_height);
+            self.snapshot.synced_height = from_height;
+        }
+        loop {
+            let mut block_ids: Vec<BlockHash> = self.recent.iter().rev().map(|(_, hash)| *hash).collect();
+            block_ids.push(genesis_hash(self.network));
+            let start_height = self.recent.front().map_or(self.snapshot.synced_height, |(height, _)| *height);
+            let batch = get_blocks_bin(&block_ids, start_height, true, node.clone())?;
+            let reached = self.snapshot.synced_height;
+            let progress = self.scan_blocks(&batch)?;
+            on_progress(&progress);
+            if progress.synced_height >= progress.chain_height || (progress.synced_height == reached && progress.reorg.is_none()) {
+                return Ok(progress.synced_height);
+            }
+        }
+    }
+
+    // Forgets what was scanned from the given height, key images are kept for outputs received again
+    fn rollback(&mut self, fork_height: u64) {
+        self.recent.retain(|(height, _)| *height < fork_height);
+        self.snapshot.outputs.retain(|output| output.height < fork_height);
+        for output in self.snapshot.outputs.iter_mut() {
+            if output.spent_height.is_some_and(|height| height >= fork_height) {
+                output.spent_height = None;
+            }
+        }
+        for transfer in self.snapshot.transfers.iter_mut() {
+            if transfer.height.is_some_and(|height| height >= fork_height) {
+                transfer.height = None;
+            }
+        }
+        self.snapshot.synced_height = fork_height;
+    }
+
+    /// Returns the sum of the unspent outputs
+    pub fn balance(&self) -> u64 {
+        self.snapshot.balance(None)
+    }
+
+    /// Returns the sum of the unspent outputs of the given account
+    pub fn account_balance(&self, account: u32) -> u64 {
+        self.snapshot.balance(Some(account))
+    }
+
+    /// Returns the sum of the unspent outputs that can be spent now, at the synced height
+    pub fn unlocked_balance(&self) -> u64 {
+        self.snapshot.unlocked_balance_at(None, self.snapshot.synced_height, unix_now())
+    }
+
+    /// Returns the balance of every subaddress holding unspent outputs, keyed by `(account, index)`
+    pub fn subaddress_balances(&self) -> BTreeMap<(u32, u32), u64> {
+        let mut balances = BTreeMap::new();
+        for output in self.snapshot.unspent(None) {
+            *balances.entry(output.subaddress).or_insert(0) += output.amount;
+        }
+        balances
+    }
+}
+
+// Current Unix time, 0 if the clock is before 1970
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
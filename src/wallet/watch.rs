@@ -0,0 +1,277 @@
+/*
+ * This file is part of Monero Builders' library libmonero
+ *
+ * Copyright (c) 2023-2024, Monero Builders (monero.builders)
+ * All Rights Reserved
+ * The code is distributed under MIT license, see LICENSE file for details.
+ * Generated by Monero Builders
+ *
+ */
+
+use crate::blocks::{get_blocks_bin, get_transaction_pool, parse_transaction_blob, BlockHash, BlocksBinResult, DaemonNode, MonitorHandle, ParsedBlock, PoolTx, RawTx, TxHash};
+use crate::keys::scan::scan_tx_with_lookup;
+use crate::keys::{OneTimeKey, PrivateKey, PublicKey};
+use crate::utils::{decode_address, AddressType, Network};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+// Number of scanned blocks remembered to detect reorganizations
+const RECENT_BLOCKS: usize = 100;
+
+/// WatchedOutput is an output received by a watched address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchedOutput {
+    /// Watched address the output was sent to, as given to [`with_address`](struct.AddressWatcher.html#method.with_address)
+    pub address: String,
+    pub tx_hash: TxHash,
+    /// Height of the block including the transaction, `None` while it is in the pool
+    pub height: Option<u64>,
+    /// Index of the output in the transaction
+    pub index: u64,
+    /// Decoded amount in piconeros
+    pub amount: u64,
+    pub output_key: OneTimeKey,
+}
+
+/// WatchEvent is streamed by an [`AddressWatcher`](struct.AddressWatcher.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// A watched address received an output, in a block or in the pool
+    Received(WatchedOutput),
+    /// Blocks from this height were replaced, outputs received in them are void unless received again
+    Reorg { fork_height: u64 },
+    /// Daemon could not be queried, scanning resumes where it stopped at the next poll
+    Unreachable { error: String },
+}
+
+/// AddressWatcher watches a fixed set of addresses and subaddresses for incoming outputs, without wallet state
+///
+/// Each address comes with the private view key of its wallet, no spend key is involved and nothing is stored
+/// besides the height reached and the hashes of the last 100 blocks. Outputs are streamed once when seen in the
+/// pool and once more when mined, spent outputs are not tracked. Addresses of several wallets can be watched at once.
+///
+/// Example:
+/// ```
+/// use libmonero::keys::{derive_subaddress, PrivateKey, ViewPair};
+/// use libmonero::utils::Network;
+/// use libmonero::wallet::AddressWatcher;
+///
+/// let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+/// let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+/// let donations = derive_subaddress(&view_pair, Network::Mainnet, 0, 1);
+/// let watcher = AddressWatcher::new(Network::Mainnet, 3000000)
+///     .with_address(&view_pair.address(Network::Mainnet), &view_pair.view)
+///     .unwrap()
+///     .with_address(&donations, &view_pair.view)
+///     .unwrap();
+/// assert_eq!(watcher.addresses().len(), 2);
+/// assert_eq!(watcher.height(), 3000000);
+///
+/// // The view key must belong to the address
+/// let other = PrivateKey::from_hex("6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e").unwrap();
+/// assert!(watcher.with_address(&donations, &other).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct AddressWatcher {
+    network: Network,
+    addresses: Vec<String>,
+    // Watched public spend keys grouped by private view key, mapped to their position in `addresses`
+    groups: Vec<(PrivateKey, HashMap<[u8; 32], usize>)>,
+    next_height: u64,
+    recent: VecDeque<(u64, BlockHash)>,
+    pool_seen: HashSet<TxHash>,
+    pool: bool,
+}
+
+impl AddressWatcher {
+    /// Creates a watcher of the given network without addresses, scanning from `start_height`
+    pub fn new(network: Network, start_height: u64) -> AddressWatcher {
+        AddressWatcher {
+            network,
+            addresses: Vec::new(),
+            groups: Vec::new(),
+            next_height: start_height,
+            recent: VecDeque::new(),
+            pool_seen: HashSet::new(),
+            pool: true,
+        }
+    }
+
+    /// Watches a standard, integrated or subaddress with the private view key of its wallet
+    ///
+    /// Returns the watcher if succesfull
+    /// Returns an error message if the address is invalid, of another network or does not match the view key
+    pub fn with_address(mut self, address: &str, private_view_key: &PrivateKey) -> Result<AddressWatcher, String> {
+        let info = decode_address(address)?;
+        if info.network != self.network {
+            return Err(format!("Address {} is not a {:?} address", address, self.network));
+        }
+        // Subaddress view keys are a * D, other addresses have A = a * G
+        let view_key = match info.address_type {
+            AddressType::Subaddress => PublicKey::from_point(info.public_spend_key.to_point() * private_view_key.to_scalar()),
+            AddressType::Standard | AddressType::Integrated => private_view_key.public_key(),
+        };
+        if view_key != info.public_view_key {
+            return Err(format!("Private view key does not belong to address {}", address));
+        }
+        let position = self.addresses.len();
+        self.addresses.push(address.to_string());
+        match self.groups.iter_mut().find(|(view, _)| view == private_view_key) {
+            Some((_, spend_keys)) => {
+                spend_keys.insert(*info.public_spend_key.as_bytes(), position);
+            }
+            None => self.groups.push((private_view_key.clone(), HashMap::from([(*info.public_spend_key.as_bytes(), position)]))),
+        }
+        Ok(self)
+    }
+
+    /// Sets whether pool transactions are scanned by [`poll`](#method.poll), true by default
+    pub fn with_pool(mut self, pool: bool) -> AddressWatcher {
+        self.pool = pool;
+        self
+    }
+
+    /// Returns the watched addresses
+    pub fn addresses(&self) -> &[String] {
+        &self.addresses
+    }
+
+    /// Returns the height of the next block to scan
+    pub fn height(&self) -> u64 {
+        self.next_height
+    }
+
+    /// Scans a transaction included at the given height, `None` for the pool, for outputs of the watched addresses
+    pub fn scan_tx(&self, tx_hash: TxHash, tx: &RawTx, height: Option<u64>) -> Vec<WatchedOutput> {
+        let mut found = Vec::new();
+        for (view_key, spend_keys) in self.groups.iter() {
+            let lookup = |spend: &PublicKey| spend_keys.get(spend.as_bytes()).map(|position| (*position as u32, 0));
+            found.extend(scan_tx_with_lookup(view_key, &lookup, tx).into_iter().map(|output| WatchedOutput {
+                address: self.addresses[output.subaddress.0 as usize].clone(),
+                tx_hash,
+                height,
+                index: output.index,
+                amount: output.amount,
+                output_key: output.output_key,
+            }));
+        }
+        found.sort_by_key(|output| output.index);
+        found
+    }
+
+    /// Scans a batch of [`get_blocks_bin`](../blocks/fn.get_blocks_bin.html), skipping blocks already scanned
+    ///
+    /// A block replacing one already scanned raises a `Reorg` event before the outputs of the new block.
+    ///
+    /// Returns the events of the batch if succesfull
+    /// Returns an error message if a block or transaction can not be parsed, nothing is scanned then
+    pub fn scan_blocks(&mut self, batch: &BlocksBinResult) -> Result<Vec<WatchEvent>, String> {
+        let mut blocks = Vec::with_capacity(batch.blocks.len());
+        for (offset, entry) in batch.blocks.iter().enumerate() {
+            let height = batch.start_height + offset as u64;
+            let block = ParsedBlock::from_blob(&entry.block)?;
+            if entry.txs.len() != block.tx_hashes.len() {
+                return Err(format!("Block {} has {} transactions, expected {}", height, entry.txs.len(), block.tx_hashes.len()));
+            }
+            let mut txs = vec![(block.miner_tx.hash()?, block.miner_tx.to_raw_tx())];
+            for (blob, tx_hash) in entry.txs.iter().zip(block.tx_hashes.iter()) {
+                txs.push((*tx_hash, parse_transaction_blob(blob)?.to_raw_tx()));
+            }
+            blocks.push((height, block.hash(), txs));
+        }
+
+        let mut events = Vec::new();
+        for (height, hash, txs) in blocks {
+            match self.recent.iter().find(|(known, _)| *known == height) {
+                Some((_, known)) if *known == hash => continue,
+                Some(_) => {
+                    self.recent.retain(|(known, _)| *known < height);
+                    events.push(WatchEvent::Reorg { fork_height: height });
+                }
+                None if height < self.next_height => continue,
+                None => {}
+            }
+            for (tx_hash, tx) in txs.iter() {
+                events.extend(self.scan_tx(*tx_hash, tx, Some(height)).into_iter().map(WatchEvent::Received));
+            }
+            self.recent.push_back((height, hash));
+            if self.recent.len() > RECENT_BLOCKS {
+                self.recent.pop_front();
+            }
+            self.next_height = height + 1;
+        }
+        Ok(events)
+    }
+
+    /// Scans the pool, as returned by [`get_transaction_pool`](../blocks/fn.get_transaction_pool.html), for
+    /// transactions not seen in the pool before
+    pub fn scan_pool(&mut self, pool: &[PoolTx]) -> Vec<WatchEvent> {
+        self.pool_seen.retain(|hash| pool.iter().any(|tx| tx.id_hash == *hash));
+        let mut events = Vec::new();
+        for tx in pool.iter() {
+            if self.pool_seen.insert(tx.id_hash) {
+                events.extend(self.scan_tx(tx.id_hash, &tx.tx, None).into_iter().map(WatchEvent::Received));
+            }
+        }
+        events
+    }
+
+    /// Scans the blocks of the given node up to its top, then its pool, and returns the events
+    ///
+    /// Returns the events if succesfull, including those found before a failure of the node
+    /// Returns an error message if the node fails before any event is found
+    pub fn poll(&mut self, node: &DaemonNode) -> Result<Vec<WatchEvent>, String> {
+        let mut events = Vec::new();
+        match self.poll_into(node, &mut events) {
+            Err(error) if events.is_empty() => Err(error),
+            _ => Ok(events),
+        }
+    }
+
+    fn poll_into(&mut self, node: &DaemonNode, events: &mut Vec<WatchEvent>) -> Result<(), String> {
+        loop {
+            let mut block_ids: Vec<BlockHash> = self.recent.iter().rev().map(|(_, hash)| *hash).collect();
+            block_ids.push(genesis_hash(self.network));
+            let start_height = self.recent.front().map_or(self.next_height, |(height, _)| *height);
+            let batch = get_blocks_bin(&block_ids, start_height, true, node.clone())?;
+            let reached = self.next_height;
+            events.extend(self.scan_blocks(&batch)?);
+            if self.next_height >= batch.current_height || self.next_height == reached {
+                break;
+            }
+        }
+        if self.pool {
+            events.extend(self.scan_pool(&get_transaction_pool(node.clone())?));
+        }
+        Ok(())
+    }
+
+    /// Polls the given node every `interval` on a background thread and calls `on_events` whenever events are raised
+    ///
+    /// Polling goes on until [`MonitorHandle::stop`](../blocks/struct.MonitorHandle.html#method.stop) is called, a
+    /// failing poll raises an `Unreachable` event
+    pub fn run<F>(mut self, node: DaemonNode, interval: Duration, mut on_events: F) -> MonitorHandle
+    where
+        F: FnMut(&[WatchEvent]) + Send + 'static,
+    {
+        MonitorHandle::spawn(interval, move || {
+            let events = match self.poll(&node) {
+                Ok(events) => events,
+                Err(error) => vec![WatchEvent::Unreachable { error }],
+            };
+            if !events.is_empty() {
+                on_events(&events);
+            }
+        })
+    }
+}
+
+// Hash of the genesis block, ending the block IDs sent to get_blocks_bin
+pub(crate) fn genesis_hash(network: Network) -> BlockHash {
+    let hash = match network {
+        Network::Mainnet => "418015bb9ae982a1975da7d79277c2705727a56894ba0fb246adaabb1f4632e3",
+        Network::Testnet => "48ca7cd3c8de5b6a4d53d2861fbdaedca141553559f9be9520068053cda8430b",
+        Network::Stagenet => "76ee3cc98646292206cd3e86f74d88b4dcc1d937088645e9b0cbca84b7ce74eb",
+    };
+    BlockHash::from_hex(hash).unwrap()
+}
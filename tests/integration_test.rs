@@ -2,30 +2,83 @@
 mod tests {
     use libmonero::keys::{derive_address, derive_hex_seed, derive_priv_keys, derive_pub_key, generate_seed};
     use libmonero::crypt::cryptonight::cn_slow_hash_v0;
+    use libmonero::utils::write_varint;
+
+    // Hashes returned by the fake daemons below
+    const HASH_AA: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const HASH_BB: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    const HASH_CC: &str = "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+    const FAKE_HEADER_HASHES: &str = r#""hash": "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", "prev_hash": "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb", "miner_tx_hash": "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc""#;
 
     #[test]
     fn seed_generation() {
         let seed = generate_seed("en", "original");
-        assert_ne!(seed, vec!["tissue", "raking", "haunted", "huts", "afraid", "volcano", "howls", "liar", "egotistic", "befit", "rounded", "older", "bluntly", "imbalance", "pivot", "exotic", "tuxedo", "amaze", "mostly", "lukewarm", "macro", "vocal", "hounded", "biplane", "rounded"].iter().map(|&s| s.to_string()).collect::<Vec<String>>());
+        assert_ne!(*seed, vec!["tissue", "raking", "haunted", "huts", "afraid", "volcano", "howls", "liar", "egotistic", "befit", "rounded", "older", "bluntly", "imbalance", "pivot", "exotic", "tuxedo", "amaze", "mostly", "lukewarm", "macro", "vocal", "hounded", "biplane", "rounded"].iter().map(|&s| s.to_string()).collect::<Vec<String>>());
     }
     
     #[test]
     fn key_derivation() {
+        use libmonero::keys::{PrivateKey, PublicKey, ViewPair};
+        use libmonero::utils::{decode_address, Network};
+
         // five saved himself oust taunts pebbles fibula organs koala copy dying vein damp dauntless code gags copy roster geek toolbox joyous apart unlikely warped taunts
         let mnemonic = ["five", "saved", "himself", "oust", "taunts", "pebbles", "fibula", "organs", "koala", "copy", "dying", "vein", "damp", "dauntless", "code", "gags", "copy", "roster", "geek", "toolbox", "joyous", "apart", "unlikely", "warped", "taunts"].to_vec().iter().map(|s| s.to_string()).collect::<Vec<String>>();
         let hex_seed = derive_hex_seed(mnemonic);
-        assert_eq!(hex_seed.clone(), "6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e".to_string());
-        let priv_keys = derive_priv_keys(hex_seed);
+        assert_eq!(*hex_seed, "6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e".to_string());
+        let priv_keys = derive_priv_keys(hex_seed.to_string());
         let priv_sk = &priv_keys[0];
         let priv_vk = &priv_keys[1];
         assert_eq!(priv_sk, &"6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e".to_string());
         assert_eq!(priv_vk, &"490447bf98677377923b4da400fa2b7e6dff6dff0ca24f7ae533a8207fd27c00".to_string());
-        let pub_sk = derive_pub_key(priv_sk.clone());
+        let pub_sk = derive_pub_key(priv_sk.clone()).unwrap();
         assert_eq!(pub_sk.clone(), "03970285bf0724d75e0f50bca9a9ea0e8db5091b69403dc944465f8936bde787".to_string());
-        let pub_vk = derive_pub_key(priv_keys[1].clone());
+        let pub_vk = derive_pub_key(priv_keys[1].clone()).unwrap();
         assert_eq!(pub_vk.clone(), "528a736a5079dc9536edb5b6fa0a5209ce820b9734fc0785024670b3d3ba4c69".to_string());
-        let addr = derive_address(pub_sk, pub_vk, 0);
+        let addr = derive_address(pub_sk.clone(), pub_vk.clone(), 0).unwrap();
         assert_eq!(addr, "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT".to_string());
+
+        // Every network is supported, by the typed API as well
+        let view_pair = ViewPair::new(PublicKey::from_hex(&pub_sk).unwrap(), PrivateKey::from_hex(priv_vk).unwrap());
+        for (byte, network) in [(0, Network::Mainnet), (1, Network::Testnet), (2, Network::Stagenet)] {
+            let address = view_pair.address(network);
+            assert_eq!(address, derive_address(pub_sk.clone(), pub_vk.clone(), byte).unwrap());
+            assert_eq!(decode_address(&address).unwrap().network, network);
+        }
+        assert!(derive_address(pub_sk, pub_vk, 3).is_err());
+
+        // Keys above the group order are reduced rather than rejected, bad hex is an error rather than a panic
+        let reduced = PrivateKey::from_bytes_mod_order([0xff; 32]);
+        assert_eq!(derive_pub_key("ff".repeat(32)).unwrap(), derive_pub_key(reduced.to_hex().to_string()).unwrap());
+        assert!(derive_pub_key("zz".repeat(32)).is_err());
+        assert!(derive_address("00".repeat(31), "00".repeat(32), 0).is_err());
+    }
+
+    #[test]
+    fn seed_passphrase() {
+        use curve25519_dalek::Scalar;
+        use libmonero::keys::{derive_hex_seed, derive_hex_seed_with_passphrase};
+
+        let address = |hex_seed: &str| {
+            let keys = derive_priv_keys(hex_seed.to_string());
+            derive_address(derive_pub_key(keys[0].clone()).unwrap(), derive_pub_key(keys[1].clone()).unwrap(), 0).unwrap()
+        };
+        // Wallet of monero-core's functional tests, restored with the seed offset "offset"
+        let mnemonic: Vec<String> = "velvet lymph giddy number token physics poetry unquoted nibs useful sabotage limits benches lifestyle eden nitrogen anvil fewest avoid batch vials washing fences goat unquoted".split(' ').map(String::from).collect();
+        let plain = derive_hex_seed_with_passphrase(mnemonic.clone(), Some("")).unwrap();
+        assert_eq!(address(&plain), "42ey1afDFnn4886T7196doS9GPMzexD9gXpsZJDwVjeRVdFCSoHnv7KPbBeGpzJBzHRCAs9UxqeoyFQMYbqSWYTfJJQAWDm");
+        let offset = derive_hex_seed_with_passphrase(mnemonic.clone(), Some("offset")).unwrap();
+        assert_eq!(*offset, "8101f6e2e88143475cb7d45c4d28167c2e2376be8c3d11ed3a7984890efc2003");
+        assert_eq!(address(&offset), "42QCEwbM7KkRFB1HL6pazuS1eGtNDKngF1V7741g7PqUPQZt5ZyEx5nA4kwkU5oMVUKSjCc82DBJn9E4kqFp1MdESu48FeY");
+        // Same as `cryptonote::decrypt_key`: the key minus the CryptoNight hash of the passphrase
+        let scalar = |hex_str: &str| Scalar::from_bytes_mod_order(hex::decode(hex_str).unwrap().try_into().unwrap());
+        let expected = scalar(&derive_hex_seed(mnemonic)) - scalar(&cn_slow_hash_v0(b"offset"));
+        assert_eq!(*offset, hex::encode(expected.as_bytes()));
+
+        // MyMonero seeds have no passphrase, asking for one is an error rather than a panic
+        let mymonero = generate_seed("en", "mymonero").to_vec();
+        assert_eq!(mymonero.len(), 13);
+        assert!(derive_hex_seed_with_passphrase(mymonero.clone(), Some("offset")).unwrap_err().contains("25-word"));
+        assert_eq!(derive_hex_seed_with_passphrase(mymonero.clone(), None).unwrap(), derive_hex_seed(mymonero));
     }
 
     #[cfg(test)]
@@ -39,4 +92,4849 @@ mod tests {
             "a084f01d1437a09c6985401b60d43554ae105802c5f5d8a9b3253649c0be6605".to_string()
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn hashing_cn_slow_hash_variants() {
+        use libmonero::crypt::cryptonight::{cn_slow_hash, CnVariant};
+
+        // Reference vectors of Monero's tests-slow-1, tests-slow-2 and tests-slow-4
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipiscing";
+        assert_eq!(cn_slow_hash(&[0u8; 76], CnVariant::V1).unwrap(), "80563c40ed46575a9e44820d93ee095e2851aa22483fd67837118c6cd951ba61");
+        assert_eq!(cn_slow_hash(lorem, CnVariant::V2).unwrap(), "72f134fc50880c330fe65a2cb7896d59b2e708a0221c6a9da3f69b3a702d8682");
+        assert_eq!(
+            cn_slow_hash(b"This is a test This is a test This is a test", CnVariant::R { height: 1806260 }).unwrap(),
+            "f759588ad57e758467295443a9bd71490abff8e9dad1b95b6bf2f5d0d78387bc"
+        );
+        assert_eq!(
+            cn_slow_hash(b"elit, sed do eiusmod tempor incididunt ut labore", CnVariant::R { height: 1806262 }).unwrap(),
+            "1ee6728da60fbd8d7d55b2b1ade487a3cf52a2c3ac6f520db12c27d8921f6cab"
+        );
+        assert_eq!(cn_slow_hash(b"This is a test", CnVariant::V0).unwrap(), "a084f01d1437a09c6985401b60d43554ae105802c5f5d8a9b3253649c0be6605");
+        assert!(cn_slow_hash(&[0u8; 42], CnVariant::V1).is_err());
+
+        assert_eq!(CnVariant::from_major_version(1, 0).unwrap(), CnVariant::V0);
+        assert_eq!(CnVariant::from_major_version(6, 1400000).unwrap(), CnVariant::V0);
+        assert_eq!(CnVariant::from_major_version(9, 1686275).unwrap(), CnVariant::V2);
+        assert_eq!(CnVariant::from_major_version(11, 1788720).unwrap(), CnVariant::R { height: 1788720 });
+        assert!(CnVariant::from_major_version(0, 0).is_err());
+        assert!(CnVariant::from_major_version(16, 3000000).is_err());
+    }
+
+    #[test]
+    fn hashing_cn_slow_hash_hardware_aes() {
+        use libmonero::crypt::cryptonight::{cn_slow_hash, cn_slow_hash_software, CnVariant};
+
+        // Without AES instructions both functions take the software path and trivially agree
+        let input = b"Excepteur sint occaecat cupidatat non proident, sunt in culpa";
+        for variant in [CnVariant::V0, CnVariant::V1, CnVariant::V2, CnVariant::R { height: 1806263 }] {
+            assert_eq!(cn_slow_hash(input, variant).unwrap(), cn_slow_hash_software(input, variant).unwrap());
+        }
+        assert!(cn_slow_hash_software(&[0u8; 42], CnVariant::V1).is_err());
+    }
+
+    #[test]
+    fn hashing_cn_slow_hash_unaligned() {
+        use libmonero::crypt::cryptonight::{cn_slow_hash_software, CnVariant};
+
+        // Input starting at an odd address, with the software AES rounds so CI can run it under Miri
+        let buffer = [0u8; 77];
+        assert_eq!(cn_slow_hash_software(&buffer[1..], CnVariant::V1).unwrap(), "80563c40ed46575a9e44820d93ee095e2851aa22483fd67837118c6cd951ba61");
+    }
+
+    #[test]
+    fn hashing_cn_hasher_reuse() {
+        use libmonero::crypt::cryptonight::{cn_slow_hash, CnHasher, CnVariant};
+
+        // Runs on a quarter of the default stack of test threads, the scratchpad is on the heap
+        let hashing = std::thread::Builder::new().stack_size(512 * 1024).spawn(|| {
+            let mut hasher = CnHasher::new();
+            let inputs: [&[u8]; 3] = [b"This is a test This is a test This is a test", b"elit, sed do eiusmod tempor incididunt ut labore", b"This is a test This is a test This is a test"];
+            // The program of the previous height must not leak into the next one
+            for (input, height) in inputs.iter().zip([1806260, 1806262, 1806260]) {
+                let variant = CnVariant::R { height };
+                assert_eq!(hasher.hash(input, variant).unwrap(), cn_slow_hash(input, variant).unwrap());
+            }
+            assert_eq!(hasher.hash(b"This is a test", CnVariant::V0).unwrap(), "a084f01d1437a09c6985401b60d43554ae105802c5f5d8a9b3253649c0be6605");
+            assert!(hasher.hash(&[0u8; 42], CnVariant::V1).is_err());
+            assert_eq!(hasher.hash(&[0u8; 76], CnVariant::V1).unwrap(), "80563c40ed46575a9e44820d93ee095e2851aa22483fd67837118c6cd951ba61");
+        });
+        hashing.unwrap().join().unwrap();
+    }
+
+    #[test]
+    fn clsag_sign_verify() {
+        use curve25519_dalek::{constants::EIGHT_TORSION, edwards::CompressedEdwardsY, Scalar};
+        use libmonero::crypt::{clsag_sign, Clsag};
+        use libmonero::keys::{commit, generate_key_image, Commitment, KeyImage, OneTimeKey, PrivateKey};
+
+        let key = |i: u64| PrivateKey::from_bytes_mod_order(Scalar::from(1000 + i).to_bytes());
+        let message = [0x5a; 32];
+        let amount = 1_250_000_000_000u64;
+        let (mask, pseudo_mask) = (Scalar::from(123456789u64), Scalar::from(987654321u64));
+        let pseudo_out = commit(amount, &pseudo_mask);
+        for (ring_size, real_index) in [(1, 0), (2, 0), (2, 1), (16, 7), (16, 15)] {
+            let secret_key = key(real_index as u64);
+            let ring: Vec<(OneTimeKey, Commitment)> = (0..ring_size)
+                .map(|i| match i == real_index {
+                    true => (OneTimeKey::from(secret_key.public_key()), commit(amount, &mask)),
+                    false => (OneTimeKey::from(key(i as u64).public_key()), commit(i as u64, &Scalar::from(i as u64 + 1))),
+                })
+                .collect();
+            let key_image = generate_key_image(&secret_key, &secret_key.public_key());
+            let signature = clsag_sign(&message, &ring, real_index, &secret_key, &mask, &pseudo_out, &pseudo_mask).unwrap();
+            assert_eq!(signature.s.len(), ring_size);
+            assert!(signature.verify(&message, &ring, &key_image, &pseudo_out), "{} {}", ring_size, real_index);
+
+            // Anything the signature commits to must invalidate it
+            assert!(!signature.verify(&[0; 32], &ring, &key_image, &pseudo_out));
+            assert!(!signature.verify(&message, &ring, &generate_key_image(&key(99), &key(99).public_key()), &pseudo_out));
+            assert!(!signature.verify(&message, &ring, &key_image, &commit(amount + 1, &pseudo_mask)));
+            assert!(!signature.verify(&message, &ring[..ring_size - 1], &key_image, &pseudo_out));
+            let mut tampered: Clsag = signature.clone();
+            tampered.s[real_index] = (Scalar::from_bytes_mod_order(tampered.s[real_index]) + Scalar::ONE).to_bytes();
+            assert!(!tampered.verify(&message, &ring, &key_image, &pseudo_out));
+            let mut tampered = signature.clone();
+            tampered.D = commit(0, &Scalar::from(5u64)).as_bytes().to_owned();
+            assert!(!tampered.verify(&message, &ring, &key_image, &pseudo_out));
+            let mut tampered = signature.clone();
+            tampered.c1 = [0xff; 32];
+            assert!(!tampered.verify(&message, &ring, &key_image, &pseudo_out));
+            if ring_size > 1 {
+                let mut swapped = ring.clone();
+                swapped.swap(0, ring_size - 1);
+                assert!(!signature.verify(&message, &swapped, &key_image, &pseudo_out));
+            }
+
+            // Same key image with a torsion component
+            let torsioned = CompressedEdwardsY(*key_image.as_bytes()).decompress().unwrap() + EIGHT_TORSION[1];
+            assert!(!signature.verify(&message, &ring, &KeyImage::from_bytes(torsioned.compress().to_bytes()), &pseudo_out));
+        }
+
+        let secret_key = key(3);
+        let ring = vec![
+            (OneTimeKey::from(key(0).public_key()), commit(1, &Scalar::ONE)),
+            (OneTimeKey::from(secret_key.public_key()), commit(amount, &mask)),
+        ];
+        assert!(clsag_sign(&message, &ring, 1, &secret_key, &mask, &pseudo_out, &pseudo_mask).is_ok());
+        assert!(clsag_sign(&message, &ring, 0, &secret_key, &mask, &pseudo_out, &pseudo_mask).is_err());
+        assert!(clsag_sign(&message, &ring, 2, &secret_key, &mask, &pseudo_out, &pseudo_mask).is_err());
+        assert!(clsag_sign(&message, &ring, 1, &secret_key, &pseudo_mask, &pseudo_out, &pseudo_mask).is_err());
+        assert!(clsag_sign(&message, &ring, 1, &secret_key, &mask, &commit(amount - 1, &pseudo_mask), &pseudo_mask).is_err());
+        assert!(clsag_sign(&message, &ring, 1, &secret_key, &mask, &commit(amount, &mask), &mask).is_err());
+    }
+
+    #[test]
+    fn hashing_hash_to_point() {
+        use libmonero::crypt::keccak::keccak256;
+        use libmonero::crypt::{ge_fromfe_frombytes_vartime, hash_to_point};
+
+        // (input, ge_fromfe_frombytes_vartime, times the cofactor), the first one is from monero-core's tests/crypto/tests.txt,
+        // the others were checked against a port of the reference code and cover inputs above p
+        let vectors = [
+            ("83efb774657700e37291f4b8dd10c839d1c739fd135c07a2fd7382334dafdd6a", "2789ecbaf36e4fcb41c6157228001538b40ca379464b718d830c58caae7ea4ca", "b3159a3146ba5ef9a2b5346e37750be0412eab5a63a4dcf4cdf49a2d75a30d4e"),
+            ("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "80c90f3f23af8763b058cf5029e42f6a78a3e48dc0eeb38f31b3a6419e64fdbf", "119d000ace03ca6a6bf6100a8d72f5779e96cd67deeca9267c480077a0a45e8a"),
+            ("edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "dcb2d5b787a4d37cda41775e3f5beee0243f0471005e0a5b41029dfa4ac9c6d8", "bfc782dc998bcb229efcd7715ded2fdb71cfa270d1173d6a318ed8d13aaaa6b1"),
+            ("38b4e652e44da7f2370d9e260e27136550a4a3a6d07f5c0c332f8b1224083fd2", "b9f5cc883bb9e5964e8442e5066878991c830f10d4a6eedc87474366e3108584", "d4cab8e951040e90bc8d7b9aea6a989efdabc55c6d936deed08f1731c5e3c30a"),
+            ("2b902f8911e81818f8c99d5d5d9831957504d90e945de2e8f54ee781cc75f636", "4258cc454173ae5e84efc3b4ea080cfd7fc889d4c0d93ec9eb0c6565236cf191", "63d05c54ce145cc805438403e30e5d9a9ab498a828a013dfc3877a15518fdc04"),
+            ("d85099095aa300165a67036f9b540d6b8f0be21124179c3dd9f73817ce6e118d", "72218eb682dee4c15f75d33e200e392db11262275e17944e029b69076307268c", "916b4d55b655826362b27f3cbf9a07238245c22d2d760465c39d46040d4c4d66"),
+        ];
+        for (input, point, cofactor_point) in vectors {
+            let bytes: [u8; 32] = hex::decode(input).unwrap().try_into().unwrap();
+            let mapped = ge_fromfe_frombytes_vartime(&bytes);
+            assert_eq!(hex::encode(mapped.compress().as_bytes()), point);
+            assert_eq!(hex::encode(mapped.mul_by_cofactor().compress().as_bytes()), cofactor_point);
+        }
+        // Zero maps to the point of order 2, which the cofactor clears
+        let zero = ge_fromfe_frombytes_vartime(&[0; 32]);
+        assert_eq!(hex::encode(zero.compress().as_bytes()), "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+        assert_eq!(hex::encode(zero.mul_by_cofactor().compress().as_bytes()), format!("01{}", "00".repeat(31)));
+
+        let key = hex::decode(vectors[0].0).unwrap();
+        assert_eq!(hash_to_point(&key), ge_fromfe_frombytes_vartime(&keccak256(&key)).mul_by_cofactor());
+        assert!(hash_to_point(&key).is_torsion_free());
+    }
+
+    #[test]
+    fn hashing_keccak() {
+        use libmonero::crypt::keccak::{hash_to_scalar, hash_to_scalar_hex, keccak1600, keccak256, keccak256_hex, keccakf};
+        use libmonero::keys::{derivation_to_scalar, KeyDerivation};
+
+        let fox = b"The quick brown fox jumps over the lazy dog";
+        assert_eq!(hex::encode(keccak256(fox)), "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15");
+        assert_eq!(keccak256_hex(&hex::encode(fox)).unwrap(), "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15");
+        assert!(keccak256_hex("abc").is_err());
+
+        // The full state starts with the hash, and permuting it gives the state of the next squeeze
+        let state = keccak1600(fox);
+        assert_eq!(state[..32], keccak256(fox));
+        let mut lanes = [0u64; 25];
+        for (lane, bytes) in lanes.iter_mut().zip(state.chunks_exact(8)) {
+            *lane = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        keccakf(&mut lanes);
+        assert_eq!(lanes[0], 0x7e86fdcbc44b19db);
+
+        // Hs is the scalar of key derivations
+        let derivation = KeyDerivation::from_bytes([7; 32]);
+        assert_eq!(hash_to_scalar(&[[7u8; 32].as_slice(), &[5]].concat()), derivation_to_scalar(&derivation, 5));
+        assert_eq!(hash_to_scalar_hex(&hex::encode([[7u8; 32].as_slice(), &[5]].concat())).unwrap(), hex::encode(derivation_to_scalar(&derivation, 5).as_bytes()));
+        assert!(hash_to_scalar_hex("0").is_err());
+    }
+
+    #[test]
+    fn hashing_rx_slow_hash() {
+        use libmonero::crypt::randomx::{rx_seed_height, RandomXCache, RandomXVm};
+
+        assert_eq!(rx_seed_height(0), 0);
+        assert_eq!(rx_seed_height(2112), 0);
+        assert_eq!(rx_seed_height(2113), 2048);
+        assert_eq!(rx_seed_height(4160), 2048);
+        assert_eq!(rx_seed_height(4161), 4096);
+
+        // Reference vectors of RandomX, with one VM reused across inputs and keys
+        let cache = RandomXCache::new(b"test key 000");
+        let mut vm = RandomXVm::light(&cache);
+        assert_eq!(hex::encode(vm.calculate_hash(b"This is a test")), "639183aae1bf4c9a35884cb46b09cad9175f04efd7684e7262a0ac1c2f0b4e3f");
+        assert_eq!(hex::encode(vm.calculate_hash(b"Lorem ipsum dolor sit amet")), "300a0adb47603dedb42228ccb2b211104f4da45af709cd7547cd049e9489c969");
+        assert_eq!(hex::encode(vm.calculate_hash(b"sed do eiusmod tempor incididunt ut labore et dolore magna aliqua")), "c36d4ed4191e617309867ed66a443be4075014e2b061bcdaf9ce7b721d2b77a8");
+        let cache = RandomXCache::new(b"test key 001");
+        let mut vm = RandomXVm::light(&cache);
+        assert_eq!(hex::encode(vm.calculate_hash(b"sed do eiusmod tempor incididunt ut labore et dolore magna aliqua")), "e9ff4503201c0c2cca26d285c93ae883f9b1d30c9eb240b820756f2d5a7905fc");
+        let block = hex::decode("0b0b98bea7e805e0010a2126d287a2a0cc833d312cb786385a7c2f9de69d25537f584a9bc9977b00000000666fd8753bf61a8631f12984e3fd44f4014eca629276817b56f32e9b68bd82f416").unwrap();
+        assert_eq!(hex::encode(vm.calculate_hash(&block)), "c56414121acda1713c2f2a819d8ae38aed7c80c35c2a769298d34f03833cd5f1");
+    }
+
+    struct FixedHeightTransport;
+
+    impl libmonero::blocks::RpcTransport for FixedHeightTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, _body: &[u8]) -> Result<Vec<u8>, String> {
+            match path {
+                "/get_height" => Ok(format!(r#"{{"hash":"{}","height":3000000,"status":"OK","untrusted":false}}"#, HASH_AA).into_bytes()),
+                _ => Err("unsupported".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn rpc_record_and_replay() {
+        use libmonero::blocks::{get_height_info, DaemonNode, RecordingTransport, ReplayTransport};
+        use std::sync::Arc;
+
+        let path = std::env::temp_dir().join(format!("libmonero_recording_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let recorder = RecordingTransport::new(Arc::new(FixedHeightTransport), &path).unwrap();
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(recorder));
+        let recorded = get_height_info(node).unwrap();
+
+        let replay = ReplayTransport::from_file(&path).unwrap();
+        let node = DaemonNode::new("127.0.0.1".to_string(), 18081, false).with_transport(Arc::new(replay));
+        let replayed = get_height_info(node.clone()).unwrap();
+        assert_eq!(replayed.height, recorded.height);
+        assert_eq!(replayed.hash, recorded.hash);
+        // Every recorded call is served only once
+        assert!(get_height_info(node).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn raw_tx_with_rings(rings: Vec<Vec<u64>>, outputs: usize) -> libmonero::blocks::RawTx {
+        use libmonero::blocks::*;
+        use libmonero::keys::{KeyImage, OneTimeKey};
+        RawTx {
+            version: 2,
+            unlock_time: 0,
+            vin: rings.into_iter().enumerate().map(|(i, ring)| VinRawTx {
+                key: KeyRawTx { amount: 0, key_offsets: relative_offsets(&ring).unwrap(), k_image: KeyImage::from_hex(&format!("{:064x}", i)).unwrap() },
+            }).collect(),
+            vout: (0..outputs).map(|_| Vout {
+                amount: 0,
+                target: Target { tagged_key: TaggedKey { key: OneTimeKey::default(), view_tag: String::new() } },
+            }).collect(),
+            extra: Vec::new(),
+            rct_signatures: RctSignatures { type_int: 6, txn_fee: 0, ecdh_info: Vec::new(), out_pk: Vec::new() },
+            rctsig_prunable: RctsigPrunable { nbp: 0, bpp: Vec::new(), CLSAGs: Vec::new(), pseudo_outs: Vec::new() },
+            untrusted: false,
+        }
+    }
+
+    #[test]
+    fn privacy_report() {
+        use libmonero::blocks::build_privacy_report;
+
+        let tx = raw_tx_with_rings(vec![vec![10, 20, 30, 40], vec![15, 20, 30, 45]], 2);
+        // Output with global index n is in block n
+        let report = build_privacy_report(&tx, &[3, 1], Some(1), &[vec![10, 20, 30, 40]], 100, Ok).unwrap();
+        assert_eq!(report.output_count, 2);
+        assert_eq!(report.change_index, Some(1));
+        assert_eq!(report.inputs[0].real_age, 60);
+        assert_eq!(report.inputs[0].decoy_ages, vec![90, 80, 70]);
+        assert_eq!(report.inputs[0].younger_decoys, 0);
+        assert!(report.inputs[0].reused_ring);
+        assert_eq!(report.inputs[1].younger_decoys, 2);
+        assert_eq!(report.inputs[1].max_shared_members, 2);
+        assert_eq!(report.youngest_real_inputs(), vec![0]);
+        assert_eq!(report.overlapping_inputs(), vec![0, 1]);
+        assert!(build_privacy_report(&tx, &[0], None, &[], 100, Ok).is_err());
+
+        // Offsets from the chain or a node are untrusted, overflowing ones are an error rather than a panic
+        let mut tx = raw_tx_with_rings(vec![vec![10, 20]], 1);
+        tx.vin[0].key.key_offsets = vec![u64::MAX, 1];
+        assert!(build_privacy_report(&tx, &[0], None, &[], 100, Ok).is_err());
+    }
+
+    #[test]
+    fn privacy_offsets() {
+        use libmonero::blocks::{absolute_offsets, relative_offsets};
+
+        assert_eq!(relative_offsets(&[100, 105, 125]).unwrap(), vec![100, 5, 20]);
+        assert_eq!(absolute_offsets(&[100, 5, 20]).unwrap(), vec![100, 105, 125]);
+        assert_eq!(absolute_offsets(&[u64::MAX, 0]).unwrap(), vec![u64::MAX, u64::MAX]);
+        assert!(relative_offsets(&[100, 125, 105]).is_err());
+        assert!(absolute_offsets(&[u64::MAX, 1]).is_err());
+    }
+
+    // Outputs and RingCT data of a mainnet CLSAG transaction, output 0 belongs to the wallet of CLSAG_TX_VIEW_KEY and CLSAG_TX_SPEND_KEY
+    const CLSAG_TX_JSON: &str = r#"{"version": 2, "unlock_time": 0, "vin": [], "vout": [{"amount": 0, "target": {"tagged_key": {"key": "9716cdbae38def9a74e7df5402c108270a1d5fc87c7e5ebaaaed68aae77701e3", "view_tag": "cf"}}}, {"amount": 0, "target": {"tagged_key": {"key": "082e27ca8af2b9e3004156c152aa98503b548b1591fdcd839ab550612ae6c9dc", "view_tag": "7e"}}}], "extra": [1, 165, 124, 147, 251, 12, 167, 122, 185, 107, 125, 253, 115, 128, 196, 132, 45, 30, 88, 192, 85, 67, 14, 13, 66, 92, 209, 199, 108, 87, 140, 202, 57, 2, 9, 1, 149, 25, 248, 193, 206, 94, 32, 48], "rct_signatures": {"type": 6, "txnFee": 2605200000, "ecdhInfo": [{"amount": "acd081c0dd2c7178"}, {"amount": "a341382720ada875"}], "outPk": ["88a96ac5cff1623fd2e4aaf56ed395a325393fbd950428a3ff7e6dc6c559669c", "8d5e8fb80d5e979c8a81c89754201d4bd094c37c143759260e282555dfed3100"]}}"#;
+    const CLSAG_TX_VIEW_KEY: &str = "9df81dd2e369004d3737850e4f0abaf2111720f270b174acf8e08547e41afb0b";
+    const CLSAG_TX_SPEND_KEY: &str = "25f7339ce03a0206129c0bdd78396f80bf28183ccd16084d4ab1cbaf74f0c204";
+
+    #[test]
+    fn scan_transaction() {
+        use libmonero::blocks::parse_transaction_json;
+        use libmonero::keys::{scan_tx_for_outputs, PrivateKey};
+
+        let tx = parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        let view_key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let owned = scan_tx_for_outputs(&view_key, &spend_key.public_key(), &tx);
+        assert_eq!(owned.len(), 1);
+        assert_eq!(owned[0].index, 0);
+        assert_eq!(owned[0].amount, 60363387616637);
+        assert_eq!(owned[0].output_key.to_hex(), "9716cdbae38def9a74e7df5402c108270a1d5fc87c7e5ebaaaed68aae77701e3");
+        assert_eq!(owned[0].tx_public_key.to_hex(), "a57c93fb0ca77ab96b7dfd7380c4842d1e58c055430e0d425cd1c76c578cca39");
+        let other_view_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+        assert!(scan_tx_for_outputs(&other_view_key, &spend_key.public_key(), &tx).is_empty());
+    }
+
+    #[test]
+    fn ringct_commitments_and_ecdh() {
+        use libmonero::blocks::{parse_transaction_json, EcdhInfo, EncryptedAmount};
+        use libmonero::keys::{commit, commitment_mask, derivation_to_scalar, ecdh_decode, ecdh_encode, generate_key_derivation, zero_commit, PrivateKey, PublicKey};
+        use curve25519_dalek::edwards::CompressedEdwardsY;
+        use curve25519_dalek::Scalar;
+
+        let point = |commitment: libmonero::keys::Commitment| CompressedEdwardsY(*commitment.as_bytes()).decompress().unwrap();
+        assert_eq!(zero_commit(0).to_hex(), "5866666666666666666666666666666666666666666666666666666666666666");
+        assert_eq!(zero_commit(5), commit(5, &Scalar::ONE));
+        // Commitments add up
+        let (x, y) = (Scalar::from(1234u64), Scalar::from(98765u64));
+        assert_eq!(point(commit(10, &x)) + point(commit(32, &y)), point(commit(42, &(x + y))));
+
+        // Output 0 of the mainnet transaction decodes to the amount found by the scanner, and encodes back to the same bytes
+        let tx = parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        let view_key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let tx_public_key = PublicKey::from_hex("a57c93fb0ca77ab96b7dfd7380c4842d1e58c055430e0d425cd1c76c578cca39").unwrap();
+        let shared_secret = derivation_to_scalar(&generate_key_derivation(&tx_public_key, &view_key), 0);
+        let encrypted = EncryptedAmount::from_ecdh_info(&tx.rct_signatures.ecdh_info[0], true).unwrap();
+        let (amount, mask) = ecdh_decode(&encrypted, &shared_secret).unwrap();
+        assert_eq!((amount, mask), (60363387616637, commitment_mask(&shared_secret)));
+        assert_eq!(commit(amount, &mask), tx.rct_signatures.out_pk[0]);
+        assert_eq!(ecdh_encode(amount, &mask, &shared_secret, true), encrypted);
+        assert_eq!(encrypted.to_ecdh_info().trunc_amount, "acd081c0dd2c7178");
+
+        // Full encoding used before Bulletproofs 2 carries the mask
+        let mask = Scalar::from(777u64);
+        let encrypted = ecdh_encode(amount, &mask, &shared_secret, false);
+        let ecdh = encrypted.to_ecdh_info();
+        assert_eq!(EncryptedAmount::from_ecdh_info(&ecdh, false), Ok(encrypted));
+        assert_eq!(ecdh_decode(&encrypted, &shared_secret), Ok((amount, mask)));
+        assert!(ecdh_decode(&encrypted, &Scalar::from(1u64)).is_err());
+        assert!(EncryptedAmount::from_ecdh_info(&EcdhInfo { trunc_amount: "acd0".to_string(), mask: String::new() }, true).is_err());
+    }
+
+    #[test]
+    fn txpool_backlog_blob() {
+        use libmonero::blocks::{get_txpool_backlog, DaemonNode, ReplayTransport, TxBacklogEntry};
+        use std::sync::Arc;
+
+        // Two entries, the daemon writes bytes above 0x7f unescaped and control characters as \u00XX
+        let mut blob = Vec::new();
+        for (weight, fee, time) in [(1500u64, 0xff_ff22u64, 34u64), (2000, 10, 0x5c)] {
+            blob.extend_from_slice(&weight.to_le_bytes());
+            blob.extend_from_slice(&fee.to_le_bytes());
+            blob.extend_from_slice(&time.to_le_bytes());
+        }
+        let mut response = br#"{"id": "0", "jsonrpc": "2.0", "result": {"backlog": ""#.to_vec();
+        for byte in blob {
+            match byte {
+                b'"' => response.extend_from_slice(b"\\\""),
+                b'\\' => response.extend_from_slice(b"\\\\"),
+                0..=0x1f => response.extend_from_slice(format!("\\u{:04x}", byte).as_bytes()),
+                _ => response.push(byte),
+            }
+        }
+        response.extend_from_slice(br#"", "status": "OK", "untrusted": false}}"#);
+        let request = br#"{"id":"0","jsonrpc":"2.0","method":"get_txpool_backlog"}"#;
+        let recording = format!(r#"{{"path":"/json_rpc","request":"{}","response":"{}"}}"#, hex::encode(request), hex::encode(&response));
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(ReplayTransport::from_reader(recording.as_bytes()).unwrap()));
+        let backlog = get_txpool_backlog(node).unwrap();
+        assert_eq!(backlog, vec![
+            TxBacklogEntry { weight: 1500, fee: 0xff_ff22, time_in_pool: 34 },
+            TxBacklogEntry { weight: 2000, fee: 10, time_in_pool: 0x5c },
+        ]);
+    }
+
+    struct TipTransport(u64, &'static str);
+
+    impl libmonero::blocks::RpcTransport for TipTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, _path: &str, _body: &[u8]) -> Result<Vec<u8>, String> {
+            Ok(format!(r#"{{"hash":"{}","height":{},"status":"OK","untrusted":false}}"#, self.1, self.0).into_bytes())
+        }
+    }
+
+    #[test]
+    fn chain_monitor_watch() {
+        use libmonero::blocks::{ChainAlarm, ChainMonitor, DaemonNode};
+        use std::sync::{mpsc, Arc};
+        use std::time::Duration;
+
+        let nodes = vec![
+            DaemonNode::new("node-a".to_string(), 18081, false).with_transport(Arc::new(TipTransport(3000000, HASH_AA))),
+            DaemonNode::new("node-b".to_string(), 18081, false).with_transport(Arc::new(TipTransport(3000000, HASH_AA))),
+            DaemonNode::new("node-c".to_string(), 18081, false).with_transport(Arc::new(TipTransport(2999000, HASH_CC))),
+        ];
+        let (sender, receiver) = mpsc::channel();
+        let handle = ChainMonitor::new(10).watch(nodes, Duration::from_millis(10), move |alarms| {
+            let _ = sender.send(alarms.to_vec());
+        });
+        let alarms = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        handle.stop();
+        assert_eq!(alarms, vec![ChainAlarm::NodeLagging { node: "http://node-c:18081".to_string(), height: 2999000, best_height: 3000000 }]);
+    }
+
+    #[test]
+    fn seed_from_entropy() {
+        use libmonero::keys::{generate_seed_from_entropy, generate_seed_with_rng};
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut entropy = [0u8; 32];
+        hex::decode_to_slice("6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e", &mut entropy).unwrap();
+        let mnemonic = generate_seed_from_entropy(&entropy, "en");
+        assert_eq!(mnemonic.join(" "), "five saved himself oust taunts pebbles fibula organs koala copy dying vein damp dauntless code gags copy roster geek toolbox joyous apart unlikely warped taunts");
+        // Every generated seed must decode back to a valid hex seed
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..200 {
+            let seed = generate_seed_with_rng(&mut rng, "en", "original");
+            assert_eq!(derive_hex_seed(seed.to_vec()).len(), 64);
+        }
+    }
+
+    #[test]
+    fn mnemonic_validation() {
+        use libmonero::keys::{generate_seed, validate_mnemonic, MnemonicError, SeedType};
+
+        for language in ["en", "eo", "fr", "it", "ja", "lj", "pt", "ru"] {
+            let seed = generate_seed(language, "original");
+            assert_eq!(validate_mnemonic(&seed), Ok(SeedType::Original), "{}", language);
+            let seed = generate_seed(language, "mymonero");
+            assert_eq!(validate_mnemonic(&seed), Ok(SeedType::MyMonero), "{}", language);
+        }
+        let seed = generate_seed("en", "original");
+        assert_eq!(validate_mnemonic(&seed[..24]), Err(MnemonicError::InvalidWordCount(24)));
+    }
+
+    #[test]
+    fn error_hierarchy() {
+        use libmonero::keys::{validate_mnemonic, MnemonicError};
+        use libmonero::utils::{Context, Error, ErrorKind, KeyError, ParseError, RpcError, WalletError};
+        use libmonero::wallet::KeyImageImportError;
+        use std::error::Error as _;
+
+        // Context is listed outermost first, the code is the one of the outermost subsystem error
+        let seed: Vec<String> = vec!["abbey".to_string(); 12];
+        let error = validate_mnemonic(&seed).context("Restoring wallet").context("Opening wallet 1").unwrap_err();
+        assert_eq!(error.code(), 1101);
+        assert_eq!(error.context().collect::<Vec<_>>(), vec!["Opening wallet 1", "Restoring wallet"]);
+        assert_eq!(error.to_string(), "Opening wallet 1: Restoring wallet: Invalid mnemonic seed");
+        assert_eq!(error.source().unwrap().to_string(), "Seed has 12 words, expected 13 or 25");
+        assert_eq!(error.kind(), &ErrorKind::Key(KeyError::Mnemonic(MnemonicError::InvalidWordCount(12))));
+
+        let error = Error::from(WalletError::from(KeyError::from(MnemonicError::UnsupportedLanguage("xx".to_string()))));
+        assert_eq!((error.code(), error.root_cause().to_string()), (4001, "Language xx is not supported".to_string()));
+        assert_eq!(Error::from(KeyImageImportError::AlreadyImported(3)).code(), 4103);
+
+        let parse = ParseError::MissingField("block_header".to_string());
+        let error = Error::from(RpcError::from(parse.clone())).with_context("Getting block 5");
+        assert_eq!(error.code(), 2005);
+        assert_eq!(error.root_cause().to_string(), parse.to_string());
+        assert!(matches!(error.kind(), ErrorKind::Rpc(RpcError::InvalidResponse(ParseError::MissingField(_)))));
+
+        // Errors still become the String errors of the rest of the crate
+        let message: String = Error::from(RpcError::Cancelled).with_context("Error while getting the block count from daemon").into();
+        assert_eq!(message, "Error while getting the block count from daemon: RPC call cancelled");
+        let response = serde_json::json!({"error": {"code": -5, "message": "Too big height"}});
+        assert_eq!(RpcError::from_json_rpc(&response).map(|error| error.code()), Some(2002));
+    }
+
+    struct LifecycleTransport(std::sync::Mutex<Vec<&'static str>>);
+
+    impl libmonero::blocks::RpcTransport for LifecycleTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, _path: &str, _body: &[u8]) -> Result<Vec<u8>, String> {
+            let mut responses = self.0.lock().unwrap();
+            let response = if responses.len() > 1 { responses.remove(0) } else { responses[0] };
+            Ok(response.as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn transaction_tracking() {
+        use libmonero::blocks::{DaemonNode, TxHash, TxState, TxStatus, TxTracker};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let transport = LifecycleTransport(Mutex::new(vec![
+            r#"{"missed_tx": ["aa"], "status": "OK"}"#,
+            r#"{"txs": [{"in_pool": true, "double_spend_seen": false}], "status": "OK"}"#,
+            r#"{"txs": [{"in_pool": false, "block_height": 3000000, "confirmations": 1}], "status": "OK"}"#,
+            r#"{"txs": [{"in_pool": false, "block_height": 3000000, "confirmations": 3}], "status": "OK"}"#,
+        ]));
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(transport));
+        let mut states = Vec::new();
+        let result = TxTracker::new(TxHash::from_hex(HASH_AA).unwrap(), 3).track(&node, Duration::from_millis(1), |state| states.push(*state)).unwrap();
+        assert_eq!(result, TxState::Confirmed { height: 3000000, confirmations: 3 });
+        assert_eq!(states, vec![
+            TxState::InPool { double_spend_seen: false },
+            TxState::Mined { height: 3000000, confirmations: 1 },
+            TxState::Confirmed { height: 3000000, confirmations: 3 },
+        ]);
+
+        // Seen in pool with a conflicting transaction, then gone
+        let mut tracker = TxTracker::new(TxHash::from_hex(HASH_BB).unwrap(), 10);
+        tracker.update(&TxStatus { found: true, in_pool: true, double_spend_seen: true, ..TxStatus::default() });
+        assert_eq!(tracker.update(&TxStatus::default()), Some(TxState::DoubleSpent));
+        // Never seen
+        let mut tracker = TxTracker::new(TxHash::from_hex(HASH_CC).unwrap(), 10).with_max_missing_polls(2);
+        assert_eq!(tracker.update(&TxStatus::default()), None);
+        assert_eq!(tracker.update(&TxStatus::default()), Some(TxState::Dropped));
+    }
+
+    #[test]
+    fn address_decoding() {
+        use libmonero::utils::{decode_address, AddressType, Network};
+        use sha3::{Digest, Keccak256};
+
+        let keys = hex::decode("03970285bf0724d75e0f50bca9a9ea0e8db5091b69403dc944465f8936bde787528a736a5079dc9536edb5b6fa0a5209ce820b9734fc0785024670b3d3ba4c69").unwrap();
+        let encode = |prefix: u8, extra: &[u8]| {
+            let mut data = [&[prefix][..], &keys, extra].concat();
+            let checksum = Keccak256::digest(&data)[..4].to_vec();
+            data.extend(checksum);
+            base58_monero::encode(&data).unwrap()
+        };
+        let integrated = decode_address(&encode(19, &[1, 2, 3, 4, 5, 6, 7, 8])).unwrap();
+        assert_eq!((integrated.network, integrated.address_type), (Network::Mainnet, AddressType::Integrated));
+        assert_eq!(integrated.payment_id, Some([1, 2, 3, 4, 5, 6, 7, 8]));
+        let subaddress = decode_address(&encode(36, &[])).unwrap();
+        assert_eq!((subaddress.network, subaddress.address_type), (Network::Stagenet, AddressType::Subaddress));
+        assert!(libmonero::utils::is_valid_addr(&encode(54, &[1, 2, 3, 4, 5, 6, 7, 8])));
+        assert!(libmonero::utils::is_valid_addr(&encode(24, &[])));
+        // Wrong length for the prefix, unknown prefix and broken checksum
+        assert!(decode_address(&encode(18, &[1, 2, 3, 4, 5, 6, 7, 8])).is_err());
+        assert!(decode_address(&encode(99, &[])).is_err());
+        assert!(decode_address("41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wU").is_err());
+    }
+
+    #[test]
+    fn sender_output_keys_for_subaddress() {
+        use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, Scalar};
+        use libmonero::keys::{derive_output_key_for, derive_output_pubkey, generate_key_derivation, needs_additional_tx_keys, OneTimeKey, PrivateKey, PublicKey};
+        use libmonero::utils::{AddressInfo, AddressType, Network};
+
+        let spend = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let view = spend.derive_view_key();
+        // Subaddress keys D = B + m * G and C = a * D for some subaddress secret m
+        let m = Scalar::from(12345u64);
+        let d = PublicKey::from_point(spend.public_key().to_point() + ED25519_BASEPOINT_TABLE * &m);
+        let c = PublicKey::from_point(d.to_point() * view.to_scalar());
+        let subaddress = AddressInfo { network: Network::Mainnet, address_type: AddressType::Subaddress, public_spend_key: d, public_view_key: c, payment_id: None };
+        let r = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+        let output = derive_output_key_for(&subaddress, &r, 0);
+        // Receiver side: the derivation uses the private view key and the published r * D
+        let derivation = generate_key_derivation(&output.tx_public_key, &view);
+        assert_eq!(OneTimeKey::from(derive_output_pubkey(&derivation, 0, &d)), output.output_key);
+
+        let standard = AddressInfo { address_type: AddressType::Standard, public_spend_key: spend.public_key(), public_view_key: view.public_key(), ..subaddress.clone() };
+        assert!(!needs_additional_tx_keys(std::slice::from_ref(&subaddress)));
+        assert!(needs_additional_tx_keys(&[subaddress, standard]));
+    }
+
+    // Fake chain of 100000 blocks, block n has timestamp 1520937818 + 120 * n + (n % 7) * 15
+    struct FakeChainTransport;
+
+    impl libmonero::blocks::RpcTransport for FakeChainTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            match path {
+                "/get_height" => Ok(format!(r#"{{"height": 100000, "hash": "{}", "status": "OK"}}"#, HASH_AA).into_bytes()),
+                _ => {
+                    let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+                    let height = request["params"]["height"].as_u64().unwrap();
+                    let timestamp = 1520937818 + 120 * height + (height % 7) * 15;
+                    Ok(format!(r#"{{"result": {{"block_header": {{"height": {}, "timestamp": {}, {}}}, "status": "OK"}}}}"#, height, timestamp, FAKE_HEADER_HASHES).into_bytes())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn height_refinement() {
+        use libmonero::blocks::{height_at_with_node, DaemonNode};
+        use libmonero::utils::Network;
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(FakeChainTransport));
+        let timestamp = |height: u64| 1520937818 + 120 * height + (height % 7) * 15;
+        for height in [0u64, 1, 5000, 45678, 99999] {
+            assert_eq!(height_at_with_node(timestamp(height), Network::Mainnet, node.clone()).unwrap(), height);
+            assert_eq!(height_at_with_node(timestamp(height) + 1, Network::Mainnet, node.clone()).unwrap(), height);
+        }
+    }
+
+    #[test]
+    fn destination_policy() {
+        use libmonero::utils::{AddressInfo, AddressType, DestinationPolicy, Network, Severity};
+
+        let standard = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB".to_string();
+        let subaddress = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H".to_string();
+        let policy = DestinationPolicy::new()
+            .with_network(Network::Testnet)
+            .with_min_amount(AddressType::Subaddress, 1000)
+            .warn_on(AddressType::Standard, "Paying a primary address")
+            .with_rule(|address: &AddressInfo, amount: u64| {
+                (address.address_type == AddressType::Subaddress && amount == 1234).then(|| (Severity::Warning, "Suspicious amount".to_string()))
+            });
+        let findings = policy.check(&[(standard.clone(), 5000), (subaddress.clone(), 999), ("not an address".to_string(), 1), (subaddress, 1234)]);
+        let summary: Vec<(usize, Severity)> = findings.iter().map(|finding| (finding.index, finding.severity)).collect();
+        assert_eq!(summary, vec![
+            (0, Severity::Error), (0, Severity::Warning),
+            (1, Severity::Error), (1, Severity::Error),
+            (2, Severity::Error),
+            (3, Severity::Error), (3, Severity::Warning),
+        ]);
+        let mainnet = DestinationPolicy::new().with_network(Network::Mainnet).warn_on(AddressType::Standard, "Paying a primary address");
+        assert_eq!(mainnet.enforce(&[(standard, 5000)]).unwrap().len(), 1);
+    }
+
+    // Answers the calls of a DaemonClient with a blocking mock transport, counting them
+    #[cfg(feature = "async")]
+    struct AsyncMockTransport<T>(T, std::sync::atomic::AtomicUsize);
+
+    #[cfg(feature = "async")]
+    impl<T: libmonero::blocks::RpcTransport> libmonero::blocks::AsyncRpcTransport for AsyncMockTransport<T> {
+        fn call<'a>(&'a self, node: &'a libmonero::blocks::DaemonNode, path: &'a str, body: &'a [u8]) -> libmonero::blocks::RpcFuture<'a> {
+            self.1.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async move { self.0.call(node, path, body) })
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_daemon_client() {
+        use libmonero::blocks::{get_block_header_by_height, height_at_with_node, DaemonClient, DaemonNode, RpcConfig};
+        use libmonero::utils::Network;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        // A single threaded runtime without blocking thread pool is enough
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let transport = Arc::new(AsyncMockTransport(FixedHeightTransport, AtomicUsize::new(0)));
+        let client = DaemonClient::new(DaemonNode::cake_wallet_default()).with_transport(transport.clone());
+        let (height, info) = runtime.block_on(async { (client.get_height().await, client.get_height_info().await) });
+        assert_eq!(height.unwrap(), 3000000);
+        assert_eq!(info.unwrap().hash.to_hex(), HASH_AA);
+        assert_eq!(transport.1.load(Ordering::SeqCst), 2);
+        assert!(runtime.block_on(client.get_txpool_backlog()).is_err());
+
+        // Failed requests are retried as set in the RpcConfig of the node
+        let config = RpcConfig { retries: 2, backoff: Duration::from_millis(1), ..RpcConfig::default() };
+        let client = DaemonClient::with_config(DaemonNode::cake_wallet_default(), config).with_transport(transport.clone());
+        assert!(runtime.block_on(client.get_txpool_backlog()).is_err());
+        assert_eq!(transport.1.load(Ordering::SeqCst), 6);
+
+        // RPCs made of several requests give the same results as their blocking versions
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(FakeChainTransport));
+        let transport = Arc::new(AsyncMockTransport(FakeChainTransport, AtomicUsize::new(0)));
+        let client = DaemonClient::new(DaemonNode::cake_wallet_default()).with_transport(transport.clone());
+        for timestamp in [0, 1520937818, 1520937818 + 120 * 45678 + 1, 1700000000, u64::MAX / 2] {
+            assert_eq!(runtime.block_on(client.height_at(timestamp, Network::Mainnet)), height_at_with_node(timestamp, Network::Mainnet, node.clone()));
+        }
+        assert!(transport.1.load(Ordering::SeqCst) > 5);
+        let header = runtime.block_on(client.get_block_header_by_height(45678)).unwrap();
+        assert_eq!(header.timestamp, get_block_header_by_height(45678, node).unwrap().timestamp);
+    }
+
+    struct JsonRpcTransport;
+
+    impl libmonero::blocks::RpcTransport for JsonRpcTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(path, "/json_rpc");
+            let result = match request["method"].as_str().unwrap() {
+                "get_info" => serde_json::json!({"height": 3100000, "target_height": 0, "nettype": "mainnet", "synchronized": true, "tx_pool_size": 12, "top_block_hash": HASH_BB, "version": "0.18.3.1", "status": "OK"}),
+                "get_last_block_header" => serde_json::json!({"block_header": {"height": 3099999, "hash": HASH_BB, "prev_hash": HASH_AA, "miner_tx_hash": HASH_CC, "timestamp": 1700000000}, "status": "OK"}),
+                "get_block_count" => serde_json::json!({"count": 3100000, "status": "OK"}),
+                "get_output_histogram" => {
+                    assert_eq!(request["params"]["unlocked"], true);
+                    serde_json::json!({"histogram": [{"amount": 20000000000u64, "total_instances": 1500000, "unlocked_instances": 1500000, "recent_instances": 0}, {"amount": 7000, "total_instances": 3, "unlocked_instances": 3, "recent_instances": 0}], "status": "OK"})
+                }
+                "get_fee_estimate" => {
+                    assert_eq!(request["params"]["grace_blocks"], 10);
+                    serde_json::json!({"fee": 20000, "fees": [20000, 80123, 320000, 4000000], "quantization_mask": 10000, "status": "OK"})
+                }
+                _ => return Ok(br#"{"error":{"code":-32601,"message":"Method not found"}}"#.to_vec()),
+            };
+            Ok(serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": result}).to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn daemon_info_rpcs() {
+        use libmonero::blocks::{estimate_fee, get_block_count, get_fee_estimate, get_info, get_last_block_header, DaemonNode, FeePriority};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(JsonRpcTransport));
+        let info = get_info(node.clone()).unwrap();
+        assert_eq!((info.height, info.nettype.as_str(), info.synchronized, info.tx_pool_size), (3100000, "mainnet", true, 12));
+        let header = get_last_block_header(node.clone()).unwrap();
+        assert_eq!((header.height, header.hash.to_hex()), (3099999, HASH_BB.to_string()));
+        assert_eq!(get_block_count(node.clone()).unwrap(), 3100000);
+        let estimate = get_fee_estimate(10, node.clone()).unwrap();
+        assert_eq!(estimate.fee_per_byte(FeePriority::Priority), 4000000);
+        // 1500 * 80123 = 120184500 is rounded up to the quantization mask
+        assert_eq!(estimate.fee_for_weight(1500, FeePriority::Normal), 120190000);
+        assert_eq!(estimate.fee_for_weight(1500, FeePriority::Low), 30000000);
+        // A 1 input 2 output transaction weighs 1536, 1536 * 80123 = 123068928
+        assert_eq!(estimate_fee(FeePriority::Normal, 1, 2, 44, node.clone()).unwrap(), 123070000);
+        assert!(libmonero::blocks::get_block_header_by_height(1, node).err().unwrap().contains("Method not found"));
+    }
+
+    struct HeadersRangeTransport;
+
+    impl libmonero::blocks::RpcTransport for HeadersRangeTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, _path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(request["method"], "get_block_headers_range");
+            let start = request["params"]["start_height"].as_u64().unwrap();
+            let end = request["params"]["end_height"].as_u64().unwrap();
+            assert!(end - start < 1000);
+            let headers: Vec<serde_json::Value> = (start..=end)
+                .map(|height| serde_json::json!({"height": height, "hash": HASH_BB, "prev_hash": HASH_AA, "miner_tx_hash": HASH_CC, "reward": 600000000000u64, "nonce": 42}))
+                .collect();
+            Ok(serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": {"headers": headers, "status": "OK"}}).to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn block_headers_range() {
+        use libmonero::blocks::{get_block_headers_range, DaemonNode};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(HeadersRangeTransport));
+        let headers = get_block_headers_range(3000000, 3002499, node.clone()).unwrap();
+        assert_eq!(headers.len(), 2500);
+        assert!(headers.iter().zip(3000000..).all(|(header, height)| header.height == height && header.nonce == 42));
+        assert_eq!(headers[0].reward, 600000000000);
+        assert!(get_block_headers_range(10, 9, node).is_err());
+    }
+
+    // Main chain up to height 103 and an alternative chain of heights 102 to 104 forked from block 101
+    struct ForkTransport;
+
+    impl ForkTransport {
+        fn hash(height: u64, alternative: bool) -> String {
+            format!("{:064x}", if alternative { 0xa000 + height } else { height })
+        }
+
+        fn header(height: u64, alternative: bool) -> serde_json::Value {
+            let prev_hash = ForkTransport::hash(height - 1, alternative && height > 102);
+            serde_json::json!({"height": height, "hash": ForkTransport::hash(height, alternative), "prev_hash": prev_hash, "miner_tx_hash": HASH_CC, "orphan_status": alternative})
+        }
+
+        fn find(hash: &str) -> serde_json::Value {
+            let value = u64::from_str_radix(hash, 16).unwrap();
+            match value >= 0xa000 {
+                true => ForkTransport::header(value - 0xa000, true),
+                false => ForkTransport::header(value, false),
+            }
+        }
+    }
+
+    impl libmonero::blocks::RpcTransport for ForkTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, _path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let params = &request["params"];
+            let result = match request["method"].as_str().unwrap() {
+                "get_alternate_chains" => serde_json::json!({"chains": [{
+                    "block_hash": ForkTransport::hash(104, true),
+                    "block_hashes": [ForkTransport::hash(104, true), ForkTransport::hash(103, true), ForkTransport::hash(102, true)],
+                    "height": 104, "length": 3, "difficulty": 3000, "wide_difficulty": "0xbb8",
+                    "main_chain_parent_block": ForkTransport::hash(101, false),
+                }], "status": "OK"}),
+                "get_block_header_by_hash" => serde_json::json!({"block_header": ForkTransport::find(params["hash"].as_str().unwrap()), "status": "OK"}),
+                "get_block" => {
+                    let header = ForkTransport::find(params["hash"].as_str().unwrap());
+                    let json = serde_json::json!({"major_version": 16, "prev_id": header["prev_hash"], "miner_tx": {"version": 2, "vin": [{"gen": {"height": header["height"]}}], "vout": []}, "tx_hashes": []});
+                    serde_json::json!({"block_header": header, "json": json.to_string(), "miner_tx_hash": HASH_CC, "status": "OK"})
+                }
+                "get_block_count" => serde_json::json!({"count": 104, "status": "OK"}),
+                "get_block_headers_range" => {
+                    let headers: Vec<serde_json::Value> = (params["start_height"].as_u64().unwrap()..=params["end_height"].as_u64().unwrap()).map(|height| ForkTransport::header(height, false)).collect();
+                    serde_json::json!({"headers": headers, "status": "OK"})
+                }
+                _ => return Ok(br#"{"error":{"code":-32601,"message":"Method not found"}}"#.to_vec()),
+            };
+            Ok(serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": result}).to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn alternate_chains_and_forks() {
+        use libmonero::blocks::{get_alternate_chains, get_block_by_hash, get_block_header_by_hash, get_fork, BlockHash, DaemonNode};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(ForkTransport));
+        let chains = get_alternate_chains(node.clone()).unwrap();
+        assert_eq!(chains.len(), 1);
+        assert_eq!((chains[0].fork_height(), chains[0].length), (102, 3));
+
+        let orphan = BlockHash::from_hex(&ForkTransport::hash(103, true)).unwrap();
+        let block = get_block_by_hash(orphan, node.clone()).unwrap();
+        assert!(block.block_header.orphan_status);
+        assert_eq!(block.json.prev_id.to_hex(), ForkTransport::hash(102, true));
+        assert_eq!(get_block_header_by_hash(orphan, node.clone()).unwrap().height, 103);
+
+        // The main chain did not reach height 104, so the main side stops at 103
+        let fork = get_fork(&chains[0], node.clone()).unwrap();
+        assert_eq!((fork.fork_height, fork.parent.to_hex()), (102, ForkTransport::hash(101, false)));
+        assert_eq!(fork.alternative.iter().map(|header| header.height).collect::<Vec<_>>(), vec![102, 103, 104]);
+        assert!(fork.alternative.iter().all(|header| header.orphan_status));
+        assert_eq!(fork.main.iter().map(|header| header.hash.to_hex()).collect::<Vec<_>>(), vec![ForkTransport::hash(102, false), ForkTransport::hash(103, false)]);
+
+        // A chain whose blocks do not attach to the announced parent is refused
+        let mut detached = chains[0].clone();
+        detached.main_chain_parent_block = BlockHash::from_hex(HASH_AA).unwrap();
+        assert!(get_fork(&detached, node).is_err());
+    }
+
+    struct MiningTransport;
+
+    impl libmonero::blocks::RpcTransport for MiningTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, _path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let result = match request["method"].as_str().unwrap() {
+                "get_block_template" => {
+                    assert_eq!(request["params"]["reserve_size"], 4);
+                    // Header with a two-byte timestamp varint, then a fake miner transaction with the reserved bytes at 42
+                    let header = format!("1010{}{}{}", "e807", HASH_AA, "00000000");
+                    serde_json::json!({
+                        "blocktemplate_blob": format!("{}{}", header, "11".repeat(3) + &"00".repeat(8)),
+                        "blockhashing_blob": format!("{}{}", header, HASH_CC),
+                        "difficulty": 300000000000u64,
+                        "expected_reward": 600000000000u64,
+                        "height": 3000000,
+                        "prev_hash": HASH_AA,
+                        "reserved_offset": 44,
+                        "seed_hash": HASH_BB,
+                        "status": "OK"
+                    })
+                }
+                "submit_block" => match request["params"][0].as_str().unwrap().contains("deadbeef") {
+                    true => serde_json::json!({"status": "OK"}),
+                    false => return Ok(br#"{"error":{"code":-7,"message":"Block not accepted"}}"#.to_vec()),
+                },
+                _ => unreachable!(),
+            };
+            Ok(serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": result}).to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn block_template_mining() {
+        use libmonero::blocks::{get_block_template, submit_block, DaemonNode};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(MiningTransport));
+        let address = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+        assert!(get_block_template(address, 256, node.clone()).is_err());
+        let mut template = get_block_template(address, 4, node.clone()).unwrap();
+        assert_eq!((template.height, template.reserved_offset, template.prev_hash.to_hex()), (3000000, 44, HASH_AA.to_string()));
+        template.set_nonce(0xefbeadde).unwrap();
+        assert_eq!(template.blocktemplate_blob[36..40], [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(template.blockhashing_blob[36..40], [0xde, 0xad, 0xbe, 0xef]);
+        template.set_reserved(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(template.blocktemplate_blob[44..48], [1, 2, 3, 4]);
+        assert!(template.set_reserved(&[0; 5]).is_err());
+        submit_block(&template.blocktemplate_blob, node.clone()).unwrap();
+        assert!(submit_block(&[0; 80], node).err().unwrap().contains("Block not accepted"));
+    }
+
+    #[test]
+    fn block_template_extra_nonce() {
+        use libmonero::blocks::{
+            nonce_ranges, BlockHash, BlockTemplate, ExtraNonceCounter, ParsedBlock, RctBase, RctType, Transaction, TransactionPrefix, TxExtra, TxHash, TxInput,
+            TxOutTarget, TxOutput, TxSignatures,
+        };
+        use libmonero::keys::{OneTimeKey, PrivateKey};
+
+        // Template whose miner transaction reserves 8 bytes in its extra nonce, like get_block_template returns
+        let tx_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap().public_key();
+        let miner_tx = Transaction {
+            prefix: TransactionPrefix {
+                version: 2,
+                unlock_time: 3000060,
+                inputs: vec![TxInput::Gen { height: 3000000 }],
+                outputs: vec![TxOutput { amount: 600000000000, target: TxOutTarget::ToTaggedKey { key: OneTimeKey::from(tx_key), view_tag: 0x42 } }],
+                extra: TxExtra::new().with_tx_public_key(&tx_key).with_nonce(vec![0xee; 8]).unwrap().to_bytes(),
+            },
+            signatures: TxSignatures::V2 { base: RctBase { rct_type: RctType::Null, fee: 0, pseudo_outs: Vec::new(), encrypted_amounts: Vec::new(), commitments: Vec::new() }, prunable: Vec::new() },
+        };
+        let block = ParsedBlock { major_version: 16, minor_version: 16, timestamp: 1700000000, prev_id: BlockHash::from_hex(HASH_AA).unwrap(), nonce: 0, miner_tx, tx_hashes: vec![TxHash::from_hex(HASH_BB).unwrap()] };
+        let mut blob = block.to_blob();
+        let reserved_offset = blob.windows(8).position(|window| window == [0xee; 8]).unwrap();
+        blob[reserved_offset..reserved_offset + 8].copy_from_slice(&[0; 8]);
+        let original_hashing_blob = ParsedBlock::from_blob(&blob).unwrap().hashing_blob();
+        let mut template = BlockTemplate {
+            blocktemplate_blob: blob.clone(),
+            blockhashing_blob: original_hashing_blob.clone(),
+            difficulty: 300000000000,
+            difficulty_top64: 0,
+            wide_difficulty: "0x45d964b800".to_string(),
+            expected_reward: 600000000000,
+            height: 3000000,
+            prev_hash: BlockHash::from_hex(HASH_AA).unwrap(),
+            reserved_offset,
+            reserve_size: 8,
+            seed_hash: HASH_CC.to_string(),
+            next_seed_hash: String::new(),
+            untrusted: false,
+        };
+
+        // Each worker gets its own extra nonce, hence its own Merkle root, and keeps the nonce it set
+        let mut counter = ExtraNonceCounter::new(&[0x01, 0x02], template.reserve_size).unwrap();
+        template.set_nonce(0x01020304).unwrap();
+        let mut workers = Vec::new();
+        for _ in 0..3 {
+            let mut worker = template.clone();
+            let extra_nonce = counter.next_extra_nonce().unwrap();
+            assert_eq!(extra_nonce.len(), 8);
+            worker.set_extra_nonce(&extra_nonce).unwrap();
+            assert_eq!(worker.blocktemplate_blob[reserved_offset..reserved_offset + 8], extra_nonce[..]);
+            let parsed = ParsedBlock::from_blob(&worker.blocktemplate_blob).unwrap();
+            assert_eq!(parsed.nonce, 0x01020304);
+            assert_eq!(parsed.miner_tx.extra().unwrap().nonce(), Some(&extra_nonce[..]));
+            assert_eq!(worker.blockhashing_blob, parsed.hashing_blob());
+            assert_eq!(worker.blockhashing_blob.len(), original_hashing_blob.len());
+            workers.push(worker.blockhashing_blob);
+        }
+        // Same header up to the nonce: versions, 5-byte timestamp varint and previous hash
+        assert_eq!(workers[0][..39], original_hashing_blob[..39]);
+        assert!(workers.iter().all(|hashing_blob| *hashing_blob != original_hashing_blob));
+        assert!(workers[0] != workers[1] && workers[1] != workers[2]);
+
+        // Shorter extra nonces zero the rest of the reserved space, so the original template comes back
+        let mut worker = template.clone();
+        worker.set_extra_nonce(&[9; 8]).unwrap();
+        worker.set_extra_nonce(&[]).unwrap();
+        assert_eq!(worker.blockhashing_blob, ParsedBlock::from_blob(&template.blocktemplate_blob).unwrap().hashing_blob());
+        assert!(worker.set_extra_nonce(&[0; 9]).is_err());
+        worker.reserved_offset = worker.blocktemplate_blob.len() - 4;
+        assert!(worker.set_extra_nonce(&[1]).is_err());
+
+        // Small counters run out
+        let mut counter = ExtraNonceCounter::new(&[0xff; 7], 8).unwrap();
+        for _ in 0..256 {
+            counter.next_extra_nonce().unwrap();
+        }
+        assert!(counter.next_extra_nonce().is_err());
+        assert!(ExtraNonceCounter::new(&[], 0).is_err());
+
+        // Nonce ranges cover the whole space without overlap
+        for workers in [1u32, 2, 7, 64] {
+            let ranges = nonce_ranges(workers);
+            assert_eq!(ranges.len(), workers as usize);
+            assert_eq!(*ranges[0].start(), 0);
+            assert_eq!(*ranges.last().unwrap().end(), u32::MAX);
+            assert!(ranges.windows(2).all(|pair| *pair[0].end() as u64 + 1 == *pair[1].start() as u64));
+            let sizes: Vec<u64> = ranges.iter().map(|range| *range.end() as u64 - *range.start() as u64 + 1).collect();
+            assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+        }
+    }
+
+    #[test]
+    fn wallet_snapshot_roundtrip() {
+        use libmonero::blocks::TxHash;
+        use libmonero::keys::{OwnedOutput, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{SnapshotOutput, SnapshotTransfer, SubaddressLabel, WalletSnapshot};
+
+        let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let private_view_key = private_spend_key.derive_view_key();
+        let owned = OwnedOutput { index: 1, amount: 5000, output_key: private_spend_key.public_key().into(), tx_public_key: private_view_key.public_key(), pre_rct: false, subaddress: (0, 0) };
+        let mut snapshot = WalletSnapshot::new(Network::Stagenet, "5AjNbhDapF6XSqgnySdRTxMm9rZE3DC7wNSq7vrMM63Pg2jmgLr8hDnhWFVmt9Bd6BeE6eDqmJR4VAFJpi1eqT7qSPQT8qf", 1500000);
+        snapshot.outputs.push(SnapshotOutput::from_owned(&owned, TxHash::from_hex(HASH_AA).unwrap(), 1400000, (0, 1), 0));
+        let mut spent = SnapshotOutput::from_owned(&owned, TxHash::from_hex(HASH_BB).unwrap(), 1400001, (1, 0), 0);
+        spent.spent_height = Some(1400100);
+        snapshot.outputs.push(spent);
+        snapshot.transfers.push(SnapshotTransfer { tx_hash: TxHash::from_hex(HASH_AA).unwrap(), height: Some(1400000), timestamp: 1700000000, account: 0, incoming: 5000, outgoing: 0, fee: 0, payment_id: None });
+        snapshot.transfers.push(SnapshotTransfer { tx_hash: TxHash::from_hex(HASH_CC).unwrap(), height: None, timestamp: 1700000100, account: 1, incoming: 0, outgoing: 4000, fee: 30, payment_id: Some("0123456789abcdef".to_string()) });
+        snapshot.subaddresses.push(SubaddressLabel { account: 0, index: 1, address: "sub".to_string(), label: "Donations".to_string() });
+
+        let json = snapshot.to_json();
+        assert!(!json.contains(private_spend_key.to_hex().as_str()));
+        assert!(!json.contains(private_view_key.to_hex().as_str()));
+        let restored = WalletSnapshot::from_json(&json).unwrap();
+        assert_eq!(restored, snapshot);
+        assert_eq!(restored.balance(None), 5000);
+        assert_eq!(restored.balance(Some(1)), 0);
+        assert_eq!(restored.label(0, 1), Some("Donations"));
+        assert!(WalletSnapshot::from_json(&json.replace("\"version\":1", "\"version\":2")).is_err());
+    }
+
+    #[test]
+    fn key_image_import_session() {
+        use libmonero::blocks::TxHash;
+        use libmonero::keys::{derive_output_key, derive_subaddress, generate_key_image, OwnedOutput, PrivateKey, ViewPair};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{KeyImageImportError, KeyImageImportSession, KeyImageRequest, KeyImageResponse, SnapshotOutput};
+
+        let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let private_view_key = private_spend_key.derive_view_key();
+        let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+        let tx_secret_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+        let output = |address: &str, index: u64, subaddress: (u32, u32)| {
+            let keys = derive_output_key(address, &tx_secret_key, index).unwrap();
+            SnapshotOutput { tx_hash: TxHash::from_hex(HASH_AA).unwrap(), height: 3000000, index, amount: 1000, output_key: keys.output_key, tx_public_key: keys.tx_public_key, subaddress, unlock_time: 0, spent_height: None, pre_rct: false }
+        };
+        let outputs = vec![output(&view_pair.address(Network::Mainnet), 0, (0, 0)), output(&derive_subaddress(&view_pair, Network::Mainnet, 1, 2), 1, (1, 2))];
+
+        let mut session = KeyImageImportSession::new();
+        let first = session.request(&outputs);
+        let second = session.request(&outputs[..1]);
+        let third = session.request(&outputs);
+        assert_eq!((first.sequence, second.sequence, third.sequence), (0, 1, 2));
+        assert_eq!(session.pending(), vec![0, 1, 2]);
+
+        // Requests and responses travel as JSON between both wallets
+        let request = KeyImageRequest::from_json(&second.to_json()).unwrap();
+        assert_eq!(request, second);
+        let response = KeyImageResponse::from_json(&request.respond(&private_view_key, &private_spend_key).unwrap().to_json()).unwrap();
+        let imported = session.import(&response).unwrap();
+        let owned = OwnedOutput { index: 0, amount: 1000, output_key: outputs[0].output_key, tx_public_key: outputs[0].tx_public_key, pre_rct: false, subaddress: (0, 0) };
+        let secret = owned.secret_key(&private_view_key, &private_spend_key);
+        assert_eq!(imported, vec![(outputs[0].output_key, generate_key_image(&secret, &secret.public_key()))]);
+
+        // Replayed and older responses are refused, newer requests stay pending
+        assert_eq!(session.import(&response), Err(KeyImageImportError::AlreadyImported(1)));
+        let stale = first.respond(&private_view_key, &private_spend_key).unwrap();
+        assert_eq!(session.import(&stale), Err(KeyImageImportError::Superseded { sequence: 0, imported: 1 }));
+        assert_eq!(session.pending(), vec![2]);
+
+        // Responses bound to another request, session or output set are refused without changing the session
+        let mut forged = third.respond(&private_view_key, &private_spend_key).unwrap();
+        forged.sequence = 5;
+        assert_eq!(session.import(&forged), Err(KeyImageImportError::UnknownRequest(5)));
+        forged.sequence = 2;
+        forged.proofs[1] = stale.proofs[1].clone();
+        assert_eq!(session.import(&forged), Err(KeyImageImportError::InvalidProof { index: 1 }));
+        forged.proofs.pop();
+        assert_eq!(session.import(&forged), Err(KeyImageImportError::OutputMismatch { index: 1 }));
+        let mut other = KeyImageImportSession::new();
+        let foreign = other.request(&outputs).respond(&private_view_key, &private_spend_key).unwrap();
+        assert_eq!(session.import(&foreign), Err(KeyImageImportError::WrongSession));
+        let imported = session.import(&third.respond(&private_view_key, &private_spend_key).unwrap()).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert!(session.pending().is_empty());
+
+        // The cold wallet refuses outputs of another wallet
+        let other_key = PrivateKey::from_hex("6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e").unwrap();
+        assert!(third.respond(&other_key.derive_view_key(), &other_key).is_err());
+    }
+
+    #[test]
+    fn auto_lock_keystore() {
+        use libmonero::keys::{derive_output_key, OwnedOutput, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{AutoLockKeyStore, KeysFile, Signer};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+        use zeroize::Zeroizing;
+
+        let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let keys_file = KeysFile::new(Network::Mainnet, private_spend_key.clone(), 1700000000).to_bytes(b"0000", 1).unwrap();
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let entered = Arc::new(Mutex::new("0000".to_string()));
+        let (counter, passphrase) = (prompts.clone(), entered.clone());
+        let keystore = AutoLockKeyStore::new(keys_file, Duration::from_millis(500), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Zeroizing::new(passphrase.lock().unwrap().clone()))
+        });
+        let signer = Signer::new(keystore.clone(), private_spend_key.derive_view_key()).unwrap();
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+        let tx_secret_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+        let keys = derive_output_key(&signer.view_pair().address(Network::Mainnet), &tx_secret_key, 0).unwrap();
+        let output = OwnedOutput { index: 0, amount: 1000, output_key: keys.output_key, tx_public_key: keys.tx_public_key, pre_rct: false, subaddress: (0, 0) };
+
+        // Operations within the idle timeout use the decrypted key
+        let key_image = signer.key_image(&output).unwrap();
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+        assert!(!keystore.lock_if_idle());
+
+        // Once idle the key is wiped and the passphrase asked again, a wrong one fails the operation
+        let timer = keystore.start_timer(Duration::from_millis(20));
+        std::thread::sleep(Duration::from_millis(1000));
+        assert!(keystore.is_locked());
+        timer.stop();
+        *entered.lock().unwrap() = "1111".to_string();
+        assert!(signer.key_image(&output).is_err());
+        assert!(keystore.is_locked());
+        *entered.lock().unwrap() = "0000".to_string();
+        assert_eq!(signer.key_image(&output).unwrap(), key_image);
+        assert_eq!(prompts.load(Ordering::SeqCst), 3);
+        keystore.lock();
+        assert!(signer.key_image(&output).is_ok());
+        assert_eq!(prompts.load(Ordering::SeqCst), 4);
+
+        // Cancelled prompts and watch-only files leave the keystore locked
+        keystore.lock();
+        let cancelled = AutoLockKeyStore::new(Vec::new(), Duration::from_secs(60), || Err("Cancelled".to_string()));
+        assert_eq!(Signer::new(cancelled.clone(), private_spend_key.derive_view_key()).unwrap_err(), "Cancelled");
+        assert!(cancelled.is_locked());
+        let watch_only = KeysFile::watch_only(Network::Mainnet, private_spend_key.public_key(), private_spend_key.derive_view_key(), 0).to_bytes(b"", 1).unwrap();
+        assert!(Signer::new(AutoLockKeyStore::new(watch_only, Duration::from_secs(60), || Ok(Zeroizing::new(String::new()))), private_spend_key.derive_view_key()).is_err());
+    }
+
+    #[test]
+    fn keystore_signer() {
+        use libmonero::blocks::TxHash;
+        use libmonero::keys::{commit, derive_output_key, derive_subaddress, generate_key_image, OneTimeKey, OwnedOutput, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{KeyImageImportSession, MemoryKeyStore, Signer, SnapshotOutput};
+        use curve25519_dalek::Scalar;
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let private_view_key = private_spend_key.derive_view_key();
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key.clone()), private_view_key.clone()).unwrap();
+        let tx_secret_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+        let subaddress = derive_subaddress(signer.view_pair(), Network::Mainnet, 1, 2);
+        let outputs: Vec<OwnedOutput> = [(signer.view_pair().address(Network::Mainnet), (0, 0)), (subaddress, (1, 2))]
+            .iter()
+            .enumerate()
+            .map(|(index, (address, subaddress))| {
+                let keys = derive_output_key(address, &tx_secret_key, index as u64).unwrap();
+                OwnedOutput { index: index as u64, amount: 1000, output_key: keys.output_key, tx_public_key: keys.tx_public_key, pre_rct: false, subaddress: *subaddress }
+            })
+            .collect();
+
+        // Key images and proofs match the ones computed with the raw keys
+        let key_images = signer.key_images(&outputs).unwrap();
+        for (output, key_image) in outputs.iter().zip(key_images.iter()) {
+            let secret = output.secret_key(&private_view_key, &private_spend_key);
+            assert_eq!(*key_image, generate_key_image(&secret, &secret.public_key()));
+            assert_eq!(signer.key_image(output).unwrap(), *key_image);
+            let proof = signer.prove_output_ownership(output, b"audit").unwrap();
+            assert_eq!(proof.key_image, *key_image);
+            assert!(proof.verify(b"audit"));
+        }
+
+        // CLSAG of an input spending the subaddress output
+        let (mask, pseudo_mask) = (Scalar::from(5u64), Scalar::from(9u64));
+        let decoy = PrivateKey::from_hex("6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e").unwrap();
+        let ring = vec![(outputs[1].output_key, commit(1000, &mask)), (OneTimeKey::from(decoy.public_key()), commit(3, &Scalar::ONE))];
+        let message = [0x24; 32];
+        let signature = signer.sign_clsag(&outputs[1], &message, &ring, 0, &mask, &pseudo_mask).unwrap();
+        assert!(signature.verify(&message, &ring, &key_images[1], &commit(1000, &pseudo_mask)));
+        assert!(signer.sign_clsag(&outputs[0], &message, &ring, 0, &mask, &pseudo_mask).is_err());
+
+        // Key image requests of a view-only wallet are answered by the signer
+        let snapshot_outputs: Vec<SnapshotOutput> = outputs.iter().map(|output| SnapshotOutput::from_owned(output, TxHash::from_hex(HASH_AA).unwrap(), 3000000, output.subaddress, 0)).collect();
+        let mut session = KeyImageImportSession::new();
+        let request = session.request(&snapshot_outputs);
+        let imported = session.import(&signer.respond_key_images(&request).unwrap()).unwrap();
+        assert_eq!(imported.iter().map(|(_, key_image)| *key_image).collect::<Vec<_>>(), key_images);
+
+        // The spend key is loaded once per operation, refusals of the keystore are passed on
+        let loads = Arc::new(AtomicUsize::new(0));
+        let locked = Arc::new(AtomicBool::new(false));
+        let (counter, lock) = (loads.clone(), locked.clone());
+        let key = private_spend_key.clone();
+        let signer = Signer::new(
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                match lock.load(Ordering::SeqCst) {
+                    true => Err("User authentication required".to_string()),
+                    false => Ok(key.clone()),
+                }
+            },
+            private_view_key.clone(),
+        )
+        .unwrap();
+        assert_eq!(signer.key_images(&outputs).unwrap(), key_images);
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        locked.store(true, Ordering::SeqCst);
+        assert_eq!(signer.key_image(&outputs[0]), Err("User authentication required".to_string()));
+
+        // Outputs of another wallet are refused
+        let other = Signer::new(MemoryKeyStore::new(decoy.clone()), decoy.derive_view_key()).unwrap();
+        assert!(other.key_image(&outputs[0]).is_err());
+        assert!(other.prove_output_ownership(&outputs[1], b"audit").is_err());
+    }
+
+    #[test]
+    fn wallet_keys_files() {
+        use libmonero::blocks::{epee_to_bytes, EpeeValue};
+        use libmonero::crypt::{chacha20, chacha8, generate_chacha_key};
+        use libmonero::keys::PrivateKey;
+        use libmonero::utils::Network;
+        use libmonero::wallet::KeysFile;
+
+        let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let keys_file = KeysFile::new(Network::Stagenet, private_spend_key.clone(), 1700000000).with_refresh_height(1500000);
+        let path = std::env::temp_dir().join(format!("libmonero_wallet_{}.keys", std::process::id()));
+        keys_file.to_file(&path, b"correct horse", 2).unwrap();
+        let opened = KeysFile::from_file(&path, b"correct horse", 2).unwrap();
+        assert!(KeysFile::open(&path, b"correct horse").is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(opened, keys_file);
+        keys_file.create(&path, b"correct horse").unwrap();
+        assert!(keys_file.create(&path, b"other").is_err());
+        assert_eq!(KeysFile::open(&path, b"correct horse").unwrap(), keys_file);
+        std::fs::remove_file(&path).unwrap();
+        assert!(opened.address().starts_with('5'));
+        let bytes = keys_file.to_bytes(b"", 1).unwrap();
+        assert!(KeysFile::from_bytes(&bytes, b"", 2).is_err());
+        assert!(KeysFile::from_bytes(&bytes[..bytes.len() - 1], b"", 1).is_err());
+
+        let watch_only = KeysFile::watch_only(Network::Mainnet, private_spend_key.public_key(), private_spend_key.derive_view_key(), 0);
+        let opened = KeysFile::from_bytes(&watch_only.to_bytes(b"view", 1).unwrap(), b"view", 1).unwrap();
+        assert_eq!(opened.private_spend_key, None);
+        assert_eq!(opened.address(), "4AQ3jTJg91yNGTXjo9iWr1ekjBGJ5mM6HEsxKqoKddHnRwJTVJYnyLXeerff6iTys5Eo8dyG87tfqZNS5CcSd7U694YiR8J");
+
+        // A ChaCha8 file whose secret keys are encrypted too, as written by older official wallets
+        let key = generate_chacha_key(b"old", 1).unwrap();
+        let memory_key = generate_chacha_key(&[key.as_slice(), b"k"].concat(), 1).unwrap();
+        let encryption_iv = [3u8; 8];
+        let key_stream = chacha20(&[0u8; 64], &memory_key, &encryption_iv);
+        let xor = |key: &[u8; 32], stream: &[u8]| key.iter().zip(stream).map(|(a, b)| a ^ b).collect::<Vec<u8>>();
+        let view_key = private_spend_key.derive_view_key();
+        let account = vec![
+            (
+                "m_keys".to_string(),
+                EpeeValue::Object(vec![
+                    (
+                        "m_account_address".to_string(),
+                        EpeeValue::Object(vec![
+                            ("m_spend_public_key".to_string(), EpeeValue::Blob(private_spend_key.public_key().as_bytes().to_vec())),
+                            ("m_view_public_key".to_string(), EpeeValue::Blob(view_key.public_key().as_bytes().to_vec())),
+                        ]),
+                    ),
+                    ("m_spend_secret_key".to_string(), EpeeValue::Blob(xor(private_spend_key.as_bytes(), &key_stream[..32]))),
+                    ("m_view_secret_key".to_string(), EpeeValue::Blob(xor(view_key.as_bytes(), &key_stream[32..]))),
+                    ("m_encryption_iv".to_string(), EpeeValue::Blob(encryption_iv.to_vec())),
+                ]),
+            ),
+            ("m_creation_timestamp".to_string(), EpeeValue::U64(1500000000)),
+        ];
+        let key_data: String = epee_to_bytes(&account).unwrap().iter().map(|byte| format!("\\u{:04x}", byte)).collect();
+        let json = format!(r#"{{"key_data":"{}","seed_language":"Deutsch","watch_only":0,"refresh_height":1234567,"nettype":1,"encrypted_secret_keys":1}}"#, key_data);
+        let iv = [9u8; 8];
+        let encrypted = chacha8(json.as_bytes(), &key, &iv);
+        let mut file = iv.to_vec();
+        write_varint(encrypted.len() as u64, &mut file);
+        file.extend_from_slice(&encrypted);
+        let opened = KeysFile::from_bytes(&file, b"old", 1).unwrap();
+        assert_eq!(opened.private_spend_key, Some(private_spend_key));
+        assert_eq!(opened.private_view_key, view_key);
+        assert_eq!((opened.network, opened.creation_timestamp, opened.seed_language.as_str()), (Network::Testnet, 1500000000, "Deutsch"));
+        assert_eq!(opened.refresh_height, 1234567);
+    }
+
+    #[test]
+    fn timelocked_outputs() {
+        use libmonero::blocks::{timestamp_at, TxHash};
+        use libmonero::keys::{OwnedOutput, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{SnapshotOutput, WalletSnapshot};
+
+        let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let owned = OwnedOutput { index: 0, amount: 7000, output_key: private_spend_key.public_key().into(), tx_public_key: private_spend_key.public_key(), pre_rct: false, subaddress: (0, 0) };
+        let mut snapshot = WalletSnapshot::new(Network::Mainnet, "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT", 3000000);
+        // Locked until a timestamp about 1000 blocks after the snapshot, in account 1
+        let unlock_timestamp = timestamp_at(3001000, Network::Mainnet);
+        snapshot.outputs.push(SnapshotOutput::from_owned(&owned, TxHash::from_hex(HASH_AA).unwrap(), 2999990, (1, 0), unlock_timestamp));
+        snapshot.outputs.push(SnapshotOutput::from_owned(&owned, TxHash::from_hex(HASH_BB).unwrap(), 2999995, (0, 0), 0));
+
+        let now = timestamp_at(3000000, Network::Mainnet);
+        assert_eq!(snapshot.spendable_at(None, 3000000, now).len(), 0);
+        assert_eq!(snapshot.locked_at(Some(1), 3000000, now).len(), 1);
+        assert_eq!(snapshot.unlocked_balance_at(Some(0), 3000005, now), 7000);
+        assert_eq!(snapshot.unlocked_balance_at(None, 3000005, unlock_timestamp - 120), 14000);
+        let schedule = snapshot.unlock_schedule(None);
+        assert_eq!(schedule[..2], [(3000000, 0), (3000005, 7000)]);
+        assert!(schedule[2].0.abs_diff(3001000) <= 1 && schedule[2].1 == 14000);
+        assert_eq!(snapshot.unlock_schedule(Some(1)).len(), 2);
+    }
+
+    #[test]
+    fn unmixable_outputs() {
+        use libmonero::blocks::{get_output_histogram, DaemonNode, TxHash};
+        use libmonero::keys::{OwnedOutput, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{SnapshotOutput, WalletSnapshot};
+        use std::sync::Arc;
+
+        let private_spend_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let mut owned = OwnedOutput { index: 0, amount: 20000000000, output_key: private_spend_key.public_key().into(), tx_public_key: private_spend_key.public_key(), pre_rct: true, subaddress: (0, 0) };
+        let mut snapshot = WalletSnapshot::new(Network::Mainnet, "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT", 3000000);
+        snapshot.outputs.push(SnapshotOutput::from_owned(&owned, TxHash::from_hex(HASH_AA).unwrap(), 1000000, (0, 0), 0));
+        owned.amount = 7000;
+        snapshot.outputs.push(SnapshotOutput::from_owned(&owned, TxHash::from_hex(HASH_BB).unwrap(), 1000001, (1, 0), 0));
+        owned.pre_rct = false;
+        snapshot.outputs.push(SnapshotOutput::from_owned(&owned, TxHash::from_hex(HASH_CC).unwrap(), 2000000, (0, 0), 0));
+
+        let legacy_amounts: Vec<u64> = snapshot.pre_rct_outputs(None).iter().map(|output| output.amount).collect();
+        assert_eq!(legacy_amounts, vec![20000000000, 7000]);
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(JsonRpcTransport));
+        let histogram = get_output_histogram(&legacy_amounts, true, node).unwrap();
+        let unmixable = snapshot.unmixable_outputs(None, &histogram, 16);
+        assert_eq!(unmixable.len(), 1);
+        assert_eq!((unmixable[0].amount, unmixable[0].subaddress), (7000, (1, 0)));
+        assert!(snapshot.unmixable_outputs(Some(0), &histogram, 16).is_empty());
+        // The flag survives a snapshot roundtrip
+        assert_eq!(WalletSnapshot::from_json(&snapshot.to_json()).unwrap().pre_rct_outputs(None).len(), 2);
+    }
+
+    struct BroadcastTransport;
+
+    impl libmonero::blocks::RpcTransport for BroadcastTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(path, "/send_raw_transaction");
+            let response = match request["tx_as_hex"].as_str().unwrap() {
+                "00" => serde_json::json!({"status": "OK", "not_relayed": request["do_not_relay"], "double_spend": false, "untrusted": false}),
+                "01" => serde_json::json!({"status": "Failed", "reason": "", "double_spend": true, "fee_too_low": false, "overspend": true, "untrusted": false}),
+                _ => return Ok(b"<html>Bad gateway</html>".to_vec()),
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn broadcast_transaction() {
+        use libmonero::blocks::{send_raw_transaction, DaemonNode};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(BroadcastTransport));
+        let accepted = send_raw_transaction("00".to_string(), true, node.clone()).unwrap();
+        assert!(accepted.accepted && accepted.not_relayed);
+        assert!(accepted.rejection_reasons().is_empty());
+        let rejected = send_raw_transaction("01".to_string(), false, node.clone()).unwrap();
+        assert!(!rejected.accepted);
+        assert_eq!(rejected.rejection_reasons(), vec!["double_spend", "overspend"]);
+        assert!(send_raw_transaction("02".to_string(), false, node).is_err());
+    }
+
+    struct PoolTransport;
+
+    impl libmonero::blocks::RpcTransport for PoolTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, _body: &[u8]) -> Result<Vec<u8>, String> {
+            let response = match path {
+                "/get_transaction_pool" => serde_json::json!({
+                    "status": "OK",
+                    "untrusted": false,
+                    "transactions": [{"id_hash": HASH_CC, "tx_json": CLSAG_TX_JSON, "blob_size": 1500, "weight": 1500, "fee": 2605200000u64, "receive_time": 1700000000, "relayed": true, "double_spend_seen": false, "kept_by_block": false}],
+                }),
+                "/get_transaction_pool_hashes" => serde_json::json!({"status": "OK", "tx_hashes": [HASH_CC], "untrusted": false}),
+                _ => return Err("unsupported".to_string()),
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn transaction_pool_payments() {
+        use libmonero::blocks::{get_transaction_pool, get_transaction_pool_hashes, DaemonNode, TxHash};
+        use libmonero::keys::{scan_tx_for_outputs, PrivateKey};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(PoolTransport));
+        assert_eq!(get_transaction_pool_hashes(node.clone()).unwrap(), vec![TxHash::from_hex(HASH_CC).unwrap()]);
+        let pool = get_transaction_pool(node).unwrap();
+        assert_eq!(pool.len(), 1);
+        assert_eq!((pool[0].id_hash.to_hex(), pool[0].fee, pool[0].relayed), (HASH_CC.to_string(), 2605200000, true));
+        // Unconfirmed payments are found by scanning the decoded pool transactions
+        let view_key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let owned = scan_tx_for_outputs(&view_key, &spend_key.public_key(), &pool[0].tx);
+        assert_eq!(owned[0].amount, 60363387616637);
+    }
+
+    struct DecoyDataTransport;
+
+    impl libmonero::blocks::RpcTransport for DecoyDataTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let response = match path {
+                "/get_outs" => {
+                    assert_eq!(request["get_txid"], true);
+                    let outs: Vec<serde_json::Value> = request["outputs"].as_array().unwrap().iter().map(|output| serde_json::json!({
+                        "height": 3000000 + output["index"].as_u64().unwrap(),
+                        "key": HASH_AA,
+                        "mask": HASH_BB,
+                        "txid": HASH_CC,
+                        "unlocked": true,
+                    })).collect();
+                    serde_json::json!({"outs": outs, "status": "OK", "untrusted": false})
+                }
+                "/json_rpc" => {
+                    assert_eq!(request["method"], "get_output_distribution");
+                    assert_eq!(request["params"]["cumulative"], true);
+                    serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": {"distributions": [{"amount": 0, "base": 100, "distribution": [105, 111, 120], "start_height": request["params"]["from_height"]}], "status": "OK"}})
+                }
+                _ => return Err("unsupported".to_string()),
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn decoy_selection_data() {
+        use libmonero::blocks::{get_output_distribution, get_outs, DaemonNode, OutputDistribution};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DecoyDataTransport));
+        let outs = get_outs(&[7, 9], node.clone()).unwrap();
+        assert_eq!(outs.iter().map(|out| out.height).collect::<Vec<u64>>(), vec![3000007, 3000009]);
+        assert_eq!((outs[1].key.to_hex(), outs[1].mask.to_hex(), outs[1].txid.to_hex()), (HASH_AA.to_string(), HASH_BB.to_string(), HASH_CC.to_string()));
+        let distributions = get_output_distribution(&[0], 2999998, 0, node).unwrap();
+        assert_eq!(distributions, vec![OutputDistribution { amount: 0, start_height: 2999998, base: 100, distribution: vec![105, 111, 120] }]);
+    }
+
+    const EPEE_SIGNATURE: [u8; 9] = [0x01, 0x11, 0x01, 0x01, 0x01, 0x01, 0x02, 0x01, 0x01];
+
+    struct OutputIndexesTransport;
+
+    impl libmonero::blocks::RpcTransport for OutputIndexesTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            assert_eq!(path, "/get_o_indexes.bin");
+            // One entry, "txid", a 32-byte string
+            let mut expected = EPEE_SIGNATURE.to_vec();
+            expected.extend_from_slice(&[0x04, 0x04]);
+            expected.extend_from_slice(b"txid");
+            expected.extend_from_slice(&[0x0a, 32 << 2]);
+            expected.extend_from_slice(&[0xaa; 32]);
+            assert_eq!(body, expected.as_slice());
+            // Three entries: "o_indexes", an array of two u64, "status" and "untrusted"
+            let mut response = EPEE_SIGNATURE.to_vec();
+            response.extend_from_slice(&[3 << 2, 9]);
+            response.extend_from_slice(b"o_indexes");
+            response.extend_from_slice(&[0x85, 2 << 2]);
+            response.extend_from_slice(&5u64.to_le_bytes());
+            response.extend_from_slice(&1234567u64.to_le_bytes());
+            response.push(6);
+            response.extend_from_slice(b"status");
+            response.extend_from_slice(&[0x0a, 2 << 2]);
+            response.extend_from_slice(b"OK");
+            response.push(9);
+            response.extend_from_slice(b"untrusted");
+            response.extend_from_slice(&[0x0b, 0]);
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn global_output_indexes() {
+        use libmonero::blocks::{get_global_output_indexes, DaemonNode, TxHash};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(OutputIndexesTransport));
+        assert_eq!(get_global_output_indexes(TxHash::from_hex(HASH_AA).unwrap(), node).unwrap(), vec![5, 1234567]);
+    }
+
+    // Chain of 1100 blocks with 10 RingCT outputs each, outputs whose index is a multiple of 7 are locked
+    struct DryRunTransport;
+
+    impl libmonero::blocks::RpcTransport for DryRunTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            use libmonero::blocks::{epee_from_bytes, epee_get, epee_to_bytes, EpeeValue};
+
+            if path == "/get_o_indexes.bin" {
+                assert_eq!(epee_get(&epee_from_bytes(body)?, "txid").unwrap().as_blob().unwrap().len(), 32);
+                let indexes = EpeeValue::Array(EpeeValue::U64(0).type_code(), vec![EpeeValue::U64(5000), EpeeValue::U64(5001)]);
+                return epee_to_bytes(&vec![("o_indexes".to_string(), indexes), ("status".to_string(), EpeeValue::Blob(b"OK".to_vec()))]);
+            }
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let response = match path {
+                "/get_outs" => {
+                    let outs: Vec<serde_json::Value> = request["outputs"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|output| {
+                            let index = output["index"].as_u64().unwrap();
+                            serde_json::json!({"height": index / 10, "key": format!("{:064x}", index), "mask": HASH_CC, "txid": HASH_BB, "unlocked": index % 7 != 0})
+                        })
+                        .collect();
+                    serde_json::json!({"outs": outs, "status": "OK"})
+                }
+                _ => {
+                    let result = match request["method"].as_str().unwrap() {
+                        "get_last_block_header" => serde_json::json!({"block_header": {"height": 1099, "timestamp": 1700000000, "hash": HASH_AA, "prev_hash": HASH_BB, "miner_tx_hash": HASH_CC}, "status": "OK"}),
+                        "get_fee_estimate" => serde_json::json!({"fee": 20000, "fees": [20000, 80000, 320000, 4000000], "quantization_mask": 10000, "status": "OK"}),
+                        "get_output_distribution" => {
+                            let distribution: Vec<u64> = (1..=1100).map(|height| height * 10).collect();
+                            serde_json::json!({"distributions": [{"amount": 0, "start_height": 0, "base": 0, "distribution": distribution}], "status": "OK"})
+                        }
+                        method => panic!("Unexpected method {}", method),
+                    };
+                    serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": result})
+                }
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    // Picks the lowest indices that are not excluded
+    struct FirstDecoys;
+
+    impl libmonero::wallet::DecoySource for FirstDecoys {
+        fn pick_decoys(&self, _distribution: &libmonero::blocks::OutputDistribution, exclude: &std::collections::BTreeSet<u64>, count: usize) -> Result<Vec<u64>, String> {
+            Ok((0..).filter(|index| !exclude.contains(index)).take(count).collect())
+        }
+    }
+
+    #[test]
+    fn gamma_decoys() {
+        use libmonero::blocks::OutputDistribution;
+        use libmonero::wallet::{DecoySource, GammaDecoys, UniformDecoys};
+        use std::collections::BTreeSet;
+
+        // A year and a half of blocks with 10 outputs each
+        let blocks = 400000u64;
+        let distribution = OutputDistribution { amount: 0, start_height: 0, base: 0, distribution: (1..=blocks).map(|height| height * 10).collect() };
+        let spendable = (blocks - 10) * 10;
+        let exclude = BTreeSet::from([spendable - 1, spendable - 2, spendable - 3]);
+        let mut ages: Vec<u64> = Vec::new();
+        for _ in 0..20 {
+            let decoys = GammaDecoys.pick_decoys(&distribution, &exclude, 15).unwrap();
+            assert_eq!(decoys.len(), 15);
+            assert!(decoys.iter().all(|index| *index < spendable && !exclude.contains(index)));
+            ages.extend(decoys.iter().map(|index| (spendable - index) / 10));
+        }
+        // Decoys are mostly a few days old, uniform ones would be half a year old
+        ages.sort();
+        assert!(ages[ages.len() / 2] < 5000, "median age {} blocks", ages[ages.len() / 2]);
+        let uniform = UniformDecoys.pick_decoys(&distribution, &exclude, 300).unwrap();
+        assert!(uniform.iter().filter(|index| spendable - **index < 50000).count() < 100);
+
+        // A week of blocks with one output each, after a base of older outputs
+        let week = OutputDistribution { amount: 0, start_height: 3000000, base: 100000, distribution: (1..=5040).map(|height| 100000 + height).collect() };
+        let decoys = GammaDecoys.pick_decoys(&week, &BTreeSet::new(), 15).unwrap();
+        assert!(decoys.iter().all(|index| (100000..105030).contains(index)));
+
+        // Not enough spendable outputs
+        let young = OutputDistribution { amount: 0, start_height: 0, base: 0, distribution: (1..=12).map(|height| height * 2).collect() };
+        assert!(GammaDecoys.pick_decoys(&young, &BTreeSet::new(), 5).is_err());
+        assert!(GammaDecoys.pick_decoys(&young, &BTreeSet::from([0]), 4).is_err());
+        let empty = OutputDistribution { distribution: vec![0; 8], ..young };
+        assert!(GammaDecoys.pick_decoys(&empty, &BTreeSet::new(), 1).is_err());
+    }
+
+    #[test]
+    fn tx_builder_dry_run() {
+        use libmonero::blocks::{DaemonNode, FeePriority, TxHash};
+        use libmonero::keys::{OneTimeKey, PublicKey};
+        use libmonero::wallet::{ChangePolicy, SnapshotOutput, TxBuilder};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DryRunTransport));
+        let output = |tx_hash: &str, height: u64, amount: u64, subaddress: (u32, u32)| SnapshotOutput {
+            tx_hash: TxHash::from_hex(tx_hash).unwrap(),
+            height,
+            index: 1,
+            amount,
+            output_key: OneTimeKey::from_hex(&format!("{:064x}", 5001)).unwrap(),
+            tx_public_key: PublicKey::from_hex(HASH_CC).unwrap(),
+            subaddress,
+            unlock_time: 0,
+            spent_height: None,
+            pre_rct: false,
+        };
+        let mut spent = output(HASH_BB, 400, 9_000_000_000_000, (0, 1));
+        spent.spent_height = Some(900);
+        let outputs = vec![
+            output(HASH_AA, 500, 3_000_000_000_000, (0, 2)),
+            // Less than 10 blocks deep
+            output(HASH_BB, 1095, 5_000_000_000_000, (0, 2)),
+            output(HASH_BB, 600, 7_000_000_000_000, (1, 0)),
+            spent,
+        ];
+        let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+        let builder = TxBuilder::new(outputs).with_destination(address, 2_500_000_000_000).with_change_policy(ChangePolicy::SameAsInputs);
+
+        let preview = builder.clone().with_decoy_source(FirstDecoys).dry_run(node.clone()).unwrap();
+        assert_eq!(preview.inputs.len(), 1);
+        assert_eq!(preview.inputs[0].output.amount, 3_000_000_000_000);
+        assert_eq!(preview.inputs[0].global_index, 5001);
+        // Locked outputs 0, 7 and 14 are skipped
+        let ring: Vec<u64> = preview.inputs[0].ring.iter().map(|(index, _)| *index).collect();
+        assert_eq!(ring, vec![1, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 13, 15, 16, 17, 5001]);
+        // 1 input and 2 outputs at the normal priority fee
+        assert_eq!((preview.weight, preview.fee_per_byte, preview.fee), (1536, 80000, 122880000));
+        assert_eq!(preview.change, 3_000_000_000_000 - 2_500_000_000_000 - 122880000);
+        assert_eq!(preview.change_subaddress, (0, 2));
+        assert_eq!(preview.amount, 2_500_000_000_000);
+
+        // Default decoys stay among spendable outputs
+        let preview = builder.clone().with_priority(FeePriority::Low).dry_run(node.clone()).unwrap();
+        assert_eq!(preview.fee, 30720000);
+        let ring = &preview.inputs[0].ring;
+        assert_eq!(ring.len(), 16);
+        assert!(ring.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(ring.iter().all(|(index, entry)| *index < 10910 && entry.unlocked));
+
+        assert!(builder.clone().with_account(1).dry_run(node.clone()).is_ok());
+        assert!(builder.clone().with_destination(address, 1_000_000_000_000).dry_run(node.clone()).unwrap_err().starts_with("Not enough unlocked funds"));
+        assert!(TxBuilder::new(Vec::new()).dry_run(node.clone()).is_err());
+        assert!(builder.with_destination("4invalid", 1).dry_run(node).is_err());
+    }
+
+    // Answers every domain with the oa1:xmr record of the given address, unvalidated, and counts the lookups
+    struct StubResolver(&'static str, std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl libmonero::utils::OpenAliasResolver for StubResolver {
+        fn resolve_txt(&self, domain: &str) -> Result<(Vec<String>, bool), String> {
+            assert_eq!(domain, "donate.example.org");
+            self.1.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok((vec![format!("oa1:xmr recipient_address={}; recipient_name=Example;", self.0)], false))
+        }
+    }
+
+    #[test]
+    fn tx_builder_openalias() {
+        use libmonero::blocks::{DaemonNode, TxHash};
+        use libmonero::keys::{OneTimeKey, PublicKey};
+        use libmonero::wallet::{SnapshotOutput, TxBuilder};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DryRunTransport));
+        let output = SnapshotOutput {
+            tx_hash: TxHash::from_hex(HASH_AA).unwrap(),
+            height: 500,
+            index: 1,
+            amount: 3_000_000_000_000,
+            output_key: OneTimeKey::from_hex(&format!("{:064x}", 5001)).unwrap(),
+            tx_public_key: PublicKey::from_hex(HASH_CC).unwrap(),
+            subaddress: (0, 0),
+            unlock_time: 0,
+            spent_height: None,
+            pre_rct: false,
+        };
+        let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let builder = TxBuilder::new(vec![output]).with_decoy_source(FirstDecoys);
+
+        // OpenAlias destinations need a resolver, and every resolved address must be confirmed
+        let to_alias = builder.clone().with_destination("donate@example.org", 1_000_000_000_000);
+        assert!(to_alias.dry_run(node.clone()).unwrap_err().contains("no resolver"));
+        let refused = to_alias.clone().with_openalias_resolver(StubResolver(address, lookups.clone()), |resolved| resolved.dnssec_valid);
+        assert!(refused.dry_run(node.clone()).unwrap_err().contains("not confirmed"));
+        let confirmed = to_alias.with_openalias_resolver(StubResolver(address, lookups.clone()), |resolved| {
+            resolved.alias == "donate@example.org" && resolved.record.recipient_name.as_deref() == Some("Example")
+        });
+        let preview = confirmed.dry_run(node.clone()).unwrap();
+        assert_eq!(preview.destinations, vec![(address.to_string(), 1_000_000_000_000)]);
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+
+        // Sweeps resolve their destinations too, plain addresses are never looked up
+        let sweeps = builder.clone().with_openalias_resolver(StubResolver(address, lookups.clone()), |_| true).sweep_all("donate.example.org", node.clone()).unwrap();
+        assert_eq!(sweeps[0].destinations[0].0, address);
+        assert_eq!(lookups.load(Ordering::SeqCst), 3);
+        builder.with_destination(address, 1_000_000_000_000).with_openalias_resolver(StubResolver(address, lookups.clone()), |_| false).dry_run(node).unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn tx_builder_coin_selection() {
+        use libmonero::blocks::{DaemonNode, TxHash};
+        use libmonero::keys::{OneTimeKey, PublicKey};
+        use libmonero::wallet::{AvoidDust, RandomCoins, SnapshotOutput, TxBuilder};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DryRunTransport));
+        // DryRunTransport gives global indexes 5000 and 5001 to the outputs of every transaction
+        let output = |tx_hash: &str, index: u64, amount: u64| SnapshotOutput {
+            tx_hash: TxHash::from_hex(tx_hash).unwrap(),
+            height: 600,
+            index,
+            amount,
+            output_key: OneTimeKey::from_hex(&format!("{:064x}", 5000 + index)).unwrap(),
+            tx_public_key: PublicKey::from_hex(HASH_CC).unwrap(),
+            subaddress: (0, 0),
+            unlock_time: 0,
+            spent_height: None,
+            pre_rct: false,
+        };
+        let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+        let builder = TxBuilder::new(vec![output(HASH_AA, 1, 3_000_000_000_000), output(HASH_BB, 0, 1_000_000_000_000)]).with_decoy_source(FirstDecoys);
+        let inputs = |builder: TxBuilder| -> Vec<u64> { builder.dry_run(node.clone()).unwrap().inputs.iter().map(|input| input.output.amount).collect() };
+
+        // Largest first by default
+        let small = builder.clone().with_destination(address, 500_000_000_000);
+        assert_eq!(inputs(small.clone()), vec![3_000_000_000_000]);
+        let random = inputs(small.clone().with_coin_selector(RandomCoins));
+        assert_eq!(random.len(), 1);
+
+        // Pinned outputs are spent first, the selector only adds what is missing
+        let pinned = small.clone().with_pinned_output(TxHash::from_hex(HASH_BB).unwrap(), 0);
+        assert_eq!(inputs(pinned.clone()), vec![1_000_000_000_000]);
+        let pinned_large = builder.clone().with_destination(address, 2_000_000_000_000).with_pinned_output(TxHash::from_hex(HASH_BB).unwrap(), 0);
+        assert_eq!(inputs(pinned_large), vec![1_000_000_000_000, 3_000_000_000_000]);
+        let unknown = small.clone().with_pinned_output(TxHash::from_hex(HASH_BB).unwrap(), 1);
+        assert!(unknown.dry_run(node.clone()).unwrap_err().contains("is not spendable"));
+
+        // Dust is left alone even when it would complete the payment
+        let avoid_dust = AvoidDust { dust_threshold: 2_000_000_000_000 };
+        assert_eq!(inputs(small.clone().with_coin_selector(avoid_dust)), vec![3_000_000_000_000]);
+        let large = builder.clone().with_destination(address, 3_500_000_000_000);
+        assert_eq!(inputs(large.clone()).len(), 2);
+        assert!(large.clone().with_coin_selector(avoid_dust).dry_run(node.clone()).unwrap_err().starts_with("Not enough unlocked funds"));
+
+        // Custom selectors are checked
+        let selector = |_: &[SnapshotOutput], _: &[SnapshotOutput], _: u64, _: &dyn Fn(usize) -> u64| -> Result<Vec<usize>, String> { Ok(vec![1]) };
+        assert_eq!(inputs(small.clone().with_coin_selector(selector)), vec![1_000_000_000_000]);
+        let overflow = |_: &[SnapshotOutput], _: &[SnapshotOutput], _: u64, _: &dyn Fn(usize) -> u64| -> Result<Vec<usize>, String> { Ok(vec![2]) };
+        assert!(small.clone().with_coin_selector(overflow).dry_run(node.clone()).is_err());
+        let short = |_: &[SnapshotOutput], _: &[SnapshotOutput], _: u64, _: &dyn Fn(usize) -> u64| -> Result<Vec<usize>, String> { Ok(vec![1]) };
+        assert!(large.with_coin_selector(short).dry_run(node.clone()).unwrap_err().contains("do not cover"));
+    }
+
+    #[test]
+    fn tx_builder_sweep_split() {
+        use libmonero::blocks::{estimate_tx_weight, DaemonNode, TxHash};
+        use libmonero::keys::{KeyImage, OneTimeKey, PublicKey};
+        use libmonero::wallet::{SnapshotOutput, TxBuilder};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DryRunTransport));
+        // DryRunTransport gives global indexes 5000 and 5001 to the outputs of every transaction
+        let output = |tx_hash: &str, index: u64, amount: u64| SnapshotOutput {
+            tx_hash: TxHash::from_hex(tx_hash).unwrap(),
+            height: 600,
+            index,
+            amount,
+            output_key: OneTimeKey::from_hex(&format!("{:064x}", 5000 + index)).unwrap(),
+            tx_public_key: PublicKey::from_hex(HASH_CC).unwrap(),
+            subaddress: (0, index as u32),
+            unlock_time: 0,
+            spent_height: None,
+            pre_rct: false,
+        };
+        let builder = TxBuilder::new(vec![output(HASH_AA, 1, 3_000_000_000_000), output(HASH_BB, 0, 1_000_000_000_000)]).with_decoy_source(FirstDecoys);
+        let treasury = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+        let payroll = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+        let destinations = [(treasury.to_string(), 2), (payroll.to_string(), 1)];
+
+        let previews = builder.sweep_split(&destinations, node.clone()).unwrap();
+        assert_eq!(previews.len(), 1);
+        let preview = &previews[0];
+        assert_eq!(preview.inputs.iter().map(|input| input.global_index).collect::<Vec<u64>>(), vec![5001, 5000]);
+        assert_eq!(preview.weight, estimate_tx_weight(2, 2, 16, 44));
+        assert_eq!(preview.fee, preview.weight * 80000);
+        assert_eq!((preview.amount, preview.change), (4_000_000_000_000 - preview.fee, 0));
+        // The rounding goes to the first destination
+        let third = preview.amount / 3;
+        assert_eq!(preview.destinations, vec![(treasury.to_string(), preview.amount - third), (payroll.to_string(), third)]);
+        assert_eq!(preview.change_subaddress, (0, 0));
+
+        // Rings this large fit one input per transaction
+        let previews = builder.clone().with_ring_size(2200).sweep_split(&destinations, node.clone()).unwrap();
+        assert_eq!(previews.len(), 2);
+        for (preview, amount) in previews.iter().zip([3_000_000_000_000, 1_000_000_000_000]) {
+            assert_eq!(preview.inputs.len(), 1);
+            assert_eq!(preview.inputs[0].ring.len(), 2200);
+            assert!(preview.weight <= libmonero::wallet::MAX_TX_WEIGHT);
+            assert_eq!(preview.amount + preview.fee, amount);
+            assert_eq!(preview.destinations.iter().map(|(_, share)| share).sum::<u64>(), preview.amount);
+        }
+
+        // Sweeping everything to one address, and a single output by key image
+        let key_image = KeyImage::from_bytes([7; 32]);
+        let previews = builder.sweep_all(treasury, node.clone()).unwrap();
+        assert_eq!(previews.len(), 1);
+        assert_eq!(previews[0].destinations, vec![(treasury.to_string(), 4_000_000_000_000 - previews[0].fee)]);
+        assert!(builder.sweep_single(&key_image, treasury, node.clone()).unwrap_err().starts_with("No output with key image"));
+        let output_key = OneTimeKey::from_hex(&format!("{:064x}", 5000)).unwrap();
+        let keyed = builder.clone().with_key_images(&[(output_key, key_image)]);
+        let preview = keyed.sweep_single(&key_image, treasury, node.clone()).unwrap();
+        assert_eq!(preview.inputs.iter().map(|input| input.output.amount).collect::<Vec<u64>>(), vec![1_000_000_000_000]);
+        assert_eq!((preview.amount + preview.fee, preview.change), (1_000_000_000_000, 0));
+        assert_eq!(preview.weight, estimate_tx_weight(1, 2, 16, 44));
+        // Outputs of other accounts are not spendable
+        assert!(keyed.with_account(1).sweep_single(&key_image, treasury, node.clone()).unwrap_err().contains("spent or locked"));
+
+        assert!(builder.sweep_split(&[], node.clone()).is_err());
+        assert!(builder.sweep_split(&[(treasury.to_string(), 1), (payroll.to_string(), 0)], node.clone()).is_err());
+        assert!(builder.sweep_split(&vec![(treasury.to_string(), 1); 16], node.clone()).is_err());
+        assert!(builder.clone().with_destination(treasury, 1).sweep_split(&destinations, node).is_err());
+    }
+
+    #[test]
+    fn tx_builder_sign() {
+        use curve25519_dalek::{edwards::CompressedEdwardsY, EdwardsPoint, Scalar};
+        use libmonero::blocks::{OutputEntry, PaymentId, RctType, Transaction, TxHash, TxSignatures};
+        use libmonero::crypt::keccak::keccak256;
+        use libmonero::crypt::{BulletproofPlus, Clsag};
+        use libmonero::keys::{commit, commitment_mask, derivation_to_scalar, derive_output_key, derive_subaddress, generate_key_derivation, OneTimeKey, OwnedOutput, PrivateKey, ViewPair};
+        use libmonero::utils::{decode_address, Network};
+        use libmonero::wallet::{MemoryKeyStore, PreviewInput, Signer, SnapshotOutput, TxBuilder, TxPreview, Wallet};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1305);
+        let mut private_key = || PrivateKey::from_bytes_mod_order(rng.gen());
+        let private_spend_key = private_key();
+        let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key.clone()), view_pair.view.clone()).unwrap();
+        let recipient_key = private_key();
+        let recipient = ViewPair::new(recipient_key.public_key(), recipient_key.derive_view_key()).address(Network::Mainnet);
+
+        // Output received by subaddress (0, 1), spent in a ring of 16 members
+        let subaddress = derive_subaddress(&view_pair, Network::Mainnet, 0, 1);
+        let sender_key = private_key();
+        let keys = derive_output_key(&subaddress, &sender_key, 0).unwrap();
+        let derivation = generate_key_derivation(&decode_address(&subaddress).unwrap().public_view_key, &sender_key);
+        let amount = 5_000_000_000_000;
+        let output = SnapshotOutput {
+            tx_hash: TxHash::from_bytes([0xaa; 32]),
+            height: 1000,
+            index: 0,
+            amount,
+            output_key: keys.output_key,
+            tx_public_key: keys.tx_public_key,
+            subaddress: (0, 1),
+            unlock_time: 0,
+            spent_height: None,
+            pre_rct: false,
+        };
+        let entry = |key: OneTimeKey, mask| OutputEntry { height: 900, key, mask, txid: TxHash::from_bytes([0xbb; 32]), unlocked: true };
+        let mut ring: Vec<(u64, OutputEntry)> = (100..116).map(|index| (index, entry(OneTimeKey::from(private_key().public_key()), commit(index, &Scalar::from(index))))).collect();
+        ring[7].1 = entry(keys.output_key, commit(amount, &commitment_mask(&derivation_to_scalar(&derivation, 0))));
+        let fee = 30_720_000;
+        let preview = TxPreview {
+            inputs: vec![PreviewInput { output: output.clone(), global_index: 107, ring }],
+            destinations: vec![(recipient.clone(), 2_000_000_000_000)],
+            amount: 2_000_000_000_000,
+            fee,
+            change: amount - 2_000_000_000_000 - fee,
+            change_subaddress: (0, 1),
+            weight: 1536,
+            fee_per_byte: 20000,
+            warnings: Vec::new(),
+        };
+        let builder = TxBuilder::new(vec![output.clone()]);
+        let signed = builder.sign(&preview, &signer).unwrap();
+        let tx = Transaction::from_blob(&signed.to_blob()).unwrap();
+        assert_eq!(tx, signed.transaction);
+        assert_eq!(tx.hash().unwrap(), signed.tx_hash);
+        assert_eq!(signed.to_hex(), hex::encode(signed.to_blob()));
+        assert_eq!(tx.prefix.outputs.len(), 2);
+        assert!(tx.prefix.outputs.iter().all(|output| output.view_tag().is_some()));
+        assert!(matches!(tx.payment_id(), Some(PaymentId::Encrypted(_))));
+        assert_eq!(tx.tx_public_key(), Some(signed.tx_secret_key.public_key()));
+        assert!(signed.additional_secret_keys.is_empty());
+        let owned = OwnedOutput { index: 0, amount, output_key: keys.output_key, tx_public_key: keys.tx_public_key, pre_rct: false, subaddress: (0, 1) };
+        assert_eq!(tx.key_images(), vec![signer.key_image(&owned).unwrap()]);
+        let libmonero::blocks::TxInput::ToKey { key_offsets, .. } = &tx.prefix.inputs[0] else { panic!() };
+        assert_eq!(key_offsets, &[100, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+
+        // The recipient and the change subaddress find their outputs
+        let mut receiving = Wallet::from_keys(Network::Mainnet, recipient_key);
+        assert_eq!(receiving.scan_tx(signed.tx_hash, &tx.to_raw_tx(), 2000).iter().map(|output| output.amount).collect::<Vec<_>>(), vec![2_000_000_000_000]);
+        let mut sending = Wallet::from_keys(Network::Mainnet, private_spend_key.clone());
+        let change = sending.scan_tx(signed.tx_hash, &tx.to_raw_tx(), 2000);
+        assert_eq!((change.len(), change[0].amount, change[0].subaddress), (1, preview.change, (0, 1)));
+
+        // Range proof, ring signature and balance of the commitments
+        let TxSignatures::V2 { base, prunable } = &tx.signatures else { panic!() };
+        assert_eq!((base.rct_type, base.fee), (RctType::BulletproofPlus, fee));
+        let key = |pos: usize| -> [u8; 32] { prunable[pos..pos + 32].try_into().unwrap() };
+        assert_eq!((prunable[0], prunable[193], prunable[194 + 7 * 32]), (1, 7, 7));
+        let proof = BulletproofPlus {
+            A: key(1),
+            A1: key(33),
+            B: key(65),
+            r1: key(97),
+            s1: key(129),
+            d1: key(161),
+            L: (0..7).map(|i| key(194 + 32 * i)).collect(),
+            R: (0..7).map(|i| key(195 + 32 * (7 + i))).collect(),
+        };
+        assert!(proof.verify(&base.commitments));
+        let clsag_start = 195 + 14 * 32;
+        let clsag = Clsag { s: (0..16).map(|i| key(clsag_start + 32 * i)).collect(), c1: key(clsag_start + 512), D: key(clsag_start + 544) };
+        let pseudo_out = libmonero::keys::Commitment::from_bytes(key(clsag_start + 576));
+        assert_eq!(prunable.len(), clsag_start + 608);
+        let mut rct_base = vec![6];
+        write_varint(fee, &mut rct_base);
+        base.encrypted_amounts.iter().for_each(|amount| rct_base.extend(hex::decode(amount.to_ecdh_info().trunc_amount).unwrap()));
+        base.commitments.iter().for_each(|commitment| rct_base.extend_from_slice(commitment.as_bytes()));
+        let message = keccak256(&[tx.prefix_hash(), keccak256(&rct_base), keccak256(&prunable[1..193].iter().chain(&prunable[194..194 + 7 * 32]).chain(&prunable[195 + 7 * 32..clsag_start]).copied().collect::<Vec<u8>>())].concat());
+        let ring: Vec<(OneTimeKey, libmonero::keys::Commitment)> = preview.inputs[0].ring.iter().map(|(_, entry)| (entry.key, entry.mask)).collect();
+        assert!(clsag.verify(&message, &ring, &tx.key_images()[0], &pseudo_out));
+        let point = |bytes: &[u8; 32]| CompressedEdwardsY(*bytes).decompress().unwrap();
+        let outputs: EdwardsPoint = base.commitments.iter().map(|commitment| point(commitment.as_bytes())).sum();
+        assert_eq!(point(pseudo_out.as_bytes()), outputs + point(commit(fee, &Scalar::ZERO).as_bytes()));
+
+        // Previews that do not balance or spend outputs of another wallet are refused
+        let mut unbalanced = preview.clone();
+        unbalanced.change += 1;
+        assert!(builder.sign(&unbalanced, &signer).is_err());
+        let other = private_key();
+        let stranger = Signer::new(MemoryKeyStore::new(other.clone()), other.derive_view_key()).unwrap();
+        assert!(builder.sign(&preview, &stranger).is_err());
+
+        // Sweeps to several destinations have no change output
+        let swept = amount - fee;
+        let sweep = TxPreview { destinations: vec![(recipient.clone(), swept / 2), (subaddress.clone(), swept - swept / 2)], amount: swept, change: 0, ..preview.clone() };
+        let signed = builder.sign(&sweep, &signer).unwrap();
+        assert_eq!(signed.transaction.prefix.outputs.len(), 2);
+    }
+
+    // Replays a chain of 1100 blocks with 10 RingCT outputs each where the wallet owns output `real`, and records the
+    // broadcast transactions
+    struct SendTransport {
+        real: (u64, libmonero::keys::OneTimeKey, libmonero::keys::Commitment),
+        accept: bool,
+        broadcast: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl libmonero::blocks::RpcTransport for SendTransport {
+        fn call(&self, node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            use libmonero::keys::{commit, PrivateKey};
+
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap_or_default();
+            let response = match path {
+                "/get_outs" => {
+                    let outs: Vec<serde_json::Value> = request["outputs"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|output| {
+                            let index = output["index"].as_u64().unwrap();
+                            let (key, mask) = match index == self.real.0 {
+                                true => (self.real.1.to_hex(), self.real.2.to_hex()),
+                                false => {
+                                    let mut bytes = [0u8; 32];
+                                    bytes[..8].copy_from_slice(&(index + 1).to_le_bytes());
+                                    (PrivateKey::from_bytes_mod_order(bytes).public_key().to_hex(), commit(index, &curve25519_dalek::Scalar::from(index)).to_hex())
+                                }
+                            };
+                            serde_json::json!({"height": index / 10, "key": key, "mask": mask, "txid": HASH_BB, "unlocked": true})
+                        })
+                        .collect();
+                    serde_json::json!({"outs": outs, "status": "OK"})
+                }
+                "/send_raw_transaction" => {
+                    self.broadcast.lock().unwrap().push(request["tx_as_hex"].as_str().unwrap().to_string());
+                    match self.accept {
+                        true => serde_json::json!({"status": "OK", "not_relayed": false, "untrusted": false}),
+                        false => serde_json::json!({"status": "Failed", "reason": "", "double_spend": true, "untrusted": false}),
+                    }
+                }
+                _ => return DryRunTransport.call(node, path, body),
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn wallet_send() {
+        use libmonero::blocks::{DaemonNode, FeePriority, OutputEntry, TxHash, TxInput, TxSignatures};
+        use libmonero::keys::{commit, commitment_mask, derivation_to_scalar, derive_output_key, generate_key_derivation, OneTimeKey, PrivateKey, ViewPair};
+        use libmonero::utils::{decode_address, Network};
+        use libmonero::wallet::{MemoryKeyStore, PreviewInput, Signer, SnapshotOutput, SnapshotTransfer, SpendingPolicy, TxBuilder, TxPreview, Wallet};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::{Arc, Mutex};
+
+        let mut rng = StdRng::seed_from_u64(1272);
+        let mut private_key = || PrivateKey::from_bytes_mod_order(rng.gen());
+        let wallet = Wallet::from_keys(Network::Mainnet, private_key());
+        let recipient_key = private_key();
+        let recipient = ViewPair::new(recipient_key.public_key(), recipient_key.derive_view_key()).address(Network::Mainnet);
+
+        // The wallet is funded with 10 XMR by a transaction of another wallet, signed from a made-up ring
+        let funder_key = private_key();
+        let funder = ViewPair::new(funder_key.public_key(), funder_key.derive_view_key());
+        let funder_address = funder.address(Network::Mainnet);
+        let sender_key = private_key();
+        let keys = derive_output_key(&funder_address, &sender_key, 0).unwrap();
+        let derivation = generate_key_derivation(&decode_address(&funder_address).unwrap().public_view_key, &sender_key);
+        let funds = 20_000_000_000_000;
+        let funder_output = SnapshotOutput {
+            tx_hash: TxHash::from_bytes([0xaa; 32]),
+            height: 500,
+            index: 0,
+            amount: funds,
+            output_key: keys.output_key,
+            tx_public_key: keys.tx_public_key,
+            subaddress: (0, 0),
+            unlock_time: 0,
+            spent_height: None,
+            pre_rct: false,
+        };
+        let entry = |key: OneTimeKey, mask| OutputEntry { height: 400, key, mask, txid: TxHash::from_bytes([0xbb; 32]), unlocked: true };
+        let mut ring: Vec<(u64, OutputEntry)> = (100..116).map(|index| (index, entry(OneTimeKey::from(private_key().public_key()), commit(index, &curve25519_dalek::Scalar::from(index))))).collect();
+        ring[7].1 = entry(keys.output_key, commit(funds, &commitment_mask(&derivation_to_scalar(&derivation, 0))));
+        let fee = 30_720_000;
+        let funding = TxPreview {
+            inputs: vec![PreviewInput { output: funder_output.clone(), global_index: 107, ring }],
+            destinations: vec![(wallet.address().to_string(), 10_000_000_000_000)],
+            amount: 10_000_000_000_000,
+            fee,
+            change: funds - 10_000_000_000_000 - fee,
+            change_subaddress: (0, 0),
+            weight: 1536,
+            fee_per_byte: 20000,
+            warnings: Vec::new(),
+        };
+        let funder_signer = Signer::new(MemoryKeyStore::new(funder_key.clone()), funder.view.clone()).unwrap();
+
+        // Signing enforces the spending policy of the builder
+        let limited = |policy: SpendingPolicy| TxBuilder::new(vec![funder_output.clone()]).with_spending_policy(policy);
+        let too_large = limited(SpendingPolicy::new().with_max_per_tx(10_000_000_000_000)).sign(&funding, &funder_signer).unwrap_err();
+        assert!(too_large.contains("above the limit"), "{}", too_large);
+        let refused = limited(SpendingPolicy::new().with_approval(|_: &TxPreview| Err("Needs a second signer".to_string()))).sign(&funding, &funder_signer);
+        assert!(refused.unwrap_err().contains("not approved: Needs a second signer"));
+        let daily = limited(SpendingPolicy::new().with_daily_limit(20_000_000_000_000));
+        assert!(daily.clone().sign(&funding, &funder_signer).unwrap_err().contains("needs the wallet history"));
+        let spent = SnapshotTransfer { tx_hash: TxHash::from_bytes([0xcc; 32]), height: Some(900), timestamp: 1700000000, account: 0, incoming: 0, outgoing: 10_000_000_000_000, fee, payment_id: None };
+        assert!(daily.clone().with_spending_history(std::slice::from_ref(&spent), 1700000000 + 3600).sign(&funding, &funder_signer).unwrap_err().contains("daily limit"));
+        assert!(daily.with_spending_history(&[spent], 1700000000 + 86400).sign(&funding, &funder_signer).is_ok());
+        let funding = TxBuilder::new(vec![funder_output]).sign(&funding, &funder_signer).unwrap();
+        let mut wallet = wallet;
+        let received = wallet.scan_tx(funding.tx_hash, &funding.transaction.to_raw_tx(), 1000);
+        assert_eq!(received.len(), 1);
+        let TxSignatures::V2 { base, .. } = &funding.transaction.signatures else { panic!() };
+        let real = (5000 + received[0].index, received[0].output_key, base.commitments[received[0].index as usize]);
+        let transport = Arc::new(SendTransport { real, accept: true, broadcast: Mutex::new(Vec::new()) });
+        let node = DaemonNode::cake_wallet_default().with_transport(transport.clone());
+
+        // A payment request is parsed, signed with rings of 16 members and broadcast
+        let uri = format!("monero:{}?tx_amount=1.5&tx_description=Invoice", recipient);
+        let sent = wallet.send(&uri, None, FeePriority::Normal, &node).unwrap();
+        assert_eq!(sent.destinations, vec![(recipient.clone(), 1_500_000_000_000)]);
+        assert_eq!(*transport.broadcast.lock().unwrap(), vec![sent.to_hex()]);
+        let TxInput::ToKey { key_offsets, key_image, .. } = &sent.transaction.prefix.inputs[0] else { panic!() };
+        assert_eq!(key_offsets.len(), 16);
+        assert_eq!(Some(*key_image), wallet.key_image(&received[0].output_key));
+        let mut receiving = Wallet::from_keys(Network::Mainnet, recipient_key);
+        assert_eq!(receiving.scan_tx(sent.tx_hash, &sent.transaction.to_raw_tx(), 1100)[0].amount, 1_500_000_000_000);
+
+        // The spent output is not used again until the transaction is scanned or dropped from the pool
+        assert_eq!(wallet.pending_spends(), vec![received[0].output_key]);
+        assert!(wallet.tx_builder().with_destination(&recipient, 1).dry_run(node.clone()).is_err());
+        assert!(wallet.send(&recipient, Some(2_000_000_000_000), FeePriority::Low, &node).is_err());
+        assert_eq!(transport.broadcast.lock().unwrap().len(), 1);
+        let mut scanned = wallet.clone();
+        scanned.scan_tx(sent.tx_hash, &sent.transaction.to_raw_tx(), 1100);
+        assert!(scanned.pending_spends().is_empty());
+        assert_eq!(scanned.outputs()[0].spent_height, Some(1100));
+        wallet.clear_pending_spends();
+
+        // Plain addresses and OpenAlias domains, the amount is then required
+        assert!(wallet.send(&recipient, Some(2_000_000_000_000), FeePriority::Low, &node).is_ok());
+        wallet.clear_pending_spends();
+        let address = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+        let mut aliased = wallet.clone().with_openalias_resolver(StubResolver(address, Arc::new(AtomicUsize::new(0))), |resolved| resolved.record.address.starts_with("42"));
+        assert_eq!(aliased.send("donate@example.org", Some(1_000_000_000_000), FeePriority::Normal, &node).unwrap().destinations[0].0, address);
+        assert!(wallet.send("donate@example.org", Some(1_000_000_000_000), FeePriority::Normal, &node).unwrap_err().contains("no resolver"));
+        assert_eq!(transport.broadcast.lock().unwrap().len(), 3);
+
+        // Addresses of another network are refused, resolved OpenAlias ones too
+        let testnet: &'static str = Box::leak(funder.address(Network::Testnet).into_boxed_str());
+        assert!(wallet.send(testnet, Some(1_000_000_000_000), FeePriority::Normal, &node).unwrap_err().contains("Testnet"));
+        let mut aliased = wallet.clone().with_openalias_resolver(StubResolver(testnet, Arc::new(AtomicUsize::new(0))), |_| true);
+        assert!(aliased.send("donate@example.org", Some(1_000_000_000_000), FeePriority::Normal, &node).unwrap_err().contains("Testnet"));
+        assert_eq!(transport.broadcast.lock().unwrap().len(), 3);
+
+        assert!(wallet.send(&recipient, None, FeePriority::Normal, &node).unwrap_err().contains("No amount"));
+        assert!(wallet.send(&uri, Some(2_000_000_000_000), FeePriority::Normal, &node).unwrap_err().contains("differs"));
+        assert!(wallet.send(&recipient, Some(50_000_000_000_000), FeePriority::Normal, &node).is_err());
+        let mut view_only = Wallet::view_only(Network::Mainnet, wallet.view_pair().spend, wallet.view_pair().view.clone());
+        assert!(view_only.send(&uri, None, FeePriority::Normal, &node).is_err());
+        let rejecting = DaemonNode::cake_wallet_default().with_transport(Arc::new(SendTransport { real, accept: false, broadcast: Mutex::new(Vec::new()) }));
+        assert!(wallet.send(&uri, None, FeePriority::Normal, &rejecting).unwrap_err().contains("double_spend"));
+
+        // Broadcast transactions are in the history the daily limit of the wallet's spending policy is computed from
+        let transfers = &wallet.snapshot().transfers;
+        assert_eq!((transfers[0].tx_hash, transfers[0].height, transfers[0].outgoing, transfers[0].fee), (sent.tx_hash, None, 1_500_000_000_000, sent.fee));
+        assert_eq!(transfers.len(), 2);
+        let mut limited = wallet.clone().with_spending_policy(SpendingPolicy::new().with_daily_limit(4_000_000_000_000));
+        assert!(limited.send(&recipient, Some(1_000_000_000_000), FeePriority::Normal, &node).unwrap_err().contains("daily limit"));
+        let mut limited = wallet.clone().with_spending_policy(SpendingPolicy::new().with_approval(|preview: &TxPreview| match preview.amount {
+            amount if amount > 1_000_000_000_000 => Err("Above the approval threshold".to_string()),
+            _ => Ok(()),
+        }));
+        assert!(limited.send(&recipient, Some(2_000_000_000_000), FeePriority::Normal, &node).unwrap_err().contains("approval threshold"));
+        assert_eq!(transport.broadcast.lock().unwrap().len(), 3);
+        assert!(limited.send(&recipient, Some(1_000_000_000_000), FeePriority::Normal, &node).is_ok());
+        assert_eq!(limited.snapshot().transfers.len(), 3);
+        wallet.scan_tx(sent.tx_hash, &sent.transaction.to_raw_tx(), 1100);
+        assert_eq!(wallet.snapshot().transfers[0].height, Some(1100));
+    }
+
+    #[test]
+    fn cold_signing_txsets() {
+        use libmonero::blocks::{OutputEntry, TxHash};
+        use libmonero::keys::{commit, commitment_mask, derivation_to_scalar, derive_output_key, derive_subaddress, generate_key_derivation, OneTimeKey, PrivateKey, ViewPair};
+        use libmonero::utils::{decode_address, Network};
+        use libmonero::wallet::{export_unsigned_txset, import_signed_txset, MemoryKeyStore, PreviewInput, Signer, SnapshotOutput, TxPreview, UnsignedTxSet, SIGNED_TX_PREFIX, UNSIGNED_TX_PREFIX};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1310);
+        let mut private_key = || PrivateKey::from_bytes_mod_order(rng.gen());
+        let private_spend_key = private_key();
+        let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+        let recipient_key = private_key();
+        let recipient = ViewPair::new(recipient_key.public_key(), recipient_key.derive_view_key()).address(Network::Mainnet);
+
+        // Online view-only wallet previews a payment spending an output of subaddress (0, 1)
+        let subaddress = derive_subaddress(&view_pair, Network::Mainnet, 0, 1);
+        let sender_key = private_key();
+        let keys = derive_output_key(&subaddress, &sender_key, 0).unwrap();
+        let derivation = generate_key_derivation(&decode_address(&subaddress).unwrap().public_view_key, &sender_key);
+        let amount = 5_000_000_000_000;
+        let output = SnapshotOutput {
+            tx_hash: TxHash::from_bytes([0xaa; 32]),
+            height: 1000,
+            index: 0,
+            amount,
+            output_key: keys.output_key,
+            tx_public_key: keys.tx_public_key,
+            subaddress: (0, 1),
+            unlock_time: 0,
+            spent_height: None,
+            pre_rct: false,
+        };
+        let entry = |key: OneTimeKey, mask| OutputEntry { height: 900, key, mask, txid: TxHash::from_bytes([0xbb; 32]), unlocked: true };
+        let mut ring: Vec<(u64, OutputEntry)> = (100..116).map(|index| (index, entry(OneTimeKey::from(private_key().public_key()), commit(index, &curve25519_dalek::Scalar::from(index))))).collect();
+        ring[3].1 = entry(keys.output_key, commit(amount, &commitment_mask(&derivation_to_scalar(&derivation, 0))));
+        let fee = 30_720_000;
+        let preview = TxPreview {
+            inputs: vec![PreviewInput { output, global_index: 103, ring }],
+            destinations: vec![(recipient.clone(), 2_000_000_000_000)],
+            amount: 2_000_000_000_000,
+            fee,
+            change: amount - 2_000_000_000_000 - fee,
+            change_subaddress: (0, 1),
+            weight: 1536,
+            fee_per_byte: 20000,
+            warnings: Vec::new(),
+        };
+        let unsigned_file = export_unsigned_txset(std::slice::from_ref(&preview), &view_pair).unwrap();
+        assert!(unsigned_file.starts_with(UNSIGNED_TX_PREFIX));
+
+        // Offline wallet reads the same payment, fee and change back, and signs it
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key.clone()), view_pair.view.clone()).unwrap();
+        let unsigned = UnsignedTxSet::from_bytes(&unsigned_file, signer.view_pair()).unwrap();
+        assert_eq!(unsigned.txs.len(), 1);
+        let read = &unsigned.txs[0];
+        assert_eq!((&read.destinations, read.amount, read.fee, read.change, read.change_subaddress), (&preview.destinations, preview.amount, fee, preview.change, (0, 1)));
+        assert_eq!(read.inputs[0].global_index, 103);
+        assert_eq!(read.inputs[0].output.output_key, keys.output_key);
+        assert_eq!(read.inputs[0].ring.iter().map(|(index, entry)| (*index, entry.key)).collect::<Vec<_>>(), preview.inputs[0].ring.iter().map(|(index, entry)| (*index, entry.key)).collect::<Vec<_>>());
+        let signed = unsigned.sign(&signer).unwrap();
+        let signed_file = signed.to_bytes(&signer.view_pair().view).unwrap();
+        assert!(signed_file.starts_with(SIGNED_TX_PREFIX));
+
+        // Online wallet gets the transactions to broadcast and the key images of the spent outputs
+        let imported = import_signed_txset(&signed_file, &view_pair.view).unwrap();
+        assert_eq!(imported.txs.len(), 1);
+        assert_eq!(imported.txs[0].transaction, signed.txs[0].transaction);
+        assert_eq!(imported.txs[0].tx_hash, signed.txs[0].tx_hash);
+        assert_eq!(imported.txs[0].tx_secret_key, signed.txs[0].tx_secret_key);
+        assert_eq!((&imported.txs[0].destinations, imported.txs[0].fee), (&preview.destinations, fee));
+        assert_eq!(imported.key_images, vec![(keys.output_key, imported.txs[0].transaction.key_images()[0])]);
+
+        // Files are bound to the view key of the wallet
+        let other = private_key();
+        let stranger = ViewPair::new(other.public_key(), other.derive_view_key());
+        assert!(UnsignedTxSet::from_bytes(&unsigned_file, &stranger).is_err());
+        assert!(import_signed_txset(&signed_file, &stranger.view).is_err());
+        assert!(import_signed_txset(&unsigned_file, &view_pair.view).is_err());
+        let mut tampered = signed_file.clone();
+        tampered[SIGNED_TX_PREFIX.len() + 20] ^= 1;
+        assert!(import_signed_txset(&tampered, &view_pair.view).is_err());
+    }
+
+    // Pre-RingCT amounts: 5 outputs of the first, the last one locked, 3 of the second and a mixable amount
+    const UNMIXABLE_AMOUNTS: [(u64, u64); 3] = [(1_234_567_000, 5), (7_654_321_000, 3), (20_000_000_000, 900000)];
+
+    // Daemon knowing the outputs of the pre-RingCT amounts, the wallet owning `owned` (amount, index, key)
+    struct UnmixableTransport {
+        owned: Vec<(u64, u64, libmonero::keys::OneTimeKey)>,
+        broadcast: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl UnmixableTransport {
+        fn key(&self, amount: u64, index: u64) -> libmonero::keys::OneTimeKey {
+            match self.owned.iter().find(|(owned_amount, owned_index, _)| (*owned_amount, *owned_index) == (amount, index)) {
+                Some((_, _, key)) => *key,
+                None => {
+                    let mut bytes = [0u8; 32];
+                    bytes[..8].copy_from_slice(&amount.to_le_bytes());
+                    bytes[8..16].copy_from_slice(&(index + 1).to_le_bytes());
+                    libmonero::keys::OneTimeKey::from(libmonero::keys::PrivateKey::from_bytes_mod_order(bytes).public_key())
+                }
+            }
+        }
+    }
+
+    impl libmonero::blocks::RpcTransport for UnmixableTransport {
+        fn call(&self, node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            use libmonero::blocks::{epee_to_bytes, EpeeValue};
+            use libmonero::keys::zero_commit;
+
+            if path == "/get_o_indexes.bin" {
+                let indexes = EpeeValue::Array(EpeeValue::U64(0).type_code(), self.owned.iter().map(|(_, index, _)| EpeeValue::U64(*index)).collect());
+                return epee_to_bytes(&vec![("o_indexes".to_string(), indexes), ("status".to_string(), EpeeValue::Blob(b"OK".to_vec()))]);
+            }
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap_or_default();
+            let response = match (path, request["method"].as_str()) {
+                ("/get_outs", _) => {
+                    let outs: Vec<serde_json::Value> = request["outputs"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|output| {
+                            let (amount, index) = (output["amount"].as_u64().unwrap(), output["index"].as_u64().unwrap());
+                            assert!(UNMIXABLE_AMOUNTS.iter().any(|(known, count)| *known == amount && index < *count));
+                            let unlocked = (amount, index) != (UNMIXABLE_AMOUNTS[0].0, 4);
+                            serde_json::json!({"height": 400, "key": self.key(amount, index).to_hex(), "mask": zero_commit(amount).to_hex(), "txid": HASH_BB, "unlocked": unlocked})
+                        })
+                        .collect();
+                    serde_json::json!({"outs": outs, "status": "OK"})
+                }
+                ("/send_raw_transaction", _) => {
+                    self.broadcast.lock().unwrap().push(request["tx_as_hex"].as_str().unwrap().to_string());
+                    serde_json::json!({"status": "OK", "not_relayed": false, "untrusted": false})
+                }
+                (_, Some("get_output_histogram")) => {
+                    assert_eq!(request["params"]["unlocked"], true);
+                    let histogram: Vec<serde_json::Value> = request["params"]["amounts"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|amount| UNMIXABLE_AMOUNTS.iter().find(|(known, _)| amount.as_u64() == Some(*known)))
+                        .map(|(amount, count)| {
+                            let unlocked = if *amount == UNMIXABLE_AMOUNTS[0].0 { count - 1 } else { *count };
+                            serde_json::json!({"amount": amount, "total_instances": count, "unlocked_instances": unlocked, "recent_instances": 0})
+                        })
+                        .collect();
+                    serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": {"histogram": histogram, "status": "OK"}})
+                }
+                _ => return DryRunTransport.call(node, path, body),
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn sweep_unmixable() {
+        use curve25519_dalek::{edwards::CompressedEdwardsY, EdwardsPoint};
+        use libmonero::blocks::{DaemonNode, FeePriority, RctType, Transaction, TransactionPrefix, TxExtra, TxHash, TxInput, TxOutTarget, TxOutput, TxSignatures};
+        use libmonero::crypt::keccak::keccak256;
+        use libmonero::crypt::Clsag;
+        use libmonero::keys::{commit, derive_output_key, zero_commit, Commitment, OneTimeKey, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{MemoryKeyStore, Signer, Wallet};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        use std::sync::{Arc, Mutex};
+
+        let mut rng = StdRng::seed_from_u64(1277);
+        let mut private_key = || PrivateKey::from_bytes_mod_order(rng.gen());
+        let private_spend_key = private_key();
+        let mut wallet = Wallet::from_keys(Network::Mainnet, private_spend_key.clone());
+
+        // A version 1 miner transaction pays the wallet one output of each pre-RingCT amount
+        let tx_secret_key = private_key();
+        let outputs = UNMIXABLE_AMOUNTS
+            .iter()
+            .enumerate()
+            .map(|(index, (amount, _))| {
+                let keys = derive_output_key(wallet.address(), &tx_secret_key, index as u64).unwrap();
+                TxOutput { amount: *amount, target: TxOutTarget::ToTaggedKey { key: keys.output_key, view_tag: keys.view_tag } }
+            })
+            .collect();
+        let funding = Transaction {
+            prefix: TransactionPrefix {
+                version: 1,
+                unlock_time: 0,
+                inputs: vec![TxInput::Gen { height: 500 }],
+                outputs,
+                extra: TxExtra::new().with_tx_public_key(&tx_secret_key.public_key()).to_bytes(),
+            },
+            signatures: TxSignatures::V1 { ring_signatures: Vec::new() },
+        };
+        let received = wallet.scan_tx(TxHash::from_bytes([0xaa; 32]), &funding.to_raw_tx(), 500);
+        assert_eq!(received.len(), 3);
+        assert!(received.iter().all(|output| output.pre_rct));
+        // The owned outputs are at index 2, 1 and 5000 among the outputs of their amount
+        let owned = received.iter().zip([2, 1, 5000]).map(|(output, index)| (output.amount, index, output.output_key)).collect();
+        let transport = Arc::new(UnmixableTransport { owned, broadcast: Mutex::new(Vec::new()) });
+        let node = DaemonNode::cake_wallet_default().with_transport(transport.clone());
+
+        // Both unmixable outputs are spent in rings of 3 members, all the second amount has, the mixable one is left
+        let builder = wallet.tx_builder();
+        let previews = builder.sweep_unmixable(wallet.address(), node.clone()).unwrap();
+        assert_eq!(previews.len(), 1);
+        let preview = &previews[0];
+        assert_eq!(preview.inputs.iter().map(|input| input.output.amount).collect::<Vec<_>>(), vec![7_654_321_000, 1_234_567_000]);
+        assert_eq!(preview.amount + preview.fee, 7_654_321_000 + 1_234_567_000);
+        assert_eq!((preview.change, preview.destinations[0].0.as_str()), (0, wallet.address()));
+        for input in preview.inputs.iter() {
+            assert_eq!(input.ring.len(), 3);
+            assert!(input.ring.iter().any(|(index, entry)| *index == input.global_index && entry.key == input.output.output_key));
+            assert!(input.ring.iter().all(|(index, entry)| entry.unlocked && *index != 4 && entry.mask == zero_commit(input.output.amount)));
+            assert!(input.ring.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        }
+
+        // The inputs reference their amount and their rings verify against the commitments to the clear amounts
+        let signer = Signer::new(MemoryKeyStore::new(private_spend_key), wallet.view_pair().view.clone()).unwrap();
+        let signed = builder.sign(preview, &signer).unwrap();
+        let tx = Transaction::from_blob(&signed.to_blob()).unwrap();
+        let TxSignatures::V2 { base, prunable } = &tx.signatures else { panic!() };
+        assert_eq!(base.rct_type, RctType::BulletproofPlus);
+        let key = |pos: usize| -> [u8; 32] { prunable[pos..pos + 32].try_into().unwrap() };
+        let clsag_start = 195 + 14 * 32;
+        let pseudo_outs_start = clsag_start + 2 * (3 * 32 + 64);
+        assert_eq!(prunable.len(), pseudo_outs_start + 2 * 32);
+        let mut rct_base = vec![6];
+        write_varint(base.fee, &mut rct_base);
+        base.encrypted_amounts.iter().for_each(|amount| rct_base.extend(hex::decode(amount.to_ecdh_info().trunc_amount).unwrap()));
+        base.commitments.iter().for_each(|commitment| rct_base.extend_from_slice(commitment.as_bytes()));
+        let message = keccak256(&[tx.prefix_hash(), keccak256(&rct_base), keccak256(&prunable[1..193].iter().chain(&prunable[194..194 + 7 * 32]).chain(&prunable[195 + 7 * 32..clsag_start]).copied().collect::<Vec<u8>>())].concat());
+        let point = |bytes: &[u8; 32]| CompressedEdwardsY(*bytes).decompress().unwrap();
+        let mut pseudo_sum = EdwardsPoint::default();
+        for (position, (input, key_image)) in tx.prefix.inputs.iter().zip(tx.key_images()).enumerate() {
+            let TxInput::ToKey { amount, key_offsets, .. } = input else { panic!() };
+            let offsets: Vec<u64> = key_offsets.iter().scan(0, |sum, offset| { *sum += offset; Some(*sum) }).collect();
+            let spent = preview.inputs.iter().find(|spent| spent.output.amount == *amount).unwrap();
+            assert_eq!(offsets, spent.ring.iter().map(|(index, _)| *index).collect::<Vec<_>>());
+            let ring: Vec<(OneTimeKey, Commitment)> = spent.ring.iter().map(|(_, entry)| (entry.key, entry.mask)).collect();
+            let start = clsag_start + position * (3 * 32 + 64);
+            let clsag = Clsag { s: (0..3).map(|i| key(start + 32 * i)).collect(), c1: key(start + 96), D: key(start + 128) };
+            let pseudo_out = Commitment::from_bytes(key(pseudo_outs_start + 32 * position));
+            assert!(clsag.verify(&message, &ring, &key_image, &pseudo_out));
+            pseudo_sum += point(pseudo_out.as_bytes());
+        }
+        let outputs: EdwardsPoint = base.commitments.iter().map(|commitment| point(commitment.as_bytes())).sum();
+        assert_eq!(pseudo_sum, outputs + point(commit(base.fee, &curve25519_dalek::Scalar::ZERO).as_bytes()));
+
+        // The wallet sweeps them to itself once, the mixable output can not be swept this way
+        let swept = wallet.sweep_unmixable(FeePriority::Normal, &node).unwrap();
+        assert_eq!(swept.len(), 1);
+        assert_eq!(*transport.broadcast.lock().unwrap(), vec![swept[0].to_hex()]);
+        let mut pending = wallet.pending_spends();
+        pending.sort_by_key(|key| key.to_hex());
+        let mut unmixable = vec![received[0].output_key, received[1].output_key];
+        unmixable.sort_by_key(|key| key.to_hex());
+        assert_eq!(pending, unmixable);
+        assert!(wallet.sweep_unmixable(FeePriority::Normal, &node).unwrap_err().contains("No unlocked unmixable outputs"));
+        let mut view_only = Wallet::view_only(Network::Mainnet, wallet.view_pair().spend, wallet.view_pair().view.clone());
+        assert!(view_only.sweep_unmixable(FeePriority::Normal, &node).unwrap_err().contains("View-only"));
+    }
+
+    #[test]
+    fn spending_policy() {
+        use libmonero::blocks::TxHash;
+        use libmonero::utils::Error;
+        use libmonero::wallet::{SnapshotTransfer, SpendingLimitError, SpendingPolicy, TxBuilder, TxPreview};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let now = 1700000000;
+        let preview = |amount: u64, destinations: usize| TxPreview {
+            inputs: Vec::new(),
+            destinations: vec![("4...".to_string(), amount / destinations as u64); destinations],
+            amount,
+            fee: 1_000_000,
+            change: 0,
+            change_subaddress: (0, 0),
+            weight: 1536,
+            fee_per_byte: 20000,
+            warnings: Vec::new(),
+        };
+        let transfer = |timestamp: u64, incoming: u64, outgoing: u64| SnapshotTransfer {
+            tx_hash: TxHash::from_hex(HASH_AA).unwrap(),
+            height: None,
+            timestamp,
+            account: 0,
+            incoming,
+            outgoing,
+            fee: 1_000_000,
+            payment_id: None,
+        };
+        // Only the two spendings of the last 24 hours count, incoming transfers never do
+        let history = vec![transfer(now - 90000, 0, 40_000_000), transfer(now - 80000, 0, 30_000_000), transfer(now - 10, 0, 20_000_000), transfer(now - 5, 90_000_000, 0)];
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let policy = SpendingPolicy::new()
+            .with_max_per_tx(50_000_000)
+            .with_daily_limit(100_000_000)
+            .with_approval(move |preview: &TxPreview| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                match preview.destinations.len() {
+                    1 => Ok(()),
+                    _ => Err("Batch payments need a second signer".to_string()),
+                }
+            });
+        assert_eq!(policy.check(&preview(40_000_000, 1), &history, now), Ok(()));
+        assert_eq!(policy.check(&preview(49_000_001, 1), &history, now), Err(SpendingLimitError::PerTransaction { amount: 50_000_001, limit: 50_000_000 }));
+        let builder = TxBuilder::new(Vec::new()).with_spending_policy(policy);
+        let mut busy = history.clone();
+        busy.push(transfer(now - 3600, 0, 30_000_000));
+        assert_eq!(builder.approve(&preview(40_000_000, 1), &busy, now), Err(SpendingLimitError::Daily { spent: 83_000_000, amount: 41_000_000, limit: 100_000_000 }));
+        let rejected = builder.approve(&preview(40_000_000, 2), &history, now).unwrap_err();
+        assert_eq!(rejected.to_string(), "Transaction was not approved: Batch payments need a second signer");
+        assert_eq!(Error::from(rejected).code(), 4203);
+        // Hooks only see transactions within the limits
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        // Builders without policy approve everything
+        assert!(TxBuilder::new(Vec::new()).approve(&preview(u64::MAX / 2, 3), &busy, now).is_ok());
+    }
+
+    // Reads one HTTP request from the stream, returns its headers and body
+    fn read_http_request<R: std::io::Read>(stream: &mut std::io::BufReader<R>) -> Option<(Vec<String>, Vec<u8>)> {
+        use std::io::{BufRead, Read};
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+            if line.trim_end().is_empty() {
+                break;
+            }
+            headers.push(line.trim_end().to_string());
+        }
+        let length = headers
+            .iter()
+            .find_map(|header| header.to_ascii_lowercase().strip_prefix("content-length:").map(|length| length.trim().parse::<usize>().unwrap()))
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        stream.read_exact(&mut body).ok()?;
+        Some((headers, body))
+    }
+
+    #[test]
+    fn digest_authentication() {
+        use libmonero::blocks::{get_height, DaemonNode, TrustLevel};
+        use md5::{Digest, Md5};
+        use std::io::{BufReader, Write};
+        use std::net::TcpListener;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        // Connections are served side by side, kept-alive ones stay open
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut stream = BufReader::new(stream.unwrap());
+                std::thread::spawn(move || {
+                    let md5_hex = |data: String| hex::encode(Md5::digest(data.as_bytes()));
+                    while let Some((headers, _)) = read_http_request(&mut stream) {
+                        let authorization = headers.iter().find_map(|header| header.strip_prefix("Authorization: Digest "));
+                        let authorized = authorization.is_some_and(|authorization| {
+                            let param = |name: &str| {
+                                let start = authorization.find(&format!("{}=", name)).unwrap() + name.len() + 1;
+                                authorization[start..].split(',').next().unwrap().trim_matches('"').to_string()
+                            };
+                            let ha1 = md5_hex(format!("{}:monero-rpc:secret", param("username")));
+                            let ha2 = md5_hex(format!("POST:{}", param("uri")));
+                            param("response") == md5_hex(format!("{}:c0ffee:{}:{}:auth:{}", ha1, param("nc"), param("cnonce"), ha2))
+                        });
+                        let response = match authorized {
+                            true => "HTTP/1.1 200 OK\r\nContent-Length: 17\r\n\r\n{\"height\": 12345}".to_string(),
+                            false => "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Digest qop=\"auth\",algorithm=MD5-sess,realm=\"monero-rpc\",nonce=\"c0ffee\",stale=false\r\nWWW-Authenticate: Digest qop=\"auth\",algorithm=MD5,realm=\"monero-rpc\",nonce=\"c0ffee\",stale=false\r\nContent-Length: 0\r\n\r\n".to_string(),
+                        };
+                        stream.get_mut().write_all(response.as_bytes()).unwrap();
+                    }
+                });
+            }
+        });
+
+        let node = DaemonNode::new("127.0.0.1".to_string(), port, false);
+        assert_eq!(node.trust, TrustLevel::Local);
+        assert!(get_height(node.clone()).err().unwrap().contains("requires an RPC login"));
+        assert!(format!("{:?}", node.clone().with_login("monero", "secret")).contains("monero"));
+        assert!(!format!("{:?}", node.clone().with_login("monero", "secret")).contains("secret"));
+        assert_eq!(get_height(node.clone().with_login("monero", "secret")).unwrap(), 12345);
+        assert!(get_height(node.clone().with_login("monero", "wrong")).err().unwrap().contains("rejected the RPC login"));
+
+        #[cfg(feature = "async")]
+        {
+            use libmonero::blocks::DaemonClient;
+
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            assert!(runtime.block_on(DaemonClient::new(node.clone()).get_height()).err().unwrap().contains("requires an RPC login"));
+            // The HTTP client of the transport is kept, its connection serves the challenge and every later request
+            let client = DaemonClient::new(node.clone().with_login("monero", "secret"));
+            let before = connections.load(Ordering::SeqCst);
+            for _ in 0..3 {
+                assert_eq!(runtime.block_on(client.get_height()).unwrap(), 12345);
+            }
+            assert_eq!(connections.load(Ordering::SeqCst), before + 1);
+            assert!(runtime.block_on(DaemonClient::new(node.with_login("monero", "wrong")).get_height()).err().unwrap().contains("rejected the RPC login"));
+        }
+    }
+
+    #[test]
+    fn socks5_proxy() {
+        use libmonero::blocks::{get_height, DaemonNode, ProxyConfig};
+        use std::io::{BufReader, Read, Write};
+        use std::net::TcpListener;
+        use std::sync::mpsc;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut stream = listener.incoming().next().unwrap().unwrap();
+            let mut greeting = [0u8; 2];
+            stream.read_exact(&mut greeting).unwrap();
+            let mut methods = vec![0u8; greeting[1] as usize];
+            stream.read_exact(&mut methods).unwrap();
+            assert!(methods.contains(&2));
+            // Answer like a real proxy would, the HTTP client waits for the handshake with a timer that misses instant answers
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            stream.write_all(&[5, 2]).unwrap();
+            // Username and password sub-negotiation
+            let mut length = [0u8; 2];
+            stream.read_exact(&mut length).unwrap();
+            let mut username = vec![0u8; length[1] as usize];
+            stream.read_exact(&mut username).unwrap();
+            stream.read_exact(&mut length[..1]).unwrap();
+            let mut password = vec![0u8; length[0] as usize];
+            stream.read_exact(&mut password).unwrap();
+            stream.write_all(&[1, 0]).unwrap();
+            // Connect request with a domain name, which must not be resolved locally
+            let mut request = [0u8; 5];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(request[..4], [5, 1, 0, 3]);
+            let mut target = vec![0u8; request[4] as usize + 2];
+            stream.read_exact(&mut target).unwrap();
+            stream.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0]).unwrap();
+            sender.send((String::from_utf8(username).unwrap(), String::from_utf8(password).unwrap(), target)).unwrap();
+            let mut stream = BufReader::new(stream);
+            let (headers, _) = read_http_request(&mut stream).unwrap();
+            assert!(headers[0].starts_with("POST /get_height"));
+            stream.get_mut().write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 17\r\n\r\n{\"height\": 54321}").unwrap();
+        });
+
+        let onion = "moneronodexxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx.onion";
+        let node = DaemonNode::new(onion.to_string(), 18089, false).with_proxy(ProxyConfig::socks5("127.0.0.1", port).with_auth("wallet-1", "isolation"));
+        assert!(!format!("{:?}", node).contains("isolation"));
+        assert_eq!(get_height(node).unwrap(), 54321);
+        let (username, password, target) = receiver.recv().unwrap();
+        assert_eq!((username.as_str(), password.as_str()), ("wallet-1", "isolation"));
+        assert_eq!(&target[..target.len() - 2], onion.as_bytes());
+        assert_eq!(target[target.len() - 2..], 18089u16.to_be_bytes());
+        let invalid = DaemonNode::new(onion.to_string(), 18089, false).with_proxy(ProxyConfig::socks5("127.0.0.1", port).with_auth("wallet:1", ""));
+        assert!(get_height(invalid).err().unwrap().contains("Proxy username"));
+    }
+
+    struct PoolMemberTransport {
+        height: Option<u64>,
+    }
+
+    impl libmonero::blocks::RpcTransport for PoolMemberTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, _body: &[u8]) -> Result<Vec<u8>, String> {
+            let height = self.height.ok_or("Connection refused")?;
+            let response = match path {
+                "/json_rpc" => serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": {"height": height, "top_block_hash": HASH_AA, "status": "OK"}}),
+                _ => serde_json::json!({"height": height, "status": "OK"}),
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn node_pool_failover() {
+        use libmonero::blocks::{get_height, ChainAlarm, DaemonNode, NodePool};
+        use std::sync::Arc;
+
+        let member = |name: &str, height: Option<u64>| DaemonNode::new(name.to_string(), 18081, false).with_transport(Arc::new(PoolMemberTransport { height }));
+        let pool = NodePool::new(vec![member("down", None), member("lagging", Some(3000000)), member("top", Some(3000010))]).unwrap();
+        // Unchecked nodes are tried in order, the unreachable one is skipped
+        assert_eq!(get_height(pool.node()).unwrap(), 3000000);
+        assert!(!pool.health()[0].healthy);
+
+        let alarms = pool.check_health();
+        assert!(alarms.iter().any(|alarm| matches!(alarm, ChainAlarm::NodeUnreachable { node, .. } if node == "http://down:18081")));
+        assert!(alarms.contains(&ChainAlarm::NodeLagging { node: "http://lagging:18081".to_string(), height: 3000000, best_height: 3000010 }));
+        let health = pool.health();
+        assert_eq!((health[1].healthy, health[1].lag), (false, 10));
+        assert!(health[2].healthy && health[2].latency.is_some());
+        // Calls now go to the only healthy node
+        assert_eq!(get_height(pool.node()).unwrap(), 3000010);
+        assert!(pool.with_max_lag(10).check_health().iter().all(|alarm| matches!(alarm, ChainAlarm::NodeUnreachable { .. })));
+
+        let dead = NodePool::new(vec![member("down", None), member("down-too", None)]).unwrap();
+        assert!(get_height(dead.node()).err().unwrap().contains("Every node of the pool failed"));
+        assert!(NodePool::new(Vec::new()).is_err());
+    }
+
+    // Records the paths called and holds the first call until the gate opens
+    struct GatedTransport {
+        paths: std::sync::Mutex<Vec<String>>,
+        entered: std::sync::Mutex<std::sync::mpsc::Sender<()>>,
+        gate: std::sync::Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl libmonero::blocks::RpcTransport for GatedTransport {
+        fn call(&self, node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let first = {
+                let mut paths = self.paths.lock().unwrap();
+                paths.push(path.to_string());
+                paths.len() == 1
+            };
+            if first {
+                self.entered.lock().unwrap().send(()).unwrap();
+                self.gate.lock().unwrap().recv().unwrap();
+            }
+            PoolMemberTransport { height: Some(3000000) }.call(node, path, body)
+        }
+    }
+
+    #[test]
+    fn node_pool_priority_and_cancel() {
+        use libmonero::blocks::{get_height, get_info, CancelToken, DaemonNode, NodePool, RpcPriority};
+        use std::sync::{mpsc, Arc, Mutex};
+        use std::time::Duration;
+
+        let (entered_sender, entered) = mpsc::channel();
+        let (open, gate) = mpsc::channel();
+        let transport = Arc::new(GatedTransport { paths: Mutex::new(Vec::new()), entered: Mutex::new(entered_sender), gate: Mutex::new(gate) });
+        let member = DaemonNode::new("member".to_string(), 18081, false).with_transport(transport.clone());
+        let pool = NodePool::new(vec![member]).unwrap().with_max_concurrent(1);
+        let background = pool.node().with_priority(RpcPriority::Background);
+
+        // A background call holds the only slot, another one waits for it
+        let running = { let node = background.clone(); std::thread::spawn(move || get_height(node)) };
+        entered.recv().unwrap();
+        let queued = { let node = background.clone(); std::thread::spawn(move || get_height(node)) };
+        std::thread::sleep(Duration::from_millis(100));
+        // An interactive call arriving later overtakes the waiting background call
+        let interactive = { let node = pool.node(); std::thread::spawn(move || get_info(node)) };
+        std::thread::sleep(Duration::from_millis(100));
+
+        // Cancelling a waiting call releases its place without reaching the daemon
+        let cancel = CancelToken::new();
+        let cancelled = { let node = background.clone().with_cancel(cancel.clone()); std::thread::spawn(move || get_height(node)) };
+        std::thread::sleep(Duration::from_millis(100));
+        cancel.cancel();
+        assert!(cancelled.join().unwrap().unwrap_err().contains("RPC call cancelled"));
+
+        open.send(()).unwrap();
+        assert_eq!(running.join().unwrap().unwrap(), 3000000);
+        assert_eq!(interactive.join().unwrap().unwrap().height, 3000000);
+        assert_eq!(queued.join().unwrap().unwrap(), 3000000);
+        assert_eq!(*transport.paths.lock().unwrap(), vec!["/get_height", "/json_rpc", "/get_height"]);
+        // Cancellation never marks nodes unhealthy
+        assert!(pool.health()[0].healthy);
+        assert!(get_height(pool.node().with_cancel(cancel)).is_err());
+        assert_eq!(transport.paths.lock().unwrap().len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_transport() {
+        use libmonero::blocks::{get_height, DaemonNode, TrustLevel};
+        use std::io::{BufReader, Write};
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("libmonero-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            for (index, stream) in listener.incoming().enumerate() {
+                let mut stream = BufReader::new(stream.unwrap());
+                let (headers, body) = read_http_request(&mut stream).unwrap();
+                assert!(headers[0].starts_with("POST /get_height HTTP/1.1"));
+                assert_eq!(body, b"{}");
+                // Second request gets a chunked answer, as sent by reverse proxies
+                let response = match index {
+                    0 => "HTTP/1.1 200 OK\r\nContent-Length: 17\r\n\r\n{\"height\": 11111}",
+                    _ => "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\n{\"hei\r\nc\r\nght\": 22222}\r\n0\r\n\r\n",
+                };
+                stream.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let node = DaemonNode::local_socket(&path);
+        assert_eq!(node.trust, TrustLevel::Local);
+        assert_eq!(get_height(node.clone()).unwrap(), 11111);
+        assert_eq!(get_height(node).unwrap(), 22222);
+        assert!(get_height(DaemonNode::local_socket(path.with_extension("missing"))).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn subaddress_scan_filters() {
+        use libmonero::blocks::parse_transaction_json;
+        use libmonero::keys::{derive_output_key_for, derive_subaddress, scan_tx_for_subaddresses, PrivateKey, ScanFilter, SubaddressTable, ViewPair};
+        use libmonero::utils::{decode_address, Network};
+
+        let view_key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let view_pair = ViewPair::new(spend_key.public_key(), view_key);
+        let table = SubaddressTable::new(&view_pair, 3, 20);
+        let clsag_tx = parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        let owned = scan_tx_for_subaddresses(&table, &clsag_tx, Some(3000000), &ScanFilter::default());
+        assert_eq!(owned.len(), 1);
+        assert_eq!(owned[0].subaddress, (0, 0));
+        assert_eq!(owned[0].amount, 60363387616637);
+
+        // Output of a clear amount transaction paying subaddress (2, 7)
+        let recipient = decode_address(&derive_subaddress(&view_pair, Network::Mainnet, 2, 7)).unwrap();
+        let tx_secret_key = PrivateKey::from_hex("0d13a94c82d7a60abb54d2217d38935c3f715295e30378f8848a1ca1abc8d908").unwrap();
+        let output = derive_output_key_for(&recipient, &tx_secret_key, 0);
+        let mut extra = vec![1u8];
+        extra.extend_from_slice(output.tx_public_key.as_bytes());
+        let json = format!(
+            r#"{{"version": 2, "unlock_time": 0, "vin": [], "vout": [{{"amount": 5000000, "target": {{"tagged_key": {{"key": "{}", "view_tag": "{:02x}"}}}}}}], "extra": {:?}, "rct_signatures": {{"type": 0}}}}"#,
+            output.output_key.to_hex(),
+            output.view_tag,
+            extra
+        );
+        let tx = parse_transaction_json(&json).unwrap();
+        let owned = scan_tx_for_subaddresses(&table, &tx, None, &ScanFilter::default());
+        assert_eq!(owned.len(), 1);
+        assert_eq!(owned[0].subaddress, (2, 7));
+        assert_eq!(owned[0].amount, 5000000);
+
+        assert!(scan_tx_for_subaddresses(&table, &tx, Some(100), &ScanFilter::default().with_height_range(200, None)).is_empty());
+        assert!(scan_tx_for_subaddresses(&table, &tx, None, &ScanFilter::default().with_height_range(0, Some(300))).is_empty());
+        assert!(scan_tx_for_subaddresses(&table, &tx, Some(250), &ScanFilter::default().with_accounts(&[0, 1])).is_empty());
+        assert_eq!(scan_tx_for_subaddresses(&table, &tx, Some(250), &ScanFilter::default().with_accounts(&[2])).len(), 1);
+        assert!(scan_tx_for_subaddresses(&table, &tx, Some(250), &ScanFilter::default().with_min_amount(5000001)).is_empty());
+        assert_eq!(scan_tx_for_subaddresses(&table, &tx, Some(250), &ScanFilter::default().with_min_amount(5000000)).len(), 1);
+        // Subaddresses outside the table are not found
+        assert!(scan_tx_for_subaddresses(&SubaddressTable::new(&view_pair, 2, 20), &tx, None, &ScanFilter::default()).is_empty());
+    }
+
+    struct FlakyTransport {
+        failures: std::sync::atomic::AtomicU32,
+    }
+
+    impl libmonero::blocks::RpcTransport for FlakyTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, _path: &str, _body: &[u8]) -> Result<Vec<u8>, String> {
+            use std::sync::atomic::Ordering;
+
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err("Connection reset".to_string());
+            }
+            Ok(b"{\"height\": 777, \"status\": \"OK\"}".to_vec())
+        }
+    }
+
+    #[test]
+    fn rpc_retries_and_timeouts() {
+        use libmonero::blocks::{get_height, DaemonNode, RpcConfig};
+        use std::net::TcpListener;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        let flaky = |failures: u32| DaemonNode::cake_wallet_default().with_transport(Arc::new(FlakyTransport { failures: failures.into() }));
+        let config = RpcConfig { retries: 2, backoff: Duration::from_millis(10), ..RpcConfig::default() };
+        assert!(get_height(flaky(1)).is_err());
+        assert_eq!(get_height(flaky(2).with_config(config)).unwrap(), 777);
+        assert!(get_height(flaky(3).with_config(config)).is_err());
+        assert_eq!(config.retry_delay(0), Duration::from_millis(10));
+        assert_eq!(config.retry_delay(3), Duration::from_millis(80));
+
+        // Daemon accepting the connection but never answering
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let _streams: Vec<_> = listener.incoming().collect();
+        });
+        let config = RpcConfig { request_timeout: Duration::from_millis(200), ..RpcConfig::default() };
+        let start = Instant::now();
+        assert!(get_height(DaemonNode::new("127.0.0.1".to_string(), port, false).with_config(config)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    struct BinaryEndpointsTransport;
+
+    impl libmonero::blocks::RpcTransport for BinaryEndpointsTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            use libmonero::blocks::{epee_from_bytes, epee_get, epee_to_bytes, EpeeValue};
+
+            let request = epee_from_bytes(body)?;
+            let entry = |name: &str, value: EpeeValue| (name.to_string(), value);
+            let object_type = EpeeValue::Object(Vec::new()).type_code();
+            let response = match path {
+                "/get_outs.bin" => {
+                    let outputs = epee_get(&request, "outputs").unwrap().as_array().unwrap();
+                    let outs = outputs
+                        .iter()
+                        .map(|output| {
+                            let index = epee_get(output.as_object().unwrap(), "index").unwrap().as_u64().unwrap();
+                            EpeeValue::Object(vec![
+                                entry("key", EpeeValue::Blob(vec![index as u8; 32])),
+                                entry("mask", EpeeValue::Blob(vec![0xcc; 32])),
+                                entry("unlocked", EpeeValue::Bool(index != 2)),
+                                entry("height", EpeeValue::U64(1000 + index)),
+                                entry("txid", EpeeValue::Blob(vec![0xaa; 32])),
+                            ])
+                        })
+                        .collect();
+                    vec![entry("outs", EpeeValue::Array(object_type, outs)), entry("status", EpeeValue::Blob(b"OK".to_vec()))]
+                }
+                "/get_blocks.bin" => {
+                    assert_eq!(epee_get(&request, "block_ids").unwrap().as_blob().unwrap().len(), 64);
+                    assert_eq!(epee_get(&request, "start_height").unwrap().as_u64(), Some(2000));
+                    let indices = |indices: &[u64]| EpeeValue::Object(vec![entry("indices", EpeeValue::Array(EpeeValue::U64(0).type_code(), indices.iter().map(|i| EpeeValue::U64(*i)).collect()))]);
+                    let blocks = vec![
+                        // Unpruned transactions are plain blobs
+                        EpeeValue::Object(vec![entry("block", EpeeValue::Blob(vec![1, 2, 3])), entry("txs", EpeeValue::Array(EpeeValue::Blob(Vec::new()).type_code(), vec![EpeeValue::Blob(vec![4, 5])]))]),
+                        // Pruned transactions are objects, blocks without transactions omit the array
+                        EpeeValue::Object(vec![entry("block", EpeeValue::Blob(vec![6])), entry("pruned", EpeeValue::Bool(true))]),
+                    ];
+                    let output_indices = vec![
+                        EpeeValue::Object(vec![entry("indices", EpeeValue::Array(object_type, vec![indices(&[10]), indices(&[11, 12])]))]),
+                        EpeeValue::Object(vec![entry("indices", EpeeValue::Array(object_type, vec![indices(&[13])]))]),
+                    ];
+                    vec![
+                        entry("blocks", EpeeValue::Array(object_type, blocks)),
+                        entry("current_height", EpeeValue::U64(3000000)),
+                        entry("output_indices", EpeeValue::Array(object_type, output_indices)),
+                        entry("start_height", EpeeValue::U64(2000)),
+                        entry("status", EpeeValue::Blob(b"OK".to_vec())),
+                        entry("untrusted", EpeeValue::Bool(false)),
+                    ]
+                }
+                _ => vec![entry("status", EpeeValue::Blob(b"Failed".to_vec()))],
+            };
+            epee_to_bytes(&response)
+        }
+    }
+
+    #[test]
+    fn binary_rpc_endpoints() {
+        use libmonero::blocks::{get_blocks_bin, get_outs_bin, BlockCompleteEntry, BlockHash, DaemonNode};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(BinaryEndpointsTransport));
+        let outs = get_outs_bin(&[1, 2], node.clone()).unwrap();
+        assert_eq!(outs.len(), 2);
+        assert_eq!(outs[1].key.as_bytes(), &[2; 32]);
+        assert_eq!((outs[0].height, outs[0].unlocked, outs[1].unlocked), (1001, true, false));
+        assert_eq!(outs[0].txid.to_hex(), HASH_AA);
+
+        let ids = [BlockHash::from_bytes([0xbb; 32]), BlockHash::from_bytes([0xcc; 32])];
+        let batch = get_blocks_bin(&ids, 2000, true, node.clone()).unwrap();
+        assert_eq!((batch.start_height, batch.current_height, batch.untrusted), (2000, 3000000, false));
+        assert_eq!(batch.blocks[0], BlockCompleteEntry { block: vec![1, 2, 3], txs: vec![vec![4, 5]], output_indices: vec![vec![10], vec![11, 12]] });
+        assert_eq!(batch.blocks[1], BlockCompleteEntry { block: vec![6], txs: Vec::new(), output_indices: vec![vec![13]] });
+        assert!(get_blocks_bin(&[], 0, false, node).is_err());
+    }
+
+    #[test]
+    fn deterministic_payment_ids() {
+        use libmonero::keys::{derive_subaddress, PrivateKey, ViewPair};
+        use libmonero::utils::{decode_address, derive_payment_id, make_integrated_address, Network, PaymentIdRegistry};
+
+        let private_spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let view_pair = ViewPair::new(private_spend_key.public_key(), PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap());
+        let address = derive_subaddress(&view_pair, Network::Mainnet, 0, 0);
+        let mut registry = PaymentIdRegistry::new(b"shop secret");
+        let first = registry.integrated_address(&address, "alice").unwrap();
+        let second = registry.integrated_address(&address, "bob").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(registry.len(), 2);
+        // A new registry with the same secret recognizes the same customers
+        let payment_id = decode_address(&first).unwrap().payment_id.unwrap();
+        assert_eq!(payment_id, derive_payment_id(b"shop secret", "alice"));
+        assert_ne!(payment_id, derive_payment_id(b"other secret", "alice"));
+        assert_eq!(PaymentIdRegistry::new(b"shop secret").integrated_address(&address, "alice").unwrap(), first);
+        assert_eq!(registry.lookup(&payment_id), Some("alice"));
+        assert_eq!(registry.lookup(&[0; 8]), None);
+        // Only standard addresses can be integrated
+        assert!(make_integrated_address(&first, payment_id).is_err());
+        assert!(make_integrated_address(&derive_subaddress(&view_pair, Network::Mainnet, 0, 1), payment_id).is_err());
+    }
+
+    #[test]
+    fn varint_encoding() {
+        use libmonero::utils::read_varint;
+
+        let values = [0, 1, 0x7f, 0x80, 300, 0x3fff, 0x4000, u32::MAX as u64, u64::MAX - 1, u64::MAX];
+        let mut out = Vec::new();
+        for value in values {
+            write_varint(value, &mut out);
+        }
+        assert_eq!(&out[..4], &[0x00, 0x01, 0x7f, 0x80]);
+        let mut single = Vec::new();
+        write_varint(u64::MAX, &mut single);
+        assert_eq!(single, vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+        let mut pos = 0;
+        for value in values {
+            assert_eq!(read_varint(&out, &mut pos), Ok(value));
+        }
+        assert_eq!(pos, out.len());
+
+        // Truncated, overflowing and non-canonical varints are refused
+        assert!(read_varint(&[0x80], &mut 0).is_err());
+        assert!(read_varint(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02], &mut 0).is_err());
+        assert!(read_varint(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x81, 0x00], &mut 0).is_err());
+        assert!(read_varint(&[0x81, 0x00], &mut 0).is_err());
+    }
+
+    #[test]
+    fn transaction_blob_parsing() {
+        use libmonero::blocks::{parse_transaction_blob, parse_transaction_json, EncryptedAmount, PaymentId, RctType, TxInput, TxSignatures};
+        use libmonero::keys::{scan_tx_for_outputs, KeyImage, PrivateKey};
+
+        // Blob of the outputs and RingCT data of CLSAG_TX_JSON with a ring input of two members
+        let json = parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        let mut blob = vec![2, 0, 1, 2, 0, 2, 100, 5];
+        blob.extend_from_slice(&[0xaa; 32]);
+        blob.push(json.vout.len() as u8);
+        for vout in json.vout.iter() {
+            blob.extend_from_slice(&[0, 3]);
+            blob.extend_from_slice(vout.target.tagged_key.key.as_bytes());
+            blob.extend_from_slice(&hex::decode(&vout.target.tagged_key.view_tag).unwrap());
+        }
+        write_varint(json.extra.len() as u64, &mut blob);
+        blob.extend_from_slice(&json.extra);
+        blob.push(6);
+        write_varint(json.rct_signatures.txn_fee, &mut blob);
+        for ecdh in json.rct_signatures.ecdh_info.iter() {
+            blob.extend_from_slice(&hex::decode(&ecdh.trunc_amount).unwrap());
+        }
+        for commitment in json.rct_signatures.out_pk.iter() {
+            blob.extend_from_slice(commitment.as_bytes());
+        }
+        let pruned_len = blob.len();
+        blob.extend_from_slice(&[1, 2, 3]);
+
+        let tx = parse_transaction_blob(&blob).unwrap();
+        assert!(!tx.is_coinbase());
+        assert_eq!(tx.prefix.inputs, vec![TxInput::ToKey { amount: 0, key_offsets: vec![100, 5], key_image: KeyImage::from_bytes([0xaa; 32]) }]);
+        assert_eq!(tx.fee(), 2605200000);
+        assert_eq!(tx.tx_public_key().unwrap().to_hex(), "a57c93fb0ca77ab96b7dfd7380c4842d1e58c055430e0d425cd1c76c578cca39");
+        assert!(tx.additional_public_keys().is_empty());
+        assert_eq!(tx.payment_id(), Some(PaymentId::Encrypted([149, 25, 248, 193, 206, 94, 32, 48])));
+        match &tx.signatures {
+            TxSignatures::V2 { base, prunable } => {
+                assert_eq!(base.rct_type, RctType::BulletproofPlus);
+                assert!(matches!(base.encrypted_amounts[0], EncryptedAmount::Compact { .. }));
+                assert_eq!(base.commitments, json.rct_signatures.out_pk);
+                assert_eq!(prunable, &vec![1, 2, 3]);
+            }
+            signatures => panic!("Unexpected signatures {:?}", signatures),
+        }
+        // Parsed transactions scan like the daemon's JSON
+        let view_key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let owned = scan_tx_for_outputs(&view_key, &spend_key.public_key(), &tx.to_raw_tx());
+        assert_eq!(owned, scan_tx_for_outputs(&view_key, &spend_key.public_key(), &json));
+        assert_eq!(owned[0].amount, 60363387616637);
+        // Pruned blobs lack the prunable part
+        let pruned = parse_transaction_blob(&blob[..pruned_len]).unwrap();
+        assert!(matches!(pruned.signatures, TxSignatures::V2 { ref prunable, .. } if prunable.is_empty()));
+        assert!(parse_transaction_blob(&blob[..pruned_len - 1]).is_err());
+
+        // Version 1 transaction with clear amounts and ring signatures
+        let mut blob = vec![1, 0, 1, 2];
+        write_varint(30000000000, &mut blob);
+        blob.extend_from_slice(&[2, 7, 1]);
+        blob.extend_from_slice(&[0xbb; 32]);
+        blob.push(1);
+        write_varint(20000000000, &mut blob);
+        blob.push(2);
+        blob.extend_from_slice(&[0xcc; 32]);
+        blob.extend_from_slice(&[2, 0, 0]);
+        blob.extend_from_slice(&[0xdd; 128]);
+        let tx = parse_transaction_blob(&blob).unwrap();
+        assert_eq!(tx.fee(), 10000000000);
+        assert_eq!(tx.prefix.outputs[0].view_tag(), None);
+        assert_eq!(tx.payment_id(), None);
+        assert!(matches!(tx.signatures, TxSignatures::V1 { ref ring_signatures } if ring_signatures.len() == 1 && ring_signatures[0].len() == 2));
+        blob.push(0);
+        assert!(parse_transaction_blob(&blob).is_err());
+        assert!(parse_transaction_blob(&[3, 0, 0, 0, 0]).is_err());
+    }
+
+    struct DoubleSpendPoolTransport {
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    impl libmonero::blocks::RpcTransport for DoubleSpendPoolTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, _body: &[u8]) -> Result<Vec<u8>, String> {
+            assert_eq!(path, "/get_transaction_pool");
+            let spending = |key_image: &str| format!(r#"{{"version": 2, "vin": [{{"key": {{"amount": 0, "key_offsets": [1, 2], "k_image": "{}"}}}}], "vout": [], "extra": [], "rct_signatures": {{"type": 6}}}}"#, key_image);
+            let pool_tx = |hash: &str, key_image: &str, double_spend_seen: bool| serde_json::json!({"id_hash": hash, "tx_json": spending(key_image), "double_spend_seen": double_spend_seen});
+            // The merchant's payment first, then a conflicting transaction shows up
+            let transactions = match self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                0 => vec![pool_tx(HASH_AA, HASH_CC, false)],
+                _ => vec![pool_tx(HASH_AA, HASH_CC, true), pool_tx(HASH_BB, HASH_CC, true)],
+            };
+            Ok(serde_json::json!({"status": "OK", "untrusted": false, "transactions": transactions}).to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn double_spend_watcher() {
+        use libmonero::blocks::{parse_transaction_json, DaemonNode, DoubleSpendAlert, DoubleSpendWatcher, TxHash};
+        use libmonero::keys::KeyImage;
+        use std::sync::{mpsc, Arc};
+        use std::time::Duration;
+
+        let paid = TxHash::from_hex(HASH_AA).unwrap();
+        let attacker = TxHash::from_hex(HASH_BB).unwrap();
+        let key_image = KeyImage::from_hex(HASH_CC).unwrap();
+        let tx = parse_transaction_json(&format!(r#"{{"version": 2, "vin": [{{"key": {{"amount": 0, "key_offsets": [7], "k_image": "{}"}}}}], "vout": [], "extra": [], "rct_signatures": {{"type": 6}}}}"#, HASH_CC)).unwrap();
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DoubleSpendPoolTransport { calls: 0.into() }));
+
+        let watcher = DoubleSpendWatcher::new();
+        // Nothing watched, the pool is not even queried
+        assert!(watcher.poll(&node).unwrap().is_empty());
+        watcher.watch_tx(paid, &tx);
+        assert_eq!(watcher.watched(), vec![paid]);
+        assert!(watcher.poll(&node).unwrap().is_empty());
+        let alerts = watcher.poll(&node).unwrap();
+        assert_eq!(alerts, vec![
+            DoubleSpendAlert::Flagged { watched: paid },
+            DoubleSpendAlert::Conflict { watched: paid, conflicting: attacker, key_images: vec![key_image] },
+        ]);
+        // Alerts are raised once
+        assert!(watcher.poll(&node).unwrap().is_empty());
+        watcher.unwatch(&paid);
+        assert!(watcher.watched().is_empty());
+
+        // Background watching, with the payment added after the start
+        let (sender, receiver) = mpsc::channel();
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DoubleSpendPoolTransport { calls: 1.into() }));
+        let handle = watcher.run(node, Duration::from_millis(10), move |alerts| sender.send(alerts.to_vec()).unwrap());
+        watcher.watch(paid, vec![key_image]);
+        let alerts = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(alerts.contains(&DoubleSpendAlert::Conflict { watched: paid, conflicting: attacker, key_images: vec![key_image] }));
+        handle.stop();
+        let unreachable = DaemonNode::cake_wallet_default().with_transport(Arc::new(FlakyTransport { failures: u32::MAX.into() }));
+        assert!(watcher.poll(&unreachable).is_err());
+    }
+
+    #[test]
+    fn block_blob_parsing() {
+        use libmonero::blocks::{get_block_hash, parse_transaction_blob, BlockHash, ParsedBlock, TxHash};
+        use sha3::{Digest, Keccak256};
+
+        let keccak = |data: &[u8]| -> [u8; 32] { Keccak256::digest(data).into() };
+        let pair = |left: &[u8; 32], right: &[u8; 32]| keccak(&[left.as_slice(), right.as_slice()].concat());
+
+        // Version 16 block with a version 2 miner transaction and four other transactions
+        let miner_tx = hex::decode(format!("02bc0801ff800101c0c3b2d2c62d03{}5e2101{}00", "aa".repeat(32), "66".repeat(32))).unwrap();
+        let hashes: Vec<[u8; 32]> = (1..=4).map(|i| [i; 32]).collect();
+        let mut blob = vec![16, 16];
+        write_varint(1700000000, &mut blob);
+        blob.extend_from_slice(&[0xcc; 32]);
+        blob.extend_from_slice(&0x01020304u32.to_le_bytes());
+        blob.extend_from_slice(&miner_tx);
+        blob.push(hashes.len() as u8);
+        for hash in hashes.iter() {
+            blob.extend_from_slice(hash);
+        }
+
+        let block = ParsedBlock::from_blob(&blob).unwrap();
+        assert_eq!(block.major_version, 16);
+        assert_eq!(block.timestamp, 1700000000);
+        assert_eq!(block.prev_id, BlockHash::from_bytes([0xcc; 32]));
+        assert_eq!(block.nonce, 0x01020304);
+        assert_eq!(block.miner_tx, parse_transaction_blob(&miner_tx).unwrap());
+        assert_eq!(block.miner_tx.to_blob(), miner_tx);
+        assert_eq!(block.tx_hashes, hashes.iter().map(|hash| TxHash::from_bytes(*hash)).collect::<Vec<_>>());
+        assert_eq!(block.to_blob(), blob);
+
+        // Miner transaction hash from its three parts, then the Merkle root of five hashes: the first two pair on
+        // their own level, the last three leaves are paired first
+        let miner_hash = keccak(&[keccak(&miner_tx[..miner_tx.len() - 1]), keccak(&[0]), [0; 32]].concat());
+        let leaves = [miner_hash, hashes[0], hashes[1], hashes[2], hashes[3]];
+        let root = pair(&pair(&leaves[0], &leaves[1]), &pair(&leaves[2], &pair(&leaves[3], &leaves[4])));
+        let header_len = 2 + 5 + 32 + 4;
+        let mut hashing_blob = blob[..header_len].to_vec();
+        hashing_blob.extend_from_slice(&root);
+        hashing_blob.push(5);
+        assert_eq!(block.hashing_blob(), hashing_blob);
+        let mut hashed = vec![hashing_blob.len() as u8];
+        hashed.extend_from_slice(&hashing_blob);
+        assert_eq!(block.hash(), BlockHash::from_bytes(keccak(&hashed)));
+        assert_eq!(get_block_hash(&blob).unwrap(), block.hash());
+
+        // Truncated blobs, trailing data and miner transactions with ring inputs are rejected
+        assert!(ParsedBlock::from_blob(&blob[..blob.len() - 1]).is_err());
+        assert!(ParsedBlock::from_blob(&[blob.as_slice(), &[0]].concat()).is_err());
+        let mut not_coinbase = blob[..header_len].to_vec();
+        not_coinbase.extend_from_slice(&[2, 0, 1, 2, 0, 1, 0]);
+        not_coinbase.extend_from_slice(&[0xaa; 32]);
+        not_coinbase.extend_from_slice(&[0, 0, 0, 0]);
+        assert!(ParsedBlock::from_blob(&not_coinbase).is_err());
+    }
+
+    #[test]
+    fn canonical_blob_fuzzing() {
+        use libmonero::blocks::{ParsedBlock, Transaction};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let miner_tx = hex::decode(format!("02bc0801ff800101c0c3b2d2c62d03{}5e2101{}00", "aa".repeat(32), "66".repeat(32))).unwrap();
+        let mut ring_tx = vec![2, 0, 1, 2, 0, 2, 100, 5];
+        ring_tx.extend_from_slice(&[0xaa; 32]);
+        ring_tx.extend_from_slice(&[1, 0, 3]);
+        ring_tx.extend_from_slice(&[0xbb; 33]);
+        ring_tx.extend_from_slice(&[0, 6, 0x80, 0x01]);
+        ring_tx.extend_from_slice(&[0xcc; 8 + 32]);
+        ring_tx.extend_from_slice(&[1, 2, 3]);
+        let mut v1_tx = vec![1, 0, 1, 2, 0x80, 0x01, 1, 7];
+        v1_tx.extend_from_slice(&[0xbb; 32]);
+        v1_tx.extend_from_slice(&[1, 0x40, 2]);
+        v1_tx.extend_from_slice(&[0xcc; 32]);
+        v1_tx.push(0);
+        v1_tx.extend_from_slice(&[0xdd; 64]);
+        let mut block = vec![16, 16, 0x80, 0x01];
+        block.extend_from_slice(&[0xcc; 36]);
+        block.extend_from_slice(&miner_tx);
+        block.push(1);
+        block.extend_from_slice(&[0x11; 32]);
+
+        for tx in [&miner_tx, &ring_tx, &v1_tx] {
+            assert_eq!(&Transaction::from_blob(tx).unwrap().to_blob(), tx);
+        }
+        assert_eq!(ParsedBlock::from_blob(&block).unwrap().to_blob(), block);
+
+        // Non-minimal varints are refused wherever they appear: the unlock time, a key offset or the timestamp
+        assert!(Transaction::from_blob(&[&miner_tx[..2], &[0x88, 0x00], &miner_tx[3..]].concat()).is_err());
+        assert!(Transaction::from_blob(&[&ring_tx[..6], &[0xe4, 0x00], &ring_tx[7..]].concat()).is_err());
+        assert!(ParsedBlock::from_blob(&[&block[..3], &[0x81, 0x00], &block[4..]].concat()).is_err());
+        // Null RingCT signatures end the transaction
+        assert!(Transaction::from_blob(&[miner_tx.as_slice(), &[0]].concat()).is_err());
+
+        // Mutated blobs never panic, and whatever is accepted serializes back to the same bytes
+        let mut rng = StdRng::seed_from_u64(1302);
+        for _ in 0..20000 {
+            let original = [&miner_tx, &ring_tx, &v1_tx, &block][rng.gen_range(0..4)];
+            let mut blob = original.clone();
+            for _ in 0..rng.gen_range(1..4) {
+                let index = rng.gen_range(0..blob.len());
+                match rng.gen_range(0..5) {
+                    0 => blob[index] = rng.gen(),
+                    1 => blob[index] ^= 1 << rng.gen_range(0..8),
+                    2 => blob.insert(index, rng.gen()),
+                    3 if blob.len() > 1 => drop(blob.remove(index)),
+                    _ => blob.truncate(index.max(1)),
+                }
+            }
+            if let Ok(tx) = Transaction::from_blob(&blob) {
+                assert_eq!(tx.to_blob(), blob);
+            }
+            if let Ok(parsed) = ParsedBlock::from_blob(&blob) {
+                assert_eq!(parsed.to_blob(), blob);
+            }
+        }
+    }
+
+    #[test]
+    fn output_ownership_proofs() {
+        use libmonero::blocks::parse_transaction_json;
+        use libmonero::keys::{derive_output_key_for, derive_subaddress, prove_output_ownership, scan_tx_for_outputs, KeyImage, OneTimeKey, OutputOwnershipProof, OwnedOutput, PrivateKey, ViewPair};
+        use libmonero::utils::{decode_address, Network};
+
+        // Output of a real transaction, proven with the key derived from the wallet keys
+        let view_key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let tx = parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        let owned = scan_tx_for_outputs(&view_key, &spend_key.public_key(), &tx).remove(0);
+        let output_secret = owned.secret_key(&view_key, &spend_key);
+        assert_eq!(OneTimeKey::from(output_secret.public_key()), owned.output_key);
+        let proof = prove_output_ownership(&output_secret, b"challenge 42");
+        assert_eq!(proof.output_key, owned.output_key);
+        assert!(proof.verify(b"challenge 42"));
+        assert!(!proof.verify(b"challenge 43"));
+        let parsed: OutputOwnershipProof = proof.to_string().parse().unwrap();
+        assert!(parsed.verify(b"challenge 42"));
+
+        // Proofs cannot be moved to another output or key image
+        let other_secret = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let mut moved = proof.clone();
+        moved.output_key = other_secret.public_key().into();
+        assert!(!moved.verify(b"challenge 42"));
+        let mut moved = proof.clone();
+        moved.key_image = prove_output_ownership(&other_secret, b"challenge 42").key_image;
+        assert!(!moved.verify(b"challenge 42"));
+        moved.key_image = KeyImage::from_bytes([0; 32]);
+        assert!(!moved.verify(b"challenge 42"));
+        assert!("OutputProofV2abc".parse::<OutputOwnershipProof>().is_err());
+        assert!(format!("OutputProofV1{}", "1".repeat(11)).parse::<OutputOwnershipProof>().is_err());
+
+        // Outputs received by a subaddress include the subaddress secret in their key
+        let view_pair = ViewPair::new(spend_key.public_key(), view_key.clone());
+        let recipient = decode_address(&derive_subaddress(&view_pair, Network::Mainnet, 1, 2)).unwrap();
+        let tx_secret_key = PrivateKey::from_hex("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08").unwrap();
+        let keys = derive_output_key_for(&recipient, &tx_secret_key, 3);
+        let output = OwnedOutput {
+            index: 3,
+            amount: 1,
+            output_key: keys.output_key,
+            tx_public_key: keys.tx_public_key,
+            pre_rct: false,
+            subaddress: (1, 2),
+        };
+        let output_secret = output.secret_key(&view_key, &spend_key);
+        assert_eq!(OneTimeKey::from(output_secret.public_key()), keys.output_key);
+        assert!(prove_output_ownership(&output_secret, b"").verify(b""));
+    }
+
+    #[test]
+    fn tx_proofs() {
+        use libmonero::blocks::{RctBase, RctType, Transaction, TransactionPrefix, TxExtra, TxHash, TxInput, TxOutTarget, TxOutput, TxSignatures};
+        use libmonero::keys::{
+            check_tx_proof, commit, commitment_mask, derivation_to_scalar, derive_output_key, derive_subaddress, ecdh_encode, generate_key_derivation,
+            generate_tx_proof, KeyImage, PrivateKey, TxProofKeys, ViewPair,
+        };
+        use libmonero::utils::{decode_address, Network};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1313);
+        let mut private_key = || PrivateKey::from_bytes_mod_order(rng.gen());
+        let recipient_key = private_key();
+        let recipient = ViewPair::new(recipient_key.public_key(), recipient_key.derive_view_key());
+        let subaddress = derive_subaddress(&recipient, Network::Mainnet, 0, 1);
+        let other_key = private_key();
+        let change_address = ViewPair::new(other_key.public_key(), other_key.derive_view_key()).address(Network::Mainnet);
+
+        // Transaction paying the subaddress at index 0 and change at index 1, each output with its additional key
+        let tx_secret_key = private_key();
+        let additional_secret_keys = vec![private_key(), private_key()];
+        let mut outputs = Vec::new();
+        let mut encrypted_amounts = Vec::new();
+        let mut commitments = Vec::new();
+        let mut additional_keys = Vec::new();
+        for (index, (destination, amount)) in [(subaddress.as_str(), 2_000_000_000_000), (change_address.as_str(), 500_000_000)].into_iter().enumerate() {
+            let keys = derive_output_key(destination, &additional_secret_keys[index], index as u64).unwrap();
+            let derivation = generate_key_derivation(&decode_address(destination).unwrap().public_view_key, &additional_secret_keys[index]);
+            let shared_secret = derivation_to_scalar(&derivation, index as u64);
+            let mask = commitment_mask(&shared_secret);
+            outputs.push(TxOutput { amount: 0, target: TxOutTarget::ToTaggedKey { key: keys.output_key, view_tag: keys.view_tag } });
+            encrypted_amounts.push(ecdh_encode(amount, &mask, &shared_secret, true));
+            commitments.push(commit(amount, &mask));
+            additional_keys.push(keys.tx_public_key);
+        }
+        let tx = Transaction {
+            prefix: TransactionPrefix {
+                version: 2,
+                unlock_time: 0,
+                inputs: vec![TxInput::ToKey { amount: 0, key_offsets: vec![1000, 20], key_image: KeyImage::from_bytes(*private_key().public_key().as_bytes()) }],
+                outputs,
+                extra: TxExtra::new().with_tx_public_key(&tx_secret_key.public_key()).with_additional_public_keys(&additional_keys).to_bytes(),
+            },
+            signatures: TxSignatures::V2 {
+                base: RctBase { rct_type: RctType::BulletproofPlus, fee: 30_720_000, pseudo_outs: Vec::new(), encrypted_amounts, commitments },
+                prunable: vec![7; 64],
+            },
+        };
+        let tx_hash = tx.hash().unwrap();
+
+        // Sender and recipient prove the same payment
+        let sender = TxProofKeys::Sender { tx_secret_key: tx_secret_key.clone(), additional_secret_keys: additional_secret_keys.clone() };
+        let out_proof = generate_tx_proof(&tx_hash, &tx, &subaddress, b"invoice 7", &sender).unwrap();
+        assert!(out_proof.starts_with("OutProofV2"));
+        assert_eq!(out_proof.len(), "OutProofV2".len() + 3 * (44 + 88));
+        assert_eq!(check_tx_proof(&tx_hash, &tx, &subaddress, b"invoice 7", &out_proof), Ok(2_000_000_000_000));
+        let in_proof = generate_tx_proof(&tx_hash, &tx, &subaddress, b"invoice 7", &TxProofKeys::Recipient { private_view_key: recipient.view.clone() }).unwrap();
+        assert!(in_proof.starts_with("InProofV2"));
+        assert_eq!(check_tx_proof(&tx_hash, &tx, &subaddress, b"invoice 7", &in_proof), Ok(2_000_000_000_000));
+
+        // The change address is proven with the same sender keys
+        let change_proof = generate_tx_proof(&tx_hash, &tx, &change_address, b"", &sender).unwrap();
+        assert_eq!(check_tx_proof(&tx_hash, &tx, &change_address, b"", &change_proof), Ok(500_000_000));
+
+        // Proofs are bound to the transaction, address and message
+        assert!(check_tx_proof(&tx_hash, &tx, &subaddress, b"invoice 8", &out_proof).is_err());
+        assert!(check_tx_proof(&tx_hash, &tx, &change_address, b"invoice 7", &out_proof).is_err());
+        assert!(check_tx_proof(&TxHash::from_bytes([1; 32]), &tx, &subaddress, b"invoice 7", &in_proof).is_err());
+        assert!(check_tx_proof(&tx_hash, &tx, &subaddress, b"invoice 7", &out_proof[..out_proof.len() - 132]).is_err());
+        assert!(check_tx_proof(&tx_hash, &tx, &subaddress, b"invoice 7", &out_proof.replace("OutProofV2", "OutProofV1")).is_err());
+
+        // Keys must match the transaction and the address
+        let missing = TxProofKeys::Sender { tx_secret_key: tx_secret_key.clone(), additional_secret_keys: Vec::new() };
+        assert!(generate_tx_proof(&tx_hash, &tx, &subaddress, b"", &missing).is_err());
+        let wrong = TxProofKeys::Sender { tx_secret_key, additional_secret_keys: additional_secret_keys.into_iter().rev().collect() };
+        assert!(generate_tx_proof(&tx_hash, &tx, &subaddress, b"", &wrong).is_err());
+        assert!(generate_tx_proof(&tx_hash, &tx, &change_address, b"", &TxProofKeys::Recipient { private_view_key: recipient.view.clone() }).is_err());
+    }
+
+    #[test]
+    fn payment_receipt() {
+        use libmonero::blocks::{parse_transaction_blob, Transaction};
+        use libmonero::keys::{derive_output_key, PrivateKey, TxProofKeys, ViewPair};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{PaymentReceipt, SnapshotTransfer};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1278);
+        let mut private_key = || PrivateKey::from_bytes_mod_order(rng.gen());
+        let recipient_key = private_key();
+        let recipient = ViewPair::new(recipient_key.public_key(), recipient_key.derive_view_key());
+        let destination = recipient.address(Network::Mainnet);
+        // Miner transactions paying 10000000 piconeros to the destination
+        let payment = |tx_secret_key: &PrivateKey| -> Transaction {
+            let output = derive_output_key(&destination, tx_secret_key, 0).unwrap();
+            let blob = format!("029c0801ffe8070180ade20403{}{:02x}2101{}00", output.output_key, output.view_tag, output.tx_public_key.to_hex());
+            parse_transaction_blob(&hex::decode(blob).unwrap()).unwrap()
+        };
+        let tx_secret_key = private_key();
+        let tx = payment(&tx_secret_key);
+        let other = payment(&private_key());
+        let transfer = SnapshotTransfer {
+            tx_hash: tx.hash().unwrap(),
+            height: Some(1000),
+            timestamp: 1700000000,
+            account: 0,
+            incoming: 0,
+            outgoing: 10_000_000,
+            fee: 30_000_000,
+            payment_id: None,
+        };
+        let sender = TxProofKeys::Sender { tx_secret_key, additional_secret_keys: Vec::new() };
+
+        // The proof is made with the receipt's transaction, destination and message, which is kept for checking it
+        let receipt = PaymentReceipt::from_transfer(&transfer, &destination, 10_000_000).unwrap().with_tx_proof(&tx, "Invoice #42", &sender).unwrap();
+        assert!(receipt.proof.as_ref().unwrap().starts_with("OutProofV2"));
+        assert_eq!(receipt.message.as_deref(), Some("Invoice #42"));
+        assert_eq!(receipt.verify(&tx), Ok(10_000_000));
+        let parsed = PaymentReceipt::from_json(&receipt.to_json()).unwrap();
+        assert_eq!(parsed, receipt);
+        assert_eq!(parsed.verify(&tx), Ok(10_000_000));
+        let recipient_proof = PaymentReceipt::from_transfer(&transfer, &destination, 10_000_000)
+            .unwrap()
+            .with_tx_proof(&tx, "", &TxProofKeys::Recipient { private_view_key: recipient.view.clone() })
+            .unwrap();
+        assert_eq!(recipient_proof.verify(&tx), Ok(10_000_000));
+
+        // Receipts claiming more, with another message, without a proof or checked against another transaction fail
+        let partial = PaymentReceipt::from_transfer(&transfer, &destination, 4_000_000).unwrap().with_tx_proof(&tx, "Invoice #42", &sender).unwrap();
+        assert_eq!(partial.verify(&tx), Ok(10_000_000));
+        let inflated = PaymentReceipt { amount: 10_000_001, ..receipt.clone() };
+        assert!(inflated.verify(&tx).unwrap_err().contains("less than"));
+        let reworded = PaymentReceipt { message: Some("Invoice #43".to_string()), ..receipt.clone() };
+        assert!(reworded.verify(&tx).is_err());
+        assert!(PaymentReceipt { message: None, ..receipt.clone() }.verify(&tx).is_err());
+        assert_eq!(PaymentReceipt { proof: None, ..receipt.clone() }.verify(&tx), Err("Receipt has no proof".to_string()));
+        assert!(receipt.verify(&other).unwrap_err().contains("is not the transaction"));
+        let forged = PaymentReceipt { tx_hash: other.hash().unwrap(), ..receipt.clone() };
+        assert!(forged.verify(&other).is_err());
+        assert!(PaymentReceipt::from_transfer(&transfer, &destination, 10_000_000).unwrap().with_tx_proof(&other, "", &sender).is_err());
+        let unrelated = TxProofKeys::Sender { tx_secret_key: PrivateKey::from_bytes_mod_order([3; 32]), additional_secret_keys: Vec::new() };
+        assert!(PaymentReceipt::from_transfer(&transfer, &destination, 10_000_000).unwrap().with_tx_proof(&tx, "", &unrelated).is_err());
+    }
+
+    // Daemon knowing the given transactions, ring members are random keys except the real output key at index 105
+    struct ProofTransport {
+        txs: Vec<libmonero::blocks::Transaction>,
+        real_key: libmonero::keys::OneTimeKey,
+        spent: libmonero::keys::KeyImage,
+    }
+
+    impl libmonero::blocks::RpcTransport for ProofTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            use libmonero::keys::{OneTimeKey, PrivateKey};
+
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let response = match path {
+                "/get_transactions" => {
+                    let hash = request["txs_hashes"][0].as_str().unwrap();
+                    let txs: Vec<serde_json::Value> =
+                        self.txs.iter().filter(|tx| tx.hash().unwrap().to_hex() == hash).map(|tx| serde_json::json!({"as_hex": hex::encode(tx.to_blob())})).collect();
+                    serde_json::json!({"txs": txs, "status": "OK"})
+                }
+                "/get_outs" => {
+                    let outs: Vec<serde_json::Value> = request["outputs"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|output| {
+                            let index = output["index"].as_u64().unwrap();
+                            let key = match index {
+                                105 => self.real_key,
+                                _ => OneTimeKey::from(PrivateKey::from_bytes_mod_order([index as u8; 32]).public_key()),
+                            };
+                            serde_json::json!({"height": index / 10, "key": key.to_hex(), "mask": HASH_CC, "txid": HASH_BB, "unlocked": true})
+                        })
+                        .collect();
+                    serde_json::json!({"outs": outs, "status": "OK"})
+                }
+                "/is_key_image_spent" => {
+                    let statuses: Vec<u64> = request["key_images"].as_array().unwrap().iter().map(|key_image| u64::from(key_image.as_str() == Some(&self.spent.to_hex()))).collect();
+                    serde_json::json!({"spent_status": statuses, "status": "OK"})
+                }
+                _ => panic!("Unexpected path {}", path),
+            };
+            Ok(response.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn spend_and_reserve_proofs() {
+        use libmonero::blocks::{DaemonNode, RctBase, RctType, Transaction, TransactionPrefix, TxExtra, TxInput, TxOutTarget, TxOutput, TxSignatures};
+        use libmonero::keys::{commit, commitment_mask, derivation_to_scalar, derive_output_key, ecdh_encode, generate_key_derivation, KeyImage, PrivateKey};
+        use libmonero::utils::{decode_address, Network};
+        use libmonero::wallet::{check_reserve_proof, check_spend_proof, ReserveProofCheck, Wallet};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        use std::sync::Arc;
+
+        let mut rng = StdRng::seed_from_u64(1314);
+        let mut private_key = || PrivateKey::from_bytes_mod_order(rng.gen());
+        // Transaction with one input and an output with its additional key for every payment
+        let mut build_tx = |key_offsets: Vec<u64>, key_image: KeyImage, payments: &[(&str, u64)]| {
+            let mut outputs = Vec::new();
+            let mut encrypted_amounts = Vec::new();
+            let mut commitments = Vec::new();
+            let mut additional_keys = Vec::new();
+            for (index, (destination, amount)) in payments.iter().enumerate() {
+                let secret = private_key();
+                let keys = derive_output_key(destination, &secret, index as u64).unwrap();
+                let derivation = generate_key_derivation(&decode_address(destination).unwrap().public_view_key, &secret);
+                let shared_secret = derivation_to_scalar(&derivation, index as u64);
+                let mask = commitment_mask(&shared_secret);
+                outputs.push(TxOutput { amount: 0, target: TxOutTarget::ToTaggedKey { key: keys.output_key, view_tag: keys.view_tag } });
+                encrypted_amounts.push(ecdh_encode(*amount, &mask, &shared_secret, true));
+                commitments.push(commit(*amount, &mask));
+                additional_keys.push(keys.tx_public_key);
+            }
+            Transaction {
+                prefix: TransactionPrefix {
+                    version: 2,
+                    unlock_time: 0,
+                    inputs: vec![TxInput::ToKey { amount: 0, key_offsets, key_image }],
+                    outputs,
+                    extra: TxExtra::new().with_tx_public_key(&private_key().public_key()).with_additional_public_keys(&additional_keys).to_bytes(),
+                },
+                signatures: TxSignatures::V2 {
+                    base: RctBase { rct_type: RctType::BulletproofPlus, fee: 30_720_000, pseudo_outs: Vec::new(), encrypted_amounts, commitments },
+                    prunable: vec![7; 64],
+                },
+            }
+        };
+
+        // The wallet receives 3 XMR on its primary address and 1 XMR on a subaddress, then spends the first output
+        let mut wallet = Wallet::from_keys(Network::Mainnet, PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap());
+        let (address, subaddress) = (wallet.address().to_string(), wallet.subaddress(0, 1));
+        let received = build_tx(vec![7, 3], KeyImage::from_bytes([1; 32]), &[(address.as_str(), 3_000_000_000_000), (subaddress.as_str(), 1_000_000_000_000)]);
+        assert_eq!(wallet.scan_tx(received.hash().unwrap(), &received.to_raw_tx(), 1000).len(), 2);
+        let spent_output = wallet.outputs()[0].clone();
+        let key_image = wallet.key_image(&spent_output.output_key).unwrap();
+        let spend = build_tx(vec![100, 5, 20], key_image, &[(subaddress.as_str(), 2_900_000_000_000)]);
+        let spend_hash = spend.hash().unwrap();
+        let transport = ProofTransport { txs: vec![received.clone(), spend], real_key: spent_output.output_key, spent: key_image };
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(transport));
+
+        // Spend proofs sign every ring with its key image
+        let spend_proof = wallet.get_spend_proof(&spend_hash, b"paid", node.clone()).unwrap();
+        assert!(spend_proof.starts_with("SpendProofV1"));
+        assert_eq!(spend_proof.len(), "SpendProofV1".len() + 3 * 88);
+        assert_eq!(check_spend_proof(&spend_hash, b"paid", &spend_proof, node.clone()), Ok(true));
+        assert_eq!(check_spend_proof(&spend_hash, b"unpaid", &spend_proof, node.clone()), Ok(false));
+        assert!(check_spend_proof(&spend_hash, b"paid", &spend_proof[..spend_proof.len() - 88], node.clone()).is_err());
+        assert!(wallet.get_spend_proof(&received.hash().unwrap(), b"paid", node.clone()).is_err());
+
+        // Reserve proofs tell how much of the proven amount was spent since
+        let reserve_proof = wallet.get_reserve_proof(None, b"audit").unwrap();
+        assert!(reserve_proof.starts_with("ReserveProofV2"));
+        let check = check_reserve_proof(&address, b"audit", &reserve_proof, node.clone());
+        assert_eq!(check, Ok(ReserveProofCheck { total: 4_000_000_000_000, spent: 3_000_000_000_000 }));
+        assert!(check_reserve_proof(&address, b"audit 2", &reserve_proof, node.clone()).is_err());
+        assert!(check_reserve_proof(&subaddress, b"audit", &reserve_proof, node.clone()).is_err());
+        assert!(check_reserve_proof(Wallet::from_keys(Network::Mainnet, private_key()).address(), b"audit", &reserve_proof, node.clone()).is_err());
+
+        // A minimum reserve is covered by the fewest largest outputs
+        let partial = wallet.get_reserve_proof(Some((0, 2_000_000_000_000)), b"audit").unwrap();
+        assert_eq!(check_reserve_proof(&address, b"audit", &partial, node.clone()).unwrap().total, 3_000_000_000_000);
+        let both = wallet.get_reserve_proof(Some((0, 3_500_000_000_000)), b"audit").unwrap();
+        assert_eq!(check_reserve_proof(&address, b"audit", &both, node).unwrap().total, 4_000_000_000_000);
+        assert!(wallet.get_reserve_proof(Some((0, 5_000_000_000_000)), b"audit").is_err());
+        assert!(wallet.get_reserve_proof(Some((1, 1)), b"audit").is_err());
+        assert!(Wallet::view_only(Network::Mainnet, wallet.view_pair().spend, wallet.view_pair().view.clone()).get_reserve_proof(None, b"audit").is_err());
+    }
+
+    #[test]
+    fn message_signatures() {
+        use libmonero::keys::{generate_signature, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{verify_message, Wallet};
+        use sha3::{Digest, Keccak256};
+
+        let spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let wallet = Wallet::from_keys(Network::Mainnet, spend_key.clone());
+        let (address, subaddress) = (wallet.address().to_string(), wallet.subaddress(2, 7));
+
+        // Signatures are bound to the message and the signing address
+        let signature = wallet.sign_message(b"I own this address", 0, 0).unwrap();
+        assert!(signature.starts_with("SigV2"));
+        assert_eq!(verify_message(&address, b"I own this address", &signature), Ok(true));
+        assert_eq!(verify_message(&address, b"I own that address", &signature), Ok(false));
+        assert_eq!(verify_message(&subaddress, b"I own this address", &signature), Ok(false));
+        let signature = wallet.sign_message(b"", 2, 7).unwrap();
+        assert_eq!(verify_message(&subaddress, b"", &signature), Ok(true));
+        assert_eq!(verify_message(&address, b"", &signature), Ok(false));
+
+        // SigV1 signatures sign the Keccak hash of the message alone
+        let hash: [u8; 32] = Keccak256::digest(b"legacy").into();
+        let legacy = format!("SigV1{}", base58_monero::encode(&generate_signature(&hash, &spend_key).to_bytes()).unwrap());
+        assert_eq!(verify_message(&address, b"legacy", &legacy), Ok(true));
+        assert_eq!(verify_message(&address, b"legacy!", &legacy), Ok(false));
+
+        assert!(verify_message(&address, b"", "SigV3").is_err());
+        assert!(verify_message(&address, b"", &signature[..signature.len() - 4]).is_err());
+        assert!(verify_message("4Invalid", b"", &signature).is_err());
+        assert!(Wallet::view_only(Network::Mainnet, wallet.view_pair().spend, wallet.view_pair().view.clone()).sign_message(b"", 0, 0).is_err());
+    }
+
+    #[test]
+    fn multisig_kex_and_signing() {
+        use curve25519_dalek::Scalar;
+        use libmonero::keys::{commit, derive_subaddress_keys, OneTimeKey, OwnedOutput, PrivateKey};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{MultisigAccount, MultisigNonceCommitment};
+
+        let keys = |i: u8| PrivateKey::from_bytes_mod_order([i; 32]);
+        assert!(MultisigAccount::new(&keys(1), &keys(2), 1, 1).is_err());
+        assert!(MultisigAccount::new(&keys(1), &keys(2), 0, 3).is_err());
+        assert!(MultisigAccount::new(&keys(1), &keys(2), 4, 3).is_err());
+
+        // 2-of-4 takes three rounds and the verification round
+        let mut accounts: Vec<MultisigAccount> = (1..=4).map(|i| MultisigAccount::new(&keys(i), &keys(i).derive_view_key(), 2, 4).unwrap()).collect();
+        assert_eq!(accounts[0].kex_rounds_required(), 4);
+        let messages: Vec<String> = accounts.iter().map(|account| account.next_kex_message().to_string()).collect();
+        assert!(messages[0].starts_with("MultisigxV2R1"));
+        assert!(accounts[0].update(&messages[..3]).is_err());
+        let stranger = MultisigAccount::new(&keys(5), &keys(5).derive_view_key(), 2, 4).unwrap();
+        assert!(accounts[0].update(&[&messages[1..], &[stranger.next_kex_message().to_string()]].concat()).is_err());
+        while !accounts[0].is_ready() {
+            assert!(accounts[0].address(Network::Mainnet).is_err());
+            let messages: Vec<String> = accounts.iter().map(|account| account.next_kex_message().to_string()).collect();
+            accounts.iter_mut().for_each(|account| account.update(&messages).unwrap());
+        }
+        assert!(accounts.iter().all(|account| account.is_ready() && account.signers().len() == 4));
+        let address = accounts[0].address(Network::Mainnet).unwrap();
+        assert!(accounts.iter().all(|account| account.address(Network::Mainnet).unwrap() == address));
+        assert_ne!(address, libmonero::keys::ViewPair::new(keys(1).public_key(), keys(1).derive_view_key()).address(Network::Mainnet));
+        // Every signer holds 3 of the 4 shares of the multisig spend key
+        assert_eq!(accounts[0].multisig_seed().unwrap().len(), 2 * (8 + 32 * (4 + 3 + 4)));
+        let late = [accounts[1].next_kex_message().to_string()];
+        assert!(accounts[0].update(&late).is_err());
+
+        // An output received by subaddress (1, 2) of the multisig wallet
+        let view_pair = accounts[0].view_pair().unwrap();
+        let (subaddress_spend_key, _) = derive_subaddress_keys(&view_pair, 1, 2);
+        let tx_public_key = libmonero::keys::PublicKey::from_point(subaddress_spend_key.to_point() * Scalar::from(77u64));
+        let derivation = libmonero::keys::generate_key_derivation(&tx_public_key, &view_pair.view);
+        let output_key = OneTimeKey::from(libmonero::keys::derive_output_pubkey(&derivation, 3, &subaddress_spend_key));
+        let output = OwnedOutput { index: 3, amount: 25000, output_key, tx_public_key, pre_rct: false, subaddress: (1, 2) };
+
+        // Any two signers compute the same key image, a single one misses shares
+        let partials: Vec<_> = accounts.iter().map(|account| account.partial_key_image(&output_key).unwrap()).collect();
+        let key_image = accounts[1].combine_key_image(&output, &partials[1..3]).unwrap();
+        assert_eq!(accounts[0].combine_key_image(&output, &[partials[0].clone(), partials[3].clone()]), Ok(key_image));
+        assert!(accounts[0].combine_key_image(&output, &partials[..1]).is_err());
+        let foreign = OwnedOutput { output_key: OneTimeKey::from(keys(9).public_key()), ..output.clone() };
+        assert!(accounts[0].combine_key_image(&foreign, &partials).is_err());
+
+        let decoys: Vec<OneTimeKey> = (10..13).map(|i| OneTimeKey::from(keys(i).public_key())).collect();
+        let (mask, pseudo_mask) = (Scalar::from(1234u64), Scalar::from(99u64));
+        let ring = vec![
+            (decoys[0], commit(1, &Scalar::from(5u64))),
+            (decoys[1], commit(2, &Scalar::from(6u64))),
+            (output_key, commit(25000, &mask)),
+            (decoys[2], commit(3, &Scalar::from(7u64))),
+        ];
+        let message = [0x5a; 32];
+        let context = accounts[3].clsag_context(&output, &message, &ring, &key_image, &mask, &pseudo_mask).unwrap();
+        assert_eq!(context.real_index, 2);
+        assert!(accounts[3].clsag_context(&output, &message, &ring, &key_image, &pseudo_mask, &pseudo_mask).is_err());
+        assert!(accounts[3].clsag_context(&output, &message, &ring[..2], &key_image, &mask, &pseudo_mask).is_err());
+
+        // Signers 1 and 3 sign, in either order
+        let sign = |signers: &[usize]| {
+            let nonces: Vec<_> = signers.iter().map(|i| accounts[*i].commit_nonces(&output_key).unwrap()).collect();
+            let commitments: Vec<MultisigNonceCommitment> = nonces.iter().map(|nonces| nonces.commitment()).collect();
+            let partials: Result<Vec<_>, String> = signers.iter().zip(nonces).map(|(i, nonces)| accounts[*i].partial_sign(&context, nonces, &commitments)).collect();
+            (commitments, partials)
+        };
+        let (commitments, partials) = sign(&[3, 1]);
+        let mut partials = partials.unwrap();
+        let clsag = context.combine(&commitments, &partials).unwrap();
+        assert!(clsag.verify(&message, &ring, &key_image, &commit(25000, &pseudo_mask)));
+        partials.reverse();
+        assert!(context.combine(&commitments, &partials).is_ok());
+        assert!(context.combine(&commitments, &partials[..1]).is_err());
+        partials[0].response = Scalar::from(1u64).to_bytes();
+        assert!(context.combine(&commitments, &partials).is_err());
+
+        // Below the threshold, or nonces of another input
+        assert!(sign(&[2]).1.is_err());
+        let nonces = accounts[0].commit_nonces(&decoys[0]).unwrap();
+        let commitments = [nonces.commitment(), accounts[2].commit_nonces(&decoys[0]).unwrap().commitment()];
+        assert!(accounts[0].partial_sign(&context, nonces, &commitments).is_err());
+
+        // All four signers sign as well
+        let (commitments, partials) = sign(&[0, 1, 2, 3]);
+        assert!(context.combine(&commitments, &partials.unwrap()).unwrap().verify(&message, &ring, &key_image, &context.pseudo_out));
+    }
+
+    #[test]
+    fn payment_request_uris() {
+        use libmonero::utils::{make_integrated_address, make_uri, parse_uri, AddressType};
+
+        let address = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+        assert_eq!(make_uri(address, None, None, None).unwrap(), format!("monero:{}", address));
+        assert_eq!(make_uri(address, Some(0), Some(""), None).unwrap(), format!("monero:{}", address));
+        assert_eq!(make_uri(address, Some(1), None, None).unwrap(), format!("monero:{}?tx_amount=0.000000000001", address));
+        assert!(make_uri(&address[1..], Some(1), None, None).is_err());
+
+        // Texts survive the round trip whatever they contain
+        let uri = make_uri(address, Some(18446744073709551615), Some("Café & co = 1+1?"), Some("Zoë's shop")).unwrap();
+        let request = parse_uri(&uri).unwrap();
+        assert_eq!(request.address, address);
+        assert_eq!(request.amount, Some(u64::MAX));
+        assert_eq!(request.description.as_deref(), Some("Café & co = 1+1?"));
+        assert_eq!(request.recipient_name.as_deref(), Some("Zoë's shop"));
+        assert!(request.unknown_parameters.is_empty());
+
+        let integrated = make_integrated_address(address, [7; 8]).unwrap();
+        let request = parse_uri(&format!("monero:{}?tx_amount=12&label=a+b%21&", integrated)).unwrap();
+        assert_eq!((request.info.address_type, request.info.payment_id), (AddressType::Integrated, Some([7; 8])));
+        assert_eq!(request.amount, Some(12000000000000));
+        assert_eq!(request.unknown_parameters, vec![("label".to_string(), "a+b!".to_string())]);
+        assert_eq!(parse_uri(&format!("monero:{}?tx_amount=.5", address)).unwrap().amount, Some(500000000000));
+
+        for invalid in [
+            format!("bitcoin:{}", address),
+            format!("monero:{}", &address[1..]),
+            format!("monero:{}?tx_amount=1.0000000000001", address),
+            format!("monero:{}?tx_amount=1e5", address),
+            format!("monero:{}?tx_amount=.", address),
+            format!("monero:{}?tx_amount=18446744.073709551616", address),
+            format!("monero:{}?tx_amount=1&tx_amount=2", address),
+            format!("monero:{}?tx_description=%2", address),
+            format!("monero:{}?tx_description=%ff", address),
+            format!("monero:{}?tx_description", address),
+            format!("monero:{}?tx_payment_id=0123456789abcdef", address),
+        ] {
+            assert!(parse_uri(&invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn piconero_amounts() {
+        use libmonero::utils::PiconeroAmount;
+
+        let parse = |amount: &str| PiconeroAmount::from_xmr_str(amount).map(|amount| amount.as_piconeros());
+        assert_eq!(parse("1.234"), Ok(1234000000000));
+        assert_eq!(parse("0.000000000001"), Ok(1));
+        assert_eq!(parse("18446744.073709551615"), Ok(u64::MAX));
+        assert_eq!(parse("7"), Ok(7000000000000));
+        assert_eq!(parse("7."), Ok(7000000000000));
+        assert_eq!(parse(".5"), Ok(500000000000));
+        for invalid in ["", ".", "-1", "+1", "1,5", " 1", "1.2.3", "0.0000000000001", "18446744.073709551616", "1e3", "٣"] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!("2.5".parse::<PiconeroAmount>(), Ok(PiconeroAmount::from_piconeros(2500000000000)));
+
+        for (piconeros, shortest, fixed) in [(0, "0", "0.000000000000"), (1, "0.000000000001", "0.000000000001"), (1000000000000, "1", "1.000000000000"), (u64::MAX, "18446744.073709551615", "18446744.073709551615")] {
+            let amount = PiconeroAmount::from(piconeros);
+            assert_eq!(amount.to_xmr_string(), shortest);
+            assert_eq!(amount.to_string(), shortest);
+            assert_eq!(format!("{:.12}", amount), fixed);
+            assert_eq!(PiconeroAmount::from_xmr_str(shortest), Ok(amount));
+        }
+        // Decimal places past the given precision are truncated, never rounded up
+        let amount = PiconeroAmount::from_piconeros(1999999999999);
+        assert_eq!(format!("{:.0}", amount), "1");
+        assert_eq!(format!("{:.3}", amount), "1.999");
+        assert_eq!(format!("{:.14}", amount), "1.99999999999900");
+        assert_eq!(format!("{:>8.2}|{:<6}|", PiconeroAmount::ONE_XMR, PiconeroAmount::ONE_XMR), "    1.00|1     |");
+
+        assert_eq!(PiconeroAmount::MAX.checked_add(PiconeroAmount::from_piconeros(1)), None);
+        assert_eq!(PiconeroAmount::ZERO.checked_sub(PiconeroAmount::from_piconeros(1)), None);
+        assert_eq!(PiconeroAmount::ONE_XMR.checked_mul(18446745), None);
+        assert_eq!(PiconeroAmount::ONE_XMR.checked_div(0), None);
+        assert_eq!(PiconeroAmount::ONE_XMR.checked_div(3).map(u64::from), Some(333333333333));
+        assert_eq!(PiconeroAmount::checked_sum([PiconeroAmount::ONE_XMR, PiconeroAmount::from_piconeros(5)]).map(u64::from), Some(1000000000005));
+        assert_eq!(PiconeroAmount::checked_sum([PiconeroAmount::MAX, PiconeroAmount::ONE_XMR]), None);
+    }
+
+    #[cfg(feature = "openalias")]
+    #[test]
+    fn openalias_dns_lookups() {
+        use libmonero::utils::{resolve_openalias, DnsResolver, OpenAliasResolver};
+        use std::io::{Read, Write};
+        use std::net::{TcpListener, UdpSocket};
+        use std::time::Duration;
+
+        const ADDRESS: &str = "42XUaeqehJTfM1wpW5prsJiQYobDUQG5FfzVe47sYa8LZG3wPwybySuC6kwADuLJJDg86k8yfcp6h963Ck8NEfWdAjfJyVB";
+        // Answers the query with a CNAME, the oa1:xmr record split in two strings and an unrelated TXT record owned by
+        // the CNAME target
+        fn answer(query: &[u8], flags: u16) -> Vec<u8> {
+            let question_end = 12 + query[12..].iter().position(|len| *len == 0).unwrap() + 5;
+            let mut response = query[..2].to_vec();
+            response.extend_from_slice(&flags.to_be_bytes());
+            response.extend_from_slice(&[0, 1, 0, 3, 0, 0, 0, 0]);
+            response.extend_from_slice(&query[12..question_end]);
+            response.extend_from_slice(&[0xc0, 12, 0, 5, 0, 1, 0, 0, 1, 0, 0, 4, 1, b'x', 0xc0, 12]);
+            let record = format!("oa1:xmr recipient_address={}; tx_description=Donation to the general fund;", ADDRESS);
+            let (first, second) = record.as_bytes().split_at(60);
+            response.extend_from_slice(&[0xc0, 12, 0, 16, 0, 1, 0, 0, 1, 0, 0, (record.len() + 2) as u8, first.len() as u8]);
+            response.extend_from_slice(first);
+            response.push(second.len() as u8);
+            response.extend_from_slice(second);
+            response.extend_from_slice(&[0xc0, (question_end + 12) as u8, 0, 16, 0, 1, 0, 0, 1, 0, 0, 6, 5]);
+            response.extend_from_slice(b"v=spf");
+            response
+        }
+
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server = udp.local_addr().unwrap();
+        let tcp = TcpListener::bind(server).unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            let mut queries = Vec::new();
+            // Validated answer, truncated answer retried over TCP, a missing domain, then a validated answer about
+            // another domain
+            for (round, flags) in [(0, 0x81a0u16), (1, 0x8380), (2, 0x8183), (3, 0x81a0)] {
+                let (len, peer) = udp.recv_from(&mut buffer).unwrap();
+                let query = buffer[..len].to_vec();
+                let response = match round {
+                    1 => query[..2].iter().copied().chain(flags.to_be_bytes()).chain([0; 8]).collect(),
+                    3 => {
+                        let mut response = answer(&query, flags);
+                        let question_end = 12 + query[12..].iter().position(|len| *len == 0).unwrap() + 5;
+                        response.truncate(question_end);
+                        response[7] = 1;
+                        response.extend_from_slice(b"\x04evil\x03org\x00\x00\x10\x00\x01\x00\x00\x01\x00\x00\x04\x03abc");
+                        response
+                    }
+                    _ => answer(&query, flags),
+                };
+                udp.send_to(&response, peer).unwrap();
+                if round == 1 {
+                    let (mut stream, _) = tcp.accept().unwrap();
+                    let mut len = [0u8; 2];
+                    stream.read_exact(&mut len).unwrap();
+                    let mut tcp_query = vec![0u8; u16::from_be_bytes(len) as usize];
+                    stream.read_exact(&mut tcp_query).unwrap();
+                    assert_eq!(tcp_query, query);
+                    let response = answer(&tcp_query, 0x8180);
+                    stream.write_all(&[&(response.len() as u16).to_be_bytes()[..], &response].concat()).unwrap();
+                }
+                queries.push(query);
+            }
+            queries
+        });
+
+        let resolver = DnsResolver::new(server).with_timeout(Duration::from_secs(10));
+        let resolved = resolve_openalias("donate@getmonero.org", &resolver).unwrap();
+        assert_eq!(resolved.record.address, ADDRESS);
+        assert_eq!(resolved.record.description.as_deref(), Some("Donation to the general fund"));
+        assert!(resolved.dnssec_valid);
+        let (records, dnssec_valid) = resolver.resolve_txt("donate.getmonero.org").unwrap();
+        assert_eq!((records.len(), dnssec_valid), (2, false));
+        assert_eq!(records[1], "v=spf");
+        assert!(resolve_openalias("missing.getmonero.org", &resolver).unwrap_err().contains("No oa1:xmr record"));
+        assert!(resolver.resolve_txt("donate.getmonero.org").unwrap_err().contains("evil.org"));
+
+        let queries = handle.join().unwrap();
+        // Recursion desired and authentic data flags, TXT question, EDNS0 record with the DNSSEC OK flag
+        assert_eq!(queries[0][2..4], [0x01, 0x20]);
+        assert_eq!(queries[0][12..34], *b"\x06donate\x09getmonero\x03org\x00");
+        assert_eq!(queries[0][34..], [0, 16, 0, 1, 0, 0, 41, 0x10, 0, 0, 0, 0x80, 0, 0, 0]);
+
+        assert!(resolver.resolve_txt("bad..domain").is_err());
+        assert!(resolve_openalias(ADDRESS, &resolver).is_err());
+        let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        assert!(DnsResolver::new(silent.local_addr().unwrap()).with_timeout(Duration::from_millis(50)).resolve_txt("getmonero.org").is_err());
+    }
+
+    #[test]
+    fn scan_discrepancy_diagnostics() {
+        use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY, Scalar};
+        use libmonero::blocks::{parse_transaction_json, TxHash};
+        use libmonero::keys::{derivation_to_scalar, generate_key_derivation, scan_tx_with_diagnostics, Commitment, OneTimeKey, PrivateKey, ScanFilter, SubaddressTable, ViewPair};
+        use libmonero::utils::{DiscrepancyKind, DiscrepancyLog};
+        use sha3::{Digest, Keccak256};
+
+        let view_key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap();
+        let spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let table = SubaddressTable::new(&ViewPair::new(spend_key.public_key(), view_key.clone()), 1, 1);
+        let hash = TxHash::from_hex(HASH_AA).unwrap();
+        let fresh = || parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        let tx = fresh();
+        let scan = |tx: &libmonero::blocks::RawTx, log: &DiscrepancyLog| scan_tx_with_diagnostics(&table, hash, tx, Some(3000000), &ScanFilter::default(), log);
+
+        // Valid transactions record nothing
+        let log = DiscrepancyLog::default();
+        assert_eq!(scan(&tx, &log)[0].amount, 60363387616637);
+        assert!(log.is_empty());
+
+        // Commitments that don't match the decoded amount are not credited
+        let mut forged = fresh();
+        forged.rct_signatures.out_pk[0] = Commitment::from_bytes(*spend_key.public_key().as_bytes());
+        assert!(scan(&forged, &log).is_empty());
+        let record = log.drain().remove(0);
+        assert_eq!(record.kind, DiscrepancyKind::CommitmentMismatch);
+        assert_eq!((record.tx_hash, record.height, record.output_index), (Some(hash), Some(3000000), Some(0)));
+
+        let mut forged = fresh();
+        forged.rct_signatures.ecdh_info[0].trunc_amount = "0102".to_string();
+        assert!(scan(&forged, &log).is_empty());
+        assert_eq!(log.drain()[0].kind, DiscrepancyKind::MalformedEncryptedAmount);
+
+        // Malformed view tags are reported, the output is still scanned without them
+        let mut forged = fresh();
+        forged.vout[0].target.tagged_key.view_tag = "zz".to_string();
+        assert_eq!(scan(&forged, &log).len(), 1);
+        assert_eq!(log.drain()[0].kind, DiscrepancyKind::MalformedViewTag);
+
+        let mut forged = fresh();
+        forged.vout[1].target.tagged_key.key = OneTimeKey::from_bytes([2; 32]);
+        assert_eq!(scan(&forged, &log).len(), 1);
+        let records = log.drain();
+        assert_eq!((records[0].kind, records[0].output_index), (DiscrepancyKind::InvalidOutputKey, Some(1)));
+        assert!(records[0].to_string().starts_with(&format!("InvalidOutputKey in tx {} output 1 at height 3000000: ", HASH_AA)));
+
+        // Full encrypted amounts of RingCT types 1 to 3 are checked against their commitment too
+        let extra_key = libmonero::keys::PublicKey::from_hex("a57c93fb0ca77ab96b7dfd7380c4842d1e58c055430e0d425cd1c76c578cca39").unwrap();
+        let shared_secret = derivation_to_scalar(&generate_key_derivation(&extra_key, &view_key), 0);
+        let hs = |data: &[u8]| Scalar::from_bytes_mod_order(Keccak256::digest(data).into());
+        let mask_secret = hs(shared_secret.as_bytes());
+        let amount_secret = hs(mask_secret.as_bytes());
+        let (amount, mask) = (123456789u64, Scalar::from(987654321u64));
+        let h = CompressedEdwardsY(hex::decode("8b655970153799af2aeadc9ff1add0ea6c7251d54154cfa92c173a0dd39c1f94").unwrap().try_into().unwrap()).decompress().unwrap();
+        let mut full = fresh();
+        full.rct_signatures.type_int = 3;
+        full.rct_signatures.ecdh_info[0].trunc_amount = hex::encode((Scalar::from(amount) + amount_secret).to_bytes());
+        full.rct_signatures.ecdh_info[0].mask = hex::encode((mask + mask_secret).to_bytes());
+        full.rct_signatures.out_pk[0] = Commitment::from_bytes((ED25519_BASEPOINT_TABLE * &mask + h * Scalar::from(amount)).compress().to_bytes());
+        assert_eq!(scan(&full, &log)[0].amount, amount);
+        assert!(log.is_empty());
+        full.rct_signatures.ecdh_info[0].mask = hex::encode((mask + mask_secret + Scalar::ONE).to_bytes());
+        assert!(scan(&full, &log).is_empty());
+        assert_eq!(log.drain()[0].kind, DiscrepancyKind::CommitmentMismatch);
+    }
+
+    #[test]
+    fn tx_extra_fields() {
+        use libmonero::blocks::{parse_transaction_json, ExtraField, PaymentId, TxExtra};
+        use libmonero::keys::PrivateKey;
+
+        // Extra of a real transaction: public key and encrypted payment ID
+        let tx = parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        let extra = TxExtra::from_bytes(&tx.extra).unwrap();
+        assert_eq!(extra.tx_public_key().unwrap().to_hex(), "a57c93fb0ca77ab96b7dfd7380c4842d1e58c055430e0d425cd1c76c578cca39");
+        assert_eq!(extra.payment_id(), Some(PaymentId::Encrypted([149, 25, 248, 193, 206, 94, 32, 48])));
+        assert_eq!(extra.to_bytes(), tx.extra);
+
+        // Built fields come in the reference wallet order whatever the call order, and replace previous values
+        let key = |hex: &str| PrivateKey::from_hex(hex).unwrap().public_key();
+        let (main, first, second) = (key(CLSAG_TX_VIEW_KEY), key(CLSAG_TX_SPEND_KEY), key("c8982eada77ba2245183f2bff85dfaf993dc714178a09828775dba01b4df9a08"));
+        let built = TxExtra::new()
+            .with_padding(3)
+            .unwrap()
+            .with_additional_public_keys(&[first, second])
+            .with_payment_id(PaymentId::Unencrypted([7; 32]))
+            .with_tx_public_key(&first)
+            .with_tx_public_key(&main);
+        assert!(matches!(
+            built.fields.as_slice(),
+            [ExtraField::PublicKey(_), ExtraField::Nonce(_), ExtraField::AdditionalPublicKeys(_), ExtraField::Padding(3)]
+        ));
+        let bytes = built.to_bytes();
+        assert_eq!(bytes.len(), 33 + 35 + 2 + 64 + 3);
+        let parsed = TxExtra::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, built);
+        assert_eq!(parsed.tx_public_key(), Some(main));
+        assert_eq!(parsed.additional_public_keys(), vec![first, second]);
+        assert_eq!(parsed.payment_id(), Some(PaymentId::Unencrypted([7; 32])));
+        assert!(TxExtra::new().with_nonce(vec![0; 256]).is_err());
+        assert!(TxExtra::new().with_padding(256).is_err());
+
+        // Merge mining tags and unknown nonces
+        let mut bytes = vec![0x03, 33, 5];
+        bytes.extend_from_slice(&[0xab; 32]);
+        bytes.extend_from_slice(&[0x02, 2, 0x42, 0x42]);
+        let parsed = TxExtra::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.fields, vec![ExtraField::MergeMining { depth: 5, merkle_root: [0xab; 32] }, ExtraField::Nonce(vec![0x42, 0x42])]);
+        assert_eq!(parsed.payment_id(), None);
+        assert_eq!(parsed.to_bytes(), bytes);
+
+        // Malformed extras
+        assert!(TxExtra::from_bytes(&[0x05, 1]).is_err());
+        assert!(TxExtra::from_bytes(&[0x00, 0x00, 0x01]).is_err());
+        assert!(TxExtra::from_bytes(&vec![0; 256]).is_err());
+        assert!(TxExtra::from_bytes(&[0x04, 2, 0xaa]).is_err());
+        assert!(TxExtra::from_bytes(&[0x02, 5, 0, 0]).is_err());
+        assert!(TxExtra::from_bytes(&[0x03, 2, 5, 0]).is_err());
+    }
+
+    #[test]
+    fn tx_extra_standardness() {
+        use libmonero::blocks::{check_tx_extra, parse_transaction_json, ExtraIssue, PaymentId, TxExtra, MAX_TX_EXTRA_SIZE};
+        use libmonero::keys::PrivateKey;
+        use libmonero::utils::Severity;
+
+        // Extra of a real transaction is standard
+        let tx = parse_transaction_json(CLSAG_TX_JSON).unwrap();
+        assert_eq!(check_tx_extra(&tx.extra, tx.vout.len()), vec![]);
+
+        let key = PrivateKey::from_hex(CLSAG_TX_VIEW_KEY).unwrap().public_key();
+        let extra = TxExtra::new().with_tx_public_key(&key).with_additional_public_keys(&[key, key]);
+        assert_eq!(extra.check(2), Ok(vec![]));
+        assert_eq!(check_tx_extra(&extra.to_bytes(), 3), vec![ExtraIssue::AdditionalKeyCount { keys: 2, outputs: 3 }]);
+        assert!(extra.check(3).unwrap_err().contains("2 additional public keys for 3 outputs"));
+
+        // Order, duplicates and deprecated fields are only warnings
+        let nonce = [&[0x02, 33, 0x00][..], &[7; 32]].concat();
+        let unsorted = [nonce.as_slice(), &extra.to_bytes()[..33], &extra.to_bytes()[..33]].concat();
+        let issues = check_tx_extra(&unsorted, 2);
+        assert_eq!(issues, vec![ExtraIssue::Unsorted, ExtraIssue::Duplicate(0x01), ExtraIssue::UnencryptedPaymentId]);
+        assert!(issues.iter().all(|issue| issue.severity() == Severity::Warning));
+        assert_eq!(check_tx_extra(&[0xde, 1, 0xff], 2), vec![ExtraIssue::MissingTxPublicKey, ExtraIssue::MysteriousMinergate]);
+        let warnings = TxExtra::new().with_payment_id(PaymentId::Unencrypted([1; 32])).check(1).unwrap();
+        assert_eq!(warnings, vec![ExtraIssue::MissingTxPublicKey, ExtraIssue::UnencryptedPaymentId]);
+
+        // Oversized or unparsable extras are not relayed
+        let mut oversized = extra.to_bytes();
+        while oversized.len() <= MAX_TX_EXTRA_SIZE {
+            oversized.extend_from_slice(&[0x02, 200]);
+            oversized.extend_from_slice(&[1; 200]);
+        }
+        let issues = check_tx_extra(&oversized, 2);
+        assert_eq!(issues[0], ExtraIssue::Oversized(oversized.len()));
+        assert_eq!(issues[0].severity(), Severity::Error);
+        assert!(matches!(check_tx_extra(&[0x01, 0xaa], 1).as_slice(), [ExtraIssue::Unparsable(_)]));
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn fixture_wallets() {
+        use libmonero::blocks::{parse_transaction_blob, ParsedBlock};
+        use libmonero::keys::{derive_hex_seed, derive_subaddress, scan_tx_for_subaddresses, validate_mnemonic, ScanFilter, SeedType};
+        use libmonero::utils::{decode_address, Network};
+        use libmonero::wallet::{FixtureConfig, FixtureWallet, SnapshotOutput};
+
+        let config = FixtureConfig { blocks: 8, incoming_transfers: 5, mined_blocks: 2, seed: 7, ..FixtureConfig::default() };
+        let fixture = FixtureWallet::generate(&config);
+        assert_eq!(fixture, FixtureWallet::generate(&config));
+        assert_ne!(fixture.primary_address, FixtureWallet::generate(&FixtureConfig { seed: 8, ..config.clone() }).primary_address);
+
+        // Keys, mnemonic and addresses agree
+        assert_eq!(validate_mnemonic(&fixture.mnemonic), Ok(SeedType::Original));
+        assert_eq!(*derive_hex_seed(fixture.mnemonic.to_vec()), *fixture.private_spend_key.to_hex());
+        assert_eq!(fixture.private_view_key, fixture.private_spend_key.derive_view_key());
+        let address = decode_address(&fixture.primary_address).unwrap();
+        assert_eq!(address.network, Network::Stagenet);
+        assert_eq!(address.public_spend_key, fixture.private_spend_key.public_key());
+        assert_eq!(fixture.snapshot.subaddresses.len(), 4);
+        assert_eq!(fixture.snapshot.subaddresses[3].address, derive_subaddress(&fixture.view_pair(), Network::Stagenet, 1, 1));
+        let testnet = FixtureWallet::generate(&FixtureConfig { network: Network::Testnet, ..config.clone() });
+        assert_eq!(decode_address(&testnet.primary_address).unwrap().network, Network::Testnet);
+
+        // Blocks chain up and every blob parses back
+        assert_eq!(fixture.blocks.len(), 8);
+        assert_eq!(fixture.chain_height(), 1008);
+        for (i, block) in fixture.blocks.iter().enumerate() {
+            assert_eq!(block.height, 1000 + i as u64);
+            assert_eq!(ParsedBlock::from_blob(&block.block.to_blob()).unwrap(), block.block);
+            if i > 0 {
+                assert_eq!(block.block.prev_id, fixture.blocks[i - 1].block.hash());
+            }
+            assert_eq!(block.block.tx_hashes, block.transactions.iter().map(|tx| tx.hash().unwrap()).collect::<Vec<_>>());
+            for tx in block.transactions.iter() {
+                assert_eq!(&parse_transaction_blob(&tx.to_blob()).unwrap(), tx);
+            }
+        }
+
+        // Scanning finds the outputs of the snapshot, with their amounts and subaddresses
+        let table = fixture.subaddress_table();
+        let mut found = Vec::new();
+        for block in fixture.blocks.iter() {
+            for tx in std::iter::once(&block.block.miner_tx).chain(block.transactions.iter()) {
+                for output in scan_tx_for_subaddresses(&table, &tx.to_raw_tx(), Some(block.height), &ScanFilter::default()) {
+                    found.push(SnapshotOutput::from_owned(&output, tx.hash().unwrap(), block.height, output.subaddress, tx.prefix.unlock_time));
+                }
+            }
+        }
+        found.sort_by_key(|output| (output.height, output.tx_hash.to_string()));
+        let mut expected = fixture.snapshot.outputs.clone();
+        expected.sort_by_key(|output| (output.height, output.tx_hash.to_string()));
+        assert_eq!(found, expected);
+        assert_eq!(fixture.snapshot.outputs.len(), 7);
+        assert_eq!(fixture.snapshot.outputs.iter().filter(|output| output.unlock_time == output.height + 60).count(), 2);
+        let subaddresses: Vec<(u32, u32)> = fixture.snapshot.outputs.iter().filter(|output| output.unlock_time == 0).map(|output| output.subaddress).collect();
+        assert_eq!(subaddresses, vec![(0, 0), (0, 1), (1, 0), (1, 1), (0, 0)]);
+        assert_eq!(fixture.snapshot.transfers.iter().map(|transfer| transfer.incoming).sum::<u64>(), fixture.snapshot.balance(None));
+    }
+
+    // Reports the listed key images as spent on the chain, the others as unspent
+    #[cfg(feature = "fixtures")]
+    struct KeyImageStatusTransport(Vec<String>);
+
+    #[cfg(feature = "fixtures")]
+    impl libmonero::blocks::RpcTransport for KeyImageStatusTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            assert_eq!(path, "/is_key_image_spent");
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let statuses: Vec<u64> = request["key_images"].as_array().unwrap().iter().map(|key_image| self.0.contains(&key_image.as_str().unwrap().to_string()) as u64).collect();
+            Ok(serde_json::json!({"status": "OK", "spent_status": statuses, "untrusted": false}).to_string().into_bytes())
+        }
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn wallet_balances() {
+        use libmonero::blocks::{parse_transaction_json, DaemonNode};
+        use libmonero::keys::KeyImage;
+        use libmonero::wallet::{FixtureConfig, FixtureWallet, Wallet};
+        use std::sync::Arc;
+
+        let config = FixtureConfig { blocks: 10, seed: 3, ..FixtureConfig::default() };
+        let fixture = FixtureWallet::generate(&config);
+        let mut wallet = Wallet::from_mnemonic(config.network, &fixture.mnemonic).unwrap().with_restore_height(config.start_height);
+        assert_eq!(wallet.address(), fixture.primary_address);
+        let mut view_only = Wallet::view_only(config.network, wallet.view_pair().spend, fixture.private_view_key.clone());
+        assert!(view_only.is_view_only());
+        let mut words = fixture.mnemonic.to_vec();
+        words.swap(0, 1);
+        assert!(Wallet::from_mnemonic(config.network, &words).is_err());
+
+        for block in fixture.blocks.iter() {
+            for tx in std::iter::once(&block.block.miner_tx).chain(block.transactions.iter()) {
+                let tx_hash = tx.hash().unwrap();
+                let raw = tx.to_raw_tx();
+                wallet.scan_tx(tx_hash, &raw, block.height);
+                view_only.scan_tx(tx_hash, &raw, block.height);
+                // Scanning a transaction again finds nothing new
+                assert!(wallet.scan_tx(tx_hash, &raw, block.height).is_empty());
+            }
+        }
+        let sorted = |outputs: &[libmonero::wallet::SnapshotOutput]| {
+            let mut outputs = outputs.to_vec();
+            outputs.sort_by_key(|output| (output.height, output.tx_hash, output.index));
+            outputs
+        };
+        assert_eq!(sorted(wallet.outputs()), sorted(&fixture.snapshot.outputs));
+        assert_eq!(wallet.synced_height(), fixture.chain_height());
+        assert_eq!(wallet.balance(), fixture.snapshot.balance(None));
+        assert_eq!(wallet.account_balance(1), fixture.snapshot.balance(Some(1)));
+        assert_eq!(wallet.subaddress_balances().values().sum::<u64>(), wallet.balance());
+        assert!(wallet.subaddress_balances().contains_key(&(1, 1)));
+        // Mined outputs are locked for 60 blocks, so only part of the balance is unlocked
+        assert!(wallet.unlocked_balance() < wallet.balance());
+
+        // Spending an output, seen by the full wallet and by the view-only wallet once it has the key image
+        let spent = wallet.outputs()[1].clone();
+        let key_image = wallet.key_image(&spent.output_key).unwrap();
+        assert_eq!(view_only.key_image(&spent.output_key), None);
+        let mut late_view_only = view_only.clone();
+        let unspent_node = DaemonNode::cake_wallet_default().with_transport(Arc::new(KeyImageStatusTransport(Vec::new())));
+        assert_eq!(view_only.import_key_images(&[(spent.output_key, key_image), (Default::default(), KeyImage::default())], &unspent_node), Ok(1));
+        assert_eq!(view_only.balance(), fixture.snapshot.balance(None));
+        let spending = parse_transaction_json(&format!(r#"{{"version": 2, "vin": [{{"key": {{"amount": 0, "key_offsets": [1, 2], "k_image": "{}"}}}}], "vout": [], "extra": [], "rct_signatures": {{"type": 6}}}}"#, key_image)).unwrap();
+        let spending_height = fixture.chain_height();
+        for wallet in [&mut wallet, &mut view_only, &mut late_view_only] {
+            wallet.scan_tx(Default::default(), &spending, spending_height);
+        }
+        for wallet in [&wallet, &view_only] {
+            assert_eq!(wallet.balance(), fixture.snapshot.balance(None) - spent.amount);
+            assert_eq!(wallet.outputs().iter().find(|output| output.output_key == spent.output_key).unwrap().spent_height, Some(spending_height));
+            assert_eq!(wallet.synced_height(), spending_height + 1);
+        }
+
+        // Key images imported after the spending transaction was scanned are checked with the node
+        assert_eq!(late_view_only.balance(), fixture.snapshot.balance(None));
+        let failing_node = DaemonNode::cake_wallet_default().with_transport(Arc::new(FixedHeightTransport));
+        assert!(late_view_only.import_key_images(&[(spent.output_key, key_image)], &failing_node).is_err());
+        assert_eq!(late_view_only.key_image(&spent.output_key), None);
+        let spent_node = DaemonNode::cake_wallet_default().with_transport(Arc::new(KeyImageStatusTransport(vec![key_image.to_hex()])));
+        assert_eq!(late_view_only.import_key_images(&[(spent.output_key, key_image)], &spent_node), Ok(1));
+        assert_eq!(late_view_only.balance(), fixture.snapshot.balance(None) - spent.amount);
+        assert_eq!(late_view_only.outputs().iter().find(|output| output.output_key == spent.output_key).unwrap().spent_height, Some(spending_height));
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn key_image_export_file() {
+        use libmonero::blocks::DaemonNode;
+        use libmonero::wallet::{import_key_images, FixtureConfig, FixtureWallet, MemoryKeyStore, Signer, Wallet, KEY_IMAGE_EXPORT_PREFIX};
+        use std::sync::Arc;
+
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(KeyImageStatusTransport(Vec::new())));
+        let config = FixtureConfig { blocks: 10, seed: 1311, ..FixtureConfig::default() };
+        let fixture = FixtureWallet::generate(&config);
+        let mut wallet = Wallet::from_mnemonic(config.network, &fixture.mnemonic).unwrap().with_restore_height(config.start_height);
+        let mut view_only = Wallet::view_only(config.network, wallet.view_pair().spend, fixture.private_view_key.clone());
+        for block in fixture.blocks.iter() {
+            for tx in std::iter::once(&block.block.miner_tx).chain(block.transactions.iter()) {
+                wallet.scan_tx(tx.hash().unwrap(), &tx.to_raw_tx(), block.height);
+                view_only.scan_tx(tx.hash().unwrap(), &tx.to_raw_tx(), block.height);
+            }
+        }
+        assert!(wallet.outputs().len() > 2);
+
+        // View-only copy learns the key image of every output from the file
+        let export = wallet.export_key_images().unwrap();
+        assert!(export.starts_with(KEY_IMAGE_EXPORT_PREFIX));
+        assert!(view_only.export_key_images().is_err());
+        assert_eq!(view_only.import_key_image_file(&export, &node), Ok(wallet.outputs().len()));
+        for output in wallet.outputs() {
+            assert_eq!(view_only.key_image(&output.output_key), wallet.key_image(&output.output_key));
+        }
+
+        // Exports can start after the outputs whose key images are already known
+        let signer = Signer::new(MemoryKeyStore::new(fixture.private_spend_key.clone()), fixture.private_view_key.clone()).unwrap();
+        let partial = signer.export_key_images(wallet.outputs(), 2).unwrap();
+        let imported = import_key_images(&partial, view_only.outputs(), view_only.view_pair()).unwrap();
+        assert_eq!(imported.len(), wallet.outputs().len() - 2);
+        assert_eq!(imported[0], (wallet.outputs()[2].output_key, wallet.key_image(&wallet.outputs()[2].output_key).unwrap()));
+        assert!(signer.export_key_images(wallet.outputs(), wallet.outputs().len() + 1).is_err());
+
+        // Outputs received in another order, or by another wallet, are refused
+        let mut reordered = view_only.outputs().to_vec();
+        reordered.swap(0, 1);
+        assert!(import_key_images(&export, &reordered, view_only.view_pair()).is_err());
+        assert!(import_key_images(&export, &view_only.outputs()[1..], view_only.view_pair()).is_err());
+        let other = FixtureWallet::generate(&FixtureConfig { seed: 1312, ..config.clone() });
+        let mut stranger = Wallet::view_only(config.network, other.view_pair().spend, other.private_view_key.clone());
+        assert!(stranger.import_key_image_file(&export, &node).is_err());
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn outputs_file_round_trip() {
+        use libmonero::blocks::DaemonNode;
+        use libmonero::wallet::{export_outputs, import_outputs, FixtureConfig, FixtureWallet, Wallet, OUTPUT_EXPORT_PREFIX};
+        use std::sync::Arc;
+
+        let config = FixtureConfig { blocks: 10, seed: 1312, ..FixtureConfig::default() };
+        let fixture = FixtureWallet::generate(&config);
+        let full = Wallet::from_mnemonic(config.network, &fixture.mnemonic).unwrap().with_restore_height(config.start_height);
+        let mut online = Wallet::view_only(config.network, full.view_pair().spend, fixture.private_view_key.clone());
+        for block in fixture.blocks.iter() {
+            for tx in std::iter::once(&block.block.miner_tx).chain(block.transactions.iter()) {
+                online.scan_tx(tx.hash().unwrap(), &tx.to_raw_tx(), block.height);
+            }
+        }
+        let count = online.outputs().len();
+        assert!(count > 2);
+
+        // Offline wallet learns the outputs from the file without syncing, in the same order
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(DryRunTransport));
+        let file = online.export_outputs(node.clone()).unwrap();
+        assert!(file.starts_with(OUTPUT_EXPORT_PREFIX));
+        let mut offline = Wallet::from_mnemonic(config.network, &fixture.mnemonic).unwrap();
+        assert_eq!(offline.import_outputs(&file), Ok(count));
+        for (imported, output) in offline.outputs().iter().zip(online.outputs()) {
+            assert_eq!((imported.output_key, imported.tx_public_key, imported.index), (output.output_key, output.tx_public_key, output.index));
+            assert_eq!((imported.amount, imported.subaddress, imported.pre_rct), (output.amount, output.subaddress, output.pre_rct));
+        }
+        assert_eq!(offline.balance(), online.balance());
+
+        // and sends back the key images of all of them
+        let status_node = DaemonNode::cake_wallet_default().with_transport(Arc::new(KeyImageStatusTransport(Vec::new())));
+        assert_eq!(online.import_key_image_file(&offline.export_key_images().unwrap(), &status_node), Ok(count));
+        assert!(online.outputs().iter().all(|output| online.key_image(&output.output_key).is_some()));
+
+        // Partial exports replace the outputs from their offset
+        let partial = export_outputs(online.outputs(), 2, online.view_pair(), node).unwrap();
+        let (offset, outputs) = import_outputs(&partial, online.view_pair()).unwrap();
+        assert_eq!((offset, outputs.len()), (2, count - 2));
+        assert_eq!(offline.import_outputs(&partial), Ok(count - 2));
+        assert_eq!(offline.outputs().len(), count);
+        let mut empty = Wallet::from_mnemonic(config.network, &fixture.mnemonic).unwrap();
+        assert!(empty.import_outputs(&partial).is_err());
+
+        // Files of another wallet are refused
+        let other = FixtureWallet::generate(&FixtureConfig { seed: 1313, ..config.clone() });
+        assert!(import_outputs(&file, &other.view_pair()).is_err());
+    }
+
+    #[cfg(feature = "fixtures")]
+    struct SyncTransport(std::sync::Mutex<Vec<libmonero::wallet::FixtureBlock>>);
+
+    #[cfg(feature = "fixtures")]
+    impl libmonero::blocks::RpcTransport for SyncTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            use libmonero::blocks::{epee_from_bytes, epee_get, epee_to_bytes, EpeeValue};
+
+            assert_eq!(path, "/get_blocks.bin");
+            let request = epee_from_bytes(body)?;
+            let chain = self.0.lock().unwrap();
+            // Blocks after the most recent known one, at most 3 per batch
+            let block_ids = epee_get(&request, "block_ids").unwrap().as_blob().unwrap().to_vec();
+            let known = block_ids.chunks(32).find_map(|id| chain.iter().find(|block| block.block.hash().as_bytes() == id).map(|block| block.height + 1));
+            let start_height = known.unwrap_or(0).max(epee_get(&request, "start_height").unwrap().as_u64().unwrap());
+            let entry = |name: &str, value: EpeeValue| (name.to_string(), value);
+            let blob_type = EpeeValue::Blob(Vec::new()).type_code();
+            let object_type = EpeeValue::Object(Vec::new()).type_code();
+            let batch: Vec<_> = chain.iter().filter(|block| block.height >= start_height).take(3).collect();
+            let blocks = batch
+                .iter()
+                .map(|block| {
+                    let txs = block.transactions.iter().map(|tx| EpeeValue::Blob(tx.to_blob())).collect();
+                    EpeeValue::Object(vec![entry("block", EpeeValue::Blob(block.block.to_blob())), entry("txs", EpeeValue::Array(blob_type, txs))])
+                })
+                .collect();
+            // The wallet does not use global output indices, empty ones are enough
+            let output_indices = batch
+                .iter()
+                .map(|block| EpeeValue::Object(vec![entry("indices", EpeeValue::Array(object_type, vec![EpeeValue::Object(Vec::new()); block.transactions.len() + 1]))]))
+                .collect();
+            epee_to_bytes(&vec![
+                entry("blocks", EpeeValue::Array(object_type, blocks)),
+                entry("output_indices", EpeeValue::Array(object_type, output_indices)),
+                entry("current_height", EpeeValue::U64(chain.last().unwrap().height + 1)),
+                entry("start_height", EpeeValue::U64(start_height)),
+                entry("status", EpeeValue::Blob(b"OK".to_vec())),
+                entry("untrusted", EpeeValue::Bool(false)),
+            ])
+        }
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn wallet_sync() {
+        use libmonero::blocks::{DaemonNode, RctBase, RctType, Transaction, TransactionPrefix, TxInput, TxSignatures};
+        use libmonero::wallet::{FixtureConfig, FixtureWallet, SyncProgress, Wallet};
+        use std::sync::{Arc, Mutex};
+
+        let config = FixtureConfig { blocks: 8, seed: 5, accounts: 1, ..FixtureConfig::default() };
+        let fixture = FixtureWallet::generate(&config);
+        let transport = Arc::new(SyncTransport(Mutex::new(fixture.blocks.clone())));
+        let node = DaemonNode::cake_wallet_default().with_transport(transport.clone());
+        let mut wallet = Wallet::from_keys(config.network, fixture.private_spend_key.clone()).with_restore_height(config.start_height);
+        let sorted = |outputs: &[libmonero::wallet::SnapshotOutput]| {
+            let mut outputs = outputs.to_vec();
+            outputs.sort_by_key(|output| (output.height, output.tx_hash, output.index));
+            outputs
+        };
+
+        // Batches of 3 blocks, progress reported after each of them
+        let mut reports: Vec<SyncProgress> = Vec::new();
+        assert_eq!(wallet.sync(&node, None, |progress| reports.push(progress.clone())).unwrap(), fixture.chain_height());
+        assert_eq!(reports.iter().map(|progress| progress.synced_height).collect::<Vec<_>>(), vec![1003, 1006, 1008]);
+        assert!(reports.iter().all(|progress| progress.chain_height == 1008 && progress.reorg.is_none()));
+        assert_eq!(reports.iter().map(|progress| progress.received.len()).sum::<usize>(), fixture.snapshot.outputs.len());
+        assert_eq!(sorted(wallet.outputs()), sorted(&fixture.snapshot.outputs));
+        // Nothing new at the top, and rescans do not record outputs twice
+        wallet.sync(&node, None, |progress| assert!(progress.received.is_empty())).unwrap();
+        assert_eq!(wallet.sync(&node, Some(1000), |_| {}).unwrap(), 1008);
+        assert_eq!(wallet.outputs().len(), fixture.snapshot.outputs.len());
+
+        // The top block is replaced by one spending the first output: its outputs are dropped, the spend recorded
+        let spent = wallet.outputs()[0].clone();
+        let spending = Transaction {
+            prefix: TransactionPrefix {
+                version: 2,
+                unlock_time: 0,
+                inputs: vec![TxInput::ToKey { amount: 0, key_offsets: vec![1], key_image: wallet.key_image(&spent.output_key).unwrap() }],
+                outputs: Vec::new(),
+                extra: Vec::new(),
+            },
+            signatures: TxSignatures::V2 {
+                base: RctBase { rct_type: RctType::BulletproofPlus, fee: 1000, pseudo_outs: Vec::new(), encrypted_amounts: Vec::new(), commitments: Vec::new() },
+                prunable: vec![1],
+            },
+        };
+        let mut replaced = fixture.blocks[7].clone();
+        replaced.block.tx_hashes = vec![spending.hash().unwrap()];
+        replaced.transactions = vec![spending];
+        let miner_hash = replaced.block.miner_tx.hash().unwrap();
+        transport.0.lock().unwrap()[7] = replaced;
+        let mut reports: Vec<SyncProgress> = Vec::new();
+        wallet.sync(&node, None, |progress| reports.push(progress.clone())).unwrap();
+        assert_eq!(reports[0].reorg, Some(1007));
+        assert_eq!(reports[0].spent, vec![spent.output_key]);
+        let expected: Vec<_> = fixture.snapshot.outputs.iter().filter(|output| output.height < 1007 || output.tx_hash == miner_hash).cloned().collect();
+        assert_eq!(sorted(wallet.outputs()).len(), expected.len());
+        assert_eq!(wallet.balance(), expected.iter().map(|output| output.amount).sum::<u64>() - spent.amount);
+        assert_eq!(wallet.synced_height(), 1008);
+
+        // Failures keep what was scanned
+        let unreachable = DaemonNode::cake_wallet_default().with_transport(Arc::new(FlakyTransport { failures: u32::MAX.into() }));
+        assert!(wallet.sync(&unreachable, None, |_| {}).is_err());
+        assert_eq!(wallet.synced_height(), 1008);
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn address_watcher() {
+        use libmonero::blocks::{BlockCompleteEntry, BlocksBinResult, PoolTx};
+        use libmonero::keys::{derive_subaddress, PrivateKey, ViewPair};
+        use libmonero::utils::Network;
+        use libmonero::wallet::{AddressWatcher, FixtureBlock, FixtureConfig, FixtureWallet, WatchEvent};
+
+        let fixture = FixtureWallet::generate(&FixtureConfig { blocks: 8, incoming_transfers: 5, mined_blocks: 2, seed: 7, ..FixtureConfig::default() });
+        let view_pair = fixture.view_pair();
+        let donations = derive_subaddress(&view_pair, Network::Stagenet, 0, 1);
+        let other = PrivateKey::from_hex("6bdaf7a0a8f3f1ce4767d6d9c38b72b48ccc3ffa4f60be91389b1b96403ff20e").unwrap();
+        let other_subaddress = derive_subaddress(&ViewPair::new(other.public_key(), other.derive_view_key()), Network::Stagenet, 0, 1);
+        let mut watcher = AddressWatcher::new(Network::Stagenet, 1000)
+            .with_address(&fixture.primary_address, &view_pair.view)
+            .unwrap()
+            .with_address(&other_subaddress, &other.derive_view_key())
+            .unwrap()
+            .with_address(&donations, &view_pair.view)
+            .unwrap();
+        assert!(watcher.clone().with_address(&fixture.primary_address, &other).is_err());
+        assert!(watcher.clone().with_address(&derive_subaddress(&view_pair, Network::Mainnet, 0, 1), &view_pair.view).is_err());
+
+        let entry = |block: &FixtureBlock| BlockCompleteEntry {
+            block: block.block.to_blob(),
+            txs: block.transactions.iter().map(|tx| tx.to_blob()).collect(),
+            output_indices: Vec::new(),
+        };
+        let batch = |from: usize, blocks: Vec<BlockCompleteEntry>| BlocksBinResult { start_height: 1000 + from as u64, current_height: 1008, blocks, untrusted: false };
+
+        // Only outputs of the watched addresses are streamed, with their block height
+        let events = watcher.scan_blocks(&batch(0, fixture.blocks[..6].iter().map(entry).collect())).unwrap();
+        let mut expected: Vec<(u64, String, u64)> = fixture
+            .snapshot
+            .outputs
+            .iter()
+            .filter(|output| output.height < 1006 && (output.subaddress == (0, 0) || output.subaddress == (0, 1)))
+            .map(|output| (output.height, output.tx_hash.to_string(), output.amount))
+            .collect();
+        let found = |events: &[WatchEvent]| {
+            let mut found: Vec<(u64, String, u64)> = events
+                .iter()
+                .map(|event| match event {
+                    WatchEvent::Received(output) => (output.height.unwrap(), output.tx_hash.to_string(), output.amount),
+                    other => panic!("unexpected event {:?}", other),
+                })
+                .collect();
+            found.sort();
+            found
+        };
+        expected.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(found(&events), expected);
+        let WatchEvent::Received(first) = &events[0] else { panic!() };
+        assert!(first.address == fixture.primary_address || first.address == donations);
+        assert_eq!(watcher.height(), 1006);
+
+        // Batches overlapping the scanned blocks only stream the new ones
+        let events = watcher.scan_blocks(&batch(5, fixture.blocks[5..].iter().map(entry).collect())).unwrap();
+        assert_eq!(watcher.height(), 1008);
+        assert!(events.iter().all(|event| matches!(event, WatchEvent::Received(output) if output.height >= Some(1006))));
+
+        // A replaced block is reported before its outputs
+        let mut replaced = fixture.blocks[7].clone();
+        replaced.block.nonce += 1;
+        let events = watcher.scan_blocks(&batch(6, vec![entry(&fixture.blocks[6]), entry(&replaced)])).unwrap();
+        assert_eq!(events.first(), Some(&WatchEvent::Reorg { fork_height: 1007 }));
+
+        // Pool transactions are streamed once, without height
+        let paying = fixture.blocks.iter().flat_map(|block| block.transactions.iter()).find(|tx| !watcher.scan_tx(tx.hash().unwrap(), &tx.to_raw_tx(), None).is_empty()).unwrap();
+        let pool = vec![PoolTx {
+            id_hash: paying.hash().unwrap(),
+            tx: paying.to_raw_tx(),
+            blob_size: 0,
+            weight: 0,
+            fee: 0,
+            receive_time: 0,
+            last_relayed_time: 0,
+            relayed: true,
+            double_spend_seen: false,
+            kept_by_block: false,
+        }];
+        let events = watcher.scan_pool(&pool);
+        assert!(!events.is_empty() && events.iter().all(|event| matches!(event, WatchEvent::Received(output) if output.height.is_none())));
+        assert!(watcher.scan_pool(&pool).is_empty());
+    }
+
+    #[cfg(feature = "bench")]
+    #[test]
+    fn scan_benchmark_harness() {
+        use libmonero::keys::{bench_scanning, generate_synthetic_blocks, run_scan_benchmark, BenchConfig, PrivateKey, SubaddressTable, ViewPair};
+
+        let private_spend_key = PrivateKey::from_hex(CLSAG_TX_SPEND_KEY).unwrap();
+        let view_pair = ViewPair::new(private_spend_key.public_key(), private_spend_key.derive_view_key());
+        let config = BenchConfig { blocks: 6, txs_per_block: 4, outputs_per_tx: 3, owned_ratio: 0.25, ..BenchConfig::default() };
+        let blocks = generate_synthetic_blocks(&view_pair, &config);
+        assert_eq!(blocks.len(), 6);
+        assert!(blocks.iter().flatten().all(|tx| tx.vout.len() == 3 && tx.vout[0].target.tagged_key.view_tag.len() == 2));
+        // Generation is deterministic and every owned output is found whatever the thread count
+        let again = generate_synthetic_blocks(&view_pair, &config);
+        assert_eq!(blocks.iter().flatten().map(|tx| tx.extra.clone()).collect::<Vec<_>>(), again.iter().flatten().map(|tx| tx.extra.clone()).collect::<Vec<_>>());
+        let table = SubaddressTable::new(&view_pair, 1, 1);
+        let single = bench_scanning(&table, &blocks, 1, true);
+        assert_eq!(single.outputs_scanned, 72);
+        assert!(single.outputs_owned > 0);
+        assert_eq!(bench_scanning(&table, &blocks, 4, true).outputs_owned, single.outputs_owned);
+
+        // Without view tags the same outputs are found the slow way
+        let untagged = BenchConfig { view_tags: false, ..config.clone() };
+        let blocks = generate_synthetic_blocks(&view_pair, &untagged);
+        assert!(blocks.iter().flatten().all(|tx| tx.vout.iter().all(|vout| vout.target.tagged_key.view_tag.is_empty())));
+        assert_eq!(bench_scanning(&table, &blocks, 2, false).outputs_owned, single.outputs_owned);
+
+        let report = run_scan_benchmark(&BenchConfig { owned_ratio: 1.0, ..config });
+        assert_eq!((report.outputs_scanned, report.outputs_owned), (72, 72));
+        assert!(report.outputs_per_second() > 0.0);
+        assert!(report.to_string().contains("with view tags"));
+    }
+
+    struct TransactionsTransport {
+        blob: Vec<u8>,
+        pruned_len: usize,
+    }
+
+    impl libmonero::blocks::RpcTransport for TransactionsTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            assert_eq!(path, "/get_transactions");
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            // HASH_AA is served pruned, anything else in full
+            let tx = match request["txs_hashes"][0].as_str().unwrap() {
+                HASH_AA => serde_json::json!({
+                    "pruned_as_hex": hex::encode(&self.blob[..self.pruned_len]),
+                    "prunable_hash": hex::encode(<sha3::Keccak256 as sha3::Digest>::digest(&self.blob[self.pruned_len..])),
+                }),
+                _ => serde_json::json!({"as_hex": hex::encode(&self.blob)}),
+            };
+            Ok(serde_json::json!({"status": "OK", "txs": [tx]}).to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn transaction_hashes() {
+        use libmonero::blocks::{get_transaction, parse_transaction_blob, DaemonNode, TxHash};
+        use sha3::{Digest, Keccak256};
+        use std::sync::Arc;
+
+        // Version 2 transaction with one ring input, one tagged output and three bytes of prunable data
+        let mut blob = vec![2, 0, 1, 2, 0, 1, 5];
+        blob.extend_from_slice(&[0xaa; 32]);
+        blob.extend_from_slice(&[1, 0, 3]);
+        blob.extend_from_slice(&[0xbb; 32]);
+        blob.extend_from_slice(&[0x42, 0, 6]);
+        write_varint(30000000, &mut blob);
+        blob.extend_from_slice(&[0xcc; 8]);
+        blob.extend_from_slice(&[0xdd; 32]);
+        let (prefix_len, pruned_len) = (7 + 32 + 3 + 32 + 2, blob.len());
+        blob.extend_from_slice(&[1, 2, 3]);
+
+        let tx = parse_transaction_blob(&blob).unwrap();
+        let prefix_hash: [u8; 32] = Keccak256::digest(&blob[..prefix_len]).into();
+        assert_eq!(tx.prefix_hash(), prefix_hash);
+        let parts = [prefix_hash, Keccak256::digest(&blob[prefix_len..pruned_len]).into(), Keccak256::digest([1, 2, 3]).into()].concat();
+        let hash = TxHash::from_bytes(Keccak256::digest(&parts).into());
+        assert!(!tx.is_pruned());
+        assert_eq!(tx.hash().unwrap(), hash);
+
+        // Pruned transactions need the prunable hash
+        let pruned = parse_transaction_blob(&blob[..pruned_len]).unwrap();
+        assert!(pruned.is_pruned());
+        assert!(pruned.hash().is_err());
+        assert_eq!(pruned.prefix_hash(), prefix_hash);
+        assert_eq!(pruned.hash_with_prunable_hash(&Keccak256::digest([1, 2, 3]).into()), hash);
+
+        // Version 1 transactions hash their blob
+        let mut v1 = vec![1, 0, 1, 2, 0, 1, 5];
+        v1.extend_from_slice(&[0xaa; 32]);
+        v1.extend_from_slice(&[1, 0, 2]);
+        v1.extend_from_slice(&[0xbb; 32]);
+        v1.push(0);
+        let v1_pruned = parse_transaction_blob(&v1).unwrap();
+        assert!(v1_pruned.is_pruned() && v1_pruned.hash().is_err());
+        v1.extend_from_slice(&[0xee; 64]);
+        assert_eq!(parse_transaction_blob(&v1).unwrap().hash().unwrap(), TxHash::from_bytes(Keccak256::digest(&v1).into()));
+
+        // Daemons must return the requested transaction, pruned ones being checked with their prunable hash
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(TransactionsTransport { blob: blob.clone(), pruned_len }));
+        assert_eq!(get_transaction(hash, node.clone()).unwrap(), tx);
+        assert!(get_transaction(TxHash::from_hex(HASH_BB).unwrap(), node.clone()).unwrap_err().contains(&hash.to_hex()));
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(TransactionsTransport { blob: blob.clone(), pruned_len: pruned_len - 1 }));
+        assert!(get_transaction(TxHash::from_hex(HASH_AA).unwrap(), node).is_err());
+        let mut relabeled = blob.clone();
+        relabeled[7] = 0xab;
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(TransactionsTransport { blob: relabeled, pruned_len }));
+        assert!(get_transaction(hash, node).is_err());
+    }
+
+    #[test]
+    fn mnemonic_conversion() {
+        use libmonero::keys::{convert_mnemonic, derive_hex_seed, derive_priv_keys, generate_seed, validate_mnemonic, MnemonicError};
+
+        let languages = ["en", "eo", "fr", "it", "ja", "lj", "pt", "ru"];
+        for seed_type in ["original", "mymonero"] {
+            let seed = generate_seed("en", seed_type);
+            let keys = derive_priv_keys(derive_hex_seed(seed.to_vec()).to_string());
+            for language in languages {
+                let converted = convert_mnemonic(&seed, language).unwrap();
+                assert_eq!(converted.len(), seed.len(), "{}", language);
+                assert_eq!(validate_mnemonic(&converted), validate_mnemonic(&seed), "{}", language);
+                assert_eq!(derive_priv_keys(derive_hex_seed(converted.to_vec()).to_string()), keys, "{}", language);
+                assert_eq!(convert_mnemonic(&converted, "en").unwrap(), seed, "{}", language);
+            }
+        }
+
+        let mut seed = generate_seed("en", "original").to_vec();
+        assert_eq!(convert_mnemonic(&seed, "xx"), Err(MnemonicError::UnsupportedLanguage("xx".to_string())));
+        seed[24] = if seed[24] == seed[0] { seed[1].clone() } else { seed[0].clone() };
+        assert!(matches!(convert_mnemonic(&seed, "fr"), Err(MnemonicError::InvalidChecksum { .. })));
+    }
+
+    #[test]
+    fn wordset_loading() {
+        use libmonero::keys::{
+            convert_mnemonic, derive_hex_seed, generate_seed, load_wordset, load_wordset_from_bytes, load_wordset_from_file, unload_wordset,
+            validate_mnemonic, wordset_languages, MnemonicError, SeedType, WordlistError,
+        };
+        use libmonero::utils::Error;
+
+        // Monero wordlist file layout: one word per line, trailing newline
+        let words: Vec<String> = (0..1626).map(|i| format!("mot{:04}", i)).collect();
+        let text = words.join("\n") + "\n";
+        let path = std::env::temp_dir().join(format!("libmonero_wordset_{}.txt", std::process::id()));
+        std::fs::write(&path, &text).unwrap();
+        load_wordset_from_file("x-test-file", 7, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(wordset_languages().contains(&"x-test-file".to_string()));
+
+        for seed_type in ["original", "mymonero"] {
+            let seed = generate_seed("x-test-file", seed_type);
+            assert!(seed.iter().all(|word| words.contains(word)));
+            let english = convert_mnemonic(&seed, "en").unwrap();
+            assert_eq!(derive_hex_seed(english.to_vec()), derive_hex_seed(seed.to_vec()));
+            assert_eq!(convert_mnemonic(&english, "x-test-file").unwrap(), seed);
+        }
+        let seed = generate_seed("x-test-file", "original");
+        assert_eq!(validate_mnemonic(&seed), Ok(SeedType::Original));
+
+        // Blank lines and surrounding whitespace are ignored
+        let padded = format!("\n{}\r\n\n", words.iter().map(|word| format!("  {} ", word)).collect::<Vec<String>>().join("\r\n"));
+        load_wordset_from_bytes("x-test-bytes", 7, padded.as_bytes()).unwrap();
+        assert_eq!(convert_mnemonic(&seed, "x-test-bytes").unwrap(), seed);
+
+        assert!(unload_wordset("x-test-file"));
+        assert!(!unload_wordset("x-test-file"));
+        assert!(!wordset_languages().contains(&"x-test-file".to_string()));
+        assert_eq!(convert_mnemonic(&seed, "x-test-file"), Err(MnemonicError::UnsupportedLanguage("x-test-file".to_string())));
+        assert!(unload_wordset("x-test-bytes"));
+
+        let mut refs: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+        assert_eq!(load_wordset("", 7, &refs), Err(WordlistError::InvalidLanguage(String::new())));
+        assert_eq!(load_wordset("x-test", 7, &refs[..1625]), Err(WordlistError::InvalidWordCount(1625)));
+        refs[10] = "two words";
+        assert_eq!(load_wordset("x-test", 7, &refs), Err(WordlistError::InvalidWord { index: 10 }));
+        refs[10] = "mot0003";
+        assert_eq!(
+            load_wordset("x-test", 7, &refs),
+            Err(WordlistError::DuplicateWord {
+                index: 10,
+                word: "mot0003".to_string()
+            })
+        );
+        refs[10] = "mot0003x";
+        assert_eq!(
+            load_wordset("x-test", 7, &refs),
+            Err(WordlistError::DuplicatePrefix {
+                index: 10,
+                word: "mot0003x".to_string(),
+                other: "mot0003".to_string()
+            })
+        );
+        // Without checksum prefixes only whole words must be unique
+        load_wordset("x-test", 0, &refs).unwrap();
+        assert!(unload_wordset("x-test"));
+        assert!(matches!(load_wordset_from_bytes("x-test", 7, &[0xff, 0xfe]), Err(WordlistError::Read(_))));
+
+        let error: Error = load_wordset_from_file("x-test", 7, std::env::temp_dir().join("libmonero_missing_wordset.txt")).unwrap_err().into();
+        assert_eq!(error.code(), 1206);
+    }
+
+    // Publisher side of a ZMTP 3.0 connection: greeting, READY exchange, then the subscriptions, returned as topic names
+    #[cfg(feature = "zmq")]
+    fn zmq_publisher_handshake(stream: &mut std::net::TcpStream, topics: usize) -> Vec<String> {
+        use std::io::{Read, Write};
+
+        let mut greeting = [0u8; 64];
+        greeting[0] = 0xff;
+        greeting[9] = 0x7f;
+        greeting[10] = 3;
+        greeting[11] = 1;
+        greeting[12..16].copy_from_slice(b"NULL");
+        stream.write_all(&greeting).unwrap();
+        let mut peer_greeting = [0u8; 64];
+        stream.read_exact(&mut peer_greeting).unwrap();
+        assert_eq!(&peer_greeting[12..16], b"NULL");
+        let mut ready = vec![0x04, 25, 5];
+        ready.extend_from_slice(b"READY\x0bSocket-Type\x00\x00\x00\x03PUB");
+        stream.write_all(&ready).unwrap();
+        let mut read_frame = || {
+            let mut header = [0u8; 2];
+            stream.read_exact(&mut header).unwrap();
+            let mut body = vec![0u8; header[1] as usize];
+            stream.read_exact(&mut body).unwrap();
+            (header[0], body)
+        };
+        let (flags, command) = read_frame();
+        assert_eq!(flags, 0x04);
+        assert!(command.ends_with(b"Socket-Type\x00\x00\x00\x03SUB"));
+        (0..topics)
+            .map(|_| {
+                let (flags, subscription) = read_frame();
+                assert_eq!((flags, subscription[0]), (0, 1));
+                String::from_utf8(subscription[1..].to_vec()).unwrap()
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "zmq")]
+    fn zmq_frame(flags: u8, body: &[u8]) -> Vec<u8> {
+        let mut frame = match u8::try_from(body.len()) {
+            Ok(size) => vec![flags, size],
+            Err(_) => [vec![flags | 0x02], (body.len() as u64).to_be_bytes().to_vec()].concat(),
+        };
+        frame.extend_from_slice(body);
+        frame
+    }
+
+    #[cfg(feature = "zmq")]
+    #[test]
+    fn zmq_subscriber() {
+        use libmonero::blocks::{parse_zmq_message, BlockHash, EncryptedAmount, RctType, TxInput, TxOutTarget, TxSignatures, ZmqEvent, ZmqSubscriber, ZmqTopic};
+        use libmonero::keys::KeyImage;
+        use std::io::Write;
+        use std::net::TcpListener;
+        use std::time::Duration;
+
+        let chain_main = format!(r#"json-minimal-chain_main:{{"first_height":3000000,"first_prev_id":"{}","ids":["{}","{}"]}}"#, HASH_AA, HASH_BB, HASH_AA);
+        let txpool_add = format!(
+            r#"json-full-txpool_add:[{{"version":2,"unlock_time":0,"inputs":[{{"to_key":{{"amount":0,"key_offsets":[5,1,2],"key_image":"{k}"}}}}],"outputs":[{{"amount":0,"to_tagged_key":{{"key":"{k}","view_tag":"4a"}}}},{{"amount":0,"to_key":{{"key":"{k}"}}}}],"extra":"0102","signatures":[],"ringct":{{"type":6,"encrypted":[{{"mask":"{z}","amount":"0102030405060708{z8}"}},{{"mask":"{z}","amount":"{z}"}}],"commitments":["{k}","{k}"],"fee":30000000,"prunable":{{"nbp":1}}}}}}]"#,
+            k = "ab".repeat(32),
+            z = "00".repeat(32),
+            z8 = "00".repeat(24)
+        );
+
+        // Messages decode without a connection
+        let ZmqEvent::ChainMain { first_height, first_prev_id, ids } = parse_zmq_message(chain_main.as_bytes()).unwrap() else { panic!("expected a chain event") };
+        assert_eq!((first_height, first_prev_id), (3000000, BlockHash::from_hex(HASH_AA).unwrap()));
+        assert_eq!(ids, vec![BlockHash::from_hex(HASH_BB).unwrap(), BlockHash::from_hex(HASH_AA).unwrap()]);
+        let ZmqEvent::TxPoolAdd(txs) = parse_zmq_message(txpool_add.as_bytes()).unwrap() else { panic!("expected a pool event") };
+        assert_eq!(txs.len(), 1);
+        let tx = &txs[0];
+        assert!(tx.is_pruned());
+        assert_eq!(tx.fee(), 30000000);
+        assert_eq!(tx.prefix.extra, vec![1, 2]);
+        assert_eq!(tx.prefix.inputs[0], TxInput::ToKey { amount: 0, key_offsets: vec![5, 1, 2], key_image: KeyImage::from_bytes([0xab; 32]) });
+        assert!(matches!(tx.prefix.outputs[0].target, TxOutTarget::ToTaggedKey { view_tag: 0x4a, .. }));
+        assert!(matches!(tx.prefix.outputs[1].target, TxOutTarget::ToKey { .. }));
+        let TxSignatures::V2 { base, .. } = &tx.signatures else { panic!("expected RingCT signatures") };
+        assert_eq!(base.rct_type, RctType::BulletproofPlus);
+        assert_eq!(base.encrypted_amounts[0], EncryptedAmount::Compact { amount: [1, 2, 3, 4, 5, 6, 7, 8] });
+        assert_eq!(tx.to_raw_tx().vout.len(), 2);
+        assert!(parse_zmq_message(b"json-minimal-txpool_add:[]").is_err());
+        assert!(parse_zmq_message(b"json-minimal-chain_main:{}").is_err());
+        assert!(parse_zmq_message(b"no topic").is_err());
+
+        // Subscribing over TCP, messages being split across reads, sent in several frames or after a command
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+        let (chain, pool) = (chain_main.clone(), txpool_add.clone());
+        let publisher = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(zmq_publisher_handshake(&mut stream, 2), vec!["json-minimal-chain_main", "json-full-txpool_add"]);
+            let (head, tail) = chain.as_bytes().split_at(30);
+            let mut data = [zmq_frame(0x01, head), zmq_frame(0, tail)].concat();
+            data.extend(zmq_frame(0x04, b"\x04PING\x00\x00"));
+            data.extend(zmq_frame(0, pool.as_bytes()));
+            for chunk in data.chunks(7) {
+                stream.write_all(chunk).unwrap();
+                stream.flush().unwrap();
+            }
+            // Second connection for the watching subscriber, the first one being dropped after an invalid message
+            stream.write_all(&zmq_frame(0, b"json-full-txpool_add:{")).unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            zmq_publisher_handshake(&mut stream, 1);
+            stream.write_all(&zmq_frame(0, chain.as_bytes())).unwrap();
+            std::thread::sleep(Duration::from_millis(300));
+        });
+        let mut subscriber = ZmqSubscriber::connect(&endpoint, &[ZmqTopic::ChainMain, ZmqTopic::TxPoolAdd]).unwrap();
+        assert!(matches!(subscriber.recv().unwrap(), ZmqEvent::ChainMain { first_height: 3000000, .. }));
+        assert_eq!(subscriber.recv_timeout(Duration::from_secs(5)).unwrap(), Some(ZmqEvent::TxPoolAdd(txs)));
+        assert!(subscriber.recv().unwrap_err().contains("json-full-txpool_add"));
+        drop(subscriber);
+
+        let (events, handle) = ZmqSubscriber::connect(&endpoint, &[ZmqTopic::ChainMain]).unwrap().watch();
+        assert!(matches!(events.recv_timeout(Duration::from_secs(5)).unwrap(), Ok(ZmqEvent::ChainMain { .. })));
+        publisher.join().unwrap();
+        // The publisher went away, which is reported, reconnecting then fails
+        assert!(events.recv_timeout(Duration::from_secs(5)).unwrap().is_err());
+        handle.stop();
+        assert!(ZmqSubscriber::connect("tcp://127.0.0.1:1", &[ZmqTopic::ChainMain]).is_err());
+        assert!(ZmqSubscriber::connect(&endpoint, &[]).is_err());
+    }
+
+    #[test]
+    fn address_batch_validation() {
+        use libmonero::utils::{decode_address, make_integrated_address, validate_addresses, validate_addresses_parallel, AddressType, ValidationResult};
+
+        let standard = "41kztevQ9HVd2LMni56Ka13SBt6k9qFH6afYGWyXfWnJPdoEE86mHddRxZxPtAwdZb2e8wsZdiFyxPFMTtaWp14PCxPF3wT";
+        let subaddress = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H";
+        let integrated = make_integrated_address(standard, [7; 8]).unwrap();
+        let bad_checksum = &format!("{}U", &standard[..94]);
+        let batch = [standard, "", subaddress, integrated.as_str(), bad_checksum, "0OIl", &standard[..94], &standard[..90], subaddress, standard];
+
+        let results = validate_addresses(&batch);
+        assert_eq!(results.len(), batch.len());
+        // Results match the ones of decode_address one by one
+        for (address, result) in batch.iter().zip(results.iter()) {
+            match decode_address(address) {
+                Ok(info) => assert_eq!(result, &ValidationResult::Valid(info)),
+                Err(e) => assert_eq!(result.error(), Some(e.as_str())),
+            }
+        }
+        assert_eq!(results.iter().filter(|result| result.is_valid()).count(), 5);
+        assert_eq!(results[3].info().unwrap().payment_id, Some([7; 8]));
+        assert_eq!(results[8].info().unwrap().address_type, AddressType::Subaddress);
+        assert_eq!(results[4].error(), Some("Invalid address checksum"));
+        assert!(results[5].error().unwrap().starts_with("Invalid base58 encoding"));
+
+        let large: Vec<&str> = batch.iter().cycle().take(1000).copied().collect();
+        let sequential = validate_addresses(&large);
+        for threads in [0, 3, 2000] {
+            assert_eq!(validate_addresses_parallel(&large, threads), sequential);
+        }
+        assert!(validate_addresses_parallel(&[], 4).is_empty());
+    }
+
+    // Serves the height and headers of a chain whose block hashes can be replaced to simulate reorganizations
+    struct ChainTransport(std::sync::Arc<std::sync::Mutex<Vec<[u8; 32]>>>);
+
+    impl libmonero::blocks::RpcTransport for ChainTransport {
+        fn call(&self, _node: &libmonero::blocks::DaemonNode, path: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+            let chain = self.0.lock().unwrap();
+            if path == "/get_height" {
+                return Ok(serde_json::json!({"height": chain.len(), "hash": hex::encode(chain.last().unwrap()), "status": "OK"}).to_string().into_bytes());
+            }
+            let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(request["method"], "get_block_headers_range");
+            let start = request["params"]["start_height"].as_u64().unwrap() as usize;
+            let end = request["params"]["end_height"].as_u64().unwrap() as usize;
+            let headers: Vec<serde_json::Value> = (start..=end)
+                .map(|height| {
+                    let prev_hash = if height == 0 { [0; 32] } else { chain[height - 1] };
+                    serde_json::json!({"height": height, "hash": hex::encode(chain[height]), "prev_hash": hex::encode(prev_hash), "miner_tx_hash": HASH_CC})
+                })
+                .collect();
+            Ok(serde_json::json!({"jsonrpc": "2.0", "id": "0", "result": {"headers": headers, "status": "OK"}}).to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn chain_tip_watcher() {
+        use libmonero::blocks::{watch_height, BlockHash, ChainTip, DaemonNode, TipWatcher};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        // Hashes carry their height and the branch they belong to
+        let block = |height: usize, branch: u8| -> [u8; 32] {
+            let mut hash = [branch; 32];
+            hash[..8].copy_from_slice(&(height as u64).to_le_bytes());
+            hash
+        };
+        let new_block = |height: usize, branch: u8| ChainTip::NewBlock { height: height as u64, hash: BlockHash::from_bytes(block(height, branch)) };
+        let chain = Arc::new(Mutex::new((0..10).map(|height| block(height, 1)).collect::<Vec<_>>()));
+        let node = DaemonNode::cake_wallet_default().with_transport(Arc::new(ChainTransport(chain.clone())));
+        let replace_from = |height: usize, count: usize, branch: u8| {
+            let mut chain = chain.lock().unwrap();
+            chain.truncate(height);
+            chain.extend((height..height + count).map(|height| block(height, branch)));
+        };
+
+        let mut watcher = TipWatcher::new(5);
+        assert_eq!(watcher.poll(&node).unwrap(), vec![new_block(9, 1)]);
+        assert!(watcher.poll(&node).unwrap().is_empty());
+        replace_from(10, 2, 1);
+        assert_eq!(watcher.poll(&node).unwrap(), vec![new_block(10, 1), new_block(11, 1)]);
+        assert_eq!(watcher.tip(), Some((11, BlockHash::from_bytes(block(11, 1)))));
+
+        // Two blocks replaced by three
+        replace_from(10, 3, 2);
+        let orphaned = vec![BlockHash::from_bytes(block(10, 1)), BlockHash::from_bytes(block(11, 1))];
+        assert_eq!(watcher.poll(&node).unwrap(), vec![ChainTip::Reorg { fork_height: 10, orphaned }, new_block(10, 2), new_block(11, 2), new_block(12, 2)]);
+        // Shorter chain winning
+        replace_from(11, 1, 3);
+        let orphaned = vec![BlockHash::from_bytes(block(11, 2)), BlockHash::from_bytes(block(12, 2))];
+        assert_eq!(watcher.poll(&node).unwrap(), vec![ChainTip::Reorg { fork_height: 11, orphaned }, new_block(11, 3)]);
+        assert_eq!(watcher.hash_at(10), Some(BlockHash::from_bytes(block(10, 2))));
+        // Deeper than the tracked blocks, which start at the first top block, everything tracked is orphaned
+        replace_from(3, 10, 4);
+        let events = watcher.poll(&node).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], ChainTip::Reorg { fork_height: 9, orphaned } if orphaned.len() == 3));
+        assert_eq!(events[1], new_block(12, 4));
+        assert_eq!(watcher.hash_at(11), None);
+
+        // Blocks given to update must link to the tracked chain
+        let (tip_height, tip_hash) = watcher.tip().unwrap();
+        let unknown = BlockHash::from_bytes([9; 32]);
+        assert!(watcher.update(tip_height + 1, unknown, &[(unknown, unknown)]).is_err());
+        assert!(watcher.update(tip_height + 1, unknown, &[]).is_err());
+        assert!(watcher.update(tip_height + 2, unknown, &[(tip_hash, tip_hash), (unknown, unknown)]).is_err());
+        assert_eq!(watcher.update(tip_height + 1, unknown, &[(tip_hash, unknown)]).unwrap(), vec![ChainTip::NewBlock { height: tip_height + 1, hash: unknown }]);
+        // Only the last 5 blocks are tracked
+        let links: Vec<(BlockHash, BlockHash)> = (14..20).map(|height| (BlockHash::from_bytes(block(height - 1, 6)), BlockHash::from_bytes(block(height, 6)))).collect();
+        let mut tracked = TipWatcher::new(5);
+        tracked.update(13, BlockHash::from_bytes(block(13, 6)), &[]).unwrap();
+        assert_eq!(tracked.update(19, links[5].1, &links).unwrap().len(), 6);
+        assert_eq!((tracked.hash_at(14), tracked.hash_at(15)), (None, Some(links[1].1)));
+
+        let mut events = watch_height(node.clone(), Duration::from_millis(1));
+        assert_eq!(events.next(), Some(new_block(12, 4)));
+        replace_from(13, 1, 4);
+        assert_eq!(events.next(), Some(new_block(13, 4)));
+
+        #[cfg(feature = "async")]
+        {
+            let runtime = tokio::runtime::Builder::new_multi_thread().enable_time().build().unwrap();
+            let transport = Arc::new(AsyncMockTransport(ChainTransport(chain.clone()), std::sync::atomic::AtomicUsize::new(0)));
+            let mut tips = libmonero::blocks::DaemonClient::new(node).with_transport(transport).watch_height(Duration::from_millis(1));
+            assert_eq!(runtime.block_on(tips.next()), new_block(13, 4));
+            replace_from(13, 2, 5);
+            assert!(matches!(runtime.block_on(tips.next()), ChainTip::Reorg { fork_height: 13, .. }));
+            assert_eq!(runtime.block_on(tips.next()), new_block(14, 5));
+        }
+    }
+}